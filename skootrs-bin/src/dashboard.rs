@@ -0,0 +1,601 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! `skootrs dashboard` is a terminal dashboard for the projects known to the local Skootrs. It lists them with
+//! whether their facets drifted, and audits, updates and archives the selected project without a separate
+//! command for each one.
+
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    layout::{Constraint, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Row, Table, Wrap},
+    DefaultTerminal, Frame,
+};
+use skootrs_lib::service::project::ProjectService;
+use skootrs_model::skootrs::{
+    facet::{FacetAuditStatus, ProjectAudit},
+    Config, ProjectArchiveParams, ProjectGetParams, ProjectUpdateParams, ReadStrategy, SkootError,
+};
+
+use crate::{helpers::Project, prompt::Prompter};
+
+/// The keys the dashboard takes, shown at the bottom of it.
+const HELP: &str = "↑/↓ select  a audit  A audit all  u update  x archive  r reload  q quit";
+
+/// Something the dashboard does with its projects in response to a key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// Audit the selected project.
+    Audit,
+    /// Audit every project.
+    AuditAll,
+    /// Update the facets of the selected project.
+    Update,
+    /// Archive the selected project, once it's confirmed.
+    Archive,
+    /// Reload the projects from the local cache.
+    Reload,
+    /// Leave the dashboard.
+    Quit,
+}
+
+/// A project listed on the dashboard.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ProjectRow {
+    url: String,
+    /// The project's last audit, or `None` if it hasn't been audited since the dashboard was opened.
+    audit: Option<ProjectAudit>,
+    /// Why the last action on the project failed.
+    error: Option<String>,
+}
+
+/// The state of the dashboard.
+#[derive(Debug, Default)]
+pub struct Dashboard {
+    projects: Vec<ProjectRow>,
+    list_state: ListState,
+    /// Whether archiving the selected project is waiting to be confirmed.
+    confirming_archive: bool,
+    /// The outcome of the last action, shown until the next key is pressed.
+    message: Option<String>,
+}
+
+/// Returns the symbol and color a project is listed with: a check if every facet is compliant, an
+/// exclamation mark if any drifted or went missing, and a question mark if it hasn't been audited.
+#[must_use]
+pub fn drift_indicator(audit: Option<&ProjectAudit>) -> (&'static str, Color) {
+    match audit {
+        None => ("?", Color::DarkGray),
+        Some(audit) if audit.is_compliant() => ("✓", Color::Green),
+        Some(audit)
+            if audit.facets.iter().any(|f| {
+                matches!(
+                    f.status,
+                    FacetAuditStatus::Drifted | FacetAuditStatus::Missing
+                )
+            }) =>
+        {
+            ("!", Color::Red)
+        }
+        Some(_) => ("~", Color::Yellow),
+    }
+}
+
+fn status_color(status: &FacetAuditStatus) -> Color {
+    match status {
+        FacetAuditStatus::Compliant => Color::Green,
+        FacetAuditStatus::Drifted | FacetAuditStatus::Missing => Color::Red,
+        FacetAuditStatus::Unverified => Color::Yellow,
+    }
+}
+
+impl Dashboard {
+    /// Returns a dashboard listing the projects, sorted by URL.
+    #[must_use]
+    pub fn new(projects: impl IntoIterator<Item = String>) -> Self {
+        let mut dashboard = Self::default();
+        dashboard.set_projects(projects);
+        dashboard
+    }
+
+    /// Replaces the listed projects, keeping the audits of the ones still listed.
+    fn set_projects(&mut self, projects: impl IntoIterator<Item = String>) {
+        let mut urls = projects.into_iter().collect::<Vec<_>>();
+        urls.sort();
+        let previous = std::mem::take(&mut self.projects);
+        self.projects = urls
+            .into_iter()
+            .map(|url| {
+                previous
+                    .iter()
+                    .find(|row| row.url == url)
+                    .cloned()
+                    .unwrap_or(ProjectRow {
+                        url,
+                        audit: None,
+                        error: None,
+                    })
+            })
+            .collect();
+        self.clamp_selection();
+    }
+
+    fn clamp_selection(&mut self) {
+        let selected = match (self.list_state.selected(), self.projects.len()) {
+            (_, 0) => None,
+            (None, _) => Some(0),
+            (Some(i), len) => Some(i.min(len - 1)),
+        };
+        self.list_state.select(selected);
+    }
+
+    /// Returns the URL of the selected project, or `None` if there are no projects.
+    #[must_use]
+    pub fn selected(&self) -> Option<&str> {
+        self.list_state
+            .selected()
+            .and_then(|i| self.projects.get(i))
+            .map(|row| row.url.as_str())
+    }
+
+    /// Moves the selection, wrapping around at either end of the list.
+    fn select_next(&mut self, forward: bool) {
+        let len = self.projects.len();
+        if len == 0 {
+            return;
+        }
+        let selected = self.list_state.selected().unwrap_or(0);
+        self.list_state.select(Some(if forward {
+            (selected + 1) % len
+        } else {
+            (selected + len - 1) % len
+        }));
+    }
+
+    /// Handles a key, returning the action it asks for. Archiving asks for confirmation first, so it's only
+    /// returned once it's confirmed with `y`.
+    pub fn handle_key(&mut self, key: KeyEvent) -> Option<Action> {
+        self.message = None;
+        if std::mem::take(&mut self.confirming_archive) {
+            return (key.code == KeyCode::Char('y')).then_some(Action::Archive);
+        }
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                Some(Action::Quit)
+            }
+            KeyCode::Char('q') | KeyCode::Esc => Some(Action::Quit),
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.select_next(false);
+                None
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.select_next(true);
+                None
+            }
+            KeyCode::Char('r') => Some(Action::Reload),
+            KeyCode::Char('A') => Some(Action::AuditAll),
+            _ if self.selected().is_none() => None,
+            KeyCode::Char('a') => Some(Action::Audit),
+            KeyCode::Char('u') => Some(Action::Update),
+            KeyCode::Char('x') => {
+                self.confirming_archive = true;
+                None
+            }
+            _ => None,
+        }
+    }
+
+    fn row_mut(&mut self, url: &str) -> Option<&mut ProjectRow> {
+        self.projects.iter_mut().find(|row| row.url == url)
+    }
+
+    /// Records the outcome of auditing a project.
+    fn record_audit(&mut self, url: &str, audit: Result<ProjectAudit, SkootError>) {
+        let Some(row) = self.row_mut(url) else {
+            return;
+        };
+        match audit {
+            Ok(audit) => {
+                row.audit = Some(audit);
+                row.error = None;
+            }
+            Err(e) => row.error = Some(e.to_string()),
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, footer] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(3)]).areas(frame.area());
+        let [projects, facets] =
+            Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)])
+                .areas(main);
+
+        let items = self
+            .projects
+            .iter()
+            .map(|row| {
+                let (symbol, color) = drift_indicator(row.audit.as_ref());
+                ListItem::new(Line::from(vec![
+                    Span::styled(format!("{symbol} "), Style::default().fg(color)),
+                    Span::raw(row.url.clone()),
+                ]))
+            })
+            .collect::<Vec<_>>();
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(" Projects ({}) ", self.projects.len())),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, projects, &mut self.list_state);
+
+        let facets_block = Block::default().borders(Borders::ALL).title(" Facets ");
+        let selected = self
+            .list_state
+            .selected()
+            .and_then(|i| self.projects.get(i));
+        match selected {
+            Some(ProjectRow {
+                audit: Some(audit),
+                error: None,
+                ..
+            }) => {
+                let rows = audit.facets.iter().map(|facet| {
+                    Row::new(vec![
+                        Span::raw(facet.facet_type.to_string()),
+                        Span::styled(
+                            format!("{:?}", facet.status),
+                            Style::default().fg(status_color(&facet.status)),
+                        ),
+                        Span::raw(facet.files.join(", ")),
+                    ])
+                });
+                let table = Table::new(
+                    rows,
+                    [
+                        Constraint::Percentage(35),
+                        Constraint::Length(10),
+                        Constraint::Min(0),
+                    ],
+                )
+                .header(
+                    Row::new(vec!["Facet", "Status", "Files"])
+                        .style(Style::default().add_modifier(Modifier::BOLD)),
+                )
+                .block(facets_block);
+                frame.render_widget(table, facets);
+            }
+            Some(row) => {
+                let text = match &row.error {
+                    Some(error) => error.clone(),
+                    None => format!("Press a to audit {}", row.url),
+                };
+                frame.render_widget(
+                    Paragraph::new(text)
+                        .wrap(Wrap { trim: true })
+                        .block(facets_block),
+                    facets,
+                );
+            }
+            None => frame.render_widget(
+                Paragraph::new("No projects are known to the local Skootrs").block(facets_block),
+                facets,
+            ),
+        }
+
+        let footer_text = if self.confirming_archive {
+            format!(
+                "Archive {}? Press y to confirm or any other key to cancel",
+                self.selected().unwrap_or_default()
+            )
+        } else {
+            self.message.clone().unwrap_or_else(|| HELP.to_string())
+        };
+        frame.render_widget(
+            Paragraph::new(footer_text).block(Block::default().borders(Borders::ALL)),
+            footer,
+        );
+    }
+
+    /// Shows that an action is running, since the dashboard doesn't redraw until it's done.
+    fn show_progress(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        message: String,
+    ) -> Result<(), SkootError> {
+        self.message = Some(message);
+        terminal.draw(|frame| self.draw(frame))?;
+        Ok(())
+    }
+
+    async fn perform<T: ProjectService + ?Sized>(
+        &mut self,
+        action: Action,
+        terminal: &mut DefaultTerminal,
+        config: &Config,
+        prompter: &dyn Prompter,
+        project_service: &T,
+    ) -> Result<(), SkootError> {
+        let get_params = |url: &str| ProjectGetParams {
+            project_url: url.to_string(),
+            read_strategy: ReadStrategy::default(),
+        };
+        let selected = self.selected().map(ToString::to_string);
+        match (action, selected) {
+            (Action::Reload, _) => {
+                self.set_projects(Project::list(config).await?);
+                self.message = Some(format!("Reloaded {} projects", self.projects.len()));
+            }
+            (Action::AuditAll, _) => {
+                let urls = self
+                    .projects
+                    .iter()
+                    .map(|row| row.url.clone())
+                    .collect::<Vec<_>>();
+                for (i, url) in urls.iter().enumerate() {
+                    self.show_progress(
+                        terminal,
+                        format!("Auditing {url} ({}/{})", i + 1, urls.len()),
+                    )?;
+                    let audit =
+                        Project::audit(config, prompter, project_service, Some(get_params(url)))
+                            .await;
+                    self.record_audit(url, audit);
+                }
+                let drifted = self
+                    .projects
+                    .iter()
+                    .filter(|row| row.audit.as_ref().is_some_and(|a| !a.is_compliant()))
+                    .count();
+                self.message = Some(format!(
+                    "Audited {} projects, {drifted} aren't compliant",
+                    urls.len()
+                ));
+            }
+            (Action::Audit, Some(url)) => {
+                self.show_progress(terminal, format!("Auditing {url}"))?;
+                let audit =
+                    Project::audit(config, prompter, project_service, Some(get_params(&url))).await;
+                self.message = Some(match &audit {
+                    Ok(_) => format!("Audited {url}"),
+                    Err(e) => format!("Failed to audit {url}: {e}"),
+                });
+                self.record_audit(&url, audit);
+            }
+            (Action::Update, Some(url)) => {
+                self.show_progress(terminal, format!("Updating {url}"))?;
+                let updated = async {
+                    let initialized_project =
+                        Project::get(config, prompter, project_service, Some(get_params(&url)))
+                            .await?;
+                    Project::update(
+                        config,
+                        prompter,
+                        project_service,
+                        Some(ProjectUpdateParams {
+                            initialized_project,
+                            facet_types: None,
+                        }),
+                    )
+                    .await
+                }
+                .await;
+                self.message = Some(match updated {
+                    Ok(_) => {
+                        // The update changes what the facets are audited against.
+                        if let Some(row) = self.row_mut(&url) {
+                            row.audit = None;
+                        }
+                        format!("Updated {url}, press a to audit it again")
+                    }
+                    Err(e) => format!("Failed to update {url}: {e}"),
+                });
+            }
+            (Action::Archive, Some(url)) => {
+                self.show_progress(terminal, format!("Archiving {url}"))?;
+                let archived = async {
+                    let initialized_project =
+                        Project::get(config, prompter, project_service, Some(get_params(&url)))
+                            .await?;
+                    Project::archive(
+                        config,
+                        prompter,
+                        project_service,
+                        Some(ProjectArchiveParams {
+                            initialized_project,
+                            cleanup: false,
+                        }),
+                        false,
+                    )
+                    .await
+                }
+                .await;
+                self.message = Some(match archived {
+                    Ok(_) => {
+                        // Archived projects are removed from the cache, so they're no longer listed.
+                        self.projects.retain(|row| row.url != url);
+                        self.clamp_selection();
+                        format!("Archived {url}")
+                    }
+                    Err(e) => format!("Failed to archive {url}: {e}"),
+                });
+            }
+            (Action::Quit, _) | (_, None) => {}
+        }
+        Ok(())
+    }
+
+    async fn event_loop<T: ProjectService + ?Sized>(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        config: &Config,
+        prompter: &dyn Prompter,
+        project_service: &T,
+    ) -> Result<(), SkootError> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            // Reading the key on a blocking thread keeps the dashboard cancellable by `--timeout`.
+            let event = tokio::task::spawn_blocking(event::read)
+                .await
+                .map_err(SkootError::other)??;
+            let Event::Key(key) = event else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match self.handle_key(key) {
+                Some(Action::Quit) => return Ok(()),
+                Some(action) => {
+                    self.perform(action, terminal, config, prompter, project_service)
+                        .await?;
+                    // Logs written while the action ran would otherwise be left on the screen.
+                    terminal.clear()?;
+                }
+                None => {}
+            }
+        }
+    }
+}
+
+/// Restores the terminal when the dashboard is left, including when it's cancelled.
+struct RestoreTerminal;
+
+impl Drop for RestoreTerminal {
+    fn drop(&mut self) {
+        ratatui::restore();
+    }
+}
+
+/// Opens the dashboard on the projects in the local cache and runs it until it's quit.
+///
+/// # Errors
+///
+/// Returns an error if the cache can't be loaded or the terminal can't be drawn to. Failed actions are shown
+/// on the dashboard instead.
+pub async fn run<T: ProjectService + ?Sized>(
+    config: &Config,
+    prompter: &dyn Prompter,
+    project_service: &T,
+) -> Result<(), SkootError> {
+    let mut dashboard = Dashboard::new(Project::list(config).await?);
+    let mut terminal = ratatui::try_init()?;
+    let _restore = RestoreTerminal;
+    dashboard
+        .event_loop(&mut terminal, config, prompter, project_service)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use skootrs_model::skootrs::facet::{FacetAudit, SupportedFacetType};
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_handle_key() {
+        let mut dashboard = Dashboard::new(vec![
+            "https://github.com/testorg/b".to_string(),
+            "https://github.com/testorg/a".to_string(),
+        ]);
+        assert_eq!(dashboard.selected(), Some("https://github.com/testorg/a"));
+        assert_eq!(dashboard.handle_key(key(KeyCode::Down)), None);
+        assert_eq!(dashboard.selected(), Some("https://github.com/testorg/b"));
+        // The selection wraps around.
+        dashboard.handle_key(key(KeyCode::Char('j')));
+        assert_eq!(dashboard.selected(), Some("https://github.com/testorg/a"));
+        dashboard.handle_key(key(KeyCode::Up));
+        assert_eq!(dashboard.selected(), Some("https://github.com/testorg/b"));
+
+        assert_eq!(
+            dashboard.handle_key(key(KeyCode::Char('a'))),
+            Some(Action::Audit)
+        );
+        // Archiving waits for confirmation, and any other key cancels it.
+        assert_eq!(dashboard.handle_key(key(KeyCode::Char('x'))), None);
+        assert_eq!(dashboard.handle_key(key(KeyCode::Char('n'))), None);
+        assert_eq!(dashboard.handle_key(key(KeyCode::Char('y'))), None);
+        dashboard.handle_key(key(KeyCode::Char('x')));
+        assert_eq!(
+            dashboard.handle_key(key(KeyCode::Char('y'))),
+            Some(Action::Archive)
+        );
+        assert_eq!(
+            dashboard.handle_key(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)),
+            Some(Action::Quit)
+        );
+
+        // Actions on a project do nothing when there isn't one.
+        let mut empty = Dashboard::new(vec![]);
+        assert_eq!(empty.handle_key(key(KeyCode::Char('u'))), None);
+        assert_eq!(
+            empty.handle_key(key(KeyCode::Char('r'))),
+            Some(Action::Reload)
+        );
+    }
+
+    #[test]
+    fn test_drift_indicator() {
+        let audit = |status| ProjectAudit {
+            project_url: "https://github.com/testorg/a".to_string(),
+            facets: vec![FacetAudit {
+                facet_type: SupportedFacetType::SecurityPolicy,
+                status,
+                files: vec![],
+            }],
+            allowlist: vec![],
+            best_practices_badge: None,
+        };
+        assert_eq!(drift_indicator(None).0, "?");
+        assert_eq!(
+            drift_indicator(Some(&audit(FacetAuditStatus::Compliant))).0,
+            "✓"
+        );
+        assert_eq!(
+            drift_indicator(Some(&audit(FacetAuditStatus::Drifted))).0,
+            "!"
+        );
+        assert_eq!(
+            drift_indicator(Some(&audit(FacetAuditStatus::Unverified))).0,
+            "~"
+        );
+    }
+
+    #[test]
+    fn test_set_projects_keeps_audits() {
+        let mut dashboard = Dashboard::new(vec!["https://github.com/testorg/a".to_string()]);
+        dashboard.record_audit(
+            "https://github.com/testorg/a",
+            Ok(ProjectAudit {
+                project_url: "https://github.com/testorg/a".to_string(),
+                facets: vec![],
+                allowlist: vec![],
+                best_practices_badge: None,
+            }),
+        );
+        dashboard.set_projects(vec![
+            "https://github.com/testorg/a".to_string(),
+            "https://github.com/testorg/b".to_string(),
+        ]);
+        assert!(dashboard.projects[0].audit.is_some());
+        assert!(dashboard.projects[1].audit.is_none());
+    }
+}
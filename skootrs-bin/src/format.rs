@@ -0,0 +1,607 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The formats the CLI writes the results of commands in. JSON and YAML are for scripts, and tables are for
+//! people. Results have a generic table built from their serialized fields, and the ones people look at the
+//! most, like projects, facet lists and outputs, have tables of their own.
+
+use std::{collections::HashSet, fmt};
+
+use chrono::SecondsFormat;
+use clap::ValueEnum;
+use serde::Serialize;
+use serde_json::Value;
+use skootrs_lib::service::config::ConfigEntry;
+use skootrs_model::skootrs::{
+    attestation::AttestationVerification,
+    audit_log::AuditLogEntry,
+    facet::{FacetDiff, FacetHistoryEntry, InitializedFacet, ProjectAudit, SourceFileContent},
+    listing::ProjectSummary,
+    policy::PolicyEvaluation,
+    report::OrgReport,
+    rollout::Rollout,
+    scorecard::ScorecardReport,
+    status::ProjectStatus,
+    DownloadedOutput, InitializedProject, ProjectArchive, ProjectBatchCreate, ProjectInfo,
+    ProjectOutput, ProjectOutputReference, ProjectOutputType, ProjectRelocation, SignedProject,
+    SkootError, StateSignatureStatus,
+};
+use skootrs_statestore::ProjectRefresh;
+
+use crate::helpers::{FacetListing, SetupCheck};
+
+/// The format the result of a command is written in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Pretty-printed JSON.
+    #[default]
+    Json,
+    /// YAML.
+    Yaml,
+    /// A table with aligned columns.
+    Table,
+}
+
+impl OutputFormat {
+    /// Renders a result in the format.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the result can't be serialized.
+    pub fn render<T: Tabular>(self, result: &T) -> Result<String, SkootError> {
+        Ok(match self {
+            Self::Json => format!("{}\n", serde_json::to_string_pretty(result)?),
+            Self::Yaml => serde_yaml::to_string(result).map_err(SkootError::serialization)?,
+            Self::Table => result.table()?.to_string(),
+        })
+    }
+}
+
+/// A table of rows with a header for each column.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Table {
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    /// Returns a table with the headers and no rows.
+    #[must_use]
+    pub fn new(headers: &[&str]) -> Self {
+        Self {
+            headers: headers.iter().map(ToString::to_string).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Adds a row to the table.
+    pub fn row(&mut self, row: Vec<String>) {
+        self.rows.push(row);
+    }
+
+    /// Builds a table from a serialized result. A list of objects has a column for each of their fields, an
+    /// object has a row for each of its fields, and anything else is a single column. Nested values are
+    /// written as compact JSON.
+    #[must_use]
+    pub fn from_value(value: &Value) -> Self {
+        match value {
+            Value::Array(items) if items.iter().all(Value::is_object) && !items.is_empty() => {
+                let mut headers: Vec<&str> = Vec::new();
+                for field in items
+                    .iter()
+                    .filter_map(Value::as_object)
+                    .flat_map(|o| o.keys())
+                {
+                    if !headers.contains(&field.as_str()) {
+                        headers.push(field);
+                    }
+                }
+                let mut table = Self::new(&headers);
+                for item in items {
+                    table.row(headers.iter().map(|field| cell(&item[*field])).collect());
+                }
+                table
+            }
+            Value::Array(items) => {
+                let mut table = Self::new(&["value"]);
+                for item in items {
+                    table.row(vec![cell(item)]);
+                }
+                table
+            }
+            Value::Object(fields) => {
+                let mut table = Self::new(&["field", "value"]);
+                for (field, value) in fields {
+                    table.row(vec![field.clone(), cell(value)]);
+                }
+                table
+            }
+            value => {
+                let mut table = Self::new(&["value"]);
+                table.row(vec![cell(value)]);
+                table
+            }
+        }
+    }
+}
+
+impl fmt::Display for Table {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let headers: Vec<String> = self.headers.iter().map(|h| h.to_uppercase()).collect();
+        let widths: Vec<usize> = (0..headers.len())
+            .map(|column| {
+                self.rows
+                    .iter()
+                    .filter_map(|row| row.get(column))
+                    .chain(std::iter::once(&headers[column]))
+                    .map(|cell| cell.chars().count())
+                    .max()
+                    .unwrap_or_default()
+            })
+            .collect();
+        for row in std::iter::once(&headers).chain(&self.rows) {
+            let line = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{cell:width$}"))
+                .collect::<Vec<_>>()
+                .join("  ");
+            writeln!(f, "{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns how a serialized value is written in a table cell. Lists of strings are joined with commas.
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Array(items) if items.iter().all(Value::is_string) => items
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .join(", "),
+        value => value.to_string(),
+    }
+}
+
+/// A result that can be written as a table.
+pub trait Tabular: Serialize {
+    /// Returns the result as a table. By default the table is built from the result's serialized fields.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the result can't be serialized.
+    fn table(&self) -> Result<Table, SkootError> {
+        Ok(Table::from_value(&serde_json::to_value(self)?))
+    }
+}
+
+impl Tabular for InitializedProject {
+    fn table(&self) -> Result<Table, SkootError> {
+        let mut facets: Vec<String> = self.facets.keys().map(ToString::to_string).collect();
+        facets.sort();
+        let mut table = Table::new(&["field", "value"]);
+        table.row(vec!["name".to_string(), self.name.clone()]);
+        table.row(vec!["repo".to_string(), self.repo.full_url()]);
+        if let Some(mirror) = &self.mirror {
+            table.row(vec!["mirror".to_string(), mirror.full_url()]);
+        }
+        table.row(vec!["ecosystem".to_string(), self.ecosystem_names()]);
+        table.row(vec!["source".to_string(), self.source.path.clone()]);
+        table.row(vec!["facets".to_string(), facets.join(", ")]);
+        Ok(table)
+    }
+}
+
+impl Tabular for ProjectInfo {
+    fn table(&self) -> Result<Table, SkootError> {
+        let mut table = self.project.table()?;
+        if let Some(stats) = &self.stats {
+            let Table { rows, .. } = Table::from_value(&serde_json::to_value(stats)?);
+            table.rows.extend(rows);
+        }
+        Ok(table)
+    }
+}
+
+impl Tabular for SignedProject {
+    fn table(&self) -> Result<Table, SkootError> {
+        let mut table = self.project.table()?;
+        let state_signature = match &self.state_signature {
+            StateSignatureStatus::Unchecked => "unchecked".to_string(),
+            StateSignatureStatus::Verified => "verified".to_string(),
+            StateSignatureStatus::Missing => "missing".to_string(),
+            StateSignatureStatus::Invalid { reason } => format!("invalid: {reason}"),
+        };
+        table.row(vec!["state signature".to_string(), state_signature]);
+        Ok(table)
+    }
+}
+
+impl Tabular for ProjectBatchCreate {
+    fn table(&self) -> Result<Table, SkootError> {
+        let mut table = Table::new(&["project", "status", "detail"]);
+        for project in &self.created {
+            table.row(vec![
+                project.name.clone(),
+                "created".to_string(),
+                project.repo.full_url(),
+            ]);
+        }
+        for failure in &self.failed {
+            table.row(vec![
+                failure.name.clone(),
+                "failed".to_string(),
+                failure.error.clone(),
+            ]);
+        }
+        Ok(table)
+    }
+}
+
+impl Tabular for HashSet<String> {
+    fn table(&self) -> Result<Table, SkootError> {
+        let mut projects: Vec<&String> = self.iter().collect();
+        projects.sort();
+        let mut table = Table::new(&["project"]);
+        for project in projects {
+            table.row(vec![project.clone()]);
+        }
+        Ok(table)
+    }
+}
+
+impl Tabular for Vec<ProjectSummary> {
+    fn table(&self) -> Result<Table, SkootError> {
+        let mut table = Table::new(&["project", "name", "ecosystems", "facets"]);
+        for project in self {
+            table.row(vec![
+                project.url.clone(),
+                project.error.as_ref().map_or_else(
+                    || project.name.clone().unwrap_or_default(),
+                    |error| format!("unavailable: {error}"),
+                ),
+                project.ecosystems.join(", "),
+                project
+                    .facet_types
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ]);
+        }
+        Ok(table)
+    }
+}
+
+impl Tabular for ProjectRefresh {
+    fn table(&self) -> Result<Table, SkootError> {
+        let mut table = Table::new(&["project", "result"]);
+        for project in &self.refreshed {
+            table.row(vec![project.clone(), "refreshed".to_string()]);
+        }
+        for project in &self.evicted {
+            table.row(vec![
+                project.clone(),
+                "forgotten, the repo doesn't exist".to_string(),
+            ]);
+        }
+        for (project, error) in &self.failed {
+            table.row(vec![project.clone(), format!("failed: {error}")]);
+        }
+        for (project, new_url) in &self.renamed {
+            table.row(vec![
+                project.clone(),
+                format!("moved to {new_url}, run `skootrs project relocate` to record it"),
+            ]);
+        }
+        Ok(table)
+    }
+}
+
+impl Tabular for Vec<FacetListing> {
+    fn table(&self) -> Result<Table, SkootError> {
+        let mut table = Table::new(&["key", "type", "labels"]);
+        for facet in self {
+            table.row(vec![
+                facet.key.to_string(),
+                facet.facet_type.to_string(),
+                facet
+                    .labels
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ]);
+        }
+        Ok(table)
+    }
+}
+
+impl Tabular for InitializedFacet {
+    fn table(&self) -> Result<Table, SkootError> {
+        let mut table = Table::new(&["field", "value"]);
+        table.row(vec!["type".to_string(), self.facet_type().to_string()]);
+        table.row(vec![
+            "labels".to_string(),
+            self.labels()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", "),
+        ]);
+        if let Self::SourceBundle(facet) = self {
+            let mut files: Vec<String> = facet
+                .source_files
+                .iter()
+                .flatten()
+                .chain(
+                    facet
+                        .source_files_content
+                        .iter()
+                        .flat_map(|contents| contents.keys()),
+                )
+                .map(|file| format!("{}/{}", file.path.trim_end_matches('/'), file.name))
+                .collect();
+            files.sort();
+            table.row(vec!["files".to_string(), files.join(", ")]);
+        }
+        Ok(table)
+    }
+}
+
+impl Tabular for FacetDiff {
+    fn table(&self) -> Result<Table, SkootError> {
+        let mut table = Table::new(&["file", "status"]);
+        for file in &self.files {
+            table.row(vec![file.path.clone(), format!("{:?}", file.status)]);
+        }
+        Ok(table)
+    }
+}
+
+impl Tabular for ProjectAudit {
+    fn table(&self) -> Result<Table, SkootError> {
+        let mut table = Table::new(&["facet", "status", "files"]);
+        for facet in &self.facets {
+            table.row(vec![
+                facet.facet_type.to_string(),
+                format!("{:?}", facet.status),
+                facet.files.join(", "),
+            ]);
+        }
+        for finding in &self.allowlist {
+            table.row(vec![
+                finding.id.clone(),
+                format!("{:?}", finding.status),
+                String::new(),
+            ]);
+        }
+        if let Some(badge) = &self.best_practices_badge {
+            table.row(vec![
+                "OpenSSF Best Practices Badge".to_string(),
+                badge.badge_level.to_string(),
+                format!("{}% passing", badge.passing_percentage),
+            ]);
+        }
+        Ok(table)
+    }
+}
+
+impl Tabular for Vec<ProjectOutputReference> {
+    fn table(&self) -> Result<Table, SkootError> {
+        let mut table = Table::new(&["name", "type", "labels"]);
+        for output in self {
+            // Attestations are told apart by what they attest to.
+            let output_type = match &output.output_type {
+                ProjectOutputType::Attestation(predicate_type) => {
+                    format!("Attestation ({predicate_type})")
+                }
+                output_type => output_type.to_string(),
+            };
+            table.row(vec![
+                output.name.clone(),
+                output_type,
+                output
+                    .labels
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ]);
+        }
+        Ok(table)
+    }
+}
+
+impl Tabular for AttestationVerification {
+    fn table(&self) -> Result<Table, SkootError> {
+        let mut table = Table::new(&["predicate type", "subjects", "verified", "reason"]);
+        for envelope in &self.envelopes {
+            table.row(vec![
+                envelope.predicate_type.to_string(),
+                envelope.subjects.join(", "),
+                envelope.verified.to_string(),
+                envelope.reason.clone().unwrap_or_default(),
+            ]);
+        }
+        Ok(table)
+    }
+}
+
+impl Tabular for ScorecardReport {
+    fn table(&self) -> Result<Table, SkootError> {
+        let mut table = Table::new(&["check", "score", "status", "missing facets"]);
+        for check in &self.checks {
+            table.row(vec![
+                check.check.clone(),
+                check.score.to_string(),
+                check.status.to_string(),
+                check
+                    .missing_facets
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ]);
+        }
+        Ok(table)
+    }
+}
+
+impl Tabular for PolicyEvaluation {
+    fn table(&self) -> Result<Table, SkootError> {
+        let mut table = Table::new(&["policy", "result", "violation"]);
+        if self.passed() {
+            table.row(vec![self.policy.clone(), "pass".to_string(), String::new()]);
+        }
+        for violation in &self.violations {
+            table.row(vec![
+                self.policy.clone(),
+                "fail".to_string(),
+                violation.clone(),
+            ]);
+        }
+        Ok(table)
+    }
+}
+
+impl Tabular for ProjectStatus {
+    fn table(&self) -> Result<Table, SkootError> {
+        let yes_no = |attached: bool| if attached { "yes" } else { "no" }.to_string();
+        let mut table = Table::new(&["field", "value"]);
+        table.row(vec!["repo".to_string(), self.project_url.clone()]);
+        table.row(vec![
+            "source bundle facets".to_string(),
+            self.source_bundle_facets.to_string(),
+        ]);
+        table.row(vec![
+            "api bundle facets".to_string(),
+            self.api_bundle_facets.to_string(),
+        ]);
+        table.row(vec![
+            "branch protection".to_string(),
+            self.branch_protection
+                .as_ref()
+                .map_or_else(|| "not managed".to_string(), |status| format!("{status:?}")),
+        ]);
+        match &self.latest_release {
+            Some(release) => {
+                table.row(vec![
+                    "latest release outputs".to_string(),
+                    release.outputs.to_string(),
+                ]);
+                table.row(vec!["sbom attached".to_string(), yes_no(release.sbom)]);
+                table.row(vec![
+                    "provenance attached".to_string(),
+                    yes_no(release.provenance),
+                ]);
+            }
+            None => table.row(vec!["latest release".to_string(), "none".to_string()]),
+        }
+        for drift in &self.drift {
+            table.row(vec!["drift".to_string(), drift.clone()]);
+        }
+        Ok(table)
+    }
+}
+
+impl Tabular for Vec<SourceFileContent> {
+    fn table(&self) -> Result<Table, SkootError> {
+        let mut table = Table::new(&["file", "lines"]);
+        for file in self {
+            table.row(vec![
+                format!("{}/{}", file.path.trim_end_matches('/'), file.name),
+                file.content.lines().count().to_string(),
+            ]);
+        }
+        Ok(table)
+    }
+}
+
+impl Tabular for ProjectOutput {}
+impl Tabular for Vec<DownloadedOutput> {}
+impl Tabular for Vec<AuditLogEntry> {
+    fn table(&self) -> Result<Table, SkootError> {
+        let mut table = Table::new(&["#", "time", "actor", "operation", "project", "outcome"]);
+        for entry in self {
+            table.row(vec![
+                entry.sequence.to_string(),
+                entry.timestamp.to_rfc3339_opts(SecondsFormat::Secs, true),
+                entry.actor.clone(),
+                entry.operation.to_string(),
+                entry.project_url.clone(),
+                entry.outcome.to_string(),
+            ]);
+        }
+        Ok(table)
+    }
+}
+
+impl Tabular for ProjectRelocation {
+    fn table(&self) -> Result<Table, SkootError> {
+        let mut table = Table::new(&["old url", "new url", "pull request"]);
+        table.row(vec![
+            self.old_url.clone(),
+            self.new_url.clone(),
+            self.pull_request.clone().unwrap_or_default(),
+        ]);
+        Ok(table)
+    }
+}
+
+impl Tabular for ProjectArchive {}
+impl Tabular for OrgReport {}
+impl Tabular for FacetHistoryEntry {}
+impl Tabular for Rollout {}
+impl Tabular for Vec<SetupCheck> {}
+impl Tabular for Vec<ConfigEntry> {}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_table_from_list() {
+        let table = Table::from_value(&json!([
+            { "labels": ["SBOM", "SPDX"], "name": "sbom.spdx.json" },
+            { "name": "checksums.txt", "size": 120 },
+        ]));
+        assert_eq!(
+            table.to_string(),
+            [
+                "LABELS      NAME            SIZE",
+                "SBOM, SPDX  sbom.spdx.json",
+                "            checksums.txt   120",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_table_from_object() {
+        let table =
+            Table::from_value(&json!({ "project_url": "https://github.com/kusaridev/skootrs" }));
+        assert_eq!(
+            table.to_string(),
+            "FIELD        VALUE\nproject_url  https://github.com/kusaridev/skootrs\n"
+        );
+    }
+}
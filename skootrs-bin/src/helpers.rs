@@ -1,48 +1,91 @@
-use inquire::Text;
-use octocrab::Page;
+use futures::stream::{self, StreamExt};
+use schemars::schema::{InstanceType, Schema, SchemaObject, SingleOrVec};
 use serde::Serialize;
-use skootrs_lib::service::{project::ProjectService, source::LocalSourceService};
+use serde_json::Value;
+use skootrs_lib::service::{
+    audit_log,
+    config::{check_values, ConfigEntry, ConfigFile, ConfigLayer, ConfigResolver},
+    github_auth::GithubAuth,
+    insights::InsightsService,
+    pagination,
+    policy::PolicyService,
+    project::ProjectService,
+    repo::LocalRepoService,
+    scorecard::ScorecardService,
+    source::{self, ConfiguredSourceService},
+};
 use skootrs_model::skootrs::{
-    facet::InitializedFacet, Config, EcosystemInitializeParams, FacetGetParams, FacetMapKey,
-    GithubRepoParams, GithubUser, GoParams, InitializedProject, ProjectArchiveParams,
-    ProjectCreateParams, ProjectGetParams, ProjectOutput, ProjectOutputGetParams,
-    ProjectOutputReference, ProjectOutputType, ProjectOutputsListParams, ProjectReleaseParam,
-    ProjectUpdateParams, RepoCreateParams, SkootError, SourceInitializeParams, SupportedEcosystems,
+    attestation::{AttestationVerification, AttestationVerifyParams},
+    audit_log::{AuditLogEntry, AuditLogFilter},
+    compliance::ComplianceReport,
+    facet::{
+        FacetAddParams, FacetDeleteParams, FacetDiff, FacetDiffParams, FacetHistoryEntry,
+        FacetHistoryGetParams, FacetPreviewParams, InitializedFacet, ProjectAudit,
+        SourceFileContent, SupportedFacetType,
+    },
+    label::{Label, Labeled},
+    listing::ProjectSummary,
+    plan::ProjectPlan,
+    policy::{PolicyEvaluation, PolicyInput},
+    registry::FacetRegistry,
+    report::OrgReport,
+    rollout::{
+        Rollout, RolloutChange, RolloutParams, RolloutProject, RolloutProjectStatus, RolloutStatus,
+    },
+    scorecard::ScorecardReport,
+    status::ProjectStatus,
+    validation::sanitize_project_name,
+    Config, DownloadedOutput, EcosystemInitializeParams, FacetGetParams, FacetListParams,
+    FacetMapKey, GithubRepoParams, GithubUser, GoParams, InitializedProject, InitializedRepo,
+    InitializedSource, ProjectArchive, ProjectArchiveParams, ProjectBatchCreate,
+    ProjectBatchCreateFailure, ProjectBatchCreateParams, ProjectCreateParams, ProjectDefaults,
+    ProjectDeleteParams, ProjectGetParams, ProjectInfo, ProjectMirrorParams, ProjectOutput,
+    ProjectOutputGetParams, ProjectOutputReference, ProjectOutputType,
+    ProjectOutputsDownloadParams, ProjectOutputsListParams, ProjectReleaseParam,
+    ProjectRelocateParams, ProjectRelocation, ProjectUpdateParams, PythonPackageManager,
+    PythonParams, ReadStrategy, RepoCreateParams, RustParams, SignedProject, SkootError,
+    SourceInitializeParams, SupportedEcosystems,
 };
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     io::Write,
+    path::Path,
     str::FromStr,
 };
 use strum::VariantNames;
-use tracing::debug;
+use tracing::{debug, error, info};
+
+use crate::{
+    format::{OutputFormat, Tabular},
+    prompt::{choose, Prompter, Question},
+};
 
 use skootrs_statestore::{
-    GitProjectStateStore, InMemoryProjectReferenceCache, ProjectReferenceCache, ProjectStateStore,
+    ConfiguredProjectReferenceCache, GitProjectStateStore, ProjectReferenceCache, ProjectRefresh,
+    ProjectStateStore,
 };
 
 /// Helper trait that lets me inline writing the result of a Skootrs function to a writer.
 pub trait HandleResponseOutput<T> {
     #[must_use]
-    fn handle_response_output<W: Write>(self, output_handler: W) -> Self;
+    fn handle_response_output<W: Write>(self, output_handler: W, format: OutputFormat) -> Self;
 }
 
 impl<T> HandleResponseOutput<T> for Result<T, SkootError>
 where
-    T: Serialize,
+    T: Tabular,
 {
-    /// Handles a response that implements `Serialize`.
+    /// Handles a response that implements `Serialize`, writing it in the given format.
     /// This is useful for functions that return a response that needs to be printed out, logged, etc. to the user.
     ///
     /// # Errors
     ///
-    /// Returns an error if the response can't be serialized to JSON or if the output can't be written to the output handler.
+    /// Returns an error if the response can't be serialized or if the output can't be written to the output handler.
     /// Also returns an error if the function that returns the response returns an error.
-    fn handle_response_output<W: Write>(self, mut output_handler: W) -> Self {
+    fn handle_response_output<W: Write>(self, mut output_handler: W, format: OutputFormat) -> Self {
         match self {
             Ok(result) => {
-                let serialized_result = serde_json::to_string_pretty(&result)?;
-                writeln!(output_handler, "{serialized_result}")?;
+                write!(output_handler, "{}", format.render(&result)?)?;
                 Ok(result)
             }
             Err(error) => Err(error),
@@ -50,6 +93,17 @@ where
     }
 }
 
+/// A facet of a project as it's listed by `skootrs facet list`.
+#[derive(Serialize, Debug)]
+pub struct FacetListing {
+    /// The key the facet is stored under in the project.
+    pub key: FacetMapKey,
+    /// The type of the facet.
+    pub facet_type: SupportedFacetType,
+    /// The labels of the facet.
+    pub labels: Vec<Label>,
+}
+
 pub struct Project;
 
 impl Project {
@@ -59,64 +113,179 @@ impl Project {
     /// The project can be created for either Go or Maven ecosystems right now.
     /// The project is created in Github, cloned down, and then initialized along with any other security supporting
     /// tasks. If the `project_params` is not provided, the user will be prompted for the project details.
+    /// Setting `rollback_on_failure` deletes the repo and local clone again if the creation fails partway.
     ///
     /// # Errors
     ///
     /// Returns an error if the user is not authenticated with Github, or if the project can't be created
     /// for any other reason.
-    pub async fn create<'a, T: ProjectService + ?Sized>(
+    pub async fn create<T: ProjectService + ?Sized>(
         config: &Config,
-        project_service: &'a T,
+        prompter: &dyn Prompter,
+        project_service: &T,
         project_params: Option<ProjectCreateParams>,
+        rollback_on_failure: bool,
     ) -> Result<InitializedProject, SkootError> {
-        let project_params = match project_params {
+        let mut project_params = match project_params {
             Some(p) => p,
-            None => Project::prompt_create(config).await?,
+            None => Project::prompt_create(config, prompter).await?,
         };
+        project_params.rollback_on_failure |= rollback_on_failure;
 
         let project = project_service.initialize(project_params).await?;
         let git_state_store = GitProjectStateStore {
             source: project.source.clone(),
-            source_service: LocalSourceService {},
+            source_service: ConfiguredSourceService::from_config(config),
+            signing: config.state_signing.clone(),
         };
 
-        let mut local_cache = InMemoryProjectReferenceCache::load_or_create("./skootcache")?;
+        let mut local_cache = ConfiguredProjectReferenceCache::open(config).await?;
         git_state_store.create(project.clone()).await?;
-        local_cache.set(project.repo.full_url()).await?;
+        local_cache.track(&project).await?;
         Ok(project)
     }
 
-    async fn prompt_create(config: &Config) -> Result<ProjectCreateParams, SkootError> {
-        let name = Text::new("The name of the repository").prompt()?;
-        let description = Text::new("The description of the repository").prompt()?;
-        let user = octocrab::instance().current().user().await?.login;
-        let Page { items, .. } = octocrab::instance()
-            .current()
-            .list_org_memberships_for_authenticated_user()
-            .send()
-            .await?;
-        let organization = inquire::Select::new(
-            "Select an organization",
-            items
+    /// Creates every project in a manifest, running up to `parallelism` creations at once. A project that
+    /// fails to be created is recorded in the result instead of stopping the others. The created projects are
+    /// written to their state stores as they're created, and tracked in the local cache once they're all done
+    /// so concurrent creations don't overwrite each other's cache entries.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `parallelism` is 0, or if the local cache can't be opened or written to.
+    pub async fn create_batch<T: ProjectService + ?Sized>(
+        config: &Config,
+        project_service: &T,
+        params: ProjectBatchCreateParams,
+        parallelism: usize,
+        rollback_on_failure: bool,
+    ) -> Result<ProjectBatchCreate, SkootError> {
+        if parallelism == 0 {
+            return Err("The parallelism has to be at least 1".into());
+        }
+
+        let results: Vec<(String, Result<InitializedProject, SkootError>)> =
+            stream::iter(params.projects)
+                .map(|mut project_params| async move {
+                    let name = project_params.name.clone();
+                    project_params.rollback_on_failure |= rollback_on_failure;
+                    let result = async {
+                        let project = project_service.initialize(project_params).await?;
+                        let git_state_store = GitProjectStateStore {
+                            source: project.source.clone(),
+                            source_service: ConfiguredSourceService::from_config(config),
+                            signing: config.state_signing.clone(),
+                        };
+                        git_state_store.create(project.clone()).await?;
+                        Ok::<_, SkootError>(project)
+                    }
+                    .await;
+                    (name, result)
+                })
+                .buffer_unordered(parallelism)
+                .collect()
+                .await;
+
+        let mut local_cache = ConfiguredProjectReferenceCache::open(config).await?;
+        let mut batch = ProjectBatchCreate::default();
+        for (name, result) in results {
+            match result {
+                Ok(project) => {
+                    local_cache.track(&project).await?;
+                    info!("Created project {name}");
+                    batch.created.push(project);
+                }
+                Err(error) => {
+                    error!(error = error.as_ref(), "Failed to create project {name}");
+                    batch.failed.push(ProjectBatchCreateFailure {
+                        name,
+                        error: error.to_string(),
+                    });
+                }
+            }
+        }
+        Ok(batch)
+    }
+
+    /// Plans the creation of a new project without creating it. The plan lists every API call, command, file
+    /// and push creating the project would make. If the `project_params` is not provided, the user will be
+    /// prompted for the project details.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the project details are invalid or the project's files can't be rendered.
+    pub async fn plan<T: ProjectService + ?Sized>(
+        config: &Config,
+        prompter: &dyn Prompter,
+        project_service: &T,
+        project_params: Option<ProjectCreateParams>,
+    ) -> Result<ProjectPlan, SkootError> {
+        let project_params = match project_params {
+            Some(p) => p,
+            None => Project::prompt_create(config, prompter).await?,
+        };
+
+        project_service.plan(project_params).await
+    }
+
+    async fn prompt_create(
+        config: &Config,
+        prompter: &dyn Prompter,
+    ) -> Result<ProjectCreateParams, SkootError> {
+        prompter.require(&["name", "repo_params", "ecosystem_params", "source_params"])?;
+        let entered = prompter.text(Question::new("name", "The name of the repository"), None)?;
+        let name = sanitize_project_name(&entered);
+        if name != entered {
+            info!("Using {name:?} as the name of the repository instead of {entered:?}");
+        }
+        let description = prompter
+            .text_skippable(
+                Question::new("description", "The description of the repository"),
+                None,
+            )?
+            .unwrap_or_default();
+        let (user, organizations) = github_owners(config).await?;
+        let owners: Vec<String> = organizations
+            .into_iter()
+            .chain(vec![user.clone()])
+            .collect();
+        let default_organization = position(&owners, config.defaults.organization.as_deref());
+        let organization = choose(
+            prompter,
+            Question::new("organization", "Select an organization"),
+            owners,
+            default_organization,
+        )?;
+        let default_ecosystem = config.defaults.ecosystem.as_ref().map(ToString::to_string);
+        let language = choose(
+            prompter,
+            Question::new("ecosystem", "Select a language"),
+            SupportedEcosystems::VARIANTS.to_vec(),
+            SupportedEcosystems::VARIANTS
                 .iter()
-                .map(|i| i.organization.login.as_str())
-                .chain(vec![user.as_str()])
-                .collect(),
-        )
-        .prompt()?;
-        let language =
-            inquire::Select::new("Select a language", SupportedEcosystems::VARIANTS.to_vec());
+                .position(|ecosystem| Some(*ecosystem) == default_ecosystem.as_deref()),
+        )?;
 
-        let gh_org = match organization {
-            x if x == user => GithubUser::User(x.to_string()),
-            x => GithubUser::Organization(x.to_string()),
+        let gh_org = match organization.clone() {
+            x if x == user => GithubUser::User(x),
+            x => GithubUser::Organization(x),
         };
 
-        let language_prompt = language.prompt()?;
-        let ecosystem_params = match SupportedEcosystems::from_str(language_prompt)? {
+        let ecosystem = SupportedEcosystems::from_str(language).map_err(SkootError::other)?;
+        let ecosystem_params = match ecosystem {
             SupportedEcosystems::Go => EcosystemInitializeParams::Go(GoParams {
                 name: name.clone(),
                 host: format!("github.com/{organization}"),
+                go_version: prompter
+                    .text_skippable(
+                        Question::new(
+                            "go_version",
+                            "The Go release to build the module with, e.g. 1.22.3",
+                        )
+                        .with_help("Leave empty to use the installed Go"),
+                        None,
+                    )?
+                    .filter(|go_version| !go_version.trim().is_empty()),
             }),
             // TODO: Re-add Maven support.
             // TODO: Unclear if this is the right way to handle Maven group and artifact.
@@ -124,21 +293,51 @@ impl Project {
                 group_id: format!("com.{organization}.{name}"),
                 artifact_id: name.clone(),
             }),*/
+            SupportedEcosystems::Python => {
+                let package_manager = choose(
+                    prompter,
+                    Question::new("package_manager", "Select a package manager"),
+                    PythonPackageManager::VARIANTS.to_vec(),
+                    None,
+                )?;
+                EcosystemInitializeParams::Python(PythonParams {
+                    name: name.clone(),
+                    package_manager: PythonPackageManager::from_str(package_manager)
+                        .map_err(SkootError::other)?,
+                })
+            }
+            SupportedEcosystems::Rust => {
+                let crates = prompter.text(
+                    Question::new("crates", "The crates in the workspace, comma separated"),
+                    Some(&name),
+                )?;
+                EcosystemInitializeParams::Rust(RustParams {
+                    name: name.clone(),
+                    crates: crates
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|c| !c.is_empty())
+                        .map(str::to_string)
+                        .collect(),
+                })
+            }
         };
 
         let repo_params = RepoCreateParams::Github(GithubRepoParams {
             name: name.clone(),
             description,
             organization: gh_org,
+            license: Some(config.license.spdx_id),
         });
 
         Ok(ProjectCreateParams {
             name: name.clone(),
             repo_params,
-            ecosystem_params,
+            ecosystem_params: vec![ecosystem_params.into()],
             source_params: SourceInitializeParams {
                 parent_path: config.local_project_path.clone(),
             },
+            rollback_on_failure: false,
         })
     }
 
@@ -147,26 +346,252 @@ impl Project {
     /// # Errors
     ///
     /// Returns an error if the project can't be fetched for some reason.
-    pub async fn get<'a, T: ProjectService + ?Sized>(
+    pub async fn get<T: ProjectService + ?Sized>(
         config: &Config,
-        _project_service: &'a T,
+        prompter: &dyn Prompter,
+        _project_service: &T,
         project_get_params: Option<ProjectGetParams>,
     ) -> Result<InitializedProject, SkootError> {
-        let mut cache = InMemoryProjectReferenceCache::load_or_create("./skootcache")?;
         let project_get_params = match project_get_params {
             Some(p) => p,
-            None => Project::prompt_get(config).await?,
+            None => Project::prompt_get(config, prompter).await?,
         };
+        let mut cache = ConfiguredProjectReferenceCache::open(config).await?;
         let project = cache.get(project_get_params.project_url.clone()).await?;
         Ok(project)
     }
 
-    async fn prompt_get(config: &Config) -> Result<ProjectGetParams, SkootError> {
-        let projects = Project::list(config).await?;
-        let selected_project =
-            inquire::Select::new("Select a project", projects.iter().collect()).prompt()?;
+    /// Fetches the contents of an `InitializedProject` along with whether its state was signed the way the
+    /// `state_signing` config says it should be. The state is read from the project's local clone if
+    /// `read_strategy`, or the one in `project_get_params` if it isn't given, says so.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the project can't be fetched or its signature can't be checked.
+    pub async fn get_signed(
+        config: &Config,
+        prompter: &dyn Prompter,
+        project_get_params: Option<ProjectGetParams>,
+        read_strategy: Option<ReadStrategy>,
+    ) -> Result<SignedProject, SkootError> {
+        let mut project_get_params = match project_get_params {
+            Some(p) => p,
+            None => Project::prompt_get(config, prompter).await?,
+        };
+        if let Some(read_strategy) = read_strategy {
+            project_get_params.read_strategy = read_strategy;
+        }
+        let local_source = source::local_source(
+            &ConfiguredSourceService::from_config(config),
+            Some(&config.local_project_path),
+            &InitializedRepo::try_from(project_get_params.project_url.clone())?,
+            project_get_params.read_strategy,
+        )?;
+        let cache = ConfiguredProjectReferenceCache::open(config).await?;
+        cache
+            .get_signed(
+                project_get_params.project_url,
+                &config.state_signing,
+                local_source.as_ref(),
+            )
+            .await
+    }
+
+    /// Fetches the contents of an `InitializedProject` along with live statistics about the project's repo
+    /// if `stats` is set. This is useful for checking whether the controls Skootrs manages are actually being
+    /// exercised.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the project or its statistics can't be fetched for some reason.
+    pub async fn info<T: ProjectService + ?Sized, I: InsightsService + ?Sized>(
+        config: &Config,
+        prompter: &dyn Prompter,
+        project_service: &T,
+        insights_service: &I,
+        project_get_params: Option<ProjectGetParams>,
+        stats: bool,
+    ) -> Result<ProjectInfo, SkootError> {
+        let project = Project::get(config, prompter, project_service, project_get_params).await?;
+        let stats = if stats {
+            Some(insights_service.stats(&project.repo).await?)
+        } else {
+            None
+        };
+        Ok(ProjectInfo { project, stats })
+    }
+
+    /// Audits every facet of a project against what's live in the project's repo. If the
+    /// `project_get_params` is not provided, the user will be prompted for the project.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the project can't be fetched for some reason.
+    pub async fn audit<T: ProjectService + ?Sized>(
+        config: &Config,
+        prompter: &dyn Prompter,
+        project_service: &T,
+        project_get_params: Option<ProjectGetParams>,
+    ) -> Result<ProjectAudit, SkootError> {
+        let project_get_params = match project_get_params {
+            Some(p) => p,
+            None => Project::prompt_get(config, prompter).await?,
+        };
+        project_service.audit(project_get_params).await
+    }
+
+    /// Fetches a project's live Scorecard results and maps each check to the facets in the facet registry that
+    /// address it. If the `project_get_params` is not provided, the user will be prompted for the project.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the project can't be fetched, or Scorecard has no results for it.
+    pub async fn scorecard<T: ProjectService + ?Sized, S: ScorecardService + ?Sized>(
+        config: &Config,
+        prompter: &dyn Prompter,
+        project_service: &T,
+        scorecard_service: &S,
+        project_get_params: Option<ProjectGetParams>,
+    ) -> Result<ScorecardReport, SkootError> {
+        let project = Project::get(config, prompter, project_service, project_get_params).await?;
+        let results = scorecard_service.results(&project.repo).await?;
+        let applied_facets = project
+            .facets
+            .values()
+            .map(InitializedFacet::facet_type)
+            .collect::<Vec<_>>();
+        Ok(ScorecardReport::new(
+            project.repo.full_url(),
+            results,
+            &config.facet_registry,
+            &applied_facets,
+        ))
+    }
+
+    /// Summarizes a project on one page: its facets, whether its branch protection is still enabled, what's
+    /// attached to its latest release and which facets drifted. If the `project_get_params` is not provided,
+    /// the user will be prompted for the project.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the project can't be fetched or audited. A project without a release is summarized
+    /// without one.
+    pub async fn status<T: ProjectService + ?Sized>(
+        config: &Config,
+        prompter: &dyn Prompter,
+        project_service: &T,
+        project_get_params: Option<ProjectGetParams>,
+    ) -> Result<ProjectStatus, SkootError> {
+        let project_get_params = match project_get_params {
+            Some(p) => p,
+            None => Project::prompt_get(config, prompter).await?,
+        };
+        let project = project_service.get(project_get_params.clone()).await?;
+        let audit = project_service.audit(project_get_params).await?;
+        let outputs = project_service
+            .outputs_list(ProjectOutputsListParams {
+                initialized_project: project.clone(),
+                release: ProjectReleaseParam::Latest,
+                workflow_run: None,
+            })
+            .await
+            .map_err(|e| debug!("Failed to list the outputs of the latest release: {e}"))
+            .ok();
+        Ok(ProjectStatus::new(&project, &audit, outputs.as_deref()))
+    }
+
+    /// Builds a project's compliance report from its facets and their live audit, for rendering as an OSCAL
+    /// component definition or Markdown. If the `project_get_params` is not provided, the user will be
+    /// prompted for the project.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the project can't be fetched or audited.
+    pub async fn compliance_report<T: ProjectService + ?Sized>(
+        config: &Config,
+        prompter: &dyn Prompter,
+        project_service: &T,
+        project_get_params: Option<ProjectGetParams>,
+    ) -> Result<ComplianceReport, SkootError> {
+        let project_get_params = match project_get_params {
+            Some(p) => p,
+            None => Project::prompt_get(config, prompter).await?,
+        };
+        let project = project_service.get(project_get_params.clone()).await?;
+        let audit = project_service.audit(project_get_params).await?;
+        Ok(ComplianceReport::new(&project, &audit, chrono::Utc::now()))
+    }
+
+    /// Evaluates a project against a policy bundle, along with its live audit and the outputs of its latest
+    /// release. If the `project_get_params` is not provided, the user will be prompted for the project.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the project can't be fetched or audited, or the policies can't be evaluated. A
+    /// project that violates the policies isn't an error.
+    pub async fn evaluate<T: ProjectService + ?Sized, P: PolicyService + ?Sized>(
+        config: &Config,
+        prompter: &dyn Prompter,
+        project_service: &T,
+        policy_service: &P,
+        project_get_params: Option<ProjectGetParams>,
+        policy: &Path,
+    ) -> Result<PolicyEvaluation, SkootError> {
+        let project_get_params = match project_get_params {
+            Some(p) => p,
+            None => Project::prompt_get(config, prompter).await?,
+        };
+        let project = project_service.get(project_get_params.clone()).await?;
+        let audit = project_service.audit(project_get_params).await?;
+        let latest_release = project_service
+            .outputs_list(ProjectOutputsListParams {
+                initialized_project: project.clone(),
+                release: ProjectReleaseParam::Latest,
+                workflow_run: None,
+            })
+            .await
+            .map_err(|e| debug!("Failed to list the outputs of the latest release: {e}"))
+            .ok();
+        let input = PolicyInput {
+            status: ProjectStatus::new(&project, &audit, latest_release.as_deref()),
+            project,
+            audit,
+            latest_release,
+        };
+        policy_service.evaluate(policy, &input)
+    }
+
+    /// Verifies a checkout of a project against the facet hashes recorded in its `.skootrs` file. Unlike
+    /// `audit` this never prompts since it's meant to run unattended in the project's own CI.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the checkout doesn't have a readable `.skootrs` file.
+    pub async fn verify_state<T: ProjectService + ?Sized>(
+        project_service: &T,
+        path: String,
+    ) -> Result<ProjectAudit, SkootError> {
+        project_service
+            .verify_state(InitializedSource { path })
+            .await
+    }
+
+    async fn prompt_get(
+        config: &Config,
+        prompter: &dyn Prompter,
+    ) -> Result<ProjectGetParams, SkootError> {
+        prompter.require(&["project_url"])?;
+        let mut projects = Project::list(config).await?.into_iter().collect::<Vec<_>>();
+        projects.sort();
+        let project_url = choose(
+            prompter,
+            Question::new("project_url", "Select a project"),
+            projects,
+            None,
+        )?;
         Ok(ProjectGetParams {
-            project_url: selected_project.clone(),
+            project_url,
+            read_strategy: ReadStrategy::default(),
         })
     }
 
@@ -175,28 +600,120 @@ impl Project {
     /// # Errors
     ///
     /// Returns an error if the project can't be updated for some reason.
-    pub async fn update<'a, T: ProjectService + ?Sized>(
+    pub async fn update<T: ProjectService + ?Sized>(
         config: &Config,
-        project_service: &'a T,
+        prompter: &dyn Prompter,
+        project_service: &T,
         project_update_params: Option<ProjectUpdateParams>,
     ) -> Result<InitializedProject, SkootError> {
-        let mut cache = InMemoryProjectReferenceCache::load_or_create("./skootcache")?;
         let project_update_params = match project_update_params {
             Some(p) => p,
-            None => Project::prompt_update(config, project_service).await?,
+            None => Project::prompt_update(config, prompter, project_service).await?,
         };
         let updated_project = project_service.update(project_update_params).await?;
-        cache.set(updated_project.repo.full_url()).await?;
+        // The state has to be kept current so the next update can tell which facets drifted.
+        let git_state_store = GitProjectStateStore {
+            source: updated_project.source.clone(),
+            source_service: ConfiguredSourceService::from_config(config),
+            signing: config.state_signing.clone(),
+        };
+        git_state_store.update(updated_project.clone()).await?;
+        let mut cache = ConfiguredProjectReferenceCache::open(config).await?;
+        cache.track(&updated_project).await?;
         Ok(updated_project)
     }
 
-    async fn prompt_update<'a, T: ProjectService + ?Sized>(
+    /// Links a mirror on another host to an existing initialized project and records it in the project's
+    /// state. If the `project_mirror_params` is not provided, the user will be prompted for the project and
+    /// the mirror's URL.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the mirror can't be linked or the project's state can't be updated.
+    pub async fn link_mirror<T: ProjectService + ?Sized>(
+        config: &Config,
+        prompter: &dyn Prompter,
+        project_service: &T,
+        project_mirror_params: Option<ProjectMirrorParams>,
+    ) -> Result<InitializedProject, SkootError> {
+        let project_mirror_params = match project_mirror_params {
+            Some(p) => p,
+            None => {
+                prompter.require(&["initialized_project", "mirror_url"])?;
+                ProjectMirrorParams {
+                    initialized_project: Project::get(config, prompter, project_service, None)
+                        .await?,
+                    mirror_url: prompter.text(
+                        Question::new("mirror_url", "The URL of the mirror's repo"),
+                        None,
+                    )?,
+                }
+            }
+        };
+        let linked_project = project_service.link_mirror(project_mirror_params).await?;
+        let git_state_store = GitProjectStateStore {
+            source: linked_project.source.clone(),
+            source_service: ConfiguredSourceService::from_config(config),
+            signing: config.state_signing.clone(),
+        };
+        git_state_store.update(linked_project.clone()).await?;
+        let mut cache = ConfiguredProjectReferenceCache::open(config).await?;
+        cache.track(&linked_project).await?;
+        Ok(linked_project)
+    }
+
+    /// Records a project under the new URL of its renamed or transferred repo, in the project's state and the
+    /// cache. If the `project_get_params` is not provided, the user will be prompted for the project's old
+    /// URL.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the project can't be fetched, its repo wasn't moved, or the project's state can't
+    /// be updated.
+    pub async fn relocate<T: ProjectService + ?Sized>(
         config: &Config,
-        project_service: &'a T,
+        prompter: &dyn Prompter,
+        project_service: &T,
+        project_get_params: Option<ProjectGetParams>,
+    ) -> Result<ProjectRelocation, SkootError> {
+        let initialized_project =
+            Project::get(config, prompter, project_service, project_get_params).await?;
+        let mut relocation = project_service
+            .relocate(ProjectRelocateParams {
+                initialized_project,
+            })
+            .await?;
+        let git_state_store = GitProjectStateStore {
+            source: relocation.project.source.clone(),
+            source_service: ConfiguredSourceService::from_config(config),
+            signing: config.state_signing.clone(),
+        };
+        // Nothing but the state changes, so it gets a pull request of its own unless changes are pushed.
+        relocation.pull_request = git_state_store
+            .propose(
+                relocation.project.clone(),
+                &LocalRepoService::from_config(config),
+                &config.change_requests,
+                "Relocated project to its new repo",
+            )
+            .await?;
+        let mut cache = ConfiguredProjectReferenceCache::open(config).await?;
+        cache
+            .relocate(relocation.old_url.clone(), &relocation.project)
+            .await?;
+        Ok(relocation)
+    }
+
+    async fn prompt_update<T: ProjectService + ?Sized>(
+        config: &Config,
+        prompter: &dyn Prompter,
+        project_service: &T,
     ) -> Result<ProjectUpdateParams, SkootError> {
-        let initialized_project = Project::get(config, project_service, None).await?;
+        prompter.require(&["initialized_project"])?;
+        let initialized_project = Project::get(config, prompter, project_service, None).await?;
         Ok(ProjectUpdateParams {
             initialized_project,
+            facet_types: None,
         })
     }
 
@@ -205,33 +722,169 @@ impl Project {
     /// # Errors
     ///
     /// Returns an error if the cache can't be loaded or if the list of projects can't be fetched.
-    pub async fn list(_config: &Config) -> Result<HashSet<String>, SkootError> {
-        let cache = InMemoryProjectReferenceCache::load_or_create("./skootcache")?;
+    pub async fn list(config: &Config) -> Result<HashSet<String>, SkootError> {
+        let cache = ConfiguredProjectReferenceCache::open(config).await?;
         let projects: HashSet<String> = cache.list().await?;
         Ok(projects)
     }
 
-    /// Archives a project by archiving the repository and removing it from the local cache.
+    /// Returns the projects stored in the cache that match every word of a search query, optionally
+    /// refreshing their state from their repos first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cache can't be loaded or if the list of projects can't be fetched.
+    pub async fn search(
+        config: &Config,
+        query: &str,
+        refresh: bool,
+    ) -> Result<Vec<ProjectSummary>, SkootError> {
+        let mut cache = ConfiguredProjectReferenceCache::open(config).await?;
+        cache.search(query, refresh).await
+    }
+
+    /// Fetches the state of a project stored in the cache, or of every one, from its repo again, forgetting
+    /// the projects whose repos don't exist anymore.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cache can't be loaded or the project isn't in it.
+    pub async fn refresh(
+        config: &Config,
+        project_url: Option<String>,
+    ) -> Result<ProjectRefresh, SkootError> {
+        let mut cache = ConfiguredProjectReferenceCache::open(config).await?;
+        cache.refresh(project_url).await
+    }
+
+    /// Builds a report of the facet coverage and drift across all the projects that are stored in the cache.
+    /// Projects that can't be fetched are recorded in the report instead of failing it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cache can't be loaded or if the list of projects can't be fetched.
+    pub async fn report<T: ProjectService + ?Sized>(
+        config: &Config,
+        project_service: &T,
+    ) -> Result<OrgReport, SkootError> {
+        let mut report = OrgReport::default();
+        for project_url in Project::list(config).await? {
+            let project_get_params = ProjectGetParams {
+                project_url: project_url.clone(),
+                read_strategy: ReadStrategy::default(),
+            };
+            match project_service.get(project_get_params.clone()).await {
+                Ok(project) => report.add_project(&project),
+                Err(e) => {
+                    error!("Failed to fetch project {project_url} for report: {e}");
+                    report.add_failed_project(project_url);
+                    continue;
+                }
+            }
+            match project_service.audit(project_get_params).await {
+                Ok(audit) => report.add_audit(&audit),
+                Err(e) => error!("Failed to audit project {project_url} for report: {e}"),
+            }
+        }
+        Ok(report)
+    }
+
+    /// Pushes a report's metrics to a Prometheus Pushgateway under the `skootrs` job.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the metrics can't be pushed.
+    pub async fn push_report_metrics(
+        report: &OrgReport,
+        push_gateway_url: &str,
+    ) -> Result<(), SkootError> {
+        let url = format!(
+            "{}/metrics/job/skootrs",
+            push_gateway_url.trim_end_matches('/')
+        );
+        reqwest::Client::new()
+            .put(url)
+            .body(report.to_prometheus())
+            .send()
+            .await
+            .map_err(SkootError::other)?
+            .error_for_status()
+            .map_err(SkootError::other)?;
+        Ok(())
+    }
+
+    /// Archives a project by archiving the repository and removing it from the local cache. If `cleanup` is
+    /// set, the repo's Actions are cleaned up before it's archived.
     ///
     /// # Errors
     ///
     /// Returns an error if the project can't be archived or deleted from the cache.
-    pub async fn archive<'a, T: ProjectService + ?Sized>(
+    pub async fn archive<T: ProjectService + ?Sized>(
         config: &Config,
-        project_service: &'a T,
+        prompter: &dyn Prompter,
+        project_service: &T,
         project_archive_params: Option<ProjectArchiveParams>,
-    ) -> Result<(), SkootError> {
-        let project_archive_params = match project_archive_params {
+        cleanup: bool,
+    ) -> Result<ProjectArchive, SkootError> {
+        let mut project_archive_params = match project_archive_params {
             Some(p) => p,
-            None => ProjectArchiveParams {
-                initialized_project: Project::get(config, project_service, None).await?,
-            },
+            None => {
+                prompter.require(&["initialized_project"])?;
+                ProjectArchiveParams {
+                    initialized_project: Project::get(config, prompter, project_service, None)
+                        .await?,
+                    cleanup,
+                }
+            }
         };
+        project_archive_params.cleanup |= cleanup;
         let url = project_archive_params.initialized_project.repo.full_url();
-        project_service.archive(project_archive_params).await?;
-        let mut local_cache = InMemoryProjectReferenceCache::load_or_create("./skootcache")?;
+        let archive = project_service.archive(project_archive_params).await?;
+        let mut local_cache = ConfiguredProjectReferenceCache::open(config).await?;
+        local_cache.delete(url).await?;
+        Ok(archive)
+    }
+
+    /// Permanently deletes a project by deleting the repository and the local clone, and removing it from the
+    /// local cache. If the `project_delete_params` is not provided, the user will be prompted for the project
+    /// and asked to confirm the deletion.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the deletion isn't confirmed, or if the project can't be deleted or removed from the
+    /// cache.
+    pub async fn delete<T: ProjectService + ?Sized>(
+        config: &Config,
+        prompter: &dyn Prompter,
+        project_service: &T,
+        project_delete_params: Option<ProjectDeleteParams>,
+    ) -> Result<(), SkootError> {
+        let project_delete_params = match project_delete_params {
+            Some(p) => p,
+            None => {
+                prompter.require(&["initialized_project", "confirm"])?;
+                let initialized_project =
+                    Project::get(config, prompter, project_service, None).await?;
+                let confirm = prompter.confirm(
+                    Question::new(
+                        "confirm",
+                        &format!(
+                            "Permanently delete {} and its local clone?",
+                            initialized_project.repo.full_url()
+                        ),
+                    ),
+                    false,
+                )?;
+                ProjectDeleteParams {
+                    initialized_project,
+                    confirm,
+                }
+            }
+        };
+        let url = project_delete_params.initialized_project.repo.full_url();
+        project_service.delete(project_delete_params).await?;
+        let mut local_cache = ConfiguredProjectReferenceCache::open(config).await?;
         local_cache.delete(url).await?;
-        local_cache.save()?;
         Ok(())
     }
 }
@@ -239,65 +892,503 @@ impl Project {
 pub struct Facet;
 
 impl Facet {
-    /// Returns the contents of a facet. This includes things like source files or API bundles.
+    /// Returns the contents of a facet. This includes things like source files or API bundles. The facet's
+    /// files are read from the project's local clone if `read_strategy`, or the one in `facet_get_params` if
+    /// it isn't given, says so.
     ///
     /// # Errors
     ///
     /// Returns an error if the facet content or project can't be fetched for some reason.
-    pub async fn get<'a, T: ProjectService + ?Sized>(
+    pub async fn get<T: ProjectService + ?Sized>(
         config: &Config,
-        project_service: &'a T,
+        prompter: &dyn Prompter,
+        project_service: &T,
         facet_get_params: Option<FacetGetParams>,
+        labels: &[String],
+        read_strategy: Option<ReadStrategy>,
     ) -> Result<InitializedFacet, SkootError> {
-        let facet_get_params = if let Some(p) = facet_get_params {
+        let mut facet_get_params = if let Some(p) = facet_get_params {
             p
         } else {
-            // let project = Project::get(config, project_service, None).await?;
-            let project_get_params = Project::prompt_get(config).await?;
+            prompter.require(&["project_get_params", "facet_map_key"])?;
+            let project_get_params = Project::prompt_get(config, prompter).await?;
             let facet_map_keys = project_service
-                .list_facets(project_get_params.clone())
+                .list_facets(FacetListParams {
+                    project_get_params: project_get_params.clone(),
+                    labels: labels.to_vec(),
+                })
                 .await?;
-            let fmk = Facet::prompt_get(config, facet_map_keys.into_iter().collect())?;
+            if facet_map_keys.is_empty() {
+                return Err(
+                    format!("The project has no facets labeled {}", labels.join(", ")).into(),
+                );
+            }
+            let mut facet_map_keys = facet_map_keys.into_iter().collect::<Vec<_>>();
+            facet_map_keys.sort_by_key(ToString::to_string);
             FacetGetParams {
-                facet_map_key: fmk,
+                facet_map_key: choose(
+                    prompter,
+                    Question::new("facet_map_key", "Select a facet"),
+                    facet_map_keys,
+                    None,
+                )?,
                 project_get_params,
             }
         };
 
+        if let Some(read_strategy) = read_strategy {
+            facet_get_params.project_get_params.read_strategy = read_strategy;
+        }
         let facet_with_content = project_service
             .get_facet_with_content(facet_get_params)
             .await?;
+        if !facet_with_content.matches_labels(labels) {
+            return Err(format!(
+                "Facet {} isn't labeled {}",
+                facet_with_content.facet_type(),
+                labels.join(", ")
+            )
+            .into());
+        }
 
         debug!("{:?}", facet_with_content);
 
         Ok(facet_with_content)
     }
 
-    fn prompt_get(
-        _config: &Config,
-        facet_map_keys: Vec<FacetMapKey>,
-    ) -> Result<FacetMapKey, SkootError> {
-        let facet_type = inquire::Select::new("Select a facet", facet_map_keys).prompt()?;
+    /// Returns the list of facets for a project. This includes things like source files or API bundles. Only
+    /// facets with labels matching each of the given labels are returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the project or list of facets can't be fetched for some reason.
+    pub async fn list<T: ProjectService + ?Sized>(
+        config: &Config,
+        prompter: &dyn Prompter,
+        project_service: &T,
+        facet_list_params: Option<FacetListParams>,
+        labels: Vec<String>,
+    ) -> Result<Vec<FacetListing>, SkootError> {
+        let mut facet_list_params = match facet_list_params {
+            Some(p) => p,
+            None => {
+                prompter.require(&["project_get_params"])?;
+                FacetListParams {
+                    project_get_params: Project::prompt_get(config, prompter).await?,
+                    labels: vec![],
+                }
+            }
+        };
+        facet_list_params.labels.extend(labels);
+        let initialized_project = project_service
+            .get(facet_list_params.project_get_params)
+            .await?;
+        let mut facets: Vec<FacetListing> = initialized_project
+            .facets
+            .iter()
+            .filter(|(_, facet)| facet.matches_labels(&facet_list_params.labels))
+            .map(|(key, facet)| FacetListing {
+                key: key.clone(),
+                facet_type: facet.facet_type(),
+                labels: facet.labels(),
+            })
+            .collect();
+        facets.sort_by_key(|facet| facet.key.to_string());
+        Ok(facets)
+    }
+
+    /// Adds a single facet to an existing project and records it in the project's state. If the params
+    /// aren't provided, the user will be prompted for the project and the facet type.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the project already has the facet or if the facet can't be added.
+    pub async fn add<T: ProjectService + ?Sized>(
+        config: &Config,
+        prompter: &dyn Prompter,
+        project_service: &T,
+        facet_add_params: Option<FacetAddParams>,
+    ) -> Result<InitializedProject, SkootError> {
+        let facet_add_params = match facet_add_params {
+            Some(p) => p,
+            None => Facet::prompt_add(config, prompter, project_service).await?,
+        };
+        let updated_project = project_service.add_facet(facet_add_params).await?;
+        let git_state_store = GitProjectStateStore {
+            source: updated_project.source.clone(),
+            source_service: ConfiguredSourceService::from_config(config),
+            signing: config.state_signing.clone(),
+        };
+        git_state_store.update(updated_project.clone()).await?;
+        let mut cache = ConfiguredProjectReferenceCache::open(config).await?;
+        cache.track(&updated_project).await?;
+        Ok(updated_project)
+    }
 
-        Ok(facet_type)
+    async fn prompt_add<T: ProjectService + ?Sized>(
+        config: &Config,
+        prompter: &dyn Prompter,
+        project_service: &T,
+    ) -> Result<FacetAddParams, SkootError> {
+        prompter.require(&["initialized_project", "facet_type"])?;
+        let initialized_project = Project::get(config, prompter, project_service, None).await?;
+        let facet_types = SupportedFacetType::VARIANTS
+            .iter()
+            .filter_map(|v| SupportedFacetType::from_str(v).ok())
+            .filter(|facet_type| {
+                FacetRegistry::is_implemented(facet_type)
+                    && !initialized_project
+                        .facets
+                        .contains_key(&FacetMapKey::Type(facet_type.clone()))
+                    && config.facet_registry.entry(facet_type).is_none_or(|entry| {
+                        initialized_project
+                            .modules
+                            .iter()
+                            .any(|module| entry.supports(&module.ecosystem))
+                    })
+            })
+            .collect::<Vec<_>>();
+        if facet_types.is_empty() {
+            return Err("The project already has every facet".into());
+        }
+        let facet_type = choose(
+            prompter,
+            Question::new("facet_type", "Select a facet to add"),
+            facet_types,
+            None,
+        )?;
+        let options = match config
+            .facet_registry
+            .entry(&facet_type)
+            .and_then(|entry| entry.options.as_ref())
+        {
+            Some(schema) => Facet::prompt_options(prompter, schema)?,
+            None => BTreeMap::new(),
+        };
+        Ok(FacetAddParams {
+            initialized_project,
+            facet_type,
+            options,
+        })
     }
 
-    /// Returns the list of facets for a project. This includes things like source files or API bundles.
+    /// Previews the content a facet would have in a new project without creating the project or writing any
+    /// files. If the params aren't provided, the user will be prompted for the project and the facet type.
     ///
     /// # Errors
     ///
-    /// Returns an error if the project or list of facets can't be fetched for some reason.
-    pub async fn list<'a, T: ProjectService + ?Sized>(
+    /// Returns an error if the params are invalid or the facet's content can't be rendered.
+    pub async fn preview<T: ProjectService + ?Sized>(
         config: &Config,
-        project_service: &'a T,
-        project_get_params: Option<ProjectGetParams>,
-    ) -> Result<Vec<FacetMapKey>, SkootError> {
-        let project_get_params = match project_get_params {
+        prompter: &dyn Prompter,
+        project_service: &T,
+        facet_preview_params: Option<FacetPreviewParams>,
+    ) -> Result<Vec<SourceFileContent>, SkootError> {
+        let facet_preview_params = match facet_preview_params {
             Some(p) => p,
-            None => Project::prompt_get(config).await?,
+            None => Facet::prompt_preview(config, prompter).await?,
+        };
+        project_service.preview_facet(facet_preview_params).await
+    }
+
+    async fn prompt_preview(
+        config: &Config,
+        prompter: &dyn Prompter,
+    ) -> Result<FacetPreviewParams, SkootError> {
+        prompter.require(&["project_params", "facet_type"])?;
+        let project_params = Project::prompt_create(config, prompter).await?;
+        // API bundle facets are enabled through the repo host's API so they have no content to preview.
+        let facet_types = SupportedFacetType::VARIANTS
+            .iter()
+            .filter_map(|v| SupportedFacetType::from_str(v).ok())
+            .filter(|facet_type| {
+                FacetRegistry::is_implemented(facet_type) && !facet_type.is_api_bundle()
+            })
+            .collect::<Vec<_>>();
+        let facet_type = choose(
+            prompter,
+            Question::new("facet_type", "Select a facet to preview"),
+            facet_types,
+            None,
+        )?;
+        let options = match config
+            .facet_registry
+            .entry(&facet_type)
+            .and_then(|entry| entry.options.as_ref())
+        {
+            Some(schema) => Facet::prompt_options(prompter, schema)?,
+            None => BTreeMap::new(),
         };
-        let facet_map_keys = project_service.list_facets(project_get_params).await?;
-        Ok(facet_map_keys)
+        Ok(FacetPreviewParams {
+            project_params,
+            facet_type,
+            options,
+        })
+    }
+
+    /// Deletes a single facet from an existing project and removes it from the project's state. If the
+    /// params aren't provided, the user will be prompted for the project and the facet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the project doesn't have the facet or if the facet can't be deleted.
+    pub async fn delete<T: ProjectService + ?Sized>(
+        config: &Config,
+        prompter: &dyn Prompter,
+        project_service: &T,
+        facet_delete_params: Option<FacetDeleteParams>,
+    ) -> Result<InitializedProject, SkootError> {
+        let facet_delete_params = match facet_delete_params {
+            Some(p) => p,
+            None => Facet::prompt_delete(config, prompter, project_service).await?,
+        };
+        let updated_project = project_service.delete_facet(facet_delete_params).await?;
+        let git_state_store = GitProjectStateStore {
+            source: updated_project.source.clone(),
+            source_service: ConfiguredSourceService::from_config(config),
+            signing: config.state_signing.clone(),
+        };
+        git_state_store.update(updated_project.clone()).await?;
+        let mut cache = ConfiguredProjectReferenceCache::open(config).await?;
+        cache.track(&updated_project).await?;
+        Ok(updated_project)
+    }
+
+    async fn prompt_delete<T: ProjectService + ?Sized>(
+        config: &Config,
+        prompter: &dyn Prompter,
+        project_service: &T,
+    ) -> Result<FacetDeleteParams, SkootError> {
+        let (initialized_project, facet_map_key) = Facet::prompt_project_facet(
+            config,
+            prompter,
+            project_service,
+            "Select a facet to delete",
+            |_| true,
+        )
+        .await?;
+        Ok(FacetDeleteParams {
+            initialized_project,
+            facet_map_key,
+        })
+    }
+
+    /// Diffs a facet's files in the project's repo against what a project update would re-render them as.
+    /// If the params aren't provided, the user will be prompted for the project and the facet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the project doesn't have the facet, the facet isn't re-rendered by an update, or
+    /// the facet's files can't be rendered or fetched.
+    pub async fn diff<T: ProjectService + ?Sized>(
+        config: &Config,
+        prompter: &dyn Prompter,
+        project_service: &T,
+        facet_diff_params: Option<FacetDiffParams>,
+    ) -> Result<FacetDiff, SkootError> {
+        let facet_diff_params = match facet_diff_params {
+            Some(p) => p,
+            None => {
+                // API bundle facets are enabled through the repo host's API so they have no files to diff.
+                let (initialized_project, facet_map_key) = Facet::prompt_project_facet(
+                    config,
+                    prompter,
+                    project_service,
+                    "Select a facet to diff",
+                    |facet| !facet.facet_type().is_api_bundle(),
+                )
+                .await?;
+                FacetDiffParams {
+                    initialized_project,
+                    facet_map_key,
+                }
+            }
+        };
+        project_service.diff_facet(facet_diff_params).await
+    }
+
+    /// Prompts for a project and one of its facets matching the filter.
+    async fn prompt_project_facet<T: ProjectService + ?Sized>(
+        config: &Config,
+        prompter: &dyn Prompter,
+        project_service: &T,
+        question: &str,
+        filter: impl Fn(&InitializedFacet) -> bool,
+    ) -> Result<(InitializedProject, FacetMapKey), SkootError> {
+        prompter.require(&["initialized_project", "facet_map_key"])?;
+        let initialized_project = Project::get(config, prompter, project_service, None).await?;
+        let facet_map_keys = initialized_project
+            .facets
+            .iter()
+            .filter(|(_, facet)| filter(facet))
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>();
+        if facet_map_keys.is_empty() {
+            return Err("The project doesn't have any facets".into());
+        }
+        let facet_map_key = choose(
+            prompter,
+            Question::new("facet_map_key", question),
+            facet_map_keys,
+            None,
+        )?;
+        Ok((initialized_project, facet_map_key))
+    }
+
+    /// Prompts for each option described by a facet's options schema. The prompt for an option is picked
+    /// from its type, `enum` values pre-select their `default`, and options that aren't required can be
+    /// skipped.
+    fn prompt_options(
+        prompter: &dyn Prompter,
+        schema: &SchemaObject,
+    ) -> Result<BTreeMap<String, Value>, SkootError> {
+        let mut options = BTreeMap::new();
+        let Some(object) = schema.object.as_deref() else {
+            return Ok(options);
+        };
+        for (name, property) in &object.properties {
+            let Schema::Object(property) = property else {
+                continue;
+            };
+            if let Some(value) =
+                Facet::prompt_option(prompter, name, property, object.required.contains(name))?
+            {
+                options.insert(name.clone(), value);
+            }
+        }
+        Ok(options)
+    }
+
+    fn prompt_option(
+        prompter: &dyn Prompter,
+        name: &str,
+        property: &SchemaObject,
+        required: bool,
+    ) -> Result<Option<Value>, SkootError> {
+        let metadata = property.metadata.as_deref();
+        let default = metadata.and_then(|m| m.default.as_ref());
+        let description = metadata
+            .and_then(|m| m.description.clone())
+            .unwrap_or_default();
+        let help = if required {
+            description
+        } else {
+            format!("{description} (optional, press esc to skip)")
+        };
+        let help = help.trim();
+        let question = Question::new(name, name).with_help(help);
+
+        if let Some(allowed) = &property.enum_values {
+            let labels = allowed
+                .iter()
+                .map(|v| v.as_str().map_or_else(|| v.to_string(), str::to_string))
+                .collect::<Vec<_>>();
+            let default = default.and_then(|d| allowed.iter().position(|v| v == d));
+            let selected = if required {
+                Some(prompter.select(question, &labels, default)?)
+            } else {
+                prompter.select_skippable(question, &labels, default)?
+            };
+            return Ok(selected.and_then(|i| allowed.get(i)).cloned());
+        }
+
+        let instance_type = match &property.instance_type {
+            Some(SingleOrVec::Single(t)) => Some(**t),
+            Some(SingleOrVec::Vec(ts)) => ts.first().copied(),
+            None => None,
+        };
+        let text = |question: Question<'_>, default: Option<&str>| {
+            if required {
+                prompter.text(question, default).map(Some)
+            } else {
+                prompter.text_skippable(question, default)
+            }
+        };
+        let value = match instance_type {
+            Some(InstanceType::Boolean) => {
+                let default = default.and_then(Value::as_bool).unwrap_or_default();
+                if required {
+                    Some(prompter.confirm(question, default)?)
+                } else {
+                    prompter.confirm_skippable(question, default)?
+                }
+                .map(Value::from)
+            }
+            Some(InstanceType::Integer) => {
+                let default = default.and_then(Value::as_i64).map(|d| d.to_string());
+                text(question, default.as_deref())?
+                    .map(|answer| {
+                        answer.trim().parse::<i64>().map(Value::from).map_err(|_| {
+                            SkootError::from(format!("{name} has to be an integer, not {answer}"))
+                        })
+                    })
+                    .transpose()?
+            }
+            Some(InstanceType::Number) => {
+                let default = default.and_then(Value::as_f64).map(|d| d.to_string());
+                text(question, default.as_deref())?
+                    .map(|answer| {
+                        answer.trim().parse::<f64>().map(Value::from).map_err(|_| {
+                            SkootError::from(format!("{name} has to be a number, not {answer}"))
+                        })
+                    })
+                    .transpose()?
+            }
+            // Lists of strings, like the branches to protect, are entered comma separated.
+            Some(InstanceType::Array) => {
+                let default = default.and_then(Value::as_array).map(|items| {
+                    items
+                        .iter()
+                        .filter_map(Value::as_str)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                });
+                let help = format!("{help} (comma separated)");
+                text(question.with_help(&help), default.as_deref())?.map(|items| {
+                    items
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|item| !item.is_empty())
+                        .map(Value::from)
+                        .collect()
+                })
+            }
+            _ => text(question, default.and_then(Value::as_str))?.map(Value::from),
+        };
+        Ok(value)
+    }
+
+    /// Returns a previous version of a facet's content from the project's facet history. If the `project_url`
+    /// is not provided, the user will be prompted for the project.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the project can't be fetched or the facet has no such version.
+    pub async fn history_show<T: ProjectService + ?Sized>(
+        config: &Config,
+        prompter: &dyn Prompter,
+        project_service: &T,
+        facet_type: SupportedFacetType,
+        version: Option<u32>,
+        project_url: Option<String>,
+    ) -> Result<FacetHistoryEntry, SkootError> {
+        let project_get_params = match project_url {
+            Some(project_url) => ProjectGetParams {
+                project_url,
+                read_strategy: ReadStrategy::default(),
+            },
+            None => {
+                prompter.require(&["--project"])?;
+                Project::prompt_get(config, prompter).await?
+            }
+        };
+        project_service
+            .get_facet_history(FacetHistoryGetParams {
+                project_get_params,
+                facet_type,
+                version,
+            })
+            .await
     }
 }
 
@@ -309,14 +1400,15 @@ impl Output {
     /// # Errors
     ///
     /// Returns an error if the project output can't be fetched from a project release.
-    pub async fn get<'a, T: ProjectService + ?Sized>(
+    pub async fn get<T: ProjectService + ?Sized>(
         config: &Config,
-        project_service: &'a T,
+        prompter: &dyn Prompter,
+        project_service: &T,
         project_output_params: Option<ProjectOutputGetParams>,
     ) -> Result<ProjectOutput, SkootError> {
         let project_output_params = match project_output_params {
             Some(p) => p,
-            None => Output::prompt_output_get(config, project_service).await?,
+            None => Output::prompt_output_get(config, prompter, project_service).await?,
         };
 
         let output = project_service.output_get(project_output_params).await?;
@@ -329,17 +1421,23 @@ impl Output {
     /// # Errors
     ///
     /// Returns an error if the project output list can't be fetched.
-    pub async fn list<'a, T: ProjectService + ?Sized>(
+    pub async fn list<T: ProjectService + ?Sized>(
         config: &Config,
-        project_service: &'a T,
+        prompter: &dyn Prompter,
+        project_service: &T,
         project_outputs_list_params: Option<ProjectOutputsListParams>,
     ) -> Result<Vec<ProjectOutputReference>, SkootError> {
         let project_outputs_list_params = match project_outputs_list_params {
             Some(p) => p,
-            None => ProjectOutputsListParams {
-                initialized_project: Project::get(config, project_service, None).await?,
-                release: ProjectReleaseParam::Latest,
-            },
+            None => {
+                prompter.require(&["initialized_project", "release"])?;
+                ProjectOutputsListParams {
+                    initialized_project: Project::get(config, prompter, project_service, None)
+                        .await?,
+                    release: ProjectReleaseParam::Latest,
+                    workflow_run: None,
+                }
+            }
         };
         let output_list = project_service
             .outputs_list(project_outputs_list_params)
@@ -347,18 +1445,122 @@ impl Output {
         Ok(output_list)
     }
 
-    async fn prompt_output_get<'a, T: ProjectService + ?Sized>(
+    /// Downloads the named output of a project's release, or all of them, to files in `dir`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an output can't be downloaded, or doesn't match the size or checksum the release
+    /// gives for it.
+    pub async fn download<T: ProjectService + ?Sized>(
+        config: &Config,
+        prompter: &dyn Prompter,
+        project_service: &T,
+        project_outputs_list_params: Option<ProjectOutputsListParams>,
+        name: Option<String>,
+        dir: String,
+    ) -> Result<Vec<DownloadedOutput>, SkootError> {
+        let project_outputs_list_params = match project_outputs_list_params {
+            Some(p) => p,
+            None => {
+                prompter.require(&["initialized_project", "release"])?;
+                ProjectOutputsListParams {
+                    initialized_project: Project::get(config, prompter, project_service, None)
+                        .await?,
+                    release: ProjectReleaseParam::Latest,
+                    workflow_run: None,
+                }
+            }
+        };
+        project_service
+            .outputs_download(ProjectOutputsDownloadParams {
+                initialized_project: project_outputs_list_params.initialized_project,
+                release: project_outputs_list_params.release,
+                workflow_run: project_outputs_list_params.workflow_run,
+                project_output: name,
+                dir,
+            })
+            .await
+    }
+
+    /// Verifies an in-toto attestation attached to a project's release against the project's identity.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the attestation can't be fetched or isn't made of DSSE envelopes, or if cosign
+    /// can't be run.
+    pub async fn verify<T: ProjectService + ?Sized>(
+        config: &Config,
+        prompter: &dyn Prompter,
+        project_service: &T,
+        params: Option<AttestationVerifyParams>,
+    ) -> Result<AttestationVerification, SkootError> {
+        let params = match params {
+            Some(p) => p,
+            None => {
+                prompter.require(&["initialized_project", "project_output", "release"])?;
+                let initialized_project =
+                    Project::get(config, prompter, project_service, None).await?;
+                let attestations = project_service
+                    .outputs_list(ProjectOutputsListParams {
+                        initialized_project: initialized_project.clone(),
+                        release: ProjectReleaseParam::Latest,
+                        workflow_run: None,
+                    })
+                    .await?
+                    .into_iter()
+                    .filter(|output| {
+                        matches!(
+                            output.output_type,
+                            ProjectOutputType::SLSAAttestation
+                                | ProjectOutputType::InToto
+                                | ProjectOutputType::Attestation(_)
+                        )
+                    })
+                    .map(|output| output.name)
+                    .collect::<Vec<_>>();
+                if attestations.is_empty() {
+                    return Err("The latest release has no attestations to verify".into());
+                }
+                AttestationVerifyParams {
+                    initialized_project,
+                    project_output: choose(
+                        prompter,
+                        Question::new("project_output", "Select an attestation"),
+                        attestations,
+                        None,
+                    )?,
+                    release: ProjectReleaseParam::Latest,
+                }
+            }
+        };
+        project_service.output_verify(params).await
+    }
+
+    async fn prompt_output_get<T: ProjectService + ?Sized>(
         config: &Config,
-        project_service: &'a T,
+        prompter: &dyn Prompter,
+        project_service: &T,
     ) -> Result<ProjectOutputGetParams, SkootError> {
-        let selected_project = Project::get(config, project_service, None).await?;
+        prompter.require(&[
+            "initialized_project",
+            "project_output_type",
+            "project_output",
+            "release",
+        ])?;
+        let selected_project = Project::get(config, prompter, project_service, None).await?;
         let project_output_list_params = ProjectOutputsListParams {
             initialized_project: selected_project.clone(),
             // TODO: This should be a prompt.
             release: ProjectReleaseParam::Latest,
+            workflow_run: None,
         };
-        let output_list =
-            Output::list(config, project_service, Some(project_output_list_params)).await?;
+        let output_list = Output::list(
+            config,
+            prompter,
+            project_service,
+            Some(project_output_list_params),
+        )
+        .await?;
         let type_output_map: HashMap<String, Vec<String>> = output_list
             .iter()
             .map(|o| (o.output_type.to_string(), o.name.clone()))
@@ -369,26 +1571,1004 @@ impl Output {
                     acc
                 },
             );
-        let selected_output_type = inquire::Select::new(
-            "Select an output type",
+        let selected_output_type = choose(
+            prompter,
+            Question::new("project_output_type", "Select an output type"),
             type_output_map.keys().cloned().collect(),
-        )
-        .prompt()?;
-        let select_output_type_enum = ProjectOutputType::from_str(&selected_output_type)?;
-        let selected_output = inquire::Select::new(
-            "Select an output",
+            None,
+        )?;
+        let select_output_type_enum =
+            ProjectOutputType::from_str(&selected_output_type).map_err(SkootError::other)?;
+        let selected_output = choose(
+            prompter,
+            Question::new("project_output", "Select an output"),
             type_output_map
                 .get(&selected_output_type)
                 .ok_or_else(|| SkootError::from("Failed to get output type"))?
                 .clone(),
-        )
-        .prompt()?;
+            None,
+        )?;
         Ok(ProjectOutputGetParams {
             initialized_project: selected_project.clone(),
             project_output_type: select_output_type_enum,
             project_output: selected_output.clone(),
             // TODO: This should be selectable
             release: ProjectReleaseParam::Latest,
+            workflow_run: None,
         })
     }
 }
+
+pub struct AuditLog;
+
+impl AuditLog {
+    /// Lists the entries of the audit log in the config that match the filter.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the config has no audit log, the log can't be read, or it was tampered with.
+    pub fn list(
+        config: &Config,
+        filter: &AuditLogFilter,
+    ) -> Result<Vec<AuditLogEntry>, SkootError> {
+        let path = config.audit_log.path.as_deref().ok_or_else(|| {
+            SkootError::from("There's no audit log, set audit_log.path in the config to record one")
+        })?;
+        audit_log::list(std::path::Path::new(path), filter)
+    }
+}
+
+/// The file the state of the current rollout is persisted to so it can be paused and resumed.
+const ROLLOUT_STATE_PATH: &str = "./skootrollout";
+
+pub struct Org;
+
+impl Org {
+    /// Plans a rollout of a facet across all the projects of an organization that are stored in the cache,
+    /// without applying it. Projects that can't be fetched are left out of the plan.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cache can't be loaded.
+    pub async fn rollout_preview<T: ProjectService + ?Sized>(
+        config: &Config,
+        project_service: &T,
+        organization: String,
+        facet_type: SupportedFacetType,
+        canary_size: usize,
+        batch_size: usize,
+    ) -> Result<Rollout, SkootError> {
+        let mut project_urls = Project::list(config).await?.into_iter().collect::<Vec<_>>();
+        // Sorted so the same org always gets the same canary.
+        project_urls.sort();
+        let mut projects = vec![];
+        for project_url in project_urls {
+            let project_get_params = ProjectGetParams {
+                project_url: project_url.clone(),
+                read_strategy: ReadStrategy::default(),
+            };
+            let project = match project_service.get(project_get_params).await {
+                Ok(project) => project,
+                Err(e) => {
+                    error!("Failed to fetch project {project_url} for rollout: {e}");
+                    continue;
+                }
+            };
+            // Repos on generic git remotes don't belong to an organization.
+            let InitializedRepo::Github(repo) = &project.repo else {
+                continue;
+            };
+            if repo.organization.get_name() != organization {
+                continue;
+            }
+            let change = if project
+                .facets
+                .contains_key(&FacetMapKey::Type(facet_type.clone()))
+            {
+                RolloutChange::Update
+            } else {
+                RolloutChange::Add
+            };
+            projects.push(RolloutProject {
+                project_url,
+                change,
+                status: RolloutProjectStatus::Pending,
+            });
+        }
+        Ok(Rollout::plan(
+            organization,
+            facet_type,
+            projects,
+            canary_size,
+            batch_size,
+        ))
+    }
+
+    /// Starts a rollout of a facet across all the projects of an organization. The user is prompted for the
+    /// organization and facet if they aren't provided, shown a preview of the rollout, and asked to confirm it
+    /// unless `yes` is set. The waves are then applied one at a time.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if another rollout hasn't completed yet, if the rollout isn't confirmed, or if the
+    /// rollout state can't be saved.
+    pub async fn rollout_start<T: ProjectService + ?Sized>(
+        config: &Config,
+        prompter: &dyn Prompter,
+        project_service: &T,
+        params: RolloutParams,
+        yes: bool,
+    ) -> Result<Rollout, SkootError> {
+        let RolloutParams {
+            organization,
+            facet_type,
+            canary_size,
+            batch_size,
+        } = params;
+        if let Ok(rollout) = Org::load_rollout() {
+            if rollout.status != RolloutStatus::Completed {
+                return Err(SkootError::from(format!(
+                    "The rollout of {} to {} hasn't completed, resume it before starting another one",
+                    rollout.facet_type, rollout.organization
+                )));
+            }
+        }
+        let missing = [
+            (organization.is_none() && config.defaults.organization.is_none()).then_some("--org"),
+            facet_type.is_none().then_some("--facet"),
+            (!yes).then_some("--yes"),
+        ];
+        prompter.require(&missing.into_iter().flatten().collect::<Vec<_>>())?;
+        let organization = match organization {
+            Some(o) => o,
+            None => prompter.text(
+                Question::new("--org", "The organization to roll out to"),
+                config.defaults.organization.as_deref(),
+            )?,
+        };
+        let facet_type = match facet_type {
+            Some(f) => f,
+            None => choose(
+                prompter,
+                Question::new("--facet", "Select a facet to roll out"),
+                SupportedFacetType::VARIANTS
+                    .iter()
+                    .filter_map(|v| SupportedFacetType::from_str(v).ok())
+                    .filter(FacetRegistry::is_implemented)
+                    .collect(),
+                None,
+            )?,
+        };
+        let rollout = Org::rollout_preview(
+            config,
+            project_service,
+            organization,
+            facet_type,
+            canary_size,
+            batch_size,
+        )
+        .await?;
+        if rollout.waves.is_empty() {
+            return Err(SkootError::from(format!(
+                "No projects in {} to roll out to",
+                rollout.organization
+            )));
+        }
+        if !yes {
+            eprint!("{}", Org::describe_rollout(&rollout));
+            let confirm = prompter.confirm(
+                Question::new(
+                    "--yes",
+                    &format!(
+                        "Roll out {} to {} in {} waves?",
+                        rollout.facet_type,
+                        rollout.organization,
+                        rollout.waves.len()
+                    ),
+                ),
+                false,
+            )?;
+            if !confirm {
+                return Err(SkootError::from("Rollout cancelled"));
+            }
+        }
+        Org::save_rollout(&rollout)?;
+        Org::apply_rollout(config, prompter, project_service, rollout, yes).await
+    }
+
+    /// Resumes a paused rollout from the first wave that hasn't completed. Projects that failed to update are
+    /// retried.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there's no rollout to resume or the rollout state can't be saved.
+    pub async fn rollout_resume<T: ProjectService + ?Sized>(
+        config: &Config,
+        prompter: &dyn Prompter,
+        project_service: &T,
+        yes: bool,
+    ) -> Result<Rollout, SkootError> {
+        let rollout = Org::load_rollout()?;
+        if rollout.status == RolloutStatus::Completed {
+            return Err(SkootError::from("The rollout has already completed"));
+        }
+        if !yes {
+            prompter.require(&["--yes"])?;
+        }
+        Org::apply_rollout(config, prompter, project_service, rollout, yes).await
+    }
+
+    /// Returns the state of the current rollout.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there's no rollout.
+    pub fn rollout_status() -> Result<Rollout, SkootError> {
+        Org::load_rollout()
+    }
+
+    /// Applies the waves of a rollout until every wave is complete. The rollout is paused if a project fails to
+    /// update or, unless `yes` is set, if the user declines to continue after a wave.
+    async fn apply_rollout<T: ProjectService + ?Sized>(
+        config: &Config,
+        prompter: &dyn Prompter,
+        project_service: &T,
+        mut rollout: Rollout,
+        yes: bool,
+    ) -> Result<Rollout, SkootError> {
+        while let Some(wave_index) = rollout.next_wave() {
+            rollout.status = RolloutStatus::InProgress;
+            Org::save_rollout(&rollout)?;
+            info!(
+                "Applying wave {} of {}",
+                wave_index + 1,
+                rollout.waves.len()
+            );
+            for i in 0..rollout.waves[wave_index].projects.len() {
+                if rollout.waves[wave_index].projects[i].status == RolloutProjectStatus::Updated {
+                    continue;
+                }
+                let project_url = rollout.waves[wave_index].projects[i].project_url.clone();
+                let status = match Org::update_project(
+                    config,
+                    prompter,
+                    project_service,
+                    &project_url,
+                    &rollout,
+                )
+                .await
+                {
+                    Ok(()) => RolloutProjectStatus::Updated,
+                    Err(e) => {
+                        error!("Failed to roll out to {project_url}: {e}");
+                        RolloutProjectStatus::Failed {
+                            error: e.to_string(),
+                        }
+                    }
+                };
+                rollout.waves[wave_index].projects[i].status = status;
+                // Saved after every project so an interrupted rollout resumes where it left off.
+                Org::save_rollout(&rollout)?;
+            }
+            if !rollout.waves[wave_index].is_complete() {
+                error!(
+                    "Wave {} failed, pausing the rollout. Fix the failed projects and resume it.",
+                    wave_index + 1
+                );
+                rollout.status = RolloutStatus::Paused;
+                Org::save_rollout(&rollout)?;
+                return Ok(rollout);
+            }
+            if rollout.next_wave().is_some()
+                && !yes
+                && !prompter.confirm(
+                    Question::new(
+                        "--yes",
+                        &format!(
+                            "Wave {} of {} complete. Continue with the next wave?",
+                            wave_index + 1,
+                            rollout.waves.len()
+                        ),
+                    ),
+                    true,
+                )?
+            {
+                rollout.status = RolloutStatus::Paused;
+                Org::save_rollout(&rollout)?;
+                return Ok(rollout);
+            }
+        }
+        rollout.status = RolloutStatus::Completed;
+        Org::save_rollout(&rollout)?;
+        Ok(rollout)
+    }
+
+    async fn update_project<T: ProjectService + ?Sized>(
+        config: &Config,
+        prompter: &dyn Prompter,
+        project_service: &T,
+        project_url: &str,
+        rollout: &Rollout,
+    ) -> Result<(), SkootError> {
+        let initialized_project = project_service
+            .get(ProjectGetParams {
+                project_url: project_url.to_string(),
+                read_strategy: ReadStrategy::default(),
+            })
+            .await?;
+        Project::update(
+            config,
+            prompter,
+            project_service,
+            Some(ProjectUpdateParams {
+                initialized_project,
+                facet_types: Some(vec![rollout.facet_type.clone()]),
+            }),
+        )
+        .await?;
+        Ok(())
+    }
+
+    fn describe_rollout(rollout: &Rollout) -> String {
+        let mut description = String::new();
+        for (i, wave) in rollout.waves.iter().enumerate() {
+            let name = if i == 0 { " (canary)" } else { "" };
+            description.push_str(&format!("Wave {}{name}:\n", i + 1));
+            for project in &wave.projects {
+                let change = match project.change {
+                    RolloutChange::Add => "add",
+                    RolloutChange::Update => "update",
+                };
+                description.push_str(&format!(
+                    "  {change} {} in {}\n",
+                    rollout.facet_type, project.project_url
+                ));
+            }
+        }
+        description
+    }
+
+    fn load_rollout() -> Result<Rollout, SkootError> {
+        let rollout = std::fs::read_to_string(ROLLOUT_STATE_PATH)
+            .map_err(|e| SkootError::from(format!("No rollout found: {e}")))?;
+        Ok(serde_json::from_str(&rollout)?)
+    }
+
+    fn save_rollout(rollout: &Rollout) -> Result<(), SkootError> {
+        std::fs::write(ROLLOUT_STATE_PATH, serde_json::to_string(rollout)?)?;
+        Ok(())
+    }
+}
+
+/// Where `skootrs init` suggests cloning projects into.
+fn default_local_project_path() -> String {
+    std::env::var("HOME").map_or_else(
+        |_| "./skootrs".to_string(),
+        |home| format!("{home}/skootrs"),
+    )
+}
+
+/// Returns the index of the `default` option, if there is one.
+fn position(options: &[String], default: Option<&str>) -> Option<usize> {
+    default.and_then(|default| options.iter().position(|option| option == default))
+}
+
+/// Returns the login of the authenticated Github user and the organizations they are a member of.
+async fn github_owners(config: &Config) -> Result<(String, Vec<String>), SkootError> {
+    let client = GithubAuth::from_config(config).client()?;
+    let user = client
+        .current()
+        .user()
+        .await
+        .map_err(SkootError::github)?
+        .login;
+    let first_page = client
+        .current()
+        .list_org_memberships_for_authenticated_user()
+        .per_page(100)
+        .send()
+        .await
+        .map_err(SkootError::github)?;
+    let memberships = pagination::all_pages(&client, first_page).await?;
+    let organizations = memberships
+        .into_iter()
+        .map(|m| m.organization.login)
+        .collect();
+    Ok((user, organizations))
+}
+
+/// The config `skootrs init` writes. Only the options that are set up are written, so everything else keeps
+/// falling back to its default.
+#[derive(Serialize)]
+struct InitConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    github_token: Option<String>,
+    local_project_path: String,
+    telemetry: bool,
+    defaults: ProjectDefaults,
+}
+
+/// The result of one of the checks `skootrs config doctor` runs.
+#[derive(Serialize, Debug)]
+pub struct SetupCheck {
+    /// What was checked, e.g. `github`.
+    pub name: &'static str,
+    /// Whether the check passed.
+    pub passed: bool,
+    /// What was found, or why the check failed.
+    pub detail: String,
+}
+
+impl SetupCheck {
+    fn new(name: &'static str, result: Result<String, SkootError>) -> Self {
+        match result {
+            Ok(detail) => Self {
+                name,
+                passed: true,
+                detail,
+            },
+            // Octocrab errors carry a backtrace after the message, which is just noise here.
+            Err(error) => Self {
+                name,
+                passed: false,
+                detail: error
+                    .to_string()
+                    .lines()
+                    .next()
+                    .unwrap_or_default()
+                    .to_string(),
+            },
+        }
+    }
+}
+
+/// The options for `skootrs init`. Any option that is `None` is prompted for.
+#[derive(Debug, Default)]
+pub struct InitParams {
+    /// A file to read the Github token from.
+    pub github_token_file: Option<String>,
+    /// The Github organization or user new projects are created in by default.
+    pub organization: Option<String>,
+    /// The directory projects are cloned into.
+    pub local_project_path: Option<String>,
+    /// Whether traces are exported.
+    pub telemetry: Option<bool>,
+    /// The ecosystem new projects are initialized with by default.
+    pub ecosystem: Option<SupportedEcosystems>,
+    /// Use the defaults for anything that isn't provided instead of prompting for it.
+    pub yes: bool,
+}
+
+pub struct Setup;
+
+impl Setup {
+    /// Writes a config file to `path` for a first-time user. Any option that isn't given is prompted for,
+    /// unless `params.yes` is set or prompting isn't possible, in which case it falls back to its default.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the token file doesn't exist, a prompt fails, or the config can't be written.
+    pub fn init(prompter: &dyn Prompter, path: &str, params: InitParams) -> Result<(), SkootError> {
+        let InitParams {
+            github_token_file,
+            organization,
+            local_project_path,
+            telemetry,
+            ecosystem,
+            yes,
+        } = params;
+        let github_token_file = match github_token_file {
+            Some(file) => Some(file),
+            None if yes => None,
+            None => prompter
+                .text_skippable(
+                    Question::new(
+                        "--github-token-file",
+                        "A file to read the Github token from",
+                    )
+                    .with_help("Leave empty to use the GITHUB_TOKEN environment variable"),
+                    None,
+                )?
+                .filter(|file| !file.trim().is_empty()),
+        };
+        // The file is read relative to wherever Skootrs runs from, so it's written as an absolute path.
+        let github_token = github_token_file
+            .map(|file| {
+                std::fs::canonicalize(&file)
+                    .map(|file| format!("file:{}", file.display()))
+                    .map_err(|e| format!("Failed to find the token file {file}: {e}"))
+            })
+            .transpose()?;
+        let organization = match organization {
+            Some(organization) => Some(organization),
+            None if yes => None,
+            None => prompter
+                .text_skippable(
+                    Question::new(
+                        "--organization",
+                        "The Github organization or user to create projects in by default",
+                    )
+                    .with_help("Leave empty to choose one for each project"),
+                    None,
+                )?
+                .filter(|organization| !organization.trim().is_empty()),
+        };
+        let local_project_path = match local_project_path {
+            Some(path) => path,
+            None if yes => default_local_project_path(),
+            None => prompter.text(
+                Question::new(
+                    "--local-project-path",
+                    "The directory to clone projects into",
+                ),
+                Some(&default_local_project_path()),
+            )?,
+        };
+        let telemetry = match telemetry {
+            Some(telemetry) => telemetry,
+            None if yes => false,
+            None => prompter
+                .confirm_skippable(
+                    Question::new("--telemetry", "Export traces to a local Jaeger?"),
+                    false,
+                )?
+                .unwrap_or_default(),
+        };
+        let ecosystem = match ecosystem {
+            Some(ecosystem) => Some(ecosystem),
+            None if yes => None,
+            None => {
+                let ecosystems = SupportedEcosystems::VARIANTS
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>();
+                prompter
+                    .select_skippable(
+                        Question::new("--ecosystem", "The language new projects use by default")
+                            .with_help("Press escape to choose one for each project"),
+                        &ecosystems,
+                        None,
+                    )?
+                    .map(|i| SupportedEcosystems::from_str(&ecosystems[i]))
+                    .transpose()
+                    .map_err(SkootError::other)?
+            }
+        };
+
+        let config = InitConfig {
+            github_token,
+            local_project_path,
+            telemetry,
+            defaults: ProjectDefaults {
+                organization,
+                ecosystem,
+            },
+        };
+        std::fs::write(
+            path,
+            format!(
+                "# Written by `skootrs init`. See the README for the other options.\n{}",
+                serde_yaml::to_string(&config).map_err(SkootError::serialization)?
+            ),
+        )
+        .map_err(|e| format!("Failed to write the config {path}: {e}"))?;
+        Ok(())
+    }
+
+    /// Sets a config option in the config file at `path`, which has to be one of the files the config is
+    /// loaded from. The value is parsed as YAML. The file is only written if the config still loads with the
+    /// change and the new value passes its checks, e.g. that a path exists or a URL parses. Returns the
+    /// values at the key as they're loaded with the change.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key isn't a config option, the value is invalid, or the file can't be read or
+    /// written.
+    pub fn set_config(
+        files: &[ConfigFile],
+        path: &std::path::Path,
+        key: &str,
+        value: &str,
+        overrides: &[String],
+    ) -> Result<Vec<ConfigEntry>, SkootError> {
+        let mut layers = ConfigLayer::read(files)?;
+        let layer = layers
+            .iter_mut()
+            .find(|layer| layer.path == path)
+            .ok_or_else(|| format!("{} isn't a config file Skootrs loads", path.display()))?;
+        layer.set(key, value)?;
+        let mut resolver = ConfigResolver::default();
+        let config: Config = resolver.load_layers(&layers, overrides)?;
+        config.validate()?;
+        // Options the config doesn't have are dropped when it's loaded, so this also catches typos.
+        check_values(&config, key)?;
+        let nested = format!("{key}.");
+        let entries = resolver
+            .entries(&config)?
+            .into_iter()
+            .filter(|entry| entry.key == key || entry.key.starts_with(&nested))
+            .collect();
+        if let Some(layer) = layers.iter().find(|layer| layer.path == path) {
+            layer.write()?;
+        }
+        Ok(entries)
+    }
+
+    /// Checks that Skootrs can do its job with the config: the Github credential works, the default
+    /// organization is reachable, git is installed, projects can be cloned into the local project path and
+    /// the state store opens. Every check runs even if an earlier one fails, so all the problems are reported
+    /// at once.
+    pub async fn check(config: &Config) -> Vec<SetupCheck> {
+        let mut checks = Vec::new();
+        let owners = github_owners(config).await;
+        checks.push(SetupCheck::new(
+            "github",
+            owners
+                .as_ref()
+                .map(|(user, _)| format!("Authenticated as {user}"))
+                .map_err(|e| e.to_string().into()),
+        ));
+        if let Some(organization) = &config.defaults.organization {
+            let result = match &owners {
+                Ok((user, organizations))
+                    if user == organization || organizations.contains(organization) =>
+                {
+                    Ok(format!(
+                        "Projects will be created in {organization} by default"
+                    ))
+                }
+                Ok((user, _)) => Err(format!("{user} isn't a member of {organization}").into()),
+                Err(_) => {
+                    Err("Can't check the organization without a working Github credential".into())
+                }
+            };
+            checks.push(SetupCheck::new("organization", result));
+        }
+        checks.push(SetupCheck::new("git", Self::check_git()));
+        checks.push(SetupCheck::new(
+            "local_project_path",
+            Self::check_local_project_path(&config.local_project_path),
+        ));
+        checks.push(SetupCheck::new(
+            "statestore",
+            ConfiguredProjectReferenceCache::open(config)
+                .await
+                .map(|_| "Opened the state store".to_string()),
+        ));
+        checks
+    }
+
+    fn check_git() -> Result<String, SkootError> {
+        let output = std::process::Command::new("git")
+            .arg("--version")
+            .output()
+            .map_err(|e| format!("Failed to run git: {e}"))?;
+        if !output.status.success() {
+            return Err("git --version failed".into());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// Creates the local project path if it doesn't exist yet and checks that files can be written to it.
+    fn check_local_project_path(path: &str) -> Result<String, SkootError> {
+        std::fs::create_dir_all(path).map_err(|e| format!("Failed to create {path}: {e}"))?;
+        let probe = std::path::Path::new(path).join(".skootrs-doctor");
+        std::fs::write(&probe, "").map_err(|e| format!("Can't write to {path}: {e}"))?;
+        std::fs::remove_file(&probe)?;
+        Ok(format!("Projects will be cloned into {path}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, process::Command};
+
+    use serde_json::json;
+    use skootrs_model::skootrs::{
+        InitializedEcosystem, InitializedGithubRepo, InitializedGo, StateStoreConfig,
+    };
+
+    use super::*;
+    use crate::prompt::NonInteractivePrompter;
+
+    /// A `ProjectService` that only creates projects. A project named `broken` fails to be created, and every
+    /// other one is created with its source in a clone under `root`.
+    struct MockProjectService {
+        root: PathBuf,
+    }
+
+    impl ProjectService for MockProjectService {
+        async fn initialize(
+            &self,
+            params: ProjectCreateParams,
+        ) -> Result<InitializedProject, SkootError> {
+            if params.name == "broken" {
+                return Err("Failed to create the repo".into());
+            }
+            Ok(InitializedProject {
+                repo: InitializedRepo::Github(InitializedGithubRepo {
+                    name: params.name.clone(),
+                    organization: GithubUser::User("testuser".to_string()),
+                }),
+                mirror: None,
+                modules: vec![InitializedEcosystem::Go(InitializedGo {
+                    name: params.name.clone(),
+                    host: "github.com/testuser".to_string(),
+                })
+                .into()],
+                source: InitializedSource {
+                    path: self.root.join(&params.name).to_string_lossy().to_string(),
+                },
+                facets: HashMap::new(),
+                name: params.name,
+                skootrs_version: None,
+                created_at: None,
+            })
+        }
+
+        async fn plan(&self, _params: ProjectCreateParams) -> Result<ProjectPlan, SkootError> {
+            unimplemented!()
+        }
+
+        async fn get(&self, _params: ProjectGetParams) -> Result<InitializedProject, SkootError> {
+            unimplemented!()
+        }
+
+        async fn get_facet_with_content(
+            &self,
+            _params: FacetGetParams,
+        ) -> Result<InitializedFacet, SkootError> {
+            unimplemented!()
+        }
+
+        async fn list_facets(
+            &self,
+            _params: FacetListParams,
+        ) -> Result<Vec<FacetMapKey>, SkootError> {
+            unimplemented!()
+        }
+
+        async fn outputs_list(
+            &self,
+            _params: ProjectOutputsListParams,
+        ) -> Result<Vec<ProjectOutputReference>, SkootError> {
+            unimplemented!()
+        }
+
+        async fn output_get(
+            &self,
+            _params: ProjectOutputGetParams,
+        ) -> Result<ProjectOutput, SkootError> {
+            unimplemented!()
+        }
+
+        async fn outputs_download(
+            &self,
+            _params: ProjectOutputsDownloadParams,
+        ) -> Result<Vec<DownloadedOutput>, SkootError> {
+            unimplemented!()
+        }
+
+        async fn output_verify(
+            &self,
+            _params: AttestationVerifyParams,
+        ) -> Result<AttestationVerification, SkootError> {
+            unimplemented!()
+        }
+
+        async fn update(
+            &self,
+            _params: ProjectUpdateParams,
+        ) -> Result<InitializedProject, SkootError> {
+            unimplemented!()
+        }
+
+        async fn add_facet(
+            &self,
+            _params: FacetAddParams,
+        ) -> Result<InitializedProject, SkootError> {
+            unimplemented!()
+        }
+
+        async fn preview_facet(
+            &self,
+            _params: FacetPreviewParams,
+        ) -> Result<Vec<SourceFileContent>, SkootError> {
+            unimplemented!()
+        }
+
+        async fn diff_facet(&self, _params: FacetDiffParams) -> Result<FacetDiff, SkootError> {
+            unimplemented!()
+        }
+
+        async fn delete_facet(
+            &self,
+            _params: FacetDeleteParams,
+        ) -> Result<InitializedProject, SkootError> {
+            unimplemented!()
+        }
+
+        async fn get_facet_history(
+            &self,
+            _params: FacetHistoryGetParams,
+        ) -> Result<FacetHistoryEntry, SkootError> {
+            unimplemented!()
+        }
+
+        async fn audit(&self, _params: ProjectGetParams) -> Result<ProjectAudit, SkootError> {
+            unimplemented!()
+        }
+
+        async fn verify_state(
+            &self,
+            _source: InitializedSource,
+        ) -> Result<ProjectAudit, SkootError> {
+            unimplemented!()
+        }
+
+        async fn link_mirror(
+            &self,
+            _params: ProjectMirrorParams,
+        ) -> Result<InitializedProject, SkootError> {
+            unimplemented!()
+        }
+
+        async fn relocate(
+            &self,
+            _params: ProjectRelocateParams,
+        ) -> Result<ProjectRelocation, SkootError> {
+            unimplemented!()
+        }
+
+        async fn archive(
+            &self,
+            _params: ProjectArchiveParams,
+        ) -> Result<ProjectArchive, SkootError> {
+            unimplemented!()
+        }
+
+        async fn delete(&self, _params: ProjectDeleteParams) -> Result<String, SkootError> {
+            unimplemented!()
+        }
+    }
+
+    fn git(args: &[&str], dir: &Path) {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {args:?} failed");
+    }
+
+    /// Clones a new remote with a single commit to `root/name`, so the project's state can be pushed to it.
+    fn cloned_remote(root: &Path, name: &str) {
+        let seed = root.join(format!("{name}-seed"));
+        std::fs::create_dir_all(&seed).unwrap();
+        git(&["init", "--initial-branch=main"], &seed);
+        git(
+            &[
+                "-c",
+                "user.name=test",
+                "-c",
+                "user.email=test@example.com",
+                "commit",
+                "--allow-empty",
+                "-m",
+                "init",
+            ],
+            &seed,
+        );
+        let remote = format!("{name}.git");
+        git(&["clone", "--bare", seed.to_str().unwrap(), &remote], root);
+        git(&["clone", &remote, name], root);
+        git(&["config", "user.name", "test"], &root.join(name));
+        git(
+            &["config", "user.email", "test@example.com"],
+            &root.join(name),
+        );
+    }
+
+    fn create_params(name: &str) -> ProjectCreateParams {
+        ProjectCreateParams {
+            name: name.to_string(),
+            repo_params: RepoCreateParams::Github(GithubRepoParams {
+                name: name.to_string(),
+                description: String::new(),
+                organization: GithubUser::User("testuser".to_string()),
+                license: None,
+            }),
+            ecosystem_params: vec![EcosystemInitializeParams::Go(GoParams {
+                name: name.to_string(),
+                host: "github.com/testuser".to_string(),
+                go_version: None,
+            })
+            .into()],
+            source_params: SourceInitializeParams {
+                parent_path: "/tmp".to_string(),
+            },
+            rollback_on_failure: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_batch() {
+        let root =
+            std::env::temp_dir().join(format!("skootrs-create-batch-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        cloned_remote(&root, "first");
+        cloned_remote(&root, "second");
+        let config = Config {
+            statestore: StateStoreConfig::Json {
+                path: root.join("cache.json").to_string_lossy().to_string(),
+                ttl_secs: 300,
+            },
+            ..Default::default()
+        };
+        let project_service = MockProjectService { root: root.clone() };
+        let params = ProjectBatchCreateParams {
+            projects: vec![
+                create_params("first"),
+                create_params("broken"),
+                create_params("second"),
+            ],
+        };
+
+        assert!(
+            Project::create_batch(&config, &project_service, params.clone(), 0, false)
+                .await
+                .is_err()
+        );
+
+        // The broken project doesn't stop the ones after it from being created.
+        let batch = Project::create_batch(&config, &project_service, params, 2, false)
+            .await
+            .unwrap();
+        let mut created = batch
+            .created
+            .iter()
+            .map(|project| project.name.as_str())
+            .collect::<Vec<_>>();
+        created.sort_unstable();
+        assert_eq!(created, vec!["first", "second"]);
+        assert_eq!(batch.failed.len(), 1);
+        assert_eq!(batch.failed[0].name, "broken");
+        assert_eq!(batch.failed[0].error, "Failed to create the repo");
+        for name in ["first", "second"] {
+            assert!(root.join(name).join(".skootrs").exists());
+        }
+        let cache = std::fs::read_to_string(root.join("cache.json")).unwrap();
+        assert!(cache.contains("https://github.com/testuser/first"));
+        assert!(!cache.contains("broken"));
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_prompt_options_non_interactive() {
+        let schema: SchemaObject = serde_json::from_value(json!({
+            "type": "object",
+            "required": ["enforcement"],
+            "properties": {
+                "enforcement": { "enum": ["active", "evaluate"], "default": "evaluate" },
+                "required_approvals": { "type": "integer", "default": 1 },
+            },
+        }))
+        .unwrap();
+        let options = Facet::prompt_options(&NonInteractivePrompter, &schema).unwrap();
+        assert_eq!(
+            options,
+            BTreeMap::from([("enforcement".to_string(), json!("evaluate"))])
+        );
+
+        let schema: SchemaObject = serde_json::from_value(json!({
+            "type": "object",
+            "required": ["branch"],
+            "properties": { "branch": { "type": "string" } },
+        }))
+        .unwrap();
+        let error = Facet::prompt_options(&NonInteractivePrompter, &schema).unwrap_err();
+        assert!(error.to_string().starts_with("Missing branch,"));
+    }
+}
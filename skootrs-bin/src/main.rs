@@ -26,28 +26,55 @@
 //! giving an interactive prompt to the user to fill in the required
 //! information.
 
+pub mod dashboard;
+pub mod format;
 pub mod helpers;
+pub mod prompt;
+pub mod telemetry;
 
-use std::io::stdout;
+use std::io::{stdout, Write};
+use std::net::{Ipv4Addr, SocketAddr};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
 
-use clap::{Parser, Subcommand};
+use chrono::{DateTime, Utc};
+use clap::{Args, Parser, Subcommand};
 use clio::Input;
+use skootrs_lib::service::audit_log::{AuditLog, AuditedProjectService};
+use skootrs_lib::service::cancellation;
+use skootrs_lib::service::config::{ConfigFile, ConfigResolver};
 use skootrs_lib::service::ecosystem::LocalEcosystemService;
+use skootrs_lib::service::events::EventEmitter;
 use skootrs_lib::service::facet::LocalFacetService;
+use skootrs_lib::service::git2_source::GitCredentials;
+use skootrs_lib::service::github_auth::GithubAuth;
+use skootrs_lib::service::insights::LocalInsightsService;
 use skootrs_lib::service::output::LocalOutputService;
+use skootrs_lib::service::pinning::ActionPinner;
+use skootrs_lib::service::policy::LocalPolicyService;
+use skootrs_lib::service::profile;
 use skootrs_lib::service::project::LocalProjectService;
-use skootrs_lib::service::repo::LocalRepoService;
-use skootrs_lib::service::source::LocalSourceService;
-use skootrs_model::skootrs::SkootError;
+use skootrs_lib::service::repo::{ContentCache, LocalRepoService};
+use skootrs_lib::service::scorecard::LocalScorecardService;
+use skootrs_lib::service::source::ConfiguredSourceService;
+use skootrs_lib::service::template::{TemplateOverrides, TemplateVariables};
+use skootrs_model::skootrs::{
+    audit_log::{AuditLogFilter, AuditOperation},
+    compliance::ComplianceFormat,
+    facet::SupportedFacetType,
+    rollout::RolloutParams,
+    scorecard::ScorecardCheckStatus,
+    ApiKeyScope, Config, ReadStrategy, SkootError, SupportedEcosystems,
+};
+use skootrs_statestore::InMemoryProjectReferenceCache;
 
-use helpers::{Facet, HandleResponseOutput, Output};
-use opentelemetry::global;
-use opentelemetry_sdk::propagation::TraceContextPropagator;
+use format::OutputFormat;
+use helpers::{Facet, HandleResponseOutput, Org, Output};
 use serde::de::DeserializeOwned;
-use tracing::error;
-use tracing_bunyan_formatter::{BunyanFormattingLayer, JsonStorageLayer};
-use tracing_subscriber::layer::SubscriberExt;
-use tracing_subscriber::{EnvFilter, Registry};
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
 
 /// Skootrs is a CLI tool for creating and managing secure-by-default projects.
 /// The commands are  using noun-verb syntax. So the commands are structured like:
@@ -59,7 +86,48 @@ use tracing_subscriber::{EnvFilter, Registry};
 #[derive(Parser)]
 #[command(name = "skootrs")]
 #[command(bin_name = "skootrs")]
+struct Cli {
+    /// Stop the command if it doesn't complete within this many seconds. Time spent at interactive prompts
+    /// counts towards the timeout. Pressing Ctrl-C stops the command the same way.
+    #[clap(long, global = true, value_name = "SECONDS")]
+    timeout: Option<u64>,
+
+    /// Refuse every operation that would change a repo, a local clone or a Github setting, so commands like
+    /// `get`, `list`, `audit` and `verify` can be run against a production org without any risk. The same as
+    /// setting `read_only` in the config.
+    #[clap(long, global = true)]
+    read_only: bool,
+
+    /// The format the result of the command is written in. JSON and YAML are meant for scripts, and tables
+    /// for reading.
+    #[clap(long, global = true, value_enum, default_value_t = OutputFormat::Json)]
+    output: OutputFormat,
+
+    /// Never prompt. A command that's missing required input fails with an error listing what's missing
+    /// instead, and anything optional falls back to its default. Confirmations have to be given with `--yes`.
+    #[clap(long, global = true, env = "SKOOTRS_NON_INTERACTIVE")]
+    non_interactive: bool,
+
+    /// Override a config option, e.g. `--config defaults.organization=kusaridev`. The value is parsed as YAML.
+    /// Overrides take precedence over the config files and the `SKOOTRS_*` environment variables, and can be
+    /// repeated.
+    #[clap(long = "config", global = true, value_name = "KEY=VALUE")]
+    config_overrides: Vec<String>,
+
+    #[clap(subcommand)]
+    command: SkootrsCli,
+}
+
+/// This is the enum for what nouns the `skootrs` command can take.
+#[derive(Subcommand)]
 enum SkootrsCli {
+    /// Set Skootrs up for the first time. Writes the config file and checks the setup works.
+    #[command(name = "init")]
+    Init {
+        #[clap(flatten)]
+        init: InitArgs,
+    },
+
     /// Project commands.
     #[command(name = "project")]
     Project {
@@ -81,6 +149,39 @@ enum SkootrsCli {
         output: OutputCommands,
     },
 
+    /// Organization commands.
+    #[command(name = "org")]
+    Org {
+        #[clap(subcommand)]
+        org: OrgCommands,
+    },
+
+    /// Commands meant to run in a project's own CI.
+    #[command(name = "ci")]
+    Ci {
+        #[clap(subcommand)]
+        ci: CiCommands,
+    },
+
+    /// Config commands.
+    #[command(name = "config")]
+    Config {
+        #[clap(subcommand)]
+        config: ConfigCommands,
+    },
+
+    /// Manage the projects known to the local Skootrs in a terminal dashboard, which shows which facets
+    /// drifted and audits, updates and archives the selected project.
+    #[command(name = "dashboard")]
+    Dashboard,
+
+    /// Audit log commands.
+    #[command(name = "audit-log")]
+    AuditLog {
+        #[clap(subcommand)]
+        audit_log: AuditLogCommands,
+    },
+
     /// Daemon commands.
     #[command(name = "daemon")]
     Daemon {
@@ -95,11 +196,42 @@ enum ProjectCommands {
     /// Create a new project.
     #[command(name = "create")]
     Create {
+        /// Print the plan for creating the project, with the API calls, commands, files and pushes it would
+        /// make, without creating it.
+        #[clap(long)]
+        dry_run: bool,
+
+        /// Delete the repo and local clone again if creating the project fails partway through, and report
+        /// what was rolled back. They are left in place by default so the failure can be looked into.
+        #[clap(long)]
+        rollback_on_failure: bool,
+
+        /// The profile to create the project with, e.g. `strict-oss`. A profile turns facets on or off and
+        /// sets the license, branch protection rules and defaults on top of the config.
+        #[clap(long)]
+        profile: Option<String>,
+
         /// This is an optional input parameter that can be used to pass in a file, pipe, url, or stdin.
         /// This is expected to be YAML or JSON. If it is not provided, the CLI will prompt the user for the input.
         #[clap(value_parser)]
         input: Option<Input>,
     },
+    /// Create every project in a manifest, reporting which were created and which failed.
+    #[command(name = "create-batch")]
+    CreateBatch {
+        /// The number of projects created at once.
+        #[clap(long, default_value_t = 4)]
+        parallelism: usize,
+
+        /// Delete the repo and local clone of a project again if creating it fails partway through.
+        #[clap(long)]
+        rollback_on_failure: bool,
+
+        /// The manifest of the projects to create, with a `projects` list of the same parameters `project create`
+        /// takes. This can be a file, pipe, url, or stdin and is expected to be YAML or JSON.
+        #[clap(value_parser)]
+        input: Input,
+    },
     /// Get the metadata for a particular project.
     #[command(name = "get")]
     Get {
@@ -107,6 +239,26 @@ enum ProjectCommands {
         /// This is expected to be YAML or JSON. If it is not provided, the CLI will prompt the user for the input.
         #[clap(value_parser)]
         input: Option<Input>,
+
+        /// Where the project's state is read from: `remote` through the repo host's API, `local` from the
+        /// project's clone under `local_project_path` after pulling it, or `auto` from the clone if there is
+        /// one. Overrides the `read_strategy` of the input.
+        #[clap(long)]
+        read: Option<ReadStrategy>,
+    },
+
+    /// Get the metadata for a particular project along with optional live statistics about its repo.
+    #[command(name = "info")]
+    Info {
+        /// Include live repo statistics like the last commit, open security alerts, and workflow success
+        /// rates over the last 30 days.
+        #[clap(long)]
+        stats: bool,
+
+        /// This is an optional input parameter that can be used to pass in a file, pipe, url, or stdin.
+        /// This is expected to be YAML or JSON. If it is not provided, the CLI will prompt the user for the input.
+        #[clap(value_parser)]
+        input: Option<Input>,
     },
 
     /// Update a project.
@@ -118,9 +270,89 @@ enum ProjectCommands {
         input: Option<Input>,
     },
 
+    /// Link a mirror on another host to a project, e.g. a Github mirror of a project hosted elsewhere.
+    #[command(name = "mirror")]
+    Mirror {
+        /// This is an optional input parameter that can be used to pass in a file, pipe, url, or stdin.
+        /// This is expected to be YAML or JSON. If it is not provided, the CLI will prompt the user for the input.
+        #[clap(value_parser)]
+        input: Option<Input>,
+    },
+
+    /// Record a project under the new URL of its repo after the repo was renamed or transferred, e.g. to
+    /// another organization. The project's new state is proposed as a pull request or pushed according to
+    /// `change_requests`, the local Skootrs is updated, and the old and new URLs are printed.
+    #[command(name = "relocate")]
+    Relocate {
+        /// This is an optional input parameter that can be used to pass in a file, pipe, url, or stdin.
+        /// This is expected to be YAML or JSON. If it is not provided, the CLI will prompt the user for the input.
+        #[clap(value_parser)]
+        input: Option<Input>,
+    },
+
+    /// Audit a project's facets against what's live in its repo.
+    #[command(name = "audit")]
+    Audit {
+        /// This is an optional input parameter that can be used to pass in a file, pipe, url, or stdin.
+        /// This is expected to be YAML or JSON. If it is not provided, the CLI will prompt the user for the input.
+        #[clap(value_parser)]
+        input: Option<Input>,
+    },
+
+    /// Summarize a project on one page: its facets, its branch protection, what's attached to its latest
+    /// release and which facets drifted.
+    #[command(name = "status")]
+    Status {
+        /// This is an optional input parameter that can be used to pass in a file, pipe, url, or stdin.
+        /// This is expected to be YAML or JSON. If it is not provided, the CLI will prompt the user for the input.
+        #[clap(value_parser)]
+        input: Option<Input>,
+    },
+
+    /// Report a project's live OpenSSF Scorecard results, along with the facets that address each check that
+    /// isn't passing.
+    #[command(name = "scorecard")]
+    Scorecard {
+        /// This is an optional input parameter that can be used to pass in a file, pipe, url, or stdin.
+        /// This is expected to be YAML or JSON. If it is not provided, the CLI will prompt the user for the input.
+        #[clap(value_parser)]
+        input: Option<Input>,
+    },
+
+    /// Evaluate a project against a Rego or CUE policy bundle, e.g. the facets, branch protection and release
+    /// outputs every project of an organization needs. Exits with an error if the project violates the
+    /// policies, so it can gate CI.
+    #[command(name = "evaluate")]
+    Evaluate {
+        /// The policy bundle, a `.rego` or `.cue` file or a directory of them. Rego policies define a `deny`
+        /// set of messages in the `skootrs` package, CUE policies are constraints the project has to unify
+        /// with.
+        #[clap(long)]
+        policy: String,
+
+        /// This is an optional input parameter that can be used to pass in a file, pipe, url, or stdin.
+        /// This is expected to be YAML or JSON. If it is not provided, the CLI will prompt the user for the input.
+        #[clap(value_parser)]
+        input: Option<Input>,
+    },
+
     /// Archive a project.
     #[command(name = "archive")]
     Archive {
+        /// Delete the repo's Actions artifacts and caches and disable its scheduled workflows before archiving
+        /// it, so the archived repo stops using runner minutes and storage.
+        #[clap(long)]
+        cleanup: bool,
+
+        /// This is an optional input parameter that can be used to pass in a file, pipe, url, or stdin.
+        /// This is expected to be YAML or JSON. If it is not provided, the CLI will prompt the user for the input.
+        #[clap(value_parser)]
+        input: Option<Input>,
+    },
+
+    /// Permanently delete a project, including its repo and local clone.
+    #[command(name = "delete")]
+    Delete {
         /// This is an optional input parameter that can be used to pass in a file, pipe, url, or stdin.
         /// This is expected to be YAML or JSON. If it is not provided, the CLI will prompt the user for the input.
         #[clap(value_parser)]
@@ -130,11 +362,73 @@ enum ProjectCommands {
     /// List all the projects known to the local Skootrs
     #[command(name = "list")]
     List,
+
+    /// Search the projects known to the local Skootrs for the ones matching every word of a query, by their
+    /// repo URL, name, organization, ecosystems and facet types, e.g. `payments go`.
+    #[command(name = "search")]
+    Search {
+        /// The words to search for, matched case insensitively.
+        query: String,
+
+        /// Fetch each project's state from its repo before matching it. Without it, the JSON state store
+        /// only matches projects by their repo URL, and `SurrealDB` matches the copies it last stored.
+        #[clap(long)]
+        refresh: bool,
+    },
+
+    /// Fetch the state of a project known to the local Skootrs, or of every one, from its repo again instead
+    /// of reusing the cached state. Projects whose repos don't exist anymore are forgotten, and the new URLs
+    /// of Github repos that were renamed or transferred are reported.
+    #[command(name = "refresh")]
+    Refresh {
+        /// The URL of the project's repo. Every project is refreshed if it isn't given.
+        project_url: Option<String>,
+    },
+
+    /// Report the facet coverage and drift across all the projects known to the local Skootrs, or a single project's
+    /// compliance report with `--format`.
+    #[command(name = "report")]
+    Report {
+        /// Output the report in the Prometheus text exposition format instead of JSON.
+        #[clap(long)]
+        prometheus: bool,
+
+        /// The URL of a Prometheus Pushgateway to push the report's metrics to.
+        #[clap(long)]
+        push_gateway: Option<String>,
+
+        /// Report a single project's compliance with the S2C2F, SLSA and Scorecard controls its facets
+        /// implement instead, as an OSCAL component definition (`oscal`) or a Markdown document (`markdown`).
+        #[clap(long, conflicts_with_all = ["prometheus", "push_gateway"])]
+        format: Option<ComplianceFormat>,
+
+        /// The project to report on with `--format`. This is an optional input parameter that can be used to
+        /// pass in a file, pipe, url, or stdin. This is expected to be YAML or JSON. If it is not provided, the
+        /// CLI will prompt the user for the input.
+        #[clap(value_parser, requires = "format")]
+        input: Option<Input>,
+    },
 }
 
 /// This is the enum for what nouns the `facet` command can take.
 #[derive(Subcommand, Debug)]
 enum FacetCommands {
+    /// Add a single facet to an existing project.
+    #[command(name = "add")]
+    Add {
+        /// This is an optional input parameter that can be used to pass in a file, pipe, url, or stdin.
+        /// This is expected to be YAML or JSON. If it is not provided, the CLI will prompt the user for the input.
+        #[clap(value_parser)]
+        input: Option<Input>,
+    },
+    /// Delete a single facet from an existing project.
+    #[command(name = "delete")]
+    Delete {
+        /// This is an optional input parameter that can be used to pass in a file, pipe, url, or stdin.
+        /// This is expected to be YAML or JSON. If it is not provided, the CLI will prompt the user for the input.
+        #[clap(value_parser)]
+        input: Option<Input>,
+    },
     /// Get the data for a facet of a particular project.
     #[command(name = "get")]
     Get {
@@ -142,6 +436,39 @@ enum FacetCommands {
         /// This is expected to be YAML or JSON. If it is not provided, the CLI will prompt the user for the input.
         #[clap(value_parser)]
         input: Option<Input>,
+
+        /// Only offers facets with a label matching this, e.g. `slsa` or `scorecard`. Can be repeated to
+        /// require several labels.
+        #[clap(long = "label")]
+        labels: Vec<String>,
+
+        /// Where the facet's files are read from: `remote` through the repo host's API, `local` from the
+        /// project's clone under `local_project_path` after pulling it, or `auto` from the clone if there is
+        /// one. Overrides the `read_strategy` of the input.
+        #[clap(long)]
+        read: Option<ReadStrategy>,
+    },
+    /// Preview the content a facet would have in a new project, without creating the project or writing
+    /// any files.
+    #[command(name = "preview")]
+    Preview {
+        /// This is an optional input parameter that can be used to pass in a file, pipe, url, or stdin.
+        /// This is expected to be YAML or JSON. If it is not provided, the CLI will prompt the user for the input.
+        #[clap(value_parser)]
+        input: Option<Input>,
+    },
+    /// Show what a project update would change in a facet's files, as a unified diff against the files in the
+    /// project's repo.
+    #[command(name = "diff")]
+    Diff {
+        /// This is an optional input parameter that can be used to pass in a file, pipe, url, or stdin.
+        /// This is expected to be YAML or JSON. If it is not provided, the CLI will prompt the user for the input.
+        #[clap(value_parser)]
+        input: Option<Input>,
+
+        /// Output which files would change in the output format instead of the unified diff.
+        #[clap(long)]
+        summary: bool,
     },
     /// List all the facets that belong to a particular project.
     #[command(name = "list")]
@@ -150,9 +477,140 @@ enum FacetCommands {
         /// This is expected to be YAML or JSON. If it is not provided, the CLI will prompt the user for the input.
         #[clap(value_parser)]
         input: Option<Input>,
+
+        /// Only lists facets with a label matching this, e.g. `slsa` or `build`. Can be repeated to require
+        /// several labels.
+        #[clap(long = "label")]
+        labels: Vec<String>,
+    },
+
+    /// Facet history commands.
+    #[command(name = "history")]
+    History {
+        #[clap(subcommand)]
+        history: FacetHistoryCommands,
+    },
+}
+
+/// This is the enum for what nouns the `facet history` command can take.
+#[derive(Subcommand, Debug)]
+enum FacetHistoryCommands {
+    /// Show a previous version of a facet's content that was replaced by a project update.
+    #[command(name = "show")]
+    Show {
+        /// The type of the facet, e.g. `SLSABuild`.
+        facet: SupportedFacetType,
+
+        /// The version to show. Defaults to the latest version.
+        #[clap(long)]
+        version: Option<u32>,
+
+        /// The URL of the project's repo. If it is not provided, the CLI will prompt the user for the project.
+        #[clap(long)]
+        project: Option<String>,
+    },
+}
+
+/// The arguments for setting Skootrs up. Anything that isn't provided is prompted for, unless `--yes` is
+/// given.
+#[derive(Args, Debug)]
+struct InitArgs {
+    /// A file to read the Github token from. The `GITHUB_TOKEN` environment variable is used if it is not
+    /// provided.
+    #[clap(long)]
+    github_token_file: Option<String>,
+
+    /// The Github organization or user new projects are created in by default.
+    #[clap(long)]
+    organization: Option<String>,
+
+    /// The directory projects are cloned into.
+    #[clap(long)]
+    local_project_path: Option<String>,
+
+    /// Whether traces are exported to a local Jaeger.
+    #[clap(long)]
+    telemetry: Option<bool>,
+
+    /// The ecosystem new projects are initialized with by default, e.g. `Rust`.
+    #[clap(long)]
+    ecosystem: Option<SupportedEcosystems>,
+
+    /// Use the defaults for anything that isn't provided instead of prompting for it.
+    #[clap(long)]
+    yes: bool,
+
+    /// Overwrite the config file if it already exists.
+    #[clap(long)]
+    force: bool,
+}
+
+/// This is the enum for what nouns the `org` command can take.
+#[derive(Subcommand, Debug)]
+enum OrgCommands {
+    /// Roll a facet out across all the projects of an organization in waves.
+    #[command(name = "rollout")]
+    Rollout {
+        #[clap(subcommand)]
+        rollout: RolloutCommands,
     },
 }
 
+/// This is the enum for what nouns the `org rollout` command can take.
+#[derive(Subcommand, Debug)]
+enum RolloutCommands {
+    /// Preview which projects a rollout would change without applying it.
+    #[command(name = "preview")]
+    Preview {
+        #[clap(flatten)]
+        plan: RolloutPlanArgs,
+    },
+
+    /// Start a rollout. The first wave is a canary and the rollout pauses after each wave for confirmation.
+    #[command(name = "start")]
+    Start {
+        #[clap(flatten)]
+        plan: RolloutPlanArgs,
+
+        /// Apply every wave without asking for confirmation.
+        #[clap(long)]
+        yes: bool,
+    },
+
+    /// Resume a paused rollout, retrying any projects that failed.
+    #[command(name = "resume")]
+    Resume {
+        /// Apply every remaining wave without asking for confirmation.
+        #[clap(long)]
+        yes: bool,
+    },
+
+    /// Show the state of the current rollout.
+    #[command(name = "status")]
+    Status,
+}
+
+/// The arguments for planning a rollout.
+#[derive(Args, Debug)]
+struct RolloutPlanArgs {
+    /// The organization to roll out to. If it is not provided, the CLI will prompt the user for it.
+    #[clap(long)]
+    org: Option<String>,
+
+    /// The type of the facet to roll out, e.g. `SLSABuild`. If it is not provided, the CLI will prompt the user
+    /// for it.
+    #[clap(long)]
+    facet: Option<SupportedFacetType>,
+
+    /// The number of projects in the canary wave.
+    #[clap(long, default_value_t = 1)]
+    canary: usize,
+
+    /// The maximum number of projects in each wave after the canary.
+    #[clap(long, default_value_t = 5)]
+    batch_size: usize,
+}
+
 /// This is the enum for what nouns the `output` command can take.
 #[derive(Subcommand, Debug)]
 enum OutputCommands {
@@ -164,7 +622,8 @@ enum OutputCommands {
         #[clap(value_parser)]
         input: Option<Input>,
     },
-    /// List all the release outputs that belong to a particular project.
+    /// List all the release outputs that belong to a particular project, or the artifacts of one of its
+    /// workflow runs if the input sets `workflow_run`.
     #[command(name = "list")]
     List {
         /// This is an optional input parameter that can be used to pass in a file, pipe, url, or stdin.
@@ -172,6 +631,90 @@ enum OutputCommands {
         #[clap(value_parser)]
         input: Option<Input>,
     },
+    /// Download one or all of the release outputs of a particular project to files, checking each against
+    /// the size and checksum the release gives for it.
+    #[command(name = "download")]
+    Download {
+        /// The directory the outputs are written to, each to a file with the output's name.
+        #[clap(long, default_value = ".")]
+        dir: String,
+
+        /// The name of the output to download. Every output of the release is downloaded if it isn't given.
+        #[clap(long)]
+        name: Option<String>,
+
+        /// This is an optional input parameter that can be used to pass in a file, pipe, url, or stdin.
+        /// This is expected to be YAML or JSON with the project and release, like for `output list`. If it is
+        /// not provided, the CLI will prompt the user for the project.
+        #[clap(value_parser)]
+        input: Option<Input>,
+    },
+    /// Verify that an in-toto attestation attached to a release of a particular project is signed by a
+    /// workflow of the project's repo and matches the release. Exits with an error if it doesn't verify.
+    #[command(name = "verify")]
+    Verify {
+        /// This is an optional input parameter that can be used to pass in a file, pipe, url, or stdin.
+        /// This is expected to be YAML or JSON with the project, the attestation's name as
+        /// `project_output` and the release. If it is not provided, the CLI will prompt the user for the
+        /// input.
+        #[clap(value_parser)]
+        input: Option<Input>,
+    },
+}
+
+/// This is the enum for what nouns the `ci` command can take.
+#[derive(Subcommand, Debug)]
+enum CiCommands {
+    /// Verify a checkout of a project against the facet hashes recorded in its `.skootrs` file. Exits with
+    /// an error if any facet has drifted.
+    #[command(name = "verify-state")]
+    VerifyState {
+        /// The path to the checkout of the project.
+        #[clap(long, default_value = ".")]
+        path: String,
+    },
+}
+
+/// This is the enum for what nouns the `config` command can take.
+#[derive(Subcommand, Debug)]
+enum ConfigCommands {
+    /// Print the loaded config as YAML, with the values of environment variable and secret file references
+    /// redacted.
+    #[command(name = "show")]
+    Show,
+
+    /// Print the loaded value of a config option, e.g. `defaults.organization`.
+    #[command(name = "get")]
+    Get {
+        /// The dotted key of the option.
+        key: String,
+    },
+
+    /// Set a config option in the config file, or in the user's own config with `--user`. The value is parsed
+    /// as YAML, and the file is only written if the config still loads and the value passes its checks, e.g.
+    /// that a path exists or a URL parses.
+    #[command(name = "set")]
+    Set {
+        /// The dotted key of the option, e.g. `defaults.organization`.
+        key: String,
+
+        /// The value of the option.
+        value: String,
+
+        /// Set the option in `~/.config/skootrs/config.yaml` instead of the config file.
+        #[clap(long)]
+        user: bool,
+    },
+
+    /// List every value of the loaded config along with where it came from: a config file, an environment
+    /// variable, a `--config` override, or its default.
+    #[command(name = "list")]
+    List,
+
+    /// Check that the loaded config works, e.g. that the Github credential is valid and the local project
+    /// path is writable.
+    #[command(name = "doctor")]
+    Doctor,
 }
 
 /// This is the enum for what nouns the `daemon` command can take.
@@ -179,52 +722,157 @@ enum OutputCommands {
 enum DaemonCommands {
     /// Start the REST server.
     #[command(name = "start")]
-    Start,
+    Start {
+        /// Serve the gRPC API too, on the `daemon.grpc_port` port. It accepts the same API keys as the REST
+        /// API and shares its project store.
+        #[clap(long)]
+        grpc: bool,
+    },
+}
+
+/// This is the enum for what nouns the `audit-log` command can take.
+#[derive(Subcommand, Debug)]
+enum AuditLogCommands {
+    /// List the changes recorded in the audit log, oldest first. The log is checked for entries that were
+    /// edited, removed or reordered first, and nothing is listed if any were.
+    #[command(name = "list")]
+    List {
+        /// Only list changes made by this actor, e.g. `user:alice` or `api_key:ci`.
+        #[clap(long)]
+        actor: Option<String>,
+
+        /// Only list this kind of change, e.g. `project_create` or `facet_add`.
+        #[clap(long)]
+        operation: Option<AuditOperation>,
+
+        /// Only list changes to the project with this repo URL.
+        #[clap(long)]
+        project: Option<String>,
+
+        /// Only list changes made at or after this time, e.g. `2024-05-01T00:00:00Z`.
+        #[clap(long)]
+        since: Option<DateTime<Utc>>,
+    },
+}
+
+/// The environment variable that points to the Skootrs config file.
+const CONFIG_PATH_ENV: &str = "SKOOTRS_CONFIG";
+
+/// The config file that is used when `SKOOTRS_CONFIG` isn't set.
+const DEFAULT_CONFIG_PATH: &str = "./skootrs.yaml";
+
+/// Returns the path of the config file, `SKOOTRS_CONFIG` or `./skootrs.yaml` if that isn't set.
+fn config_path() -> String {
+    std::env::var(CONFIG_PATH_ENV).unwrap_or_else(|_| DEFAULT_CONFIG_PATH.to_string())
+}
+
+/// Loads the config in layers: the user's own `~/.config/skootrs/config.yaml`, the YAML file in
+/// `SKOOTRS_CONFIG` or `./skootrs.yaml` if that isn't set, the `SKOOTRS_*` environment variables, and the
+/// `--config` overrides. Options that none of them set fall back to their defaults. The returned resolver
+/// redacts the values that environment variable and secret file references in the config were expanded to.
+fn load_config(overrides: &[String]) -> Result<(Config, ConfigResolver), SkootError> {
+    let mut resolver = ConfigResolver::default();
+    let files = config_files(&resolver);
+    let config: Config = resolver
+        .load(&files, overrides)
+        .map_err(|e| SkootError::from(format!("Failed to load config: {e}")))?;
+    config.validate()?;
+    Ok((config, resolver))
 }
 
-fn init_tracing() {
-    let app_name = "skootrs";
-
-    // Start a new Jaeger trace pipeline.
-    // Spans are exported in batch - recommended setup for a production application.
-    global::set_text_map_propagator(TraceContextPropagator::new());
-    let tracer = opentelemetry_jaeger::new_agent_pipeline()
-        .with_service_name(app_name)
-        .install_simple()
-        .expect("Failed to install OpenTelemetry tracer.");
-
-    // Filter based on level - trace, debug, info, warn, error
-    // Tunable via `RUST_LOG` env variable
-    let env_filter = EnvFilter::try_from_default_env().unwrap_or(EnvFilter::new("info"));
-    // Create a `tracing` layer using the Jaeger tracer
-    let telemetry = tracing_opentelemetry::layer().with_tracer(tracer);
-    // Create a `tracing` layer to emit spans as structured logs to stdout
-    let formatting_layer = BunyanFormattingLayer::new(app_name.into(), std::io::stdout);
-    // Combined them all together in a `tracing` subscriber
-    let subscriber = Registry::default()
-        .with(env_filter)
-        .with(telemetry)
-        .with(JsonStorageLayer)
-        .with(formatting_layer);
-    tracing::subscriber::set_global_default(subscriber)
-        .expect("Failed to install `tracing` subscriber.");
+/// Returns the files the config is loaded from, the user's own config and then the config file.
+fn config_files(resolver: &ConfigResolver) -> Vec<ConfigFile> {
+    resolver
+        .user_config_path()
+        .map(|path| ConfigFile {
+            path,
+            required: false,
+        })
+        .into_iter()
+        .chain(std::iter::once(ConfigFile {
+            path: config_path().into(),
+            // A config that was pointed to explicitly has to exist.
+            required: std::env::var(CONFIG_PATH_ENV).is_ok(),
+        }))
+        .collect()
+}
+
+/// The project service the CLI runs its commands with. Every change it makes is recorded in the audit log.
+type CliProjectService = AuditedProjectService<
+    LocalProjectService<
+        LocalRepoService,
+        LocalEcosystemService,
+        ConfiguredSourceService,
+        LocalFacetService,
+        LocalOutputService,
+    >,
+>;
+
+/// Returns the config with a profile applied on top of it, and the project service built from it.
+fn with_profile(config: &Config, name: &str) -> Result<(Config, CliProjectService), SkootError> {
+    let config = profile::load(config, name)?.apply(config)?;
+    let project_service = init_project_service(&config)?;
+    Ok((config, project_service))
 }
 
 /// TODO: This probably should be configurable in some way.
-fn init_project_service() -> LocalProjectService<
-    LocalRepoService,
-    LocalEcosystemService,
-    LocalSourceService,
-    LocalFacetService,
-    LocalOutputService,
-> {
-    LocalProjectService {
-        repo_service: LocalRepoService {},
+fn init_project_service(config: &Config) -> Result<CliProjectService, SkootError> {
+    let events = EventEmitter::new(config.event_sinks.clone());
+    let project_service = LocalProjectService {
+        repo_service: LocalRepoService {
+            github_auth: GithubAuth::from_config(config),
+            retry_policy: config.github_retry.clone(),
+            bitbucket: config.bitbucket.clone(),
+            read_only: config.read_only,
+            git_backend: config.git_backend,
+            git_credentials: GitCredentials::from_config(config),
+            events: events.clone(),
+            content_cache: ContentCache::default(),
+        },
         ecosystem_service: LocalEcosystemService {},
-        source_service: LocalSourceService {},
-        facet_service: LocalFacetService {},
+        source_service: ConfiguredSourceService::from_config(config),
+        facet_service: LocalFacetService {
+            template_variables: TemplateVariables::new(&config.template_variables)?,
+            template_overrides: TemplateOverrides::new(config.template_dir.as_deref())?,
+            github_auth: GithubAuth::from_config(config),
+            retry_policy: config.github_retry.clone(),
+            read_only: config.read_only,
+            security_policy: config.security_policy.clone(),
+            private_vulnerability_reporting: config
+                .facet_registry
+                .entry(&SupportedFacetType::VulnerabilityReporting)
+                .is_some_and(|entry| entry.enabled),
+            branch_protection: config.branch_protection.clone(),
+            code_review: config.code_review.clone(),
+            license: config.license.clone(),
+            contributing: config.contributing.clone(),
+            repo_ruleset: config.repo_ruleset.clone(),
+            guac_forwarding: config.guac_forwarding.clone(),
+            pinning: config.pinning.clone(),
+            dependency_updates: config.dependency_updates.clone(),
+            vulnerability_scanner: config.vulnerability_scanner.clone(),
+            publish_packages: config.publish_packages.clone(),
+            container: config.container.clone(),
+            artifact_signing: config.artifact_signing.clone(),
+            best_practices: config.best_practices.clone(),
+            bitbucket: config.bitbucket.clone(),
+            action_pinner: config
+                .facet_registry
+                .entry(&SupportedFacetType::PinnedDependencies)
+                .is_some_and(|entry| entry.enabled)
+                .then(|| ActionPinner::new(config.pinning.clone())),
+        },
         output_service: LocalOutputService {},
-    }
+        facet_registry: config.facet_registry.clone(),
+        change_requests: config.change_requests.clone(),
+        commit_grouping: config.commit_grouping,
+        events,
+        local_project_path: Some(config.local_project_path.clone()),
+    };
+    Ok(AuditedProjectService {
+        project_service,
+        audit_log: AuditLog::new(&config.audit_log),
+    })
 }
 
 fn parse_optional_input<T: DeserializeOwned>(
@@ -234,7 +882,7 @@ fn parse_optional_input<T: DeserializeOwned>(
         Some(input) => {
             // This should also support JSON since most modern YAML is a superset of JSON.
             // I don't care enough to support the edge cases right now.
-            let params: T = serde_yaml::from_reader(input)?;
+            let params: T = serde_yaml::from_reader(input).map_err(SkootError::serialization)?;
             Ok(Some(params))
         }
         None => Ok(None),
@@ -244,119 +892,804 @@ fn parse_optional_input<T: DeserializeOwned>(
 #[allow(clippy::too_many_lines)]
 #[tokio::main]
 async fn main() -> std::result::Result<(), SkootError> {
-    init_tracing();
-    let cli = SkootrsCli::parse();
-    let o: octocrab::Octocrab = octocrab::Octocrab::builder()
-        .personal_token(
-            std::env::var("GITHUB_TOKEN").expect("GITHUB_TOKEN env var must be populated"),
-        )
-        .build()?;
-    octocrab::initialise(o);
-
-    let project_service = init_project_service();
-    // TODO: This should only default when it can't pull a valid config from the environment.
-    let config = skootrs_model::skootrs::Config::default();
-
-    match cli {
-        SkootrsCli::Project { project } => match project {
-            ProjectCommands::Create { input } => {
-                let project_create_params = parse_optional_input(input)?;
-                if let Err(ref error) =
-                    helpers::Project::create(&config, &project_service, project_create_params)
-                        .await
-                        .handle_response_output(stdout())
-                {
-                    error!(error = error.as_ref(), "Failed to create project");
+    let cli = Cli::parse();
+    let (mut config, resolver) = load_config(&cli.config_overrides)?;
+    config.read_only |= cli.read_only;
+    telemetry::init_tracing(&config.telemetry)?;
+    debug!("Loaded config:\n{}", resolver.redacted_yaml(&config)?);
+    let github_auth = GithubAuth::from_config(&config);
+    // Commands that don't call the Github API, like `ci verify-state`, work without a credential, so a missing
+    // credential only fails the commands that need one.
+    if let Ok(client) = github_auth.client() {
+        octocrab::initialise(client);
+    }
+
+    let project_service = init_project_service(&config)?;
+
+    let token = CancellationToken::new();
+    cancel_on_ctrl_c(token.clone());
+    let timeout = cli.timeout.map(Duration::from_secs);
+    let output_format = cli.output;
+    let prompter = prompt::prompter(cli.non_interactive);
+    let result = cancellation::run(token, timeout, async move {
+        let prompter = prompter.as_ref();
+        match cli.command {
+            SkootrsCli::Init { init } => {
+                let path = config_path();
+                if std::path::Path::new(&path).exists() && !init.force {
+                    return Err(format!(
+                        "The config {path} already exists, pass --force to overwrite it"
+                    )
+                    .into());
                 }
+                let params = helpers::InitParams {
+                    github_token_file: init.github_token_file,
+                    organization: init.organization,
+                    local_project_path: init.local_project_path,
+                    telemetry: init.telemetry,
+                    ecosystem: init.ecosystem,
+                    yes: init.yes,
+                };
+                helpers::Setup::init(prompter, &path, params)?;
+                println!("Wrote the config to {path}");
+                // The checks run against the config as every later command will load it.
+                let (config, _) = load_config(&cli.config_overrides)?;
+                report_setup_checks(helpers::Setup::check(&config).await, output_format)?;
             }
-            ProjectCommands::Get { input } => {
-                let project_get_params = parse_optional_input(input)?;
-                if let Err(ref error) =
-                    helpers::Project::get(&config, &project_service, project_get_params)
+            SkootrsCli::Project { project } => match project {
+                ProjectCommands::Create {
+                    dry_run: true,
+                    profile,
+                    input,
+                    ..
+                } => {
+                    let profiled = profile
+                        .map(|name| with_profile(&config, &name))
+                        .transpose()?;
+                    let (config, project_service) = match &profiled {
+                        Some((config, project_service)) => (config, project_service),
+                        None => (&config, &project_service),
+                    };
+                    let project_create_params = parse_optional_input(input)?;
+                    match helpers::Project::plan(
+                        config,
+                        prompter,
+                        project_service,
+                        project_create_params,
+                    )
+                    .await
+                    {
+                        Ok(plan) => println!("{plan}"),
+                        Err(ref error) => {
+                            error!(error = error.as_ref(), "Failed to plan project");
+                        }
+                    }
+                }
+                ProjectCommands::Create {
+                    dry_run: false,
+                    rollback_on_failure,
+                    profile,
+                    input,
+                } => {
+                    let profiled = profile
+                        .map(|name| with_profile(&config, &name))
+                        .transpose()?;
+                    let (config, project_service) = match &profiled {
+                        Some((config, project_service)) => (config, project_service),
+                        None => (&config, &project_service),
+                    };
+                    let project_create_params = parse_optional_input(input)?;
+                    if let Err(ref error) = helpers::Project::create(
+                        config,
+                        prompter,
+                        project_service,
+                        project_create_params,
+                        rollback_on_failure,
+                    )
+                    .await
+                    .handle_response_output(stdout(), output_format)
+                    {
+                        error!(error = error.as_ref(), "Failed to create project");
+                    }
+                }
+                ProjectCommands::CreateBatch {
+                    parallelism,
+                    rollback_on_failure,
+                    input,
+                } => {
+                    let batch_params =
+                        serde_yaml::from_reader(input).map_err(SkootError::serialization)?;
+                    match helpers::Project::create_batch(
+                        &config,
+                        &project_service,
+                        batch_params,
+                        parallelism,
+                        rollback_on_failure,
+                    )
+                    .await
+                    .handle_response_output(stdout(), output_format)
+                    {
+                        // A non-zero exit lets scripts tell that some of the projects are missing.
+                        Ok(batch) if !batch.failed.is_empty() => {
+                            return Err(format!(
+                                "Failed to create {} of {} projects",
+                                batch.failed.len(),
+                                batch.failed.len() + batch.created.len()
+                            )
+                            .into());
+                        }
+                        Ok(_) => {}
+                        Err(ref error) => {
+                            error!(error = error.as_ref(), "Failed to create projects");
+                        }
+                    }
+                }
+                ProjectCommands::Get { input, read } => {
+                    let project_get_params = parse_optional_input(input)?;
+                    if let Err(ref error) =
+                        helpers::Project::get_signed(&config, prompter, project_get_params, read)
+                            .await
+                            .handle_response_output(stdout(), output_format)
+                    {
+                        error!(error = error.as_ref(), "Failed to get project info");
+                    }
+                }
+                ProjectCommands::Info { stats, input } => {
+                    let project_get_params = parse_optional_input(input)?;
+                    if let Err(ref error) = helpers::Project::info(
+                        &config,
+                        prompter,
+                        &project_service,
+                        &LocalInsightsService {
+                            github_auth: github_auth.clone(),
+                        },
+                        project_get_params,
+                        stats,
+                    )
+                    .await
+                    .handle_response_output(stdout(), output_format)
+                    {
+                        error!(error = error.as_ref(), "Failed to get project info");
+                    }
+                }
+                ProjectCommands::Update { input } => {
+                    let project_update_params = parse_optional_input(input)?;
+                    if let Err(ref error) = helpers::Project::update(
+                        &config,
+                        prompter,
+                        &project_service,
+                        project_update_params,
+                    )
+                    .await
+                    .handle_response_output(stdout(), output_format)
+                    {
+                        error!(error = error.as_ref(), "Failed to update project");
+                    }
+                }
+                ProjectCommands::Mirror { input } => {
+                    let project_mirror_params = parse_optional_input(input)?;
+                    if let Err(ref error) = helpers::Project::link_mirror(
+                        &config,
+                        prompter,
+                        &project_service,
+                        project_mirror_params,
+                    )
+                    .await
+                    .handle_response_output(stdout(), output_format)
+                    {
+                        error!(error = error.as_ref(), "Failed to link mirror to project");
+                    }
+                }
+                ProjectCommands::Relocate { input } => {
+                    let project_get_params = parse_optional_input(input)?;
+                    if let Err(ref error) = helpers::Project::relocate(
+                        &config,
+                        prompter,
+                        &project_service,
+                        project_get_params,
+                    )
+                    .await
+                    .handle_response_output(stdout(), output_format)
+                    {
+                        error!(error = error.as_ref(), "Failed to relocate project");
+                    }
+                }
+                ProjectCommands::Audit { input } => {
+                    let project_get_params = parse_optional_input(input)?;
+                    match helpers::Project::audit(
+                        &config,
+                        prompter,
+                        &project_service,
+                        project_get_params,
+                    )
+                    .await
+                    .handle_response_output(stdout(), output_format)
+                    {
+                        Ok(audit) if !audit.is_compliant() => {
+                            error!(
+                                "Project {} has facets that aren't compliant",
+                                audit.project_url
+                            );
+                        }
+                        Ok(_) => {}
+                        Err(ref error) => {
+                            error!(error = error.as_ref(), "Failed to audit project");
+                        }
+                    }
+                }
+                ProjectCommands::Status { input } => {
+                    let project_get_params = parse_optional_input(input)?;
+                    match helpers::Project::status(
+                        &config,
+                        prompter,
+                        &project_service,
+                        project_get_params,
+                    )
+                    .await
+                    .handle_response_output(stdout(), output_format)
+                    {
+                        Ok(status) if !status.drift.is_empty() => {
+                            warn!(
+                                "{} facets of {} drifted",
+                                status.drift.len(),
+                                status.project_url
+                            );
+                        }
+                        Ok(_) => {}
+                        Err(ref error) => {
+                            error!(error = error.as_ref(), "Failed to get project status");
+                        }
+                    }
+                }
+                ProjectCommands::Scorecard { input } => {
+                    let project_get_params = parse_optional_input(input)?;
+                    match helpers::Project::scorecard(
+                        &config,
+                        prompter,
+                        &project_service,
+                        &LocalScorecardService {
+                            config: config.scorecard.clone(),
+                        },
+                        project_get_params,
+                    )
+                    .await
+                    .handle_response_output(stdout(), output_format)
+                    {
+                        Ok(report) => {
+                            let gaps = report
+                                .gaps()
+                                .filter(|gap| gap.status == ScorecardCheckStatus::FacetMissing)
+                                .count();
+                            if gaps > 0 {
+                                info!(
+                                    "{gaps} Scorecard checks of {} can be addressed by adding facets",
+                                    report.project_url
+                                );
+                            }
+                        }
+                        Err(ref error) => {
+                            error!(error = error.as_ref(), "Failed to get Scorecard results");
+                        }
+                    }
+                }
+                ProjectCommands::Evaluate { policy, input } => {
+                    let project_get_params = parse_optional_input(input)?;
+                    let evaluation = helpers::Project::evaluate(
+                        &config,
+                        prompter,
+                        &project_service,
+                        &LocalPolicyService::default(),
+                        project_get_params,
+                        Path::new(&policy),
+                    )
+                    .await
+                    .handle_response_output(stdout(), output_format)?;
+                    // A non-zero exit is what fails the CI job.
+                    if !evaluation.passed() {
+                        return Err(format!(
+                            "Project {} violates {} of the policies in {}",
+                            evaluation.project_url,
+                            evaluation.violations.len(),
+                            evaluation.policy
+                        )
+                        .into());
+                    }
+                }
+                ProjectCommands::List => {
+                    if let Err(ref error) = helpers::Project::list(&config)
                         .await
-                        .handle_response_output(stdout())
-                {
-                    error!(error = error.as_ref(), "Failed to get project info");
+                        .handle_response_output(stdout(), output_format)
+                    {
+                        error!(error = error.as_ref(), "Failed to list projects");
+                    }
                 }
-            }
-            ProjectCommands::Update { input } => {
-                let project_update_params = parse_optional_input(input)?;
-                if let Err(ref error) =
-                    helpers::Project::update(&config, &project_service, project_update_params)
+                ProjectCommands::Search { query, refresh } => {
+                    if let Err(ref error) = helpers::Project::search(&config, &query, refresh)
                         .await
-                        .handle_response_output(stdout())
-                {
-                    error!(error = error.as_ref(), "Failed to update project");
+                        .handle_response_output(stdout(), output_format)
+                    {
+                        error!(error = error.as_ref(), "Failed to search projects");
+                    }
                 }
-            }
-            ProjectCommands::List => {
-                if let Err(ref error) = helpers::Project::list(&config)
+                ProjectCommands::Refresh { project_url } => {
+                    if let Err(ref error) = helpers::Project::refresh(&config, project_url)
+                        .await
+                        .handle_response_output(stdout(), output_format)
+                    {
+                        error!(error = error.as_ref(), "Failed to refresh projects");
+                    }
+                }
+                ProjectCommands::Report {
+                    format: Some(format),
+                    input,
+                    ..
+                } => {
+                    let project_get_params = parse_optional_input(input)?;
+                    let report = helpers::Project::compliance_report(
+                        &config,
+                        prompter,
+                        &project_service,
+                        project_get_params,
+                    )
                     .await
-                    .handle_response_output(stdout())
-                {
-                    error!(error = error.as_ref(), "Failed to list projects");
+                    .and_then(|report| {
+                        match format {
+                            ComplianceFormat::Oscal => writeln!(
+                                stdout(),
+                                "{}",
+                                serde_json::to_string_pretty(&report.to_oscal())?
+                            )?,
+                            ComplianceFormat::Markdown => {
+                                write!(stdout(), "{}", report.to_markdown())?;
+                            }
+                        }
+                        Ok(report)
+                    });
+                    if let Err(ref error) = report {
+                        error!(error = error.as_ref(), "Failed to report on project compliance");
+                    }
                 }
-            }
-            ProjectCommands::Archive { input } => {
-                let project_archive_params = parse_optional_input(input)?;
-                if let Err(ref error) =
-                    helpers::Project::archive(&config, &project_service, project_archive_params)
-                        .await
-                {
-                    error!(error = error.as_ref(), "Failed to archive project");
+                ProjectCommands::Report {
+                    prometheus,
+                    push_gateway,
+                    ..
+                } => {
+                    let report = helpers::Project::report(&config, &project_service).await;
+                    let report = if prometheus {
+                        report.and_then(|r| {
+                            write!(stdout(), "{}", r.to_prometheus())?;
+                            Ok(r)
+                        })
+                    } else {
+                        report.handle_response_output(stdout(), output_format)
+                    };
+                    match report {
+                        Ok(report) => {
+                            if let Some(push_gateway) = push_gateway {
+                                if let Err(ref error) =
+                                    helpers::Project::push_report_metrics(&report, &push_gateway)
+                                        .await
+                                {
+                                    error!(error = error.as_ref(), "Failed to push report metrics");
+                                }
+                            }
+                        }
+                        Err(ref error) => {
+                            error!(error = error.as_ref(), "Failed to report on projects");
+                        }
+                    }
                 }
-            }
-        },
-        SkootrsCli::Facet { facet } => match facet {
-            FacetCommands::Get { input } => {
-                let facet_get_params = parse_optional_input(input)?;
-                if let Err(ref error) = Facet::get(&config, &project_service, facet_get_params)
+                ProjectCommands::Archive { cleanup, input } => {
+                    let project_archive_params = parse_optional_input(input)?;
+                    if let Err(ref error) = helpers::Project::archive(
+                        &config,
+                        prompter,
+                        &project_service,
+                        project_archive_params,
+                        cleanup,
+                    )
                     .await
-                    .handle_response_output(stdout())
-                {
-                    error!(error = error.as_ref(), "Failed to get facet");
+                    .handle_response_output(stdout(), output_format)
+                    {
+                        error!(error = error.as_ref(), "Failed to archive project");
+                    }
                 }
-            }
-            FacetCommands::List { input } => {
-                let project_get_params = parse_optional_input(input)?;
-                if let Err(ref error) = Facet::list(&config, &project_service, project_get_params)
+                ProjectCommands::Delete { input } => {
+                    let project_delete_params = parse_optional_input(input)?;
+                    if let Err(ref error) = helpers::Project::delete(
+                        &config,
+                        prompter,
+                        &project_service,
+                        project_delete_params,
+                    )
                     .await
-                    .handle_response_output(stdout())
-                {
-                    error!(error = error.as_ref(), "Failed to list facets for project");
+                    {
+                        error!(error = error.as_ref(), "Failed to delete project");
+                    }
                 }
-            }
-        },
-        SkootrsCli::Output { output } => match output {
-            OutputCommands::Get { input } => {
-                let output_get_params = parse_optional_input(input)?;
-                if let Err(ref error) = Output::get(&config, &project_service, output_get_params)
+            },
+            SkootrsCli::Facet { facet } => match facet {
+                FacetCommands::Add { input } => {
+                    let facet_add_params = parse_optional_input(input)?;
+                    if let Err(ref error) =
+                        Facet::add(&config, prompter, &project_service, facet_add_params)
+                            .await
+                            .handle_response_output(stdout(), output_format)
+                    {
+                        error!(error = error.as_ref(), "Failed to add facet");
+                    }
+                }
+                FacetCommands::Delete { input } => {
+                    let facet_delete_params = parse_optional_input(input)?;
+                    if let Err(ref error) =
+                        Facet::delete(&config, prompter, &project_service, facet_delete_params)
+                            .await
+                            .handle_response_output(stdout(), output_format)
+                    {
+                        error!(error = error.as_ref(), "Failed to delete facet");
+                    }
+                }
+                FacetCommands::Get {
+                    input,
+                    labels,
+                    read,
+                } => {
+                    let facet_get_params = parse_optional_input(input)?;
+                    if let Err(ref error) = Facet::get(
+                        &config,
+                        prompter,
+                        &project_service,
+                        facet_get_params,
+                        &labels,
+                        read,
+                    )
                     .await
-                    .handle_response_output(stdout())
-                {
-                    error!(error = error.as_ref(), "Failed to get output");
+                    .handle_response_output(stdout(), output_format)
+                    {
+                        error!(error = error.as_ref(), "Failed to get facet");
+                    }
                 }
-            }
-            OutputCommands::List { input } => {
-                let output_list_params = parse_optional_input(input)?;
-                if let Err(ref error) = Output::list(&config, &project_service, output_list_params)
+                FacetCommands::List { input, labels } => {
+                    let facet_list_params = parse_optional_input(input)?;
+                    if let Err(ref error) = Facet::list(
+                        &config,
+                        prompter,
+                        &project_service,
+                        facet_list_params,
+                        labels,
+                    )
                     .await
-                    .handle_response_output(stdout())
-                {
-                    error!(error = error.as_ref(), "Failed to list outputs for project");
+                    .handle_response_output(stdout(), output_format)
+                    {
+                        error!(error = error.as_ref(), "Failed to list facets for project");
+                    }
                 }
+                FacetCommands::Preview { input } => {
+                    let facet_preview_params = parse_optional_input(input)?;
+                    if let Err(ref error) =
+                        Facet::preview(&config, prompter, &project_service, facet_preview_params)
+                            .await
+                            .handle_response_output(stdout(), output_format)
+                    {
+                        error!(error = error.as_ref(), "Failed to preview facet");
+                    }
+                }
+                FacetCommands::Diff { input, summary } => {
+                    let facet_diff_params = parse_optional_input(input)?;
+                    let diff =
+                        Facet::diff(&config, prompter, &project_service, facet_diff_params).await;
+                    let diff = if summary {
+                        diff.handle_response_output(stdout(), output_format)
+                    } else {
+                        diff.and_then(|d| {
+                            write!(stdout(), "{}", d.patch())?;
+                            Ok(d)
+                        })
+                    };
+                    if let Err(ref error) = diff {
+                        error!(error = error.as_ref(), "Failed to diff facet");
+                    }
+                }
+                FacetCommands::History { history } => match history {
+                    FacetHistoryCommands::Show {
+                        facet,
+                        version,
+                        project,
+                    } => {
+                        if let Err(ref error) = Facet::history_show(
+                            &config,
+                            prompter,
+                            &project_service,
+                            facet,
+                            version,
+                            project,
+                        )
+                        .await
+                        .handle_response_output(stdout(), output_format)
+                        {
+                            error!(error = error.as_ref(), "Failed to show facet history");
+                        }
+                    }
+                },
+            },
+            SkootrsCli::Output { output } => match output {
+                OutputCommands::Get { input } => {
+                    let output_get_params = parse_optional_input(input)?;
+                    if let Err(ref error) =
+                        Output::get(&config, prompter, &project_service, output_get_params)
+                            .await
+                            .handle_response_output(stdout(), output_format)
+                    {
+                        error!(error = error.as_ref(), "Failed to get output");
+                    }
+                }
+                OutputCommands::List { input } => {
+                    let output_list_params = parse_optional_input(input)?;
+                    if let Err(ref error) =
+                        Output::list(&config, prompter, &project_service, output_list_params)
+                            .await
+                            .handle_response_output(stdout(), output_format)
+                    {
+                        error!(error = error.as_ref(), "Failed to list outputs for project");
+                    }
+                }
+                OutputCommands::Download { dir, name, input } => {
+                    let output_list_params = parse_optional_input(input)?;
+                    if let Err(ref error) = Output::download(
+                        &config,
+                        prompter,
+                        &project_service,
+                        output_list_params,
+                        name,
+                        dir,
+                    )
+                    .await
+                    .handle_response_output(stdout(), output_format)
+                    {
+                        error!(error = error.as_ref(), "Failed to download outputs");
+                    }
+                }
+                OutputCommands::Verify { input } => {
+                    let verify_params = parse_optional_input(input)?;
+                    let verification =
+                        Output::verify(&config, prompter, &project_service, verify_params)
+                            .await
+                            .handle_response_output(stdout(), output_format)?;
+                    if !verification.verified {
+                        return Err(format!(
+                            "Attestation {} doesn't verify against {}",
+                            verification.name, verification.identity
+                        )
+                        .into());
+                    }
+                }
+            },
+            SkootrsCli::Org { org } => match org {
+                OrgCommands::Rollout { rollout } => match rollout {
+                    RolloutCommands::Preview { plan } => {
+                        let organization = plan
+                            .org
+                            .ok_or_else(|| SkootError::from("--org is required for a preview"))?;
+                        let facet_type = plan
+                            .facet
+                            .ok_or_else(|| SkootError::from("--facet is required for a preview"))?;
+                        if let Err(ref error) = Org::rollout_preview(
+                            &config,
+                            &project_service,
+                            organization,
+                            facet_type,
+                            plan.canary,
+                            plan.batch_size,
+                        )
+                        .await
+                        .handle_response_output(stdout(), output_format)
+                        {
+                            error!(error = error.as_ref(), "Failed to preview rollout");
+                        }
+                    }
+                    RolloutCommands::Start { plan, yes } => {
+                        let params = RolloutParams {
+                            organization: plan.org,
+                            facet_type: plan.facet,
+                            canary_size: plan.canary,
+                            batch_size: plan.batch_size,
+                        };
+                        if let Err(ref error) = Org::rollout_start(
+                            &config,
+                            prompter,
+                            &project_service,
+                            params,
+                            yes,
+                        )
+                        .await
+                        .handle_response_output(stdout(), output_format)
+                        {
+                            error!(error = error.as_ref(), "Failed to start rollout");
+                        }
+                    }
+                    RolloutCommands::Resume { yes } => {
+                        if let Err(ref error) =
+                            Org::rollout_resume(&config, prompter, &project_service, yes)
+                                .await
+                                .handle_response_output(stdout(), output_format)
+                        {
+                            error!(error = error.as_ref(), "Failed to resume rollout");
+                        }
+                    }
+                    RolloutCommands::Status => {
+                        if let Err(ref error) =
+                            Org::rollout_status().handle_response_output(stdout(), output_format)
+                        {
+                            error!(error = error.as_ref(), "Failed to get rollout status");
+                        }
+                    }
+                },
+            },
+            SkootrsCli::Ci { ci } => match ci {
+                CiCommands::VerifyState { path } => {
+                    let audit = helpers::Project::verify_state(&project_service, path)
+                        .await
+                        .handle_response_output(stdout(), output_format)?;
+                    // A non-zero exit is what fails the CI job.
+                    if !audit.is_compliant() {
+                        return Err(format!(
+                            "Project {} has drifted from its recorded state",
+                            audit.project_url
+                        )
+                        .into());
+                    }
+                }
+            },
+            SkootrsCli::Config {
+                config: config_command,
+            } => match config_command {
+                ConfigCommands::Show => {
+                    print!("{}", resolver.redacted_yaml(&config)?);
+                }
+                ConfigCommands::Get { key } => {
+                    print!("{}", resolver.redacted_yaml_at(&config, &key)?);
+                }
+                ConfigCommands::Set { key, value, user } => {
+                    let path = if user {
+                        resolver.user_config_path().ok_or_else(|| {
+                            SkootError::from("Can't find the user config without a home directory")
+                        })?
+                    } else {
+                        config_path().into()
+                    };
+                    let entries = helpers::Setup::set_config(
+                        &config_files(&resolver),
+                        &path,
+                        &key,
+                        &value,
+                        &cli.config_overrides,
+                    )?;
+                    let written = path.display().to_string();
+                    for entry in entries.iter().filter(|entry| entry.source != written) {
+                        warn!(
+                            "{} is set in {written} but overridden by {}",
+                            entry.key, entry.source
+                        );
+                    }
+                    Ok(entries).handle_response_output(stdout(), output_format)?;
+                }
+                ConfigCommands::List => {
+                    Ok(resolver.entries(&config)?)
+                        .handle_response_output(stdout(), output_format)?;
+                }
+                ConfigCommands::Doctor => {
+                    report_setup_checks(helpers::Setup::check(&config).await, output_format)?;
+                }
+            },
+            SkootrsCli::Dashboard => {
+                dashboard::run(&config, prompter, &project_service).await?;
             }
-        },
-        SkootrsCli::Daemon { daemon } => match daemon {
-            DaemonCommands::Start => {
-                tokio::task::spawn_blocking(|| {
-                    skootrs_rest::server::rest::run_server().expect("Failed to start REST Server");
-                })
-                .await
-                .expect("REST Server Task Panicked");
-            }
-        },
+            SkootrsCli::AuditLog { audit_log } => match audit_log {
+                AuditLogCommands::List {
+                    actor,
+                    operation,
+                    project,
+                    since,
+                } => {
+                    let filter = AuditLogFilter {
+                        actor,
+                        operation,
+                        project_url: project,
+                        since,
+                    };
+                    if let Err(ref error) = helpers::AuditLog::list(&config, &filter)
+                        .handle_response_output(stdout(), output_format)
+                    {
+                        error!(error = error.as_ref(), "Failed to list the audit log");
+                    }
+                }
+            },
+            SkootrsCli::Daemon { daemon } => match daemon {
+                DaemonCommands::Start { grpc } => {
+                    if config.daemon.api_keys.is_empty() {
+                        warn!("No API keys are configured, so the daemon will reject every API request");
+                    }
+                    let mut daemon_config = config.daemon.clone();
+                    if config.read_only {
+                        // The daemon's services can't change anything, so no key is allowed to try.
+                        for api_key in &mut daemon_config.api_keys {
+                            api_key.scopes = vec![ApiKeyScope::Read];
+                        }
+                        // Reconciling a project changes it, so webhook deliveries are rejected too.
+                        daemon_config.webhook_secret = None;
+                        daemon_config.reconciliation.update = false;
+                    }
+                    let github_auth = github_auth.clone();
+                    let audit_log = AuditLog::new(&config.audit_log);
+                    let mut cache = InMemoryProjectReferenceCache::new("/tmp/cache.json".into());
+                    cache.local_repo_service = LocalRepoService {
+                        github_auth: github_auth.clone(),
+                        ..Default::default()
+                    };
+                    let project_store = Arc::new(Mutex::new(cache));
+                    let grpc_server = grpc.then(|| {
+                        skootrs_grpc::server::run_server(
+                            SocketAddr::from((Ipv4Addr::UNSPECIFIED, daemon_config.grpc_port)),
+                            github_auth.clone(),
+                            daemon_config.api_keys.clone(),
+                            audit_log.clone(),
+                            project_store.clone(),
+                        )
+                    });
+                    let rest_server = tokio::task::spawn_blocking(|| {
+                        skootrs_rest::server::rest::run_server(
+                            daemon_config,
+                            github_auth,
+                            audit_log,
+                            project_store,
+                        )
+                        .expect("Failed to start REST Server");
+                    });
+                    match grpc_server {
+                        // Both APIs are served until either of them stops.
+                        Some(grpc_server) => tokio::select! {
+                            result = rest_server => result.expect("REST Server Task Panicked"),
+                            result = grpc_server => result?,
+                        },
+                        None => rest_server.await.expect("REST Server Task Panicked"),
+                    }
+                }
+            },
+        }
+
+        Ok(())
+    })
+    .await;
+    if let Err(ref error) = result {
+        error!(error = error.as_ref(), "Command stopped before completing");
     }
+    telemetry::shutdown();
+    result
+}
 
+/// Prints the setup checks, and fails if any of them didn't pass so scripts can tell the setup is broken.
+fn report_setup_checks(
+    checks: Vec<helpers::SetupCheck>,
+    output_format: OutputFormat,
+) -> Result<(), SkootError> {
+    let checks = Ok(checks).handle_response_output(stdout(), output_format)?;
+    let failed = checks.iter().filter(|check| !check.passed).count();
+    if failed > 0 {
+        return Err(format!("{failed} of the setup checks failed").into());
+    }
     Ok(())
 }
+
+/// Cancels the running command when Ctrl-C is pressed so it can clean up after itself. Pressing Ctrl-C again
+/// exits straight away.
+fn cancel_on_ctrl_c(token: CancellationToken) {
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            eprintln!("Cancelling, press Ctrl-C again to exit immediately");
+            token.cancel();
+            if tokio::signal::ctrl_c().await.is_ok() {
+                std::process::exit(130);
+            }
+        }
+    });
+}
@@ -0,0 +1,344 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Commands prompt for any input they aren't given through a `Prompter`. People get interactive prompts, and
+//! scripts running with `--non-interactive` get an error naming the inputs that are missing instead of a
+//! prompt that hangs waiting for an answer.
+
+use std::fmt;
+
+use skootrs_model::skootrs::SkootError;
+
+/// A question asked for an input a command wasn't given.
+#[derive(Clone, Copy, Debug)]
+pub struct Question<'a> {
+    /// The input the answer is used for, e.g. `name`. This is what's reported as missing when prompting
+    /// isn't possible.
+    pub field: &'a str,
+    /// What the user is asked.
+    pub message: &'a str,
+    /// Help shown along with the question.
+    pub help: Option<&'a str>,
+}
+
+impl<'a> Question<'a> {
+    /// Returns a question for an input.
+    #[must_use]
+    pub const fn new(field: &'a str, message: &'a str) -> Self {
+        Self {
+            field,
+            message,
+            help: None,
+        }
+    }
+
+    /// Adds help shown along with the question.
+    #[must_use]
+    pub const fn with_help(mut self, help: &'a str) -> Self {
+        self.help = Some(help);
+        self
+    }
+}
+
+/// Asks for the inputs a command wasn't given. Skippable questions return `None` when they're skipped, and
+/// selections return the index of the selected option.
+pub trait Prompter: Send + Sync {
+    /// Called before a command prompts for the inputs it wasn't given, with every input it needs. This lets
+    /// all the missing inputs be reported at once instead of one at a time.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the inputs can't be prompted for.
+    fn require(&self, fields: &[&str]) -> Result<(), SkootError>;
+
+    /// Asks for a non-empty text answer, or the default if one is given and nothing is entered.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the question can't be asked or is cancelled.
+    fn text(&self, question: Question<'_>, default: Option<&str>) -> Result<String, SkootError>;
+
+    /// Asks for a text answer that can be skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the question can't be asked.
+    fn text_skippable(
+        &self,
+        question: Question<'_>,
+        default: Option<&str>,
+    ) -> Result<Option<String>, SkootError>;
+
+    /// Asks to select one of the options, starting at the default.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the question can't be asked or is cancelled.
+    fn select(
+        &self,
+        question: Question<'_>,
+        options: &[String],
+        default: Option<usize>,
+    ) -> Result<usize, SkootError>;
+
+    /// Asks to select one of the options, starting at the default, or to skip the selection.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the question can't be asked.
+    fn select_skippable(
+        &self,
+        question: Question<'_>,
+        options: &[String],
+        default: Option<usize>,
+    ) -> Result<Option<usize>, SkootError>;
+
+    /// Asks a yes or no question.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the question can't be asked or is cancelled.
+    fn confirm(&self, question: Question<'_>, default: bool) -> Result<bool, SkootError>;
+
+    /// Asks a yes or no question that can be skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the question can't be asked.
+    fn confirm_skippable(
+        &self,
+        question: Question<'_>,
+        default: bool,
+    ) -> Result<Option<bool>, SkootError>;
+}
+
+/// Returns the prompter for the mode the CLI runs in.
+#[must_use]
+pub fn prompter(non_interactive: bool) -> Box<dyn Prompter> {
+    if non_interactive {
+        Box::new(NonInteractivePrompter)
+    } else {
+        Box::new(InquirePrompter)
+    }
+}
+
+/// Asks to select one of the options and returns it.
+///
+/// # Errors
+///
+/// Returns an error if the question can't be asked or is cancelled.
+pub fn choose<T: fmt::Display>(
+    prompter: &dyn Prompter,
+    question: Question<'_>,
+    options: Vec<T>,
+    default: Option<usize>,
+) -> Result<T, SkootError> {
+    let labels = options.iter().map(ToString::to_string).collect::<Vec<_>>();
+    let index = prompter.select(question, &labels, default)?;
+    options
+        .into_iter()
+        .nth(index)
+        .ok_or_else(|| format!("No option was selected for {}", question.field).into())
+}
+
+/// Prompts people in the terminal.
+pub struct InquirePrompter;
+
+impl Prompter for InquirePrompter {
+    fn require(&self, _fields: &[&str]) -> Result<(), SkootError> {
+        Ok(())
+    }
+
+    fn text(&self, question: Question<'_>, default: Option<&str>) -> Result<String, SkootError> {
+        let mut prompt = inquire::Text::new(question.message).with_validator(inquire::required!());
+        if let Some(help) = question.help {
+            prompt = prompt.with_help_message(help);
+        }
+        if let Some(default) = default {
+            prompt = prompt.with_default(default);
+        }
+        prompt.prompt().map_err(SkootError::other)
+    }
+
+    fn text_skippable(
+        &self,
+        question: Question<'_>,
+        default: Option<&str>,
+    ) -> Result<Option<String>, SkootError> {
+        let mut prompt = inquire::Text::new(question.message);
+        if let Some(help) = question.help {
+            prompt = prompt.with_help_message(help);
+        }
+        if let Some(default) = default {
+            prompt = prompt.with_default(default);
+        }
+        prompt.prompt_skippable().map_err(SkootError::other)
+    }
+
+    fn select(
+        &self,
+        question: Question<'_>,
+        options: &[String],
+        default: Option<usize>,
+    ) -> Result<usize, SkootError> {
+        let mut prompt = inquire::Select::new(question.message, options.to_vec())
+            .with_starting_cursor(default.unwrap_or_default());
+        if let Some(help) = question.help {
+            prompt = prompt.with_help_message(help);
+        }
+        Ok(prompt.raw_prompt().map_err(SkootError::other)?.index)
+    }
+
+    fn select_skippable(
+        &self,
+        question: Question<'_>,
+        options: &[String],
+        default: Option<usize>,
+    ) -> Result<Option<usize>, SkootError> {
+        let mut prompt = inquire::Select::new(question.message, options.to_vec())
+            .with_starting_cursor(default.unwrap_or_default());
+        if let Some(help) = question.help {
+            prompt = prompt.with_help_message(help);
+        }
+        match prompt.raw_prompt() {
+            Ok(option) => Ok(Some(option.index)),
+            Err(inquire::InquireError::OperationCanceled) => Ok(None),
+            Err(e) => Err(SkootError::other(e)),
+        }
+    }
+
+    fn confirm(&self, question: Question<'_>, default: bool) -> Result<bool, SkootError> {
+        let mut prompt = inquire::Confirm::new(question.message).with_default(default);
+        if let Some(help) = question.help {
+            prompt = prompt.with_help_message(help);
+        }
+        prompt.prompt().map_err(SkootError::other)
+    }
+
+    fn confirm_skippable(
+        &self,
+        question: Question<'_>,
+        default: bool,
+    ) -> Result<Option<bool>, SkootError> {
+        let mut prompt = inquire::Confirm::new(question.message).with_default(default);
+        if let Some(help) = question.help {
+            prompt = prompt.with_help_message(help);
+        }
+        prompt.prompt_skippable().map_err(SkootError::other)
+    }
+}
+
+/// Never prompts. Questions with a default are answered with it, skippable questions are skipped, and
+/// anything else is an error naming the missing input. Confirmations are always an error, since they're only
+/// asked before changes a person has to agree to.
+pub struct NonInteractivePrompter;
+
+impl NonInteractivePrompter {
+    fn missing(fields: &[&str]) -> SkootError {
+        format!(
+            "Missing {}, which can't be prompted for in non-interactive mode. Pass them as arguments or with --input",
+            fields.join(", ")
+        )
+        .into()
+    }
+}
+
+impl Prompter for NonInteractivePrompter {
+    fn require(&self, fields: &[&str]) -> Result<(), SkootError> {
+        if fields.is_empty() {
+            return Ok(());
+        }
+        Err(Self::missing(fields))
+    }
+
+    fn text(&self, question: Question<'_>, default: Option<&str>) -> Result<String, SkootError> {
+        default
+            .map(str::to_string)
+            .ok_or_else(|| Self::missing(&[question.field]))
+    }
+
+    fn text_skippable(
+        &self,
+        _question: Question<'_>,
+        _default: Option<&str>,
+    ) -> Result<Option<String>, SkootError> {
+        Ok(None)
+    }
+
+    fn select(
+        &self,
+        question: Question<'_>,
+        _options: &[String],
+        default: Option<usize>,
+    ) -> Result<usize, SkootError> {
+        default.ok_or_else(|| Self::missing(&[question.field]))
+    }
+
+    fn select_skippable(
+        &self,
+        _question: Question<'_>,
+        _options: &[String],
+        _default: Option<usize>,
+    ) -> Result<Option<usize>, SkootError> {
+        Ok(None)
+    }
+
+    fn confirm(&self, question: Question<'_>, _default: bool) -> Result<bool, SkootError> {
+        Err(Self::missing(&[question.field]))
+    }
+
+    fn confirm_skippable(
+        &self,
+        _question: Question<'_>,
+        _default: bool,
+    ) -> Result<Option<bool>, SkootError> {
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_non_interactive_require() {
+        assert!(NonInteractivePrompter.require(&[]).is_ok());
+        let error = NonInteractivePrompter
+            .require(&["initialized_project", "facet_type"])
+            .unwrap_err()
+            .to_string();
+        assert!(error.starts_with("Missing initialized_project, facet_type,"));
+    }
+
+    #[test]
+    fn test_non_interactive_defaults() {
+        let prompter = NonInteractivePrompter;
+        let question = Question::new("local_project_path", "The directory to clone projects into");
+        assert_eq!(prompter.text(question, Some("/tmp")).unwrap(), "/tmp");
+        assert!(prompter.text(question, None).is_err());
+        assert_eq!(
+            prompter.text_skippable(question, Some("/tmp")).unwrap(),
+            None
+        );
+        let options = vec!["Go".to_string(), "Rust".to_string()];
+        assert_eq!(
+            choose(&prompter, question, options.clone(), Some(1)).unwrap(),
+            "Rust"
+        );
+        assert!(prompter.select(question, &options, None).is_err());
+        assert!(prompter.confirm(question, true).is_err());
+        assert_eq!(prompter.confirm_skippable(question, true).unwrap(), None);
+    }
+}
@@ -0,0 +1,265 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Sets up tracing from the `telemetry` config. Spans are always logged to stdout, and traces are exported
+//! with OTLP to a local Jaeger or an OpenTelemetry collector. The daemon runs in the same process, so its
+//! requests are traced the same way.
+
+use std::collections::BTreeMap;
+
+use opentelemetry::{
+    global,
+    trace::{TraceError, TracerProvider as _},
+    KeyValue,
+};
+use opentelemetry_otlp::{Protocol, SpanExporterBuilder, WithExportConfig};
+use opentelemetry_sdk::{
+    propagation::TraceContextPropagator,
+    runtime::TokioCurrentThread,
+    trace::{Config, Sampler, TracerProvider},
+    Resource,
+};
+use skootrs_model::skootrs::{
+    telemetry::{OtlpProtocol, TelemetryConfig, TelemetryExporter},
+    SkootError,
+};
+use tonic::metadata::{MetadataKey, MetadataMap, MetadataValue};
+use tracing_bunyan_formatter::{BunyanFormattingLayer, JsonStorageLayer};
+use tracing_subscriber::{layer::SubscriberExt, EnvFilter, Registry};
+
+/// Where a local Jaeger receives OTLP over gRPC.
+const JAEGER_ENDPOINT: &str = "http://localhost:4317";
+
+/// Installs the global `tracing` subscriber for the config.
+///
+/// # Errors
+///
+/// Returns an error if the OTLP exporter can't be set up, e.g. because its endpoint isn't a valid URL, or if a
+/// subscriber was already installed.
+pub fn init_tracing(config: &TelemetryConfig) -> Result<(), SkootError> {
+    global::set_text_map_propagator(TraceContextPropagator::new());
+    let tracer = tracer_provider(config)
+        .map_err(|err| SkootError::Config(format!("Failed to set up the trace exporter: {err}")))?
+        .map(|provider| {
+            let tracer = provider.tracer("skootrs");
+            global::set_tracer_provider(provider);
+            tracer
+        });
+    let telemetry = tracer.map(|tracer| tracing_opentelemetry::layer().with_tracer(tracer));
+
+    // Filter based on level - trace, debug, info, warn, error
+    // Tunable via `RUST_LOG` env variable
+    let env_filter = EnvFilter::try_from_default_env().unwrap_or(EnvFilter::new("info"));
+    // Create a `tracing` layer to emit spans as structured logs to stdout
+    let formatting_layer = BunyanFormattingLayer::new(config.service_name.clone(), std::io::stdout);
+    // Combined them all together in a `tracing` subscriber
+    let subscriber = Registry::default()
+        .with(env_filter)
+        .with(telemetry)
+        .with(JsonStorageLayer)
+        .with(formatting_layer);
+    tracing::subscriber::set_global_default(subscriber).map_err(SkootError::other)
+}
+
+/// Exports the spans that haven't been yet, so the end of a command isn't lost.
+pub fn shutdown() {
+    global::shutdown_tracer_provider();
+}
+
+/// Returns the provider that exports traces where the config says, or `None` if they're only logged.
+fn tracer_provider(config: &TelemetryConfig) -> Result<Option<TracerProvider>, TraceError> {
+    let exporter = match &config.exporter {
+        TelemetryExporter::Stdout => return Ok(None),
+        TelemetryExporter::Jaeger => {
+            span_exporter(OtlpProtocol::Grpc, JAEGER_ENDPOINT, &BTreeMap::new())?
+        }
+        TelemetryExporter::Otlp {
+            protocol,
+            endpoint,
+            headers,
+        } => span_exporter(*protocol, endpoint, headers)?,
+    };
+    // Traces a caller of the daemon started keep the caller's sampling decision.
+    let trace_config = Config::default()
+        .with_sampler(Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+            config.sampling_ratio,
+        ))))
+        .with_resource(Resource::new([KeyValue::new(
+            "service.name",
+            config.service_name.clone(),
+        )]));
+    opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(exporter)
+        .with_trace_config(trace_config)
+        .install_batch(TokioCurrentThread)
+        .map(Some)
+}
+
+/// Returns the OTLP exporter for the protocol. Headers are sent as gRPC metadata over gRPC.
+fn span_exporter(
+    protocol: OtlpProtocol,
+    endpoint: &str,
+    headers: &BTreeMap<String, String>,
+) -> Result<SpanExporterBuilder, TraceError> {
+    let exporter = match protocol {
+        OtlpProtocol::Grpc => {
+            let mut metadata = MetadataMap::new();
+            for (name, value) in headers {
+                let key = MetadataKey::from_bytes(name.to_lowercase().as_bytes())
+                    .map_err(|_| TraceError::from(format!("Invalid OTLP header name {name}")))?;
+                let value = MetadataValue::try_from(value.as_str()).map_err(|_| {
+                    TraceError::from(format!("Invalid value for OTLP header {name}"))
+                })?;
+                metadata.insert(key, value);
+            }
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(endpoint)
+                .with_metadata(metadata)
+                .into()
+        }
+        OtlpProtocol::HttpProto | OtlpProtocol::HttpJson => opentelemetry_otlp::new_exporter()
+            .http()
+            .with_protocol(if protocol == OtlpProtocol::HttpJson {
+                Protocol::HttpJson
+            } else {
+                Protocol::HttpBinary
+            })
+            .with_endpoint(endpoint)
+            .with_headers(headers.clone().into_iter().collect())
+            .into(),
+    };
+    Ok(exporter)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        sync::mpsc,
+        thread,
+    };
+
+    use opentelemetry::trace::Tracer as _;
+
+    use super::*;
+
+    /// The request line and headers of an export, lowercased, and its body.
+    type Export = (Vec<String>, Vec<u8>);
+
+    /// Accepts one export and returns its headers and body.
+    fn fake_collector() -> (String, mpsc::Receiver<Export>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("http://{}/v1/traces", listener.local_addr().unwrap());
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut headers = vec![];
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                headers.push(line.trim().to_lowercase());
+            }
+            let length = headers
+                .iter()
+                .find_map(|header| header.strip_prefix("content-length: "))
+                .map_or(0, |length| length.parse().unwrap());
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            let mut stream = stream;
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+                .unwrap();
+            sender.send((headers, body)).unwrap();
+        });
+        (endpoint, receiver)
+    }
+
+    #[tokio::test]
+    async fn test_export_http_json() {
+        let (endpoint, exports) = fake_collector();
+        let config = TelemetryConfig {
+            exporter: TelemetryExporter::Otlp {
+                protocol: OtlpProtocol::HttpJson,
+                endpoint,
+                headers: BTreeMap::from([(
+                    "Authorization".to_string(),
+                    "Bearer t0ken".to_string(),
+                )]),
+            },
+            ..TelemetryConfig::default()
+        };
+        let provider = tracer_provider(&config).unwrap().unwrap();
+        provider.tracer("skootrs").in_span("create_project", |_| {});
+        provider.force_flush();
+
+        let (headers, body) = exports.recv().unwrap();
+        assert!(headers.contains(&"post /v1/traces http/1.1".to_string()));
+        assert!(headers.contains(&"content-type: application/json".to_string()));
+        assert!(headers.contains(&"authorization: bearer t0ken".to_string()));
+        let request: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let resource_spans = &request["resourceSpans"][0];
+        assert_eq!(
+            resource_spans["resource"]["attributes"][0]["key"],
+            "service.name"
+        );
+        assert_eq!(
+            resource_spans["scopeSpans"][0]["spans"][0]["name"],
+            "create_project"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_jaeger_exports_over_grpc() {
+        // The channel connects lazily, so the provider is built without a Jaeger running.
+        let provider = tracer_provider(&TelemetryConfig::default()).unwrap();
+        assert!(provider.is_some());
+    }
+
+    #[test]
+    fn test_span_exporter_invalid_header() {
+        let headers = BTreeMap::from([("bad header".to_string(), "value".to_string())]);
+        assert!(span_exporter(OtlpProtocol::Grpc, JAEGER_ENDPOINT, &headers).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_init_tracing_invalid_endpoint() {
+        let config = TelemetryConfig {
+            exporter: TelemetryExporter::Otlp {
+                protocol: OtlpProtocol::Grpc,
+                endpoint: "not a url".to_string(),
+                headers: BTreeMap::new(),
+            },
+            ..TelemetryConfig::default()
+        };
+        let err = init_tracing(&config).unwrap_err();
+        assert!(matches!(err, SkootError::Config(_)));
+    }
+
+    #[test]
+    fn test_stdout_has_no_provider() {
+        let config = TelemetryConfig {
+            exporter: TelemetryExporter::Stdout,
+            ..TelemetryConfig::default()
+        };
+        assert!(tracer_provider(&config).unwrap().is_none());
+    }
+}
@@ -0,0 +1,377 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Conversions between the protobuf messages and the `skootrs-model` types they mirror. Messages that come in
+//! are checked as they're converted, since protobuf can't require a field to be set.
+
+use skootrs_model::skootrs::{
+    facet::InitializedFacet,
+    validation::{FieldError, ValidationError},
+    BitbucketRepoParams, EcosystemInitializeParams, FacetMapKey, GithubRepoParams, GithubUser,
+    GoParams, InitializedEcosystem, InitializedModule, InitializedProject, MavenParams,
+    ModuleInitializeParams, ProjectArchive, ProjectCreateParams, ProjectOutput,
+    ProjectOutputReference, ProjectReleaseParam, PythonPackageManager, PythonParams,
+    RepoCreateParams, RustParams, SkootError, SourceInitializeParams, WorkflowRunParam,
+    ROOT_MODULE_PATH,
+};
+
+use crate::proto;
+
+fn invalid(field: &str, message: String) -> SkootError {
+    SkootError::Validation(ValidationError {
+        errors: vec![FieldError {
+            field: field.to_string(),
+            message,
+        }],
+    })
+}
+
+fn missing(field: &str) -> SkootError {
+    invalid(field, "is required".to_string())
+}
+
+impl TryFrom<proto::ProjectCreateParams> for ProjectCreateParams {
+    type Error = SkootError;
+
+    fn try_from(params: proto::ProjectCreateParams) -> Result<Self, Self::Error> {
+        let repo_params = params
+            .repo_params
+            .and_then(|repo_params| repo_params.repo)
+            .ok_or_else(|| missing("repo_params"))?;
+        let ecosystem_params = params
+            .ecosystem_params
+            .into_iter()
+            .map(ModuleInitializeParams::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        if ecosystem_params.is_empty() {
+            return Err(missing("ecosystem_params"));
+        }
+        Ok(Self {
+            name: params.name,
+            repo_params: repo_params.try_into()?,
+            ecosystem_params,
+            source_params: SourceInitializeParams {
+                parent_path: params
+                    .source_params
+                    .ok_or_else(|| missing("source_params"))?
+                    .parent_path,
+            },
+            rollback_on_failure: params.rollback_on_failure,
+        })
+    }
+}
+
+impl TryFrom<proto::repo_create_params::Repo> for RepoCreateParams {
+    type Error = SkootError;
+
+    fn try_from(repo: proto::repo_create_params::Repo) -> Result<Self, Self::Error> {
+        Ok(match repo {
+            proto::repo_create_params::Repo::Github(github) => Self::Github(GithubRepoParams {
+                name: github.name,
+                description: github.description,
+                organization: match github.organization.ok_or_else(|| missing("organization"))? {
+                    proto::github_repo_params::Organization::User(user) => GithubUser::User(user),
+                    proto::github_repo_params::Organization::Org(org) => {
+                        GithubUser::Organization(org)
+                    }
+                },
+                license: None,
+            }),
+            proto::repo_create_params::Repo::Bitbucket(bitbucket) => {
+                Self::Bitbucket(BitbucketRepoParams {
+                    name: bitbucket.name,
+                    description: bitbucket.description,
+                    workspace: bitbucket.workspace,
+                })
+            }
+        })
+    }
+}
+
+impl TryFrom<proto::ModuleInitializeParams> for ModuleInitializeParams {
+    type Error = SkootError;
+
+    fn try_from(module: proto::ModuleInitializeParams) -> Result<Self, Self::Error> {
+        let ecosystem = match module
+            .ecosystem
+            .and_then(|ecosystem| ecosystem.ecosystem)
+            .ok_or_else(|| missing("ecosystem"))?
+        {
+            proto::ecosystem_initialize_params::Ecosystem::Go(go) => {
+                EcosystemInitializeParams::Go(GoParams {
+                    name: go.name,
+                    host: go.host,
+                    go_version: go.go_version,
+                })
+            }
+            proto::ecosystem_initialize_params::Ecosystem::Maven(maven) => {
+                EcosystemInitializeParams::Maven(MavenParams {
+                    group_id: maven.group_id,
+                    artifact_id: maven.artifact_id,
+                    ..Default::default()
+                })
+            }
+            proto::ecosystem_initialize_params::Ecosystem::Python(python) => {
+                EcosystemInitializeParams::Python(PythonParams {
+                    package_manager: match python.package_manager() {
+                        proto::PythonPackageManager::Pip => PythonPackageManager::Pip,
+                        proto::PythonPackageManager::Poetry => PythonPackageManager::Poetry,
+                    },
+                    name: python.name,
+                })
+            }
+            proto::ecosystem_initialize_params::Ecosystem::Rust(rust) => {
+                EcosystemInitializeParams::Rust(RustParams {
+                    name: rust.name,
+                    crates: rust.crates,
+                })
+            }
+        };
+        Ok(Self {
+            path: if module.path.is_empty() {
+                ROOT_MODULE_PATH.to_string()
+            } else {
+                module.path
+            },
+            ecosystem,
+        })
+    }
+}
+
+impl TryFrom<proto::FacetMapKey> for FacetMapKey {
+    type Error = SkootError;
+
+    fn try_from(key: proto::FacetMapKey) -> Result<Self, Self::Error> {
+        match key.key.ok_or_else(|| missing("facet_map_key"))? {
+            proto::facet_map_key::Key::Name(name) => Ok(Self::Name(name)),
+            proto::facet_map_key::Key::Type(facet_type) => {
+                Ok(Self::Type(facet_type.parse().map_err(|_| {
+                    invalid(
+                        "facet_map_key",
+                        format!("has an unknown facet type {facet_type}"),
+                    )
+                })?))
+            }
+        }
+    }
+}
+
+impl From<&FacetMapKey> for proto::FacetMapKey {
+    fn from(key: &FacetMapKey) -> Self {
+        Self {
+            key: Some(match key {
+                FacetMapKey::Name(name) => proto::facet_map_key::Key::Name(name.clone()),
+                FacetMapKey::Type(facet_type) => {
+                    proto::facet_map_key::Key::Type(facet_type.to_string())
+                }
+            }),
+        }
+    }
+}
+
+impl TryFrom<&InitializedProject> for proto::InitializedProject {
+    type Error = SkootError;
+
+    fn try_from(project: &InitializedProject) -> Result<Self, Self::Error> {
+        Ok(Self {
+            name: project.name.clone(),
+            repo_url: project.repo.full_url(),
+            skootrs_version: project.skootrs_version.clone(),
+            modules: project
+                .modules
+                .iter()
+                .map(proto::InitializedModule::from)
+                .collect(),
+            source_path: project.source.path.clone(),
+            facets: project
+                .facets
+                .keys()
+                .map(proto::FacetMapKey::from)
+                .collect(),
+            state_json: serde_json::to_string(project)?,
+        })
+    }
+}
+
+impl From<&InitializedModule> for proto::InitializedModule {
+    fn from(module: &InitializedModule) -> Self {
+        let ecosystem = match module.ecosystem {
+            InitializedEcosystem::Go(_) => "Go",
+            InitializedEcosystem::Maven(_) => "Maven",
+            InitializedEcosystem::Python(_) => "Python",
+            InitializedEcosystem::Rust(_) => "Rust",
+        };
+        Self {
+            path: module.path.clone(),
+            ecosystem: ecosystem.to_string(),
+        }
+    }
+}
+
+impl From<InitializedFacet> for proto::InitializedFacet {
+    fn from(facet: InitializedFacet) -> Self {
+        let facet_type = facet.facet_type().to_string();
+        match facet {
+            InitializedFacet::SourceBundle(facet) => Self {
+                facet_type,
+                labels: facet.labels.iter().map(ToString::to_string).collect(),
+                facet: Some(proto::initialized_facet::Facet::SourceBundle(
+                    proto::SourceBundleFacet {
+                        source_files: facet
+                            .source_files_content
+                            .unwrap_or_default()
+                            .into_iter()
+                            .map(|(source_file, content)| proto::SourceFileContent {
+                                path: source_file.into(),
+                                content,
+                            })
+                            .collect(),
+                    },
+                )),
+            },
+            InitializedFacet::APIBundle(facet) => Self {
+                facet_type,
+                labels: facet.labels.iter().map(ToString::to_string).collect(),
+                facet: Some(proto::initialized_facet::Facet::ApiBundle(
+                    proto::ApiBundleFacet {
+                        apis: facet
+                            .apis
+                            .into_iter()
+                            .map(|api| proto::ApiContent {
+                                name: api.name,
+                                url: api.url,
+                                response: api.response,
+                            })
+                            .collect(),
+                        skipped_reason: facet.skipped_reason,
+                    },
+                )),
+            },
+        }
+    }
+}
+
+impl TryFrom<ProjectArchive> for proto::ProjectArchive {
+    type Error = SkootError;
+
+    fn try_from(archive: ProjectArchive) -> Result<Self, Self::Error> {
+        Ok(Self {
+            project_url: archive.project_url,
+            cleanup_json: archive
+                .cleanup
+                .map(|cleanup| serde_json::to_string(&cleanup))
+                .transpose()?,
+        })
+    }
+}
+
+impl From<ProjectOutputReference> for proto::ProjectOutputReference {
+    fn from(reference: ProjectOutputReference) -> Self {
+        Self {
+            output_type: reference.output_type.to_string(),
+            name: reference.name,
+            labels: reference.labels.iter().map(ToString::to_string).collect(),
+        }
+    }
+}
+
+impl From<ProjectOutput> for proto::ProjectOutput {
+    fn from(output: ProjectOutput) -> Self {
+        Self {
+            reference: Some(output.reference.into()),
+            output: output.output,
+        }
+    }
+}
+
+impl proto::ProjectOutputsListParams {
+    /// Returns the release the outputs are listed from. The latest release is used if no tag is set.
+    #[must_use]
+    pub fn release(&self) -> ProjectReleaseParam {
+        self.tag
+            .clone()
+            .map_or(ProjectReleaseParam::Latest, ProjectReleaseParam::Tag)
+    }
+
+    /// Returns the workflow run the outputs are listed from instead of a release, if any.
+    #[must_use]
+    pub fn workflow_run(&self) -> Option<WorkflowRunParam> {
+        self.workflow_run
+            .clone()
+            .map(|workflow_run| match workflow_run {
+                proto::project_outputs_list_params::WorkflowRun::RunId(id) => {
+                    WorkflowRunParam::Id(id)
+                }
+                proto::project_outputs_list_params::WorkflowRun::RunBranch(branch) => {
+                    WorkflowRunParam::LatestOn(branch)
+                }
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn test_project_create_params_try_from() {
+        let params = proto::ProjectCreateParams {
+            name: "test".to_string(),
+            repo_params: Some(proto::RepoCreateParams {
+                repo: Some(proto::repo_create_params::Repo::Github(
+                    proto::GithubRepoParams {
+                        name: "test".to_string(),
+                        description: "A test project".to_string(),
+                        organization: Some(proto::github_repo_params::Organization::Org(
+                            "testorg".to_string(),
+                        )),
+                    },
+                )),
+            }),
+            ecosystem_params: vec![proto::ModuleInitializeParams {
+                path: String::new(),
+                ecosystem: Some(proto::EcosystemInitializeParams {
+                    ecosystem: Some(proto::ecosystem_initialize_params::Ecosystem::Go(
+                        proto::GoParams {
+                            name: "test".to_string(),
+                            host: "github.com/testorg".to_string(),
+                            go_version: None,
+                        },
+                    )),
+                }),
+            }],
+            source_params: Some(proto::SourceInitializeParams {
+                parent_path: "/tmp".to_string(),
+            }),
+            rollback_on_failure: false,
+        };
+        let params = ProjectCreateParams::try_from(params).unwrap();
+        assert!(matches!(
+            params.repo_params,
+            RepoCreateParams::Github(GithubRepoParams { organization: GithubUser::Organization(ref org), .. }) if org == "testorg"
+        ));
+        assert_eq!(params.ecosystem_params[0].path, ROOT_MODULE_PATH);
+
+        let params = proto::ProjectCreateParams {
+            name: "test".to_string(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            ProjectCreateParams::try_from(params),
+            Err(SkootError::Validation(_))
+        ));
+    }
+}
@@ -0,0 +1,30 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! This is the crate where the gRPC API for `Skootrs` is defined. It serves the project, facet and output
+//! operations of the REST API with `tonic`, from the same project service, for tooling that prefers gRPC.
+//! The messages are defined in `proto/skootrs.proto` and mirror the types in `skootrs-model`.
+
+// Every call returns tonic's `Status` as its error, which is large, but it's what the generated service expects.
+#![allow(clippy::result_large_err)]
+
+pub mod convert;
+pub mod server;
+
+/// The messages and service generated from `proto/skootrs.proto`.
+#[allow(missing_docs, clippy::pedantic, clippy::nursery)]
+pub mod proto {
+    tonic::include_proto!("skootrs.v1");
+}
@@ -0,0 +1,513 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The gRPC server. Each call is authorized with the same API keys as the REST API and served by the same
+//! project service, and projects created, updated and archived over gRPC are recorded in the same state store.
+
+use std::{net::SocketAddr, sync::Arc};
+
+use skootrs_lib::service::{
+    audit_log::{AuditLog, AuditedProjectService},
+    github_auth::GithubAuth,
+    project::{DaemonProjectService, ProjectService},
+    source::{ConcurrencyError, LocalSourceService},
+};
+use skootrs_model::skootrs::{
+    facet::SupportedFacetType, ApiKeyConfig, ApiKeyError, ApiKeyScope, FacetGetParams,
+    FacetListParams, InitializedProject, ProjectArchiveParams, ProjectCreateParams,
+    ProjectGetParams, ProjectOutputGetParams, ProjectOutputType, ProjectOutputsListParams,
+    ProjectUpdateParams, ReadStrategy, SkootError, StateSigning,
+};
+use skootrs_statestore::{
+    GitProjectStateStore, InMemoryProjectReferenceCache, ProjectReferenceCache, ProjectStateStore,
+};
+use tokio::sync::Mutex;
+use tonic::{transport::Server, Request, Response, Status};
+use tracing::info;
+
+use crate::proto::{
+    self,
+    skootrs_server::{Skootrs, SkootrsServer},
+};
+
+/// The metadata API keys are sent in, the same name as the REST API's header.
+pub const API_KEY_METADATA: &str = "skootrs_apikey";
+
+/// The implementation of the `Skootrs` gRPC service.
+pub struct SkootrsGrpc {
+    github_auth: GithubAuth,
+    api_keys: Arc<Vec<ApiKeyConfig>>,
+    audit_log: AuditLog,
+    project_store: Arc<Mutex<InMemoryProjectReferenceCache>>,
+}
+
+impl SkootrsGrpc {
+    /// Returns the service, which accepts calls with one of `api_keys`, records the changes they make in
+    /// `audit_log` and records projects in `project_store`.
+    #[must_use]
+    pub fn new(
+        github_auth: GithubAuth,
+        api_keys: Vec<ApiKeyConfig>,
+        audit_log: AuditLog,
+        project_store: Arc<Mutex<InMemoryProjectReferenceCache>>,
+    ) -> Self {
+        Self {
+            github_auth,
+            api_keys: Arc::new(api_keys),
+            audit_log,
+            project_store,
+        }
+    }
+
+    /// Checks that the call has an API key with `scope`, like the REST API's middleware does for requests, and
+    /// returns who the changes the call makes are recorded in the audit log as made by.
+    fn authorize<T>(&self, request: &Request<T>, scope: ApiKeyScope) -> Result<String, Status> {
+        let key = request
+            .metadata()
+            .get(API_KEY_METADATA)
+            .and_then(|value| value.to_str().ok())
+            .ok_or_else(|| {
+                Status::unauthenticated(format!("The {API_KEY_METADATA} metadata is missing"))
+            })?;
+        let api_key =
+            ApiKeyConfig::authorize(&self.api_keys, key, &scope).map_err(|err| match err {
+                ApiKeyError::InvalidKey => Status::unauthenticated(err.to_string()),
+                ApiKeyError::MissingScope { .. } => Status::permission_denied(err.to_string()),
+            })?;
+        Ok(format!("api_key:{}", api_key.name))
+    }
+
+    fn project_service(&self) -> Result<DaemonProjectService, Status> {
+        DaemonProjectService::for_daemon(&self.github_auth).map_err(service_status)
+    }
+
+    /// Returns the project service for calls that change projects, which records the changes in the audit log
+    /// as made by `actor`.
+    fn audited_project_service(
+        &self,
+        actor: String,
+    ) -> Result<AuditedProjectService<DaemonProjectService>, Status> {
+        Ok(AuditedProjectService {
+            project_service: self.project_service()?,
+            audit_log: self.audit_log.with_actor(actor),
+        })
+    }
+
+    async fn fetch_project(&self, project_url: String) -> Result<InitializedProject, Status> {
+        self.project_service()?
+            .get(ProjectGetParams {
+                project_url,
+                read_strategy: ReadStrategy::default(),
+            })
+            .await
+            .map_err(service_status)
+    }
+}
+
+/// Maps an error from the project service to the status for its kind of failure, like the REST API does with
+/// status codes.
+fn service_status(err: SkootError) -> Status {
+    if err.downcast_ref::<ConcurrencyError>().is_some() {
+        return Status::aborted(err.to_string());
+    }
+    match err {
+        SkootError::Validation(_) => Status::invalid_argument(err.to_string()),
+        SkootError::NotFound(_) => Status::not_found(err.to_string()),
+        SkootError::GithubApi(_) => Status::unavailable(err.to_string()),
+        _ => Status::internal(err.to_string()),
+    }
+}
+
+#[tonic::async_trait]
+impl Skootrs for SkootrsGrpc {
+    async fn create_project(
+        &self,
+        request: Request<proto::ProjectCreateParams>,
+    ) -> Result<Response<proto::InitializedProject>, Status> {
+        let actor = self.authorize(&request, ApiKeyScope::Write)?;
+        let params = ProjectCreateParams::try_from(request.into_inner()).map_err(service_status)?;
+        let project = self
+            .audited_project_service(actor)?
+            .initialize(params)
+            .await
+            .map_err(service_status)?;
+        let mut project_store = self.project_store.lock().await;
+        project_store
+            .set(project.repo.full_url())
+            .await
+            .map_err(service_status)?;
+        project_store.save().map_err(service_status)?;
+        Ok(Response::new(
+            (&project).try_into().map_err(service_status)?,
+        ))
+    }
+
+    async fn get_project(
+        &self,
+        request: Request<proto::ProjectGetParams>,
+    ) -> Result<Response<proto::InitializedProject>, Status> {
+        self.authorize(&request, ApiKeyScope::Read)?;
+        let project = self.fetch_project(request.into_inner().project_url).await?;
+        Ok(Response::new(
+            (&project).try_into().map_err(service_status)?,
+        ))
+    }
+
+    async fn list_projects(
+        &self,
+        request: Request<proto::ProjectListParams>,
+    ) -> Result<Response<proto::ProjectList>, Status> {
+        self.authorize(&request, ApiKeyScope::Read)?;
+        let mut project_urls = self
+            .project_store
+            .lock()
+            .await
+            .list()
+            .await
+            .map_err(service_status)?
+            .into_iter()
+            .collect::<Vec<_>>();
+        project_urls.sort();
+        Ok(Response::new(proto::ProjectList { project_urls }))
+    }
+
+    async fn update_project(
+        &self,
+        request: Request<proto::ProjectUpdateParams>,
+    ) -> Result<Response<proto::InitializedProject>, Status> {
+        let actor = self.authorize(&request, ApiKeyScope::Write)?;
+        let params = request.into_inner();
+        let facet_types = params
+            .facet_types
+            .iter()
+            .map(|facet_type| {
+                facet_type.parse::<SupportedFacetType>().map_err(|_| {
+                    Status::invalid_argument(format!("Unknown facet type {facet_type}"))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let initialized_project = self.fetch_project(params.project_url).await?;
+        let project = self
+            .audited_project_service(actor)?
+            .update(ProjectUpdateParams {
+                initialized_project,
+                facet_types: (!facet_types.is_empty()).then_some(facet_types),
+            })
+            .await
+            .map_err(service_status)?;
+        // The state has to be kept current so the next update can tell which facets drifted.
+        let git_state_store = GitProjectStateStore {
+            source: project.source.clone(),
+            source_service: LocalSourceService::default(),
+            signing: StateSigning::default(),
+        };
+        git_state_store
+            .update(project.clone())
+            .await
+            .map_err(service_status)?;
+        let mut project_store = self.project_store.lock().await;
+        project_store
+            .set(project.repo.full_url())
+            .await
+            .map_err(service_status)?;
+        project_store.save().map_err(service_status)?;
+        Ok(Response::new(
+            (&project).try_into().map_err(service_status)?,
+        ))
+    }
+
+    async fn archive_project(
+        &self,
+        request: Request<proto::ProjectArchiveParams>,
+    ) -> Result<Response<proto::ProjectArchive>, Status> {
+        let actor = self.authorize(&request, ApiKeyScope::Write)?;
+        let params = request.into_inner();
+        let initialized_project = self.fetch_project(params.project_url).await?;
+        let url = initialized_project.repo.full_url();
+        let archive = self
+            .audited_project_service(actor)?
+            .archive(ProjectArchiveParams {
+                initialized_project,
+                cleanup: params.cleanup,
+            })
+            .await
+            .map_err(service_status)?;
+        let mut project_store = self.project_store.lock().await;
+        project_store.delete(url).await.map_err(service_status)?;
+        project_store.save().map_err(service_status)?;
+        Ok(Response::new(archive.try_into().map_err(service_status)?))
+    }
+
+    async fn list_facets(
+        &self,
+        request: Request<proto::FacetListParams>,
+    ) -> Result<Response<proto::FacetMapKeyList>, Status> {
+        self.authorize(&request, ApiKeyScope::Read)?;
+        let params = request.into_inner();
+        let keys = self
+            .project_service()?
+            .list_facets(FacetListParams {
+                project_get_params: ProjectGetParams {
+                    project_url: params.project_url,
+                    read_strategy: ReadStrategy::default(),
+                },
+                labels: params.labels,
+            })
+            .await
+            .map_err(service_status)?;
+        Ok(Response::new(proto::FacetMapKeyList {
+            keys: keys.iter().map(proto::FacetMapKey::from).collect(),
+        }))
+    }
+
+    async fn get_facet(
+        &self,
+        request: Request<proto::FacetGetParams>,
+    ) -> Result<Response<proto::InitializedFacet>, Status> {
+        self.authorize(&request, ApiKeyScope::Read)?;
+        let params = request.into_inner();
+        let facet_map_key = params
+            .facet_map_key
+            .unwrap_or_default()
+            .try_into()
+            .map_err(service_status)?;
+        let facet = self
+            .project_service()?
+            .get_facet_with_content(FacetGetParams {
+                project_get_params: ProjectGetParams {
+                    project_url: params.project_url,
+                    read_strategy: ReadStrategy::default(),
+                },
+                facet_map_key,
+            })
+            .await
+            .map_err(service_status)?;
+        Ok(Response::new(facet.into()))
+    }
+
+    async fn list_outputs(
+        &self,
+        request: Request<proto::ProjectOutputsListParams>,
+    ) -> Result<Response<proto::ProjectOutputReferenceList>, Status> {
+        self.authorize(&request, ApiKeyScope::Read)?;
+        let params = request.into_inner();
+        let initialized_project = self.fetch_project(params.project_url.clone()).await?;
+        let outputs = self
+            .project_service()?
+            .outputs_list(ProjectOutputsListParams {
+                initialized_project,
+                release: params.release(),
+                workflow_run: params.workflow_run(),
+            })
+            .await
+            .map_err(service_status)?;
+        Ok(Response::new(proto::ProjectOutputReferenceList {
+            outputs: outputs.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    async fn get_output(
+        &self,
+        request: Request<proto::ProjectOutputGetParams>,
+    ) -> Result<Response<proto::ProjectOutput>, Status> {
+        self.authorize(&request, ApiKeyScope::Read)?;
+        let params = request.into_inner();
+        let release = params.release.unwrap_or_default();
+        let project_output_type =
+            params
+                .output_type
+                .parse::<ProjectOutputType>()
+                .map_err(|_| {
+                    Status::invalid_argument(format!("Unknown output type {}", params.output_type))
+                })?;
+        let initialized_project = self.fetch_project(release.project_url.clone()).await?;
+        let output = self
+            .project_service()?
+            .output_get(ProjectOutputGetParams {
+                initialized_project,
+                project_output_type,
+                project_output: params.name,
+                release: release.release(),
+                workflow_run: release.workflow_run(),
+            })
+            .await
+            .map_err(service_status)?;
+        Ok(Response::new(output.into()))
+    }
+}
+
+/// Serves the Skootrs gRPC API on `addr` until the server fails. Calls are only accepted with one of
+/// `api_keys`, and the server calls the Github API with `github_auth`. The changes made to projects are
+/// recorded in `audit_log`, and projects in `project_store`, which the REST API shares.
+///
+/// # Errors
+///
+/// Returns an error if the server can't be started.
+pub async fn run_server(
+    addr: SocketAddr,
+    github_auth: GithubAuth,
+    api_keys: Vec<ApiKeyConfig>,
+    audit_log: AuditLog,
+    project_store: Arc<Mutex<InMemoryProjectReferenceCache>>,
+) -> Result<(), SkootError> {
+    info!("Serving the gRPC API on {addr}");
+    Server::builder()
+        .add_service(SkootrsServer::new(SkootrsGrpc::new(
+            github_auth,
+            api_keys,
+            audit_log,
+            project_store,
+        )))
+        .serve(addr)
+        .await
+        .map_err(SkootError::other)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tonic::transport::Channel;
+
+    use super::*;
+    use crate::proto::skootrs_client::SkootrsClient;
+
+    fn service(project_store: InMemoryProjectReferenceCache) -> SkootrsGrpc {
+        SkootrsGrpc::new(
+            GithubAuth::default(),
+            vec![
+                ApiKeyConfig {
+                    name: "dashboard".to_string(),
+                    key: "read-key".to_string(),
+                    scopes: vec![ApiKeyScope::Read],
+                },
+                ApiKeyConfig {
+                    name: "ci".to_string(),
+                    key: "write-key".to_string(),
+                    scopes: vec![ApiKeyScope::Read, ApiKeyScope::Write],
+                },
+            ],
+            AuditLog::default(),
+            Arc::new(Mutex::new(project_store)),
+        )
+    }
+
+    fn project_store() -> InMemoryProjectReferenceCache {
+        InMemoryProjectReferenceCache::new(
+            std::env::temp_dir()
+                .join(format!("skootrs-grpc-cache-{}.json", std::process::id()))
+                .to_string_lossy()
+                .to_string(),
+        )
+    }
+
+    /// Serves the API on a free local port and returns a client connected to it.
+    async fn client(service: SkootrsGrpc) -> SkootrsClient<Channel> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(
+            Server::builder()
+                .add_service(SkootrsServer::new(service))
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+        SkootrsClient::connect(format!("http://{addr}"))
+            .await
+            .unwrap()
+    }
+
+    /// Returns a request with the API key in its metadata.
+    fn with_key<T>(message: T, key: &str) -> Request<T> {
+        let mut request = Request::new(message);
+        request
+            .metadata_mut()
+            .insert(API_KEY_METADATA, key.parse().unwrap());
+        request
+    }
+
+    #[test]
+    fn test_authorize() {
+        let service = service(project_store());
+        let request = Request::new(proto::ProjectListParams {});
+        assert_eq!(
+            service
+                .authorize(&request, ApiKeyScope::Read)
+                .unwrap_err()
+                .code(),
+            tonic::Code::Unauthenticated
+        );
+        let request = with_key(proto::ProjectListParams {}, "read-key");
+        assert_eq!(
+            service.authorize(&request, ApiKeyScope::Read).unwrap(),
+            "api_key:dashboard"
+        );
+        assert_eq!(
+            service
+                .authorize(&request, ApiKeyScope::Write)
+                .unwrap_err()
+                .code(),
+            tonic::Code::PermissionDenied
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_projects() {
+        let mut project_store = project_store();
+        for url in [
+            "https://github.com/testorg/payments",
+            "https://github.com/testorg/accounts",
+        ] {
+            project_store.set(url.to_string()).await.unwrap();
+        }
+        let mut client = client(service(project_store)).await;
+
+        let status = client
+            .list_projects(proto::ProjectListParams {})
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+
+        let project_list = client
+            .list_projects(with_key(proto::ProjectListParams {}, "read-key"))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(
+            project_list.project_urls,
+            vec![
+                "https://github.com/testorg/accounts",
+                "https://github.com/testorg/payments"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_project_rejected() {
+        let mut client = client(service(project_store())).await;
+
+        let status = client
+            .create_project(with_key(proto::ProjectCreateParams::default(), "read-key"))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+
+        // The parameters are checked before anything is created.
+        let status = client
+            .create_project(with_key(proto::ProjectCreateParams::default(), "write-key"))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert!(status.message().contains("repo_params is required"));
+    }
+}
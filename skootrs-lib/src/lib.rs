@@ -23,6 +23,8 @@
 //! that is managed by Skootrs to provide a secure-by-default project. This includes things like sets of files
 //! in the source code or calls to the repository API. For example the SECURITY.md file or the API call that
 //! enables branch protection would be facets.
-#![feature(array_try_map)]
 
 pub mod service;
+
+/// The version of Skootrs that's recorded in the commits and the state of the projects it manages.
+pub const SKOOTRS_VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -0,0 +1,617 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The audit log is an append-only JSONL file recording every change Skootrs makes to projects: who made it,
+//! when, with what parameters, and what it did, down to the API calls of the facets it applied. Each entry
+//! carries the hash of the entry before it, so `read` can tell when the log was tampered with.
+//!
+//! `AuditedProjectService` wraps a project service and records each of its changes. An entry that can't be
+//! written is logged instead of failing the change, since the change has already happened by then.
+
+#![allow(clippy::module_name_repetitions)]
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use tracing::error;
+
+use skootrs_model::skootrs::{
+    attestation::{AttestationVerification, AttestationVerifyParams},
+    audit_log::{
+        AuditLogConfig, AuditLogEntry, AuditLogFilter, AuditOperation, AuditOutcome, GENESIS_HASH,
+    },
+    facet::{
+        FacetAddParams, FacetDeleteParams, FacetDiff, FacetDiffParams, FacetHistoryEntry,
+        FacetHistoryGetParams, FacetPreviewParams, InitializedFacet, ProjectAudit,
+        SourceFileContent, SupportedFacetType,
+    },
+    plan::ProjectPlan,
+    DownloadedOutput, FacetGetParams, FacetListParams, FacetMapKey, InitializedProject,
+    InitializedSource, ProjectArchive, ProjectArchiveParams, ProjectCreateParams,
+    ProjectDeleteParams, ProjectGetParams, ProjectMirrorParams, ProjectOutput,
+    ProjectOutputGetParams, ProjectOutputReference, ProjectOutputsDownloadParams,
+    ProjectOutputsListParams, ProjectRelocateParams, ProjectRelocation, ProjectUpdateParams,
+    SkootError,
+};
+
+use super::project::ProjectService;
+
+/// How much of the log is read at a time while looking for its last entry.
+const TAIL_CHUNK_SIZE: u64 = 8 * 1024;
+
+/// The `AuditLog` struct appends the changes Skootrs makes to the audit log.
+#[derive(Debug, Clone, Default)]
+pub struct AuditLog {
+    /// The JSONL file entries are appended to, or `None` if nothing is logged.
+    pub path: Option<PathBuf>,
+    /// Who the changes are recorded as made by, e.g. `user:alice`.
+    pub actor: String,
+}
+
+impl AuditLog {
+    /// Returns the audit log in the `audit_log` config. Changes are recorded as made by the configured actor,
+    /// or by the user in the `USER` environment variable if there isn't one.
+    #[must_use]
+    pub fn new(config: &AuditLogConfig) -> Self {
+        Self {
+            path: config.path.as_ref().map(PathBuf::from),
+            actor: config.actor.clone().unwrap_or_else(|| {
+                format!(
+                    "user:{}",
+                    std::env::var("USER").unwrap_or_else(|_| "unknown".to_string())
+                )
+            }),
+        }
+    }
+
+    /// Returns the same audit log, recording changes as made by `actor` instead.
+    #[must_use]
+    pub fn with_actor(&self, actor: impl Into<String>) -> Self {
+        Self {
+            path: self.path.clone(),
+            actor: actor.into(),
+        }
+    }
+
+    /// Appends an entry for a change to the log, if there is one. `result` is what the change did if it
+    /// succeeded. Failures to write the entry are logged rather than returned.
+    pub fn record(
+        &self,
+        operation: AuditOperation,
+        project_url: &str,
+        parameters: Value,
+        result: Result<Value, &SkootError>,
+    ) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let (outcome, result) = match result {
+            Ok(result) => (AuditOutcome::Succeeded, result),
+            Err(error) => (AuditOutcome::Failed, json!({ "error": error.to_string() })),
+        };
+        let entry = AuditLogEntry {
+            sequence: 0,
+            timestamp: chrono::Utc::now(),
+            actor: self.actor.clone(),
+            operation,
+            project_url: project_url.to_string(),
+            parameters,
+            outcome,
+            result,
+            previous_hash: String::new(),
+            hash: String::new(),
+        };
+        if let Err(e) = append(path, entry) {
+            error!(
+                "Failed to record the {operation} of {project_url} in the audit log at {}: {e}",
+                path.display()
+            );
+        }
+    }
+}
+
+/// Appends an entry to the log at `path`, chained to the last entry already in it. The sequence number and
+/// hashes of `entry` are filled in.
+///
+/// # Errors
+///
+/// Returns an error if the log can't be read or written.
+pub fn append(path: &Path, mut entry: AuditLogEntry) -> Result<AuditLogEntry, SkootError> {
+    let mut file = OpenOptions::new()
+        .read(true)
+        .append(true)
+        .create(true)
+        .open(path)?;
+    // Other writers of the same log wait until the entry is written, so they can't fork the chain of hashes.
+    // The lock is released when the file is closed.
+    file.lock()?;
+    let previous = match last_line(&mut file)? {
+        Some(line) => Some(serde_json::from_str::<AuditLogEntry>(&line)?),
+        None => None,
+    };
+    entry.sequence = previous
+        .as_ref()
+        .map_or(1, |previous| previous.sequence + 1);
+    entry.previous_hash =
+        previous.map_or_else(|| GENESIS_HASH.to_string(), |previous| previous.hash);
+    entry.hash = hash(&entry)?;
+    let mut line = serde_json::to_string(&entry)?;
+    line.push('\n');
+    file.write_all(line.as_bytes())?;
+    file.sync_data()?;
+    Ok(entry)
+}
+
+/// Reads every entry of the log at `path`, checking that none of them were edited, removed or reordered. A
+/// log that doesn't exist yet has no entries.
+///
+/// # Errors
+///
+/// Returns an error if the log can't be read, or if the chain of hashes is broken.
+pub fn read(path: &Path) -> Result<Vec<AuditLogEntry>, SkootError> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(e.into()),
+    };
+    let mut entries: Vec<AuditLogEntry> = vec![];
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry: AuditLogEntry = serde_json::from_str(&line).map_err(|e| {
+            SkootError::from(format!(
+                "Line {} of the audit log isn't an entry: {e}",
+                index + 1
+            ))
+        })?;
+        let (expected_sequence, expected_previous_hash) =
+            entries.last().map_or((1, GENESIS_HASH), |previous| {
+                (previous.sequence + 1, previous.hash.as_str())
+            });
+        let tampered = if entry.sequence != expected_sequence {
+            Some(format!("expected entry {expected_sequence}"))
+        } else if entry.previous_hash != expected_previous_hash {
+            Some("it doesn't follow the entry before it".to_string())
+        } else if entry.hash != hash(&entry)? {
+            Some("its hash doesn't match its content".to_string())
+        } else {
+            None
+        };
+        if let Some(reason) = tampered {
+            return Err(SkootError::from(format!(
+                "The audit log was tampered with at entry {} on line {}: {reason}",
+                entry.sequence,
+                index + 1
+            )));
+        }
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+/// Reads the entries of the log at `path` that match the filter, checking the log like `read` does.
+///
+/// # Errors
+///
+/// Returns an error if the log can't be read, or if the chain of hashes is broken.
+pub fn list(path: &Path, filter: &AuditLogFilter) -> Result<Vec<AuditLogEntry>, SkootError> {
+    Ok(read(path)?
+        .into_iter()
+        .filter(|entry| filter.matches(entry))
+        .collect())
+}
+
+/// Returns the hex encoded SHA-256 hash of the entry's JSON with its `hash` left empty.
+fn hash(entry: &AuditLogEntry) -> Result<String, SkootError> {
+    let unhashed = AuditLogEntry {
+        hash: String::new(),
+        ..entry.clone()
+    };
+    Ok(format!(
+        "{:x}",
+        Sha256::digest(serde_json::to_vec(&unhashed)?)
+    ))
+}
+
+/// Returns the last line of the file, without reading more of it than that line. Logs grow with every change,
+/// so reading all of it for every entry would get slower over time.
+fn last_line(file: &mut File) -> Result<Option<String>, SkootError> {
+    let mut end = file.seek(SeekFrom::End(0))?;
+    let mut tail: Vec<u8> = vec![];
+    while end > 0 {
+        let start = end.saturating_sub(TAIL_CHUNK_SIZE);
+        let mut chunk = vec![0; usize::try_from(end - start).map_err(SkootError::other)?];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut chunk)?;
+        chunk.extend_from_slice(&tail);
+        tail = chunk;
+        end = start;
+        let content = tail.trim_ascii_end();
+        if let Some(newline) = content.iter().rposition(|byte| *byte == b'\n') {
+            return Ok(Some(
+                String::from_utf8_lossy(&content[newline + 1..]).into_owned(),
+            ));
+        }
+    }
+    let content = tail.trim_ascii_end();
+    Ok((!content.is_empty()).then(|| String::from_utf8_lossy(content).into_owned()))
+}
+
+/// Returns the parameters of a change for its entry. The state of the project the change is made to is left
+/// out, since the project's repo URL is recorded with the entry and the state can be large.
+fn parameters<P: Serialize>(params: &P) -> Value {
+    let mut parameters = serde_json::to_value(params).unwrap_or_default();
+    if let Some(parameters) = parameters.as_object_mut() {
+        parameters.remove("initialized_project");
+    }
+    parameters
+}
+
+/// Returns what a change did to a project's facets for its entry: the files of the source bundle facets and
+/// the API calls of the API bundle facets. Only the facets of `facet_types` are included if it's given.
+fn facets_result(
+    project: &InitializedProject,
+    facet_types: Option<&[SupportedFacetType]>,
+) -> Value {
+    let facets = project
+        .facets
+        .values()
+        .filter(|facet| facet_types.is_none_or(|types| types.contains(&facet.facet_type())))
+        .collect::<Vec<&InitializedFacet>>();
+    json!({ "facets": facets })
+}
+
+/// The `AuditedProjectService` struct wraps a project service and records every change it makes to projects
+/// in the audit log. Operations that only read are passed through without being recorded.
+#[derive(Debug)]
+pub struct AuditedProjectService<PS: ProjectService> {
+    pub project_service: PS,
+    pub audit_log: AuditLog,
+}
+
+impl<PS> ProjectService for AuditedProjectService<PS>
+where
+    PS: ProjectService + Send + Sync,
+{
+    async fn initialize(
+        &self,
+        params: ProjectCreateParams,
+    ) -> Result<InitializedProject, SkootError> {
+        let parameters = parameters(&params);
+        let project_url = params
+            .repo_params
+            .initialized_repo()
+            .map(|repo| repo.full_url())
+            .unwrap_or_default();
+        let result = self.project_service.initialize(params).await;
+        self.audit_log.record(
+            AuditOperation::ProjectCreate,
+            &project_url,
+            parameters,
+            result.as_ref().map(|project| facets_result(project, None)),
+        );
+        result
+    }
+
+    async fn plan(&self, params: ProjectCreateParams) -> Result<ProjectPlan, SkootError> {
+        self.project_service.plan(params).await
+    }
+
+    async fn get(&self, params: ProjectGetParams) -> Result<InitializedProject, SkootError> {
+        self.project_service.get(params).await
+    }
+
+    async fn get_facet_with_content(
+        &self,
+        params: FacetGetParams,
+    ) -> Result<InitializedFacet, SkootError> {
+        self.project_service.get_facet_with_content(params).await
+    }
+
+    async fn list_facets(&self, params: FacetListParams) -> Result<Vec<FacetMapKey>, SkootError> {
+        self.project_service.list_facets(params).await
+    }
+
+    async fn outputs_list(
+        &self,
+        params: ProjectOutputsListParams,
+    ) -> Result<Vec<ProjectOutputReference>, SkootError> {
+        self.project_service.outputs_list(params).await
+    }
+
+    async fn output_get(
+        &self,
+        params: ProjectOutputGetParams,
+    ) -> Result<ProjectOutput, SkootError> {
+        self.project_service.output_get(params).await
+    }
+
+    async fn outputs_download(
+        &self,
+        params: ProjectOutputsDownloadParams,
+    ) -> Result<Vec<DownloadedOutput>, SkootError> {
+        self.project_service.outputs_download(params).await
+    }
+
+    async fn output_verify(
+        &self,
+        params: AttestationVerifyParams,
+    ) -> Result<AttestationVerification, SkootError> {
+        self.project_service.output_verify(params).await
+    }
+
+    async fn update(&self, params: ProjectUpdateParams) -> Result<InitializedProject, SkootError> {
+        let parameters = parameters(&params);
+        let project_url = params.initialized_project.repo.full_url();
+        let facet_types = params.facet_types.clone();
+        let result = self.project_service.update(params).await;
+        self.audit_log.record(
+            AuditOperation::ProjectUpdate,
+            &project_url,
+            parameters,
+            result
+                .as_ref()
+                .map(|project| facets_result(project, facet_types.as_deref())),
+        );
+        result
+    }
+
+    async fn add_facet(&self, params: FacetAddParams) -> Result<InitializedProject, SkootError> {
+        let parameters = parameters(&params);
+        let project_url = params.initialized_project.repo.full_url();
+        let facet_type = params.facet_type.clone();
+        let result = self.project_service.add_facet(params).await;
+        self.audit_log.record(
+            AuditOperation::FacetAdd,
+            &project_url,
+            parameters,
+            result
+                .as_ref()
+                .map(|project| facets_result(project, Some(&[facet_type]))),
+        );
+        result
+    }
+
+    async fn preview_facet(
+        &self,
+        params: FacetPreviewParams,
+    ) -> Result<Vec<SourceFileContent>, SkootError> {
+        self.project_service.preview_facet(params).await
+    }
+
+    async fn diff_facet(&self, params: FacetDiffParams) -> Result<FacetDiff, SkootError> {
+        self.project_service.diff_facet(params).await
+    }
+
+    async fn delete_facet(
+        &self,
+        params: FacetDeleteParams,
+    ) -> Result<InitializedProject, SkootError> {
+        let parameters = parameters(&params);
+        let project_url = params.initialized_project.repo.full_url();
+        let result = self.project_service.delete_facet(params).await;
+        self.audit_log.record(
+            AuditOperation::FacetDelete,
+            &project_url,
+            parameters,
+            result.as_ref().map(|_| Value::Null),
+        );
+        result
+    }
+
+    async fn get_facet_history(
+        &self,
+        params: FacetHistoryGetParams,
+    ) -> Result<FacetHistoryEntry, SkootError> {
+        self.project_service.get_facet_history(params).await
+    }
+
+    async fn audit(&self, params: ProjectGetParams) -> Result<ProjectAudit, SkootError> {
+        self.project_service.audit(params).await
+    }
+
+    async fn verify_state(&self, source: InitializedSource) -> Result<ProjectAudit, SkootError> {
+        self.project_service.verify_state(source).await
+    }
+
+    async fn link_mirror(
+        &self,
+        params: ProjectMirrorParams,
+    ) -> Result<InitializedProject, SkootError> {
+        let parameters = parameters(&params);
+        let project_url = params.initialized_project.repo.full_url();
+        let result = self.project_service.link_mirror(params).await;
+        self.audit_log.record(
+            AuditOperation::MirrorLink,
+            &project_url,
+            parameters,
+            result.as_ref().map(|_| Value::Null),
+        );
+        result
+    }
+
+    async fn relocate(
+        &self,
+        params: ProjectRelocateParams,
+    ) -> Result<ProjectRelocation, SkootError> {
+        let parameters = parameters(&params);
+        let project_url = params.initialized_project.repo.full_url();
+        let result = self.project_service.relocate(params).await;
+        self.audit_log.record(
+            AuditOperation::ProjectRelocate,
+            &project_url,
+            parameters,
+            result
+                .as_ref()
+                .map(|relocation| json!({ "new_url": relocation.new_url })),
+        );
+        result
+    }
+
+    async fn archive(&self, params: ProjectArchiveParams) -> Result<ProjectArchive, SkootError> {
+        let parameters = parameters(&params);
+        let project_url = params.initialized_project.repo.full_url();
+        let result = self.project_service.archive(params).await;
+        self.audit_log.record(
+            AuditOperation::ProjectArchive,
+            &project_url,
+            parameters,
+            result
+                .as_ref()
+                .map(|archive| serde_json::to_value(archive).unwrap_or_default()),
+        );
+        result
+    }
+
+    async fn delete(&self, params: ProjectDeleteParams) -> Result<String, SkootError> {
+        let parameters = parameters(&params);
+        let project_url = params.initialized_project.repo.full_url();
+        let result = self.project_service.delete(params).await;
+        self.audit_log.record(
+            AuditOperation::ProjectDelete,
+            &project_url,
+            parameters,
+            result.as_ref().map(|_| Value::Null),
+        );
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempdir::TempDir;
+
+    use super::*;
+
+    fn record(audit_log: &AuditLog, operation: AuditOperation, project_url: &str) {
+        audit_log.record(
+            operation,
+            project_url,
+            json!({ "cleanup": false }),
+            Ok(Value::Null),
+        );
+    }
+
+    #[test]
+    fn test_append_and_read() {
+        let temp_dir = TempDir::new("test_audit_log").unwrap();
+        let path = temp_dir.path().join("audit.jsonl");
+        let audit_log = AuditLog::new(&AuditLogConfig {
+            path: Some(path.to_string_lossy().into_owned()),
+            actor: Some("user:alice".to_string()),
+        });
+        record(
+            &audit_log,
+            AuditOperation::ProjectCreate,
+            "https://github.com/org/a",
+        );
+        record(
+            &audit_log.with_actor("api_key:ci"),
+            AuditOperation::ProjectArchive,
+            "https://github.com/org/a",
+        );
+        audit_log.record(
+            AuditOperation::FacetAdd,
+            "https://github.com/org/b",
+            json!({ "facet_type": "Readme" }),
+            Err(&SkootError::from("Unable to push")),
+        );
+
+        let entries = read(&path).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].previous_hash, GENESIS_HASH);
+        assert_eq!(entries[1].previous_hash, entries[0].hash);
+        assert_eq!(entries[2].sequence, 3);
+        assert_eq!(entries[2].outcome, AuditOutcome::Failed);
+        assert_eq!(entries[2].result, json!({ "error": "Unable to push" }));
+
+        let archived = list(
+            &path,
+            &AuditLogFilter {
+                actor: Some("api_key:ci".to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(archived.len(), 1);
+        assert_eq!(archived[0].operation, AuditOperation::ProjectArchive);
+
+        // Editing an entry breaks the chain, even if its hash is recomputed.
+        let log = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, log.replace("api_key:ci", "api_key:other")).unwrap();
+        assert!(read(&path).is_err());
+        let mut edited = read_unchecked(&path);
+        edited[1].hash = hash(&edited[1]).unwrap();
+        write_unchecked(&path, &edited);
+        assert!(read(&path).is_err());
+
+        // Removing an entry breaks the chain too.
+        let mut removed = read_unchecked(&path);
+        removed.remove(0);
+        write_unchecked(&path, &removed);
+        assert!(read(&path).is_err());
+    }
+
+    #[test]
+    fn test_read_missing_log() {
+        let temp_dir = TempDir::new("test_audit_log").unwrap();
+        assert!(read(&temp_dir.path().join("audit.jsonl"))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_last_line() {
+        let temp_dir = TempDir::new("test_audit_log").unwrap();
+        let path = temp_dir.path().join("log");
+        // The last line is longer than a chunk, so it spans several reads.
+        let long_line = "x".repeat(usize::try_from(TAIL_CHUNK_SIZE).unwrap() * 2 + 10);
+        std::fs::write(&path, format!("first\n{long_line}\n")).unwrap();
+        let mut file = File::open(&path).unwrap();
+        assert_eq!(last_line(&mut file).unwrap(), Some(long_line));
+
+        std::fs::write(&path, "only\n").unwrap();
+        let mut file = File::open(&path).unwrap();
+        assert_eq!(last_line(&mut file).unwrap(), Some("only".to_string()));
+
+        std::fs::write(&path, "").unwrap();
+        let mut file = File::open(&path).unwrap();
+        assert_eq!(last_line(&mut file).unwrap(), None);
+    }
+
+    fn read_unchecked(path: &Path) -> Vec<AuditLogEntry> {
+        std::fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    fn write_unchecked(path: &Path, entries: &[AuditLogEntry]) {
+        let log = entries
+            .iter()
+            .map(|entry| serde_json::to_string(entry).unwrap() + "\n")
+            .collect::<String>();
+        std::fs::write(path, log).unwrap();
+    }
+}
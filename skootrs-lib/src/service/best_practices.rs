@@ -0,0 +1,104 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![allow(clippy::module_name_repetitions)]
+
+use reqwest::StatusCode;
+use tracing::info;
+
+use skootrs_model::skootrs::{
+    best_practices::{BestPracticesBadge, BestPracticesConfig},
+    InitializedRepo, SkootError,
+};
+
+/// The `BestPracticesService` trait provides an interface for looking up a project's OpenSSF Best Practices
+/// Badge. Projects are registered for a badge by their maintainers on the badge site, this is how the badge
+/// is found and its status checked afterwards.
+pub trait BestPracticesService {
+    /// Finds the badge of the project with the repo, or `None` if the project isn't registered.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the badge site can't be queried.
+    fn find(
+        &self,
+        initialized_repo: &InitializedRepo,
+    ) -> impl std::future::Future<Output = Result<Option<BestPracticesBadge>, SkootError>> + Send;
+
+    /// Gets the current status of a badge.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there's no badge with the ID or it can't be fetched.
+    fn get(
+        &self,
+        id: u64,
+    ) -> impl std::future::Future<Output = Result<BestPracticesBadge, SkootError>> + Send;
+}
+
+/// The `LocalBestPracticesService` struct provides an implementation of the `BestPracticesService` trait that
+/// queries the badge site's API.
+#[derive(Debug, Default)]
+pub struct LocalBestPracticesService {
+    /// Where the badges are looked up.
+    pub config: BestPracticesConfig,
+}
+
+impl BestPracticesService for LocalBestPracticesService {
+    async fn find(
+        &self,
+        initialized_repo: &InitializedRepo,
+    ) -> Result<Option<BestPracticesBadge>, SkootError> {
+        let url = format!(
+            "{}/projects.json",
+            self.config.api_url.trim_end_matches('/')
+        );
+        info!(
+            "Looking up the OpenSSF Best Practices Badge of {}",
+            initialized_repo.full_url()
+        );
+        // The API matches the URL against the repo and home page URLs the project was registered with.
+        let badges: Vec<BestPracticesBadge> = serde_json::from_str(
+            &reqwest::Client::new()
+                .get(&url)
+                .query(&[("url", initialized_repo.full_url())])
+                .send()
+                .await
+                .map_err(SkootError::other)?
+                .error_for_status()
+                .map_err(SkootError::other)?
+                .text()
+                .await
+                .map_err(SkootError::other)?,
+        )?;
+        Ok(badges.into_iter().next())
+    }
+
+    async fn get(&self, id: u64) -> Result<BestPracticesBadge, SkootError> {
+        let url = format!("{}.json", self.config.project_url(id));
+        let response = reqwest::get(&url).await.map_err(SkootError::other)?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Err(format!("There's no OpenSSF Best Practices Badge with ID {id}").into());
+        }
+        Ok(serde_json::from_str(
+            &response
+                .error_for_status()
+                .map_err(SkootError::other)?
+                .text()
+                .await
+                .map_err(SkootError::other)?,
+        )?)
+    }
+}
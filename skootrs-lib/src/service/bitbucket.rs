@@ -0,0 +1,256 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! `BitbucketClient` is how Skootrs calls the Bitbucket Cloud REST API for projects hosted on Bitbucket. It
+//! authenticates with an app password and username, or with an access token on its own.
+
+#![allow(clippy::module_name_repetitions)]
+
+use reqwest::{header::CONTENT_TYPE, Method, RequestBuilder, StatusCode};
+use serde::{de::DeserializeOwned, Serialize};
+use tracing::debug;
+
+use skootrs_model::skootrs::{
+    bitbucket::{BitbucketConfig, BranchRestriction},
+    InitializedBitbucketRepo, SkootError,
+};
+
+/// The environment variable the token is read from if the config doesn't have one.
+const BITBUCKET_TOKEN_ENV: &str = "BITBUCKET_TOKEN";
+
+/// A page of a Bitbucket list endpoint.
+#[derive(serde::Deserialize)]
+struct Page<T> {
+    values: Vec<T>,
+    /// The URL of the next page, if there is one.
+    next: Option<String>,
+}
+
+/// A branch restriction as returned by the Bitbucket API.
+#[derive(serde::Deserialize, Clone, Debug)]
+pub struct ExistingBranchRestriction {
+    /// The ID of the restriction, which it's deleted by.
+    pub id: u64,
+    /// The kind of the restriction.
+    pub kind: String,
+    /// The glob of the branches the restriction applies to.
+    #[serde(default)]
+    pub pattern: String,
+    /// The number the restriction takes, if the kind takes one.
+    #[serde(default)]
+    pub value: Option<u8>,
+}
+
+impl ExistingBranchRestriction {
+    /// Returns true if this is the restriction that was requested.
+    #[must_use]
+    pub fn matches(&self, restriction: &BranchRestriction) -> bool {
+        self.kind == restriction.kind
+            && self.pattern == restriction.pattern
+            && (restriction.value.is_none() || self.value == restriction.value)
+    }
+}
+
+/// The `BitbucketClient` struct makes authenticated calls to the Bitbucket Cloud API.
+#[derive(Debug, Default)]
+pub struct BitbucketClient {
+    /// Where the API is and how it's authenticated to.
+    pub config: BitbucketConfig,
+}
+
+impl BitbucketClient {
+    /// Returns the route of a repo, which the routes of everything in the repo are under.
+    #[must_use]
+    pub fn repo_route(repo: &InitializedBitbucketRepo) -> String {
+        format!("/repositories/{}/{}", repo.workspace, repo.name)
+    }
+
+    fn request(&self, method: Method, url: &str) -> Result<RequestBuilder, SkootError> {
+        let token = self
+            .config
+            .token
+            .clone()
+            .or_else(|| std::env::var(BITBUCKET_TOKEN_ENV).ok())
+            .ok_or_else(|| {
+                SkootError::Config(format!(
+                    "A Bitbucket token is needed, set bitbucket.token or {BITBUCKET_TOKEN_ENV}"
+                ))
+            })?;
+        let url = if url.starts_with("http") {
+            url.to_string()
+        } else {
+            format!("{}{url}", self.config.api_url.trim_end_matches('/'))
+        };
+        debug!("{method} {url}");
+        let request = reqwest::Client::new().request(method, url);
+        Ok(match &self.config.username {
+            Some(username) => request.basic_auth(username, Some(token)),
+            None => request.bearer_auth(token),
+        })
+    }
+
+    async fn send(request: RequestBuilder, route: &str) -> Result<String, SkootError> {
+        let response = request.send().await.map_err(SkootError::other)?;
+        let status = response.status();
+        let text = response.text().await.map_err(SkootError::other)?;
+        if status == StatusCode::NOT_FOUND {
+            return Err(SkootError::NotFound(format!("{route} isn't on Bitbucket")));
+        }
+        if !status.is_success() {
+            return Err(
+                format!("Bitbucket API call to {route} failed with {status}: {text}").into(),
+            );
+        }
+        Ok(text)
+    }
+
+    /// Gets a route and returns the raw response, e.g. the content of a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SkootError::NotFound` if the route doesn't exist, or an error if the call fails.
+    pub async fn get_text(&self, route: &str) -> Result<String, SkootError> {
+        Self::send(self.request(Method::GET, route)?, route).await
+    }
+
+    /// Gets a route and deserializes the response.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SkootError::NotFound` if the route doesn't exist, or an error if the call fails.
+    pub async fn get<T: DeserializeOwned>(&self, route: &str) -> Result<T, SkootError> {
+        Ok(serde_json::from_str(&self.get_text(route).await?)?)
+    }
+
+    /// Gets every page of a list route.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the pages can't be fetched.
+    pub async fn get_all<T: DeserializeOwned>(&self, route: &str) -> Result<Vec<T>, SkootError> {
+        let mut values = vec![];
+        let mut next = Some(format!("{route}?pagelen=100"));
+        while let Some(url) = next {
+            let page: Page<T> = self.get(&url).await?;
+            values.extend(page.values);
+            next = page.next;
+        }
+        Ok(values)
+    }
+
+    /// Posts a body to a route and deserializes the response.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the call fails.
+    pub async fn post<T: DeserializeOwned, B: Serialize + Sync>(
+        &self,
+        route: &str,
+        body: &B,
+    ) -> Result<T, SkootError> {
+        let request = self
+            .request(Method::POST, route)?
+            .header(CONTENT_TYPE, "application/json")
+            .body(serde_json::to_string(body)?);
+        Ok(serde_json::from_str(&Self::send(request, route).await?)?)
+    }
+
+    /// Deletes a route.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the call fails.
+    pub async fn delete(&self, route: &str) -> Result<(), SkootError> {
+        Self::send(self.request(Method::DELETE, route)?, route).await?;
+        Ok(())
+    }
+
+    /// Lists the branch restrictions of a repo.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the restrictions can't be listed.
+    pub async fn branch_restrictions(
+        &self,
+        repo: &InitializedBitbucketRepo,
+    ) -> Result<Vec<ExistingBranchRestriction>, SkootError> {
+        self.get_all(&format!("{}/branch-restrictions", Self::repo_route(repo)))
+            .await
+    }
+
+    /// Adds the branch restrictions a repo doesn't have yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the restrictions can't be listed or one can't be added.
+    pub async fn enable_branch_restrictions(
+        &self,
+        repo: &InitializedBitbucketRepo,
+        restrictions: &[BranchRestriction],
+    ) -> Result<(), SkootError> {
+        let existing = self.branch_restrictions(repo).await?;
+        let route = format!("{}/branch-restrictions", Self::repo_route(repo));
+        for restriction in restrictions {
+            if existing.iter().any(|e| e.matches(restriction)) {
+                continue;
+            }
+            let _created: serde_json::Value = self.post(&route, restriction).await?;
+        }
+        Ok(())
+    }
+
+    /// Removes the branch restrictions of a repo that match the ones given.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the restrictions can't be listed or one can't be removed.
+    pub async fn remove_branch_restrictions(
+        &self,
+        repo: &InitializedBitbucketRepo,
+        restrictions: &[BranchRestriction],
+    ) -> Result<(), SkootError> {
+        let route = format!("{}/branch-restrictions", Self::repo_route(repo));
+        for existing in self.branch_restrictions(repo).await? {
+            if restrictions.iter().any(|r| existing.matches(r)) {
+                self.delete(&format!("{route}/{}", existing.id)).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_existing_branch_restriction_matches() {
+        let existing = ExistingBranchRestriction {
+            id: 1,
+            kind: "require_approvals_to_merge".to_string(),
+            pattern: "main".to_string(),
+            value: Some(2),
+        };
+        let requested = |value| BranchRestriction {
+            kind: "require_approvals_to_merge".to_string(),
+            branch_match_kind: "glob".to_string(),
+            pattern: "main".to_string(),
+            value,
+        };
+        assert!(existing.matches(&requested(Some(2))));
+        assert!(!existing.matches(&requested(Some(1))));
+        assert!(existing.matches(&requested(None)));
+    }
+}
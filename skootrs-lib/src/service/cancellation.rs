@@ -0,0 +1,307 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Operations can be time-boxed and cancelled. An operation is run with `run`, which scopes a
+//! `CancellationToken` and an optional deadline to it. Once the operation is cancelled or times out its future
+//! is dropped at the next await point, external commands started through `CancellableCommand` are killed, and
+//! the cleanups the services registered with `on_cancel` undo the partial state the operation left behind,
+//! e.g. a half finished clone. Outside of `run` nothing can be cancelled and the helpers behave like their
+//! plain counterparts.
+
+use std::{
+    error::Error,
+    fmt,
+    future::Future,
+    io::Read,
+    path::Path,
+    process::{Child, Command, Output, Stdio},
+    sync::{Arc, Mutex},
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use skootrs_model::skootrs::SkootError;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, warn};
+
+/// How often a running command is checked for cancellation.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// The `CancellationError` enum represents the ways an operation can be stopped before it completes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CancellationError {
+    /// The operation was cancelled, e.g. by the user pressing Ctrl-C.
+    Cancelled,
+    /// The operation didn't complete within its timeout.
+    TimedOut(Duration),
+}
+
+impl fmt::Display for CancellationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cancelled => write!(f, "Operation was cancelled"),
+            Self::TimedOut(timeout) => {
+                write!(f, "Operation timed out after {}s", timeout.as_secs_f64())
+            }
+        }
+    }
+}
+
+impl Error for CancellationError {}
+
+/// Returns true if the error is the result of an operation being cancelled or timing out.
+#[must_use]
+pub fn is_cancellation(error: &SkootError) -> bool {
+    error.downcast_ref::<CancellationError>().is_some()
+}
+
+type Cleanup = Box<dyn FnOnce() + Send>;
+
+#[derive(Clone)]
+struct OperationContext {
+    token: CancellationToken,
+    deadline: Option<Instant>,
+    cleanups: Arc<Mutex<Vec<Cleanup>>>,
+}
+
+impl OperationContext {
+    fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled() || self.deadline.is_some_and(|d| Instant::now() >= d)
+    }
+}
+
+tokio::task_local! {
+    static OPERATION: OperationContext;
+}
+
+/// Runs an operation until it completes, `token` is cancelled, or `timeout` elapses. If the operation is
+/// stopped, the cleanups registered during it are run in reverse order and an error with a
+/// `CancellationError` is returned.
+///
+/// # Errors
+///
+/// Returns the operation's error, or a `CancellationError` if the operation was cancelled or timed out.
+pub async fn run<T, F>(
+    token: CancellationToken,
+    timeout: Option<Duration>,
+    operation: F,
+) -> Result<T, SkootError>
+where
+    F: Future<Output = Result<T, SkootError>>,
+{
+    let context = OperationContext {
+        token: token.clone(),
+        deadline: timeout.map(|t| Instant::now() + t),
+        cleanups: Arc::default(),
+    };
+    let cleanups = context.cleanups.clone();
+    let result = OPERATION
+        .scope(context, async {
+            tokio::select! {
+                result = operation => result,
+                () = token.cancelled() => Err(SkootError::other(CancellationError::Cancelled)),
+                () = sleep_for(timeout) => Err(SkootError::other(CancellationError::Cancelled)),
+            }
+        })
+        .await;
+
+    let pending_cleanups = std::mem::take(&mut *cleanups.lock().map_err(|e| e.to_string())?);
+    match result {
+        Err(error) if is_cancellation(&error) => {
+            // Errors raised by killed commands can't tell a timeout from a cancellation, so the reason is
+            // worked out from the token once the operation has stopped.
+            let error = match timeout {
+                Some(timeout) if !token.is_cancelled() => CancellationError::TimedOut(timeout),
+                _ => CancellationError::Cancelled,
+            };
+            warn!("{error}, cleaning up");
+            for cleanup in pending_cleanups.into_iter().rev() {
+                cleanup();
+            }
+            Err(SkootError::other(error))
+        }
+        result => result,
+    }
+}
+
+async fn sleep_for(timeout: Option<Duration>) {
+    match timeout {
+        Some(timeout) => tokio::time::sleep(timeout).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Registers a cleanup that undoes partial state if the current operation is cancelled or times out. Cleanups
+/// are dropped without running when the operation completes, and outside of `run` this does nothing.
+pub fn on_cancel(cleanup: impl FnOnce() + Send + 'static) {
+    let _ = OPERATION.try_with(|context| {
+        if let Ok(mut cleanups) = context.cleanups.lock() {
+            cleanups.push(Box::new(cleanup));
+        }
+    });
+}
+
+/// Checks whether the current operation was cancelled or timed out. This is used before steps that can't be
+/// undone, like pushing to a remote, so they aren't started by an operation that is already stopping.
+///
+/// # Errors
+///
+/// Returns a `CancellationError` if the current operation was cancelled or timed out.
+pub fn check() -> Result<(), SkootError> {
+    let cancelled = OPERATION
+        .try_with(OperationContext::is_cancelled)
+        .unwrap_or(false);
+    if cancelled {
+        return Err(SkootError::other(CancellationError::Cancelled));
+    }
+    Ok(())
+}
+
+/// The `CancellableCommand` trait runs external commands so that they are killed when the current operation
+/// is cancelled or times out.
+pub trait CancellableCommand {
+    /// Runs the command to completion and collects its output like `Command::output`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the command can't be run, or a `CancellationError` if it was killed because the
+    /// current operation was cancelled or timed out.
+    fn cancellable_output(&mut self) -> Result<Output, SkootError>;
+}
+
+impl CancellableCommand for Command {
+    fn cancellable_output(&mut self) -> Result<Output, SkootError> {
+        let Ok(context) = OPERATION.try_with(Clone::clone) else {
+            return Ok(self.output()?);
+        };
+        check()?;
+        let mut child = self
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let stdout = read_in_background(child.stdout.take());
+        let stderr = read_in_background(child.stderr.take());
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if context.is_cancelled() {
+                kill(self, &mut child)?;
+                return Err(SkootError::other(CancellationError::Cancelled));
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        };
+        Ok(Output {
+            status,
+            stdout: stdout.join().map_err(|_| "Failed to read stdout")??,
+            stderr: stderr.join().map_err(|_| "Failed to read stderr")??,
+        })
+    }
+}
+
+fn read_in_background<R: Read + Send + 'static>(
+    pipe: Option<R>,
+) -> JoinHandle<std::io::Result<Vec<u8>>> {
+    std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            pipe.read_to_end(&mut buf)?;
+        }
+        Ok(buf)
+    })
+}
+
+fn kill(command: &Command, child: &mut Child) -> Result<(), SkootError> {
+    debug!("Killing cancelled command {:?}", command.get_program());
+    child.kill()?;
+    child.wait()?;
+    // A killed git command leaves its index lock behind, which would make every later git command in the
+    // repo fail.
+    if command.get_program() == "git" {
+        let index_lock = command
+            .get_current_dir()
+            .unwrap_or_else(|| Path::new("."))
+            .join(".git")
+            .join("index.lock");
+        if index_lock.exists() {
+            std::fs::remove_file(&index_lock)?;
+            debug!("Removed {:?}", &index_lock);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_run_completes() {
+        let cleaned_up = Arc::new(AtomicBool::new(false));
+        let flag = cleaned_up.clone();
+        let output = run(CancellationToken::new(), None, async move {
+            on_cancel(move || flag.store(true, Ordering::SeqCst));
+            Command::new("echo").arg("hello").cancellable_output()
+        })
+        .await
+        .unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "hello");
+        assert!(!cleaned_up.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_run_times_out() {
+        let cleaned_up = Arc::new(AtomicBool::new(false));
+        let flag = cleaned_up.clone();
+        let started = Instant::now();
+        let err = run(
+            CancellationToken::new(),
+            Some(Duration::from_millis(200)),
+            async move {
+                on_cancel(move || flag.store(true, Ordering::SeqCst));
+                Command::new("sleep").arg("10").cancellable_output()
+            },
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<CancellationError>(),
+            Some(&CancellationError::TimedOut(Duration::from_millis(200)))
+        );
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(cleaned_up.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_run_cancelled() {
+        let token = CancellationToken::new();
+        token.cancel();
+        let err = run(token, None, async {
+            check()?;
+            Ok(())
+        })
+        .await
+        .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<CancellationError>(),
+            Some(&CancellationError::Cancelled)
+        );
+        // Outside of an operation nothing is cancelled.
+        assert!(check().is_ok());
+    }
+}
@@ -0,0 +1,127 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! External commands like `git push` or `go mod init` report failure through their exit status, which is
+//! easy to forget to check. `CommandRunner::run_checked` runs a command the same way as `cancellable_output`,
+//! and turns an unsuccessful exit into a `CommandError` carrying the command's stderr.
+
+use std::{
+    error::Error,
+    fmt,
+    process::{Command, Output},
+};
+
+use skootrs_model::skootrs::SkootError;
+use tracing::debug;
+
+use super::cancellation::CancellableCommand;
+
+/// The `CommandError` struct represents an external command that exited unsuccessfully.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandError {
+    /// The command line that was run, e.g. `git push`.
+    pub command: String,
+    /// The exit code of the command, or `None` if it was killed by a signal.
+    pub code: Option<i32>,
+    /// What the command wrote to stderr, trimmed.
+    pub stderr: String,
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.code {
+            Some(code) => write!(f, "`{}` failed with exit code {code}", self.command)?,
+            None => write!(f, "`{}` was killed by a signal", self.command)?,
+        }
+        if !self.stderr.is_empty() {
+            write!(f, ": {}", self.stderr)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for CommandError {}
+
+/// The `CommandRunner` trait runs external commands and checks that they succeeded.
+pub trait CommandRunner {
+    /// Runs the command to completion like `cancellable_output`, and checks its exit status.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `CommandError` if the command exits unsuccessfully, an error if it can't be run, or a
+    /// `CancellationError` if it was killed because the current operation was cancelled or timed out.
+    fn run_checked(&mut self) -> Result<Output, SkootError>;
+}
+
+impl CommandRunner for Command {
+    fn run_checked(&mut self) -> Result<Output, SkootError> {
+        let command = command_line(self);
+        debug!("Running `{command}`");
+        let output = self.cancellable_output()?;
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        if !output.status.success() {
+            return Err(SkootError::other(CommandError {
+                command,
+                code: output.status.code(),
+                stderr,
+            }));
+        }
+        // Commands like git report progress on stderr even when they succeed.
+        if !stderr.is_empty() {
+            debug!("`{command}` stderr: {stderr}");
+        }
+        Ok(output)
+    }
+}
+
+fn command_line(command: &Command) -> String {
+    std::iter::once(command.get_program())
+        .chain(command.get_args())
+        .map(|arg| arg.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_checked() {
+        let output = Command::new("echo").arg("hello").run_checked().unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "hello");
+
+        let err = Command::new("sh")
+            .args(["-c", "echo oops >&2; exit 3"])
+            .run_checked()
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<CommandError>(),
+            Some(&CommandError {
+                command: "sh -c echo oops >&2; exit 3".to_string(),
+                code: Some(3),
+                stderr: "oops".to_string(),
+            })
+        );
+        assert_eq!(
+            err.to_string(),
+            "`sh -c echo oops >&2; exit 3` failed with exit code 3: oops"
+        );
+
+        assert!(Command::new("skootrs-missing-command")
+            .run_checked()
+            .is_err());
+    }
+}
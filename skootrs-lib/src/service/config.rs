@@ -0,0 +1,767 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Config values can reference environment variables with `${NAME}` or the contents of a secret file with
+//! `file:/path/to/secret`, so secrets like the Github token don't have to be written into the config itself.
+//! `ConfigResolver` expands the references when the config is loaded and remembers the values they expanded to,
+//! so they can be redacted from anything that is logged or printed afterwards.
+//!
+//! A config is loaded in layers, each overriding the ones before it: the user's own config in
+//! `~/.config/skootrs/config.yaml`, the config file, the `SKOOTRS_*` environment variables in `ENV_OVERRIDES`,
+//! and `key=value` overrides given on the command line. The resolver remembers which layer each value came
+//! from, so `skootrs config list` can show where a value is set.
+
+#![allow(clippy::module_name_repetitions)]
+
+use std::{collections::BTreeMap, fmt, path::PathBuf};
+
+use serde::{de::DeserializeOwned, Serialize};
+use serde_yaml::{Mapping, Value};
+use skootrs_model::skootrs::SkootError;
+
+/// The prefix of a config value that is read from a file, e.g. `file:/run/secrets/github_token`.
+const FILE_PREFIX: &str = "file:";
+
+/// What resolved secret values are replaced with when they are redacted.
+const REDACTED: &str = "<redacted>";
+
+/// The path of the user's own config in their config directory.
+const USER_CONFIG_FILE: &str = "skootrs/config.yaml";
+
+/// The environment variables that override a single config value, and the key of the value each overrides.
+/// Values that are YAML mappings or lists, like `{backend: json, path: ./skootrs.json}`, are parsed, anything
+/// else is taken as a string.
+pub const ENV_OVERRIDES: &[(&str, &str)] = &[
+    ("SKOOTRS_LOCAL_PROJECT_PATH", "local_project_path"),
+    ("SKOOTRS_DEFAULT_ORG", "defaults.organization"),
+    ("SKOOTRS_TEMPLATE_DIR", "template_dir"),
+    ("SKOOTRS_STATE_STORE", "statestore"),
+    ("SKOOTRS_GITHUB_AUTH", "github_auth"),
+];
+
+/// A file a layer of the config is read from.
+#[derive(Clone, Debug)]
+pub struct ConfigFile {
+    /// The path of the file.
+    pub path: PathBuf,
+    /// Whether it's an error for the file not to exist. Files that aren't required are skipped if they don't.
+    pub required: bool,
+}
+
+/// A layer of the config read from a file. A file that doesn't exist is an empty layer.
+#[derive(Clone, Debug)]
+pub struct ConfigLayer {
+    /// The path of the file.
+    pub path: PathBuf,
+    /// The YAML in the file, with its references not yet expanded.
+    pub value: Value,
+}
+
+impl ConfigLayer {
+    /// Reads the layers of the config files in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a required file doesn't exist or a file isn't valid YAML.
+    pub fn read(files: &[ConfigFile]) -> Result<Vec<Self>, SkootError> {
+        files
+            .iter()
+            .map(|file| {
+                let path = file.path.display();
+                let mut value = Value::Mapping(Mapping::new());
+                if file.required || file.path.exists() {
+                    let contents = std::fs::read_to_string(&file.path)
+                        .map_err(|e| format!("Failed to read config {path}: {e}"))?;
+                    let layer: Value = serde_yaml::from_str(&contents)
+                        .map_err(|e| format!("Failed to parse config {path}: {e}"))?;
+                    // An empty file is an empty layer.
+                    if !layer.is_null() {
+                        value = layer;
+                    }
+                }
+                Ok(Self {
+                    path: file.path.clone(),
+                    value,
+                })
+            })
+            .collect()
+    }
+
+    /// Sets the value of a dotted key like `defaults.organization` in the layer. The value is parsed as YAML.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value isn't valid YAML.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), SkootError> {
+        let value = serde_yaml::from_str(value)
+            .map_err(|e| format!("Failed to parse the value of {key}: {e}"))?;
+        set(&mut self.value, key, value);
+        Ok(())
+    }
+
+    /// Writes the layer back to its file, creating the directory it's in if needed. Comments in the file
+    /// aren't kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be written.
+    pub fn write(&self) -> Result<(), SkootError> {
+        let path = self.path.display();
+        if let Some(dir) = self.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create the directory of {path}: {e}"))?;
+        }
+        std::fs::write(
+            &self.path,
+            serde_yaml::to_string(&self.value).map_err(SkootError::serialization)?,
+        )
+        .map_err(|e| format!("Failed to write config {path}: {e}").into())
+    }
+}
+
+/// Where the value of a config option came from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConfigSource {
+    /// None of the layers set it.
+    Default,
+    /// A config file.
+    File(PathBuf),
+    /// One of the environment variables in `ENV_OVERRIDES`.
+    Env(&'static str),
+    /// A `key=value` override on the command line.
+    Override,
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Default => write!(f, "default"),
+            Self::File(path) => write!(f, "{}", path.display()),
+            Self::Env(name) => write!(f, "{name}"),
+            Self::Override => write!(f, "--config"),
+        }
+    }
+}
+
+/// A single value of a loaded config.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct ConfigEntry {
+    /// The dotted key of the value, e.g. `defaults.organization`.
+    pub key: String,
+    /// The value, with the values references were expanded to redacted.
+    pub value: Value,
+    /// Where the value came from, e.g. the path of a config file or `default`.
+    pub source: String,
+}
+
+type Lookup = Box<dyn Fn(&str) -> Option<String> + Send + Sync>;
+
+/// The `ConfigResolver` struct expands the environment variable and secret file references in a config.
+pub struct ConfigResolver {
+    lookup: Lookup,
+    secrets: Vec<String>,
+    sources: BTreeMap<String, ConfigSource>,
+}
+
+impl Default for ConfigResolver {
+    fn default() -> Self {
+        Self::new(|name| std::env::var(name).ok())
+    }
+}
+
+impl ConfigResolver {
+    /// Creates a resolver that looks up `${NAME}` references with `lookup`. The default resolver looks them up
+    /// in the environment.
+    pub fn new(lookup: impl Fn(&str) -> Option<String> + Send + Sync + 'static) -> Self {
+        Self {
+            lookup: Box::new(lookup),
+            secrets: Vec::new(),
+            sources: BTreeMap::new(),
+        }
+    }
+
+    /// Parses a YAML config and expands the references in its values. Keys are left as they are.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the config isn't valid YAML, a referenced environment variable isn't set, a
+    /// referenced file can't be read, or the expanded config can't be deserialized.
+    pub fn resolve<T: DeserializeOwned>(&mut self, contents: &str) -> Result<T, SkootError> {
+        let mut value: Value =
+            serde_yaml::from_str(contents).map_err(|e| SkootError::Config(e.to_string()))?;
+        self.resolve_value(&mut value)?;
+        // The error could quote an expanded value, so it's redacted like any other output.
+        serde_yaml::from_value(value).map_err(|e| SkootError::Config(self.redact(&e.to_string())))
+    }
+
+    /// Returns the path of the user's own config, `$XDG_CONFIG_HOME/skootrs/config.yaml` or
+    /// `~/.config/skootrs/config.yaml`, or `None` if there's no home directory.
+    #[must_use]
+    pub fn user_config_path(&self) -> Option<PathBuf> {
+        let config_home = (self.lookup)("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| (self.lookup)("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(config_home.join(USER_CONFIG_FILE))
+    }
+
+    /// Loads a config from its layers, each overriding the ones before it: the files in order, the
+    /// environment variables in `ENV_OVERRIDES`, then `key=value` overrides like
+    /// `defaults.organization=kusaridev` whose values are parsed as YAML. The files are merged key by key, so a
+    /// later file only has to set what it changes. References are expanded once every layer is merged.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a required file doesn't exist, a file or override isn't valid YAML, a reference
+    /// can't be expanded, or the merged config can't be deserialized.
+    pub fn load<T: DeserializeOwned>(
+        &mut self,
+        files: &[ConfigFile],
+        overrides: &[String],
+    ) -> Result<T, SkootError> {
+        let layers = ConfigLayer::read(files)?;
+        self.load_layers(&layers, overrides)
+    }
+
+    /// Loads a config like `load` from layers that were already read, e.g. to check a change to one of them
+    /// before it's written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an override isn't valid YAML, a reference can't be expanded, or the merged config
+    /// can't be deserialized.
+    pub fn load_layers<T: DeserializeOwned>(
+        &mut self,
+        layers: &[ConfigLayer],
+        overrides: &[String],
+    ) -> Result<T, SkootError> {
+        let mut config = Value::Mapping(Mapping::new());
+        self.sources.clear();
+        for layer in layers {
+            merge(&mut config, layer.value.clone());
+            self.record("", &layer.value, &ConfigSource::File(layer.path.clone()));
+        }
+        for &(name, key) in ENV_OVERRIDES {
+            if let Some(raw) = (self.lookup)(name) {
+                let value = match serde_yaml::from_str(&raw) {
+                    Ok(value @ (Value::Mapping(_) | Value::Sequence(_))) => value,
+                    _ => Value::String(raw),
+                };
+                self.record_replaced(key, &value, &ConfigSource::Env(name));
+                set(&mut config, key, value);
+            }
+        }
+        for config_override in overrides {
+            let (key, raw) = config_override.split_once('=').ok_or_else(|| {
+                SkootError::Config(format!(
+                    "The config override {config_override} isn't in the form key=value"
+                ))
+            })?;
+            let key = key.trim();
+            let value = serde_yaml::from_str(raw)
+                .map_err(|e| format!("Failed to parse the config override {key}: {e}"))?;
+            self.record_replaced(key, &value, &ConfigSource::Override);
+            set(&mut config, key, value);
+        }
+        self.resolve_value(&mut config)?;
+        serde_yaml::from_value(config).map_err(|e| self.redact(&e.to_string()).into())
+    }
+
+    /// Remembers that the values in a layer merged in at `prefix` came from `source`.
+    fn record(&mut self, prefix: &str, value: &Value, source: &ConfigSource) {
+        for (key, _) in flatten(prefix, value) {
+            // A value replaces whatever was set above or below it by an earlier layer.
+            let nested = format!("{key}.");
+            self.sources.retain(|existing, _| {
+                !existing.starts_with(&nested) && !key.starts_with(&format!("{existing}."))
+            });
+            self.sources.insert(key, source.clone());
+        }
+    }
+
+    /// Remembers that the value at `key` was replaced as a whole by one from `source`.
+    fn record_replaced(&mut self, key: &str, value: &Value, source: &ConfigSource) {
+        let nested = format!("{key}.");
+        self.sources
+            .retain(|existing, _| existing != key && !existing.starts_with(&nested));
+        self.record(key, value, source);
+    }
+
+    /// Returns where the value at a dotted key of the last loaded config came from.
+    #[must_use]
+    pub fn source(&self, key: &str) -> ConfigSource {
+        let mut key = key;
+        loop {
+            if let Some(source) = self.sources.get(key) {
+                return source.clone();
+            }
+            match key.rsplit_once('.') {
+                Some((parent, _)) => key = parent,
+                None => return ConfigSource::Default,
+            }
+        }
+    }
+
+    /// Returns every value of a loaded config by its dotted key, along with where it came from. Values that
+    /// aren't mappings, like lists, are single entries.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the config can't be serialized.
+    pub fn entries<T: Serialize>(&self, config: &T) -> Result<Vec<ConfigEntry>, SkootError> {
+        let config = serde_yaml::to_value(config).map_err(SkootError::serialization)?;
+        Ok(flatten("", &config)
+            .into_iter()
+            .map(|(key, value)| ConfigEntry {
+                source: self.source(&key).to_string(),
+                value: self.redact_value(value.clone()),
+                key,
+            })
+            .collect())
+    }
+
+    /// Serializes the value at a dotted key of a config to YAML, with the values references were expanded to
+    /// redacted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the config doesn't have the key or can't be serialized.
+    pub fn redacted_yaml_at<T: Serialize>(
+        &self,
+        config: &T,
+        key: &str,
+    ) -> Result<String, SkootError> {
+        let config = serde_yaml::to_value(config).map_err(SkootError::serialization)?;
+        let value = get(&config, key).ok_or_else(|| format!("Unknown config option {key}"))?;
+        self.redacted_yaml(value)
+    }
+
+    fn resolve_value(&mut self, value: &mut Value) -> Result<(), SkootError> {
+        match value {
+            Value::String(s) => *s = self.expand(s)?,
+            Value::Sequence(values) => {
+                for value in values {
+                    self.resolve_value(value)?;
+                }
+            }
+            Value::Mapping(mapping) => {
+                for (_, value) in mapping.iter_mut() {
+                    self.resolve_value(value)?;
+                }
+            }
+            Value::Tagged(tagged) => self.resolve_value(&mut tagged.value)?,
+            Value::Null | Value::Bool(_) | Value::Number(_) => {}
+        }
+        Ok(())
+    }
+
+    /// Expands a single config value. A value starting with `file:` is replaced by the contents of the file
+    /// without its trailing new line, otherwise every `${NAME}` is replaced by the variable's value. `$${` is
+    /// kept as a literal `${`, and placeholders that aren't valid variable names like `${skootrs.runs_on}` are
+    /// left alone.
+    fn expand(&mut self, value: &str) -> Result<String, SkootError> {
+        if let Some(path) = value.strip_prefix(FILE_PREFIX) {
+            let contents = std::fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read secret file {path}: {e}"))?;
+            let secret = contents.trim_end_matches(['\n', '\r']).to_string();
+            self.remember(&secret);
+            return Ok(secret);
+        }
+
+        let mut expanded = String::with_capacity(value.len());
+        let mut rest = value;
+        while let Some(start) = rest.find('$') {
+            expanded.push_str(&rest[..start]);
+            rest = &rest[start..];
+            if let Some(escaped) = rest.strip_prefix("$${") {
+                expanded.push_str("${");
+                rest = escaped;
+                continue;
+            }
+            let reference = rest
+                .strip_prefix("${")
+                .and_then(|r| r.find('}').map(|end| &r[..end]))
+                .filter(|name| is_variable_name(name));
+            match reference {
+                Some(name) => {
+                    let resolved = (self.lookup)(name).ok_or_else(|| {
+                        format!("Environment variable {name} referenced in the config isn't set")
+                    })?;
+                    self.remember(&resolved);
+                    expanded.push_str(&resolved);
+                    rest = &rest[name.len() + 3..];
+                }
+                None => {
+                    expanded.push('$');
+                    rest = &rest[1..];
+                }
+            }
+        }
+        expanded.push_str(rest);
+        Ok(expanded)
+    }
+
+    fn remember(&mut self, secret: &str) {
+        if !secret.is_empty() && !self.secrets.iter().any(|s| s == secret) {
+            self.secrets.push(secret.to_string());
+            // Longer secrets are redacted first so a secret that contains a shorter one is redacted whole.
+            self.secrets.sort_by_key(|s| std::cmp::Reverse(s.len()));
+        }
+    }
+
+    /// Replaces every value a reference was expanded to with `<redacted>`.
+    #[must_use]
+    pub fn redact(&self, text: &str) -> String {
+        self.secrets.iter().fold(text.to_string(), |text, secret| {
+            text.replace(secret, REDACTED)
+        })
+    }
+
+    fn redact_value(&self, value: Value) -> Value {
+        match value {
+            Value::String(s) => Value::String(self.redact(&s)),
+            Value::Sequence(values) => Value::Sequence(
+                values
+                    .into_iter()
+                    .map(|value| self.redact_value(value))
+                    .collect(),
+            ),
+            Value::Mapping(mapping) => Value::Mapping(
+                mapping
+                    .into_iter()
+                    .map(|(key, value)| (key, self.redact_value(value)))
+                    .collect(),
+            ),
+            value => value,
+        }
+    }
+
+    /// Serializes a config to YAML with the values references were expanded to redacted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the config can't be serialized.
+    pub fn redacted_yaml<T: serde::Serialize>(&self, config: &T) -> Result<String, SkootError> {
+        Ok(self.redact(&serde_yaml::to_string(config).map_err(SkootError::serialization)?))
+    }
+}
+
+/// Checks the values at or under a dotted key of a config: paths in options named `*_path` or `*_dir` have to
+/// exist, and options named `*_url` or `*_endpoint` have to be valid URLs.
+///
+/// # Errors
+///
+/// Returns an error naming the first value that fails its check, or if the config doesn't have the key.
+pub fn check_values<T: Serialize>(config: &T, key: &str) -> Result<(), SkootError> {
+    let config = serde_yaml::to_value(config).map_err(SkootError::serialization)?;
+    let value = get(&config, key).ok_or_else(|| format!("Unknown config option {key}"))?;
+    for (key, value) in flatten(key, value) {
+        let (Some(name), Value::String(value)) = (key.rsplit('.').next(), value) else {
+            continue;
+        };
+        if (name.ends_with("_path") || name.ends_with("_dir"))
+            && !std::path::Path::new(value).exists()
+        {
+            return Err(SkootError::Config(format!(
+                "{key} is set to {value}, which doesn't exist"
+            )));
+        }
+        if name.ends_with("_url") || name.ends_with("_endpoint") {
+            url::Url::parse(value).map_err(|e| format!("{key} isn't a valid URL: {e}"))?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the values of a config by their dotted keys. Mappings are flattened into the values they contain,
+/// and anything else, including an empty mapping, is a value of its own.
+fn flatten<'a>(prefix: &str, value: &'a Value) -> Vec<(String, &'a Value)> {
+    match value {
+        Value::Mapping(mapping) if !mapping.is_empty() => mapping
+            .iter()
+            .flat_map(|(key, value)| {
+                let key = key.as_str().map_or_else(
+                    || serde_yaml::to_string(key).unwrap_or_default(),
+                    ToString::to_string,
+                );
+                let key = key.trim();
+                let key = if prefix.is_empty() {
+                    key.to_string()
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                flatten(&key, value)
+            })
+            .collect(),
+        _ if prefix.is_empty() => Vec::new(),
+        value => vec![(prefix.to_string(), value)],
+    }
+}
+
+/// Returns the value at a dotted key like `defaults.organization`.
+fn get<'a>(config: &'a Value, key: &str) -> Option<&'a Value> {
+    key.split('.')
+        .try_fold(config, |value, part| value.as_mapping()?.get(part))
+}
+
+/// Merges a layer into a config. Mappings are merged key by key, and anything else in the layer replaces what
+/// the config has.
+fn merge(config: &mut Value, layer: Value) {
+    match (config, layer) {
+        (Value::Mapping(config), Value::Mapping(layer)) => {
+            for (key, value) in layer {
+                match config.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        config.insert(key, value);
+                    }
+                }
+            }
+        }
+        (config, layer) => *config = layer,
+    }
+}
+
+/// Sets the value of a dotted key like `defaults.organization`, replacing whatever the config has there and
+/// adding the mappings on the way to it if they're missing.
+fn set(config: &mut Value, key: &str, value: Value) {
+    let mut current = config;
+    for part in key.split('.') {
+        if !current.is_mapping() {
+            *current = Value::Mapping(Mapping::new());
+        }
+        let Value::Mapping(mapping) = current else {
+            return;
+        };
+        current = mapping.entry(Value::from(part)).or_insert(Value::Null);
+    }
+    *current = value;
+}
+
+fn is_variable_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+impl std::fmt::Debug for ConfigResolver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConfigResolver")
+            .field("secrets", &self.secrets.len())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use tempdir::TempDir;
+
+    use super::*;
+
+    fn resolver() -> ConfigResolver {
+        let variables = HashMap::from([
+            ("GITHUB_TOKEN".to_string(), "ghp_secret".to_string()),
+            ("RUNNER".to_string(), "self-hosted".to_string()),
+        ]);
+        ConfigResolver::new(move |name| variables.get(name).cloned())
+    }
+
+    #[test]
+    fn test_resolve_env_references() {
+        let mut resolver = resolver();
+        let config: HashMap<String, Value> = resolver
+            .resolve(
+                "token: ${GITHUB_TOKEN}\nrunner: ${RUNNER}-large\nliteral: $${RUNNER}\nplaceholder: ${skootrs.runs_on}\nprice: $5\n",
+            )
+            .unwrap();
+        assert_eq!(config["token"], Value::from("ghp_secret"));
+        assert_eq!(config["runner"], Value::from("self-hosted-large"));
+        assert_eq!(config["literal"], Value::from("${RUNNER}"));
+        assert_eq!(config["placeholder"], Value::from("${skootrs.runs_on}"));
+        assert_eq!(config["price"], Value::from("$5"));
+
+        let err = resolver
+            .resolve::<HashMap<String, Value>>("token: ${MISSING}")
+            .unwrap_err();
+        assert!(err.to_string().contains("MISSING"));
+    }
+
+    #[test]
+    fn test_resolve_file_references_and_redact() {
+        let dir = TempDir::new("skootrs-config-test").unwrap();
+        let path = dir.path().join("token");
+        std::fs::write(&path, "file_secret\n").unwrap();
+
+        let mut resolver = resolver();
+        let config: HashMap<String, Vec<String>> = resolver
+            .resolve(&format!(
+                "tokens:\n  - file:{}\n  - ${{GITHUB_TOKEN}}\n",
+                path.display()
+            ))
+            .unwrap();
+        assert_eq!(config["tokens"], vec!["file_secret", "ghp_secret"]);
+
+        let yaml = resolver.redacted_yaml(&config).unwrap();
+        assert!(!yaml.contains("file_secret"));
+        assert!(!yaml.contains("ghp_secret"));
+        assert_eq!(yaml.matches(REDACTED).count(), 2);
+
+        assert!(resolver
+            .resolve::<HashMap<String, String>>("token: file:/does/not/exist")
+            .is_err());
+    }
+
+    #[test]
+    fn test_load_layers() {
+        let dir = TempDir::new("skootrs-config-test").unwrap();
+        let user = dir.path().join("user.yaml");
+        std::fs::write(
+            &user,
+            "local_project_path: /home/me/skootrs\ndefaults:\n  organization: me\n  ecosystem: Rust\ntoken: ${GITHUB_TOKEN}\n",
+        )
+        .unwrap();
+        let project = dir.path().join("skootrs.yaml");
+        std::fs::write(&project, "defaults:\n  organization: kusaridev\n").unwrap();
+
+        let variables = HashMap::from([
+            ("GITHUB_TOKEN".to_string(), "ghp_secret".to_string()),
+            (
+                "SKOOTRS_TEMPLATE_DIR".to_string(),
+                "./templates".to_string(),
+            ),
+            (
+                "SKOOTRS_STATE_STORE".to_string(),
+                "{backend: json, path: ./skootrs.json}".to_string(),
+            ),
+            ("HOME".to_string(), "/home/me".to_string()),
+        ]);
+        let mut resolver = ConfigResolver::new(move |name| variables.get(name).cloned());
+        assert_eq!(
+            resolver.user_config_path(),
+            Some(PathBuf::from("/home/me/.config/skootrs/config.yaml"))
+        );
+        let files = [
+            ConfigFile {
+                path: user,
+                required: false,
+            },
+            ConfigFile {
+                path: project,
+                required: true,
+            },
+            ConfigFile {
+                path: dir.path().join("missing.yaml"),
+                required: false,
+            },
+        ];
+        let config: Value = resolver
+            .load(&files, &["local_project_path=/tmp/skootrs".to_string()])
+            .unwrap();
+        assert_eq!(config["local_project_path"], Value::from("/tmp/skootrs"));
+        assert_eq!(config["defaults"]["organization"], Value::from("kusaridev"));
+        assert_eq!(config["defaults"]["ecosystem"], Value::from("Rust"));
+        assert_eq!(config["token"], Value::from("ghp_secret"));
+        assert_eq!(config["template_dir"], Value::from("./templates"));
+        assert_eq!(config["statestore"]["path"], Value::from("./skootrs.json"));
+
+        assert!(resolver
+            .load::<Value>(&files, &["local_project_path".to_string()])
+            .is_err());
+        let missing = [ConfigFile {
+            path: dir.path().join("missing.yaml"),
+            required: true,
+        }];
+        assert!(resolver.load::<Value>(&missing, &[]).is_err());
+    }
+
+    #[test]
+    fn test_sources_and_entries() {
+        let dir = TempDir::new("skootrs-config-test").unwrap();
+        let user = dir.path().join("user.yaml");
+        std::fs::write(
+            &user,
+            "token: ${GITHUB_TOKEN}\ndefaults:\n  organization: me\n  ecosystem: Rust\nstatestore: surrealdb\n",
+        )
+        .unwrap();
+        let project = dir.path().join("skootrs.yaml");
+        std::fs::write(&project, "defaults:\n  organization: kusaridev\n").unwrap();
+
+        let variables = HashMap::from([
+            ("GITHUB_TOKEN".to_string(), "ghp_secret".to_string()),
+            (
+                "SKOOTRS_STATE_STORE".to_string(),
+                "{backend: json, path: ./skootrs.json}".to_string(),
+            ),
+        ]);
+        let mut resolver = ConfigResolver::new(move |name| variables.get(name).cloned());
+        let files = [
+            ConfigFile {
+                path: user.clone(),
+                required: true,
+            },
+            ConfigFile {
+                path: project.clone(),
+                required: true,
+            },
+        ];
+        let config: Value = resolver
+            .load(&files, &["defaults.ecosystem=Go".to_string()])
+            .unwrap();
+        assert_eq!(resolver.source("token"), ConfigSource::File(user));
+        assert_eq!(
+            resolver.source("defaults.organization"),
+            ConfigSource::File(project)
+        );
+        assert_eq!(
+            resolver.source("defaults.ecosystem"),
+            ConfigSource::Override
+        );
+        assert_eq!(
+            resolver.source("statestore.backend"),
+            ConfigSource::Env("SKOOTRS_STATE_STORE")
+        );
+        assert_eq!(resolver.source("telemetry"), ConfigSource::Default);
+
+        let entries = resolver.entries(&config).unwrap();
+        let token = entries.iter().find(|entry| entry.key == "token").unwrap();
+        assert_eq!(token.value, Value::from(REDACTED));
+        assert!(entries.iter().any(|entry| entry.key == "statestore.path"));
+        assert_eq!(
+            resolver
+                .redacted_yaml_at(&config, "defaults.ecosystem")
+                .unwrap(),
+            "Go\n"
+        );
+        assert!(resolver.redacted_yaml_at(&config, "missing").is_err());
+    }
+
+    #[test]
+    fn test_check_values() {
+        let dir = TempDir::new("skootrs-config-test").unwrap();
+        let config: Value = serde_yaml::from_str(&format!(
+            "local_project_path: {}\ntemplate_dir: /does/not/exist\nscorecard:\n  api_url: not a url\n",
+            dir.path().display()
+        ))
+        .unwrap();
+        assert!(check_values(&config, "local_project_path").is_ok());
+        assert!(check_values(&config, "template_dir").is_err());
+        assert!(check_values(&config, "scorecard").is_err());
+        assert!(check_values(&config, "missing").is_err());
+    }
+}
@@ -1,12 +1,15 @@
 #![allow(clippy::module_name_repetitions)]
 
-use std::process::Command;
+use std::{fs, path::Path, process::Command};
 
+use askama::Template;
 use tracing::info;
 
+use super::command::CommandRunner;
+
 use skootrs_model::skootrs::{
-    EcosystemInitializeParams, GoParams, InitializedEcosystem, InitializedGo, InitializedMaven,
-    InitializedSource, MavenParams, SkootError,
+    plan::PlannedAction, EcosystemInitializeParams, GoParams, InitializedEcosystem,
+    InitializedSource, MavenParams, PythonPackageManager, PythonParams, RustParams, SkootError,
 };
 
 /// The `EcosystemService` trait provides an interface for initializing and managing a project's ecosystem.
@@ -23,9 +26,21 @@ pub trait EcosystemService {
         params: EcosystemInitializeParams,
         source: InitializedSource,
     ) -> Result<InitializedEcosystem, SkootError>;
+
+    /// Returns the commands and files initializing a project's ecosystem in `source` would run and write,
+    /// without running or writing anything.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the params are invalid or the ecosystem's files can't be rendered.
+    fn plan(
+        &self,
+        params: &EcosystemInitializeParams,
+        source: &InitializedSource,
+    ) -> Result<Vec<PlannedAction>, SkootError>;
 }
 
-/// The `LocalEcosystemService` struct provides an implementation of the `EcosystemService` trait for initializing 
+/// The `LocalEcosystemService` struct provides an implementation of the `EcosystemService` trait for initializing
 /// and managing a project's ecosystem on the local machine.
 #[derive(Debug)]
 pub struct LocalEcosystemService {}
@@ -36,52 +51,282 @@ impl EcosystemService for LocalEcosystemService {
         params: EcosystemInitializeParams,
         source: InitializedSource,
     ) -> Result<InitializedEcosystem, SkootError> {
-        match params {
+        // Modules of a project are initialized in directories of the source that don't exist yet.
+        fs::create_dir_all(&source.path)?;
+        match &params {
             EcosystemInitializeParams::Maven(m) => {
-                LocalMavenEcosystemHandler::initialize(&source.path, &m)?;
-                Ok(InitializedEcosystem::Maven(InitializedMaven {
-                    group_id: m.group_id,
-                    artifact_id: m.artifact_id,
-                }))
+                LocalMavenEcosystemHandler::initialize(&source.path, m)?;
             }
             EcosystemInitializeParams::Go(g) => {
-                LocalGoEcosystemHandler::initialize(&source.path, &g)?;
-                Ok(InitializedEcosystem::Go(InitializedGo {
-                    name: g.name,
-                    host: g.host,
-                }))
+                LocalGoEcosystemHandler::initialize(&source.path, g)?;
+            }
+            EcosystemInitializeParams::Python(p) => {
+                LocalPythonEcosystemHandler::initialize(&source.path, p)?;
+            }
+            EcosystemInitializeParams::Rust(r) => {
+                LocalRustEcosystemHandler::initialize(&source.path, r)?;
+            }
+        }
+        Ok(params.initialized_ecosystem())
+    }
+
+    fn plan(
+        &self,
+        params: &EcosystemInitializeParams,
+        source: &InitializedSource,
+    ) -> Result<Vec<PlannedAction>, SkootError> {
+        match params {
+            EcosystemInitializeParams::Maven(m) => Ok(LocalMavenEcosystemHandler::plan(m, source)),
+            EcosystemInitializeParams::Go(g) => Ok(LocalGoEcosystemHandler::plan(g, source)),
+            EcosystemInitializeParams::Python(p) => {
+                Ok(vec![
+                    LocalPythonEcosystemHandler::render(p)?.into_planned_action()
+                ])
             }
+            EcosystemInitializeParams::Rust(r) => Ok(LocalRustEcosystemHandler::render(r)?
+                .into_iter()
+                .map(EcosystemFile::into_planned_action)
+                .collect()),
+        }
+    }
+}
+
+/// A file generated by an ecosystem handler, relative to the root of the source.
+struct EcosystemFile {
+    path: String,
+    name: String,
+    content: String,
+}
+
+impl EcosystemFile {
+    fn write(&self, root: &str) -> Result<(), SkootError> {
+        let dir = Path::new(root).join(&self.path);
+        fs::create_dir_all(&dir)?;
+        fs::write(dir.join(&self.name), &self.content)?;
+        Ok(())
+    }
+
+    fn into_planned_action(self) -> PlannedAction {
+        PlannedAction::WriteFile {
+            path: self.path,
+            name: self.name,
+            content: self.content,
+            facet_type: None,
         }
     }
 }
 
+/// Runs an ecosystem's command, e.g. `go mod init`, in the source, and fails if the command does.
+fn run(args: &[String], path: &str) -> Result<std::process::Output, SkootError> {
+    let (program, args) = args.split_first().ok_or("Missing command")?;
+    Command::new(program)
+        .args(args)
+        .current_dir(path)
+        .run_checked()
+}
 
-/// The `LocalMavenEcosystemHandler` struct represents a handler for initializing and managing a Maven 
+/// The `LocalMavenEcosystemHandler` struct represents a handler for initializing and managing a Maven
 /// project on the local machine.
 struct LocalMavenEcosystemHandler {}
 
+/// The Maven options that fail on dependencies whose checksums don't match, instead of warning about them.
+const MAVEN_CONFIG: &str = "--strict-checksums\n";
+
 impl LocalMavenEcosystemHandler {
     /// Returns `Ok(())` if the Maven project initialization is successful,
     /// otherwise returns an error.
     fn initialize(path: &str, params: &MavenParams) -> Result<(), SkootError> {
-        let output = Command::new("mvn")
-            .arg("archetype:generate")
-            .arg(format!("-DgroupId={}", params.group_id))
-            .arg(format!("-DartifactId={}", params.artifact_id))
-            .arg("-DarchetypeArtifactId=maven-archetype-quickstart")
-            .arg("-DinteractiveMode=false")
-            .current_dir(path)
-            .output()?;
-        if output.status.success() {
-            info!("Initialized maven project for {}", params.artifact_id);
-            Ok(())
-        } else {
-            Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                "Failed to run mvn generate",
-            )))
+        run(&Self::args(params), path)?;
+        // The archetype generates the project in a directory named after its artifact.
+        let project_path = Path::new(path).join(&params.artifact_id);
+        if params.java_version.is_some() || params.harden_pom {
+            let pom_path = project_path.join("pom.xml");
+            let pom = fs::read_to_string(&pom_path)?;
+            fs::write(&pom_path, Self::pom(&pom, params)?)?;
+        }
+        if let Some(file) = Self::maven_config(params) {
+            file.write(path)?;
+        }
+        if params.wrapper {
+            run(&Self::wrapper_args(), &project_path.to_string_lossy())?;
+        }
+        info!("Initialized maven project for {}", params.artifact_id);
+        Ok(())
+    }
+
+    fn args(params: &MavenParams) -> Vec<String> {
+        let archetype = &params.archetype;
+        let mut args = vec![
+            "mvn".to_string(),
+            "archetype:generate".to_string(),
+            format!("-DgroupId={}", params.group_id),
+            format!("-DartifactId={}", params.artifact_id),
+            format!("-DarchetypeGroupId={}", archetype.group_id),
+            format!("-DarchetypeArtifactId={}", archetype.artifact_id),
+        ];
+        if let Some(version) = &archetype.version {
+            args.push(format!("-DarchetypeVersion={version}"));
+        }
+        args.push("-DinteractiveMode=false".to_string());
+        args
+    }
+
+    fn wrapper_args() -> Vec<String> {
+        vec![
+            "mvn".to_string(),
+            "--non-recursive".to_string(),
+            "wrapper:wrapper".to_string(),
+        ]
+    }
+
+    /// Returns the commands initializing the project runs, and the files it writes besides the ones the
+    /// archetype generates.
+    fn plan(params: &MavenParams, source: &InitializedSource) -> Vec<PlannedAction> {
+        let archetype = &params.archetype;
+        let mut description = format!(
+            "Generate the Maven project {} from {}:{}",
+            params.artifact_id, archetype.group_id, archetype.artifact_id
+        );
+        if let Some(java_version) = &params.java_version {
+            description.push_str(&format!(", compiled for Java {java_version}"));
+        }
+        if params.harden_pom {
+            description.push_str(", with the enforcer plugin added to its pom");
         }
+        let mut actions = vec![PlannedAction::Command {
+            description,
+            args: Self::args(params),
+            dir: source.path.clone(),
+        }];
+        actions.extend(Self::maven_config(params).map(EcosystemFile::into_planned_action));
+        if params.wrapper {
+            actions.push(PlannedAction::Command {
+                description: format!("Generate the Maven wrapper of {}", params.artifact_id),
+                args: Self::wrapper_args(),
+                dir: Path::new(&source.path)
+                    .join(&params.artifact_id)
+                    .to_string_lossy()
+                    .to_string(),
+            });
+        }
+        actions
+    }
+
+    /// Returns the `.mvn/maven.config` of a hardened project, or `None` if the pom isn't hardened.
+    fn maven_config(params: &MavenParams) -> Option<EcosystemFile> {
+        params.harden_pom.then(|| EcosystemFile {
+            path: format!("{}/.mvn/", params.artifact_id),
+            name: "maven.config".to_string(),
+            content: MAVEN_CONFIG.to_string(),
+        })
     }
+
+    /// Returns the pom the archetype generated, compiled for the project's Java release and with the
+    /// enforcer plugin if the pom is hardened.
+    fn pom(pom: &str, params: &MavenParams) -> Result<String, SkootError> {
+        #[derive(Template)]
+        #[template(path = "maven.enforcer.xml", escape = "none")]
+        struct EnforcerTemplateParams<'a> {
+            java_version: Option<&'a str>,
+        }
+
+        let mut pom = pom.to_string();
+        if let Some(java_version) = &params.java_version {
+            pom = set_java_release(&pom, java_version)?;
+        }
+        if params.harden_pom {
+            let plugin = EnforcerTemplateParams {
+                java_version: params.java_version.as_deref(),
+            }
+            .render()
+            .map_err(SkootError::template)?;
+            pom = add_build_plugin(&pom, &plugin)?;
+        }
+        Ok(pom)
+    }
+}
+
+/// Sets the Java release a pom is compiled for, replacing the source and target versions the archetype set.
+fn set_java_release(pom: &str, release: &str) -> Result<String, SkootError> {
+    let property = format!("<maven.compiler.release>{release}</maven.compiler.release>");
+    let mut lines = pom
+        .lines()
+        .filter(|line| {
+            let line = line.trim();
+            !["source", "target", "release"]
+                .iter()
+                .any(|version| line.starts_with(&format!("<maven.compiler.{version}>")))
+        })
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    if let Some(index) = lines.iter().position(|line| line.trim() == "<properties>") {
+        lines.insert(index + 1, format!("    {property}"));
+    } else {
+        let index = lines
+            .iter()
+            .position(|line| ["<dependencies>", "<build>", "</project>"].contains(&line.trim()))
+            .ok_or("The pom.xml the archetype generated has no </project>")?;
+        lines.splice(
+            index..index,
+            [
+                "  <properties>".to_string(),
+                format!("    {property}"),
+                "  </properties>".to_string(),
+                String::new(),
+            ],
+        );
+    }
+    Ok(lines.join("\n") + "\n")
+}
+
+/// Adds a plugin to the build of a pom, rather than to the plugin management of the build.
+fn add_build_plugin(pom: &str, plugin: &str) -> Result<String, SkootError> {
+    let plugin = plugin.trim_end_matches('\n');
+    let Some(build_start) = pom.find("<build>") else {
+        let end = pom
+            .rfind("</project>")
+            .ok_or("The pom.xml the archetype generated has no </project>")?;
+        return Ok(format!(
+            "{}  <build>\n    <plugins>\n{plugin}\n    </plugins>\n  </build>\n{}",
+            &pom[..end],
+            &pom[end..]
+        ));
+    };
+    let build_end = build_start
+        + pom[build_start..]
+            .find("</build>")
+            .ok_or("The pom.xml the archetype generated has no </build>")?;
+    // The plugin management of the build has plugins of its own, which are only defaults.
+    let management = pom[build_start..build_end]
+        .find("<pluginManagement>")
+        .zip(pom[build_start..build_end].find("</pluginManagement>"))
+        .map_or(build_end..build_end, |(start, end)| {
+            build_start + start..build_start + end
+        });
+    let plugins = pom[build_start..management.start]
+        .find("<plugins>")
+        .map(|start| build_start + start)
+        .or_else(|| {
+            pom[management.end..build_end]
+                .find("<plugins>")
+                .map(|start| management.end + start)
+        });
+    let (index, insert) = match plugins {
+        Some(start) => {
+            let line_end = pom[start..]
+                .find('\n')
+                .map_or(start + "<plugins>".len(), |end| start + end + 1);
+            (line_end, format!("{plugin}\n"))
+        }
+        None => {
+            let line_start = pom[..build_end].rfind('\n').map_or(build_end, |i| i + 1);
+            (
+                line_start,
+                format!("    <plugins>\n{plugin}\n    </plugins>\n"),
+            )
+        }
+    };
+    Ok(format!("{}{insert}{}", &pom[..index], &pom[index..]))
 }
 
 /// The `LocalGoEcosystemHandler` struct represents a handler for initializing and managing a Go
@@ -96,30 +341,205 @@ impl LocalGoEcosystemHandler {
     ///
     /// * `path` - The path where the Go module should be initialized.
     fn initialize(path: &str, params: &GoParams) -> Result<(), SkootError> {
-        let output = Command::new("go")
-            .arg("mod")
-            .arg("init")
-            .arg(params.module())
-            .current_dir(path)
-            .output()?;
-        if output.status.success() {
-            info!("Initialized go module for {}", params.name);
-            Ok(())
-        } else {
-            Err(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                format!(
-                    "Failed to run go mod init: {}",
-                    String::from_utf8(output.stderr)?
+        run(&Self::args(params), path)?;
+        if let Some(args) = Self::edit_args(params) {
+            run(&args, path)?;
+        }
+        info!("Initialized go module for {}", params.name);
+        Ok(())
+    }
+
+    fn args(params: &GoParams) -> Vec<String> {
+        vec![
+            "go".to_string(),
+            "mod".to_string(),
+            "init".to_string(),
+            params.module(),
+        ]
+    }
+
+    /// Returns the command that sets the `go` and `toolchain` directives of the module's `go.mod`, or `None`
+    /// if the module is built with the installed `go`, which `go mod init` already set.
+    fn edit_args(params: &GoParams) -> Option<Vec<String>> {
+        let (go, toolchain) = go_directives(params.go_version.as_deref()?);
+        Some(vec![
+            "go".to_string(),
+            "mod".to_string(),
+            "edit".to_string(),
+            format!("-go={go}"),
+            format!("-toolchain={toolchain}"),
+        ])
+    }
+
+    /// Returns the commands initializing the module runs.
+    fn plan(params: &GoParams, source: &InitializedSource) -> Vec<PlannedAction> {
+        let mut actions = vec![PlannedAction::Command {
+            description: format!("Initialize the Go module {}", params.module()),
+            args: Self::args(params),
+            dir: source.path.clone(),
+        }];
+        if let (Some(args), Some(go_version)) = (Self::edit_args(params), &params.go_version) {
+            actions.push(PlannedAction::Command {
+                description: format!(
+                    "Build the Go module {} with Go {go_version}",
+                    params.module()
                 ),
-            )))
+                args,
+                dir: source.path.clone(),
+            });
         }
+        actions
+    }
+}
+
+/// Returns the `go` and `toolchain` directives of a module built with a Go release, e.g. `1.22.0` and
+/// `go1.22.3` for `1.22.3`. The language version is the minor release's, so the module can still be built
+/// with any of its patch releases.
+fn go_directives(go_version: &str) -> (String, String) {
+    let mut numbers = go_version.splitn(3, '.');
+    let major = numbers.next().unwrap_or("1");
+    let minor = numbers.next().unwrap_or("0");
+    let patch = numbers.next().unwrap_or("0");
+    (
+        format!("{major}.{minor}.0"),
+        format!("go{major}.{minor}.{patch}"),
+    )
+}
+
+/// The `LocalPythonEcosystemHandler` struct represents a handler for initializing and managing a Python
+/// project on the local machine.
+struct LocalPythonEcosystemHandler {}
+
+impl LocalPythonEcosystemHandler {
+    /// Returns an error if the `pyproject.toml` for the Python project can't be generated and written
+    /// to the specified path.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path where the Python project should be initialized.
+    fn initialize(path: &str, params: &PythonParams) -> Result<(), SkootError> {
+        Self::render(params)?.write(path)?;
+        info!("Initialized python project for {}", params.name);
+        Ok(())
+    }
+
+    /// Renders the `pyproject.toml` for the Python project.
+    fn render(params: &PythonParams) -> Result<EcosystemFile, SkootError> {
+        #[derive(Template)]
+        #[template(path = "pyproject.pip.toml", escape = "none")]
+        struct PipPyprojectTemplateParams<'a> {
+            name: &'a str,
+            package: &'a str,
+        }
+
+        #[derive(Template)]
+        #[template(path = "pyproject.poetry.toml", escape = "none")]
+        struct PoetryPyprojectTemplateParams<'a> {
+            name: &'a str,
+            package: &'a str,
+        }
+
+        if params.name.is_empty() {
+            return Err("Python project name can't be empty".into());
+        }
+
+        let package = params.package();
+        let content = match params.package_manager {
+            PythonPackageManager::Pip => PipPyprojectTemplateParams {
+                name: &params.name,
+                package: &package,
+            }
+            .render()
+            .map_err(SkootError::template)?,
+            PythonPackageManager::Poetry => PoetryPyprojectTemplateParams {
+                name: &params.name,
+                package: &package,
+            }
+            .render()
+            .map_err(SkootError::template)?,
+        };
+        Ok(EcosystemFile {
+            path: "./".to_string(),
+            name: "pyproject.toml".to_string(),
+            content,
+        })
+    }
+}
+
+/// The `LocalRustEcosystemHandler` struct represents a handler for initializing and managing a Rust
+/// project on the local machine.
+struct LocalRustEcosystemHandler {}
+
+impl LocalRustEcosystemHandler {
+    /// Returns an error if the Cargo workspace for the Rust project can't be generated and written
+    /// to the specified path.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path where the Rust project should be initialized.
+    fn initialize(path: &str, params: &RustParams) -> Result<(), SkootError> {
+        for file in Self::render(params)? {
+            file.write(path)?;
+        }
+        info!("Initialized cargo workspace for {}", params.name);
+        Ok(())
+    }
+
+    /// Renders the workspace `Cargo.toml` and the manifest of each crate in the workspace.
+    fn render(params: &RustParams) -> Result<Vec<EcosystemFile>, SkootError> {
+        #[derive(Template)]
+        #[template(path = "rust.workspace.toml", escape = "none")]
+        struct WorkspaceTemplateParams<'a> {
+            members: &'a [String],
+        }
+
+        #[derive(Template)]
+        #[template(path = "rust.crate.toml", escape = "none")]
+        struct CrateTemplateParams<'a> {
+            name: &'a str,
+            workspace: &'a str,
+        }
+
+        if params.name.is_empty() {
+            return Err("Rust workspace name can't be empty".into());
+        }
+        let members = params.members();
+        if let Some(invalid) = members.iter().find(|member| {
+            member.is_empty()
+                || !member
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        }) {
+            return Err(format!("Invalid crate name: {invalid:?}").into());
+        }
+
+        let mut files = vec![EcosystemFile {
+            path: "./".to_string(),
+            name: "Cargo.toml".to_string(),
+            content: WorkspaceTemplateParams { members: &members }
+                .render()
+                .map_err(SkootError::template)?,
+        }];
+        for member in &members {
+            files.push(EcosystemFile {
+                path: format!("crates/{member}/"),
+                name: "Cargo.toml".to_string(),
+                content: CrateTemplateParams {
+                    name: member,
+                    workspace: &params.name,
+                }
+                .render()
+                .map_err(SkootError::template)?,
+            });
+        }
+        Ok(files)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use skootrs_model::skootrs::MavenArchetype;
     use tempdir::TempDir;
 
     #[test]
@@ -129,6 +549,7 @@ mod tests {
         let params = MavenParams {
             group_id: "com.example".to_string(),
             artifact_id: "my-project".to_string(),
+            ..Default::default()
         };
 
         let result = LocalMavenEcosystemHandler::initialize(path, &params);
@@ -144,6 +565,7 @@ mod tests {
             // Invalid group ID
             group_id: "".to_string(),
             artifact_id: "my-project".to_string(),
+            ..Default::default()
         };
 
         let result = LocalMavenEcosystemHandler::initialize(path, &params);
@@ -158,6 +580,7 @@ mod tests {
         let params = GoParams {
             name: "my-project".to_string(),
             host: "github.com".to_string(),
+            go_version: None,
         };
 
         let result = LocalGoEcosystemHandler::initialize(path, &params);
@@ -173,10 +596,267 @@ mod tests {
             // Invalid project name
             name: "".to_string(),
             host: "github.com".to_string(),
+            go_version: None,
         };
 
         let result = LocalGoEcosystemHandler::initialize(path, &params);
 
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_local_python_ecosystem_handler_initialize_success() {
+        let temp_dir = TempDir::new("test").unwrap();
+        let path = temp_dir.path().to_str().unwrap();
+        let params = PythonParams {
+            name: "my-project".to_string(),
+            package_manager: PythonPackageManager::Poetry,
+        };
+
+        let result = LocalPythonEcosystemHandler::initialize(path, &params);
+
+        assert!(result.is_ok());
+        let pyproject = fs::read_to_string(temp_dir.path().join("pyproject.toml")).unwrap();
+        assert!(pyproject.contains("[tool.poetry]"));
+        assert!(pyproject.contains(r#"include = "my_project""#));
+    }
+
+    #[test]
+    fn test_local_python_ecosystem_handler_initialize_failure() {
+        let temp_dir = TempDir::new("test").unwrap();
+        let path = temp_dir.path().to_str().unwrap();
+        let params = PythonParams {
+            // Invalid project name
+            name: "".to_string(),
+            package_manager: PythonPackageManager::Pip,
+        };
+
+        let result = LocalPythonEcosystemHandler::initialize(path, &params);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_local_rust_ecosystem_handler_initialize_success() {
+        let temp_dir = TempDir::new("test").unwrap();
+        let path = temp_dir.path().to_str().unwrap();
+        let params = RustParams {
+            name: "my-workspace".to_string(),
+            crates: vec!["my-core".to_string(), "my-cli".to_string()],
+        };
+
+        let result = LocalRustEcosystemHandler::initialize(path, &params);
+
+        assert!(result.is_ok());
+        let workspace = fs::read_to_string(temp_dir.path().join("Cargo.toml")).unwrap();
+        assert!(workspace.contains(r#""crates/my-core","#));
+        assert!(workspace.contains(r#""crates/my-cli","#));
+        let manifest =
+            fs::read_to_string(temp_dir.path().join("crates/my-cli/Cargo.toml")).unwrap();
+        assert!(manifest.contains(r#"name = "my-cli""#));
+    }
+
+    #[test]
+    fn test_local_rust_ecosystem_handler_initialize_failure() {
+        let temp_dir = TempDir::new("test").unwrap();
+        let path = temp_dir.path().to_str().unwrap();
+        let params = RustParams {
+            name: "my-workspace".to_string(),
+            // Invalid crate name
+            crates: vec!["my crate".to_string()],
+        };
+
+        let result = LocalRustEcosystemHandler::initialize(path, &params);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_local_ecosystem_service_plan() {
+        let source = InitializedSource {
+            path: "/does/not/exist".to_string(),
+        };
+        let plan = LocalEcosystemService {}
+            .plan(
+                &EcosystemInitializeParams::Rust(RustParams {
+                    name: "my-workspace".to_string(),
+                    crates: vec!["my-core".to_string()],
+                }),
+                &source,
+            )
+            .unwrap();
+        let files = plan
+            .iter()
+            .map(|action| match action {
+                PlannedAction::WriteFile { path, name, .. } => format!("{path}{name}"),
+                _ => panic!("Expected only files, got {action}"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(files, vec!["./Cargo.toml", "crates/my-core/Cargo.toml"]);
+        assert!(!Path::new(&source.path).exists());
+
+        let plan = LocalEcosystemService {}
+            .plan(
+                &EcosystemInitializeParams::Go(GoParams {
+                    name: "my-project".to_string(),
+                    host: "github.com".to_string(),
+                    go_version: None,
+                }),
+                &source,
+            )
+            .unwrap();
+        assert_eq!(
+            plan,
+            vec![PlannedAction::Command {
+                description: "Initialize the Go module github.com/my-project".to_string(),
+                args: vec![
+                    "go".to_string(),
+                    "mod".to_string(),
+                    "init".to_string(),
+                    "github.com/my-project".to_string()
+                ],
+                dir: "/does/not/exist".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_go_plan() {
+        let source = InitializedSource {
+            path: "/does/not/exist".to_string(),
+        };
+        let params = GoParams {
+            name: "my-project".to_string(),
+            host: "github.com/my-org".to_string(),
+            go_version: Some("1.22.3".to_string()),
+        };
+        let plan = LocalEcosystemService {}
+            .plan(&EcosystemInitializeParams::Go(params), &source)
+            .unwrap();
+        assert_eq!(plan.len(), 2);
+        assert_eq!(
+            plan[1],
+            PlannedAction::Command {
+                description: "Build the Go module github.com/my-org/my-project with Go 1.22.3"
+                    .to_string(),
+                args: vec![
+                    "go".to_string(),
+                    "mod".to_string(),
+                    "edit".to_string(),
+                    "-go=1.22.0".to_string(),
+                    "-toolchain=go1.22.3".to_string(),
+                ],
+                dir: "/does/not/exist".to_string(),
+            }
+        );
+        assert_eq!(
+            go_directives("1.21"),
+            ("1.21.0".to_string(), "go1.21.0".to_string())
+        );
+    }
+
+    #[test]
+    fn test_maven_plan() {
+        let source = InitializedSource {
+            path: "/does/not/exist".to_string(),
+        };
+        let params = MavenParams {
+            group_id: "com.example".to_string(),
+            artifact_id: "my-project".to_string(),
+            archetype: MavenArchetype {
+                group_id: "org.apache.maven.archetypes".to_string(),
+                artifact_id: "maven-archetype-webapp".to_string(),
+                version: Some("1.5".to_string()),
+            },
+            java_version: Some("21".to_string()),
+            wrapper: true,
+            harden_pom: true,
+        };
+        let plan = LocalEcosystemService {}
+            .plan(&EcosystemInitializeParams::Maven(params), &source)
+            .unwrap();
+        assert_eq!(plan.len(), 3);
+        let PlannedAction::Command { args, .. } = &plan[0] else {
+            panic!("Expected a command, got {}", plan[0]);
+        };
+        assert!(args.contains(&"-DarchetypeArtifactId=maven-archetype-webapp".to_string()));
+        assert!(args.contains(&"-DarchetypeVersion=1.5".to_string()));
+        assert_eq!(
+            plan[1],
+            PlannedAction::WriteFile {
+                path: "my-project/.mvn/".to_string(),
+                name: "maven.config".to_string(),
+                content: MAVEN_CONFIG.to_string(),
+                facet_type: None,
+            }
+        );
+        let PlannedAction::Command { args, dir, .. } = &plan[2] else {
+            panic!("Expected a command, got {}", plan[2]);
+        };
+        assert_eq!(args, &LocalMavenEcosystemHandler::wrapper_args());
+        assert_eq!(dir, "/does/not/exist/my-project");
+    }
+
+    #[test]
+    fn test_maven_pom() {
+        let params = MavenParams {
+            group_id: "com.example".to_string(),
+            artifact_id: "my-project".to_string(),
+            java_version: Some("21".to_string()),
+            harden_pom: true,
+            ..Default::default()
+        };
+        let pom = r#"<project>
+  <properties>
+    <project.build.sourceEncoding>UTF-8</project.build.sourceEncoding>
+    <maven.compiler.source>1.7</maven.compiler.source>
+    <maven.compiler.target>1.7</maven.compiler.target>
+  </properties>
+
+  <build>
+    <pluginManagement>
+      <plugins>
+        <plugin>
+          <artifactId>maven-clean-plugin</artifactId>
+        </plugin>
+      </plugins>
+    </pluginManagement>
+  </build>
+</project>
+"#;
+        let pom = LocalMavenEcosystemHandler::pom(pom, &params).unwrap();
+        assert!(pom.contains(
+            "  <properties>\n    <maven.compiler.release>21</maven.compiler.release>\n    \
+             <project.build.sourceEncoding>"
+        ));
+        assert!(!pom.contains("maven.compiler.source"));
+        // The enforcer plugin is added to the build's plugins, not to the plugin management.
+        let plugins = pom.find("    <plugins>\n      <plugin>\n").unwrap();
+        assert!(plugins > pom.find("</pluginManagement>").unwrap());
+        assert!(pom[plugins..].contains("<dependencyConvergence/>"));
+        assert!(pom[plugins..]
+            .contains("<requireJavaVersion>\n                  <version>21</version>"));
+        assert!(pom.ends_with("    </plugins>\n  </build>\n</project>\n"));
+
+        // A pom with build plugins and no properties gets the enforcer added to its plugins.
+        let pom = "<project>\n  <build>\n    <plugins>\n    </plugins>\n  </build>\n</project>\n";
+        let pom = LocalMavenEcosystemHandler::pom(pom, &params).unwrap();
+        assert!(pom.starts_with(
+            "<project>\n  <properties>\n    <maven.compiler.release>21</maven.compiler.release>\n  \
+             </properties>\n\n  <build>\n    <plugins>\n      <plugin>\n"
+        ));
+        assert_eq!(pom.matches("<plugins>").count(), 1);
+
+        // A pom without a build gets one.
+        let pom = LocalMavenEcosystemHandler::pom(
+            "<project>\n</project>\n",
+            &MavenParams {
+                harden_pom: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(pom.starts_with("<project>\n  <build>\n    <plugins>\n      <plugin>\n"));
+        assert!(!pom.contains("requireJavaVersion"));
+    }
 }
@@ -0,0 +1,152 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Skootrs emits CDEvents when it creates a repo, creates or updates a facet, archives a project or observes a
+//! release, so other systems can react to those without polling. `EventEmitter` sends each event to every
+//! sink in the `event_sinks` config. An event that can't be delivered is logged instead of failing the
+//! operation, since the change it describes has already happened.
+
+use reqwest::header::CONTENT_TYPE;
+use serde::Serialize;
+use serde_json::Value;
+use tracing::{debug, error};
+
+use skootrs_model::skootrs::{events::EventSinkConfig, SkootError};
+
+/// The `EventEmitter` struct sends the events Skootrs emits to the configured sinks.
+#[derive(Debug, Clone, Default)]
+pub struct EventEmitter {
+    /// The sinks every event is sent to.
+    pub sinks: Vec<EventSinkConfig>,
+    client: reqwest::Client,
+}
+
+impl EventEmitter {
+    /// Returns an emitter that sends events to the sinks.
+    #[must_use]
+    pub fn new(sinks: Vec<EventSinkConfig>) -> Self {
+        Self {
+            sinks,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Sends an event to every sink. Failures are logged rather than returned.
+    pub async fn emit<E: Serialize + Sync>(&self, event: &E) {
+        let event = match serde_json::to_value(event) {
+            Ok(event) => event,
+            Err(e) => {
+                error!("Failed to serialize an event: {e}");
+                return;
+            }
+        };
+        debug!("Emitting event {event}");
+        for sink in &self.sinks {
+            if let Err(e) = self.send(sink, &event).await {
+                error!(
+                    "Failed to send event {} to {sink:?}: {e}",
+                    context(&event, "id")
+                );
+            }
+        }
+    }
+
+    async fn send(&self, sink: &EventSinkConfig, event: &Value) -> Result<(), SkootError> {
+        match sink {
+            EventSinkConfig::Stdout => println!("{event}"),
+            EventSinkConfig::Webhook { url, headers } => {
+                let mut request = self
+                    .client
+                    .post(url)
+                    .header(CONTENT_TYPE, "application/json")
+                    .body(event.to_string());
+                for (name, value) in headers {
+                    request = request.header(name, value);
+                }
+                request
+                    .send()
+                    .await
+                    .map_err(SkootError::other)?
+                    .error_for_status()
+                    .map_err(SkootError::other)?;
+            }
+            EventSinkConfig::CloudEvents { url } => {
+                self.client
+                    .post(url)
+                    .header("ce-specversion", "1.0")
+                    .header("ce-id", context(event, "id"))
+                    .header("ce-source", context(event, "source"))
+                    .header("ce-type", context(event, "type"))
+                    .header("ce-time", context(event, "timestamp"))
+                    .header(CONTENT_TYPE, "application/json")
+                    .body(event.to_string())
+                    .send()
+                    .await
+                    .map_err(SkootError::other)?
+                    .error_for_status()
+                    .map_err(SkootError::other)?;
+            }
+            EventSinkConfig::Kafka {
+                rest_proxy_url,
+                topic,
+            } => {
+                let records = serde_json::json!({
+                    "records": [{ "key": event["subject"]["id"], "value": event }],
+                });
+                self.client
+                    .post(format!(
+                        "{}/topics/{topic}",
+                        rest_proxy_url.trim_end_matches('/')
+                    ))
+                    .header(CONTENT_TYPE, "application/vnd.kafka.json.v2+json")
+                    .body(records.to_string())
+                    .send()
+                    .await
+                    .map_err(SkootError::other)?
+                    .error_for_status()
+                    .map_err(SkootError::other)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns a field of the event's context as a string, or an empty string if it's missing.
+fn context(event: &Value, field: &str) -> String {
+    event["context"][field]
+        .as_str()
+        .unwrap_or_default()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use skootrs_model::skootrs::events::CDEvent;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_emit_without_sinks() {
+        let event = CDEvent::project_archived("https://github.com/kusaridev/skootrs");
+        let value = serde_json::to_value(&event).unwrap();
+        assert_eq!(context(&value, "source"), "skootrs");
+        assert_eq!(
+            context(&value, "type"),
+            "dev.cdeventsx.skootrs-project.archived.0.1.0"
+        );
+        assert_eq!(context(&value, "missing"), "");
+        EventEmitter::default().emit(&event).await;
+    }
+}
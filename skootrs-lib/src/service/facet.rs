@@ -21,14 +21,15 @@
 #![allow(clippy::module_name_repetitions)]
 #![allow(clippy::unused_self)]
 
-use std::str::FromStr;
+use std::{collections::BTreeMap, str::FromStr};
 
 use askama::Template;
 use chrono::Datelike;
+use sha2::Digest;
 
 use tracing::info;
 
-use crate::service::source::SourceService;
+use crate::{service::source::SourceService, SKOOTRS_VERSION};
 use skootrs_model::{
     security_insights::insights10::{
         SecurityInsightsVersion100YamlSchema,
@@ -39,25 +40,168 @@ use skootrs_model::{
         SecurityInsightsVersion100YamlSchemaHeader,
         SecurityInsightsVersion100YamlSchemaHeaderSchemaVersion,
         SecurityInsightsVersion100YamlSchemaProjectLifecycle,
+        SecurityInsightsVersion100YamlSchemaProjectLifecycleReleaseProcess,
         SecurityInsightsVersion100YamlSchemaProjectLifecycleStatus,
         SecurityInsightsVersion100YamlSchemaVulnerabilityReporting,
+        SecurityInsightsVersion100YamlSchemaVulnerabilityReportingComment,
+        SecurityInsightsVersion100YamlSchemaVulnerabilityReportingPgpKey,
     },
     skootrs::{
+        best_practices::{BestPracticesBadge, BestPracticesConfig},
+        bitbucket::BitbucketConfig,
+        branch_protection::BranchProtectionConfig,
+        code_review::CodeReviewConfig,
+        container::ContainerConfig,
+        contributing::{ContributingConfig, ContributionSignOff},
+        dependency_updates::DependencyUpdateConfig,
         facet::{
             APIBundleFacet, APIBundleFacetParams, APIContent, CommonFacetCreateParams,
             FacetCreateParams, FacetSetCreateParams, InitializedFacet, SourceBundleFacet,
             SourceBundleFacetCreateParams, SourceFile, SourceFileContent, SupportedFacetType,
         },
-        label::Label,
-        InitializedEcosystem, InitializedGithubRepo, InitializedRepo, SkootError,
+        guac::GuacForwardingConfig,
+        license::LicenseConfig,
+        pinning::{is_workflow, PinningPolicy, PINNING_POLICY_PATH},
+        plan::PlannedAction,
+        publishing::{MavenRepository, PackageCoordinates, PackageKind, PublishPackagesConfig},
+        registry::{FacetRegistry, FacetRegistryEntry},
+        ruleset::RepoRulesetConfig,
+        scan_policy::VulnerabilityScannerConfig,
+        signing::{self, ArtifactSigningConfig},
+        InitializedEcosystem, InitializedModule, InitializedRepo, InitializedSource, RetryPolicy,
+        SecurityPolicyConfig, SkootError, PROVENANCE_VERIFICATION_FILE,
     },
 };
 
-use super::source::LocalSourceService;
+use super::{
+    best_practices::{BestPracticesService, LocalBestPracticesService},
+    bitbucket::BitbucketClient,
+    github_auth::GithubAuth,
+    metrics::{self, Counter},
+    pinning::ActionPinner,
+    read_only::ensure_writable,
+    repo_api::{self, BitbucketRepoApi, GithubRepoApi, HostRepoApi, RepoApiProvider},
+    source::LocalSourceService,
+    template::{TemplateOverrides, TemplateVariables},
+};
+
+/// The facets whose templates have changed what they generate since they were first released, along with
+/// the current version of their template. Bump a facet's version whenever its template changes so
+/// `project update` can tell which recorded facets were generated by an older template.
+const TEMPLATE_VERSIONS: &[(SupportedFacetType, u32)] =
+    &[(SupportedFacetType::DependencyUpdateTool, 2)];
+
+/// The template version of the facets whose templates haven't changed since they were first released.
+const INITIAL_TEMPLATE_VERSION: u32 = 1;
+
+/// Returns the current version of a facet's template.
+#[must_use]
+pub fn template_version(facet_type: &SupportedFacetType) -> u32 {
+    TEMPLATE_VERSIONS
+        .iter()
+        .find(|(t, _)| t == facet_type)
+        .map_or(INITIAL_TEMPLATE_VERSION, |(_, version)| *version)
+}
+
+/// Returns the fingerprint of the generator of a facet, the SHA-256 digest of the Skootrs version, the
+/// facet's template version and the ecosystem, modules and options it's generated with. Two facets with the
+/// same fingerprint were generated the same way.
+fn generator_fingerprint(params: &FacetCreateParams) -> String {
+    let (common, options) = match params {
+        FacetCreateParams::SourceBundle(params) => (&params.common, &params.options),
+        FacetCreateParams::APIBundle(params) => (&params.common, &params.options),
+    };
+    let facet_type = params.facet_type();
+    let mut generator = serde_json::json!({
+        "skootrs_version": SKOOTRS_VERSION,
+        "facet_type": facet_type.to_string(),
+        "template_version": template_version(&facet_type),
+        "ecosystem": common.ecosystem.name(),
+        "options": options,
+    });
+    // Facets of projects with a single module keep the fingerprint they had before projects could have
+    // several.
+    // The README shows the badge, so it's regenerated once the project has one.
+    if let Some(id) = common.best_practices_badge_id {
+        generator["best_practices_badge_id"] = id.into();
+    }
+    // The README and SECURITY-INSIGHTS tell how to verify signed releases.
+    if common.artifact_signing {
+        generator["artifact_signing"] = true.into();
+    }
+    if common.modules.len() > 1 {
+        generator["modules"] = common
+            .modules
+            .iter()
+            .map(|module| format!("{}:{}", module.path, module.ecosystem.name()))
+            .collect();
+    }
+    format!(
+        "{:x}",
+        sha2::Sha256::digest(generator.to_string().as_bytes())
+    )
+}
 
 /// The `LocalFacetService` struct represents a service for creating and managing facets on the local machine.
-#[derive(Debug)]
-pub struct LocalFacetService {}
+#[derive(Debug, Default)]
+pub struct LocalFacetService {
+    /// The approved variables that are injected into the content of source bundle facets.
+    pub template_variables: TemplateVariables,
+    /// The files that replace the built-in content of source bundle facets.
+    pub template_overrides: TemplateOverrides,
+    /// The credential used for the Github API by API bundle facets.
+    pub github_auth: GithubAuth,
+    /// How the Github API calls of API bundle facets are retried when they fail with a transient error or hit
+    /// a rate limit.
+    pub retry_policy: RetryPolicy,
+    /// Whether facets are refused from being written to the source or enabled on the repo.
+    pub read_only: bool,
+    /// How the security policy facets tell people to report vulnerabilities.
+    pub security_policy: SecurityPolicyConfig,
+    /// Whether projects get the `VulnerabilityReporting` facet, which enables Github's private vulnerability
+    /// reporting that the security policy facets point to.
+    pub private_vulnerability_reporting: bool,
+    /// The rules the `BranchProtection` facet enables, unless the facet is added with options overriding them.
+    pub branch_protection: BranchProtectionConfig,
+    /// Who owns the code and how many approvals the `CodeReview` facet requires, unless the facet is added
+    /// with options overriding them.
+    pub code_review: CodeReviewConfig,
+    /// The license the `License` facet licenses projects under, unless the facet is added with options
+    /// overriding it. The README mentions it too.
+    pub license: LicenseConfig,
+    /// How the `ContributingPolicy` facet asks contributors to certify their changes, unless the facet is
+    /// added with options overriding it, and who the `CodeOfConduct` facet has violations reported to.
+    pub contributing: ContributingConfig,
+    /// Where the `RepoRuleset` facet creates its ruleset and what it's called.
+    pub repo_ruleset: RepoRulesetConfig,
+    /// Where the `GUACForwardingConfig` facet forwards SBOMs and SLSA attestations, unless the facet is added
+    /// with options overriding it.
+    pub guac_forwarding: GuacForwardingConfig,
+    /// Which actions and reusable workflows the `PinnedDependencies` facet lets projects reference by tag.
+    pub pinning: PinningPolicy,
+    /// What the `DependencyUpdateTool` facet has Dependabot keep up to date, unless the facet is added with
+    /// options overriding it.
+    pub dependency_updates: DependencyUpdateConfig,
+    /// Which findings fail the vulnerability scans, unless the `VulnerabilityScanner` facet is added with
+    /// options overriding it.
+    pub vulnerability_scanner: VulnerabilityScannerConfig,
+    /// Where the `PublishPackages` facet publishes packages to, unless the facet is added with options
+    /// overriding it.
+    pub publish_packages: PublishPackagesConfig,
+    /// Where the `Container` facet pushes images to and what they're based on, unless the facet is added
+    /// with options overriding it.
+    pub container: ContainerConfig,
+    /// What the `ArtifactSigning` facet signs along with the assets of projects' releases.
+    pub artifact_signing: ArtifactSigningConfig,
+    /// Where the `BestPracticesBadge` facet looks up projects' OpenSSF Best Practices Badges, which the README
+    /// shows.
+    pub best_practices: BestPracticesConfig,
+    /// How the Bitbucket API is reached to enable API bundle facets on repos hosted on Bitbucket.
+    pub bitbucket: BitbucketConfig,
+    /// Pins the actions of every generated workflow to commit SHAs, or `None` if projects don't get the
+    /// `PinnedDependencies` facet.
+    pub action_pinner: Option<ActionPinner>,
+}
 
 /// The `RootFacetService` trait provides an interface for initializing and managing a project's facets.
 /// This includes things like initializing and managing source files, source bundles, and API bundles.
@@ -71,6 +215,57 @@ pub trait RootFacetService {
         &self,
         params: FacetSetCreateParams,
     ) -> impl std::future::Future<Output = Result<Vec<InitializedFacet>, SkootError>> + Send;
+
+    /// Checks whether the settings an API bundle facet enabled on the repo are still enabled.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the settings can't be fetched or there's no way to check the facet type.
+    fn verify_api_bundle(
+        &self,
+        repo: &InitializedRepo,
+        facet_type: &SupportedFacetType,
+    ) -> impl std::future::Future<Output = Result<bool, SkootError>> + Send;
+
+    /// Gets the current status of a project's OpenSSF Best Practices Badge.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there's no badge with the ID or it can't be fetched.
+    fn best_practices_badge(
+        &self,
+        id: u64,
+    ) -> impl std::future::Future<Output = Result<BestPracticesBadge, SkootError>> + Send;
+
+    /// Returns the files and API calls initializing a facet would write and make, without changing anything.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the facet's content can't be rendered.
+    fn plan(&self, params: &FacetCreateParams) -> Result<Vec<PlannedAction>, SkootError>;
+
+    /// Returns the content of the files a source bundle facet would write, without writing them to the
+    /// source or calling the repo host.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the facet's content can't be rendered or the facet is an API bundle facet, which
+    /// has no content.
+    fn render(&self, params: &FacetCreateParams) -> Result<Vec<SourceFileContent>, SkootError>;
+
+    /// Tears down a facet. The files of a source bundle facet are removed from the source, leaving the
+    /// removal to be committed by the caller, and the settings of an API bundle facet are reverted on the
+    /// repo host.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the files can't be removed or the API bundle facet can't be reverted.
+    fn delete(
+        &self,
+        source: &InitializedSource,
+        repo: &InitializedRepo,
+        facet: &InitializedFacet,
+    ) -> impl std::future::Future<Output = Result<(), SkootError>> + Send;
 }
 
 /// The `SourceBundleFacetService` trait provides an interface for initializing and managing a project's source
@@ -100,52 +295,12 @@ impl SourceBundleFacetService for LocalFacetService {
         &self,
         params: SourceBundleFacetCreateParams,
     ) -> Result<SourceBundleFacet, SkootError> {
-        let source_service = LocalSourceService {};
-        let default_source_bundle_content_handler = DefaultSourceBundleContentHandler {};
-        // TODO: Update this to be more generic on the repo service
-        let language_specific_source_bundle_content_handler = match params.common.ecosystem {
-            InitializedEcosystem::Go(_) => GoGithubSourceBundleContentHandler {},
-            InitializedEcosystem::Maven(_) => todo!(),
-        };
-
-        let source_bundle_content = match params.facet_type {
-            SupportedFacetType::Readme
-            | SupportedFacetType::License
-            | SupportedFacetType::SecurityPolicy
-            | SupportedFacetType::Scorecard
-            | SupportedFacetType::SecurityInsights => {
-                default_source_bundle_content_handler.generate_content(&params)?
-            }
-            SupportedFacetType::Gitignore
-            | SupportedFacetType::SLSABuild
-            | SupportedFacetType::DependencyUpdateTool => {
-                language_specific_source_bundle_content_handler.generate_content(&params)?
-            }
-            SupportedFacetType::SBOMGenerator => todo!(),
-            SupportedFacetType::StaticCodeAnalysis => todo!(),
-            SupportedFacetType::BranchProtection => todo!(),
-            SupportedFacetType::CodeReview => todo!(),
-            SupportedFacetType::Fuzzing => {
-                language_specific_source_bundle_content_handler.generate_content(&params)?
-            }
-            SupportedFacetType::PublishPackages => todo!(),
-            SupportedFacetType::PinnedDependencies => todo!(),
-            SupportedFacetType::SAST => {
-                default_source_bundle_content_handler.generate_content(&params)?
-            }
-            SupportedFacetType::VulnerabilityScanner => todo!(),
-            SupportedFacetType::GUACForwardingConfig => todo!(),
-            SupportedFacetType::Allstar => todo!(),
-            SupportedFacetType::DefaultSourceCode => {
-                language_specific_source_bundle_content_handler.generate_content(&params)?
-            }
-            SupportedFacetType::VulnerabilityReporting => {
-                unimplemented!("VulnerabilityReporting is not implemented for source bundles")
-            }
-            SupportedFacetType::Other => todo!(),
+        let source_service = LocalSourceService {
+            read_only: self.read_only,
+            ..Default::default()
         };
-
-        for source_file_content in &source_bundle_content.source_files_content {
+        let source_files_content = self.render_source_bundle(&params)?;
+        for source_file_content in &source_files_content {
             info!(
                 "Starting to write file {} to {}",
                 source_file_content.name, source_file_content.path
@@ -154,12 +309,11 @@ impl SourceBundleFacetService for LocalFacetService {
                 params.common.source.clone(),
                 source_file_content.path.clone(),
                 source_file_content.name.clone(),
-                source_file_content.content.clone(),
+                &source_file_content.content,
             )?;
         }
 
-        let source_files: Vec<SourceFile> = source_bundle_content
-            .source_files_content
+        let source_files: Vec<SourceFile> = source_files_content
             .iter()
             .map(|source_file_content| {
                 Ok::<SourceFile, SkootError>(SourceFile {
@@ -174,17 +328,320 @@ impl SourceBundleFacetService for LocalFacetService {
             })
             .collect::<Result<Vec<_>, _>>()?;
 
+        // The coordinates are recorded so the published packages can be found without parsing the workflows.
+        let packages = match params.facet_type {
+            SupportedFacetType::PublishPackages => {
+                let config = self.publish_packages.with_options(&params.options)?;
+                let mut packages = vec![];
+                for module in params.common.project_modules() {
+                    for package in config.packages(&params.common.repo, &module.ecosystem) {
+                        if !packages.contains(&package) {
+                            packages.push(package);
+                        }
+                    }
+                }
+                packages
+            }
+            _ => vec![],
+        };
+
         let source_bundle_facet = SourceBundleFacet {
             source_files: Some(source_files),
             facet_type: params.facet_type,
             source_files_content: None,
             labels: params.labels,
+            packages,
+            template_version: None,
+            generator_fingerprint: None,
         };
 
         Ok(source_bundle_facet)
     }
 }
 
+impl LocalFacetService {
+    /// Returns the branch protection rules and code review config an API bundle facet is enabled with. The
+    /// options the facet was added with override the code review config for `CodeReview` and the branch
+    /// protection rules for the other facets.
+    fn api_bundle_configs(
+        &self,
+        params: &APIBundleFacetParams,
+    ) -> Result<(BranchProtectionConfig, CodeReviewConfig), SkootError> {
+        if params.facet_type == SupportedFacetType::CodeReview {
+            Ok((
+                self.branch_protection.clone(),
+                self.code_review.with_options(&params.options)?,
+            ))
+        } else {
+            Ok((
+                self.branch_protection.with_options(&params.options)?,
+                self.code_review.clone(),
+            ))
+        }
+    }
+
+    /// Returns the API of the repo's host that API bundle facets are enabled with.
+    fn repo_api(
+        &self,
+        repo: &InitializedRepo,
+        branch_protection: BranchProtectionConfig,
+        code_review: CodeReviewConfig,
+    ) -> Result<HostRepoApi, SkootError> {
+        match repo {
+            InitializedRepo::Github(repo) => Ok(HostRepoApi::Github(GithubRepoApi {
+                repo: repo.clone(),
+                github_auth: self.github_auth.clone(),
+                retry_policy: self.retry_policy.clone(),
+                branch_protection,
+                code_review,
+                repo_ruleset: self.repo_ruleset.clone(),
+            })),
+            InitializedRepo::Bitbucket(repo) => Ok(HostRepoApi::Bitbucket(BitbucketRepoApi {
+                repo: repo.clone(),
+                client: BitbucketClient {
+                    config: self.bitbucket.clone(),
+                },
+                branch_protection,
+            })),
+            InitializedRepo::GenericGit(g) => Err(SkootError::from(format!(
+                "{} is a generic git remote which has no API",
+                g.remote_url
+            ))),
+        }
+    }
+
+    /// Looks up the project's OpenSSF Best Practices Badge and records it in the facet, so the README can show
+    /// the badge. The project has to be registered on the badge site by a maintainer first.
+    async fn generate_best_practices_badge(
+        &self,
+        params: APIBundleFacetParams,
+    ) -> Result<APIBundleFacet, SkootError> {
+        let Some(badge) = (LocalBestPracticesService {
+            config: self.best_practices.clone(),
+        })
+        .find(&params.common.repo)
+        .await?
+        else {
+            return Err(format!(
+                "{} isn't registered for the OpenSSF Best Practices Badge. Register it at {} and add the {} \
+                 facet again",
+                params.common.repo.full_url(),
+                self.best_practices.registration_url(),
+                params.facet_type
+            )
+            .into());
+        };
+        info!(
+            "Found OpenSSF Best Practices Badge {} of {} with level {}",
+            badge.id,
+            params.common.repo.full_url(),
+            badge.badge_level
+        );
+        Ok(APIBundleFacet {
+            apis: vec![APIContent {
+                name: "Look up the OpenSSF Best Practices Badge".to_string(),
+                url: self.best_practices.project_url(badge.id),
+                response: serde_json::to_string(&badge)?,
+            }],
+            facet_type: params.facet_type,
+            labels: params.labels,
+            skipped_reason: None,
+            template_version: None,
+            generator_fingerprint: None,
+        })
+    }
+
+    /// Renders the files of a source bundle facet with the template overrides and template variables applied.
+    /// Facets that are specific to an ecosystem are rendered for each of the project's modules they apply to
+    /// and the other facets once for the project.
+    fn render_source_bundle(
+        &self,
+        params: &SourceBundleFacetCreateParams,
+    ) -> Result<Vec<SourceFileContent>, SkootError> {
+        let modules = params.common.project_modules();
+        let (language_specific_modules, other_modules): (Vec<_>, Vec<_>) = modules
+            .iter()
+            .partition(|module| is_language_specific(&params.facet_type, &module.ecosystem));
+
+        let mut source_files_content = vec![];
+        if let Some(module) = other_modules.first().or_else(|| {
+            language_specific_modules
+                .iter()
+                .find(|module| extends_default_content(&params.facet_type, &module.ecosystem))
+        }) {
+            let module_params = SourceBundleFacetCreateParams {
+                common: params.common.for_module(module),
+                ..params.clone()
+            };
+            source_files_content = self.override_templates(
+                &module.ecosystem,
+                self.generate_default_content(&module_params)?,
+            )?;
+        }
+        for module in language_specific_modules {
+            let module_params = SourceBundleFacetCreateParams {
+                common: params.common.for_module(module),
+                ..params.clone()
+            };
+            let module_files_content = self.override_templates(
+                &module.ecosystem,
+                self.language_specific_handler(&module.ecosystem)
+                    .generate_content(&module_params)?,
+            )?;
+            for source_file_content in module_files_content {
+                add_module_file(
+                    &mut source_files_content,
+                    relocate_module_file(module, source_file_content),
+                )?;
+            }
+        }
+
+        source_files_content
+            .into_iter()
+            .map(|source_file_content| {
+                let mut content = self
+                    .template_variables
+                    .substitute(&source_file_content.content)?;
+                if let Some(action_pinner) = &self.action_pinner {
+                    if is_workflow(&format!(
+                        "{}/{}",
+                        source_file_content.path.trim_end_matches('/'),
+                        source_file_content.name
+                    )) {
+                        content = action_pinner.pin(&content)?;
+                    }
+                }
+                Ok(SourceFileContent {
+                    content,
+                    ..source_file_content
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the handler for the facets that are specific to an ecosystem.
+    // TODO: Update this to be more generic on the repo service
+    fn language_specific_handler(
+        &self,
+        ecosystem: &InitializedEcosystem,
+    ) -> Box<dyn SourceBundleContentGenerator + '_> {
+        match ecosystem {
+            InitializedEcosystem::Go(_) => Box::new(GoGithubSourceBundleContentHandler {
+                publish_packages: &self.publish_packages,
+                dependency_updates: &self.dependency_updates,
+                vulnerability_scanner: &self.vulnerability_scanner,
+            }),
+            InitializedEcosystem::Maven(_) => Box::new(MavenGithubSourceBundleContentHandler {
+                publish_packages: &self.publish_packages,
+                dependency_updates: &self.dependency_updates,
+                container: &self.container,
+            }),
+            InitializedEcosystem::Python(_) => Box::new(PythonGithubSourceBundleContentHandler {
+                dependency_updates: &self.dependency_updates,
+                container: &self.container,
+            }),
+            InitializedEcosystem::Rust(_) => Box::new(RustGithubSourceBundleContentHandler {
+                dependency_updates: &self.dependency_updates,
+            }),
+        }
+    }
+
+    /// Generates the content of a facet that isn't specific to an ecosystem.
+    fn generate_default_content(
+        &self,
+        params: &SourceBundleFacetCreateParams,
+    ) -> Result<SourceBundleContent, SkootError> {
+        let default_source_bundle_content_handler = DefaultSourceBundleContentHandler {
+            security_policy: &self.security_policy,
+            private_vulnerability_reporting: self.private_vulnerability_reporting,
+            license: &self.license,
+            contributing: &self.contributing,
+            guac_forwarding: &self.guac_forwarding,
+            pinning: &self.pinning,
+            vulnerability_scanner: &self.vulnerability_scanner,
+            best_practices: &self.best_practices,
+            publish_packages: &self.publish_packages,
+            container: &self.container,
+            artifact_signing: &self.artifact_signing,
+        };
+
+        match params.facet_type {
+            SupportedFacetType::Readme
+            | SupportedFacetType::License
+            | SupportedFacetType::SecurityPolicy
+            | SupportedFacetType::CodeOfConduct
+            | SupportedFacetType::ContributingPolicy
+            | SupportedFacetType::Scorecard
+            | SupportedFacetType::StateVerification
+            | SupportedFacetType::SecurityInsights
+            | SupportedFacetType::GUACForwardingConfig
+            | SupportedFacetType::PinnedDependencies
+            | SupportedFacetType::SBOMGenerator
+            | SupportedFacetType::SLSAVerification
+            | SupportedFacetType::ArtifactSigning
+            | SupportedFacetType::SAST
+            | SupportedFacetType::VulnerabilityScanner => {
+                default_source_bundle_content_handler.generate_content(params)
+            }
+            SupportedFacetType::Gitignore
+            | SupportedFacetType::SLSABuild
+            | SupportedFacetType::DependencyUpdateTool
+            | SupportedFacetType::Fuzzing
+            | SupportedFacetType::PublishPackages
+            | SupportedFacetType::Container
+            | SupportedFacetType::DefaultSourceCode => {
+                unreachable!("{} is specific to an ecosystem", params.facet_type)
+            }
+            SupportedFacetType::BranchProtection
+            | SupportedFacetType::CodeReview
+            | SupportedFacetType::VulnerabilityReporting
+            | SupportedFacetType::RepoRuleset
+            | SupportedFacetType::BestPracticesBadge => Err(SkootError::Config(format!(
+                "{} is an API bundle facet, it can't be generated as a source bundle",
+                params.facet_type
+            ))),
+            SupportedFacetType::StaticCodeAnalysis
+            | SupportedFacetType::Allstar
+            | SupportedFacetType::Other => Err(SkootError::Config(format!(
+                "{} isn't implemented yet",
+                params.facet_type
+            ))),
+        }
+    }
+
+    /// Replaces the content of the files there's a template override for.
+    fn override_templates(
+        &self,
+        ecosystem: &InitializedEcosystem,
+        source_bundle_content: SourceBundleContent,
+    ) -> Result<Vec<SourceFileContent>, SkootError> {
+        source_bundle_content
+            .source_files_content
+            .into_iter()
+            .map(|source_file_content| {
+                let content = match self.template_overrides.find(
+                    ecosystem,
+                    &source_file_content.path,
+                    &source_file_content.name,
+                )? {
+                    Some(content) => {
+                        info!(
+                            "Using template override for {} in {}",
+                            source_file_content.name, source_file_content.path
+                        );
+                        content
+                    }
+                    None => source_file_content.content,
+                };
+                Ok(SourceFileContent {
+                    content,
+                    ..source_file_content
+                })
+            })
+            .collect()
+    }
+}
+
 /// The `APIBundleFacetService` trait provides an interface for initializing and managing a project's API
 /// bundle facets. This includes things like initializing and managing API calls to services like Github.
 ///
@@ -198,17 +655,40 @@ pub trait APIBundleFacetService {
 
 impl APIBundleFacetService for LocalFacetService {
     async fn initialize(&self, params: APIBundleFacetParams) -> Result<APIBundleFacet, SkootError> {
-        // TODO: This should support more than just Github
-        match params.facet_type {
-            SupportedFacetType::CodeReview
-            | SupportedFacetType::BranchProtection
-            | SupportedFacetType::VulnerabilityReporting => {
-                let github_api_bundle_handler = GithubAPIBundleHandler {};
-                let api_bundle_facet = github_api_bundle_handler.generate(&params).await?;
-                Ok(api_bundle_facet)
-            }
-            _ => todo!("Not implemented yet"),
+        ensure_writable(
+            self.read_only,
+            format_args!(
+                "enable {} on {}",
+                params.facet_type,
+                params.common.repo.full_url()
+            ),
+        )?;
+        // The badge is looked up by the repo's URL, so it works for any repo host.
+        if params.facet_type == SupportedFacetType::BestPracticesBadge {
+            return self.generate_best_practices_badge(params).await;
+        }
+        if let InitializedRepo::GenericGit(g) = &params.common.repo {
+            let skipped_reason = format!(
+                "{} is a generic git remote which has no API to enable {} with",
+                g.remote_url, params.facet_type
+            );
+            info!("Skipping facet {}: {skipped_reason}", params.facet_type);
+            return Ok(APIBundleFacet {
+                facet_type: params.facet_type,
+                apis: vec![],
+                labels: params.labels,
+                skipped_reason: Some(skipped_reason),
+                template_version: None,
+                generator_fingerprint: None,
+            });
         }
+        let (branch_protection, code_review) = self.api_bundle_configs(&params)?;
+        let repo_api = self.repo_api(&params.common.repo, branch_protection, code_review)?;
+        let api_bundle_facet = repo_api::enable(&repo_api, &params.facet_type).await?;
+        Ok(APIBundleFacet {
+            labels: params.labels,
+            ..api_bundle_facet
+        })
     }
 }
 
@@ -220,14 +700,29 @@ pub struct SourceBundleContent {
 
 impl RootFacetService for LocalFacetService {
     async fn initialize(&self, params: FacetCreateParams) -> Result<InitializedFacet, SkootError> {
+        // Recorded so it's clear which generator produced the facet and whether it needs regenerating.
+        let version = Some(template_version(&params.facet_type()));
+        let fingerprint = Some(generator_fingerprint(&params));
+        metrics::global().increment(
+            Counter::FacetsCreated,
+            &[("facet_type", &params.facet_type().to_string())],
+        );
         match params {
             FacetCreateParams::SourceBundle(params) => {
                 let source_bundle_facet = SourceBundleFacetService::initialize(self, params)?;
-                Ok(InitializedFacet::SourceBundle(source_bundle_facet))
+                Ok(InitializedFacet::SourceBundle(SourceBundleFacet {
+                    template_version: version,
+                    generator_fingerprint: fingerprint,
+                    ..source_bundle_facet
+                }))
             }
             FacetCreateParams::APIBundle(params) => {
                 let api_bundle_facet = APIBundleFacetService::initialize(self, params).await?;
-                Ok(InitializedFacet::APIBundle(api_bundle_facet))
+                Ok(InitializedFacet::APIBundle(APIBundleFacet {
+                    template_version: version,
+                    generator_fingerprint: fingerprint,
+                    ..api_bundle_facet
+                }))
             }
         }
     }
@@ -236,126 +731,155 @@ impl RootFacetService for LocalFacetService {
         &self,
         params: FacetSetCreateParams,
     ) -> Result<Vec<InitializedFacet>, SkootError> {
-        let futures = params
+        // CodeReview changes the protection of branches, so it runs after the other facets to keep
+        // BranchProtection from overwriting the reviews it requires.
+        let (code_review_params, other_params): (Vec<_>, Vec<_>) = params
             .facets_params
-            .iter()
-            .map(move |params| RootFacetService::initialize(self, params.clone()));
-
-        let results = futures::future::try_join_all(futures).await?;
+            .into_iter()
+            .partition(|params| params.facet_type() == SupportedFacetType::CodeReview);
+        let futures = other_params
+            .into_iter()
+            .map(move |params| RootFacetService::initialize(self, params));
+
+        let mut results = futures::future::try_join_all(futures).await?;
+        for params in code_review_params {
+            results.push(RootFacetService::initialize(self, params).await?);
+        }
         Ok(results)
     }
-}
 
-/// The `APIBundleHandler` trait provides an interface for generating an `APIBundleFacet`.
-/// This includes calling APIs to services like Github to enable features like branch protection,
-/// vulnerability reporting, etc.
-trait APIBundleHandler {
-    async fn generate(&self, params: &APIBundleFacetParams) -> Result<APIBundleFacet, SkootError>;
-}
+    async fn verify_api_bundle(
+        &self,
+        repo: &InitializedRepo,
+        facet_type: &SupportedFacetType,
+    ) -> Result<bool, SkootError> {
+        if let InitializedRepo::GenericGit(g) = repo {
+            return Err(SkootError::from(format!(
+                "{} is a generic git remote which has no API to verify {facet_type} with",
+                g.remote_url
+            )));
+        }
+        self.repo_api(
+            repo,
+            self.branch_protection.clone(),
+            self.code_review.clone(),
+        )?
+        .verify(facet_type)
+        .await
+    }
 
-/// The `GithubAPIBundleHandler` struct represents a handler for generating an `APIBundleFacet` related to
-/// API calls made to Github.
-struct GithubAPIBundleHandler {}
+    async fn best_practices_badge(&self, id: u64) -> Result<BestPracticesBadge, SkootError> {
+        LocalBestPracticesService {
+            config: self.best_practices.clone(),
+        }
+        .get(id)
+        .await
+    }
 
-impl APIBundleHandler for GithubAPIBundleHandler {
-    async fn generate(&self, params: &APIBundleFacetParams) -> Result<APIBundleFacet, SkootError> {
-        let InitializedRepo::Github(repo) = &params.common.repo;
-        match params.facet_type {
-            SupportedFacetType::BranchProtection => self.generate_branch_protection(repo).await,
-            SupportedFacetType::VulnerabilityReporting => {
-                self.generate_vulnerability_reporting(repo).await
+    fn plan(&self, params: &FacetCreateParams) -> Result<Vec<PlannedAction>, SkootError> {
+        match params {
+            FacetCreateParams::SourceBundle(params) => Ok(self
+                .render_source_bundle(params)?
+                .into_iter()
+                .map(|source_file_content| PlannedAction::WriteFile {
+                    path: source_file_content.path,
+                    name: source_file_content.name,
+                    content: source_file_content.content,
+                    facet_type: Some(params.facet_type.clone()),
+                })
+                .collect()),
+            FacetCreateParams::APIBundle(params)
+                if params.facet_type == SupportedFacetType::BestPracticesBadge =>
+            {
+                Ok(vec![PlannedAction::ApiCall {
+                    description: "Look up the OpenSSF Best Practices Badge".to_string(),
+                    method: "GET".to_string(),
+                    url: format!(
+                        "{}/projects.json?url={}",
+                        self.best_practices.api_url.trim_end_matches('/'),
+                        params.common.repo.full_url()
+                    ),
+                    body: None,
+                    facet_type: Some(params.facet_type.clone()),
+                }])
+            }
+            // API bundle facets are skipped for generic git remotes.
+            FacetCreateParams::APIBundle(params)
+                if matches!(params.common.repo, InitializedRepo::GenericGit(_)) =>
+            {
+                Ok(vec![])
+            }
+            FacetCreateParams::APIBundle(params) => {
+                let (branch_protection, code_review) = self.api_bundle_configs(params)?;
+                self.repo_api(&params.common.repo, branch_protection, code_review)?
+                    .plan(&params.facet_type)
             }
-            _ => todo!("Not implemented yet"),
         }
     }
-}
-
-impl GithubAPIBundleHandler {
-    async fn generate_branch_protection(
-        &self,
-        repo: &InitializedGithubRepo,
-    ) -> Result<APIBundleFacet, SkootError> {
-        let enforce_branch_protection_endpoint = format!(
-            "/repos/{owner}/{repo}/branches/{branch}/protection",
-            owner = repo.organization.get_name(),
-            repo = repo.name,
-            branch = "main",
-        );
-        info!(
-            "Enabling branch protection for {}",
-            enforce_branch_protection_endpoint
-        );
-        // TODO: This should be a struct that serializes to json instead of just json directly
-        let enforce_branch_protection_body = serde_json::json!({
-            "enforce_admins": true,
-            "required_pull_request_reviews": null,
-            "required_status_checks": null,
-            "restrictions": null,
-            "required_linear_history": true,
-            "allow_force_pushes": false,
-            "allow_deletions": null,
-        });
 
-        // FIXME: I don't quite know why in some cases octocrab loses my auth and I have to re-authenticate
-        let o: octocrab::Octocrab = octocrab::Octocrab::builder()
-            .personal_token(
-                std::env::var("GITHUB_TOKEN").expect("GITHUB_TOKEN env var must be populated"),
-            )
-            .build()?;
-        octocrab::initialise(o);
-        let response: serde_json::Value = octocrab::instance()
-            .put(
-                &enforce_branch_protection_endpoint,
-                Some(&enforce_branch_protection_body),
-            )
-            .await?;
-
-        let apis = vec![APIContent {
-            name: "Enforce Branch Protection".to_string(),
-            url: enforce_branch_protection_endpoint,
-            response: serde_json::to_string_pretty(&response)?,
-        }];
-
-        Ok(APIBundleFacet {
-            facet_type: SupportedFacetType::BranchProtection,
-            apis,
-            labels: vec![],
-        })
+    fn render(&self, params: &FacetCreateParams) -> Result<Vec<SourceFileContent>, SkootError> {
+        match params {
+            FacetCreateParams::SourceBundle(params) => self.render_source_bundle(params),
+            FacetCreateParams::APIBundle(params) => Err(SkootError::from(format!(
+                "{} is enabled through the repo host's API and has no content to render",
+                params.facet_type
+            ))),
+        }
     }
 
-    async fn generate_vulnerability_reporting(
+    async fn delete(
         &self,
-        repo: &InitializedGithubRepo,
-    ) -> Result<APIBundleFacet, SkootError> {
-        let vulnerability_reporting_endpoint = format!(
-            "/repos/{owner}/{repo}/private-vulnerability-reporting",
-            owner = repo.organization.get_name(),
-            repo = repo.name,
-        );
-        info!(
-            "Enabling vulnerability reporting for {}",
-            &vulnerability_reporting_endpoint
-        );
-        // Note: This call just returns a status with no JSON output also the normal .put I think expects json
-        // output and will fail.
-        octocrab::instance()
-            ._put(&vulnerability_reporting_endpoint, None::<&()>)
-            .await?;
-        let apis = vec![APIContent {
-            name: "Enabling vulnerability reporting".to_string(),
-            url: vulnerability_reporting_endpoint.clone(),
-            response: "Success".to_string(),
-        }];
-        info!(
-            "Vulnerability reporting enabled for {}",
-            &vulnerability_reporting_endpoint
-        );
-
-        Ok(APIBundleFacet {
-            facet_type: SupportedFacetType::VulnerabilityReporting,
-            apis,
-            labels: vec![],
-        })
+        source: &InitializedSource,
+        repo: &InitializedRepo,
+        facet: &InitializedFacet,
+    ) -> Result<(), SkootError> {
+        ensure_writable(
+            self.read_only,
+            format_args!("tear down a facet of {}", repo.full_url()),
+        )?;
+        match facet {
+            InitializedFacet::SourceBundle(s) => {
+                let source_service = LocalSourceService {
+                    read_only: self.read_only,
+                    ..Default::default()
+                };
+                for source_file in s.source_files.iter().flatten() {
+                    info!(
+                        "Removing file {} from {}",
+                        source_file.name, source_file.path
+                    );
+                    source_service.remove_file(
+                        source,
+                        &source_file.path,
+                        source_file.name.clone(),
+                    )?;
+                }
+                Ok(())
+            }
+            // Nothing was enabled for a skipped facet so there is nothing to revert.
+            InitializedFacet::APIBundle(a) if a.skipped_reason.is_some() => Ok(()),
+            // The badge was only looked up, and the project stays registered on the badge site.
+            InitializedFacet::APIBundle(a)
+                if a.facet_type == SupportedFacetType::BestPracticesBadge =>
+            {
+                Ok(())
+            }
+            InitializedFacet::APIBundle(a) => {
+                if let InitializedRepo::GenericGit(g) = repo {
+                    return Err(SkootError::from(format!(
+                        "{} is a generic git remote which has no API to revert {} with",
+                        g.remote_url, a.facet_type
+                    )));
+                }
+                self.repo_api(
+                    repo,
+                    self.branch_protection.clone(),
+                    self.code_review.clone(),
+                )?
+                .revert(&a.facet_type)
+                .await
+            }
+        }
     }
 }
 
@@ -370,9 +894,21 @@ trait SourceBundleContentGenerator {
 
 /// Handles the generation of source files content that are generic to all projects by default,
 /// e.g. README.md, LICENSE, etc.
-struct DefaultSourceBundleContentHandler {}
+struct DefaultSourceBundleContentHandler<'a> {
+    security_policy: &'a SecurityPolicyConfig,
+    private_vulnerability_reporting: bool,
+    license: &'a LicenseConfig,
+    contributing: &'a ContributingConfig,
+    guac_forwarding: &'a GuacForwardingConfig,
+    pinning: &'a PinningPolicy,
+    vulnerability_scanner: &'a VulnerabilityScannerConfig,
+    best_practices: &'a BestPracticesConfig,
+    publish_packages: &'a PublishPackagesConfig,
+    container: &'a ContainerConfig,
+    artifact_signing: &'a ArtifactSigningConfig,
+}
 
-impl SourceBundleContentGenerator for DefaultSourceBundleContentHandler {
+impl SourceBundleContentGenerator for DefaultSourceBundleContentHandler<'_> {
     fn generate_content(
         &self,
         params: &SourceBundleFacetCreateParams,
@@ -381,14 +917,79 @@ impl SourceBundleContentGenerator for DefaultSourceBundleContentHandler {
             SupportedFacetType::Readme => self.generate_readme_content(params),
             SupportedFacetType::License => self.generate_license_content(params),
             SupportedFacetType::SecurityPolicy => self.generate_security_policy_content(params),
+            SupportedFacetType::CodeOfConduct => self.generate_code_of_conduct_content(params),
+            SupportedFacetType::ContributingPolicy => {
+                self.generate_contributing_policy_content(params)
+            }
             SupportedFacetType::Scorecard => self.generate_scorecard_content(params),
+            SupportedFacetType::StateVerification => {
+                self.generate_state_verification_content(params)
+            }
             SupportedFacetType::SecurityInsights => self.generate_security_insights_content(params),
             SupportedFacetType::SAST => self.generate_sast_content(params),
-            _ => todo!("Not implemented yet"),
+            SupportedFacetType::GUACForwardingConfig => {
+                self.generate_guac_forwarding_content(params)
+            }
+            SupportedFacetType::PinnedDependencies => {
+                self.generate_pinned_dependencies_content(params)
+            }
+            SupportedFacetType::VulnerabilityScanner => {
+                self.generate_vulnerability_scanner_content(params)
+            }
+            SupportedFacetType::SBOMGenerator => self.generate_sbom_generator_content(params),
+            SupportedFacetType::SLSAVerification => self.generate_slsa_verification_content(params),
+            SupportedFacetType::ArtifactSigning => self.generate_artifact_signing_content(params),
+            _ => Err(format!(
+                "Facet {} doesn't have content that isn't specific to an ecosystem",
+                params.facet_type
+            )
+            .into()),
         }
     }
 }
-impl DefaultSourceBundleContentHandler {
+impl DefaultSourceBundleContentHandler<'_> {
+    /// Returns where vulnerabilities are reported privately on Github, or `None` if the repo doesn't have
+    /// private vulnerability reporting enabled by the `VulnerabilityReporting` facet.
+    fn private_reporting_url(&self, repo: &InitializedRepo) -> Option<String> {
+        match repo {
+            InitializedRepo::Github(_) if self.private_vulnerability_reporting => {
+                Some(format!("{}/security/advisories/new", repo.full_url()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the images the `ArtifactSigning` facet signs for a project, without tags: the images its
+    /// modules publish, unless signing them is turned off, and the configured ones.
+    fn signed_images(&self, common: &CommonFacetCreateParams) -> Vec<String> {
+        let mut images = vec![];
+        if self.artifact_signing.sign_images {
+            for module in common.project_modules() {
+                match module.ecosystem {
+                    InitializedEcosystem::Go(_) => images.extend(
+                        self.publish_packages
+                            .packages(&common.repo, &module.ecosystem)
+                            .into_iter()
+                            .filter(|package| package.kind == PackageKind::Container)
+                            .map(|package| format!("{}/{}", package.registry, package.name)),
+                    ),
+                    InitializedEcosystem::Maven(_) | InitializedEcosystem::Python(_) => {
+                        images.push(self.container.image(&common.repo, &module));
+                    }
+                    InitializedEcosystem::Rust(_) => {}
+                }
+            }
+        }
+        images.extend(self.artifact_signing.images.iter().cloned());
+        let mut signed_images = vec![];
+        for image in images {
+            if !signed_images.contains(&image) {
+                signed_images.push(image);
+            }
+        }
+        signed_images
+    }
+
     fn generate_readme_content(
         &self,
         params: &SourceBundleFacetCreateParams,
@@ -397,13 +998,40 @@ impl DefaultSourceBundleContentHandler {
         #[template(path = "README.md", escape = "none")]
         struct ReadmeTemplateParams {
             project_name: String,
+            license_name: &'static str,
+            spdx_id: &'static str,
+            /// The image and page of the project's OpenSSF Best Practices Badge.
+            best_practices_badge: Option<(String, String)>,
+            /// How the project's signed releases are verified.
+            release_signing: Option<ReleaseSigning>,
+        }
+
+        struct ReleaseSigning {
+            identity: String,
+            issuer: &'static str,
+            images: Vec<String>,
         }
 
         let readme_template_params = ReadmeTemplateParams {
             project_name: params.common.project_name.clone(),
+            license_name: self.license.spdx_id.name(),
+            spdx_id: self.license.spdx_id.spdx_id(),
+            best_practices_badge: params.common.best_practices_badge_id.map(|id| {
+                (
+                    self.best_practices.badge_url(id),
+                    self.best_practices.project_url(id),
+                )
+            }),
+            release_signing: params.common.artifact_signing.then(|| ReleaseSigning {
+                identity: signing::certificate_identity(&params.common.repo),
+                issuer: signing::CERTIFICATE_OIDC_ISSUER,
+                images: self.signed_images(&params.common),
+            }),
         };
 
-        let content = readme_template_params.render()?;
+        let content = readme_template_params
+            .render()
+            .map_err(SkootError::template)?;
 
         Ok(SourceBundleContent {
             source_files_content: vec![SourceFileContent {
@@ -414,7 +1042,6 @@ impl DefaultSourceBundleContentHandler {
             facet_type: SupportedFacetType::Readme,
         })
     }
-    // TODO: Support more than Apache 2.0
     fn generate_license_content(
         &self,
         params: &SourceBundleFacetCreateParams,
@@ -424,14 +1051,19 @@ impl DefaultSourceBundleContentHandler {
         struct LicenseTemplateParams {
             project_name: String,
             date: i32,
+            spdx_id: &'static str,
         }
 
+        let license = self.license.with_options(&params.options)?;
         let license_template_params = LicenseTemplateParams {
             project_name: params.common.project_name.clone(),
             date: chrono::Utc::now().year(),
+            spdx_id: license.spdx_id.spdx_id(),
         };
 
-        let content = license_template_params.render()?;
+        let content = license_template_params
+            .render()
+            .map_err(SkootError::template)?;
 
         Ok(SourceBundleContent {
             source_files_content: vec![SourceFileContent {
@@ -442,18 +1074,32 @@ impl DefaultSourceBundleContentHandler {
             facet_type: SupportedFacetType::License,
         })
     }
-    // TODO: Create actual security policy
     fn generate_security_policy_content(
         &self,
-        _params: &SourceBundleFacetCreateParams,
+        params: &SourceBundleFacetCreateParams,
     ) -> Result<SourceBundleContent, SkootError> {
-        // TODO: Turn this into a real default security policy
         #[derive(Template)]
-        #[template(path = "SECURITY.prerelease.md", escape = "none")]
-        struct SecurityPolicyTemplateParams {}
+        #[template(path = "SECURITY.md", escape = "none")]
+        struct SecurityPolicyTemplateParams<'a> {
+            project_name: &'a str,
+            private_reporting_url: Option<String>,
+            contact_email: &'a Option<String>,
+            pgp_key: &'a Option<String>,
+            acknowledgement_days: u32,
+            embargo_days: u32,
+        }
 
-        let security_policy_template_params = SecurityPolicyTemplateParams {};
-        let content = security_policy_template_params.render()?;
+        let security_policy_template_params = SecurityPolicyTemplateParams {
+            project_name: &params.common.project_name,
+            private_reporting_url: self.private_reporting_url(&params.common.repo),
+            contact_email: &self.security_policy.contact_email,
+            pgp_key: &self.security_policy.pgp_key,
+            acknowledgement_days: self.security_policy.acknowledgement_days,
+            embargo_days: self.security_policy.embargo_days,
+        };
+        let content = security_policy_template_params
+            .render()
+            .map_err(SkootError::template)?;
 
         Ok(SourceBundleContent {
             source_files_content: vec![SourceFileContent {
@@ -465,6 +1111,76 @@ impl DefaultSourceBundleContentHandler {
         })
     }
 
+    fn generate_code_of_conduct_content(
+        &self,
+        params: &SourceBundleFacetCreateParams,
+    ) -> Result<SourceBundleContent, SkootError> {
+        #[derive(Template)]
+        #[template(path = "CODE_OF_CONDUCT.md", escape = "none")]
+        struct CodeOfConductTemplateParams<'a> {
+            project_name: &'a str,
+            contact: Option<&'a str>,
+        }
+
+        let code_of_conduct_template_params = CodeOfConductTemplateParams {
+            project_name: &params.common.project_name,
+            contact: self
+                .contributing
+                .conduct_contact
+                .as_deref()
+                .or(self.security_policy.contact_email.as_deref()),
+        };
+        let content = code_of_conduct_template_params
+            .render()
+            .map_err(SkootError::template)?;
+
+        Ok(SourceBundleContent {
+            source_files_content: vec![SourceFileContent {
+                name: "CODE_OF_CONDUCT.md".to_string(),
+                path: "./".to_string(),
+                content,
+            }],
+            facet_type: SupportedFacetType::CodeOfConduct,
+        })
+    }
+
+    fn generate_contributing_policy_content(
+        &self,
+        params: &SourceBundleFacetCreateParams,
+    ) -> Result<SourceBundleContent, SkootError> {
+        #[derive(Template)]
+        #[template(path = "CONTRIBUTING.md", escape = "none")]
+        struct ContributingTemplateParams<'a> {
+            project_name: &'a str,
+            repo_url: String,
+            dco: bool,
+            cla_url: Option<&'a str>,
+        }
+
+        let config = self.contributing.with_options(&params.options)?;
+        let contributing_template_params = ContributingTemplateParams {
+            project_name: &params.common.project_name,
+            repo_url: params.common.repo.full_url(),
+            dco: config.sign_off == ContributionSignOff::Dco,
+            cla_url: config
+                .cla_url
+                .as_deref()
+                .filter(|_| config.sign_off == ContributionSignOff::Cla),
+        };
+        let content = contributing_template_params
+            .render()
+            .map_err(SkootError::template)?;
+
+        Ok(SourceBundleContent {
+            source_files_content: vec![SourceFileContent {
+                name: "CONTRIBUTING.md".to_string(),
+                path: "./".to_string(),
+                content,
+            }],
+            facet_type: SupportedFacetType::ContributingPolicy,
+        })
+    }
+
     fn generate_scorecard_content(
         &self,
         _params: &SourceBundleFacetCreateParams,
@@ -475,7 +1191,9 @@ impl DefaultSourceBundleContentHandler {
         struct ScorecardTemplateParams {}
 
         let scorecard_template_params = ScorecardTemplateParams {};
-        let content = scorecard_template_params.render()?;
+        let content = scorecard_template_params
+            .render()
+            .map_err(SkootError::template)?;
 
         Ok(SourceBundleContent {
             source_files_content: vec![SourceFileContent {
@@ -487,62 +1205,61 @@ impl DefaultSourceBundleContentHandler {
         })
     }
 
+    fn generate_state_verification_content(
+        &self,
+        _params: &SourceBundleFacetCreateParams,
+    ) -> Result<SourceBundleContent, SkootError> {
+        #[derive(Template)]
+        #[template(path = "skootrs-verify.yml", escape = "none")]
+        struct StateVerificationTemplateParams {}
+
+        let state_verification_template_params = StateVerificationTemplateParams {};
+        let content = state_verification_template_params
+            .render()
+            .map_err(SkootError::template)?;
+
+        Ok(SourceBundleContent {
+            source_files_content: vec![SourceFileContent {
+                name: "skootrs-verify.yml".to_string(),
+                path: "./.github/workflows".to_string(),
+                content,
+            }],
+            facet_type: SupportedFacetType::StateVerification,
+        })
+    }
+
     #[allow(clippy::too_many_lines)]
     fn generate_security_insights_content(
         &self,
         params: &SourceBundleFacetCreateParams,
     ) -> Result<SourceBundleContent, SkootError> {
+        let sbom = SbomDocument::new(&params.common);
+        // Bitbucket serves files from `src` rather than `blob`, and has Downloads instead of releases.
+        let (file_path, sbom_path) = match &params.common.repo {
+            InitializedRepo::Bitbucket(_) => ("src/main", "downloads"),
+            _ => ("blob/main", "releases/latest/download"),
+        };
         let insights = SecurityInsightsVersion100YamlSchema {
             contribution_policy: SecurityInsightsVersion100YamlSchemaContributionPolicy {
                 accepts_automated_pull_requests: true,
                 accepts_pull_requests: true,
                 automated_tools_list: None,
-                code_of_conduct: None,
-                contributing_policy: None,
+                code_of_conduct: Some(format!("{}/blob/main/CODE_OF_CONDUCT.md", params.common.repo.full_url())),
+                contributing_policy: Some(format!("{}/blob/main/CONTRIBUTING.md", params.common.repo.full_url())),
             },
             dependencies: Some(SecurityInsightsVersion100YamlSchemaDependencies{
                 dependencies_lifecycle: None,
                 dependencies_lists: vec![
-                    format!("{}/blob/main/go.mod", &params.common.repo.full_url())
+                    format!("{}/{file_path}/{}", params.common.repo.full_url(), dependency_manifest(&params.common.ecosystem))
                 ],
                 env_dependencies_policy: None,
-                sbom: Some(vec![
-                    SecurityInsightsVersion100YamlSchemaDependenciesSbomItem {
-                        sbom_creation: Some(
-                            SecurityInsightsVersion100YamlSchemaDependenciesSbomItemSbomCreation::from_str("Created by goreleaser")?),
-                        sbom_file: Some(format!("{}/releases/latest/download/main-linux-amd64.spdx.sbom.json", &params.common.repo.full_url())), 
-                        sbom_format: Some("SPDX".to_string()),
-                        sbom_url: Some("https://spdx.github.io/spdx-spec/v2.3/".to_string()), 
-                    },
-                    SecurityInsightsVersion100YamlSchemaDependenciesSbomItem {
-                        sbom_creation: Some(
-                            SecurityInsightsVersion100YamlSchemaDependenciesSbomItemSbomCreation::from_str("Created by goreleaser")?),
-                        sbom_file: Some(format!("{}/releases/latest/download/main-linux-arm.spdx.sbom.json", &params.common.repo.full_url())), 
-                        sbom_format: Some("SPDX".to_string()),
-                        sbom_url: Some("https://spdx.github.io/spdx-spec/v2.3/".to_string()), 
-                    },
-                    SecurityInsightsVersion100YamlSchemaDependenciesSbomItem {
-                        sbom_creation: Some(
-                            SecurityInsightsVersion100YamlSchemaDependenciesSbomItemSbomCreation::from_str("Created by goreleaser")?),
-                        sbom_file: Some(format!("{}/releases/latest/download/main-linux-arm64.spdx.sbom.json", &params.common.repo.full_url())), 
-                        sbom_format: Some("SPDX".to_string()),
-                        sbom_url: Some("https://spdx.github.io/spdx-spec/v2.3/".to_string()), 
-                    },
-                    SecurityInsightsVersion100YamlSchemaDependenciesSbomItem {
-                        sbom_creation: Some(
-                            SecurityInsightsVersion100YamlSchemaDependenciesSbomItemSbomCreation::from_str("Created by goreleaser")?),
-                        sbom_file: Some(format!("{}/releases/latest/download/main-windows-amd64.exe.spdx.sbom.json", &params.common.repo.full_url())), 
-                        sbom_format: Some("SPDX".to_string()),
-                        sbom_url: Some("https://spdx.github.io/spdx-spec/v2.3/".to_string()), 
-                    },
-                    SecurityInsightsVersion100YamlSchemaDependenciesSbomItem {
-                        sbom_creation: Some(
-                            SecurityInsightsVersion100YamlSchemaDependenciesSbomItemSbomCreation::from_str("Created by goreleaser")?),
-                        sbom_file: Some(format!("{}/releases/latest/download/main.spdx.sbom.json", &params.common.repo.full_url())), 
-                        sbom_format: Some("SPDX".to_string()),
-                        sbom_url: Some("https://spdx.github.io/spdx-spec/v2.3/".to_string()), 
-                    },
-                ]),
+                sbom: Some(vec![SecurityInsightsVersion100YamlSchemaDependenciesSbomItem {
+                    sbom_creation: Some(
+                        SecurityInsightsVersion100YamlSchemaDependenciesSbomItemSbomCreation::from_str(sbom.creation)?),
+                    sbom_file: Some(format!("{}/{sbom_path}/{}", params.common.repo.full_url(), sbom.file_name)),
+                    sbom_format: Some(sbom.format.to_string()),
+                    sbom_url: Some(sbom.spec_url.to_string()),
+                }]),
                 third_party_packages: Some(true),
             }),
             distribution_points: Vec::new(),
@@ -555,7 +1272,7 @@ impl DefaultSourceBundleContentHandler {
                 last_updated: Some(chrono::Utc::now()),
                 license: Some(format!(
                     "{}/blob/main/LICENSE",
-                    &params.common.repo.full_url()
+                    params.common.repo.full_url()
                 )),
                 project_release: None,
                 project_url: params.common.repo.full_url(),
@@ -565,7 +1282,11 @@ impl DefaultSourceBundleContentHandler {
                 bug_fixes_only: false,
                 core_maintainers: None,
                 release_cycle: None,
-                release_process: None,
+                release_process: params
+                    .common
+                    .artifact_signing
+                    .then(|| Self::release_process(&params.common.repo))
+                    .transpose()?,
                 roadmap: None,
                 status: SecurityInsightsVersion100YamlSchemaProjectLifecycleStatus::Active,
             },
@@ -579,16 +1300,22 @@ impl DefaultSourceBundleContentHandler {
                 accepts_vulnerability_reports: true,
                 bug_bounty_available: None,
                 bug_bounty_url: None,
-                comment: None,
-                email_contact: None,
+                comment: Some(self.vulnerability_reporting_comment(&params.common.repo)?),
+                email_contact: self.security_policy.contact_email.clone(),
                 in_scope: None,
                 out_scope: None,
-                pgp_key: None,
-                security_policy: Some(format!("{}/blob/main/SECURITY.md", &params.common.repo.full_url())),
+                pgp_key: self
+                    .security_policy
+                    .pgp_key
+                    .as_deref()
+                    .map(|key| SecurityInsightsVersion100YamlSchemaVulnerabilityReportingPgpKey::from_str(key.trim()))
+                    .transpose()
+                    .map_err(|e| format!("The security policy's PGP key isn't an armored public key: {e}"))?,
+                security_policy: Some(format!("{}/blob/main/SECURITY.md", params.common.repo.full_url())),
             },
         };
 
-        let content = serde_yaml::to_string(&insights)?;
+        let content = serde_yaml::to_string(&insights).map_err(SkootError::serialization)?;
 
         Ok(SourceBundleContent {
             source_files_content: vec![SourceFileContent {
@@ -600,6 +1327,49 @@ impl DefaultSourceBundleContentHandler {
         })
     }
 
+    /// Describes how the assets of signed releases are verified, matching what the README says.
+    fn release_process(
+        repo: &InitializedRepo,
+    ) -> Result<SecurityInsightsVersion100YamlSchemaProjectLifecycleReleaseProcess, SkootError>
+    {
+        let release_process = format!(
+            "Release assets are signed with cosign keyless signing, and each asset's signature (.sig) and \
+             certificate (.pem) are attached to the release. Verify them with `cosign verify-blob` using the \
+             certificate identity {} and the OIDC issuer {}.",
+            signing::certificate_identity(repo),
+            signing::CERTIFICATE_OIDC_ISSUER
+        );
+        SecurityInsightsVersion100YamlSchemaProjectLifecycleReleaseProcess::from_str(
+            &release_process,
+        )
+        .map_err(|e| format!("The release process is too long for SECURITY-INSIGHTS: {e}").into())
+    }
+
+    /// Describes how vulnerabilities are reported, matching what the SECURITY.md says.
+    fn vulnerability_reporting_comment(
+        &self,
+        repo: &InitializedRepo,
+    ) -> Result<SecurityInsightsVersion100YamlSchemaVulnerabilityReportingComment, SkootError> {
+        let how = match (
+            self.private_reporting_url(repo),
+            &self.security_policy.contact_email,
+        ) {
+            (Some(url), Some(email)) => format!(
+                "Report vulnerabilities privately with Github's private vulnerability reporting at {url}, or email {email}."
+            ),
+            (Some(url), None) => format!(
+                "Report vulnerabilities privately with Github's private vulnerability reporting at {url}."
+            ),
+            (None, Some(email)) => format!("Report vulnerabilities privately to {email}."),
+            (None, None) => "Contact the maintainers privately to report vulnerabilities.".to_string(),
+        };
+        let comment = format!(
+            "{how} Reports are acknowledged within {} days, and vulnerabilities are kept under embargo for up to {} days.",
+            self.security_policy.acknowledgement_days, self.security_policy.embargo_days
+        );
+        Ok(SecurityInsightsVersion100YamlSchemaVulnerabilityReportingComment::from_str(&comment)?)
+    }
+
     fn generate_sast_content(
         &self,
         _params: &SourceBundleFacetCreateParams,
@@ -609,7 +1379,9 @@ impl DefaultSourceBundleContentHandler {
         struct SASTTemplateParams {}
 
         let sast_template_params = SASTTemplateParams {};
-        let content = sast_template_params.render()?;
+        let content = sast_template_params
+            .render()
+            .map_err(SkootError::template)?;
 
         Ok(SourceBundleContent {
             source_files_content: vec![SourceFileContent {
@@ -620,13 +1392,241 @@ impl DefaultSourceBundleContentHandler {
             facet_type: SupportedFacetType::SAST,
         })
     }
-}
 
-/// Handles the generation of source files content specific to Go projects hosted on Github.
+    fn generate_guac_forwarding_content(
+        &self,
+        params: &SourceBundleFacetCreateParams,
+    ) -> Result<SourceBundleContent, SkootError> {
+        #[derive(Template)]
+        #[template(path = "guac-forwarding.yml", escape = "none")]
+        struct GuacForwardingTemplateParams<'a> {
+            gql_endpoint: &'a str,
+            auth_token_secret: &'a Option<String>,
+            guac_version: &'a str,
+        }
+
+        let config = self.guac_forwarding.with_options(&params.options)?;
+        let guac_forwarding_template_params = GuacForwardingTemplateParams {
+            gql_endpoint: config.endpoint()?,
+            auth_token_secret: &config.auth_token_secret,
+            guac_version: &config.guac_version,
+        };
+        let content = guac_forwarding_template_params
+            .render()
+            .map_err(SkootError::template)?;
+
+        Ok(SourceBundleContent {
+            source_files_content: vec![SourceFileContent {
+                name: "guac-forwarding.yml".to_string(),
+                path: "./.github/workflows".to_string(),
+                content,
+            }],
+            facet_type: SupportedFacetType::GUACForwardingConfig,
+        })
+    }
+
+    fn generate_pinned_dependencies_content(
+        &self,
+        _params: &SourceBundleFacetCreateParams,
+    ) -> Result<SourceBundleContent, SkootError> {
+        #[derive(Template)]
+        #[template(path = "pinned-dependencies.yml", escape = "none")]
+        struct PinnedDependenciesTemplateParams {}
+
+        let content = PinnedDependenciesTemplateParams {}
+            .render()
+            .map_err(SkootError::template)?;
+        let policy = format!(
+            "# The actions and reusable workflows that can be referenced by tag instead of a commit SHA.\n{}",
+            serde_yaml::to_string(self.pinning).map_err(SkootError::serialization)?
+        );
+        let (policy_path, policy_name) = PINNING_POLICY_PATH
+            .rsplit_once('/')
+            .ok_or("The pinning policy path has no directory")?;
+
+        Ok(SourceBundleContent {
+            source_files_content: vec![
+                SourceFileContent {
+                    name: "pinned-dependencies.yml".to_string(),
+                    path: "./.github/workflows".to_string(),
+                    content,
+                },
+                SourceFileContent {
+                    name: policy_name.to_string(),
+                    path: format!("./{policy_path}"),
+                    content: policy,
+                },
+            ],
+            facet_type: SupportedFacetType::PinnedDependencies,
+        })
+    }
+
+    // Note: The SBOM is generated from the source rather than the build, so it's the same for every platform a
+    // project is built for.
+    fn generate_sbom_generator_content(
+        &self,
+        params: &SourceBundleFacetCreateParams,
+    ) -> Result<SourceBundleContent, SkootError> {
+        #[derive(Template)]
+        #[template(path = "sbom.yml", escape = "none")]
+        struct SbomTemplateParams {
+            maven_project_dir: Option<String>,
+            sbom_file: String,
+        }
+
+        // Bitbucket repos run Bitbucket Pipelines instead of Github Actions.
+        #[derive(Template)]
+        #[template(path = "bitbucket-pipelines.yml", escape = "none")]
+        struct BitbucketPipelinesTemplateParams {
+            maven_project_dir: Option<String>,
+            sbom_file: String,
+        }
+
+        let maven_project_dir = match &params.common.ecosystem {
+            InitializedEcosystem::Maven(maven) => {
+                Some(params.common.module().relative_path(&maven.artifact_id))
+            }
+            _ => None,
+        };
+        let sbom_file = SbomDocument::new(&params.common).file_name;
+        let source_file_content = if let InitializedRepo::Bitbucket(_) = &params.common.repo {
+            SourceFileContent {
+                name: "bitbucket-pipelines.yml".to_string(),
+                path: "./".to_string(),
+                content: BitbucketPipelinesTemplateParams {
+                    maven_project_dir,
+                    sbom_file,
+                }
+                .render()
+                .map_err(SkootError::template)?,
+            }
+        } else {
+            SourceFileContent {
+                name: "sbom.yml".to_string(),
+                path: "./.github/workflows".to_string(),
+                content: SbomTemplateParams {
+                    maven_project_dir,
+                    sbom_file,
+                }
+                .render()
+                .map_err(SkootError::template)?,
+            }
+        };
+
+        Ok(SourceBundleContent {
+            source_files_content: vec![source_file_content],
+            facet_type: SupportedFacetType::SBOMGenerator,
+        })
+    }
+
+    fn generate_slsa_verification_content(
+        &self,
+        _params: &SourceBundleFacetCreateParams,
+    ) -> Result<SourceBundleContent, SkootError> {
+        #[derive(Template)]
+        #[template(path = "slsa-verify.yml", escape = "none")]
+        struct SlsaVerificationTemplateParams {
+            result_file: &'static str,
+        }
+
+        let slsa_verification_template_params = SlsaVerificationTemplateParams {
+            result_file: PROVENANCE_VERIFICATION_FILE,
+        };
+        let content = slsa_verification_template_params
+            .render()
+            .map_err(SkootError::template)?;
+
+        Ok(SourceBundleContent {
+            source_files_content: vec![SourceFileContent {
+                name: "slsa-verify.yml".to_string(),
+                path: "./.github/workflows".to_string(),
+                content,
+            }],
+            facet_type: SupportedFacetType::SLSAVerification,
+        })
+    }
+
+    fn generate_artifact_signing_content(
+        &self,
+        params: &SourceBundleFacetCreateParams,
+    ) -> Result<SourceBundleContent, SkootError> {
+        #[derive(Template)]
+        #[template(path = "sign-release.yml", escape = "none")]
+        struct ArtifactSigningTemplateParams {
+            images: Vec<String>,
+            /// The registries the images are pushed to, which the signatures are pushed to as well.
+            registries: Vec<String>,
+        }
+
+        self.artifact_signing.validate()?;
+        let images = self.signed_images(&params.common);
+        let mut registries: Vec<String> = vec![];
+        for image in &images {
+            let registry = match image.split_once('/') {
+                Some((host, _)) if host.contains(['.', ':']) || host == "localhost" => host,
+                _ => "docker.io",
+            };
+            if !registries.iter().any(|r| r == registry) {
+                registries.push(registry.to_string());
+            }
+        }
+
+        let content = ArtifactSigningTemplateParams { images, registries }
+            .render()
+            .map_err(SkootError::template)?;
+
+        Ok(SourceBundleContent {
+            source_files_content: vec![SourceFileContent {
+                name: "sign-release.yml".to_string(),
+                path: "./.github/workflows".to_string(),
+                content,
+            }],
+            facet_type: SupportedFacetType::ArtifactSigning,
+        })
+    }
+
+    fn generate_vulnerability_scanner_content(
+        &self,
+        params: &SourceBundleFacetCreateParams,
+    ) -> Result<SourceBundleContent, SkootError> {
+        #[derive(Template)]
+        #[template(path = "osv-scanner.yml", escape = "none")]
+        struct OsvScannerTemplateParams<'a> {
+            severity_threshold: f32,
+            fail_on_new_findings: bool,
+            osv_scanner_version: &'a str,
+        }
+
+        let config = self.vulnerability_scanner.with_options(&params.options)?;
+        let osv_scanner_template_params = OsvScannerTemplateParams {
+            severity_threshold: config.severity_threshold.min_score(),
+            fail_on_new_findings: config.fail_on_new_findings,
+            osv_scanner_version: &config.osv_scanner_version,
+        };
+        let content = osv_scanner_template_params
+            .render()
+            .map_err(SkootError::template)?;
+
+        Ok(SourceBundleContent {
+            source_files_content: vec![SourceFileContent {
+                name: "osv-scanner.yml".to_string(),
+                path: "./.github/workflows".to_string(),
+                content,
+            }],
+            facet_type: SupportedFacetType::VulnerabilityScanner,
+        })
+    }
+}
+
+/// Handles the generation of source files content specific to Go projects hosted on Github.
 /// e.g. Github actions running goreleaser
-struct GoGithubSourceBundleContentHandler {}
+struct GoGithubSourceBundleContentHandler<'a> {
+    publish_packages: &'a PublishPackagesConfig,
+    dependency_updates: &'a DependencyUpdateConfig,
+    vulnerability_scanner: &'a VulnerabilityScannerConfig,
+}
 
-impl SourceBundleContentGenerator for GoGithubSourceBundleContentHandler {
+impl SourceBundleContentGenerator for GoGithubSourceBundleContentHandler<'_> {
     fn generate_content(
         &self,
         params: &SourceBundleFacetCreateParams,
@@ -634,7 +1634,7 @@ impl SourceBundleContentGenerator for GoGithubSourceBundleContentHandler {
         match params.facet_type {
             SupportedFacetType::Gitignore => self.generate_gitignore_content(params),
             // TODO: Rename this to something like SecureBuild.
-            // This also does a bunch of other stuff like setting up releases, generating SBOM, etc.
+            // This also does a bunch of other stuff like setting up releases, signing checksums, etc.
             // So for now just we just use it instead of creating multiple facets.
             // The better option is to probably set up some mapping of properties like SLSA, SBOMGenerating, etc.
             // to a single SecureBuild facet.
@@ -642,15 +1642,23 @@ impl SourceBundleContentGenerator for GoGithubSourceBundleContentHandler {
             SupportedFacetType::DependencyUpdateTool => {
                 self.generate_dependency_update_tool_content(params)
             }
+            SupportedFacetType::PublishPackages => self.generate_publish_packages_content(params),
+            SupportedFacetType::VulnerabilityScanner => {
+                self.generate_vulnerability_scanner_content(params)
+            }
             SupportedFacetType::Fuzzing => self.generate_fuzzing_content(params),
             SupportedFacetType::DefaultSourceCode => {
                 self.generate_default_source_code_content(params)
             }
-            _ => todo!("Not implemented yet"),
+            _ => Err(format!(
+                "Facet {} is not supported for Go projects",
+                params.facet_type
+            )
+            .into()),
         }
     }
 }
-impl GoGithubSourceBundleContentHandler {
+impl GoGithubSourceBundleContentHandler<'_> {
     fn generate_gitignore_content(
         &self,
         _params: &SourceBundleFacetCreateParams,
@@ -660,7 +1668,9 @@ impl GoGithubSourceBundleContentHandler {
         struct GitignoreTemplateParams {}
 
         let gitignore_template_params = GitignoreTemplateParams {};
-        let content = gitignore_template_params.render()?;
+        let content = gitignore_template_params
+            .render()
+            .map_err(SkootError::template)?;
 
         Ok(SourceBundleContent {
             source_files_content: vec![SourceFileContent {
@@ -671,8 +1681,9 @@ impl GoGithubSourceBundleContentHandler {
             facet_type: SupportedFacetType::Gitignore,
         })
     }
-    // Note: GoReleaser also does a bunch of other stuff like setting up releases, generating SBOM, etc.
-    // So for now just we just use it instead of creating multiple facets.
+    // Note: GoReleaser also does a bunch of other stuff like setting up releases, signing checksums, etc.
+    // So for now just we just use it instead of creating multiple facets. The container image is published
+    // by the PublishPackages facet instead.
     // Note: Content mostly taken from https://github.com/guacsec/guac/blob/f1703bd4ca3c0ec0fa55c5a3401d50578fb1680e/.github/workflows/release.yaml
     fn generate_slsa_build_content(
         &self,
@@ -683,12 +1694,6 @@ impl GoGithubSourceBundleContentHandler {
         #[template(path = "go.releases.yml", escape = "none")]
         struct ReleaseTemplateParams {}
 
-        #[derive(Template)]
-        #[template(path = "Dockerfile.goreleaser", escape = "none")]
-        struct DockerfileTemplateParams {
-            project_name: String,
-        }
-
         #[derive(Template)]
         #[template(path = "goreleaser.yml", escape = "none")]
         struct GoReleaserTemplateParams {
@@ -703,9 +1708,6 @@ impl GoGithubSourceBundleContentHandler {
         };
 
         let slsa_build_template_params = ReleaseTemplateParams {};
-        let dockerfile_template_params = DockerfileTemplateParams {
-            project_name: params.common.project_name.clone(),
-        };
         let goreleaser_template_params = GoReleaserTemplateParams {
             project_name: params.common.project_name.clone(),
             module_name: module,
@@ -716,48 +1718,104 @@ impl GoGithubSourceBundleContentHandler {
                 SourceFileContent {
                     name: "releases.yml".to_string(),
                     path: ".github/workflows/".to_string(),
-                    content: slsa_build_template_params.render()?,
-                },
-                SourceFileContent {
-                    name: "Dockerfile.goreleaser".to_string(),
-                    path: "./".to_string(),
-                    content: dockerfile_template_params.render()?,
+                    content: slsa_build_template_params
+                        .render()
+                        .map_err(SkootError::template)?,
                 },
                 SourceFileContent {
                     name: ".goreleaser.yml".to_string(),
                     path: "./".to_string(),
-                    content: goreleaser_template_params.render()?,
+                    content: goreleaser_template_params
+                        .render()
+                        .map_err(SkootError::template)?,
                 },
             ],
             facet_type: SupportedFacetType::SLSABuild,
         })
     }
 
-    fn generate_dependency_update_tool_content(
+    // Note: The image is built with ko, so the project doesn't need a Dockerfile, and it's signed with cosign
+    // keyless signing and gets SLSA provenance like the release binaries.
+    fn generate_publish_packages_content(
         &self,
-        _params: &SourceBundleFacetCreateParams,
+        params: &SourceBundleFacetCreateParams,
+    ) -> Result<SourceBundleContent, SkootError> {
+        #[derive(Template)]
+        #[template(path = "go.publish.yml", escape = "none")]
+        struct PublishTemplateParams<'a> {
+            registry: &'a str,
+            image: String,
+            ghcr: bool,
+        }
+
+        let config = self.publish_packages.with_options(&params.options)?;
+        let image = config
+            .packages(&params.common.repo, &params.common.ecosystem)
+            .first()
+            .map(PackageCoordinates::locator)
+            .ok_or("Go projects publish a container image")?;
+        let publish_template_params = PublishTemplateParams {
+            registry: &config.container_registry,
+            image,
+            ghcr: config.container_registry == "ghcr.io",
+        };
+
+        Ok(SourceBundleContent {
+            source_files_content: vec![SourceFileContent {
+                name: "publish.yml".to_string(),
+                path: ".github/workflows/".to_string(),
+                content: publish_template_params
+                    .render()
+                    .map_err(SkootError::template)?,
+            }],
+            facet_type: SupportedFacetType::PublishPackages,
+        })
+    }
+
+    // Note: govulncheck only reports the vulnerabilities the module's code reaches, including the ones in the
+    // standard library of its toolchain, which the OSV-Scanner workflow of the project doesn't see.
+    fn generate_vulnerability_scanner_content(
+        &self,
+        params: &SourceBundleFacetCreateParams,
     ) -> Result<SourceBundleContent, SkootError> {
         #[derive(Template)]
-        #[template(path = "dependabot.yml", escape = "none")]
-        struct DependabotTemplateParams {
-            ecosystem: String,
+        #[template(path = "govulncheck.yml", escape = "none")]
+        struct GovulncheckTemplateParams<'a> {
+            go_mod: String,
+            govulncheck_version: &'a str,
         }
 
-        let dependabot_template_params = DependabotTemplateParams {
-            ecosystem: "gomod".to_string(),
+        let config = self.vulnerability_scanner.with_options(&params.options)?;
+        let govulncheck_template_params = GovulncheckTemplateParams {
+            go_mod: params.common.module().relative_path("go.mod"),
+            govulncheck_version: &config.govulncheck_version,
         };
-        let content = dependabot_template_params.render()?;
+        let content = govulncheck_template_params
+            .render()
+            .map_err(SkootError::template)?;
 
         Ok(SourceBundleContent {
             source_files_content: vec![SourceFileContent {
-                name: "dependabot.yml".to_string(),
-                path: ".github/".to_string(),
+                name: "govulncheck.yml".to_string(),
+                path: ".github/workflows/".to_string(),
                 content,
             }],
-            facet_type: SupportedFacetType::DependencyUpdateTool,
+            facet_type: SupportedFacetType::VulnerabilityScanner,
         })
     }
 
+    fn generate_dependency_update_tool_content(
+        &self,
+        params: &SourceBundleFacetCreateParams,
+    ) -> Result<SourceBundleContent, SkootError> {
+        dependabot_content(
+            &self.dependency_updates.with_options(&params.options)?,
+            &params.common.module(),
+            "gomod",
+            params.common.module().repo_path(),
+        )
+    }
+
     fn generate_fuzzing_content(
         &self,
         params: &SourceBundleFacetCreateParams,
@@ -773,7 +1831,9 @@ impl GoGithubSourceBundleContentHandler {
             project_name: params.common.project_name.clone(),
             language: "go".to_string(),
         };
-        let content = fuzzing_template_params.render()?;
+        let content = fuzzing_template_params
+            .render()
+            .map_err(SkootError::template)?;
 
         Ok(SourceBundleContent {
             source_files_content: vec![SourceFileContent {
@@ -794,7 +1854,9 @@ impl GoGithubSourceBundleContentHandler {
         struct DefaultSourceCodeTemplateParams {}
 
         let default_source_code_template_params = DefaultSourceCodeTemplateParams {};
-        let content = default_source_code_template_params.render()?;
+        let content = default_source_code_template_params
+            .render()
+            .map_err(SkootError::template)?;
 
         Ok(SourceBundleContent {
             source_files_content: vec![SourceFileContent {
@@ -807,148 +1869,1928 @@ impl GoGithubSourceBundleContentHandler {
     }
 }
 
-/// The `FacetSetParamsGenerator` struct represents a service for generating params for a set of facets.
-/// This includes things like generating default params for source bundles and API bundles.
-pub struct FacetSetParamsGenerator {}
+/// Handles the generation of source files content specific to Maven projects hosted on Github.
+/// e.g. Github actions building the project with SLSA provenance.
+struct MavenGithubSourceBundleContentHandler<'a> {
+    publish_packages: &'a PublishPackagesConfig,
+    dependency_updates: &'a DependencyUpdateConfig,
+    container: &'a ContainerConfig,
+}
 
-impl FacetSetParamsGenerator {
-    /// Generates the default set of facet params for a project.
-    /// This includes things like generating default source bundle and API bundle facet params.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if any of the facet set params can't be generated.
-    pub fn generate_default(
+impl SourceBundleContentGenerator for MavenGithubSourceBundleContentHandler<'_> {
+    fn generate_content(
         &self,
-        common_params: &CommonFacetCreateParams,
-    ) -> Result<FacetSetCreateParams, SkootError> {
-        let source_bundle_params =
-            self.generate_default_source_bundle_facet_params(common_params)?;
-        let api_bundle_params = self.generate_default_api_bundle(common_params)?;
-        let total_params = FacetSetCreateParams {
-            facets_params: [
-                source_bundle_params.facets_params,
-                api_bundle_params.facets_params,
-            ]
-            .concat(),
-        };
+        params: &SourceBundleFacetCreateParams,
+    ) -> Result<SourceBundleContent, SkootError> {
+        match params.facet_type {
+            SupportedFacetType::Gitignore => self.generate_gitignore_content(params),
+            SupportedFacetType::SLSABuild => self.generate_slsa_build_content(params),
+            SupportedFacetType::DependencyUpdateTool => {
+                self.generate_dependency_update_tool_content(params)
+            }
+            SupportedFacetType::PublishPackages => self.generate_publish_packages_content(params),
+            SupportedFacetType::Container => self.generate_container_content(params),
+            SupportedFacetType::Fuzzing => self.generate_fuzzing_content(params),
+            SupportedFacetType::DefaultSourceCode => {
+                self.generate_default_source_code_content(params)
+            }
+            _ => Err(format!(
+                "Facet {} is not supported for Maven projects",
+                params.facet_type
+            )
+            .into()),
+        }
+    }
+}
 
-        Ok(total_params)
+impl MavenGithubSourceBundleContentHandler<'_> {
+    /// Returns the directory the Maven project lives in, relative to the root of the source. `mvn
+    /// archetype:generate` creates the project in a directory of the module named after the artifact ID.
+    fn project_dir(params: &SourceBundleFacetCreateParams) -> String {
+        #[allow(clippy::match_wildcard_for_single_variants)]
+        match &params.common.ecosystem {
+            InitializedEcosystem::Maven(maven) => {
+                params.common.module().relative_path(&maven.artifact_id)
+            }
+            _ => unreachable!("Ecosystem should be Maven"),
+        }
     }
 
-    /// Generates the default set of API bundle facet params for a project.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if the default set of API bundle facets can't be generated.
-    pub fn generate_default_api_bundle(
+    fn generate_gitignore_content(
         &self,
-        common_params: &CommonFacetCreateParams,
-    ) -> Result<FacetSetCreateParams, SkootError> {
-        use SupportedFacetType::{BranchProtection, VulnerabilityReporting};
-        let supported_facets = [
-            //CodeReview,
-            BranchProtection,
-            VulnerabilityReporting,
-        ];
-        let facets_params = supported_facets
-            .iter()
-            .map(|facet_type| {
-                FacetCreateParams::APIBundle(APIBundleFacetParams {
-                    common: common_params.clone(),
-                    facet_type: facet_type.clone(),
-                })
-            })
-            .collect::<Vec<FacetCreateParams>>();
+        _params: &SourceBundleFacetCreateParams,
+    ) -> Result<SourceBundleContent, SkootError> {
+        #[derive(Template)]
+        #[template(path = "maven.gitignore", escape = "none")]
+        struct GitignoreTemplateParams {}
 
-        Ok(FacetSetCreateParams { facets_params })
+        let gitignore_template_params = GitignoreTemplateParams {};
+        let content = gitignore_template_params
+            .render()
+            .map_err(SkootError::template)?;
+
+        Ok(SourceBundleContent {
+            source_files_content: vec![SourceFileContent {
+                name: ".gitignore".to_string(),
+                path: "./".to_string(),
+                content,
+            }],
+            facet_type: SupportedFacetType::Gitignore,
+        })
     }
 
-    // TODO: Come up with a better solution than hard coding the default facets
-    /// Generates the default set of source bundle facet params for a project.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if the default set of source bundle facets can't be generated.
-    pub fn generate_default_source_bundle_facet_params(
+    fn generate_slsa_build_content(
         &self,
-        common_params: &CommonFacetCreateParams,
-    ) -> Result<FacetSetCreateParams, SkootError> {
-        use SupportedFacetType::{
-            DefaultSourceCode, DependencyUpdateTool, Gitignore, License, Readme, SLSABuild,
-            Scorecard, SecurityInsights, SecurityPolicy, SAST,
+        params: &SourceBundleFacetCreateParams,
+    ) -> Result<SourceBundleContent, SkootError> {
+        #[derive(Template)]
+        #[template(path = "maven.releases.yml", escape = "none")]
+        struct ReleaseTemplateParams {
+            project_dir: String,
+        }
+
+        let release_template_params = ReleaseTemplateParams {
+            project_dir: Self::project_dir(params),
         };
-        let supported_facets = [
-            FacetTypeLabels {
-                supported_facet_type: Readme,
-                labels: vec![],
-            },
-            FacetTypeLabels {
-                supported_facet_type: License,
-                labels: vec![],
-            },
-            FacetTypeLabels {
-                supported_facet_type: Gitignore,
-                labels: vec![],
-            },
-            FacetTypeLabels {
-                supported_facet_type: SecurityPolicy,
-                labels: vec![],
-            },
-            FacetTypeLabels {
-                supported_facet_type: SecurityInsights,
-                labels: vec![],
-            },
-            FacetTypeLabels {
-                supported_facet_type: SLSABuild,
-                labels: vec![Label::SLSABuildLevel3, Label::S2C2FAUD1],
-            },
-            // SBOMGenerator, // Handled by the SLSABuild facet
-            // StaticCodeAnalysis,
-            FacetTypeLabels {
-                supported_facet_type: DependencyUpdateTool,
-                labels: vec![Label::S2C2FUPD2],
-            },
-            // TODO: Fuzzing right now requires a bunch of resources that are unavailable to most projects without
-            // some sort of manual intervention. This is disabled until some option becomes available.
-            // Fuzzing,
-            FacetTypeLabels {
-                supported_facet_type: Scorecard,
-                labels: vec![],
-            },
-            // PublishPackages,
-            // PinnedDependencies,
-            FacetTypeLabels {
-                supported_facet_type: SAST,
-                labels: vec![Label::S2C2FSCA1],
-            },
-            // VulnerabilityScanner,
-            // GUACForwardingConfig,
-            // These are at the end to allow Skootrs to push initial commits without needing
-            // code review or branches.
-            // CodeReview, // TODO: Implement this
-            //BranchProtection, //TODO: Implement this
-            FacetTypeLabels {
-                supported_facet_type: DefaultSourceCode,
-                labels: vec![],
-            },
-        ];
-        let facets_params = supported_facets
-            .iter()
-            .map(|facet_type_labels| {
-                FacetCreateParams::SourceBundle(SourceBundleFacetCreateParams {
-                    common: common_params.clone(),
-                    facet_type: facet_type_labels.supported_facet_type.clone(),
-                    labels: facet_type_labels.labels.clone(),
-                })
-            })
-            .collect::<Vec<FacetCreateParams>>();
 
-        Ok(FacetSetCreateParams { facets_params })
+        Ok(SourceBundleContent {
+            source_files_content: vec![SourceFileContent {
+                name: "releases.yml".to_string(),
+                path: ".github/workflows/".to_string(),
+                content: release_template_params
+                    .render()
+                    .map_err(SkootError::template)?,
+            }],
+            facet_type: SupportedFacetType::SLSABuild,
+        })
+    }
+
+    // Note: The artifacts are deployed on every tag with the version taken from the tag, and their cosign
+    // keyless signatures are attached to the release next to the provenance from the SLSABuild facet.
+    fn generate_publish_packages_content(
+        &self,
+        params: &SourceBundleFacetCreateParams,
+    ) -> Result<SourceBundleContent, SkootError> {
+        #[derive(Template)]
+        #[template(path = "maven.publish.yml", escape = "none")]
+        struct PublishTemplateParams {
+            project_dir: String,
+            maven_central: bool,
+            repository_url: String,
+        }
+
+        let config = self.publish_packages.with_options(&params.options)?;
+        let repository_url = config
+            .packages(&params.common.repo, &params.common.ecosystem)
+            .first()
+            .map(|package| package.registry.clone())
+            .ok_or("Maven projects publish their artifacts")?;
+        let publish_template_params = PublishTemplateParams {
+            project_dir: Self::project_dir(params),
+            maven_central: config.maven_repository == MavenRepository::MavenCentral,
+            repository_url,
+        };
+
+        Ok(SourceBundleContent {
+            source_files_content: vec![SourceFileContent {
+                name: "publish.yml".to_string(),
+                path: ".github/workflows/".to_string(),
+                content: publish_template_params
+                    .render()
+                    .map_err(SkootError::template)?,
+            }],
+            facet_type: SupportedFacetType::PublishPackages,
+        })
+    }
+
+    fn generate_dependency_update_tool_content(
+        &self,
+        params: &SourceBundleFacetCreateParams,
+    ) -> Result<SourceBundleContent, SkootError> {
+        dependabot_content(
+            &self.dependency_updates.with_options(&params.options)?,
+            &params.common.module(),
+            "maven",
+            format!("/{}", Self::project_dir(params)),
+        )
+    }
+
+    // Note: The quickstart archetype doesn't make the jar executable, so the image runs the `App` class it
+    // generates in the group ID's package.
+    fn generate_container_content(
+        &self,
+        params: &SourceBundleFacetCreateParams,
+    ) -> Result<SourceBundleContent, SkootError> {
+        #[derive(Template)]
+        #[template(path = "maven.Dockerfile", escape = "none")]
+        struct DockerfileTemplateParams<'a> {
+            artifact_id: &'a str,
+            main_class: String,
+            base_image: &'a str,
+        }
+
+        #[allow(clippy::match_wildcard_for_single_variants)]
+        let maven = match &params.common.ecosystem {
+            InitializedEcosystem::Maven(maven) => maven,
+            _ => unreachable!("Ecosystem should be Maven"),
+        };
+        let config = self.container.with_options(&params.options)?;
+        let dockerfile = DockerfileTemplateParams {
+            artifact_id: &maven.artifact_id,
+            main_class: format!("{}.App", maven.group_id),
+            base_image: config
+                .base_image
+                .as_deref()
+                .unwrap_or("gcr.io/distroless/java21-debian12:nonroot"),
+        }
+        .render()
+        .map_err(SkootError::template)?;
+
+        container_content(&config, params, dockerfile, &["**/target"])
+    }
+
+    fn generate_fuzzing_content(
+        &self,
+        params: &SourceBundleFacetCreateParams,
+    ) -> Result<SourceBundleContent, SkootError> {
+        #[derive(Template)]
+        #[template(path = "cifuzz.yml", escape = "none")]
+        struct FuzzingTemplateParams {
+            project_name: String,
+            language: String,
+        }
+
+        let fuzzing_template_params = FuzzingTemplateParams {
+            project_name: params.common.project_name.clone(),
+            language: "jvm".to_string(),
+        };
+        let content = fuzzing_template_params
+            .render()
+            .map_err(SkootError::template)?;
+
+        Ok(SourceBundleContent {
+            source_files_content: vec![SourceFileContent {
+                name: "cifuzz.yml".to_string(),
+                path: ".github/workflows/".to_string(),
+                content,
+            }],
+            facet_type: SupportedFacetType::Fuzzing,
+        })
+    }
+
+    // Note: The quickstart archetype already generates the default source code when the ecosystem is
+    // initialized, so there is nothing left to write here.
+    fn generate_default_source_code_content(
+        &self,
+        _params: &SourceBundleFacetCreateParams,
+    ) -> Result<SourceBundleContent, SkootError> {
+        Ok(SourceBundleContent {
+            source_files_content: vec![],
+            facet_type: SupportedFacetType::DefaultSourceCode,
+        })
+    }
+}
+
+/// Handles the generation of source files content specific to Python projects hosted on Github.
+/// e.g. Github actions building and publishing to `PyPI` with SLSA provenance.
+struct PythonGithubSourceBundleContentHandler<'a> {
+    dependency_updates: &'a DependencyUpdateConfig,
+    container: &'a ContainerConfig,
+}
+
+impl SourceBundleContentGenerator for PythonGithubSourceBundleContentHandler<'_> {
+    fn generate_content(
+        &self,
+        params: &SourceBundleFacetCreateParams,
+    ) -> Result<SourceBundleContent, SkootError> {
+        match params.facet_type {
+            SupportedFacetType::Gitignore => self.generate_gitignore_content(params),
+            SupportedFacetType::SLSABuild => self.generate_slsa_build_content(params),
+            SupportedFacetType::DependencyUpdateTool => {
+                self.generate_dependency_update_tool_content(params)
+            }
+            SupportedFacetType::VulnerabilityScanner => {
+                self.generate_vulnerability_scanner_content(params)
+            }
+            SupportedFacetType::Container => self.generate_container_content(params),
+            SupportedFacetType::Fuzzing => self.generate_fuzzing_content(params),
+            SupportedFacetType::DefaultSourceCode => {
+                self.generate_default_source_code_content(params)
+            }
+            _ => Err(format!(
+                "Facet {} is not supported for Python projects",
+                params.facet_type
+            )
+            .into()),
+        }
+    }
+}
+
+impl PythonGithubSourceBundleContentHandler<'_> {
+    fn generate_gitignore_content(
+        &self,
+        _params: &SourceBundleFacetCreateParams,
+    ) -> Result<SourceBundleContent, SkootError> {
+        #[derive(Template)]
+        #[template(path = "python.gitignore", escape = "none")]
+        struct GitignoreTemplateParams {}
+
+        let gitignore_template_params = GitignoreTemplateParams {};
+        let content = gitignore_template_params
+            .render()
+            .map_err(SkootError::template)?;
+
+        Ok(SourceBundleContent {
+            source_files_content: vec![SourceFileContent {
+                name: ".gitignore".to_string(),
+                path: "./".to_string(),
+                content,
+            }],
+            facet_type: SupportedFacetType::Gitignore,
+        })
+    }
+
+    // Note: The release workflow builds the sdist and wheel, generates SLSA provenance for them, and then
+    // publishes them to PyPI using trusted publishing.
+    fn generate_slsa_build_content(
+        &self,
+        _params: &SourceBundleFacetCreateParams,
+    ) -> Result<SourceBundleContent, SkootError> {
+        #[derive(Template)]
+        #[template(path = "python.releases.yml", escape = "none")]
+        struct ReleaseTemplateParams {}
+
+        let release_template_params = ReleaseTemplateParams {};
+
+        Ok(SourceBundleContent {
+            source_files_content: vec![SourceFileContent {
+                name: "releases.yml".to_string(),
+                path: ".github/workflows/".to_string(),
+                content: release_template_params
+                    .render()
+                    .map_err(SkootError::template)?,
+            }],
+            facet_type: SupportedFacetType::SLSABuild,
+        })
+    }
+
+    fn generate_dependency_update_tool_content(
+        &self,
+        params: &SourceBundleFacetCreateParams,
+    ) -> Result<SourceBundleContent, SkootError> {
+        // Note: Dependabot uses the `pip` ecosystem for both pip and poetry projects.
+        dependabot_content(
+            &self.dependency_updates.with_options(&params.options)?,
+            &params.common.module(),
+            "pip",
+            params.common.module().repo_path(),
+        )
+    }
+
+    // Note: The image runs the `main` function of the package the default source code generates.
+    fn generate_container_content(
+        &self,
+        params: &SourceBundleFacetCreateParams,
+    ) -> Result<SourceBundleContent, SkootError> {
+        #[derive(Template)]
+        #[template(path = "python.Dockerfile", escape = "none")]
+        struct DockerfileTemplateParams<'a> {
+            package: String,
+            base_image: &'a str,
+        }
+
+        #[allow(clippy::match_wildcard_for_single_variants)]
+        let package = match &params.common.ecosystem {
+            InitializedEcosystem::Python(python) => python.package(),
+            _ => unreachable!("Ecosystem should be Python"),
+        };
+        let config = self.container.with_options(&params.options)?;
+        let dockerfile = DockerfileTemplateParams {
+            package,
+            base_image: config
+                .base_image
+                .as_deref()
+                .unwrap_or("gcr.io/distroless/python3-debian12:nonroot"),
+        }
+        .render()
+        .map_err(SkootError::template)?;
+
+        container_content(
+            &config,
+            params,
+            dockerfile,
+            &["**/__pycache__", "*.py[cod]", ".venv", "build", "dist"],
+        )
+    }
+
+    fn generate_vulnerability_scanner_content(
+        &self,
+        _params: &SourceBundleFacetCreateParams,
+    ) -> Result<SourceBundleContent, SkootError> {
+        #[derive(Template)]
+        #[template(path = "pip-audit.yml", escape = "none")]
+        struct PipAuditTemplateParams {}
+
+        let pip_audit_template_params = PipAuditTemplateParams {};
+        let content = pip_audit_template_params
+            .render()
+            .map_err(SkootError::template)?;
+
+        Ok(SourceBundleContent {
+            source_files_content: vec![SourceFileContent {
+                name: "pip-audit.yml".to_string(),
+                path: ".github/workflows/".to_string(),
+                content,
+            }],
+            facet_type: SupportedFacetType::VulnerabilityScanner,
+        })
+    }
+
+    fn generate_fuzzing_content(
+        &self,
+        params: &SourceBundleFacetCreateParams,
+    ) -> Result<SourceBundleContent, SkootError> {
+        #[derive(Template)]
+        #[template(path = "cifuzz.yml", escape = "none")]
+        struct FuzzingTemplateParams {
+            project_name: String,
+            language: String,
+        }
+
+        let fuzzing_template_params = FuzzingTemplateParams {
+            project_name: params.common.project_name.clone(),
+            language: "python".to_string(),
+        };
+        let content = fuzzing_template_params
+            .render()
+            .map_err(SkootError::template)?;
+
+        Ok(SourceBundleContent {
+            source_files_content: vec![SourceFileContent {
+                name: "cifuzz.yml".to_string(),
+                path: ".github/workflows/".to_string(),
+                content,
+            }],
+            facet_type: SupportedFacetType::Fuzzing,
+        })
+    }
+
+    fn generate_default_source_code_content(
+        &self,
+        params: &SourceBundleFacetCreateParams,
+    ) -> Result<SourceBundleContent, SkootError> {
+        #[derive(Template)]
+        #[template(path = "main.py.tmpl", escape = "none")]
+        struct DefaultSourceCodeTemplateParams {}
+
+        #[allow(clippy::match_wildcard_for_single_variants)]
+        let package = match &params.common.ecosystem {
+            InitializedEcosystem::Python(python) => python.package(),
+            _ => unreachable!("Ecosystem should be Python"),
+        };
+
+        let default_source_code_template_params = DefaultSourceCodeTemplateParams {};
+        let content = default_source_code_template_params
+            .render()
+            .map_err(SkootError::template)?;
+
+        Ok(SourceBundleContent {
+            source_files_content: vec![SourceFileContent {
+                name: "__init__.py".to_string(),
+                path: format!("./src/{package}"),
+                content,
+            }],
+            facet_type: SupportedFacetType::DefaultSourceCode,
+        })
+    }
+}
+
+/// Handles the generation of source files content specific to Rust projects hosted on Github.
+/// e.g. release-please for versioning the crates of the workspace and publishing them to crates.io
+struct RustGithubSourceBundleContentHandler<'a> {
+    dependency_updates: &'a DependencyUpdateConfig,
+}
+
+impl SourceBundleContentGenerator for RustGithubSourceBundleContentHandler<'_> {
+    fn generate_content(
+        &self,
+        params: &SourceBundleFacetCreateParams,
+    ) -> Result<SourceBundleContent, SkootError> {
+        match params.facet_type {
+            SupportedFacetType::Gitignore => self.generate_gitignore_content(params),
+            SupportedFacetType::PublishPackages => self.generate_publish_packages_content(params),
+            SupportedFacetType::DependencyUpdateTool => {
+                self.generate_dependency_update_tool_content(params)
+            }
+            SupportedFacetType::DefaultSourceCode => {
+                self.generate_default_source_code_content(params)
+            }
+            _ => Err(format!(
+                "Facet {} is not supported for Rust projects",
+                params.facet_type
+            )
+            .into()),
+        }
+    }
+}
+
+impl RustGithubSourceBundleContentHandler<'_> {
+    fn members(params: &SourceBundleFacetCreateParams) -> Vec<String> {
+        #[allow(clippy::match_wildcard_for_single_variants)]
+        match &params.common.ecosystem {
+            InitializedEcosystem::Rust(rust) => rust.members(),
+            _ => unreachable!("Ecosystem should be Rust"),
+        }
+    }
+
+    fn generate_gitignore_content(
+        &self,
+        _params: &SourceBundleFacetCreateParams,
+    ) -> Result<SourceBundleContent, SkootError> {
+        #[derive(Template)]
+        #[template(path = "rust.gitignore", escape = "none")]
+        struct GitignoreTemplateParams {}
+
+        let gitignore_template_params = GitignoreTemplateParams {};
+        let content = gitignore_template_params
+            .render()
+            .map_err(SkootError::template)?;
+
+        Ok(SourceBundleContent {
+            source_files_content: vec![SourceFileContent {
+                name: ".gitignore".to_string(),
+                path: "./".to_string(),
+                content,
+            }],
+            facet_type: SupportedFacetType::Gitignore,
+        })
+    }
+
+    // Note: release-please opens a release PR that bumps the version of every changed crate, and its
+    // dependents via the cargo-workspace plugin. Merging the PR tags the release and the workflow then
+    // attests the packaged crates with build provenance and publishes them to crates.io with trusted
+    // publishing, so no long lived crates.io token is stored in the repo.
+    fn generate_publish_packages_content(
+        &self,
+        params: &SourceBundleFacetCreateParams,
+    ) -> Result<SourceBundleContent, SkootError> {
+        #[derive(Template)]
+        #[template(path = "rust.releases.yml", escape = "none")]
+        struct ReleaseTemplateParams {}
+
+        let members = Self::members(params);
+        let release_please_config = serde_json::json!({
+            "$schema": "https://raw.githubusercontent.com/googleapis/release-please/main/schemas/config.json",
+            "release-type": "rust",
+            "plugins": ["cargo-workspace"],
+            "packages": members
+                .iter()
+                .map(|member| (format!("crates/{member}"), serde_json::json!({ "component": member })))
+                .collect::<serde_json::Map<_, _>>(),
+        });
+        let release_please_manifest = members
+            .iter()
+            .map(|member| (format!("crates/{member}"), serde_json::json!("0.1.0")))
+            .collect::<serde_json::Map<_, _>>();
+
+        Ok(SourceBundleContent {
+            source_files_content: vec![
+                SourceFileContent {
+                    name: "release-please-config.json".to_string(),
+                    path: "./".to_string(),
+                    content: serde_json::to_string_pretty(&release_please_config)?,
+                },
+                SourceFileContent {
+                    name: ".release-please-manifest.json".to_string(),
+                    path: "./".to_string(),
+                    content: serde_json::to_string_pretty(&release_please_manifest)?,
+                },
+                SourceFileContent {
+                    name: "releases.yml".to_string(),
+                    path: ".github/workflows/".to_string(),
+                    content: ReleaseTemplateParams {}
+                        .render()
+                        .map_err(SkootError::template)?,
+                },
+            ],
+            facet_type: SupportedFacetType::PublishPackages,
+        })
+    }
+
+    fn generate_dependency_update_tool_content(
+        &self,
+        params: &SourceBundleFacetCreateParams,
+    ) -> Result<SourceBundleContent, SkootError> {
+        // Note: Dependabot picks up every crate in the workspace from the root manifest.
+        dependabot_content(
+            &self.dependency_updates.with_options(&params.options)?,
+            &params.common.module(),
+            "cargo",
+            params.common.module().repo_path(),
+        )
+    }
+
+    fn generate_default_source_code_content(
+        &self,
+        params: &SourceBundleFacetCreateParams,
+    ) -> Result<SourceBundleContent, SkootError> {
+        #[derive(Template)]
+        #[template(path = "lib.rs.tmpl", escape = "none")]
+        struct DefaultSourceCodeTemplateParams {}
+
+        let content = DefaultSourceCodeTemplateParams {}
+            .render()
+            .map_err(SkootError::template)?;
+        let source_files_content = Self::members(params)
+            .iter()
+            .map(|member| SourceFileContent {
+                name: "lib.rs".to_string(),
+                path: format!("./crates/{member}/src"),
+                content: content.clone(),
+            })
+            .collect();
+
+        Ok(SourceBundleContent {
+            source_files_content,
+            facet_type: SupportedFacetType::DefaultSourceCode,
+        })
+    }
+}
+
+/// The SBOM the `SBOMGenerator` facet attaches to a project's releases. It's named following
+/// <https://github.com/ossf/sbom-everywhere/blob/main/reference/sbom_naming.md> so it's found as an output.
+struct SbomDocument {
+    file_name: String,
+    format: &'static str,
+    spec_url: &'static str,
+    creation: &'static str,
+}
+
+impl SbomDocument {
+    fn new(params: &CommonFacetCreateParams) -> Self {
+        let name = params.repo.name();
+        match params.ecosystem {
+            // The CycloneDX plugin resolves the dependencies the way Maven does, which syft can't for a pom.
+            InitializedEcosystem::Maven(_) => Self {
+                file_name: format!("{name}.cdx.json"),
+                format: "CycloneDX",
+                spec_url: "https://cyclonedx.org/specification/overview/",
+                creation: "Created by the CycloneDX Maven plugin in the sbom workflow",
+            },
+            _ => Self {
+                file_name: format!("{name}.spdx.json"),
+                format: "SPDX",
+                spec_url: "https://spdx.github.io/spdx-spec/v2.3/",
+                creation: "Created by syft in the sbom workflow",
+            },
+        }
+    }
+}
+
+/// Returns the file that lists the dependencies for a project in the given ecosystem.
+fn dependency_manifest(ecosystem: &InitializedEcosystem) -> &'static str {
+    match ecosystem {
+        InitializedEcosystem::Go(_) => "go.mod",
+        InitializedEcosystem::Maven(_) => "pom.xml",
+        InitializedEcosystem::Python(_) => "pyproject.toml",
+        InitializedEcosystem::Rust(_) => "Cargo.toml",
+    }
+}
+
+/// An ecosystem Dependabot keeps up to date in a directory.
+struct DependabotUpdate {
+    comment: &'static str,
+    ecosystem: &'static str,
+    directory: String,
+    interval: &'static str,
+}
+
+/// Returns the Dependabot config of a module, which keeps the dependencies of its ecosystem, the actions
+/// its workflows use and the base images of its Dockerfiles up to date.
+fn dependabot_content(
+    config: &DependencyUpdateConfig,
+    module: &InitializedModule,
+    ecosystem: &'static str,
+    directory: String,
+) -> Result<SourceBundleContent, SkootError> {
+    #[derive(Template)]
+    #[template(path = "dependabot.yml", escape = "none")]
+    struct DependabotTemplateParams {
+        updates: Vec<DependabotUpdate>,
+        group_updates: bool,
+    }
+
+    let mut updates = vec![
+        DependabotUpdate {
+            comment: "Maintain the project's ecosystem dependencies.",
+            ecosystem,
+            directory,
+            interval: config.interval.as_str(),
+        },
+        // Github reads the workflows of every module from the root of the repo.
+        DependabotUpdate {
+            comment: "Maintain dependencies for GitHub Actions.",
+            ecosystem: "github-actions",
+            directory: "/".to_string(),
+            interval: config.github_actions_interval.as_str(),
+        },
+    ];
+    updates.extend(config.docker_directories.iter().map(|docker_directory| {
+        let directory = format!(
+            "{}/{}",
+            module.repo_path().trim_end_matches('/'),
+            docker_directory.trim_matches('/')
+        );
+        DependabotUpdate {
+            comment: "Maintain the base images of the Dockerfile.",
+            ecosystem: "docker",
+            directory: match directory.trim_end_matches('/') {
+                "" => "/".to_string(),
+                directory => directory.to_string(),
+            },
+            interval: config.docker_interval.as_str(),
+        }
+    }));
+    let content = DependabotTemplateParams {
+        updates,
+        group_updates: config.group_updates,
+    }
+    .render()
+    .map_err(SkootError::template)?;
+
+    Ok(SourceBundleContent {
+        source_files_content: vec![SourceFileContent {
+            name: "dependabot.yml".to_string(),
+            path: ".github/".to_string(),
+            content,
+        }],
+        facet_type: SupportedFacetType::DependencyUpdateTool,
+    })
+}
+
+/// Returns the content of the `Container` facet for a module: its Dockerfile, a `.dockerignore` that also
+/// leaves out the ecosystem's build output, and a workflow that builds the image on every pull request and
+/// pushes and signs it on every push to `main` and every `v*` tag.
+fn container_content(
+    config: &ContainerConfig,
+    params: &SourceBundleFacetCreateParams,
+    dockerfile: String,
+    dockerignore_entries: &[&str],
+) -> Result<SourceBundleContent, SkootError> {
+    #[derive(Template)]
+    #[template(path = "dockerignore", escape = "none")]
+    struct DockerignoreTemplateParams<'a> {
+        entries: &'a [&'a str],
+    }
+
+    #[derive(Template)]
+    #[template(path = "container.yml", escape = "none")]
+    struct ContainerTemplateParams<'a> {
+        registry: &'a str,
+        image: String,
+        ghcr: bool,
+        context: String,
+    }
+
+    let module = params.common.module();
+    let dockerignore = DockerignoreTemplateParams {
+        entries: dockerignore_entries,
+    }
+    .render()
+    .map_err(SkootError::template)?;
+    let workflow = ContainerTemplateParams {
+        registry: &config.container_registry,
+        image: config.image(&params.common.repo, &module),
+        ghcr: config.container_registry == "ghcr.io",
+        // Actions don't run from the module's working directory, so the build context is relative to the
+        // root of the repo.
+        context: module.relative_path("./"),
+    }
+    .render()
+    .map_err(SkootError::template)?;
+
+    Ok(SourceBundleContent {
+        source_files_content: vec![
+            SourceFileContent {
+                name: "Dockerfile".to_string(),
+                path: "./".to_string(),
+                content: dockerfile,
+            },
+            SourceFileContent {
+                name: ".dockerignore".to_string(),
+                path: "./".to_string(),
+                content: dockerignore,
+            },
+            SourceFileContent {
+                name: "container.yml".to_string(),
+                path: ".github/workflows/".to_string(),
+                content: workflow,
+            },
+        ],
+        facet_type: SupportedFacetType::Container,
+    })
+}
+
+/// Returns true if a facet's content for a module is generated by the handler for the module's ecosystem.
+/// Python projects are scanned with pip-audit, since most of them don't have a lock file for OSV-Scanner to
+/// scan, and Go modules with govulncheck.
+const fn is_language_specific(
+    facet_type: &SupportedFacetType,
+    ecosystem: &InitializedEcosystem,
+) -> bool {
+    match facet_type {
+        SupportedFacetType::Gitignore
+        | SupportedFacetType::SLSABuild
+        | SupportedFacetType::DependencyUpdateTool
+        | SupportedFacetType::Fuzzing
+        | SupportedFacetType::PublishPackages
+        | SupportedFacetType::Container
+        | SupportedFacetType::DefaultSourceCode => true,
+        SupportedFacetType::VulnerabilityScanner => matches!(
+            ecosystem,
+            InitializedEcosystem::Python(_) | InitializedEcosystem::Go(_)
+        ),
+        _ => false,
+    }
+}
+
+/// Returns true if a facet's language specific content for a module is generated on top of the content that
+/// isn't specific to an ecosystem, rather than instead of it. Go modules are scanned with govulncheck as well
+/// as OSV-Scanner, which scans the whole project once.
+const fn extends_default_content(
+    facet_type: &SupportedFacetType,
+    ecosystem: &InitializedEcosystem,
+) -> bool {
+    matches!(
+        (facet_type, ecosystem),
+        (
+            SupportedFacetType::VulnerabilityScanner,
+            InitializedEcosystem::Go(_)
+        )
+    )
+}
+
+/// The directory Github runs workflows from.
+const WORKFLOWS_DIR: &str = ".github/workflows";
+
+/// The file Dependabot is configured in.
+const DEPENDABOT_FILE: &str = ".github/dependabot.yml";
+
+/// Moves a file generated for a module that isn't at the root of the source into the module's directory.
+/// Files Github reads from `.github/` stay there. A module's workflows are suffixed with its name so they
+/// don't clash with the other modules', and run their commands from its directory.
+fn relocate_module_file(
+    module: &InitializedModule,
+    source_file_content: SourceFileContent,
+) -> SourceFileContent {
+    let Some(slug) = module.slug() else {
+        return source_file_content;
+    };
+    let path = source_file_content.path.trim_start_matches("./");
+    if path.trim_end_matches('/') == WORKFLOWS_DIR {
+        let name = match source_file_content.name.rsplit_once('.') {
+            Some((stem, extension)) => format!("{stem}-{slug}.{extension}"),
+            None => format!("{}-{slug}", source_file_content.name),
+        };
+        let content = if source_file_content
+            .content
+            .lines()
+            .any(|line| line.starts_with("defaults:"))
+        {
+            source_file_content.content
+        } else {
+            source_file_content.content.replacen(
+                "\njobs:",
+                &format!(
+                    "\ndefaults:\n  run:\n    working-directory: {}\n\njobs:",
+                    module.dir()
+                ),
+                1,
+            )
+        };
+        return SourceFileContent {
+            name,
+            content,
+            ..source_file_content
+        };
+    }
+    if path.starts_with(".github") {
+        return source_file_content;
+    }
+    SourceFileContent {
+        path: module.relative_path(&source_file_content.path),
+        ..source_file_content
+    }
+}
+
+/// Adds a file generated for a module to the files generated for the other modules. Every module's
+/// Dependabot updates are merged into one file, and a file several modules generate the same way is only
+/// added once.
+///
+/// # Errors
+///
+/// Returns an error if another module generated the same file with different content.
+fn add_module_file(
+    source_files_content: &mut Vec<SourceFileContent>,
+    source_file_content: SourceFileContent,
+) -> Result<(), SkootError> {
+    let file_path = |file: &SourceFileContent| {
+        format!(
+            "{}/{}",
+            file.path.trim_start_matches("./").trim_end_matches('/'),
+            file.name
+        )
+    };
+    let path = file_path(&source_file_content);
+    let Some(existing) = source_files_content
+        .iter_mut()
+        .find(|existing| file_path(existing) == path)
+    else {
+        source_files_content.push(source_file_content);
+        return Ok(());
+    };
+    if existing.content == source_file_content.content {
+        return Ok(());
+    }
+    if path == DEPENDABOT_FILE {
+        existing.content =
+            merge_dependabot_updates(&existing.content, &source_file_content.content)?;
+        return Ok(());
+    }
+    Err(format!("Several modules generate {path} with different content").into())
+}
+
+/// Adds the updates of a Dependabot config that aren't in another one to it.
+fn merge_dependabot_updates(config: &str, other: &str) -> Result<String, SkootError> {
+    let mut config: serde_yaml::Value =
+        serde_yaml::from_str(config).map_err(SkootError::serialization)?;
+    let other: serde_yaml::Value =
+        serde_yaml::from_str(other).map_err(SkootError::serialization)?;
+    let updates = config
+        .get_mut("updates")
+        .and_then(serde_yaml::Value::as_sequence_mut)
+        .ok_or_else(|| SkootError::from("The Dependabot config has no updates"))?;
+    for update in other
+        .get("updates")
+        .and_then(serde_yaml::Value::as_sequence)
+        .into_iter()
+        .flatten()
+    {
+        if !updates.contains(update) {
+            updates.push(update.clone());
+        }
+    }
+    serde_yaml::to_string(&config).map_err(SkootError::serialization)
+}
+
+/// The `FacetSetParamsGenerator` struct represents a service for generating params for a set of facets.
+/// This includes things like generating default params for source bundles and API bundles from the facets
+/// declared in the facet registry.
+pub struct FacetSetParamsGenerator<'a> {
+    /// The registry that declares which facets are generated and in what order.
+    pub registry: &'a FacetRegistry,
+}
+
+impl FacetSetParamsGenerator<'_> {
+    /// Generates the default set of facet params for a project.
+    /// This includes things like generating default source bundle and API bundle facet params.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the facet set params can't be generated.
+    pub fn generate_default(
+        &self,
+        common_params: &CommonFacetCreateParams,
+    ) -> Result<FacetSetCreateParams, SkootError> {
+        let source_bundle_params =
+            self.generate_default_source_bundle_facet_params(common_params)?;
+        let api_bundle_params = self.generate_default_api_bundle(common_params)?;
+        let total_params = FacetSetCreateParams {
+            facets_params: [
+                source_bundle_params.facets_params,
+                api_bundle_params.facets_params,
+            ]
+            .concat(),
+        };
+
+        Ok(total_params)
+    }
+
+    /// Generates the params for a single facet. The facet's bundle and labels come from its registry entry,
+    /// so facets that are disabled by default, like fuzzing, can still be added explicitly. Facets that
+    /// aren't declared in the registry are generated without labels.
+    #[must_use]
+    pub fn generate_facet_params(
+        &self,
+        common_params: &CommonFacetCreateParams,
+        facet_type: &SupportedFacetType,
+        options: &BTreeMap<String, serde_json::Value>,
+    ) -> FacetCreateParams {
+        let entry = self.registry.entry(facet_type);
+        let labels = entry.map(|entry| entry.labels.clone()).unwrap_or_default();
+        let common = entry.map_or_else(
+            || common_params.clone(),
+            |entry| Self::supported_modules(common_params, entry),
+        );
+        if facet_type.is_api_bundle() {
+            FacetCreateParams::APIBundle(APIBundleFacetParams {
+                common,
+                facet_type: facet_type.clone(),
+                labels,
+                options: options.clone(),
+            })
+        } else {
+            FacetCreateParams::SourceBundle(SourceBundleFacetCreateParams {
+                common,
+                facet_type: facet_type.clone(),
+                labels,
+                options: options.clone(),
+            })
+        }
+    }
+
+    /// Returns the common params for a facet with only the project's modules it supports, so a facet that's
+    /// specific to some ecosystems isn't generated for the project's other modules. The params are unchanged
+    /// if it doesn't support any of them.
+    fn supported_modules(
+        common_params: &CommonFacetCreateParams,
+        entry: &FacetRegistryEntry,
+    ) -> CommonFacetCreateParams {
+        common_params.with_modules(
+            common_params
+                .project_modules()
+                .into_iter()
+                .filter(|module| entry.supports(&module.ecosystem))
+                .collect(),
+        )
+    }
+
+    /// Generates the default set of API bundle facet params for a project.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the default set of API bundle facets can't be generated.
+    pub fn generate_default_api_bundle(
+        &self,
+        common_params: &CommonFacetCreateParams,
+    ) -> Result<FacetSetCreateParams, SkootError> {
+        let modules = common_params.project_modules();
+        let facets_params = self
+            .registry
+            .api_bundle_facets(&modules)
+            .map(|entry| {
+                FacetCreateParams::APIBundle(APIBundleFacetParams {
+                    common: Self::supported_modules(common_params, entry),
+                    facet_type: entry.facet_type.clone(),
+                    labels: entry.labels.clone(),
+                    options: BTreeMap::new(),
+                })
+            })
+            .collect::<Vec<FacetCreateParams>>();
+
+        Ok(FacetSetCreateParams { facets_params })
+    }
+
+    /// Generates the default set of source bundle facet params for a project.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the default set of source bundle facets can't be generated.
+    pub fn generate_default_source_bundle_facet_params(
+        &self,
+        common_params: &CommonFacetCreateParams,
+    ) -> Result<FacetSetCreateParams, SkootError> {
+        let modules = common_params.project_modules();
+        // The README and SECURITY-INSIGHTS of projects created with the `ArtifactSigning` facet tell how to
+        // verify their releases.
+        let mut common_params = common_params.clone();
+        common_params.artifact_signing |= self
+            .registry
+            .source_bundle_facets(&modules)
+            .any(|entry| entry.facet_type == SupportedFacetType::ArtifactSigning);
+        let facets_params = self
+            .registry
+            .source_bundle_facets(&modules)
+            .map(|entry| {
+                FacetCreateParams::SourceBundle(SourceBundleFacetCreateParams {
+                    common: Self::supported_modules(&common_params, entry),
+                    facet_type: entry.facet_type.clone(),
+                    labels: entry.labels.clone(),
+                    options: BTreeMap::new(),
+                })
+            })
+            .collect::<Vec<FacetCreateParams>>();
+
+        Ok(FacetSetCreateParams { facets_params })
     }
 }
 
-struct FacetTypeLabels {
-    supported_facet_type: SupportedFacetType,
-    labels: Vec<Label>,
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::Engine;
+    use skootrs_model::skootrs::{
+        license::SpdxLicense, GithubUser, InitializedBitbucketRepo, InitializedGenericGitRepo,
+        InitializedGithubRepo, InitializedGo, InitializedMaven, InitializedPython,
+        PythonPackageManager,
+    };
+
+    fn params(
+        facet_type: SupportedFacetType,
+        repo: InitializedRepo,
+    ) -> SourceBundleFacetCreateParams {
+        SourceBundleFacetCreateParams {
+            common: CommonFacetCreateParams::new(
+                "test".to_string(),
+                InitializedSource {
+                    path: "test".to_string(),
+                },
+                repo,
+                vec![InitializedEcosystem::Go(InitializedGo {
+                    name: "test".to_string(),
+                    host: "github.com/testorg".to_string(),
+                })
+                .into()],
+            )
+            .unwrap(),
+            facet_type,
+            labels: vec![],
+            options: BTreeMap::new(),
+        }
+    }
+
+    fn github_repo() -> InitializedRepo {
+        InitializedRepo::Github(InitializedGithubRepo {
+            name: "test".to_string(),
+            organization: GithubUser::Organization("testorg".to_string()),
+        })
+    }
+
+    fn render(facet_service: &LocalFacetService, params: &SourceBundleFacetCreateParams) -> String {
+        facet_service
+            .render_source_bundle(params)
+            .unwrap()
+            .remove(0)
+            .content
+    }
+
+    #[test]
+    fn test_security_policy() {
+        let mut facet_service = LocalFacetService {
+            private_vulnerability_reporting: true,
+            ..Default::default()
+        };
+        let policy = render(
+            &facet_service,
+            &params(SupportedFacetType::SecurityPolicy, github_repo()),
+        );
+        assert!(policy.contains("https://github.com/testorg/test/security/advisories/new"));
+        assert!(policy.contains("within 3 days"));
+        assert!(policy.contains("up to 90 days"));
+        assert!(!policy.contains("email"));
+
+        facet_service.security_policy = SecurityPolicyConfig {
+            contact_email: Some("security@example.com".to_string()),
+            embargo_days: 30,
+            ..Default::default()
+        };
+        let policy = render(
+            &facet_service,
+            &params(SupportedFacetType::SecurityPolicy, github_repo()),
+        );
+        assert!(policy.contains("https://github.com/testorg/test/security/advisories/new"));
+        assert!(policy.contains(
+            "If you can't use Github, you can email your report to security@example.com"
+        ));
+        assert!(policy.contains("up to 30 days"));
+        let insights = render(
+            &facet_service,
+            &params(SupportedFacetType::SecurityInsights, github_repo()),
+        );
+        assert!(insights.contains("email-contact: security@example.com"));
+        assert!(insights.contains("https://github.com/testorg/test/security/advisories/new"));
+
+        // Generic git remotes have no private vulnerability reporting to point to.
+        let generic_repo = InitializedRepo::GenericGit(InitializedGenericGitRepo {
+            name: "test".to_string(),
+            remote_url: "https://git.example.com/test.git".to_string(),
+        });
+        let policy = render(
+            &facet_service,
+            &params(SupportedFacetType::SecurityPolicy, generic_repo),
+        );
+        assert!(!policy.contains("/security/advisories/new"));
+        assert!(policy.contains("Instead, email your report to security@example.com."));
+
+        facet_service.security_policy.pgp_key = Some("not a key".to_string());
+        assert!(facet_service
+            .render_source_bundle(&params(SupportedFacetType::SecurityInsights, github_repo()))
+            .is_err());
+    }
+
+    #[test]
+    fn test_api_bundle_facets_as_source_bundles() {
+        let facet_service = LocalFacetService::default();
+        for facet_type in [
+            SupportedFacetType::BranchProtection,
+            SupportedFacetType::CodeReview,
+            SupportedFacetType::VulnerabilityReporting,
+            SupportedFacetType::RepoRuleset,
+            SupportedFacetType::BestPracticesBadge,
+        ] {
+            let result = facet_service.render_source_bundle(&params(facet_type, github_repo()));
+            assert!(matches!(result, Err(SkootError::Config(_))));
+        }
+    }
+
+    #[test]
+    fn test_unimplemented_facets() {
+        let facet_service = LocalFacetService::default();
+        for facet_type in [
+            SupportedFacetType::StaticCodeAnalysis,
+            SupportedFacetType::Allstar,
+            SupportedFacetType::Other,
+        ] {
+            let error = facet_service
+                .render_source_bundle(&params(facet_type.clone(), github_repo()))
+                .unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                format!("Invalid config: {facet_type} isn't implemented yet")
+            );
+        }
+    }
+
+    #[test]
+    fn test_generator_fingerprint() {
+        let readme = params(SupportedFacetType::Readme, github_repo());
+        let fingerprint = generator_fingerprint(&FacetCreateParams::SourceBundle(readme.clone()));
+        assert_eq!(fingerprint.len(), 64);
+        assert_eq!(
+            template_version(&SupportedFacetType::Readme),
+            INITIAL_TEMPLATE_VERSION
+        );
+
+        // The project the facet is generated for doesn't change the generator, but its options do.
+        let mut other_project = readme.clone();
+        other_project.common.project_name = "other".to_string();
+        assert_eq!(
+            generator_fingerprint(&FacetCreateParams::SourceBundle(other_project)),
+            fingerprint
+        );
+        let mut with_options = readme;
+        with_options.options = BTreeMap::from([("badge".to_string(), true.into())]);
+        assert_ne!(
+            generator_fingerprint(&FacetCreateParams::SourceBundle(with_options)),
+            fingerprint
+        );
+    }
+
+    #[test]
+    fn test_guac_forwarding() {
+        let facet_service = LocalFacetService {
+            guac_forwarding: GuacForwardingConfig {
+                gql_endpoint: Some("https://guac.example.com/query".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let workflow = render(
+            &facet_service,
+            &params(SupportedFacetType::GUACForwardingConfig, github_repo()),
+        );
+        assert!(
+            workflow.contains("TAG: ${{ inputs.tag || github.event.workflow_run.head_branch }}")
+        );
+        assert!(workflow.contains("ghcr.io/guacsec/guac:v0.8.0"));
+        assert!(workflow.contains("--gql-addr 'https://guac.example.com/query'"));
+        assert!(!workflow.contains("secrets."));
+
+        // The facet's options override the configured endpoint and add the credentials.
+        let mut facet_params = params(SupportedFacetType::GUACForwardingConfig, github_repo());
+        facet_params.options = BTreeMap::from([
+            (
+                "gql_endpoint".to_string(),
+                "https://guac.team.example.com/query".into(),
+            ),
+            ("auth_token_secret".to_string(), "GUAC_TOKEN".into()),
+        ]);
+        let workflow = render(&facet_service, &facet_params);
+        assert!(workflow.contains("GUAC_TOKEN: ${{ secrets.GUAC_TOKEN }}"));
+        assert!(workflow.contains(
+            "--gql-addr 'https://guac.team.example.com/query' --header-file /guac-headers"
+        ));
+
+        assert!(LocalFacetService::default()
+            .render_source_bundle(&params(
+                SupportedFacetType::GUACForwardingConfig,
+                github_repo()
+            ))
+            .is_err());
+    }
+
+    #[test]
+    fn test_pinned_dependencies() {
+        let facet_service = LocalFacetService {
+            action_pinner: Some(ActionPinner::new(PinningPolicy::default())),
+            ..Default::default()
+        };
+        let files = facet_service
+            .render_source_bundle(&params(
+                SupportedFacetType::PinnedDependencies,
+                github_repo(),
+            ))
+            .unwrap();
+        assert_eq!(files[0].name, "pinned-dependencies.yml");
+        assert!(PinningPolicy::default()
+            .unpinned(&files[0].content)
+            .is_empty());
+        assert_eq!(
+            format!("{}/{}", files[1].path, files[1].name),
+            format!("./{PINNING_POLICY_PATH}")
+        );
+        assert_eq!(
+            serde_yaml::from_str::<PinningPolicy>(&files[1].content).unwrap(),
+            PinningPolicy::default()
+        );
+
+        // Workflows that are already pinned are left as they are.
+        let scorecard = params(SupportedFacetType::Scorecard, github_repo());
+        assert_eq!(
+            render(&facet_service, &scorecard),
+            render(&LocalFacetService::default(), &scorecard)
+        );
+    }
+
+    #[test]
+    fn test_vulnerability_scanner() {
+        let facet_service = LocalFacetService::default();
+        let mut facet_params = params(SupportedFacetType::VulnerabilityScanner, github_repo());
+        let workflow = render(&facet_service, &facet_params);
+        assert!(
+            workflow.contains("go install github.com/google/osv-scanner/cmd/osv-scanner@v1.9.1\n")
+        );
+        assert!(workflow.contains("SEVERITY_THRESHOLD: \"0.1\""));
+        assert!(workflow.contains("FAIL_ON_NEW_FINDINGS: \"true\""));
+        assert!(workflow.contains("ref: ${{ github.event.pull_request.base.sha }}"));
+
+        facet_params.options = BTreeMap::from([
+            ("severity_threshold".to_string(), "high".into()),
+            ("fail_on_new_findings".to_string(), false.into()),
+        ]);
+        let workflow = render(&facet_service, &facet_params);
+        assert!(workflow.contains("SEVERITY_THRESHOLD: \"7\""));
+        assert!(workflow.contains("FAIL_ON_NEW_FINDINGS: \"false\""));
+
+        // Go modules are also scanned with govulncheck, using the toolchain of their go.mod.
+        let files = facet_service.render_source_bundle(&facet_params).unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[1].name, "govulncheck.yml");
+        assert!(files[1].content.contains("go-version-file: go.mod\n"));
+        assert!(files[1]
+            .content
+            .contains("go run golang.org/x/vuln/cmd/govulncheck@v1.1.4 ./..."));
+
+        let mut monorepo = facet_params.clone();
+        monorepo.common = monorepo.common.with_modules(vec![
+            InitializedEcosystem::Python(InitializedPython {
+                name: "test".to_string(),
+                package_manager: PythonPackageManager::Pip,
+            })
+            .into(),
+            InitializedModule {
+                path: "services/api".to_string(),
+                ecosystem: InitializedEcosystem::Go(InitializedGo {
+                    name: "api".to_string(),
+                    host: "github.com/testorg".to_string(),
+                }),
+            },
+        ]);
+        let files = facet_service.render_source_bundle(&monorepo).unwrap();
+        assert_eq!(
+            files.iter().map(|file| &file.name).collect::<Vec<_>>(),
+            vec![
+                "osv-scanner.yml",
+                "pip-audit.yml",
+                "govulncheck-services-api.yml"
+            ]
+        );
+        assert!(files[2]
+            .content
+            .contains("go-version-file: services/api/go.mod\n"));
+
+        // Python projects keep being scanned with pip-audit.
+        facet_params.common = facet_params
+            .common
+            .with_modules(vec![InitializedEcosystem::Python(InitializedPython {
+                name: "test".to_string(),
+                package_manager: PythonPackageManager::Pip,
+            })
+            .into()]);
+        let files = facet_service.render_source_bundle(&facet_params).unwrap();
+        assert_eq!(files[0].name, "pip-audit.yml");
+    }
+
+    #[test]
+    fn test_publish_packages() {
+        let facet_service = LocalFacetService::default();
+        let mut facet_params = params(SupportedFacetType::PublishPackages, github_repo());
+        let workflow = render(&facet_service, &facet_params);
+        assert!(workflow.contains("IMAGE: ghcr.io/testorg/test\n"));
+        assert!(workflow.contains("password: ${{ secrets.GITHUB_TOKEN }}"));
+        assert!(workflow.contains("cosign sign --yes"));
+
+        facet_params.options = BTreeMap::from([(
+            "container_registry".to_string(),
+            "registry.example.com".into(),
+        )]);
+        let workflow = render(&facet_service, &facet_params);
+        assert!(workflow.contains("IMAGE: registry.example.com/testorg/test\n"));
+        assert!(workflow.contains("password: ${{ secrets.REGISTRY_PASSWORD }}"));
+
+        facet_params.common = facet_params
+            .common
+            .with_modules(vec![InitializedEcosystem::Maven(InitializedMaven {
+                group_id: "com.example".to_string(),
+                artifact_id: "test".to_string(),
+            })
+            .into()]);
+        facet_params.options = BTreeMap::new();
+        let workflow = render(&facet_service, &facet_params);
+        assert!(workflow.contains(
+            "-DaltDeploymentRepository=github::https://maven.pkg.github.com/testorg/test\n"
+        ));
+        assert!(workflow.contains("cosign sign-blob --yes"));
+
+        facet_params.options =
+            BTreeMap::from([("maven_repository".to_string(), "maven_central".into())]);
+        let workflow = render(&facet_service, &facet_params);
+        assert!(workflow.contains("server-id: central"));
+        assert!(!workflow.contains("altDeploymentRepository"));
+    }
+
+    #[test]
+    fn test_container() {
+        let facet_service = LocalFacetService::default();
+        let mut facet_params = params(SupportedFacetType::Container, github_repo());
+        facet_params.common = facet_params
+            .common
+            .with_modules(vec![InitializedEcosystem::Maven(InitializedMaven {
+                group_id: "com.example".to_string(),
+                artifact_id: "test".to_string(),
+            })
+            .into()]);
+        let files = facet_service.render_source_bundle(&facet_params).unwrap();
+        let names = files
+            .iter()
+            .map(|file| file.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["Dockerfile", ".dockerignore", "container.yml"]);
+        assert!(files[0].content.contains("COPY test/pom.xml .\n"));
+        assert!(files[0]
+            .content
+            .contains("FROM gcr.io/distroless/java21-debian12:nonroot\n"));
+        assert!(files[0].content.contains("USER nonroot:nonroot\n"));
+        assert!(files[0]
+            .content
+            .contains(r#"ENTRYPOINT ["java", "-cp", "/app/app.jar", "com.example.App"]"#));
+        assert!(files[1].content.ends_with("Dockerfile\n**/target\n"));
+        assert!(files[2].content.contains("IMAGE: ghcr.io/testorg/test\n"));
+        assert!(files[2].content.contains("context: ./\n"));
+        assert!(files[2].content.contains("cosign sign --yes"));
+
+        // A module's image is named after it and built from its directory.
+        facet_params.common = facet_params.common.with_modules(vec![InitializedModule {
+            path: "tools".to_string(),
+            ecosystem: InitializedEcosystem::Python(InitializedPython {
+                name: "my-tools".to_string(),
+                package_manager: PythonPackageManager::Pip,
+            }),
+        }]);
+        facet_params.options = BTreeMap::from([(
+            "base_image".to_string(),
+            "cgr.dev/chainguard/python:latest".into(),
+        )]);
+        let files = facet_service.render_source_bundle(&facet_params).unwrap();
+        assert_eq!(files[0].path, "tools/");
+        assert!(files[0]
+            .content
+            .contains("FROM cgr.dev/chainguard/python:latest\n"));
+        assert!(files[0]
+            .content
+            .contains(r#""-c", "from my_tools import main; main()"]"#));
+        assert!(files[1].content.contains("**/__pycache__\n"));
+        assert_eq!(files[2].name, "container-tools.yml");
+        assert!(files[2]
+            .content
+            .contains("IMAGE: ghcr.io/testorg/test-tools\n"));
+        assert!(files[2].content.contains("context: tools/\n"));
+
+        // Go modules aren't containerized, so the facet is refused instead of panicking.
+        let error = facet_service
+            .render_source_bundle(&params(SupportedFacetType::Container, github_repo()))
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Facet Container is not supported for Go projects"
+        );
+    }
+
+    #[test]
+    fn test_dependency_update_tool() {
+        let facet_service = LocalFacetService::default();
+        let mut facet_params = params(SupportedFacetType::DependencyUpdateTool, github_repo());
+        let dependabot = render(&facet_service, &facet_params);
+        let config: serde_yaml::Value = serde_yaml::from_str(&dependabot).unwrap();
+        let updates = config["updates"].as_sequence().unwrap();
+        assert_eq!(updates.len(), 2);
+        assert_eq!(updates[0]["package-ecosystem"], "gomod");
+        assert_eq!(updates[0]["schedule"]["interval"], "weekly");
+        assert_eq!(updates[1]["package-ecosystem"], "github-actions");
+        assert_eq!(
+            updates[1]["groups"]["github-actions"]["update-types"][1],
+            "patch"
+        );
+
+        facet_params.options = BTreeMap::from([
+            ("interval".to_string(), serde_json::Value::from("daily")),
+            (
+                "docker_directories".to_string(),
+                serde_json::Value::from(vec!["/", "/deploy/"]),
+            ),
+            ("group_updates".to_string(), serde_json::Value::from(false)),
+        ]);
+        let dependabot = render(&facet_service, &facet_params);
+        let config: serde_yaml::Value = serde_yaml::from_str(&dependabot).unwrap();
+        let updates = config["updates"].as_sequence().unwrap();
+        assert_eq!(updates[0]["schedule"]["interval"], "daily");
+        assert_eq!(updates[1]["schedule"]["interval"], "weekly");
+        assert_eq!(updates[2]["package-ecosystem"], "docker");
+        assert_eq!(updates[2]["directory"], "/");
+        assert_eq!(updates[3]["directory"], "/deploy");
+        assert!(updates.iter().all(|update| update.get("groups").is_none()));
+    }
+
+    #[test]
+    fn test_maven_facets() {
+        let facet_service = LocalFacetService::default();
+        let maven_params = |facet_type| {
+            let mut facet_params = params(facet_type, github_repo());
+            facet_params.common =
+                facet_params
+                    .common
+                    .with_modules(vec![InitializedEcosystem::Maven(InitializedMaven {
+                        group_id: "com.example".to_string(),
+                        artifact_id: "test".to_string(),
+                    })
+                    .into()]);
+            facet_params
+        };
+
+        let files = facet_service
+            .render_source_bundle(&maven_params(SupportedFacetType::Gitignore))
+            .unwrap();
+        assert_eq!(files[0].name, ".gitignore");
+        assert!(files[0].content.contains("\ntarget/\n"));
+
+        let files = facet_service
+            .render_source_bundle(&maven_params(SupportedFacetType::SLSABuild))
+            .unwrap();
+        assert_eq!(files[0].name, "releases.yml");
+        assert_eq!(files[0].path, ".github/workflows/");
+        let workflow: serde_yaml::Value = serde_yaml::from_str(&files[0].content).unwrap();
+        assert_eq!(
+            workflow["jobs"]["build"]["defaults"]["run"]["working-directory"],
+            "test"
+        );
+        assert!(files[0].content.contains("test/target/*.jar"));
+
+        let dependabot = render(
+            &facet_service,
+            &maven_params(SupportedFacetType::DependencyUpdateTool),
+        );
+        let config: serde_yaml::Value = serde_yaml::from_str(&dependabot).unwrap();
+        let updates = config["updates"].as_sequence().unwrap();
+        assert_eq!(updates[0]["package-ecosystem"], "maven");
+        assert_eq!(updates[0]["directory"], "/test");
+        assert_eq!(updates[1]["package-ecosystem"], "github-actions");
+    }
+
+    #[test]
+    fn test_python_unsupported_facet() {
+        let facet_service = LocalFacetService::default();
+        let mut facet_params = params(SupportedFacetType::PublishPackages, github_repo());
+        facet_params.common = facet_params
+            .common
+            .with_modules(vec![InitializedEcosystem::Python(InitializedPython {
+                name: "test".to_string(),
+                package_manager: PythonPackageManager::Pip,
+            })
+            .into()]);
+        let error = facet_service
+            .render_source_bundle(&facet_params)
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Facet PublishPackages is not supported for Python projects"
+        );
+    }
+
+    #[test]
+    fn test_monorepo_modules() {
+        let facet_service = LocalFacetService::default();
+        let mut facet_params = params(SupportedFacetType::DependencyUpdateTool, github_repo());
+        let go = facet_params.common.module();
+        let maven = InitializedModule {
+            path: "libs/core".to_string(),
+            ecosystem: InitializedEcosystem::Maven(InitializedMaven {
+                group_id: "com.example".to_string(),
+                artifact_id: "core".to_string(),
+            }),
+        };
+        facet_params.common = facet_params.common.with_modules(vec![go, maven]);
+
+        // Every module's updates end up in the one Dependabot config, with the Github Actions ones once.
+        let files = facet_service.render_source_bundle(&facet_params).unwrap();
+        assert_eq!(files.len(), 1);
+        let dependabot: serde_yaml::Value = serde_yaml::from_str(&files[0].content).unwrap();
+        let directories = dependabot["updates"]
+            .as_sequence()
+            .unwrap()
+            .iter()
+            .map(|update| update["directory"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(directories, vec!["/", "/", "/libs/core/core"]);
+
+        // The modules' workflows don't clash and run in their module's directory.
+        facet_params.facet_type = SupportedFacetType::SLSABuild;
+        let files = facet_service.render_source_bundle(&facet_params).unwrap();
+        let maven_workflow = files
+            .iter()
+            .find(|file| file.name == "releases-libs-core.yml")
+            .unwrap();
+        assert!(files.iter().any(|file| file.name == "releases.yml"));
+        assert!(maven_workflow
+            .content
+            .contains("defaults:\n  run:\n    working-directory: libs/core\n\njobs:"));
+        assert!(maven_workflow
+            .content
+            .contains("working-directory: libs/core/core\n"));
+
+        facet_params.facet_type = SupportedFacetType::Gitignore;
+        let files = facet_service.render_source_bundle(&facet_params).unwrap();
+        assert_eq!(files[1].path, "libs/core/");
+        assert_eq!(files[1].name, ".gitignore");
+
+        // Facets that aren't specific to an ecosystem are only generated once.
+        facet_params.facet_type = SupportedFacetType::Readme;
+        assert_eq!(
+            facet_service
+                .render_source_bundle(&facet_params)
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // Facets are only generated for the modules with an ecosystem they support.
+        let registry = FacetRegistry::default();
+        let generator = FacetSetParamsGenerator {
+            registry: &registry,
+        };
+        let python = InitializedModule {
+            path: "tools".to_string(),
+            ecosystem: InitializedEcosystem::Python(InitializedPython {
+                name: "tools".to_string(),
+                package_manager: PythonPackageManager::Pip,
+            }),
+        };
+        let common = facet_params
+            .common
+            .with_modules(vec![python, facet_params.common.module()]);
+        let FacetCreateParams::SourceBundle(publish_params) = generator.generate_facet_params(
+            &common,
+            &SupportedFacetType::PublishPackages,
+            &BTreeMap::new(),
+        ) else {
+            panic!("PublishPackages is a source bundle facet");
+        };
+        assert_eq!(publish_params.common.modules.len(), 1);
+        assert_eq!(publish_params.common.ecosystem.name(), "Go");
+    }
+
+    #[test]
+    fn test_sbom_generator() {
+        let facet_service = LocalFacetService::default();
+        let mut facet_params = params(SupportedFacetType::SBOMGenerator, github_repo());
+        let workflow = render(&facet_service, &facet_params);
+        assert!(workflow.contains("uses: anchore/sbom-action@"));
+        assert!(workflow.contains("output-file: test.spdx.json\n"));
+        assert!(workflow.contains("--clobber"));
+
+        facet_params.common = facet_params
+            .common
+            .with_modules(vec![InitializedEcosystem::Maven(InitializedMaven {
+                group_id: "com.example".to_string(),
+                artifact_id: "app".to_string(),
+            })
+            .into()]);
+        let workflow = render(&facet_service, &facet_params);
+        assert!(workflow.contains("working-directory: app\n"));
+        assert!(workflow.contains("mv target/bom.json \"${GITHUB_WORKSPACE}/test.cdx.json\""));
+        assert!(!workflow.contains("anchore/sbom-action"));
+
+        // SECURITY-INSIGHTS.yml points to the SBOM the workflow attaches to releases.
+        facet_params.facet_type = SupportedFacetType::SecurityInsights;
+        let insights = render(&facet_service, &facet_params);
+        assert!(insights
+            .contains("https://github.com/testorg/test/releases/latest/download/test.cdx.json"));
+        assert!(insights.contains("CycloneDX"));
+    }
+
+    #[test]
+    fn test_bitbucket_facets() {
+        let facet_service = LocalFacetService::default();
+        let bitbucket_repo = InitializedRepo::Bitbucket(InitializedBitbucketRepo {
+            name: "test".to_string(),
+            workspace: "testorg".to_string(),
+        });
+
+        // The SBOM is generated by Bitbucket Pipelines and uploaded to the repo's Downloads.
+        let mut facet_params = params(SupportedFacetType::SBOMGenerator, bitbucket_repo.clone());
+        let pipeline = facet_service.render_source_bundle(&facet_params).unwrap();
+        assert_eq!(pipeline[0].name, "bitbucket-pipelines.yml");
+        assert_eq!(pipeline[0].path, "./");
+        assert!(pipeline[0]
+            .content
+            .contains("syft scan dir:. --output spdx-json=test.spdx.json\n"));
+        assert!(pipeline[0].content.contains("FILENAME: test.spdx.json\n"));
+        facet_params.facet_type = SupportedFacetType::SecurityInsights;
+        assert!(render(&facet_service, &facet_params)
+            .contains("https://bitbucket.org/testorg/test/downloads/test.spdx.json"));
+
+        // Branch protection is enforced with a branch restriction per rule.
+        let api_params = |facet_type| {
+            FacetCreateParams::APIBundle(APIBundleFacetParams {
+                common: facet_params.common.clone(),
+                facet_type,
+                labels: vec![],
+                options: BTreeMap::new(),
+            })
+        };
+        let actions = facet_service
+            .plan(&api_params(SupportedFacetType::BranchProtection))
+            .unwrap();
+        assert_eq!(actions.len(), 2);
+        assert!(matches!(
+            &actions[0],
+            PlannedAction::ApiCall { url, .. } if url == "/repositories/testorg/test/branch-restrictions"
+        ));
+        assert!(facet_service
+            .plan(&api_params(SupportedFacetType::VulnerabilityReporting))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_license() {
+        let facet_service = LocalFacetService::default();
+        let mut facet_params = params(SupportedFacetType::License, github_repo());
+        assert!(render(&facet_service, &facet_params).starts_with("Apache License\n"));
+
+        facet_params.options = BTreeMap::from([("spdx_id".to_string(), "MIT".into())]);
+        let license = render(&facet_service, &facet_params);
+        assert!(license.starts_with("MIT License\n"));
+        assert!(license.contains(" test authors\n"));
+
+        facet_params.options = BTreeMap::from([("spdx_id".to_string(), "AGPL-3.0-only".into())]);
+        assert!(render(&facet_service, &facet_params).contains("GNU AFFERO GENERAL PUBLIC LICENSE"));
+
+        // The README mentions the license projects are configured with.
+        let facet_service = LocalFacetService {
+            license: LicenseConfig {
+                spdx_id: SpdxLicense::Bsd3Clause,
+            },
+            ..Default::default()
+        };
+        facet_params.facet_type = SupportedFacetType::Readme;
+        facet_params.options = BTreeMap::new();
+        assert!(render(&facet_service, &facet_params)
+            .contains("[BSD 3-Clause License](LICENSE) (`BSD-3-Clause`)"));
+    }
+
+    #[test]
+    fn test_contributing_policy() {
+        let facet_service = LocalFacetService::default();
+        let mut facet_params = params(SupportedFacetType::ContributingPolicy, github_repo());
+        let contributing = render(&facet_service, &facet_params);
+        assert!(contributing.contains("https://github.com/testorg/test"));
+        assert!(contributing.contains("## Developer Certificate of Origin"));
+        assert!(!contributing.contains("## Contributor License Agreement"));
+
+        facet_params.options = BTreeMap::from([
+            ("sign_off".to_string(), "cla".into()),
+            ("cla_url".to_string(), "https://cla.example.com".into()),
+        ]);
+        let contributing = render(&facet_service, &facet_params);
+        assert!(contributing.contains("Agreement at https://cla.example.com."));
+        assert!(!contributing.contains("## Developer Certificate of Origin"));
+
+        // Violations of the Code of Conduct go to the security contact unless a conduct contact is set.
+        let facet_service = LocalFacetService {
+            security_policy: SecurityPolicyConfig {
+                contact_email: Some("security@example.com".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        facet_params.facet_type = SupportedFacetType::CodeOfConduct;
+        assert!(render(&facet_service, &facet_params).contains("at\nsecurity@example.com."));
+
+        facet_params.facet_type = SupportedFacetType::SecurityInsights;
+        let insights = render(&facet_service, &facet_params);
+        assert!(insights.contains(
+            "code-of-conduct: https://github.com/testorg/test/blob/main/CODE_OF_CONDUCT.md"
+        ));
+        assert!(insights.contains(
+            "contributing-policy: https://github.com/testorg/test/blob/main/CONTRIBUTING.md"
+        ));
+    }
+
+    #[test]
+    fn test_slsa_verification() {
+        let workflow = render(
+            &LocalFacetService::default(),
+            &params(SupportedFacetType::SLSAVerification, github_repo()),
+        );
+        assert!(workflow.contains("uses: slsa-framework/slsa-verifier/actions/installer@"));
+        assert!(workflow.contains("--source-tag \"${TAG}\""));
+        assert!(workflow.contains(&format!(
+            "gh release upload \"${{TAG}}\" \"{PROVENANCE_VERIFICATION_FILE}\""
+        )));
+    }
+
+    #[test]
+    fn test_artifact_signing() {
+        let mut facet_service = LocalFacetService::default();
+        let workflow = render(
+            &facet_service,
+            &params(SupportedFacetType::ArtifactSigning, github_repo()),
+        );
+        assert!(workflow.contains("cosign sign-blob --yes"));
+        assert!(workflow.contains("gh release upload \"${TAG}\" *.sig *.pem *.sigstore.json"));
+        assert!(workflow.contains("registry: ghcr.io\n          username: ${{ github.actor }}"));
+        assert!(workflow.contains("for image in ghcr.io/testorg/test; do"));
+
+        // Images aren't signed when there are none to sign.
+        facet_service.artifact_signing.sign_images = false;
+        let workflow = render(
+            &facet_service,
+            &params(SupportedFacetType::ArtifactSigning, github_repo()),
+        );
+        assert!(workflow.contains("cosign sign-blob --yes"));
+        assert!(!workflow.contains("crane"));
+
+        facet_service.artifact_signing.images = vec!["docker.io/testorg/test".to_string()];
+        let workflow = render(
+            &facet_service,
+            &params(SupportedFacetType::ArtifactSigning, github_repo()),
+        );
+        assert!(workflow.contains("password: ${{ secrets.REGISTRY_PASSWORD }}"));
+        assert!(workflow.contains("for image in docker.io/testorg/test; do"));
+
+        // The README tells how to verify signed releases.
+        let mut readme = params(SupportedFacetType::Readme, github_repo());
+        assert!(!render(&facet_service, &readme).contains("## Verifying releases"));
+        readme.common.artifact_signing = true;
+        let content = render(&facet_service, &readme);
+        assert!(content.contains(
+            "  --certificate-identity https://github.com/testorg/test/.github/workflows/sign-release.yml@refs/heads/main \\\n"
+        ));
+        assert!(content.contains("cosign verify docker.io/testorg/test:<version> \\\n"));
+
+        let mut insights = params(SupportedFacetType::SecurityInsights, github_repo());
+        insights.common.artifact_signing = true;
+        assert!(render(&facet_service, &insights).contains("cosign verify-blob"));
+    }
+
+    #[test]
+    fn test_code_review_plan() {
+        let facet_service = LocalFacetService::default();
+        let mut facet_params = APIBundleFacetParams {
+            common: params(SupportedFacetType::CodeReview, github_repo()).common,
+            facet_type: SupportedFacetType::CodeReview,
+            labels: vec![],
+            options: BTreeMap::from([
+                (
+                    "code_owners".to_string(),
+                    vec!["@testorg/maintainers"].into(),
+                ),
+                ("required_approving_reviews".to_string(), 2.into()),
+            ]),
+        };
+        let actions = facet_service
+            .plan(&FacetCreateParams::APIBundle(facet_params.clone()))
+            .unwrap();
+        let PlannedAction::ApiCall { url, body, .. } = &actions[0] else {
+            panic!("expected an API call");
+        };
+        assert_eq!(url, "/repos/testorg/test/contents/.github/CODEOWNERS");
+        let content = body.as_ref().unwrap()["content"].as_str().unwrap();
+        let content = base64::engine::general_purpose::STANDARD
+            .decode(content)
+            .unwrap();
+        assert!(String::from_utf8(content)
+            .unwrap()
+            .contains("\n* @testorg/maintainers"));
+        let PlannedAction::ApiCall { url, body, .. } = &actions[1] else {
+            panic!("expected an API call");
+        };
+        assert_eq!(
+            url,
+            "/repos/testorg/test/branches/main/protection/required_pull_request_reviews"
+        );
+        assert_eq!(body.as_ref().unwrap()["required_approving_review_count"], 2);
+
+        // Unlike BranchProtection, CodeReview always requires an approval.
+        facet_params.options =
+            BTreeMap::from([("required_approving_reviews".to_string(), 0.into())]);
+        assert!(facet_service
+            .plan(&FacetCreateParams::APIBundle(facet_params))
+            .is_err());
+    }
+
+    #[test]
+    fn test_best_practices_badge() {
+        let facet_service = LocalFacetService::default();
+        let readme = params(SupportedFacetType::Readme, github_repo());
+        assert!(!render(&facet_service, &readme).contains("OpenSSF Best Practices"));
+
+        // The README shows the badge once the project has one, so it's regenerated.
+        let mut with_badge = readme.clone();
+        with_badge.common.best_practices_badge_id = Some(8490);
+        assert!(render(&facet_service, &with_badge).contains(
+            "[![OpenSSF Best Practices](https://www.bestpractices.dev/projects/8490/badge)]\
+             (https://www.bestpractices.dev/projects/8490)"
+        ));
+        assert_ne!(
+            generator_fingerprint(&FacetCreateParams::SourceBundle(with_badge)),
+            generator_fingerprint(&FacetCreateParams::SourceBundle(readme.clone()))
+        );
+
+        let actions = facet_service
+            .plan(&FacetCreateParams::APIBundle(APIBundleFacetParams {
+                common: readme.common,
+                facet_type: SupportedFacetType::BestPracticesBadge,
+                labels: vec![],
+                options: BTreeMap::new(),
+            }))
+            .unwrap();
+        let PlannedAction::ApiCall { url, .. } = &actions[0] else {
+            panic!("expected an API call");
+        };
+        assert_eq!(
+            url,
+            "https://www.bestpractices.dev/projects.json?url=https://github.com/testorg/test"
+        );
+    }
 }
@@ -0,0 +1,914 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The libgit2 based implementation of the `SourceService` trait, selected with `git_backend: libgit2`. It
+//! doesn't need a `git` binary, and every failed clone, commit, fetch or push is returned as an error instead of
+//! depending on the exit status of a command.
+
+#![allow(clippy::module_name_repetitions)]
+
+use std::{cell::RefCell, path::Path};
+
+use git2::{
+    build::{CheckoutBuilder, RepoBuilder},
+    Cred, CredentialType, Direction, ErrorCode, FetchOptions, IndexAddOption, PushOptions, Remote,
+    RemoteCallbacks, Repository, ResetType, StatusOptions,
+};
+use tracing::{debug, error, info};
+
+use skootrs_model::skootrs::{
+    CommitSigning, Config, InitializedRepo, InitializedSource, SkootError, SourceInitializeParams,
+};
+
+use super::{
+    cancellation,
+    read_only::ensure_writable,
+    repo::remove_on_cancel,
+    signing,
+    source::{
+        ConcurrencyError, LocalSourceService, PushVerificationError, SourceLock, SourceService,
+    },
+};
+
+/// The environment variable the Github token is read from if the config doesn't set one.
+const GITHUB_TOKEN_ENV: &str = "GITHUB_TOKEN";
+
+/// How many times a remote can ask for credentials before giving up. libgit2 keeps asking as long as the
+/// credentials it's given are rejected.
+const MAX_CREDENTIAL_ATTEMPTS: usize = 3;
+
+/// The `GitCredentials` struct holds what libgit2 authenticates to remotes with.
+///
+/// The Github token is only sent to `https://github.com`. Other HTTPS remotes use the git credential helpers,
+/// and SSH remotes use the SSH agent, the same as the `git` binary would.
+#[derive(Clone, Default)]
+pub struct GitCredentials {
+    /// The Github token used for HTTPS remotes on `github.com`.
+    pub github_token: Option<String>,
+}
+
+impl GitCredentials {
+    /// Returns the credentials for the config. The Github token is `github_token`, or the `GITHUB_TOKEN`
+    /// environment variable if it isn't set.
+    #[must_use]
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            github_token: config
+                .github_token
+                .clone()
+                .or_else(|| std::env::var(GITHUB_TOKEN_ENV).ok()),
+        }
+    }
+
+    /// Returns the callbacks that authenticate to a remote, and abort the transfer if the current operation
+    /// is cancelled.
+    fn callbacks(&self) -> RemoteCallbacks<'_> {
+        let mut callbacks = RemoteCallbacks::new();
+        let mut attempts = 0;
+        callbacks.credentials(move |url, username, allowed| {
+            attempts += 1;
+            if attempts > MAX_CREDENTIAL_ATTEMPTS {
+                return Err(git2::Error::from_str(&format!(
+                    "Authentication to {url} failed"
+                )));
+            }
+            if allowed.contains(CredentialType::SSH_KEY) {
+                return Cred::ssh_key_from_agent(username.unwrap_or("git"));
+            }
+            if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) {
+                // The token is tried first, falling back to the credential helpers if it's rejected.
+                if let Some(token) = self
+                    .github_token
+                    .as_deref()
+                    .filter(|_| attempts == 1 && url.starts_with("https://github.com/"))
+                {
+                    return Cred::userpass_plaintext("x-access-token", token);
+                }
+                return Cred::credential_helper(&git2::Config::open_default()?, url, username);
+            }
+            Cred::default()
+        });
+        callbacks.transfer_progress(|_| cancellation::check().is_ok());
+        callbacks
+    }
+}
+
+impl std::fmt::Debug for GitCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // The token itself is never printed.
+        f.debug_struct("GitCredentials")
+            .field(
+                "github_token",
+                &self.github_token.as_ref().map(|_| "<redacted>"),
+            )
+            .finish()
+    }
+}
+
+/// Clones a repo into `parent_path` with libgit2.
+///
+/// # Errors
+///
+/// Returns an error if the repo can't be cloned, e.g. because it doesn't exist or the credentials were
+/// rejected.
+pub fn clone(
+    credentials: &GitCredentials,
+    initialized_repo: &InitializedRepo,
+    parent_path: &str,
+) -> Result<InitializedSource, SkootError> {
+    let url = match initialized_repo {
+        InitializedRepo::Github(g) => g.full_url(),
+        InitializedRepo::GenericGit(g) => g.remote_url.clone(),
+        InitializedRepo::Bitbucket(b) => format!("{}.git", b.full_url()),
+    };
+    let name = initialized_repo.name();
+    let path = Path::new(parent_path).join(&name);
+    debug!("Cloning {url}");
+    remove_on_cancel(path.clone());
+    let mut fetch_options = FetchOptions::new();
+    fetch_options.remote_callbacks(credentials.callbacks());
+    if let Err(e) = RepoBuilder::new()
+        .fetch_options(fetch_options)
+        .clone(&url, &path)
+    {
+        // A cancelled clone fails with a libgit2 error, which should be reported as the cancellation.
+        cancellation::check()?;
+        return Err(format!("Failed to clone {url}: {}", e.message()).into());
+    }
+
+    Ok(InitializedSource {
+        path: format!("{parent_path}/{name}"),
+    })
+}
+
+/// The `Git2SourceService` struct provides an implementation of the `SourceService` trait on top of libgit2.
+/// Only the git operations differ from `LocalSourceService`, the files are read and written the same way.
+#[derive(Debug, Default)]
+pub struct Git2SourceService {
+    /// Whether operations that change the source are blocked.
+    pub read_only: bool,
+    /// What remotes are authenticated to with.
+    pub credentials: GitCredentials,
+    /// How the commits made to the source are signed.
+    pub commit_signing: CommitSigning,
+}
+
+impl SourceService for Git2SourceService {
+    fn initialize(
+        &self,
+        params: SourceInitializeParams,
+        initialized_repo: InitializedRepo,
+    ) -> Result<InitializedSource, SkootError> {
+        clone(&self.credentials, &initialized_repo, &params.parent_path)
+    }
+
+    fn commit_and_push_changes(
+        &self,
+        source: InitializedSource,
+        message: String,
+    ) -> Result<(), SkootError> {
+        ensure_writable(
+            self.read_only,
+            format_args!("commit and push {}", source.path),
+        )?;
+        let repo = open(&source)?;
+        commit(&repo, &source, None, &message, &self.commit_signing)?;
+
+        // The push can't be undone, so a cancelled operation has to stop before it.
+        cancellation::check()?;
+        let branch = current_branch(&repo)?;
+        self.push(
+            &mut repo.find_remote("origin").map_err(SkootError::other)?,
+            &[format!("refs/heads/{branch}:refs/heads/{branch}")],
+        )?;
+        info!("Pushed changes for {}", source.path);
+        Ok(())
+    }
+
+    fn commit_changes(
+        &self,
+        source: &InitializedSource,
+        paths: Option<&[String]>,
+        message: &str,
+    ) -> Result<bool, SkootError> {
+        ensure_writable(self.read_only, format_args!("commit to {}", source.path))?;
+        commit(&open(source)?, source, paths, message, &self.commit_signing)
+    }
+
+    fn commit_and_push_branch(
+        &self,
+        source: InitializedSource,
+        branch: &str,
+        message: String,
+    ) -> Result<String, SkootError> {
+        ensure_writable(
+            self.read_only,
+            format_args!("commit and push {} to {branch}", source.path),
+        )?;
+        let repo = open(&source)?;
+        let base = current_branch(&repo)?;
+        // The new branch starts at the checked out commit, so the working tree is left as it is.
+        repo.branch(
+            branch,
+            &repo
+                .head()
+                .map_err(SkootError::other)?
+                .peel_to_commit()
+                .map_err(SkootError::other)?,
+            true,
+        )
+        .map_err(SkootError::other)?;
+        repo.set_head(&format!("refs/heads/{branch}"))
+            .map_err(SkootError::other)?;
+        commit(&repo, &source, None, &message, &self.commit_signing)?;
+
+        // The push can't be undone, so a cancelled operation has to stop before it.
+        cancellation::check()?;
+        self.push(
+            &mut repo.find_remote("origin").map_err(SkootError::other)?,
+            &[format!("refs/heads/{branch}:refs/heads/{branch}")],
+        )?;
+        info!("Pushed changes for {} to {branch}", source.path);
+        Ok(base)
+    }
+
+    fn checkout_default_branch(&self, source: &InitializedSource) -> Result<bool, SkootError> {
+        let repo = open(source)?;
+        // The remote is asked since a clone of an empty repo doesn't know its default branch.
+        let default_branch = {
+            let mut remote = repo.find_remote("origin").map_err(SkootError::other)?;
+            let connection = remote
+                .connect_auth(Direction::Fetch, Some(self.credentials.callbacks()), None)
+                .map_err(SkootError::other)?;
+            connection
+                .default_branch()
+                .map_err(SkootError::other)?
+                .as_str()
+                .and_then(|reference| reference.strip_prefix("refs/heads/"))
+                .map(ToString::to_string)
+                .ok_or_else(|| format!("Failed to get the default branch of {}", source.path))?
+        };
+        if current_branch(&repo)? == default_branch {
+            return Ok(false);
+        }
+        let reference = format!("refs/heads/{default_branch}");
+        repo.checkout_tree(
+            &repo
+                .revparse_single(&reference)
+                .map_err(SkootError::other)?,
+            Some(CheckoutBuilder::new().safe()),
+        )
+        .map_err(SkootError::other)?;
+        repo.set_head(&reference).map_err(SkootError::other)?;
+        info!("Checked out {default_branch} of {}", source.path);
+        Ok(true)
+    }
+
+    fn write_file<P: AsRef<Path>, C: AsRef<[u8]>>(
+        &self,
+        source: InitializedSource,
+        path: P,
+        name: String,
+        contents: C,
+    ) -> Result<(), SkootError> {
+        self.files().write_file(source, path, name, contents)
+    }
+
+    fn read_file<P: AsRef<Path>>(
+        &self,
+        source: &InitializedSource,
+        path: P,
+        name: String,
+    ) -> Result<String, SkootError> {
+        self.files().read_file(source, path, name)
+    }
+
+    fn remove_file<P: AsRef<Path>>(
+        &self,
+        source: &InitializedSource,
+        path: P,
+        name: String,
+    ) -> Result<(), SkootError> {
+        self.files().remove_file(source, path, name)
+    }
+
+    fn hash_file<P: AsRef<Path>>(
+        &self,
+        source: &InitializedSource,
+        path: P,
+        name: String,
+    ) -> Result<String, SkootError> {
+        self.files().hash_file(source, path, name)
+    }
+
+    /// Fetches the current branch from `origin` and fast-forwards to it. Unlike `git pull`, a branch that has
+    /// diverged from the remote is an error instead of being merged.
+    fn pull_updates(&self, source: InitializedSource) -> Result<(), SkootError> {
+        let repo = open(&source)?;
+        let branch = current_branch(&repo)?;
+        let mut fetch_options = FetchOptions::new();
+        fetch_options.remote_callbacks(self.credentials.callbacks());
+        repo.find_remote("origin")
+            .map_err(SkootError::other)?
+            .fetch(&[&branch], Some(&mut fetch_options), None)
+            .map_err(SkootError::other)?;
+        let fetched = repo
+            .reference_to_annotated_commit(
+                &repo
+                    .find_reference("FETCH_HEAD")
+                    .map_err(SkootError::other)?,
+            )
+            .map_err(SkootError::other)?;
+        let (analysis, _) = repo
+            .merge_analysis(&[&fetched])
+            .map_err(SkootError::other)?;
+        if analysis.is_up_to_date() {
+            debug!("{} is up to date", source.path);
+            return Ok(());
+        }
+        if !analysis.is_fast_forward() && !analysis.is_unborn() {
+            return Err(format!(
+                "{} has diverged from origin/{branch} and can't be fast-forwarded",
+                source.path
+            )
+            .into());
+        }
+        // Checked out before moving the branch, so local changes that would be overwritten fail the pull.
+        repo.checkout_tree(
+            &repo
+                .find_object(fetched.id(), None)
+                .map_err(SkootError::other)?,
+            Some(CheckoutBuilder::new().safe()),
+        )
+        .map_err(SkootError::other)?;
+        let reference = format!("refs/heads/{branch}");
+        repo.reference(&reference, fetched.id(), true, "pull: fast-forward")
+            .map_err(SkootError::other)?;
+        repo.set_head(&reference).map_err(SkootError::other)?;
+        info!("Pulled updates for {}", source.path);
+        Ok(())
+    }
+
+    fn set_remote_url(&self, source: &InitializedSource, url: &str) -> Result<(), SkootError> {
+        ensure_writable(
+            self.read_only,
+            format_args!("set the remote of {} to {url}", source.path),
+        )?;
+        open(source)?
+            .remote_set_url("origin", url)
+            .map_err(SkootError::other)?;
+        info!("Set the remote of {} to {url}", source.path);
+        Ok(())
+    }
+
+    fn delete(&self, source: InitializedSource) -> Result<(), SkootError> {
+        self.files().delete(source)
+    }
+
+    fn verify_push(&self, source: &InitializedSource) -> Result<(), SkootError> {
+        let repo = open(source)?;
+        let local = self.head_commit(source)?;
+        let branch = current_branch(&repo)?;
+        let Some(remote) = self.remote_branch_commit(&repo, &branch)? else {
+            return Err(SkootError::other(PushVerificationError::RemoteRefMissing {
+                branch,
+            }));
+        };
+
+        if remote != local {
+            return Err(SkootError::other(PushVerificationError::CommitMismatch {
+                branch,
+                local,
+                remote,
+            }));
+        }
+
+        debug!(
+            "Verified remote branch {branch} is at {local} for {}",
+            source.path
+        );
+        Ok(())
+    }
+
+    fn push_mirror(
+        &self,
+        source: &InitializedSource,
+        mirror: &InitializedRepo,
+    ) -> Result<(), SkootError> {
+        ensure_writable(
+            self.read_only,
+            format_args!("push {} to mirror {}", source.path, mirror.full_url()),
+        )?;
+        let repo = open(source)?;
+        let branch = current_branch(&repo)?;
+        // libgit2 has no equivalent of `--follow-tags`, so every tag is pushed along with the branch.
+        let mut refspecs = vec![format!("+refs/heads/{branch}:refs/heads/{branch}")];
+        refspecs.extend(
+            repo.tag_names(None)
+                .map_err(SkootError::other)?
+                .iter()
+                .flatten()
+                .map(|tag| format!("refs/tags/{tag}:refs/tags/{tag}")),
+        );
+        cancellation::check()?;
+        self.push(
+            &mut repo
+                .remote_anonymous(&mirror.full_url())
+                .map_err(SkootError::other)?,
+            &refspecs,
+        )?;
+        info!(
+            "Pushed {branch} of {} to mirror {}",
+            source.path,
+            mirror.full_url()
+        );
+        Ok(())
+    }
+
+    fn head_commit(&self, source: &InitializedSource) -> Result<String, SkootError> {
+        let repo = open(source)?;
+        let commit = repo
+            .head()
+            .map_err(SkootError::other)?
+            .peel_to_commit()
+            .map_err(SkootError::other)?;
+        Ok(commit.id().to_string())
+    }
+
+    fn verify_remote_head(
+        &self,
+        source: &InitializedSource,
+        expected_parent: &str,
+    ) -> Result<(), SkootError> {
+        let repo = open(source)?;
+        let branch = current_branch(&repo)?;
+        let actual = self.remote_branch_commit(&repo, &branch)?;
+        if actual.as_deref() != Some(expected_parent) {
+            return Err(SkootError::other(ConcurrencyError::Conflict {
+                branch,
+                expected: expected_parent.to_string(),
+                actual,
+            }));
+        }
+        Ok(())
+    }
+
+    fn lock(&self, source: &InitializedSource) -> Result<SourceLock, SkootError> {
+        self.files().lock(source)
+    }
+
+    fn reset_on_cancel(&self, source: &InitializedSource, commit: &str) {
+        let source = source.clone();
+        let commit = commit.to_string();
+        cancellation::on_cancel(move || match reset(&source, &commit) {
+            Ok(()) => info!(
+                "Reset {} to {commit} after the operation was cancelled",
+                source.path
+            ),
+            Err(e) => error!("Failed to reset {} to {commit}: {e}", source.path),
+        });
+    }
+}
+
+impl Git2SourceService {
+    /// The files of a source are handled the same as by `LocalSourceService`.
+    fn files(&self) -> LocalSourceService {
+        LocalSourceService {
+            read_only: self.read_only,
+            commit_signing: self.commit_signing.clone(),
+        }
+    }
+
+    /// Returns the commit a branch points to on `origin`, or `None` if the branch doesn't exist there.
+    fn remote_branch_commit(
+        &self,
+        repo: &Repository,
+        branch: &str,
+    ) -> Result<Option<String>, SkootError> {
+        let mut remote = repo.find_remote("origin").map_err(SkootError::other)?;
+        let connection = remote
+            .connect_auth(Direction::Fetch, Some(self.credentials.callbacks()), None)
+            .map_err(SkootError::other)?;
+        let reference = format!("refs/heads/{branch}");
+        Ok(connection
+            .list()
+            .map_err(SkootError::other)?
+            .iter()
+            .find(|head| head.name() == reference)
+            .map(|head| head.oid().to_string()))
+    }
+
+    /// Pushes the refspecs to the remote. A ref the remote rejects fails the push, which libgit2 otherwise
+    /// only reports through a callback.
+    fn push(&self, remote: &mut Remote<'_>, refspecs: &[String]) -> Result<(), SkootError> {
+        let rejected = RefCell::new(Vec::new());
+        {
+            let mut callbacks = self.credentials.callbacks();
+            callbacks.push_update_reference(|reference, status| {
+                if let Some(status) = status {
+                    rejected.borrow_mut().push(format!("{reference}: {status}"));
+                }
+                Ok(())
+            });
+            let mut push_options = PushOptions::new();
+            push_options.remote_callbacks(callbacks);
+            remote
+                .push(refspecs, Some(&mut push_options))
+                .map_err(|e| PushVerificationError::PushFailed {
+                    stderr: e.message().to_string(),
+                })
+                .map_err(SkootError::other)?;
+        }
+        let rejected = rejected.into_inner();
+        if !rejected.is_empty() {
+            return Err(SkootError::other(PushVerificationError::PushFailed {
+                stderr: rejected.join("; "),
+            }));
+        }
+        Ok(())
+    }
+}
+
+/// Hard resets the source to `commit` and removes the untracked files, like `git reset --hard` followed by
+/// `git clean -fd`.
+fn reset(source: &InitializedSource, commit: &str) -> Result<(), SkootError> {
+    let repo = open(source)?;
+    let target = repo.revparse_single(commit).map_err(SkootError::other)?;
+    repo.reset(&target, ResetType::Hard, None)
+        .map_err(SkootError::other)?;
+
+    let mut status_options = StatusOptions::new();
+    status_options.include_untracked(true);
+    let statuses = repo
+        .statuses(Some(&mut status_options))
+        .map_err(SkootError::other)?;
+    // Untracked directories are reported as a whole, with a trailing slash.
+    for path in statuses
+        .iter()
+        .filter(|entry| entry.status().is_wt_new())
+        .filter_map(|entry| entry.path().map(|path| Path::new(&source.path).join(path)))
+    {
+        if path.is_dir() {
+            std::fs::remove_dir_all(&path)?;
+        } else {
+            std::fs::remove_file(&path)?;
+        }
+    }
+    Ok(())
+}
+
+fn open(source: &InitializedSource) -> Result<Repository, SkootError> {
+    Repository::open(&source.path).map_err(|e| {
+        format!(
+            "Failed to open the git repo at {}: {}",
+            source.path,
+            e.message()
+        )
+        .into()
+    })
+}
+
+/// Commits the changes to the given files, or every change if `paths` is `None`, to the checked out branch,
+/// signed the way `signing` says. Nothing is committed if nothing changed. Returns whether anything was
+/// committed.
+fn commit(
+    repo: &Repository,
+    source: &InitializedSource,
+    paths: Option<&[String]>,
+    message: &str,
+    signing: &CommitSigning,
+) -> Result<bool, SkootError> {
+    let pathspecs = paths.map_or_else(|| vec!["*".to_string()], <[String]>::to_vec);
+    let mut index = repo.index().map_err(SkootError::other)?;
+    index
+        .add_all(&pathspecs, IndexAddOption::DEFAULT, None)
+        .map_err(SkootError::other)?;
+    // `add_all` doesn't stage deletions.
+    index
+        .update_all(&pathspecs, None)
+        .map_err(SkootError::other)?;
+    index.write().map_err(SkootError::other)?;
+    let tree = repo
+        .find_tree(index.write_tree().map_err(SkootError::other)?)
+        .map_err(SkootError::other)?;
+    let parent = match repo.head() {
+        Ok(head) => Some(head.peel_to_commit().map_err(SkootError::other)?),
+        // A new repo has no commits yet.
+        Err(e) if e.code() == ErrorCode::UnbornBranch || e.code() == ErrorCode::NotFound => None,
+        Err(e) => return Err(SkootError::other(e)),
+    };
+    if parent.as_ref().is_some_and(|p| p.tree_id() == tree.id()) {
+        debug!("Nothing to commit for {}", source.path);
+        return Ok(false);
+    }
+    let signature = repo.signature().map_err(|e| {
+        format!(
+            "Failed to commit to {}, set user.name and user.email in the git config: {}",
+            source.path,
+            e.message()
+        )
+    })?;
+    let parents = parent.iter().collect::<Vec<_>>();
+    let buffer = repo
+        .commit_create_buffer(&signature, &signature, message, &tree, &parents)
+        .map_err(SkootError::other)?;
+    let buffer = buffer.as_str().ok_or_else(|| {
+        format!(
+            "Failed to commit to {}, the commit isn't UTF-8",
+            source.path
+        )
+    })?;
+    match signing::sign(signing, buffer)? {
+        // libgit2 doesn't move the branch for a signed commit, so HEAD's branch is moved by hand.
+        Some(armored) => {
+            let id = repo
+                .commit_signed(buffer, &armored, None)
+                .map_err(SkootError::other)?;
+            let branch = format!("refs/heads/{}", current_branch(repo)?);
+            repo.reference(&branch, id, true, message)
+                .map_err(SkootError::other)?;
+        }
+        None => {
+            repo.commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                message,
+                &tree,
+                &parents,
+            )
+            .map_err(SkootError::other)?;
+        }
+    }
+    info!("Committed changes for {}", source.path);
+    Ok(true)
+}
+
+fn current_branch(repo: &Repository) -> Result<String, SkootError> {
+    let head = repo.find_reference("HEAD").map_err(SkootError::other)?;
+    head.symbolic_target()
+        .and_then(|target| target.strip_prefix("refs/heads/"))
+        .map(ToString::to_string)
+        .ok_or_else(|| "HEAD isn't on a branch".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use skootrs_model::skootrs::InitializedGenericGitRepo;
+    use tempdir::TempDir;
+
+    use super::*;
+
+    /// Creates an empty bare remote and clones it, with a committer configured so commits can be made.
+    fn cloned_remote(temp_dir: &TempDir) -> (Git2SourceService, InitializedSource) {
+        let remote_path = temp_dir.path().join("remote.git");
+        Repository::init_bare(&remote_path).unwrap();
+        let initialized_repo = InitializedRepo::GenericGit(
+            InitializedGenericGitRepo::from_remote_url(remote_path.to_str().unwrap().to_string())
+                .unwrap(),
+        );
+        let source_service = Git2SourceService::default();
+        let source = source_service
+            .initialize(
+                SourceInitializeParams {
+                    parent_path: temp_dir.path().to_str().unwrap().to_string(),
+                },
+                initialized_repo,
+            )
+            .unwrap();
+        let mut config = Repository::open(&source.path).unwrap().config().unwrap();
+        config.set_str("user.name", "Skootrs").unwrap();
+        config.set_str("user.email", "skootrs@example.com").unwrap();
+        (source_service, source)
+    }
+
+    #[test]
+    fn test_commit_and_push_changes() {
+        let temp_dir = TempDir::new("test").unwrap();
+        let (source_service, source) = cloned_remote(&temp_dir);
+        assert_eq!(source.path, format!("{}/remote", temp_dir.path().display()));
+
+        source_service
+            .write_file(source.clone(), "./", "README.md".to_string(), "readme")
+            .unwrap();
+        source_service
+            .commit_and_push_changes(source.clone(), "Initialized project".to_string())
+            .unwrap();
+        source_service.verify_push(&source).unwrap();
+        let head = source_service.head_commit(&source).unwrap();
+        source_service.verify_remote_head(&source, &head).unwrap();
+
+        // A commit that only exists locally hasn't landed on the remote.
+        source_service
+            .remove_file(&source, "./", "README.md".to_string())
+            .unwrap();
+        let repo = open(&source).unwrap();
+        let mut index = repo.index().unwrap();
+        index.update_all(["*"], None).unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = repo.signature().unwrap();
+        let parent = repo.head().unwrap().peel_to_commit().unwrap();
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            "Removed readme",
+            &tree,
+            &[&parent],
+        )
+        .unwrap();
+        let err = source_service.verify_push(&source).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<PushVerificationError>(),
+            Some(PushVerificationError::CommitMismatch { .. })
+        ));
+        assert!(source_service
+            .verify_remote_head(&source, &source_service.head_commit(&source).unwrap())
+            .is_err());
+    }
+
+    #[test]
+    fn test_reset() {
+        let temp_dir = TempDir::new("test").unwrap();
+        let (source_service, source) = cloned_remote(&temp_dir);
+        source_service
+            .write_file(source.clone(), "./", "README.md".to_string(), "readme")
+            .unwrap();
+        source_service
+            .commit_changes(&source, None, "Initialized project")
+            .unwrap();
+        let initial = source_service.head_commit(&source).unwrap();
+
+        source_service
+            .write_file(source.clone(), "./", "README.md".to_string(), "changed")
+            .unwrap();
+        source_service
+            .commit_changes(&source, None, "Changed readme")
+            .unwrap();
+        source_service
+            .write_file(
+                source.clone(),
+                ".github/",
+                "SECURITY.md".to_string(),
+                "policy",
+            )
+            .unwrap();
+        source_service
+            .write_file(
+                source.clone(),
+                "./",
+                "untracked.txt".to_string(),
+                "untracked",
+            )
+            .unwrap();
+
+        reset(&source, &initial).unwrap();
+        assert_eq!(source_service.head_commit(&source).unwrap(), initial);
+        let path = Path::new(&source.path);
+        assert_eq!(
+            std::fs::read_to_string(path.join("README.md")).unwrap(),
+            "readme"
+        );
+        assert!(!path.join(".github").exists());
+        assert!(!path.join("untracked.txt").exists());
+    }
+
+    #[test]
+    fn test_commit_changes() {
+        let temp_dir = TempDir::new("test").unwrap();
+        let (source_service, source) = cloned_remote(&temp_dir);
+        source_service
+            .write_file(source.clone(), "./", "README.md".to_string(), "readme")
+            .unwrap();
+        source_service
+            .write_file(
+                source.clone(),
+                ".github/",
+                "SECURITY.md".to_string(),
+                "policy",
+            )
+            .unwrap();
+
+        let paths = [".github/SECURITY.md".to_string()];
+        assert!(source_service
+            .commit_changes(&source, Some(&paths), "Added SecurityPolicy facet")
+            .unwrap());
+        assert!(!source_service
+            .commit_changes(&source, Some(&paths), "Added SecurityPolicy facet")
+            .unwrap());
+        let repo = open(&source).unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert!(head.tree().unwrap().get_name("README.md").is_none());
+
+        assert!(source_service
+            .commit_changes(&source, None, "Initialized project")
+            .unwrap());
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert!(head.tree().unwrap().get_name("README.md").is_some());
+        assert_eq!(head.parent_count(), 1);
+    }
+
+    #[test]
+    fn test_set_remote_url() {
+        let temp_dir = TempDir::new("test").unwrap();
+        let (source_service, source) = cloned_remote(&temp_dir);
+        let new_url = "https://github.com/new-org/payments";
+        source_service.set_remote_url(&source, new_url).unwrap();
+        assert_eq!(
+            open(&source).unwrap().find_remote("origin").unwrap().url(),
+            Some(new_url)
+        );
+    }
+
+    #[test]
+    fn test_pull_updates() {
+        let temp_dir = TempDir::new("test").unwrap();
+        let (source_service, source) = cloned_remote(&temp_dir);
+        source_service
+            .write_file(source.clone(), "./", "README.md".to_string(), "readme")
+            .unwrap();
+        source_service
+            .commit_and_push_changes(source.clone(), "Initialized project".to_string())
+            .unwrap();
+
+        // A second clone of the same remote picks up the push.
+        let other_parent = temp_dir.path().join("other");
+        std::fs::create_dir(&other_parent).unwrap();
+        let other = clone(
+            &GitCredentials::default(),
+            &InitializedRepo::GenericGit(
+                InitializedGenericGitRepo::from_remote_url(
+                    temp_dir
+                        .path()
+                        .join("remote.git")
+                        .to_str()
+                        .unwrap()
+                        .to_string(),
+                )
+                .unwrap(),
+            ),
+            other_parent.to_str().unwrap(),
+        )
+        .unwrap();
+        source_service
+            .write_file(source.clone(), "./", "README.md".to_string(), "updated")
+            .unwrap();
+        source_service
+            .commit_and_push_changes(source.clone(), "Updated readme".to_string())
+            .unwrap();
+        source_service.pull_updates(other.clone()).unwrap();
+        assert_eq!(
+            source_service
+                .read_file(&other, "./", "README.md".to_string())
+                .unwrap(),
+            "updated"
+        );
+        assert_eq!(
+            source_service.head_commit(&other).unwrap(),
+            source_service.head_commit(&source).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_clone_missing_remote() {
+        let temp_dir = TempDir::new("test").unwrap();
+        let initialized_repo = InitializedRepo::GenericGit(
+            InitializedGenericGitRepo::from_remote_url(
+                temp_dir
+                    .path()
+                    .join("missing.git")
+                    .to_str()
+                    .unwrap()
+                    .to_string(),
+            )
+            .unwrap(),
+        );
+        let err = clone(
+            &GitCredentials::default(),
+            &initialized_repo,
+            temp_dir.path().to_str().unwrap(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().starts_with("Failed to clone"));
+    }
+
+    #[test]
+    fn test_git_credentials_debug() {
+        let credentials = GitCredentials {
+            github_token: Some("ghp_secret".to_string()),
+        };
+        assert!(!format!("{credentials:?}").contains("ghp_secret"));
+    }
+}
@@ -0,0 +1,184 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! `GithubAuth` is how Skootrs authenticates to the Github API: a token from the config, a Github App
+//! installation, or the ambient `GITHUB_TOKEN` environment variable like the one Github Actions gives a
+//! workflow. The services build their `Octocrab` clients from it instead of reading the token themselves.
+
+#![allow(clippy::module_name_repetitions)]
+
+use octocrab::{
+    models::{AppId, InstallationId},
+    Octocrab,
+};
+use skootrs_model::skootrs::{Config, GithubAuthMode, SkootError};
+
+/// The environment variable the ambient token is read from.
+const GITHUB_TOKEN_ENV: &str = "GITHUB_TOKEN";
+
+/// The `GithubAuth` enum is the credential Skootrs uses for the Github API.
+#[derive(Clone, Default)]
+pub enum GithubAuth {
+    /// A personal access token.
+    Token(String),
+    /// A Github App installation. Octocrab mints and refreshes the installation token as it's needed.
+    App {
+        /// The ID of the Github App.
+        app_id: u64,
+        /// The ID of the App's installation.
+        installation_id: u64,
+        /// The App's PEM encoded private key.
+        private_key: String,
+    },
+    /// The `GITHUB_TOKEN` environment variable, read when a client is built.
+    #[default]
+    Ambient,
+}
+
+impl GithubAuth {
+    /// Returns the credential the config selects. With the default `auto` mode a Github App takes precedence
+    /// over `github_token`, and the ambient `GITHUB_TOKEN` is used if neither is set. The other modes only
+    /// use the credential they name, falling back to the ambient `GITHUB_TOKEN` if it isn't configured.
+    #[must_use]
+    pub fn from_config(config: &Config) -> Self {
+        match (config.github_auth, &config.github_app, &config.github_token) {
+            (GithubAuthMode::Auto | GithubAuthMode::App, Some(app), _) => Self::App {
+                app_id: app.app_id,
+                installation_id: app.installation_id,
+                private_key: app.private_key.clone(),
+            },
+            (GithubAuthMode::Auto | GithubAuthMode::Token, _, Some(token)) => {
+                Self::Token(token.clone())
+            }
+            _ => Self::Ambient,
+        }
+    }
+
+    /// Builds an `Octocrab` client authenticated with the credential. A new client is built for every call
+    /// since a client is tied to the async runtime it was built in, and the daemon serves requests from
+    /// several runtimes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the ambient `GITHUB_TOKEN` isn't set, the App's private key isn't a valid RSA
+    /// key, or the client can't be built.
+    pub fn client(&self) -> Result<Octocrab, SkootError> {
+        match self {
+            Self::Token(token) => Ok(Octocrab::builder()
+                .personal_token(token.clone())
+                .build()
+                .map_err(SkootError::github)?),
+            Self::App {
+                app_id,
+                installation_id,
+                private_key,
+            } => {
+                let key = jsonwebtoken::EncodingKey::from_rsa_pem(private_key.as_bytes())
+                    .map_err(|e| format!("Failed to read the Github App private key: {e}"))?;
+                let app = Octocrab::builder()
+                    .app(AppId(*app_id), key)
+                    .build()
+                    .map_err(SkootError::github)?;
+                Ok(app.installation(InstallationId(*installation_id)))
+            }
+            Self::Ambient => {
+                let token = std::env::var(GITHUB_TOKEN_ENV).map_err(|_| {
+                    format!("{GITHUB_TOKEN_ENV} env var or a Github credential in the config must be set")
+                })?;
+                Ok(Octocrab::builder()
+                    .personal_token(token)
+                    .build()
+                    .map_err(SkootError::github)?)
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for GithubAuth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // The credentials themselves are never printed.
+        match self {
+            Self::Token(_) => f.write_str("Token"),
+            Self::App {
+                app_id,
+                installation_id,
+                ..
+            } => f
+                .debug_struct("App")
+                .field("app_id", app_id)
+                .field("installation_id", installation_id)
+                .finish_non_exhaustive(),
+            Self::Ambient => f.write_str("Ambient"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use skootrs_model::skootrs::GithubAppConfig;
+
+    use super::*;
+
+    #[test]
+    fn test_github_auth_from_config() {
+        let mut config = Config::default();
+        assert!(matches!(
+            GithubAuth::from_config(&config),
+            GithubAuth::Ambient
+        ));
+
+        config.github_token = Some("ghp_secret".to_string());
+        let auth = GithubAuth::from_config(&config);
+        assert!(matches!(&auth, GithubAuth::Token(token) if token == "ghp_secret"));
+        assert!(!format!("{auth:?}").contains("ghp_secret"));
+
+        config.github_app = Some(GithubAppConfig {
+            app_id: 1,
+            installation_id: 2,
+            private_key: "not a key".to_string(),
+        });
+        let auth = GithubAuth::from_config(&config);
+        assert!(matches!(
+            auth,
+            GithubAuth::App {
+                app_id: 1,
+                installation_id: 2,
+                ..
+            }
+        ));
+        assert!(!format!("{auth:?}").contains("not a key"));
+
+        config.github_auth = GithubAuthMode::Token;
+        assert!(matches!(
+            GithubAuth::from_config(&config),
+            GithubAuth::Token(_)
+        ));
+        config.github_auth = GithubAuthMode::Ambient;
+        assert!(matches!(
+            GithubAuth::from_config(&config),
+            GithubAuth::Ambient
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_github_auth_invalid_app_key() {
+        let auth = GithubAuth::App {
+            app_id: 1,
+            installation_id: 2,
+            private_key: "not a key".to_string(),
+        };
+        assert!(auth.client().is_err());
+    }
+}
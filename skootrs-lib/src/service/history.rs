@@ -0,0 +1,170 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Facet history is kept in the project's source next to the `.skootrs` state file. Each time a facet's
+//! content is replaced by an update the previous content is written as a gzip compressed JSON
+//! `FacetHistoryEntry` to `.skootrs-history/<facet type>/<version>.json.gz`.
+
+use std::{
+    fs,
+    io::{Read, Write},
+    path::Path,
+};
+
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use skootrs_model::skootrs::{
+    facet::{FacetHistoryEntry, SupportedFacetType},
+    InitializedSource, SkootError,
+};
+
+/// The directory in the source that facet history is kept in.
+pub const HISTORY_DIR: &str = ".skootrs-history";
+
+/// Returns the directory, relative to the root of the source, that the history of a facet type is kept in.
+#[must_use]
+pub fn history_path(facet_type: &SupportedFacetType) -> String {
+    format!("{HISTORY_DIR}/{facet_type}/")
+}
+
+/// Returns the name of the file a version of a facet's history is kept in.
+#[must_use]
+pub fn history_file_name(version: u32) -> String {
+    format!("{version}.json.gz")
+}
+
+/// Compresses a history entry.
+///
+/// # Errors
+///
+/// Returns an error if the entry can't be serialized or compressed.
+pub fn encode(entry: &FacetHistoryEntry) -> Result<Vec<u8>, SkootError> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&serde_json::to_vec(entry)?)?;
+    Ok(encoder.finish()?)
+}
+
+/// Decompresses a history entry.
+///
+/// # Errors
+///
+/// Returns an error if the entry can't be decompressed or deserialized.
+pub fn decode(bytes: &[u8]) -> Result<FacetHistoryEntry, SkootError> {
+    let mut json = Vec::new();
+    GzDecoder::new(bytes).read_to_end(&mut json)?;
+    Ok(serde_json::from_slice(&json)?)
+}
+
+/// Returns the latest version of a facet's history in the source, or `None` if it has no history.
+///
+/// # Errors
+///
+/// Returns an error if the history directory exists but can't be read.
+pub fn latest_version(
+    source: &InitializedSource,
+    facet_type: &SupportedFacetType,
+) -> Result<Option<u32>, SkootError> {
+    let dir = Path::new(&source.path).join(history_path(facet_type));
+    if !dir.exists() {
+        return Ok(None);
+    }
+    let mut latest = None;
+    for entry in fs::read_dir(dir)? {
+        let file_name = entry?.file_name();
+        let version = file_name
+            .to_str()
+            .and_then(|name| name.strip_suffix(".json.gz"))
+            .and_then(|version| version.parse::<u32>().ok());
+        latest = latest.max(version);
+    }
+    Ok(latest)
+}
+
+/// Reads a version of a facet's history from the source. Reads the latest version if `version` is `None`.
+///
+/// # Errors
+///
+/// Returns an error if the facet has no history or the version can't be read.
+pub fn read(
+    source: &InitializedSource,
+    facet_type: &SupportedFacetType,
+    version: Option<u32>,
+) -> Result<FacetHistoryEntry, SkootError> {
+    let version = match version {
+        Some(v) => v,
+        None => latest_version(source, facet_type)?
+            .ok_or_else(|| SkootError::NotFound(format!("Facet {facet_type} has no history")))?,
+    };
+    let path = Path::new(&source.path)
+        .join(history_path(facet_type))
+        .join(history_file_name(version));
+    let bytes = fs::read(&path).map_err(|e| {
+        SkootError::from(format!(
+            "Version {version} of facet {facet_type} not found: {e}"
+        ))
+    })?;
+    decode(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use skootrs_model::skootrs::facet::FacetDriftStatus;
+    use tempdir::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn test_write_and_read_history() {
+        let temp_dir = TempDir::new("test").unwrap();
+        let source = InitializedSource {
+            path: temp_dir.path().to_str().unwrap().to_string(),
+        };
+        assert_eq!(
+            latest_version(&source, &SupportedFacetType::Readme).unwrap(),
+            None
+        );
+
+        for version in 1..=2 {
+            let entry = FacetHistoryEntry {
+                facet_type: SupportedFacetType::Readme,
+                version,
+                status: FacetDriftStatus::Outdated,
+                previous_commit: "abc123".to_string(),
+                replaced_at: chrono::Utc::now(),
+                files: BTreeMap::from([("./README.md".to_string(), format!("v{version}"))]),
+            };
+            let dir = temp_dir
+                .path()
+                .join(history_path(&SupportedFacetType::Readme));
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(
+                dir.join(history_file_name(version)),
+                encode(&entry).unwrap(),
+            )
+            .unwrap();
+        }
+
+        assert_eq!(
+            latest_version(&source, &SupportedFacetType::Readme).unwrap(),
+            Some(2)
+        );
+        let latest = read(&source, &SupportedFacetType::Readme, None).unwrap();
+        assert_eq!(latest.files["./README.md"], "v2");
+        let first = read(&source, &SupportedFacetType::Readme, Some(1)).unwrap();
+        assert_eq!(first.files["./README.md"], "v1");
+        assert!(read(&source, &SupportedFacetType::Readme, Some(3)).is_err());
+    }
+}
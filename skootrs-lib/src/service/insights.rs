@@ -0,0 +1,210 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![allow(clippy::module_name_repetitions)]
+
+use chrono::{DateTime, Utc};
+use octocrab::Page;
+use serde::Deserialize;
+use tracing::{debug, info};
+
+use super::{github_auth::GithubAuth, pagination};
+
+use skootrs_model::skootrs::{
+    CommitSummary, InitializedGithubRepo, InitializedRepo, ProjectStats, SkootError,
+    WorkflowRunStats,
+};
+
+/// The number of days of workflow runs that are used for the workflow statistics.
+const WORKFLOW_RUN_WINDOW_DAYS: i64 = 30;
+
+/// The `InsightsService` trait provides an interface for gathering live statistics about a project's repo.
+/// This is used to gauge whether the controls Skootrs manages are actually being exercised, e.g. whether
+/// the workflows it sets up are running and passing.
+pub trait InsightsService {
+    /// Gets the live statistics for a project's repo.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the statistics can't be fetched from the repo host.
+    fn stats(
+        &self,
+        initialized_repo: &InitializedRepo,
+    ) -> impl std::future::Future<Output = Result<ProjectStats, SkootError>> + Send;
+}
+
+/// The `LocalInsightsService` struct provides an implementation of the `InsightsService` trait that calls the
+/// repo host APIs from the local machine.
+#[derive(Debug, Default)]
+pub struct LocalInsightsService {
+    /// The credential used for the Github API.
+    pub github_auth: GithubAuth,
+}
+
+impl InsightsService for LocalInsightsService {
+    async fn stats(&self, initialized_repo: &InitializedRepo) -> Result<ProjectStats, SkootError> {
+        match initialized_repo {
+            InitializedRepo::Github(g) => {
+                GithubInsightsHandler::stats(&self.github_auth.client()?, g).await
+            }
+            InitializedRepo::GenericGit(g) => Err(SkootError::from(format!(
+                "Stats aren't available for generic git remote {}",
+                g.remote_url
+            ))),
+            InitializedRepo::Bitbucket(b) => Err(SkootError::from(format!(
+                "Stats aren't available for Bitbucket repo {}",
+                b.full_url()
+            ))),
+        }
+    }
+}
+
+/// The `GithubInsightsHandler` struct represents a handler for gathering statistics from the Github API.
+struct GithubInsightsHandler;
+
+impl GithubInsightsHandler {
+    async fn stats(
+        client: &octocrab::Octocrab,
+        repo: &InitializedGithubRepo,
+    ) -> Result<ProjectStats, SkootError> {
+        let owner = repo.organization.get_name();
+        let name = &repo.name;
+
+        info!("Fetching stats for {owner}/{name}");
+        let last_commit = Self::last_commit(client, &owner, name).await?;
+        let open_security_alerts = Self::open_security_alerts(client, &owner, name).await;
+        let since = Utc::now() - chrono::Duration::days(WORKFLOW_RUN_WINDOW_DAYS);
+        let workflow_runs = Self::workflow_runs(client, &owner, name, since).await?;
+
+        Ok(ProjectStats {
+            last_commit,
+            open_security_alerts,
+            workflow_runs,
+        })
+    }
+
+    async fn last_commit(
+        client: &octocrab::Octocrab,
+        owner: &str,
+        name: &str,
+    ) -> Result<Option<CommitSummary>, SkootError> {
+        let commits: Vec<GithubCommit> = client
+            .get(
+                format!("/repos/{owner}/{name}/commits"),
+                Some(&[("per_page", "1")]),
+            )
+            .await
+            .map_err(SkootError::github)?;
+        Ok(commits.into_iter().next().map(|c| CommitSummary {
+            sha: c.sha,
+            message: c.commit.message,
+            date: c.commit.committer.and_then(|committer| committer.date),
+        }))
+    }
+
+    /// Counts the open Dependabot and code scanning alerts across every page of them. Either of those APIs
+    /// return an error if the feature isn't enabled for the repo, in which case the count is unknown rather
+    /// than a hard failure.
+    async fn open_security_alerts(
+        client: &octocrab::Octocrab,
+        owner: &str,
+        name: &str,
+    ) -> Option<u64> {
+        let mut total = 0;
+        for alerts_endpoint in ["dependabot/alerts", "code-scanning/alerts"] {
+            let first_page: Result<Page<serde_json::Value>, _> = client
+                .get(
+                    format!("/repos/{owner}/{name}/{alerts_endpoint}"),
+                    Some(&[("state", "open"), ("per_page", "100")]),
+                )
+                .await;
+            let alerts = match first_page {
+                Ok(first_page) => pagination::all_pages(client, first_page).await,
+                Err(e) => Err(SkootError::github(e)),
+            };
+            match alerts {
+                Ok(alerts) => total += alerts.len() as u64,
+                Err(e) => {
+                    debug!("Failed to fetch {alerts_endpoint} for {owner}/{name}: {e}");
+                    return None;
+                }
+            }
+        }
+        Some(total)
+    }
+
+    async fn workflow_runs(
+        client: &octocrab::Octocrab,
+        owner: &str,
+        name: &str,
+        since: DateTime<Utc>,
+    ) -> Result<WorkflowRunStats, SkootError> {
+        let created = format!(">={}", since.format("%Y-%m-%d"));
+        let first_page: Page<GithubWorkflowRun> = client
+            .get(
+                format!("/repos/{owner}/{name}/actions/runs"),
+                Some(&[("created", created.as_str()), ("per_page", "100")]),
+            )
+            .await
+            .map_err(SkootError::github)?;
+        let runs = pagination::all_pages(client, first_page).await?;
+        Ok(WorkflowRunStats::from_conclusions(
+            runs.iter().map(|r| r.conclusion.as_deref()),
+        ))
+    }
+}
+
+#[derive(Deserialize)]
+struct GithubCommit {
+    sha: String,
+    commit: GithubCommitDetails,
+}
+
+#[derive(Deserialize)]
+struct GithubCommitDetails {
+    message: String,
+    committer: Option<GithubCommitAuthor>,
+}
+
+#[derive(Deserialize)]
+struct GithubCommitAuthor {
+    date: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize)]
+struct GithubWorkflowRun {
+    conclusion: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_workflow_runs() {
+        let runs: Vec<GithubWorkflowRun> = serde_json::from_str(
+            r#"[
+                { "id": 1, "conclusion": "success" },
+                { "id": 2, "conclusion": "failure" },
+                { "id": 3, "conclusion": null }
+            ]"#,
+        )
+        .unwrap();
+        let stats =
+            WorkflowRunStats::from_conclusions(runs.iter().map(|r| r.conclusion.as_deref()));
+        assert_eq!(stats.completed, 2);
+        assert_eq!(stats.successful, 1);
+    }
+}
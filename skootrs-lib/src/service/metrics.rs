@@ -0,0 +1,342 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Counters and histograms of what Skootrs does, so operators can monitor deployments. The services record
+//! into a process wide registry as they work, and the daemon serves it at `/metrics` in the Prometheus text
+//! exposition format.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{Mutex, PoisonError},
+    time::Duration,
+};
+
+/// The upper bounds, in seconds, of the buckets of every histogram. Creating a project takes anywhere from a
+/// second to a few minutes, while a Github API call takes well under a second.
+const BUCKETS: [f64; 12] = [
+    0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0,
+];
+
+/// The registry the services record into.
+static METRICS: Metrics = Metrics::new();
+
+/// Returns the registry the services record into.
+#[must_use]
+pub fn global() -> &'static Metrics {
+    &METRICS
+}
+
+/// The counters Skootrs keeps.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Counter {
+    /// Projects created successfully.
+    ProjectsCreated,
+    /// Projects that failed to be created.
+    ProjectCreationFailures,
+    /// Facets created, by their `facet_type`.
+    FacetsCreated,
+    /// Github API calls made, including retries.
+    GithubApiCalls,
+    /// Github API calls that failed, including ones that were retried.
+    GithubApiFailures,
+    /// Projects reconciled in the background, by their `outcome`.
+    Reconciliations,
+}
+
+/// The histograms Skootrs keeps. Every histogram is of a duration in seconds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Histogram {
+    /// How long creating a project took, whether it succeeded or not.
+    ProjectCreationDuration,
+    /// How long a Github API call took.
+    GithubApiCallDuration,
+    /// How long reconciling a project in the background took.
+    ReconciliationDuration,
+}
+
+impl Counter {
+    const ALL: [Self; 6] = [
+        Self::ProjectsCreated,
+        Self::ProjectCreationFailures,
+        Self::FacetsCreated,
+        Self::GithubApiCalls,
+        Self::GithubApiFailures,
+        Self::Reconciliations,
+    ];
+
+    const fn name(self) -> &'static str {
+        match self {
+            Self::ProjectsCreated => "skootrs_projects_created_total",
+            Self::ProjectCreationFailures => "skootrs_project_creation_failures_total",
+            Self::FacetsCreated => "skootrs_facets_created_total",
+            Self::GithubApiCalls => "skootrs_github_api_calls_total",
+            Self::GithubApiFailures => "skootrs_github_api_failures_total",
+            Self::Reconciliations => "skootrs_reconciliations_total",
+        }
+    }
+
+    /// Whether the counter's series are recorded under labels, so there's no series to read as zero before
+    /// anything was recorded.
+    const fn labelled(self) -> bool {
+        matches!(self, Self::FacetsCreated | Self::Reconciliations)
+    }
+
+    const fn help(self) -> &'static str {
+        match self {
+            Self::ProjectsCreated => "Projects created successfully.",
+            Self::ProjectCreationFailures => "Projects that failed to be created.",
+            Self::FacetsCreated => "Facets created, by facet type.",
+            Self::GithubApiCalls => "Github API calls made, including retries.",
+            Self::GithubApiFailures => {
+                "Github API calls that failed, including ones that were retried."
+            }
+            Self::Reconciliations => "Projects reconciled in the background, by outcome.",
+        }
+    }
+}
+
+impl Histogram {
+    const ALL: [Self; 3] = [
+        Self::ProjectCreationDuration,
+        Self::GithubApiCallDuration,
+        Self::ReconciliationDuration,
+    ];
+
+    const fn name(self) -> &'static str {
+        match self {
+            Self::ProjectCreationDuration => "skootrs_project_creation_duration_seconds",
+            Self::GithubApiCallDuration => "skootrs_github_api_call_duration_seconds",
+            Self::ReconciliationDuration => "skootrs_reconciliation_duration_seconds",
+        }
+    }
+
+    const fn help(self) -> &'static str {
+        match self {
+            Self::ProjectCreationDuration => "How long creating a project took.",
+            Self::GithubApiCallDuration => "How long a Github API call took.",
+            Self::ReconciliationDuration => {
+                "How long reconciling a project in the background took."
+            }
+        }
+    }
+}
+
+/// The label names and values a series is recorded under, in order.
+type Labels = Vec<(&'static str, String)>;
+
+#[derive(Default)]
+struct HistogramSeries {
+    /// How many observations fell into each of `BUCKETS`, not counting the smaller buckets.
+    buckets: [u64; BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+/// A registry of counters and histograms.
+pub struct Metrics {
+    counters: Mutex<BTreeMap<(Counter, Labels), u64>>,
+    histograms: Mutex<BTreeMap<(Histogram, Labels), HistogramSeries>>,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metrics {
+    /// Returns an empty registry.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            counters: Mutex::new(BTreeMap::new()),
+            histograms: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Adds one to the counter's series with the labels.
+    pub fn increment(&self, counter: Counter, labels: &[(&'static str, &str)]) {
+        let mut counters = self.counters.lock().unwrap_or_else(PoisonError::into_inner);
+        *counters.entry((counter, owned(labels))).or_default() += 1;
+    }
+
+    /// Records a duration in the histogram's series with the labels.
+    pub fn observe(
+        &self,
+        histogram: Histogram,
+        labels: &[(&'static str, &str)],
+        duration: Duration,
+    ) {
+        let seconds = duration.as_secs_f64();
+        let mut histograms = self
+            .histograms
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let series = histograms.entry((histogram, owned(labels))).or_default();
+        if let Some(bucket) = BUCKETS.iter().position(|bound| seconds <= *bound) {
+            series.buckets[bucket] += 1;
+        }
+        series.count += 1;
+        series.sum += seconds;
+    }
+
+    /// Renders every metric in the Prometheus text exposition format. Metrics without labels are rendered
+    /// even before anything was recorded, so they read as zero rather than missing.
+    #[must_use]
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let counters = self.counters.lock().unwrap_or_else(PoisonError::into_inner);
+        for counter in Counter::ALL {
+            let name = counter.name();
+            let _ = writeln!(
+                out,
+                "# HELP {name} {}\n# TYPE {name} counter",
+                counter.help()
+            );
+            let mut series = counters
+                .iter()
+                .filter(|((c, _), _)| *c == counter)
+                .peekable();
+            if series.peek().is_none() && !counter.labelled() {
+                let _ = writeln!(out, "{name} 0");
+            }
+            for ((_, labels), value) in series {
+                let _ = writeln!(out, "{name}{} {value}", render_labels(labels, None));
+            }
+        }
+        drop(counters);
+
+        let histograms = self
+            .histograms
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        for histogram in Histogram::ALL {
+            let name = histogram.name();
+            let _ = writeln!(
+                out,
+                "# HELP {name} {}\n# TYPE {name} histogram",
+                histogram.help()
+            );
+            for ((_, labels), series) in histograms.iter().filter(|((h, _), _)| *h == histogram) {
+                let mut cumulative = 0;
+                for (bound, count) in BUCKETS.iter().zip(series.buckets) {
+                    cumulative += count;
+                    let le = bound.to_string();
+                    let _ = writeln!(
+                        out,
+                        "{name}_bucket{} {cumulative}",
+                        render_labels(labels, Some(&le))
+                    );
+                }
+                let _ = writeln!(
+                    out,
+                    "{name}_bucket{} {}",
+                    render_labels(labels, Some("+Inf")),
+                    series.count
+                );
+                let _ = writeln!(
+                    out,
+                    "{name}_sum{} {}",
+                    render_labels(labels, None),
+                    series.sum
+                );
+                let _ = writeln!(
+                    out,
+                    "{name}_count{} {}",
+                    render_labels(labels, None),
+                    series.count
+                );
+            }
+        }
+        out
+    }
+}
+
+fn owned(labels: &[(&'static str, &str)]) -> Labels {
+    labels
+        .iter()
+        .map(|(name, value)| (*name, (*value).to_string()))
+        .collect()
+}
+
+/// Renders labels like `{facet_type="Readme"}`, with the histogram bucket's `le` label last if there is one.
+fn render_labels(labels: &Labels, le: Option<&str>) -> String {
+    let labels = labels
+        .iter()
+        .map(|(name, value)| (*name, value.as_str()))
+        .chain(le.map(|le| ("le", le)))
+        .map(|(name, value)| {
+            let value = value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+            format!("{name}=\"{value}\"")
+        })
+        .collect::<Vec<_>>();
+    if labels.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", labels.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let metrics = Metrics::new();
+        metrics.increment(Counter::ProjectsCreated, &[]);
+        metrics.increment(Counter::FacetsCreated, &[("facet_type", "Readme")]);
+        metrics.increment(Counter::FacetsCreated, &[("facet_type", "Readme")]);
+        metrics.increment(Counter::FacetsCreated, &[("facet_type", "Say \"hi\"")]);
+        metrics.observe(
+            Histogram::ProjectCreationDuration,
+            &[],
+            Duration::from_millis(700),
+        );
+        metrics.observe(
+            Histogram::ProjectCreationDuration,
+            &[],
+            Duration::from_secs(400),
+        );
+        let rendered = metrics.render();
+
+        assert!(rendered.contains(
+            "# TYPE skootrs_projects_created_total counter\nskootrs_projects_created_total 1\n"
+        ));
+        assert!(rendered.contains("skootrs_project_creation_failures_total 0\n"));
+        assert!(rendered.contains("skootrs_facets_created_total{facet_type=\"Readme\"} 2\n"));
+        assert!(
+            rendered.contains("skootrs_facets_created_total{facet_type=\"Say \\\"hi\\\"\"} 1\n")
+        );
+        // Labelled counters aren't rendered until something is recorded.
+        assert!(!rendered.contains("\nskootrs_reconciliations_total"));
+        assert!(
+            rendered.contains("skootrs_project_creation_duration_seconds_bucket{le=\"0.5\"} 0\n")
+        );
+        assert!(rendered.contains("skootrs_project_creation_duration_seconds_bucket{le=\"1\"} 1\n"));
+        assert!(
+            rendered.contains("skootrs_project_creation_duration_seconds_bucket{le=\"300\"} 1\n")
+        );
+        assert!(
+            rendered.contains("skootrs_project_creation_duration_seconds_bucket{le=\"+Inf\"} 2\n")
+        );
+        assert!(rendered.contains("skootrs_project_creation_duration_seconds_sum 400.7\n"));
+        assert!(rendered.contains("skootrs_project_creation_duration_seconds_count 2\n"));
+    }
+}
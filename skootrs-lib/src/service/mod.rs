@@ -13,9 +13,31 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod audit_log;
+pub mod best_practices;
+pub mod bitbucket;
+pub mod cancellation;
+pub mod command;
+pub mod config;
 pub mod ecosystem;
+pub mod events;
 pub mod facet;
+pub mod git2_source;
+pub mod github_auth;
+pub mod history;
+pub mod insights;
+pub mod metrics;
 pub mod output;
+pub mod pagination;
+pub mod pinning;
+pub mod policy;
+pub mod profile;
 pub mod project;
+pub mod read_only;
 pub mod repo;
+pub mod repo_api;
+pub mod retry;
+pub mod scorecard;
+pub mod signing;
 pub mod source;
+pub mod template;
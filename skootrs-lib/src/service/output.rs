@@ -15,11 +15,72 @@
 
 #![allow(clippy::module_name_repetitions)]
 
-use octocrab::models::repos::{Asset, Release};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{Cursor, Read, Write},
+    path::Path,
+    process::Command,
+    sync::Arc,
+};
+
+use base64::Engine;
+use octocrab::{
+    models::{
+        repos::{Asset, Release},
+        workflows::WorkflowListArtifact,
+        RunId,
+    },
+    params::actions::ArchiveFormat,
+    Octocrab, Page,
+};
+use serde::Deserialize;
+use sha2::Digest;
 use skootrs_model::skootrs::{
-    label::Label, ProjectOutput, ProjectOutputGetParams, ProjectOutputReference, ProjectOutputType,
-    ProjectOutputsListParams, SkootError,
+    attestation::{
+        AttestationVerification, AttestationVerifyParams, PredicateType, VerifiedEnvelope,
+    },
+    facet::InitializedFacet,
+    label::Label,
+    publishing::PackageCoordinates,
+    signing::CERTIFICATE_OIDC_ISSUER,
+    DownloadedOutput, InitializedProject, ProjectOutput, ProjectOutputGetParams,
+    ProjectOutputReference, ProjectOutputType, ProjectOutputsDownloadParams,
+    ProjectOutputsListParams, ProvenanceVerificationResult, SkootError, WorkflowRunParam,
+    PROVENANCE_VERIFICATION_FILE,
+};
+use tracing::{debug, info, warn};
+
+use super::{
+    command::{CommandError, CommandRunner},
+    pagination,
 };
+
+/// The glob patterns of the release assets that are detached signatures of other assets, the same suffixes
+/// the Scorecard Signed-Releases check looks for.
+const SIGNATURE_PATTERNS: [&str; 6] = [
+    "*.sig",
+    "*.sign",
+    "*.asc",
+    "*.minisig",
+    "*.sigstore",
+    "*.sigstore.json",
+];
+
+/// The glob patterns of the release assets that are SBOMs, following
+/// <https://github.com/ossf/sbom-everywhere/blob/main/reference/sbom_naming.md>.
+const SBOM_PATTERNS: [&str; 4] = ["*.spdx", "*.spdx.*", "*.cdx", "*.cdx.*"];
+
+/// The glob patterns of the release assets that are in-toto attestations in DSSE envelopes, other than the
+/// SLSA provenance of the slsa-github-generator.
+const ATTESTATION_PATTERNS: [&str; 4] = ["*.intoto", "*.intoto.*", "*.dsse", "*.dsse.json"];
+
+/// The number of release assets or releases Github is asked for per page, the most it allows.
+const PER_PAGE: u8 = 100;
+
+/// The payload type of the DSSE envelopes in-toto attestations are wrapped in.
+const IN_TOTO_PAYLOAD_TYPE: &str = "application/vnd.in-toto+json";
+
 pub trait OutputService {
     fn list(
         &self,
@@ -30,31 +91,114 @@ pub trait OutputService {
         &self,
         _params: ProjectOutputGetParams,
     ) -> impl std::future::Future<Output = Result<ProjectOutput, SkootError>> + Send;
+
+    /// Downloads one or all of the outputs of a release to files, and checks each of them against the size
+    /// and checksum the release gives for it. The artifacts of a workflow run are downloaded unchecked.
+    fn download(
+        &self,
+        params: ProjectOutputsDownloadParams,
+    ) -> impl std::future::Future<Output = Result<Vec<DownloadedOutput>, SkootError>> + Send;
+
+    /// Verifies that every DSSE envelope of an in-toto attestation attached to a release is signed by a
+    /// workflow of the project's repo, and that the subjects of its statements match the release.
+    fn verify(
+        &self,
+        params: AttestationVerifyParams,
+    ) -> impl std::future::Future<Output = Result<AttestationVerification, SkootError>> + Send;
 }
 
 pub struct LocalOutputService;
 
 impl OutputService for LocalOutputService {
-    fn list(
+    async fn list(
         &self,
         params: ProjectOutputsListParams,
-    ) -> impl std::future::Future<Output = Result<Vec<ProjectOutputReference>, SkootError>> + Send
-    {
-        match params.initialized_project.repo {
-            skootrs_model::skootrs::InitializedRepo::Github(g) => {
+    ) -> Result<Vec<ProjectOutputReference>, SkootError> {
+        let packages = published_packages(&params.initialized_project)
+            .map(|(package, labels)| ProjectOutputReference {
+                output_type: ProjectOutputType::Package,
+                name: package.locator(),
+                labels,
+            })
+            .collect::<Vec<_>>();
+        match (params.initialized_project.repo, params.workflow_run) {
+            (skootrs_model::skootrs::InitializedRepo::Github(g), Some(run)) => {
+                GithubArtifactHandler {
+                    client: octocrab::instance(),
+                }
+                .outputs_list(GithubWorkflowRunParams {
+                    owner: g.organization.get_name(),
+                    repo: g.name,
+                    run,
+                })
+                .await
+            }
+            (skootrs_model::skootrs::InitializedRepo::Github(g), None) => {
                 let github_params = GithubReleaseParams {
                     owner: g.organization.get_name(),
                     repo: g.name,
                     tag: params.release.tag(),
                 };
-                GithubReleaseHandler::outputs_list(github_params)
+                let mut references = GithubReleaseHandler::outputs_list(github_params).await?;
+                references.extend(packages);
+                Ok(references)
+            }
+            // Published packages are known from the project's state even without a release API.
+            (
+                skootrs_model::skootrs::InitializedRepo::GenericGit(_)
+                | skootrs_model::skootrs::InitializedRepo::Bitbucket(_),
+                _,
+            ) if !packages.is_empty() => Ok(packages),
+            (skootrs_model::skootrs::InitializedRepo::GenericGit(g), _) => {
+                Err(SkootError::from(format!(
+                    "Release outputs aren't available for generic git remote {}",
+                    g.remote_url
+                )))
+            }
+            (skootrs_model::skootrs::InitializedRepo::Bitbucket(b), _) => {
+                Err(SkootError::from(format!(
+                    "Release outputs aren't available for Bitbucket repo {}",
+                    b.full_url()
+                )))
             }
         }
     }
 
     async fn get(&self, params: ProjectOutputGetParams) -> Result<ProjectOutput, SkootError> {
-        match params.initialized_project.repo {
-            skootrs_model::skootrs::InitializedRepo::Github(g) => {
+        if matches!(params.project_output_type, ProjectOutputType::Package) {
+            let (package, labels) = published_packages(&params.initialized_project)
+                .find(|(package, _)| package.locator() == params.project_output)
+                .ok_or_else(|| {
+                    format!(
+                        "Package {} is not published by the project",
+                        params.project_output
+                    )
+                })?;
+            return Ok(ProjectOutput {
+                reference: ProjectOutputReference {
+                    output_type: ProjectOutputType::Package,
+                    name: package.locator(),
+                    labels,
+                },
+                output: serde_json::to_string_pretty(package)?,
+            });
+        }
+        match (params.initialized_project.repo, params.workflow_run) {
+            (skootrs_model::skootrs::InitializedRepo::Github(g), Some(run)) => {
+                GithubArtifactHandler {
+                    client: octocrab::instance(),
+                }
+                .get_output(
+                    GithubWorkflowRunParams {
+                        owner: g.organization.get_name(),
+                        repo: g.name,
+                        run,
+                    },
+                    &params.project_output,
+                )
+                .await
+            }
+            (skootrs_model::skootrs::InitializedRepo::Github(g), None) => {
                 let github_params = GithubOutputGetParams {
                     release: GithubReleaseHandler::get_release(GithubReleaseParams {
                         owner: g.organization.get_name(),
@@ -66,68 +210,188 @@ impl OutputService for LocalOutputService {
                 };
                 GithubReleaseHandler::get_output(github_params).await
             }
+            (skootrs_model::skootrs::InitializedRepo::GenericGit(g), _) => {
+                Err(SkootError::from(format!(
+                    "Release outputs aren't available for generic git remote {}",
+                    g.remote_url
+                )))
+            }
+            (skootrs_model::skootrs::InitializedRepo::Bitbucket(b), _) => {
+                Err(SkootError::from(format!(
+                    "Release outputs aren't available for Bitbucket repo {}",
+                    b.full_url()
+                )))
+            }
+        }
+    }
+
+    async fn download(
+        &self,
+        params: ProjectOutputsDownloadParams,
+    ) -> Result<Vec<DownloadedOutput>, SkootError> {
+        match (params.initialized_project.repo, params.workflow_run) {
+            (skootrs_model::skootrs::InitializedRepo::Github(g), Some(run)) => {
+                GithubArtifactHandler {
+                    client: octocrab::instance(),
+                }
+                .download(
+                    GithubWorkflowRunParams {
+                        owner: g.organization.get_name(),
+                        repo: g.name,
+                        run,
+                    },
+                    params.project_output,
+                    &params.dir,
+                )
+                .await
+            }
+            (skootrs_model::skootrs::InitializedRepo::Github(g), None) => {
+                let release = GithubReleaseHandler::get_release(GithubReleaseParams {
+                    owner: g.organization.get_name(),
+                    repo: g.name.clone(),
+                    tag: params.release.tag(),
+                })
+                .await?;
+                GithubReleaseHandler::download(&release, params.project_output, &params.dir).await
+            }
+            (skootrs_model::skootrs::InitializedRepo::GenericGit(g), _) => {
+                Err(SkootError::from(format!(
+                    "Release outputs aren't available for generic git remote {}",
+                    g.remote_url
+                )))
+            }
+            (skootrs_model::skootrs::InitializedRepo::Bitbucket(b), _) => {
+                Err(SkootError::from(format!(
+                    "Release outputs aren't available for Bitbucket repo {}",
+                    b.full_url()
+                )))
+            }
+        }
+    }
+
+    async fn verify(
+        &self,
+        params: AttestationVerifyParams,
+    ) -> Result<AttestationVerification, SkootError> {
+        match &params.initialized_project.repo {
+            skootrs_model::skootrs::InitializedRepo::Github(g) => {
+                let release = GithubReleaseHandler::get_release(GithubReleaseParams {
+                    owner: g.organization.get_name(),
+                    repo: g.name.clone(),
+                    tag: params.release.tag(),
+                })
+                .await?;
+                GithubReleaseHandler::verify_envelopes(
+                    &release,
+                    &params.project_output,
+                    &format!("{}/{}", g.organization.get_name(), g.name),
+                )
+                .await
+            }
+            // Only Github Actions signs attestations with an identity tied to the repo.
+            repo => Err(SkootError::from(format!(
+                "Attestations can only be verified for Github repos, not {}",
+                repo.full_url()
+            ))),
         }
     }
 }
 
+/// Returns the packages recorded by the project's facets along with the labels of the facet publishing them.
+fn published_packages(
+    project: &InitializedProject,
+) -> impl Iterator<Item = (&PackageCoordinates, Vec<Label>)> {
+    project.facets.values().flat_map(|facet| match facet {
+        InitializedFacet::SourceBundle(s) => s
+            .packages
+            .iter()
+            .map(|package| (package, s.labels.clone()))
+            .collect(),
+        InitializedFacet::APIBundle(_) => vec![],
+    })
+}
+
 struct GithubReleaseHandler;
 impl GithubReleaseHandler {
     async fn outputs_list(
         params: GithubReleaseParams,
     ) -> Result<Vec<ProjectOutputReference>, SkootError> {
         let release = Self::get_release(params).await?;
-
-        let assets = release.assets;
-        let references = assets
-            .iter()
-            .map(|asset| ProjectOutputReference {
+        let mut references = Vec::with_capacity(release.assets.len());
+        for asset in &release.assets {
+            let (mut output_type, mut labels) = classify_asset(&asset.name);
+            // What an attestation attests to is only known from its statement.
+            if output_type == ProjectOutputType::InToto {
+                match Self::download_text(asset).await {
+                    Ok(content) => (output_type, labels) = classify_attestation(&content),
+                    Err(e) => warn!(
+                        "Failed to download {}, skipping its predicate: {e}",
+                        asset.name
+                    ),
+                }
+            }
+            references.push(ProjectOutputReference {
                 name: asset.name.clone(),
-                output_type: Self::get_type(asset),
-                labels: Self::get_labels(asset),
-            })
-            .collect();
+                output_type,
+                labels,
+            });
+        }
 
         Ok(references)
     }
 
-    async fn get_release(params: GithubReleaseParams) -> Result<Release, octocrab::Error> {
-        match params.tag {
-            Some(tag) => {
-                octocrab::instance()
-                    .repos(params.owner, params.repo)
-                    .releases()
-                    .get_by_tag(tag.as_str())
-                    .await
-            }
-            None => {
-                octocrab::instance()
-                    .repos(params.owner, params.repo)
-                    .releases()
-                    .get_latest()
-                    .await
-            }
-        }
-    }
-
-    fn get_type(asset: &Asset) -> ProjectOutputType {
-        // TODO: This matching probably isn't GitHub specific and can live somewhere more generalized.
-        match asset.url {
-            // Follows: https://github.com/ossf/sbom-everywhere/blob/main/reference/sbom_naming.md
-            _ if asset.name.contains(".spdx.") => ProjectOutputType::SBOM,
-            _ if asset.name.contains(".cdx.") => ProjectOutputType::SBOM,
-            _ if asset.name.contains(".intoto.") => ProjectOutputType::InToto,
-            // TODO: Add more types
-            _ => ProjectOutputType::Unknown("Unknown".to_string()),
-        }
+    /// Gets the release with the given tag, or the latest release, along with all of its assets. A release
+    /// embeds only its first page of assets, so the rest are listed page by page. Draft releases can't be got
+    /// by their tag, so if there's no published release with the tag the releases are searched for it.
+    async fn get_release(params: GithubReleaseParams) -> Result<Release, SkootError> {
+        let client = octocrab::instance();
+        let repo = client.repos(&params.owner, &params.repo);
+        let releases = repo.releases();
+        let release = match &params.tag {
+            Some(tag) => match releases.get_by_tag(tag.as_str()).await {
+                Ok(release) => release,
+                Err(octocrab::Error::GitHub { source, .. }) if source.message == "Not Found" => {
+                    debug!("No published release with tag {tag}, searching all releases");
+                    let first_page = releases
+                        .list()
+                        .per_page(PER_PAGE)
+                        .send()
+                        .await
+                        .map_err(SkootError::github)?;
+                    pagination::all_pages(&client, first_page)
+                        .await?
+                        .into_iter()
+                        .find(|release| &release.tag_name == tag)
+                        .ok_or_else(|| {
+                            SkootError::NotFound(format!(
+                                "There's no release with tag {tag} in {}/{}",
+                                params.owner, params.repo
+                            ))
+                        })?
+                }
+                Err(e) => return Err(SkootError::github(e)),
+            },
+            None => releases.get_latest().await.map_err(SkootError::github)?,
+        };
+        let assets = Self::list_assets(&client, &params, &release).await?;
+        Ok(Release { assets, ..release })
     }
 
-    fn get_labels(asset: &Asset) -> Vec<Label> {
-        match asset.url {
-            _ if asset.name.contains(".spdx.") => vec![Label::S2C2FAUD4],
-            _ if asset.name.contains(".cdx.") => vec![Label::S2C2FAUD4],
-            _ if asset.name.contains(".intoto.") => vec![Label::SLSABuildLevel3],
-            _ => vec![],
-        }
+    /// Lists every asset of a release.
+    async fn list_assets(
+        client: &Octocrab,
+        params: &GithubReleaseParams,
+        release: &Release,
+    ) -> Result<Vec<Asset>, SkootError> {
+        let route = format!(
+            "/repos/{}/{}/releases/{}/assets",
+            params.owner, params.repo, release.id
+        );
+        let first_page: Page<Asset> = client
+            .get(route, Some(&[("per_page", PER_PAGE)]))
+            .await
+            .map_err(SkootError::github)?;
+        pagination::all_pages(client, first_page).await
     }
 
     async fn get_output(params: GithubOutputGetParams) -> Result<ProjectOutput, SkootError> {
@@ -136,24 +400,802 @@ impl GithubReleaseHandler {
             .assets
             .iter()
             .find(|a| a.name == params.name)
-            .ok_or("Asset not found".to_string())?;
+            .ok_or_else(|| SkootError::NotFound("Asset not found".to_string()))?;
 
         // TODO: Figure out how to support assets in private repos
         let content = reqwest::get(asset.browser_download_url.clone())
             .await
             .map_err(|e| e.to_string())?
             .text()
-            .await?;
+            .await
+            .map_err(SkootError::github)?;
+
+        let (mut output_type, mut labels) = classify_asset(&asset.name);
+        if output_type == ProjectOutputType::InToto {
+            (output_type, labels) = classify_attestation(&content);
+        }
+        let output = match output_type {
+            // The verification result is parsed so a malformed result isn't mistaken for a verified release.
+            ProjectOutputType::ProvenanceVerification => serde_json::to_string_pretty(
+                &serde_json::from_str::<ProvenanceVerificationResult>(&content)?,
+            )?,
+            // An attestation whose subjects don't match the release isn't returned.
+            ProjectOutputType::SLSAAttestation => {
+                Self::verify_attestation(&params.release, &content).await?;
+                info!(
+                    "Verified the subjects of {} against the release",
+                    asset.name
+                );
+                serde_json::to_string_pretty(&content)?
+            }
+            _ => serde_json::to_string_pretty(&content)?,
+        };
 
         Ok(ProjectOutput {
             reference: ProjectOutputReference {
                 name: asset.name.clone(),
-                output_type: Self::get_type(asset),
-                labels: Self::get_labels(asset),
+                output_type,
+                labels,
+            },
+            output,
+        })
+    }
+
+    /// Checks that every subject of an attestation is an artifact of the release with the digest the
+    /// attestation gives for it. The signatures of the attestation aren't checked here, that's what the
+    /// `SLSAVerification` facet runs slsa-verifier in the project's CI for.
+    async fn verify_attestation(release: &Release, attestation: &str) -> Result<(), SkootError> {
+        let subjects = attestation_subjects(attestation)?;
+        if subjects.is_empty() {
+            return Err("The attestation has no subjects".into());
+        }
+        for subject in subjects {
+            let asset = release
+                .assets
+                .iter()
+                .find(|a| a.name == subject.name)
+                .ok_or_else(|| {
+                    format!(
+                        "The attested artifact {} isn't in release {}",
+                        subject.name, release.tag_name
+                    )
+                })?;
+            let artifact = reqwest::get(asset.browser_download_url.clone())
+                .await
+                .map_err(SkootError::github)?
+                .error_for_status()
+                .map_err(SkootError::github)?
+                .bytes()
+                .await
+                .map_err(SkootError::github)?;
+            subject.verify(&artifact)?;
+        }
+        Ok(())
+    }
+
+    /// Verifies each DSSE envelope of an attestation with `cosign verify-blob-attestation`, which checks that
+    /// it's signed by a Github Actions workflow running in the repo and logged to Rekor, and checks the
+    /// subjects of its statement that are release assets against their digests. Subjects that aren't release
+    /// assets, e.g. images, are left to cosign.
+    async fn verify_envelopes(
+        release: &Release,
+        name: &str,
+        repository: &str,
+    ) -> Result<AttestationVerification, SkootError> {
+        let asset = release
+            .assets
+            .iter()
+            .find(|a| a.name == name)
+            .ok_or_else(|| {
+                SkootError::NotFound(format!(
+                    "Output {name} isn't in release {}",
+                    release.tag_name
+                ))
+            })?;
+        let envelopes = envelopes(&Self::download_text(asset).await?)?;
+        // cosign verifies files, so each envelope is written to a directory of its own.
+        let dir = std::env::temp_dir().join(format!(
+            "skootrs-attestation-{}-{}",
+            std::process::id(),
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+        let mut verified_envelopes = Vec::with_capacity(envelopes.len());
+        for (index, envelope) in envelopes.iter().enumerate() {
+            let result =
+                Self::verify_envelope(release, envelope, repository, &dir.join(index.to_string()))
+                    .await;
+            verified_envelopes.push(result);
+        }
+        // The envelopes are only cleaned up on a best effort basis, they're public anyway.
+        let _ = std::fs::remove_dir_all(&dir);
+        let verified_envelopes = verified_envelopes
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(AttestationVerification {
+            name: name.to_string(),
+            identity: format!("https://github.com/{repository}"),
+            verified: !verified_envelopes.is_empty()
+                && verified_envelopes.iter().all(|envelope| envelope.verified),
+            envelopes: verified_envelopes,
+        })
+    }
+
+    async fn verify_envelope(
+        release: &Release,
+        envelope: &AttestationEnvelope,
+        repository: &str,
+        path: &Path,
+    ) -> Result<VerifiedEnvelope, SkootError> {
+        let statement = envelope.envelope.statement()?;
+        let mut verified = VerifiedEnvelope {
+            predicate_type: PredicateType::from_uri(&statement.predicate_type),
+            subjects: statement
+                .subject
+                .iter()
+                .map(|subject| subject.name.clone())
+                .collect(),
+            verified: false,
+            reason: None,
+        };
+        for subject in &statement.subject {
+            let Some(asset) = release.assets.iter().find(|a| a.name == subject.name) else {
+                continue;
+            };
+            let artifact = reqwest::get(asset.browser_download_url.clone())
+                .await
+                .map_err(SkootError::github)?
+                .error_for_status()
+                .map_err(SkootError::github)?
+                .bytes()
+                .await
+                .map_err(SkootError::github)?;
+            if let Err(e) = subject.verify(&artifact) {
+                verified.reason = Some(e.to_string());
+                return Ok(verified);
+            }
+        }
+
+        std::fs::write(path, envelope.raw.to_string())?;
+        let mut command = Command::new("cosign");
+        command
+            .arg("verify-blob-attestation")
+            .args(["--type", statement.predicate_type.as_str()])
+            .arg("--check-claims=false")
+            .args(["--certificate-oidc-issuer", CERTIFICATE_OIDC_ISSUER])
+            // Reusable workflows like the slsa-github-generator sign with their own identity, so the repo is
+            // checked by the workflow repository the certificate records rather than by the identity.
+            .args(["--certificate-identity-regexp", "^https://github\\.com/"])
+            .args(["--certificate-github-workflow-repository", repository]);
+        if envelope.bundled {
+            command.arg("--new-bundle-format").arg("--bundle").arg(path);
+        } else {
+            command.arg("--signature").arg(path);
+            if let Some(cert) = envelope.envelope.certificate() {
+                let cert_path = path.with_extension("pem");
+                std::fs::write(&cert_path, cert)?;
+                command.arg("--certificate").arg(cert_path);
+            }
+        }
+        match command.run_checked() {
+            Ok(_) => verified.verified = true,
+            Err(e) => match e.downcast_ref::<CommandError>() {
+                Some(error) => verified.reason = Some(error.stderr.clone()),
+                None => return Err(e),
+            },
+        }
+        Ok(verified)
+    }
+
+    /// Downloads the named asset of the release, or all of them, to files in `dir`.
+    async fn download(
+        release: &Release,
+        name: Option<String>,
+        dir: &str,
+    ) -> Result<Vec<DownloadedOutput>, SkootError> {
+        let assets = match name {
+            Some(name) => {
+                vec![release
+                    .assets
+                    .iter()
+                    .find(|a| a.name == name)
+                    .ok_or_else(|| {
+                        SkootError::NotFound(format!(
+                            "Output {name} isn't in release {}",
+                            release.tag_name
+                        ))
+                    })?]
+            }
+            None => release.assets.iter().collect(),
+        };
+        let checksums = Self::release_checksums(release).await;
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {dir}: {e}"))?;
+        let total = assets.len();
+        let mut downloaded = Vec::new();
+        for (index, asset) in assets.into_iter().enumerate() {
+            info!(
+                "Downloading {} ({}/{total}, {} bytes)",
+                asset.name,
+                index + 1,
+                asset.size
+            );
+            downloaded.push(Self::download_asset(asset, checksums.get(&asset.name), dir).await?);
+        }
+        Ok(downloaded)
+    }
+
+    /// Returns the SHA-256 checksums the release publishes for its assets, from checksum files like
+    /// `SHA256SUMS` and from the subjects of its SLSA attestations. A checksum file or attestation that can't
+    /// be read is skipped, the assets it covers are then only checked by size.
+    async fn release_checksums(release: &Release) -> HashMap<String, String> {
+        let mut checksums = HashMap::new();
+        for asset in &release.assets {
+            let is_attestation = asset.name.ends_with(".intoto.jsonl");
+            if !is_attestation && !is_checksum_file(&asset.name) {
+                continue;
+            }
+            let content = match Self::download_text(asset).await {
+                Ok(content) => content,
+                Err(e) => {
+                    warn!(
+                        "Failed to download {}, skipping its checksums: {e}",
+                        asset.name
+                    );
+                    continue;
+                }
+            };
+            if is_attestation {
+                match attestation_subjects(&content) {
+                    Ok(subjects) => checksums.extend(subjects.into_iter().filter_map(|subject| {
+                        subject
+                            .digest
+                            .get("sha256")
+                            .map(|digest| (subject.name.clone(), digest.to_lowercase()))
+                    })),
+                    Err(e) => warn!("Skipping the checksums of {}: {e}", asset.name),
+                }
+            } else {
+                checksums.extend(parse_checksums(&asset.name, &content));
+            }
+        }
+        checksums
+    }
+
+    async fn download_text(asset: &Asset) -> Result<String, SkootError> {
+        reqwest::get(asset.browser_download_url.as_str())
+            .await
+            .map_err(SkootError::github)?
+            .error_for_status()
+            .map_err(SkootError::github)?
+            .text()
+            .await
+            .map_err(SkootError::github)
+    }
+
+    /// Downloads an asset to a file named after it in `dir`. The asset is written to a `.part` file first, which
+    /// is only renamed once its size and checksum are verified, so a failed download doesn't leave a file
+    /// behind that looks complete.
+    async fn download_asset(
+        asset: &Asset,
+        expected_sha256: Option<&String>,
+        dir: &str,
+    ) -> Result<DownloadedOutput, SkootError> {
+        // Asset names come from the release, so they must not be able to point outside of `dir`.
+        if asset.name.contains(['/', '\\']) || asset.name == ".." {
+            return Err(format!("Refusing to download {} outside of {dir}", asset.name).into());
+        }
+        let path = Path::new(dir).join(&asset.name);
+        let partial = Path::new(dir).join(format!("{}.part", asset.name));
+        let result = Self::write_asset(asset, &partial)
+            .await
+            .and_then(|(size, sha256)| {
+                let expected_size = u64::try_from(asset.size).map_err(SkootError::other)?;
+                if size != expected_size {
+                    return Err(format!(
+                        "{} is {size} bytes instead of the {expected_size} bytes the release lists",
+                        asset.name
+                    )
+                    .into());
+                }
+                if let Some(expected) = expected_sha256 {
+                    if !expected.eq_ignore_ascii_case(&sha256) {
+                        return Err(format!(
+                            "{} doesn't match the release's checksum, its sha256 digest is {sha256} \
+                             instead of {expected}",
+                            asset.name
+                        )
+                        .into());
+                    }
+                }
+                std::fs::rename(&partial, &path)?;
+                Ok(DownloadedOutput {
+                    name: asset.name.clone(),
+                    path: path.display().to_string(),
+                    size,
+                    sha256,
+                    checksum_verified: expected_sha256.is_some(),
+                })
+            });
+        if result.is_err() {
+            // The partial file is only cleaned up on a best effort basis, the download already failed.
+            let _ = std::fs::remove_file(&partial);
+        }
+        result
+    }
+
+    /// Streams an asset to a file, logging the progress every 25%, and returns its size and SHA-256 digest.
+    async fn write_asset(asset: &Asset, path: &Path) -> Result<(u64, String), SkootError> {
+        let mut response = reqwest::get(asset.browser_download_url.clone())
+            .await
+            .map_err(SkootError::github)?
+            .error_for_status()
+            .map_err(SkootError::github)?;
+        let mut file =
+            File::create(path).map_err(|e| format!("Failed to create {}: {e}", path.display()))?;
+        let mut hasher = sha2::Sha256::new();
+        let total = u64::try_from(asset.size).map_err(SkootError::other)?;
+        let mut size = 0;
+        let mut reported = 0;
+        while let Some(chunk) = response.chunk().await.map_err(SkootError::github)? {
+            file.write_all(&chunk)?;
+            hasher.update(&chunk);
+            size += u64::try_from(chunk.len()).map_err(SkootError::other)?;
+            // An empty asset has nothing to report progress on.
+            if let Some(percent) = (size * 100).checked_div(total) {
+                let percent = percent.min(100);
+                if percent >= reported + 25 {
+                    reported = percent - percent % 25;
+                    info!("Downloaded {reported}% of {}", asset.name);
+                }
+            }
+        }
+        file.flush()?;
+        Ok((size, format!("{:x}", hasher.finalize())))
+    }
+}
+
+/// Lists and downloads the artifacts workflow runs upload, e.g. SBOMs and scan results generated on push rather
+/// than attached to a release.
+struct GithubArtifactHandler {
+    client: Arc<Octocrab>,
+}
+
+impl GithubArtifactHandler {
+    async fn outputs_list(
+        &self,
+        params: GithubWorkflowRunParams,
+    ) -> Result<Vec<ProjectOutputReference>, SkootError> {
+        let references = self
+            .list_artifacts(&params)
+            .await?
+            .into_iter()
+            .map(|artifact| {
+                let (output_type, labels) = classify_asset(&artifact.name);
+                ProjectOutputReference {
+                    name: artifact.name,
+                    output_type,
+                    labels,
+                }
+            })
+            .collect();
+        Ok(references)
+    }
+
+    /// Returns the content of an artifact. Github serves artifacts as zip archives, so only an artifact of a
+    /// single file can be returned as its content, one with several files has to be downloaded.
+    async fn get_output(
+        &self,
+        params: GithubWorkflowRunParams,
+        name: &str,
+    ) -> Result<ProjectOutput, SkootError> {
+        let artifact = self.find_artifact(&params, name).await?;
+        let archive = self.download_archive(&params, &artifact).await?;
+        let mut archive = zip::ZipArchive::new(Cursor::new(archive)).map_err(SkootError::other)?;
+        if archive.len() != 1 {
+            return Err(format!(
+                "Artifact {name} has {} files, download it instead",
+                archive.len()
+            )
+            .into());
+        }
+        let mut content = String::new();
+        archive
+            .by_index(0)
+            .map_err(SkootError::other)?
+            .read_to_string(&mut content)?;
+        let (output_type, labels) = classify_asset(&artifact.name);
+        Ok(ProjectOutput {
+            reference: ProjectOutputReference {
+                name: artifact.name,
+                output_type,
+                labels,
             },
             output: serde_json::to_string_pretty(&content)?,
         })
     }
+
+    /// Downloads the named artifact of the run, or all of them, to `<name>.zip` files in `dir`. Github doesn't
+    /// publish checksums for artifacts, so they aren't verified.
+    async fn download(
+        &self,
+        params: GithubWorkflowRunParams,
+        name: Option<String>,
+        dir: &str,
+    ) -> Result<Vec<DownloadedOutput>, SkootError> {
+        let artifacts = match name {
+            Some(name) => vec![self.find_artifact(&params, &name).await?],
+            None => self.list_artifacts(&params).await?,
+        };
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {dir}: {e}"))?;
+        let total = artifacts.len();
+        let mut downloaded = Vec::new();
+        for (index, artifact) in artifacts.into_iter().enumerate() {
+            // Artifact names come from the workflow, so they must not be able to point outside of `dir`.
+            if artifact.name.contains(['/', '\\']) || artifact.name == ".." {
+                return Err(
+                    format!("Refusing to download {} outside of {dir}", artifact.name).into(),
+                );
+            }
+            info!(
+                "Downloading {} ({}/{total}, {} bytes)",
+                artifact.name,
+                index + 1,
+                artifact.size_in_bytes
+            );
+            let archive = self.download_archive(&params, &artifact).await?;
+            let path = Path::new(dir).join(format!("{}.zip", artifact.name));
+            std::fs::write(&path, &archive)
+                .map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+            downloaded.push(DownloadedOutput {
+                name: artifact.name,
+                path: path.display().to_string(),
+                size: u64::try_from(archive.len()).map_err(SkootError::other)?,
+                sha256: format!("{:x}", sha2::Sha256::digest(&archive)),
+                checksum_verified: false,
+            });
+        }
+        Ok(downloaded)
+    }
+
+    /// Returns the ID of the workflow run the params refer to.
+    async fn run_id(&self, params: &GithubWorkflowRunParams) -> Result<RunId, SkootError> {
+        match &params.run {
+            WorkflowRunParam::Id(id) => Ok(RunId(*id)),
+            WorkflowRunParam::LatestOn(branch) => self
+                .client
+                .workflows(&params.owner, &params.repo)
+                .list_all_runs()
+                .branch(branch)
+                .status("success")
+                .per_page(1u8)
+                .send()
+                .await
+                .map_err(SkootError::github)?
+                .items
+                .first()
+                .map(|run| run.id)
+                .ok_or_else(|| {
+                    SkootError::NotFound(format!(
+                        "There's no successful workflow run on {branch} in {}/{}",
+                        params.owner, params.repo
+                    ))
+                }),
+        }
+    }
+
+    /// Lists every artifact of the run that hasn't expired yet.
+    async fn list_artifacts(
+        &self,
+        params: &GithubWorkflowRunParams,
+    ) -> Result<Vec<WorkflowListArtifact>, SkootError> {
+        let run_id = self.run_id(params).await?;
+        let route = format!(
+            "/repos/{}/{}/actions/runs/{run_id}/artifacts",
+            params.owner, params.repo
+        );
+        let first_page: Page<WorkflowListArtifact> = self
+            .client
+            .get(route, Some(&[("per_page", PER_PAGE)]))
+            .await
+            .map_err(SkootError::github)?;
+        let artifacts = pagination::all_pages(&self.client, first_page).await?;
+        Ok(artifacts
+            .into_iter()
+            .filter(|artifact| {
+                if artifact.expired {
+                    debug!("Skipping expired artifact {}", artifact.name);
+                }
+                !artifact.expired
+            })
+            .collect())
+    }
+
+    async fn find_artifact(
+        &self,
+        params: &GithubWorkflowRunParams,
+        name: &str,
+    ) -> Result<WorkflowListArtifact, SkootError> {
+        self.list_artifacts(params)
+            .await?
+            .into_iter()
+            .find(|artifact| artifact.name == name)
+            .ok_or_else(|| {
+                SkootError::NotFound(format!("Artifact {name} isn't in the workflow run"))
+            })
+    }
+
+    async fn download_archive(
+        &self,
+        params: &GithubWorkflowRunParams,
+        artifact: &WorkflowListArtifact,
+    ) -> Result<Vec<u8>, SkootError> {
+        Ok(self
+            .client
+            .actions()
+            .download_artifact(&params.owner, &params.repo, artifact.id, ArchiveFormat::Zip)
+            .await
+            .map_err(SkootError::github)?
+            .to_vec())
+    }
+}
+
+/// Returns whether a release asset is a file of checksums for the other assets, e.g. `SHA256SUMS`,
+/// `checksums.txt` or `skootrs.tar.gz.sha256`.
+fn is_checksum_file(name: &str) -> bool {
+    let name = name.to_lowercase();
+    name.contains("sha256sums") || name.ends_with("checksums.txt") || name.ends_with(".sha256")
+}
+
+/// Parses a checksum file in the format `sha256sum` writes, a digest and a file name on each line. A
+/// `<asset>.sha256` file can also only hold the digest of `<asset>`.
+fn parse_checksums(file_name: &str, content: &str) -> HashMap<String, String> {
+    let is_sha256_digest =
+        |digest: &str| digest.len() == 64 && digest.chars().all(|c| c.is_ascii_hexdigit());
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let digest = fields.next().filter(|digest| is_sha256_digest(digest))?;
+            // `sha256sum --binary` marks the file name with a `*`.
+            let name = match fields.next() {
+                Some(name) => name.trim_start_matches('*'),
+                None => file_name.strip_suffix(".sha256")?,
+            };
+            Some((name.to_string(), digest.to_lowercase()))
+        })
+        .collect()
+}
+
+/// Classifies a release asset by its name into the type of output it is, along with the labels of the
+/// requirements it shows the project meets. The first matching kind of asset wins, so e.g. the signature of an
+/// SBOM is classified as a signature.
+fn classify_asset(name: &str) -> (ProjectOutputType, Vec<Label>) {
+    if name == PROVENANCE_VERIFICATION_FILE {
+        (
+            ProjectOutputType::ProvenanceVerification,
+            vec![Label::SLSABuildLevel3],
+        )
+    } else if is_signature(name) {
+        (
+            ProjectOutputType::Signature,
+            vec![Label::ScorecardSignedReleases],
+        )
+    } else if glob_matches("*.intoto.jsonl", name) {
+        // The slsa-github-generator names the provenance it attaches to releases `*.intoto.jsonl`.
+        (
+            ProjectOutputType::SLSAAttestation,
+            vec![Label::SLSABuildLevel3, Label::ScorecardSignedReleases],
+        )
+    } else if SBOM_PATTERNS
+        .iter()
+        .any(|pattern| glob_matches(pattern, name))
+    {
+        (ProjectOutputType::SBOM, vec![Label::S2C2FAUD4])
+    } else if ATTESTATION_PATTERNS
+        .iter()
+        .any(|pattern| glob_matches(pattern, name))
+    {
+        (ProjectOutputType::InToto, vec![Label::SLSABuildLevel3])
+    } else {
+        // TODO: Add more types
+        (ProjectOutputType::Unknown("Unknown".to_string()), vec![])
+    }
+}
+
+/// Classifies an in-toto attestation by the predicate type of its first statement, along with the labels of
+/// the requirements it shows the project meets. An attestation whose statement can't be read stays `InToto`.
+fn classify_attestation(content: &str) -> (ProjectOutputType, Vec<Label>) {
+    let predicate_type = envelopes(content).and_then(|envelopes| {
+        let envelope = envelopes
+            .first()
+            .ok_or_else(|| SkootError::from("The attestation has no envelopes"))?;
+        Ok(PredicateType::from_uri(
+            &envelope.envelope.statement()?.predicate_type,
+        ))
+    });
+    match predicate_type {
+        Ok(predicate_type) => {
+            let labels = match predicate_type {
+                PredicateType::SLSAProvenance | PredicateType::SLSAVerificationSummary => {
+                    vec![Label::SLSABuildLevel3]
+                }
+                PredicateType::SPDX | PredicateType::CycloneDX => vec![Label::S2C2FAUD4],
+                _ => vec![],
+            };
+            (ProjectOutputType::Attestation(predicate_type), labels)
+        }
+        Err(e) => {
+            debug!("Failed to read the statement of the attestation: {e}");
+            (ProjectOutputType::InToto, vec![Label::SLSABuildLevel3])
+        }
+    }
+}
+
+/// Returns whether a release asset is a detached signature of another asset.
+fn is_signature(name: &str) -> bool {
+    SIGNATURE_PATTERNS
+        .iter()
+        .any(|pattern| glob_matches(pattern, name))
+}
+
+/// Returns whether a name matches a glob pattern, where `*` matches any run of characters and `?` matches a
+/// single character.
+fn glob_matches(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let name = name.chars().collect::<Vec<_>>();
+    let (mut p, mut n) = (0, 0);
+    // Where the last `*` was in the pattern and the position in the name it's matched up to so far, so the
+    // match can backtrack to it.
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    star = Some((star_p, star_n + 1));
+                    p = star_p + 1;
+                    n = star_n + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// A DSSE envelope, one of which is on each line of a `*.intoto.jsonl` attestation.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Envelope {
+    payload_type: String,
+    payload: String,
+    #[serde(default)]
+    signatures: Vec<EnvelopeSignature>,
+}
+
+impl Envelope {
+    /// Returns the in-toto statement in the envelope's payload.
+    fn statement(&self) -> Result<Statement, SkootError> {
+        if self.payload_type != IN_TOTO_PAYLOAD_TYPE {
+            return Err(format!(
+                "The attestation has a {} payload instead of an in-toto statement",
+                self.payload_type
+            )
+            .into());
+        }
+        let payload = base64::engine::general_purpose::STANDARD
+            .decode(&self.payload)
+            .map_err(SkootError::other)?;
+        Ok(serde_json::from_slice(&payload)
+            .map_err(|e| format!("The attestation isn't an in-toto statement: {e}"))?)
+    }
+
+    /// Returns the certificate the envelope was signed with, if a signature carries one like the ones of the
+    /// slsa-github-generator do.
+    fn certificate(&self) -> Option<&str> {
+        self.signatures
+            .iter()
+            .find_map(|signature| signature.cert.as_deref())
+    }
+}
+
+/// A signature of a DSSE envelope. Only the certificate is needed, cosign checks the signature itself.
+#[derive(Deserialize)]
+struct EnvelopeSignature {
+    #[serde(default)]
+    cert: Option<String>,
+}
+
+/// A DSSE envelope of an attestation, along with the JSON it was attached as.
+struct AttestationEnvelope {
+    envelope: Envelope,
+    /// The envelope as it was attached, or the Sigstore bundle it's in.
+    raw: serde_json::Value,
+    /// Whether the envelope is in a Sigstore bundle, which carries the certificate it was signed with.
+    bundled: bool,
+}
+
+/// The in-toto statement in the payload of an envelope. Only the subjects and predicate type are needed to
+/// check it against the release.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Statement {
+    #[serde(default)]
+    predicate_type: String,
+    subject: Vec<Subject>,
+}
+
+/// An artifact an attestation is about.
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+struct Subject {
+    name: String,
+    digest: HashMap<String, String>,
+}
+
+impl Subject {
+    /// Checks the SHA-256 digest of the artifact against the one the attestation gives for it.
+    fn verify(&self, artifact: &[u8]) -> Result<(), SkootError> {
+        let expected = self
+            .digest
+            .get("sha256")
+            .ok_or_else(|| format!("The attestation has no sha256 digest for {}", self.name))?;
+        let actual = format!("{:x}", sha2::Sha256::digest(artifact));
+        if !expected.eq_ignore_ascii_case(&actual) {
+            return Err(format!(
+                "{} doesn't match its attestation, its sha256 digest is {actual} instead of {expected}",
+                self.name
+            )
+            .into());
+        }
+        Ok(())
+    }
+}
+
+/// Returns the DSSE envelopes of an attestation, which is either a single envelope, e.g. `*.intoto.json`, a
+/// Sigstore bundle of one, or an envelope on each line, e.g. `*.intoto.jsonl`.
+fn envelopes(attestation: &str) -> Result<Vec<AttestationEnvelope>, SkootError> {
+    let documents = match serde_json::from_str::<serde_json::Value>(attestation) {
+        Ok(document) => vec![document],
+        Err(_) => attestation
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(serde_json::from_str)
+            .collect::<Result<Vec<serde_json::Value>, _>>()
+            .map_err(|e| format!("The attestation isn't a DSSE envelope: {e}"))?,
+    };
+    documents
+        .into_iter()
+        .map(|raw| {
+            let (envelope, bundled) = match raw.get("dsseEnvelope") {
+                Some(envelope) => (envelope.clone(), true),
+                None => (raw.clone(), false),
+            };
+            let envelope = serde_json::from_value(envelope)
+                .map_err(|e| format!("The attestation isn't a DSSE envelope: {e}"))?;
+            Ok(AttestationEnvelope {
+                envelope,
+                raw,
+                bundled,
+            })
+        })
+        .collect()
+}
+
+/// Returns the subjects of every statement in an attestation.
+fn attestation_subjects(attestation: &str) -> Result<Vec<Subject>, SkootError> {
+    let mut subjects = Vec::new();
+    for envelope in envelopes(attestation)? {
+        subjects.extend(envelope.envelope.statement()?.subject);
+    }
+    Ok(subjects)
 }
 
 struct GithubReleaseParams {
@@ -162,7 +1204,350 @@ struct GithubReleaseParams {
     tag: Option<String>,
 }
 
+struct GithubWorkflowRunParams {
+    owner: String,
+    repo: String,
+    run: WorkflowRunParam,
+}
+
 struct GithubOutputGetParams {
     release: Release,
     name: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader},
+        sync::Mutex,
+    };
+
+    use tempdir::TempDir;
+
+    use super::*;
+
+    fn attestation(payload_type: &str, statement: &serde_json::Value) -> String {
+        serde_json::json!({
+            "payloadType": payload_type,
+            "payload": base64::engine::general_purpose::STANDARD.encode(statement.to_string()),
+            "signatures": [],
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_attestation_subjects() {
+        let artifact = b"skootrs";
+        let digest = format!("{:x}", sha2::Sha256::digest(artifact));
+        let statement = serde_json::json!({
+            "_type": "https://in-toto.io/Statement/v0.1",
+            "predicateType": "https://slsa.dev/provenance/v0.2",
+            "subject": [{"name": "skootrs.tar.gz", "digest": {"sha256": digest}}],
+            "predicate": {},
+        });
+        let content = format!("{}\n", attestation(IN_TOTO_PAYLOAD_TYPE, &statement));
+        let subjects = attestation_subjects(&content).unwrap();
+        assert_eq!(subjects.len(), 1);
+        assert_eq!(subjects[0].name, "skootrs.tar.gz");
+        assert!(subjects[0].verify(artifact).is_ok());
+        assert!(subjects[0].verify(b"tampered").is_err());
+
+        assert!(attestation_subjects(&attestation("text/plain", &statement)).is_err());
+        assert!(attestation_subjects("not json").is_err());
+    }
+
+    #[test]
+    fn test_classify_attestation() {
+        let statement = serde_json::json!({
+            "_type": "https://in-toto.io/Statement/v1",
+            "predicateType": "https://spdx.dev/Document/v2.3",
+            "subject": [{"name": "skootrs.tar.gz", "digest": {"sha256": "a".repeat(64)}}],
+            "predicate": {},
+        });
+        let envelope = attestation(IN_TOTO_PAYLOAD_TYPE, &statement);
+        assert_eq!(
+            classify_attestation(&envelope),
+            (
+                ProjectOutputType::Attestation(PredicateType::SPDX),
+                vec![Label::S2C2FAUD4]
+            )
+        );
+
+        // A single envelope can span several lines, and a Sigstore bundle wraps its envelope.
+        let pretty: serde_json::Value = serde_json::from_str(&envelope).unwrap();
+        let envelopes = envelopes(&serde_json::to_string_pretty(&pretty).unwrap()).unwrap();
+        assert_eq!(envelopes.len(), 1);
+        assert!(!envelopes[0].bundled);
+        let bundle = serde_json::json!({"mediaType": "application/vnd.dev.sigstore.bundle.v0.3+json", "dsseEnvelope": pretty});
+        let envelopes = super::envelopes(&bundle.to_string()).unwrap();
+        assert!(envelopes[0].bundled);
+        assert_eq!(envelopes[0].raw, bundle);
+        assert_eq!(
+            envelopes[0].envelope.statement().unwrap().predicate_type,
+            "https://spdx.dev/Document/v2.3"
+        );
+
+        assert_eq!(
+            classify_attestation(&attestation("text/plain", &statement)).0,
+            ProjectOutputType::InToto
+        );
+    }
+
+    #[test]
+    fn test_parse_checksums() {
+        let digest = "a".repeat(64);
+        let content = format!("{digest}  skootrs.tar.gz\n{digest} *skootrs.zip\nnot a checksum\n");
+        let checksums = parse_checksums("SHA256SUMS", &content);
+        assert_eq!(checksums.len(), 2);
+        assert_eq!(checksums["skootrs.tar.gz"], digest);
+        assert_eq!(checksums["skootrs.zip"], digest);
+
+        let checksums = parse_checksums("skootrs.tar.gz.sha256", &digest.to_uppercase());
+        assert_eq!(checksums["skootrs.tar.gz"], digest);
+
+        assert!(is_checksum_file("SHA256SUMS"));
+        assert!(is_checksum_file("skootrs_1.0.0_checksums.txt"));
+        assert!(!is_checksum_file("skootrs.tar.gz"));
+    }
+
+    #[test]
+    fn test_glob_matches() {
+        assert!(glob_matches("*.spdx.*", "skootrs.spdx.json"));
+        assert!(glob_matches("*.intoto.jsonl", "multiple.intoto.jsonl"));
+        assert!(glob_matches("skootrs-?.tar.gz", "skootrs-1.tar.gz"));
+        assert!(glob_matches("*", ""));
+        assert!(glob_matches("*.sig*", "skootrs.tar.gz.sigstore.json"));
+        assert!(!glob_matches("*.spdx.*", "skootrs.spdx"));
+        assert!(!glob_matches("*.sig", "skootrs.sig.txt"));
+        assert!(!glob_matches("skootrs-?.tar.gz", "skootrs-10.tar.gz"));
+    }
+
+    #[test]
+    fn test_classify_asset() {
+        let output_type = |name| classify_asset(name).0;
+        assert_eq!(
+            output_type(PROVENANCE_VERIFICATION_FILE),
+            ProjectOutputType::ProvenanceVerification
+        );
+        assert_eq!(
+            output_type("multiple.intoto.jsonl"),
+            ProjectOutputType::SLSAAttestation
+        );
+        assert_eq!(output_type("skootrs.spdx.json"), ProjectOutputType::SBOM);
+        assert_eq!(output_type("skootrs.cdx.xml"), ProjectOutputType::SBOM);
+        assert_eq!(output_type("skootrs.spdx"), ProjectOutputType::SBOM);
+        assert_eq!(
+            output_type("skootrs.spdx.json.sig"),
+            ProjectOutputType::Signature
+        );
+        assert_eq!(
+            output_type("skootrs.intoto.json"),
+            ProjectOutputType::InToto
+        );
+        assert_eq!(output_type("skootrs.dsse.json"), ProjectOutputType::InToto);
+        assert_eq!(
+            output_type("skootrs.tar.gz"),
+            ProjectOutputType::Unknown("Unknown".to_string())
+        );
+        assert_eq!(
+            classify_asset("multiple.intoto.jsonl").1,
+            vec![Label::SLSABuildLevel3, Label::ScorecardSignedReleases]
+        );
+    }
+
+    #[test]
+    fn test_is_signature() {
+        assert!(is_signature("skootrs.tar.gz.sig"));
+        assert!(is_signature("skootrs.tar.gz.sigstore.json"));
+        assert!(is_signature("skootrs.spdx.json.asc"));
+        assert!(!is_signature("multiple.intoto.jsonl"));
+        assert!(!is_signature("skootrs.spdx.json"));
+    }
+
+    type FakeResponse = (u16, Vec<u8>);
+
+    /// Serves the Github API from a local port, answering each request with what `respond` returns for its
+    /// route. Returns a handler using it along with the routes it received.
+    fn fake_github(
+        respond: impl Fn(&str) -> FakeResponse + Send + 'static,
+    ) -> (GithubArtifactHandler, Arc<Mutex<Vec<String>>>) {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let routes = Arc::new(Mutex::new(Vec::new()));
+        let received = routes.clone();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut lines = BufReader::new(&stream)
+                    .lines()
+                    .map_while(Result::ok)
+                    .take_while(|line| !line.is_empty());
+                let route = lines
+                    .next()
+                    .unwrap_or_default()
+                    .split(' ')
+                    .nth(1)
+                    .unwrap_or_default()
+                    .to_string();
+                lines.for_each(drop);
+                let (status, body) = respond(&route);
+                received.lock().unwrap().push(route);
+                let reason = if status == 200 { "OK" } else { "Not Found" };
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {status} {reason}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                );
+                let _ = stream.write_all(&body);
+            }
+        });
+        let client = Octocrab::builder().base_uri(url).unwrap().build().unwrap();
+        (
+            GithubArtifactHandler {
+                client: Arc::new(client),
+            },
+            routes,
+        )
+    }
+
+    fn artifact(id: u64, name: &str, expired: bool) -> serde_json::Value {
+        serde_json::json!({
+            "id": id,
+            "node_id": format!("MDg6QXJ0aWZhY3Q{id}"),
+            "name": name,
+            "size_in_bytes": 1024,
+            "url": format!("https://api.github.com/repos/kusaridev/skootrs/actions/artifacts/{id}"),
+            "archive_download_url": format!("https://api.github.com/repos/kusaridev/skootrs/actions/artifacts/{id}/zip"),
+            "expired": expired,
+            "created_at": "2024-03-01T00:00:00Z",
+            "updated_at": "2024-03-01T00:00:00Z",
+            "expires_at": "2024-06-01T00:00:00Z",
+        })
+    }
+
+    /// Zips a single file the way Github archives an artifact.
+    fn zipped(name: &str, content: &str) -> Vec<u8> {
+        let mut archive = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        archive
+            .start_file(
+                name,
+                zip::write::FileOptions::default()
+                    .compression_method(zip::CompressionMethod::Stored),
+            )
+            .unwrap();
+        archive.write_all(content.as_bytes()).unwrap();
+        archive.finish().unwrap().into_inner()
+    }
+
+    /// A fake Github API with run 42 of `kusaridev/skootrs`, which uploaded an SBOM and an expired scan result.
+    fn respond(route: &str) -> FakeResponse {
+        match route {
+            "/repos/kusaridev/skootrs/actions/runs/42/artifacts?per_page=100" => (
+                200,
+                serde_json::json!({
+                    "total_count": 2,
+                    "artifacts": [
+                        artifact(7, "skootrs.spdx.json", false),
+                        artifact(8, "scan-results", true),
+                    ],
+                })
+                .to_string()
+                .into_bytes(),
+            ),
+            "/repos/kusaridev/skootrs/actions/artifacts/7/zip" => (
+                200,
+                zipped("skootrs.spdx.json", r#"{"spdxVersion": "SPDX-2.3"}"#),
+            ),
+            route if route.starts_with("/repos/kusaridev/skootrs/actions/runs?") => (
+                200,
+                serde_json::json!({"total_count": 0, "workflow_runs": []})
+                    .to_string()
+                    .into_bytes(),
+            ),
+            _ => (
+                404,
+                br#"{"message": "Not Found", "documentation_url": "https://docs.github.com"}"#
+                    .to_vec(),
+            ),
+        }
+    }
+
+    fn run_params(run: WorkflowRunParam) -> GithubWorkflowRunParams {
+        GithubWorkflowRunParams {
+            owner: "kusaridev".to_string(),
+            repo: "skootrs".to_string(),
+            run,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_workflow_run_outputs_list() {
+        let (handler, routes) = fake_github(respond);
+        let references = handler
+            .outputs_list(run_params(WorkflowRunParam::Id(42)))
+            .await
+            .unwrap();
+        // The expired scan result can't be downloaded anymore, so it isn't listed.
+        assert_eq!(references.len(), 1);
+        assert_eq!(references[0].name, "skootrs.spdx.json");
+        assert_eq!(references[0].output_type, ProjectOutputType::SBOM);
+        assert_eq!(
+            *routes.lock().unwrap(),
+            vec!["/repos/kusaridev/skootrs/actions/runs/42/artifacts?per_page=100"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_workflow_run_without_success() {
+        let (handler, _) = fake_github(respond);
+        let result = handler
+            .outputs_list(run_params(WorkflowRunParam::LatestOn("main".to_string())))
+            .await;
+        assert!(matches!(result, Err(SkootError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_workflow_run_get_output() {
+        let (handler, _) = fake_github(respond);
+        let output = handler
+            .get_output(run_params(WorkflowRunParam::Id(42)), "skootrs.spdx.json")
+            .await
+            .unwrap();
+        assert_eq!(output.reference.output_type, ProjectOutputType::SBOM);
+        assert_eq!(
+            serde_json::from_str::<String>(&output.output).unwrap(),
+            r#"{"spdxVersion": "SPDX-2.3"}"#
+        );
+        let missing = handler
+            .get_output(run_params(WorkflowRunParam::Id(42)), "scan-results")
+            .await;
+        assert!(matches!(missing, Err(SkootError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_workflow_run_download() {
+        let (handler, routes) = fake_github(respond);
+        let temp_dir = TempDir::new("test_workflow_run_download").unwrap();
+        let dir = temp_dir.path().join("outputs").display().to_string();
+        let downloaded = handler
+            .download(run_params(WorkflowRunParam::Id(42)), None, &dir)
+            .await
+            .unwrap();
+        let archive = zipped("skootrs.spdx.json", r#"{"spdxVersion": "SPDX-2.3"}"#);
+        let path = Path::new(&dir).join("skootrs.spdx.json.zip");
+        assert_eq!(
+            downloaded,
+            vec![DownloadedOutput {
+                name: "skootrs.spdx.json".to_string(),
+                path: path.display().to_string(),
+                size: archive.len() as u64,
+                sha256: format!("{:x}", sha2::Sha256::digest(&archive)),
+                checksum_verified: false,
+            }]
+        );
+        assert_eq!(std::fs::read(path).unwrap(), archive);
+        assert!(routes
+            .lock()
+            .unwrap()
+            .contains(&"/repos/kusaridev/skootrs/actions/artifacts/7/zip".to_string()));
+    }
+}
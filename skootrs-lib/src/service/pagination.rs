@@ -0,0 +1,72 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Github's list endpoints, like org memberships or an org's repos, return their results a `Page` at a time.
+//! `all_pages` follows the pages to the end. Listing a large org can run into Github's rate limits, so when a
+//! page is rate limited it waits for the limit to reset and tries the page again instead of failing the whole
+//! listing.
+
+use std::time::Duration;
+
+use octocrab::{Octocrab, Page};
+use serde::de::DeserializeOwned;
+use skootrs_model::skootrs::SkootError;
+use tracing::warn;
+
+use super::retry::{is_rate_limited, rate_limit_wait, unix_now};
+
+/// The number of times a rate limited page is retried before giving up.
+const MAX_RATE_LIMIT_RETRIES: u32 = 3;
+
+/// The longest Skootrs waits for a rate limit to reset. Github's primary rate limit resets hourly, so a listing
+/// that would have to wait longer than this fails instead of hanging.
+const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(15 * 60);
+
+/// Returns the items of `first_page` and every page after it.
+///
+/// # Errors
+///
+/// Returns an error if a page can't be fetched, or if it is still rate limited after retrying.
+pub async fn all_pages<T: DeserializeOwned>(
+    client: &Octocrab,
+    mut first_page: Page<T>,
+) -> Result<Vec<T>, SkootError> {
+    let mut items = first_page.take_items();
+    let mut next = first_page.next;
+    let mut retries = 0;
+    while next.is_some() {
+        match client.get_page::<T>(&next).await {
+            Ok(Some(mut page)) => {
+                items.append(&mut page.take_items());
+                next = page.next;
+                retries = 0;
+            }
+            Ok(None) => break,
+            Err(e) if is_rate_limited(&e) && retries < MAX_RATE_LIMIT_RETRIES => {
+                retries += 1;
+                let rate_limit = client.ratelimit().get().await.map_err(SkootError::github)?;
+                let wait =
+                    rate_limit_wait(&rate_limit.resources.core, unix_now(), MAX_RATE_LIMIT_WAIT)?;
+                warn!(
+                    "Github rate limit hit, retrying in {}s ({retries}/{MAX_RATE_LIMIT_RETRIES})",
+                    wait.as_secs()
+                );
+                tokio::time::sleep(wait).await;
+            }
+            Err(e) => return Err(SkootError::github(e)),
+        }
+    }
+    Ok(items)
+}
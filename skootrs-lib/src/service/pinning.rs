@@ -0,0 +1,151 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The workflows Skootrs generates reference actions by tag so the templates stay readable. Projects with
+//! the `PinnedDependencies` facet get them rewritten to the commit SHAs the tags point to when they're
+//! generated, so a moved tag can't change what runs in the project's workflows.
+
+use std::{collections::HashMap, process::Command, sync::Mutex};
+
+use skootrs_model::skootrs::{
+    pinning::{action_reference, PinningPolicy},
+    SkootError,
+};
+use tracing::info;
+
+use super::command::CommandRunner;
+
+/// The `ActionPinner` struct rewrites the actions and reusable workflows of a workflow to the commit SHAs
+/// their tags point to.
+#[derive(Debug, Default)]
+pub struct ActionPinner {
+    /// The actions and reusable workflows that are left referenced by tag.
+    pub policy: PinningPolicy,
+    /// The commit SHAs that were already resolved, by the reference they were resolved for, so each action is
+    /// only resolved once.
+    resolved: Mutex<HashMap<String, String>>,
+}
+
+impl ActionPinner {
+    /// Returns a pinner that pins everything the policy doesn't exempt.
+    #[must_use]
+    pub fn new(policy: PinningPolicy) -> Self {
+        Self {
+            policy,
+            resolved: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the workflow with every reference the policy requires to be pinned pinned to a commit SHA.
+    /// The tag is kept in a comment so it's still clear which version is used.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a reference can't be resolved to a commit SHA, e.g. because the tag doesn't exist.
+    pub fn pin(&self, workflow: &str) -> Result<String, SkootError> {
+        let mut pinned = workflow
+            .lines()
+            .map(|line| match action_reference(line) {
+                Some(reference)
+                    if !self.policy.is_pinned(reference) && !reference.starts_with("docker://") =>
+                {
+                    let (action, version) = reference
+                        .split_once('@')
+                        .ok_or_else(|| format!("{reference} doesn't reference a version"))?;
+                    let sha = self.resolve(action, version)?;
+                    Ok(line.replacen(reference, &format!("{action}@{sha} # {version}"), 1))
+                }
+                _ => Ok(line.to_string()),
+            })
+            .collect::<Result<Vec<_>, SkootError>>()?
+            .join("\n");
+        if workflow.ends_with('\n') {
+            pinned.push('\n');
+        }
+        Ok(pinned)
+    }
+
+    /// Resolves the version of an action to the commit SHA it points to. Annotated tags are resolved to the
+    /// commit they tag, and versions that aren't tags are resolved as branches.
+    fn resolve(&self, action: &str, version: &str) -> Result<String, SkootError> {
+        let key = format!("{action}@{version}");
+        if let Some(sha) = self.resolved.lock().map_err(|e| e.to_string())?.get(&key) {
+            return Ok(sha.clone());
+        }
+        // Reusable workflows and actions in subdirectories live in the repo named by the first two segments.
+        let repo = action.splitn(3, '/').take(2).collect::<Vec<_>>().join("/");
+        let output = Command::new("git")
+            .arg("ls-remote")
+            .arg(format!("https://github.com/{repo}"))
+            .arg(format!("refs/tags/{version}"))
+            .arg(format!("refs/tags/{version}^{{}}"))
+            .arg(format!("refs/heads/{version}"))
+            .run_checked()?;
+        let refs = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.split_once('\t'))
+            .map(|(sha, name)| (name.to_string(), sha.to_string()))
+            .collect::<HashMap<_, _>>();
+        let sha = [
+            format!("refs/tags/{version}^{{}}"),
+            format!("refs/tags/{version}"),
+            format!("refs/heads/{version}"),
+        ]
+        .iter()
+        .find_map(|name| refs.get(name))
+        .ok_or_else(|| format!("Failed to resolve {key} to a commit"))?
+        .clone();
+        info!("Pinned {key} to {sha}");
+        self.resolved
+            .lock()
+            .map_err(|e| e.to_string())?
+            .insert(key, sha.clone());
+        Ok(sha)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pin() {
+        let pinner = ActionPinner::new(PinningPolicy::default());
+        pinner.resolved.lock().unwrap().insert(
+            "googleapis/release-please-action@v4".to_string(),
+            "7987652d64b4581673a76e33ad5e98e3dd56832f".to_string(),
+        );
+        let workflow = "steps:
+  - uses: actions/checkout@9bb56186c3b09b4f86b1c65136769dd318469633 # v4.1.2
+  - uses: googleapis/release-please-action@v4
+    with:
+      release-type: rust
+provenance:
+  uses: slsa-framework/slsa-github-generator/.github/workflows/generator_generic_slsa3.yml@v1.10.0
+";
+        assert_eq!(
+            pinner.pin(workflow).unwrap(),
+            "steps:
+  - uses: actions/checkout@9bb56186c3b09b4f86b1c65136769dd318469633 # v4.1.2
+  - uses: googleapis/release-please-action@7987652d64b4581673a76e33ad5e98e3dd56832f # v4
+    with:
+      release-type: rust
+provenance:
+  uses: slsa-framework/slsa-github-generator/.github/workflows/generator_generic_slsa3.yml@v1.10.0
+"
+        );
+        assert!(pinner.pin("  - uses: actions/checkout").is_err());
+    }
+}
@@ -0,0 +1,274 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Organizations have requirements of their projects beyond what any single facet covers, e.g. that every
+//! project has branch protection and attaches an SBOM to its releases. They write those requirements as OPA
+//! Rego or CUE policies, and the `PolicyService` evaluates a project against them with `opa` or `cue`, so
+//! `skootrs project evaluate` can gate CI on them.
+
+#![allow(clippy::module_name_repetitions)]
+
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use serde_json::Value;
+use tracing::info;
+
+use skootrs_model::skootrs::{
+    policy::{PolicyEvaluation, PolicyInput, PolicyLanguage, REGO_PACKAGE},
+    SkootError,
+};
+
+use super::command::{CommandError, CommandRunner};
+
+/// The `PolicyService` trait provides an interface for evaluating a project against a policy bundle.
+pub trait PolicyService {
+    /// Evaluates a project against the policies of a bundle, which is a `.rego` or `.cue` file or a
+    /// directory of them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bundle can't be read or the policies can't be evaluated, e.g. because a Rego
+    /// bundle doesn't define a `deny` rule. A project that violates the policies isn't an error.
+    fn evaluate(&self, bundle: &Path, input: &PolicyInput) -> Result<PolicyEvaluation, SkootError>;
+}
+
+/// The `LocalPolicyService` struct provides an implementation of the `PolicyService` trait that runs the
+/// `opa` or `cue` CLI, whichever the bundle is written for.
+#[derive(Debug, Default)]
+pub struct LocalPolicyService {}
+
+impl PolicyService for LocalPolicyService {
+    fn evaluate(&self, bundle: &Path, input: &PolicyInput) -> Result<PolicyEvaluation, SkootError> {
+        let language = bundle_language(bundle)?;
+        info!(
+            "Evaluating {} against the {language} policies in {}",
+            input.project.repo.full_url(),
+            bundle.display()
+        );
+        // Both CLIs read the input from a file.
+        let dir = std::env::temp_dir().join(format!(
+            "skootrs-policy-{}-{}",
+            std::process::id(),
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+        let input_path = dir.join("input.json");
+        let violations = std::fs::write(&input_path, serde_json::to_vec(input)?)
+            .map_err(SkootError::other)
+            .and_then(|()| match language {
+                PolicyLanguage::Rego => evaluate_rego(bundle, &input_path),
+                PolicyLanguage::Cue => evaluate_cue(bundle, &input_path),
+            });
+        let _ = std::fs::remove_dir_all(&dir);
+        Ok(PolicyEvaluation {
+            project_url: input.project.repo.full_url(),
+            policy: bundle.display().to_string(),
+            language,
+            violations: violations?,
+        })
+    }
+}
+
+/// Returns the language of a policy bundle, which is either a policy file or a directory of policy files in
+/// one language.
+///
+/// # Errors
+///
+/// Returns an error if the bundle isn't a policy file, or the directory has no policy files or mixes
+/// languages.
+pub fn bundle_language(bundle: &Path) -> Result<PolicyLanguage, SkootError> {
+    if !bundle.is_dir() {
+        return PolicyLanguage::from_path(bundle).ok_or_else(|| {
+            format!(
+                "{} is not a policy bundle, it has to be a .rego or .cue file or a directory of them",
+                bundle.display()
+            )
+            .into()
+        });
+    }
+    let mut languages = policy_files(bundle)?
+        .iter()
+        .filter_map(|path| PolicyLanguage::from_path(path))
+        .collect::<Vec<_>>();
+    languages.dedup();
+    match languages.as_slice() {
+        [language] => Ok(*language),
+        [] => Err(format!("{} has no .rego or .cue policies", bundle.display()).into()),
+        _ => Err(format!(
+            "{} mixes Rego and CUE policies, a bundle has to be in one language",
+            bundle.display()
+        )
+        .into()),
+    }
+}
+
+/// Returns the policy files of a bundle directory, sorted by language and then by name.
+fn policy_files(bundle: &Path) -> Result<Vec<PathBuf>, SkootError> {
+    let mut files = std::fs::read_dir(bundle)
+        .map_err(|e| format!("Failed to read {}: {e}", bundle.display()))?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(SkootError::other)?
+        .into_iter()
+        .filter(|path| PolicyLanguage::from_path(path).is_some())
+        .collect::<Vec<_>>();
+    files.sort_by_key(|path| (path.extension().map(ToOwned::to_owned), path.clone()));
+    Ok(files)
+}
+
+/// Evaluates the `deny` rule of the `skootrs` package with `opa eval`.
+fn evaluate_rego(bundle: &Path, input: &Path) -> Result<Vec<String>, SkootError> {
+    let query = format!("data.{REGO_PACKAGE}.deny");
+    let output = Command::new("opa")
+        .args(["eval", "--format", "json", "--data"])
+        .arg(bundle)
+        .arg("--input")
+        .arg(input)
+        .arg(&query)
+        .run_checked()?;
+    rego_violations(&serde_json::from_slice(&output.stdout)?).ok_or_else(|| {
+        format!(
+            "The policies in {} don't define a deny rule in the {REGO_PACKAGE} package",
+            bundle.display()
+        )
+        .into()
+    })
+}
+
+/// Returns the messages of the `deny` set in the result of `opa eval`, or `None` if the rule isn't defined.
+/// Messages are either strings or, like conftest's, objects with a `msg`.
+fn rego_violations(result: &Value) -> Option<Vec<String>> {
+    let deny = result
+        .get("result")?
+        .get(0)?
+        .get("expressions")?
+        .get(0)?
+        .get("value")?
+        .as_array()?;
+    Some(
+        deny.iter()
+            .map(|message| match message {
+                Value::String(message) => message.clone(),
+                _ => message
+                    .get("msg")
+                    .and_then(Value::as_str)
+                    .map_or_else(|| message.to_string(), ToString::to_string),
+            })
+            .collect(),
+    )
+}
+
+/// Unifies the input with the constraints of the bundle with `cue vet`. Every conflict is a violation.
+fn evaluate_cue(bundle: &Path, input: &Path) -> Result<Vec<String>, SkootError> {
+    let files = if bundle.is_dir() {
+        policy_files(bundle)?
+    } else {
+        vec![bundle.to_path_buf()]
+    };
+    match Command::new("cue")
+        .arg("vet")
+        .args(&files)
+        .arg(input)
+        .run_checked()
+    {
+        Ok(_) => Ok(vec![]),
+        Err(e) => match e.downcast_ref::<CommandError>() {
+            // cue exits with 1 when the input doesn't unify, and lists each conflict followed by the
+            // indented positions it comes from.
+            Some(error) if error.code == Some(1) => Ok(cue_violations(&error.stderr)),
+            _ => Err(e),
+        },
+    }
+}
+
+fn cue_violations(stderr: &str) -> Vec<String> {
+    stderr
+        .lines()
+        .filter(|line| !line.is_empty() && !line.starts_with(char::is_whitespace))
+        .map(|line| line.trim_end_matches(':').to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use tempdir::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn test_bundle_language() {
+        let dir = TempDir::new("policy").unwrap();
+        std::fs::write(dir.path().join("facets.rego"), "package skootrs").unwrap();
+        std::fs::write(dir.path().join("release.rego"), "package skootrs").unwrap();
+        std::fs::write(dir.path().join("README.md"), "").unwrap();
+
+        assert_eq!(
+            bundle_language(&dir.path().join("facets.rego")).unwrap(),
+            PolicyLanguage::Rego
+        );
+        assert_eq!(bundle_language(dir.path()).unwrap(), PolicyLanguage::Rego);
+        assert!(bundle_language(&dir.path().join("README.md")).is_err());
+
+        std::fs::write(dir.path().join("release.cue"), "").unwrap();
+        assert!(bundle_language(dir.path()).is_err());
+
+        let empty = TempDir::new("policy").unwrap();
+        assert!(bundle_language(empty.path()).is_err());
+    }
+
+    #[test]
+    fn test_rego_violations() {
+        let result = json!({
+            "result": [{
+                "expressions": [{
+                    "value": [
+                        "BranchProtection facet is missing",
+                        {"msg": "No SBOM is attached to the latest release"},
+                    ],
+                    "text": "data.skootrs.deny",
+                }],
+            }],
+        });
+        assert_eq!(
+            rego_violations(&result),
+            Some(vec![
+                "BranchProtection facet is missing".to_string(),
+                "No SBOM is attached to the latest release".to_string(),
+            ])
+        );
+        let passed = json!({"result": [{"expressions": [{"value": []}]}]});
+        assert_eq!(rego_violations(&passed), Some(vec![]));
+        assert_eq!(rego_violations(&json!({})), None);
+    }
+
+    #[test]
+    fn test_cue_violations() {
+        let stderr = "status.latest_release.sbom: conflicting values true and false:\n    \
+                      ./policy.cue:3:30\n    ./input.json:1:200\n\
+                      status.drift: incompatible list lengths (0 and 1)\n";
+        assert_eq!(
+            cue_violations(stderr),
+            vec![
+                "status.latest_release.sbom: conflicting values true and false".to_string(),
+                "status.drift: incompatible list lengths (0 and 1)".to_string(),
+            ]
+        );
+    }
+}
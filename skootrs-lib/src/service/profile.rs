@@ -0,0 +1,122 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Profiles are read from the `<name>.yaml` files in the config's `profile_dir`, and fall back to the ones
+//! built into Skootrs. A profile is checked against the config when it's loaded, so a typo in a profile fails
+//! before a project is created with half of it applied.
+
+use std::path::Path;
+
+use skootrs_model::skootrs::{
+    profile::{Profile, BUILTIN_PROFILES},
+    Config, SkootError,
+};
+
+/// The extension of profile files.
+const PROFILE_EXTENSION: &str = "yaml";
+
+/// Loads a profile by name and checks it against the config.
+///
+/// # Errors
+///
+/// Returns an error if there's no profile by that name, its file can't be read or has fields a profile
+/// doesn't have, or it isn't valid for the config.
+pub fn load(config: &Config, name: &str) -> Result<Profile, SkootError> {
+    let file = config
+        .profile_dir
+        .as_ref()
+        .map(|dir| Path::new(dir).join(format!("{name}.{PROFILE_EXTENSION}")))
+        .filter(|file| file.exists());
+    let profile = match file {
+        Some(file) => {
+            let contents = std::fs::read_to_string(&file)
+                .map_err(|e| format!("Failed to read profile {}: {e}", file.display()))?;
+            serde_yaml::from_str(&contents)
+                .map_err(|e| format!("Failed to parse profile {}: {e}", file.display()))?
+        }
+        None => Profile::builtin(name).ok_or_else(|| {
+            format!(
+                "There's no profile named {name}, the available profiles are {}",
+                list(config).unwrap_or_default().join(", ")
+            )
+        })?,
+    };
+    profile
+        .validate(config)
+        .map_err(|e| format!("Profile {name} isn't valid: {e}"))?;
+    Ok(profile)
+}
+
+/// Returns the names of the profiles that can be loaded, the ones in the profile directory and then the
+/// built-in ones.
+///
+/// # Errors
+///
+/// Returns an error if the profile directory can't be read.
+pub fn list(config: &Config) -> Result<Vec<String>, SkootError> {
+    let mut names = Vec::new();
+    if let Some(dir) = &config.profile_dir {
+        let entries = std::fs::read_dir(dir)
+            .map_err(|e| format!("Failed to read the profile directory {dir}: {e}"))?;
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_some_and(|e| e == PROFILE_EXTENSION) {
+                if let Some(name) = path.file_stem().and_then(|s| s.to_str()) {
+                    names.push(name.to_string());
+                }
+            }
+        }
+        names.sort();
+    }
+    for name in BUILTIN_PROFILES {
+        if !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+    }
+    Ok(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use skootrs_model::skootrs::{facet::SupportedFacetType, license::SpdxLicense};
+    use tempdir::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn test_load_profiles() {
+        let dir = TempDir::new("skootrs-profile-test").unwrap();
+        std::fs::write(
+            dir.path().join("python-service.yaml"),
+            "description: A Python service\nenable: [Fuzzing]\nlicense:\n  spdx_id: MIT\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("typo.yaml"), "enabled: [Fuzzing]\n").unwrap();
+        std::fs::write(dir.path().join("undeclared.yaml"), "enable: [Allstar]\n").unwrap();
+        let config = Config {
+            profile_dir: Some(dir.path().display().to_string()),
+            ..Config::default()
+        };
+
+        let profile = load(&config, "python-service").unwrap();
+        assert_eq!(profile.enable, vec![SupportedFacetType::Fuzzing]);
+        assert_eq!(profile.license.unwrap().spdx_id, SpdxLicense::Mit);
+        assert!(load(&config, "strict-oss").is_ok());
+        assert!(load(&config, "typo").is_err());
+        assert!(load(&config, "undeclared").is_err());
+        let error = load(&config, "missing").unwrap_err().to_string();
+        assert!(error.contains("python-service, typo, undeclared, strict-oss, internal-library"));
+    }
+}
@@ -15,34 +15,96 @@
 
 #![allow(clippy::module_name_repetitions)]
 
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, HashMap},
+    error::Error,
+    fmt,
+    time::Instant,
+};
+
+use askama::Template;
+use chrono::Utc;
+use sha2::Digest;
 
-use crate::service::facet::{FacetSetParamsGenerator, RootFacetService};
+use crate::{
+    service::facet::{FacetSetParamsGenerator, RootFacetService},
+    SKOOTRS_VERSION,
+};
 
 use skootrs_model::skootrs::{
-    facet::{CommonFacetCreateParams, InitializedFacet, SourceFile},
-    FacetGetParams, FacetMapKey, InitializedProject, InitializedSource, ProjectArchiveParams,
-    ProjectCreateParams, ProjectGetParams, ProjectOutput, ProjectOutputGetParams,
-    ProjectOutputReference, ProjectOutputsListParams, ProjectUpdateParams, SkootError,
+    attestation::{AttestationVerification, AttestationVerifyParams},
+    change_request::{ChangeMode, ChangeRequestConfig, PullRequestParams},
+    events::{CDEvent, EventPredicate},
+    facet::{
+        CommonFacetCreateParams, FacetAddParams, FacetAudit, FacetAuditStatus, FacetCreateParams,
+        FacetDeleteParams, FacetDiff, FacetDiffParams, FacetDrift, FacetDriftStatus, FacetFileDiff,
+        FacetFileDiffStatus, FacetHistoryEntry, FacetHistoryGetParams, FacetPreviewParams,
+        InitializedFacet, ProjectAudit, SourceBundleFacet, SourceFile, SourceFileContent,
+        SupportedFacetType,
+    },
+    label::Labeled,
+    pinning::{is_workflow, PinningPolicy, PINNING_POLICY_PATH},
+    plan::{PlannedAction, ProjectPlan},
+    registry::FacetRegistry,
+    scan_policy::{AllowlistAudit, ScanPolicy, SCAN_POLICY_PATH},
+    CommitGrouping, DownloadedOutput, FacetGetParams, FacetListParams, FacetMapKey,
+    InitializationRollback, InitializationStep, InitializedModule, InitializedProject,
+    InitializedRepo, InitializedSource, ModuleInitializeParams, ProjectArchive,
+    ProjectArchiveParams, ProjectCreateParams, ProjectDeleteParams, ProjectGetParams,
+    ProjectMirrorParams, ProjectOutput, ProjectOutputGetParams, ProjectOutputReference,
+    ProjectOutputsDownloadParams, ProjectOutputsListParams, ProjectRelocateParams,
+    ProjectRelocation, ProjectUpdateParams, RetryPolicy, SkootError,
 };
 
 use super::{
-    ecosystem::EcosystemService, output::OutputService, repo::RepoService, source::SourceService,
+    ecosystem::{EcosystemService, LocalEcosystemService},
+    events::EventEmitter,
+    facet::LocalFacetService,
+    github_auth::GithubAuth,
+    history,
+    metrics::{self, Counter, Histogram},
+    output::{LocalOutputService, OutputService},
+    repo::{LocalRepoService, RepoService},
+    source::{self, LocalSourceService, SourceService},
+    template::TemplateVariables,
 };
 use tracing::{debug, error, info};
 
+/// The message of the commit that pushes a new project's initial source.
+const INITIAL_COMMIT_MESSAGE: &str = "Initialized project";
+
+/// The trailer recording a facet a commit applies.
+const FACET_TRAILER: &str = "Skootrs-Facet";
+
+/// The trailer recording the version of Skootrs that made a commit.
+const VERSION_TRAILER: &str = "Skootrs-Version";
+
 /// The `ProjectService` trait provides an interface for initializing and managing a Skootrs project.
 pub trait ProjectService {
-    /// Initializes a Skootrs project.
+    /// Initializes a Skootrs project. If `rollback_on_failure` is set and initialization fails partway
+    /// through, the repo and local clone that were created are deleted again.
     ///
     /// # Errors
     ///
-    /// Returns an error if the project can't be initialized for any reason.
+    /// Returns an error if the project can't be initialized for any reason. With `rollback_on_failure` set
+    /// the error is an `InitializationError` that reports what was rolled back.
     fn initialize(
         &self,
         params: ProjectCreateParams,
     ) -> impl std::future::Future<Output = Result<InitializedProject, SkootError>> + Send;
 
+    /// Plans the creation of a Skootrs project without creating it. The plan lists the API calls, commands,
+    /// files and pushes that `initialize` would make with the same params, with every facet's content rendered.
+    /// Nothing is sent to the repo host and nothing is written to the filesystem.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the params are invalid or a facet's content can't be rendered.
+    fn plan(
+        &self,
+        params: ProjectCreateParams,
+    ) -> impl std::future::Future<Output = Result<ProjectPlan, SkootError>> + Send;
+
     /// Gets an initialized project.
     ///
     /// # Errors
@@ -63,14 +125,15 @@ pub trait ProjectService {
         params: FacetGetParams,
     ) -> impl std::future::Future<Output = Result<InitializedFacet, SkootError>> + Send;
 
-    /// Lists the facets of an initialized project.
+    /// Lists the facets of an initialized project, optionally only the ones with labels matching a query like
+    /// `slsa`.
     ///
     /// # Errors
     ///
     /// Returns an error if the list of facets can't be fetched.
     fn list_facets(
         &self,
-        params: ProjectGetParams,
+        params: FacetListParams,
     ) -> impl std::future::Future<Output = Result<Vec<FacetMapKey>, SkootError>> + Send;
 
     /// Lists the outputs of an initialized project.
@@ -88,12 +151,147 @@ pub trait ProjectService {
         _params: ProjectOutputGetParams,
     ) -> impl std::future::Future<Output = Result<ProjectOutput, SkootError>> + Send;
 
+    /// Downloads one or all of the outputs of a release of an initialized project to files.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an output can't be downloaded, or doesn't match the size or checksum the release
+    /// gives for it.
+    fn outputs_download(
+        &self,
+        params: ProjectOutputsDownloadParams,
+    ) -> impl std::future::Future<Output = Result<Vec<DownloadedOutput>, SkootError>> + Send;
+
+    /// Verifies an in-toto attestation attached to a release of an initialized project against the
+    /// project's identity.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the attestation can't be fetched, isn't made of DSSE envelopes, or cosign can't be
+    /// run. An envelope cosign rejects is reported as unverified rather than as an error.
+    fn output_verify(
+        &self,
+        params: AttestationVerifyParams,
+    ) -> impl std::future::Future<Output = Result<AttestationVerification, SkootError>> + Send;
+
     fn update(
         &self,
         params: ProjectUpdateParams,
     ) -> impl std::future::Future<Output = Result<InitializedProject, SkootError>> + Send;
 
-    /// Archives an initialized project.
+    /// Adds a single facet to an initialized project. Source bundle facets are committed and pushed to the
+    /// project's repo, and API bundle facets are applied through the repo host's API. Returns the project
+    /// with the new facet recorded.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the project already has the facet or if the facet can't be initialized.
+    fn add_facet(
+        &self,
+        params: FacetAddParams,
+    ) -> impl std::future::Future<Output = Result<InitializedProject, SkootError>> + Send;
+
+    /// Previews the content a source bundle facet would have in a project created with the given params.
+    /// Nothing is sent to the repo host and nothing is written to the filesystem.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the params or the facet's options are invalid, or the facet's content can't be
+    /// rendered.
+    fn preview_facet(
+        &self,
+        params: FacetPreviewParams,
+    ) -> impl std::future::Future<Output = Result<Vec<SourceFileContent>, SkootError>> + Send;
+
+    /// Diffs the files of a source bundle facet in the project's repo against what a project update would
+    /// re-render them as, so the changes can be reviewed before they're applied. Nothing is written to the
+    /// repo or the local clone.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the project doesn't have the facet, the facet isn't one a project update
+    /// re-renders, or the files can't be rendered or fetched from the repo.
+    fn diff_facet(
+        &self,
+        params: FacetDiffParams,
+    ) -> impl std::future::Future<Output = Result<FacetDiff, SkootError>> + Send;
+
+    /// Deletes a single facet from an initialized project. The files of a source bundle facet are removed and
+    /// the removal is committed and pushed to the project's repo, and API bundle facets are reverted through
+    /// the repo host's API where possible. Returns the project without the facet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the project doesn't have the facet or if the facet can't be torn down.
+    fn delete_facet(
+        &self,
+        params: FacetDeleteParams,
+    ) -> impl std::future::Future<Output = Result<InitializedProject, SkootError>> + Send;
+
+    /// Gets a previous version of a facet's content from the project's facet history.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the project can't be fetched or the facet has no such version.
+    fn get_facet_history(
+        &self,
+        params: FacetHistoryGetParams,
+    ) -> impl std::future::Future<Output = Result<FacetHistoryEntry, SkootError>> + Send;
+
+    /// Audits every facet of an initialized project against what's live in the project's repo. Source bundle
+    /// facets are checked by fetching their files from the repo and comparing them against the recorded
+    /// hashes, and API bundle facets are checked by asking the repo host whether their settings are still
+    /// enabled.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the project can't be fetched.
+    fn audit(
+        &self,
+        params: ProjectGetParams,
+    ) -> impl std::future::Future<Output = Result<ProjectAudit, SkootError>> + Send;
+
+    /// Verifies a checkout of a project's source against the facet hashes recorded in its `.skootrs` file.
+    /// This only reads the checkout, so it can run inside the project's own CI without access to the repo
+    /// host. The default source code is left out since it belongs to the project's maintainers once the
+    /// project exists, and API bundle facets are left out since they can't be verified from a checkout.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the checkout doesn't have a readable `.skootrs` file.
+    fn verify_state(
+        &self,
+        source: InitializedSource,
+    ) -> impl std::future::Future<Output = Result<ProjectAudit, SkootError>> + Send;
+
+    /// Links a mirror on another host to an initialized project, e.g. a Github mirror of a project whose
+    /// canonical repo is on a generic git remote. The project's source is pushed to the mirror, and the API
+    /// facets are applied to the mirror if it's the repo API facets belong on. Updates keep the mirror in sync
+    /// from then on. Returns the project with the mirror recorded.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the mirror doesn't exist, is the project's own repo, or can't be pushed to.
+    fn link_mirror(
+        &self,
+        params: ProjectMirrorParams,
+    ) -> impl std::future::Future<Output = Result<InitializedProject, SkootError>> + Send;
+
+    /// Relocates an initialized project whose repo was renamed or transferred, e.g. to another organization.
+    /// The repo's new location is found by following the repo host's redirect from the old URL, and the local
+    /// clone is brought up to date from it and pointed at the new URL. Returns the old and new URLs along with
+    /// the project with its new repo recorded, which the caller writes to the project's state and the cache.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the repo doesn't exist, hasn't been renamed or transferred, or can't be pulled.
+    fn relocate(
+        &self,
+        params: ProjectRelocateParams,
+    ) -> impl std::future::Future<Output = Result<ProjectRelocation, SkootError>> + Send;
+
+    /// Archives an initialized project. If `cleanup` is set, the repo's Actions are cleaned up first and what
+    /// was cleaned up is returned with the result.
     ///
     /// # Errors
     ///
@@ -101,6 +299,17 @@ pub trait ProjectService {
     fn archive(
         &self,
         _params: ProjectArchiveParams,
+    ) -> impl std::future::Future<Output = Result<ProjectArchive, SkootError>> + Send;
+
+    /// Permanently deletes an initialized project. This deletes the project's repo, which tears down all of
+    /// its facets, along with the local clone of its source. Returns the URL of the deleted repo.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the deletion isn't confirmed or if the project can't be deleted.
+    fn delete(
+        &self,
+        params: ProjectDeleteParams,
     ) -> impl std::future::Future<Output = Result<String, SkootError>> + Send;
 }
 
@@ -119,6 +328,58 @@ pub struct LocalProjectService<
     pub source_service: SS,
     pub facet_service: FS,
     pub output_service: OS,
+    pub facet_registry: FacetRegistry,
+    /// How changes to the facets of existing projects reach their repos.
+    pub change_requests: ChangeRequestConfig,
+    /// How the changes facets make to a new project's source are split into commits.
+    pub commit_grouping: CommitGrouping,
+    /// Where the events for changes to projects are sent.
+    pub events: EventEmitter,
+    /// The local path to cloned projects, which are read from instead of the repo host's API when a
+    /// project is got with `ReadStrategy::Local` or `ReadStrategy::Auto`.
+    pub local_project_path: Option<String>,
+}
+
+/// The project service the daemon serves its REST and gRPC APIs with.
+pub type DaemonProjectService = LocalProjectService<
+    LocalRepoService,
+    LocalEcosystemService,
+    LocalSourceService,
+    LocalFacetService,
+    LocalOutputService,
+>;
+
+impl DaemonProjectService {
+    /// Returns the project service the daemon's APIs are served by, which calls the Github API with
+    /// `github_auth`. Both APIs use the same one, so a project is changed the same way whichever is called.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the template variables can't be set up.
+    pub fn for_daemon(github_auth: &GithubAuth) -> Result<Self, SkootError> {
+        Ok(Self {
+            repo_service: LocalRepoService {
+                github_auth: github_auth.clone(),
+                retry_policy: RetryPolicy::default(),
+                ..Default::default()
+            },
+            ecosystem_service: LocalEcosystemService {},
+            source_service: LocalSourceService::default(),
+            facet_service: LocalFacetService {
+                template_variables: TemplateVariables::new(&HashMap::new())?,
+                github_auth: github_auth.clone(),
+                retry_policy: RetryPolicy::default(),
+                private_vulnerability_reporting: true,
+                ..Default::default()
+            },
+            output_service: LocalOutputService {},
+            facet_registry: FacetRegistry::default(),
+            change_requests: ChangeRequestConfig::default(),
+            commit_grouping: CommitGrouping::default(),
+            events: EventEmitter::default(),
+            local_project_path: None,
+        })
+    }
 }
 
 impl<RS, ES, SS, FS, OS> ProjectService for LocalProjectService<RS, ES, SS, FS, OS>
@@ -133,76 +394,126 @@ where
         &self,
         params: ProjectCreateParams,
     ) -> Result<InitializedProject, SkootError> {
-        debug!("Starting repo initialization");
-        let initialized_repo = self
+        // Invalid names are rejected before the repo or anything else is created.
+        params.validate()?;
+        let rollback_on_failure = params.rollback_on_failure;
+        let mut progress = InitializationProgress::default();
+        let started = Instant::now();
+        let result = self.run_initialization(params, &mut progress).await;
+        let metrics = metrics::global();
+        metrics.observe(Histogram::ProjectCreationDuration, &[], started.elapsed());
+        metrics.increment(
+            if result.is_ok() {
+                Counter::ProjectsCreated
+            } else {
+                Counter::ProjectCreationFailures
+            },
+            &[],
+        );
+        match result {
+            Ok(initialized_project) => {
+                self.emit_facet_events(
+                    EventPredicate::Created,
+                    &initialized_project,
+                    initialized_project.facets.values(),
+                )
+                .await;
+                Ok(initialized_project)
+            }
+            Err(error) if rollback_on_failure => {
+                Err(SkootError::other(self.roll_back(error, progress).await))
+            }
+            Err(error) => {
+                if !progress.steps.is_empty() {
+                    error!(
+                        "Project initialization failed after it {}, which was left in place",
+                        join(&progress.steps)
+                    );
+                }
+                Err(error)
+            }
+        }
+    }
+
+    async fn plan(&self, params: ProjectCreateParams) -> Result<ProjectPlan, SkootError> {
+        params.validate()?;
+        let initialized_repo = params.repo_params.initialized_repo()?;
+        let initialized_source = InitializedSource {
+            path: params.source_params.path(&initialized_repo.name()),
+        };
+        let modules = params
+            .ecosystem_params
+            .iter()
+            .map(ModuleInitializeParams::initialized_module)
+            .collect::<Vec<_>>();
+        let mut actions = self
             .repo_service
-            .initialize(params.repo_params.clone())
-            .await?;
-        debug!("Starting source initialization");
-        let initialized_source: InitializedSource = self
-            .source_service
-            .initialize(params.source_params.clone(), initialized_repo.clone())?;
-        debug!("Starting ecosystem initialization");
-        let initialized_ecosystem = self
-            .ecosystem_service
-            .initialize(params.ecosystem_params.clone(), initialized_source.clone())?;
-        debug!("Starting facet initialization");
-        // TODO: This is ugly and this should probably be configured somewhere better, preferably outside of code.
-        let facet_set_params_generator = FacetSetParamsGenerator {};
-        let common_params = CommonFacetCreateParams {
-            project_name: params.name.clone(),
-            source: initialized_source.clone(),
-            repo: initialized_repo.clone(),
-            ecosystem: initialized_ecosystem.clone(),
+            .plan(&params.repo_params, &params.source_params.parent_path)?;
+        for module_params in &params.ecosystem_params {
+            let module = module_params.initialized_module();
+            actions.extend(
+                self.ecosystem_service
+                    .plan(
+                        &module_params.ecosystem,
+                        &module.source(&initialized_source),
+                    )?
+                    .into_iter()
+                    .map(|action| module_action(&module, action)),
+            );
+        }
+
+        let facet_set_params_generator = FacetSetParamsGenerator {
+            registry: &self.facet_registry,
         };
-        let source_facet_set_params = facet_set_params_generator
-            .generate_default_source_bundle_facet_params(&common_params)?;
-        let api_facet_set_params =
-            facet_set_params_generator.generate_default_api_bundle(&common_params)?;
-        let initialized_source_facets = self
-            .facet_service
-            .initialize_all(source_facet_set_params)
-            .await?;
-        // TODO: Figure out how to better order commits and pushes
-        self.source_service.commit_and_push_changes(
+        let common_params = CommonFacetCreateParams::new(
+            params.name.clone(),
             initialized_source.clone(),
-            "Initialized project".to_string(),
+            initialized_repo.clone(),
+            modules.clone(),
         )?;
-        let initialized_api_facets = self
-            .facet_service
-            .initialize_all(api_facet_set_params)
-            .await?;
-        // FIXME: Also add facet by name as well
-        let initialized_facets = [initialized_source_facets, initialized_api_facets]
-            .concat()
-            .into_iter()
-            .map(|f| (FacetMapKey::Type(f.facet_type()), f))
-            .collect::<HashMap<FacetMapKey, InitializedFacet>>();
-
-        info!("Completed project initialization");
+        // Planned in the same order `initialize` runs them in.
+        let source_facets_params = facet_set_params_generator
+            .generate_default_source_bundle_facet_params(&common_params)?
+            .facets_params;
+        if self.commit_grouping == CommitGrouping::Facet {
+            actions.push(PlannedAction::Commit {
+                message: ecosystem_commit_message(&modules),
+            });
+        }
+        for facet_params in &source_facets_params {
+            actions.extend(self.facet_service.plan(facet_params)?);
+            if self.commit_grouping == CommitGrouping::Facet {
+                let facet_type = facet_params.facet_type();
+                actions.push(PlannedAction::Commit {
+                    message: facet_commit_message(&facet_type),
+                });
+            }
+        }
+        let facet_types = source_facets_params
+            .iter()
+            .map(FacetCreateParams::facet_type)
+            .collect::<Vec<_>>();
+        actions.push(PlannedAction::Push {
+            message: self.initial_commit_message(&facet_types),
+        });
+        for facet_params in facet_set_params_generator
+            .generate_default_api_bundle(&common_params)?
+            .facets_params
+        {
+            actions.extend(self.facet_service.plan(&facet_params)?);
+        }
 
-        Ok(InitializedProject {
+        Ok(ProjectPlan {
+            name: params.name,
             repo: initialized_repo,
-            ecosystem: initialized_ecosystem,
+            modules,
             source: initialized_source,
-            facets: initialized_facets,
-            name: params.name.clone(),
+            actions,
         })
     }
 
     async fn get(&self, params: ProjectGetParams) -> Result<InitializedProject, SkootError> {
-        let get_repo_params = skootrs_model::skootrs::InitializedRepoGetParams {
-            repo_url: params.project_url.clone(),
-        };
-        debug!("Getting repo: {get_repo_params:?}");
-        let repo = self.repo_service.get(get_repo_params).await?;
-        // TODO: Skootrs file path should be kept as a global constant somewhere.
-        let skootrs_file = self
-            .repo_service
-            .fetch_file_content(&repo, ".skootrs")
-            .await?;
-        debug!("Skootrs file: {skootrs_file}");
-        let initialized_project: InitializedProject = serde_json::from_str(&skootrs_file)?;
+        let (initialized_project, _) = self.get_with_local_source(params).await?;
         Ok(initialized_project)
     }
 
@@ -210,48 +521,46 @@ where
         &self,
         params: FacetGetParams,
     ) -> Result<InitializedFacet, SkootError> {
-        let initialized_project = self.get(params.project_get_params.clone()).await?;
+        let (initialized_project, local_source) = self
+            .get_with_local_source(params.project_get_params.clone())
+            .await?;
         let facet = initialized_project
             .facets
             .get(&params.facet_map_key)
-            .ok_or(SkootError::from("Facet not found"))?;
+            .ok_or_else(|| SkootError::NotFound("Facet not found".to_string()))?;
 
         match facet {
             InitializedFacet::SourceBundle(s) => {
                 if let Some(source_files) = s.source_files.clone() {
-                    let source_files_content_futures = source_files.into_iter().map(|sf| async {
-                        let path = std::path::Path::new(&sf.path).join(&sf.name);
-                        // FIXME: This stripped path should probably be handled by the fetch facet content function
-                        let stripped_path = path.strip_prefix("./").unwrap_or(&path);
-
-                        let content = self
-                            .repo_service
-                            .fetch_file_content(&initialized_project.repo, stripped_path)
-                            .await;
-                        match content {
-                            Ok(c) => Ok((sf, c)),
-                            Err(e) => {
-                                error!(
-                                    "Error fetching file content for path: {stripped_path:#?}, error: {e}"
-                                );
-                                Err(e)
-                            }
+                    let mut contents = match &local_source {
+                        Some(source) => self.read_source_contents(source, s),
+                        None => {
+                            self.fetch_source_contents(&initialized_project.repo, s)
+                                .await?
                         }
-                    });
-                    let source_files_content_results =
-                        futures::future::join_all(source_files_content_futures)
-                            .await
-                            .into_iter()
-                            .collect::<Result<Vec<(SourceFile, String)>, SkootError>>()?;
-                    let source_files_content_map = source_files_content_results
+                    };
+                    let source_files_content_map = source_files
                         .into_iter()
-                        .collect::<HashMap<SourceFile, String>>();
+                        .map(|sf| {
+                            let path = source_file_path(&sf);
+                            let content = contents.remove(&path).ok_or_else(|| {
+                                SkootError::NotFound(format!(
+                                    "{path} isn't in {}",
+                                    initialized_project.repo.full_url()
+                                ))
+                            })?;
+                            Ok((sf, content))
+                        })
+                        .collect::<Result<HashMap<SourceFile, String>, SkootError>>()?;
                     Ok(InitializedFacet::SourceBundle(
                         skootrs_model::skootrs::facet::SourceBundleFacet {
                             facet_type: s.facet_type.clone(),
                             source_files: None,
                             source_files_content: Some(source_files_content_map),
                             labels: s.labels.clone(),
+                            packages: s.packages.clone(),
+                            template_version: s.template_version,
+                            generator_fingerprint: s.generator_fingerprint.clone(),
                         },
                     ))
                 } else {
@@ -265,62 +574,443 @@ where
     // TODO: A lot of this code is copied from the initialize function. This should be refactored to avoid code duplication.
     async fn update(&self, params: ProjectUpdateParams) -> Result<InitializedProject, SkootError> {
         let initialized_project = params.initialized_project.clone();
-        let initialized_repo = initialized_project.repo;
+        let api_repo = initialized_project.api_repo().clone();
+        let initialized_repo = initialized_project.repo.clone();
         let initialized_source = self.repo_service.clone_local_or_pull(
             initialized_repo.clone(),
             initialized_project.source.path.clone(),
         )?;
-        let initialized_ecosystem = initialized_project.ecosystem;
-
-        let facet_set_params_generator = FacetSetParamsGenerator {};
+        let _lock = self.source_service.lock(&initialized_source)?;
+        // The commit the update is based on. If the remote moves past it before the update is pushed, someone
+        // else changed the project concurrently and the update must be retried on top of their changes.
+        let expected_parent = self.source_service.head_commit(&initialized_source)?;
+        self.source_service
+            .reset_on_cancel(&initialized_source, &expected_parent);
+        let modules = initialized_project.modules.clone();
+
+        let facet_set_params_generator = FacetSetParamsGenerator {
+            registry: &self.facet_registry,
+        };
         let common_params = CommonFacetCreateParams {
-            project_name: initialized_project.name.clone(),
-            source: initialized_source.clone(),
-            repo: initialized_repo.clone(),
-            ecosystem: initialized_ecosystem.clone(),
+            best_practices_badge_id: initialized_project.best_practices_badge_id(),
+            artifact_signing: initialized_project.signs_artifacts(),
+            ..CommonFacetCreateParams::new(
+                initialized_project.name.clone(),
+                initialized_source.clone(),
+                initialized_repo.clone(),
+                modules.clone(),
+            )?
         };
-        let source_facet_set_params = facet_set_params_generator
+        let mut source_facet_set_params = facet_set_params_generator
             .generate_default_source_bundle_facet_params(&common_params)?;
-        let api_facet_set_params =
-            facet_set_params_generator.generate_default_api_bundle(&common_params)?;
+        // The default source code is only scaffolding for a new project. Once the project exists the code
+        // belongs to its maintainers so it must never be re-rendered over their changes.
+        source_facet_set_params
+            .facets_params
+            .retain(|facet_params| {
+                !matches!(
+                    facet_params,
+                    FacetCreateParams::SourceBundle(p)
+                        if p.facet_type == SupportedFacetType::DefaultSourceCode
+                )
+            });
+        let api_common_params = CommonFacetCreateParams {
+            repo: api_repo,
+            ..common_params.clone()
+        };
+        let mut api_facet_set_params =
+            facet_set_params_generator.generate_default_api_bundle(&api_common_params)?;
+        // Updates can be limited to specific facets, e.g. when a single facet is rolled out across an org.
+        if let Some(facet_types) = &params.facet_types {
+            for facet_set_params in [&mut source_facet_set_params, &mut api_facet_set_params] {
+                facet_set_params
+                    .facets_params
+                    .retain(|facet_params| facet_types.contains(&facet_params.facet_type()));
+            }
+        }
+        // The recorded hashes only tell us whether a facet drifted if the files are hashed before they are
+        // re-rendered.
+        let current_hashes =
+            self.current_source_hashes(&initialized_source, &initialized_project.facets);
+        let current_contents =
+            self.current_source_contents(&initialized_source, &initialized_project.facets);
         let initialized_source_facets = self
             .facet_service
             .initialize_all(source_facet_set_params)
             .await?;
-        // TODO: Figure out how to better order commits and pushes
-        self.source_service.commit_and_push_changes(
-            initialized_source.clone(),
-            "Updated facets for project".to_string(),
-        )?;
+        let drift = reconcile_source_facets(
+            &initialized_project.facets,
+            &current_hashes,
+            &initialized_source_facets,
+        );
+        for facet_drift in &drift {
+            match facet_drift.status {
+                FacetDriftStatus::UpToDate => {
+                    debug!("Facet {} is up to date", facet_drift.facet_type);
+                }
+                _ => info!(
+                    "Facet {} is {:?}: {}",
+                    facet_drift.facet_type,
+                    facet_drift.status,
+                    facet_drift.files.join(", ")
+                ),
+            }
+        }
+        for facet in outdated_templates(&initialized_project.facets, &initialized_source_facets) {
+            info!(
+                "Facet {} was generated by an older template and is regenerated with template version {}",
+                facet.facet_type(),
+                facet.template_version().unwrap_or_default()
+            );
+        }
+        if drift.iter().all(|d| d.status == FacetDriftStatus::UpToDate) {
+            info!("All source facets are up to date, nothing to push");
+            // Pushed even when the facets are up to date, since the canonical repo could have moved on
+            // without Skootrs.
+            if let Some(mirror) = &initialized_project.mirror {
+                self.source_service
+                    .push_mirror(&initialized_source, mirror)?;
+            }
+        } else {
+            self.record_facet_history(
+                &initialized_source,
+                &initialized_project.facets,
+                &drift,
+                &current_contents,
+                &expected_parent,
+            )?;
+            let changed = drift
+                .iter()
+                .filter(|d| d.status != FacetDriftStatus::UpToDate)
+                .collect::<Vec<_>>();
+            let changes = changed
+                .iter()
+                .map(|d| format!("{} is {:?}: {}", d.facet_type, d.status, d.files.join(", ")))
+                .collect::<Vec<_>>();
+            let facet_types = changed
+                .iter()
+                .map(|d| d.facet_type.clone())
+                .collect::<Vec<_>>();
+            self.publish_changes(
+                &initialized_project,
+                &initialized_source,
+                &expected_parent,
+                "Updated facets for project",
+                &changes,
+                &facet_types,
+            )
+            .await?;
+        }
         let initialized_api_facets = self
             .facet_service
             .initialize_all(api_facet_set_params)
             .await?;
+        for facet_drift in drift
+            .iter()
+            .filter(|d| d.status != FacetDriftStatus::UpToDate)
+        {
+            let predicate = if facet_drift.status == FacetDriftStatus::Added {
+                EventPredicate::Created
+            } else {
+                EventPredicate::Updated
+            };
+            self.emit_facet_events(
+                predicate,
+                &initialized_project,
+                initialized_source_facets
+                    .iter()
+                    .filter(|f| f.facet_type() == facet_drift.facet_type),
+            )
+            .await;
+        }
+        // Facets that weren't re-initialized, like the default source code, keep their recorded state.
         // FIXME: Also add facet by name as well
-        let initialized_facets = [initialized_source_facets, initialized_api_facets]
-            .concat()
-            .into_iter()
-            .map(|f| (FacetMapKey::Type(f.facet_type()), f))
-            .collect::<HashMap<FacetMapKey, InitializedFacet>>();
+        let mut initialized_facets = initialized_project.facets.clone();
+        initialized_facets.extend(
+            [initialized_source_facets, initialized_api_facets]
+                .concat()
+                .into_iter()
+                .map(|f| (FacetMapKey::Type(f.facet_type()), f)),
+        );
 
         Ok(InitializedProject {
             repo: initialized_repo,
-            ecosystem: initialized_ecosystem,
+            mirror: initialized_project.mirror,
+            modules,
             source: initialized_source,
             facets: initialized_facets,
             name: initialized_project.name.clone(),
+            skootrs_version: Some(SKOOTRS_VERSION.to_string()),
+            created_at: initialized_project.created_at,
         })
     }
 
+    async fn add_facet(&self, params: FacetAddParams) -> Result<InitializedProject, SkootError> {
+        let mut initialized_project = params.initialized_project;
+        let facet_key = FacetMapKey::Type(params.facet_type.clone());
+        if initialized_project.facets.contains_key(&facet_key) {
+            return Err(format!(
+                "Project {} already has the {} facet",
+                initialized_project.repo.full_url(),
+                params.facet_type
+            )
+            .into());
+        }
+        self.validate_facet_options(&params.facet_type, &params.options)?;
+        self.validate_facet_ecosystems(&params.facet_type, &initialized_project.modules)?;
+        let initialized_source = self.repo_service.clone_local_or_pull(
+            initialized_project.repo.clone(),
+            initialized_project.source.path.clone(),
+        )?;
+        let _lock = self.source_service.lock(&initialized_source)?;
+        let expected_parent = self.source_service.head_commit(&initialized_source)?;
+        self.source_service
+            .reset_on_cancel(&initialized_source, &expected_parent);
+
+        let facet_set_params_generator = FacetSetParamsGenerator {
+            registry: &self.facet_registry,
+        };
+        let repo = if params.facet_type.is_api_bundle() {
+            initialized_project.api_repo()
+        } else {
+            &initialized_project.repo
+        };
+        let common_params = CommonFacetCreateParams {
+            best_practices_badge_id: initialized_project.best_practices_badge_id(),
+            artifact_signing: initialized_project.signs_artifacts(),
+            ..CommonFacetCreateParams::new(
+                initialized_project.name.clone(),
+                initialized_source.clone(),
+                repo.clone(),
+                initialized_project.modules.clone(),
+            )?
+        };
+        let facet_params = facet_set_params_generator.generate_facet_params(
+            &common_params,
+            &params.facet_type,
+            &params.options,
+        );
+        let initialized_facet = self.facet_service.initialize(facet_params).await?;
+        if let InitializedFacet::SourceBundle(_) = initialized_facet {
+            self.publish_changes(
+                &initialized_project,
+                &initialized_source,
+                &expected_parent,
+                &format!("Added {} facet to project", params.facet_type),
+                &[format!("{} is added", params.facet_type)],
+                std::slice::from_ref(&params.facet_type),
+            )
+            .await?;
+        }
+        info!(
+            "Added {} facet to {}",
+            params.facet_type,
+            initialized_project.repo.full_url()
+        );
+        if params.facet_type == SupportedFacetType::BestPracticesBadge {
+            info!("Run `skootrs project update` to show the badge in the README");
+        }
+        if params.facet_type == SupportedFacetType::ArtifactSigning {
+            info!("Run `skootrs project update` to tell how to verify releases in the README and SECURITY-INSIGHTS");
+        }
+        self.emit_facet_events(
+            EventPredicate::Created,
+            &initialized_project,
+            std::iter::once(&initialized_facet),
+        )
+        .await;
+
+        initialized_project.source = initialized_source;
+        initialized_project.facets.insert(
+            FacetMapKey::Type(initialized_facet.facet_type()),
+            initialized_facet,
+        );
+        Ok(initialized_project)
+    }
+
+    async fn preview_facet(
+        &self,
+        params: FacetPreviewParams,
+    ) -> Result<Vec<SourceFileContent>, SkootError> {
+        let project_params = params.project_params;
+        project_params.validate()?;
+        self.validate_facet_options(&params.facet_type, &params.options)?;
+        let modules = project_params
+            .ecosystem_params
+            .iter()
+            .map(ModuleInitializeParams::initialized_module)
+            .collect::<Vec<_>>();
+        self.validate_facet_ecosystems(&params.facet_type, &modules)?;
+        let initialized_repo = project_params.repo_params.initialized_repo()?;
+        let common_params = CommonFacetCreateParams::new(
+            project_params.name.clone(),
+            InitializedSource {
+                path: project_params.source_params.path(&initialized_repo.name()),
+            },
+            initialized_repo,
+            modules,
+        )?;
+        let facet_params = FacetSetParamsGenerator {
+            registry: &self.facet_registry,
+        }
+        .generate_facet_params(&common_params, &params.facet_type, &params.options);
+        self.facet_service.render(&facet_params)
+    }
+
+    async fn diff_facet(&self, params: FacetDiffParams) -> Result<FacetDiff, SkootError> {
+        let initialized_project = params.initialized_project;
+        let Some(facet) = initialized_project.facets.get(&params.facet_map_key) else {
+            return Err(SkootError::NotFound(format!(
+                "Project {} doesn't have a facet with key {}",
+                initialized_project.repo.full_url(),
+                params.facet_map_key
+            )));
+        };
+        let facet_type = facet.facet_type();
+        if facet_type.is_api_bundle() {
+            return Err(format!(
+                "Facet {facet_type} is enabled through the repo host's API so it has no files to diff"
+            )
+            .into());
+        }
+        if facet_type == SupportedFacetType::DefaultSourceCode {
+            return Err("The default source code belongs to the project's maintainers once the project exists, so a project update never re-renders it".into());
+        }
+        // The facet is rendered the same way `update` renders it, so the diff is exactly what an update
+        // would push.
+        let common_params = CommonFacetCreateParams {
+            best_practices_badge_id: initialized_project.best_practices_badge_id(),
+            artifact_signing: initialized_project.signs_artifacts(),
+            ..CommonFacetCreateParams::new(
+                initialized_project.name.clone(),
+                initialized_project.source.clone(),
+                initialized_project.repo.clone(),
+                initialized_project.modules.clone(),
+            )?
+        };
+        let facet_params = FacetSetParamsGenerator {
+            registry: &self.facet_registry,
+        }
+        .generate_default_source_bundle_facet_params(&common_params)?
+        .facets_params
+        .into_iter()
+        .find(|facet_params| facet_params.facet_type() == facet_type)
+        .ok_or_else(|| {
+            SkootError::from(format!(
+                "Facet {facet_type} isn't in the facet registry's defaults, so a project update doesn't re-render it"
+            ))
+        })?;
+        let rendered = self.facet_service.render(&facet_params)?;
+        let paths = rendered
+            .iter()
+            .map(|file| {
+                let path = source_file_path(&SourceFile {
+                    name: file.name.clone(),
+                    path: file.path.clone(),
+                    hash: String::new(),
+                });
+                path.strip_prefix("./").unwrap_or(&path).to_string()
+            })
+            .collect::<Vec<_>>();
+        let mut current_contents = self
+            .repo_service
+            .fetch_file_contents(&initialized_project.repo, &paths)
+            .await?;
+        let files = paths
+            .into_iter()
+            .zip(rendered)
+            .map(|(path, file)| {
+                diff_file(
+                    current_contents.remove(&path).as_deref(),
+                    &file.content,
+                    path,
+                )
+            })
+            .collect();
+        Ok(FacetDiff { facet_type, files })
+    }
+
+    async fn delete_facet(
+        &self,
+        params: FacetDeleteParams,
+    ) -> Result<InitializedProject, SkootError> {
+        let mut initialized_project = params.initialized_project;
+        let Some(facet) = initialized_project.facets.remove(&params.facet_map_key) else {
+            return Err(format!(
+                "Project {} doesn't have a facet with key {}",
+                initialized_project.repo.full_url(),
+                params.facet_map_key
+            )
+            .into());
+        };
+        let initialized_source = self.repo_service.clone_local_or_pull(
+            initialized_project.repo.clone(),
+            initialized_project.source.path.clone(),
+        )?;
+        let _lock = self.source_service.lock(&initialized_source)?;
+        let expected_parent = self.source_service.head_commit(&initialized_source)?;
+        self.source_service
+            .reset_on_cancel(&initialized_source, &expected_parent);
+
+        self.facet_service
+            .delete(&initialized_source, initialized_project.api_repo(), &facet)
+            .await?;
+        if let InitializedFacet::SourceBundle(_) = facet {
+            self.publish_changes(
+                &initialized_project,
+                &initialized_source,
+                &expected_parent,
+                &format!("Removed {} facet from project", facet.facet_type()),
+                &[format!("{} is removed", facet.facet_type())],
+                &[facet.facet_type()],
+            )
+            .await?;
+        }
+        info!(
+            "Deleted {} facet from {}",
+            facet.facet_type(),
+            initialized_project.repo.full_url()
+        );
+
+        initialized_project.source = initialized_source;
+        Ok(initialized_project)
+    }
+
     async fn outputs_list(
         &self,
         params: ProjectOutputsListParams,
     ) -> Result<Vec<ProjectOutputReference>, SkootError> {
-        self.output_service.list(params).await
+        let project_url = params.initialized_project.repo.full_url();
+        let tag = params.release.tag();
+        let is_release = params.workflow_run.is_none();
+        let outputs = self.output_service.list(params).await?;
+        // The artifacts of a workflow run aren't a release.
+        if !is_release {
+            return Ok(outputs);
+        }
+        let names = outputs
+            .iter()
+            .map(|output| output.name.clone())
+            .collect::<Vec<_>>();
+        self.events
+            .emit(&CDEvent::release_observed(
+                &project_url,
+                tag.as_deref(),
+                &names,
+            ))
+            .await;
+        Ok(outputs)
     }
 
-    async fn list_facets(&self, params: ProjectGetParams) -> Result<Vec<FacetMapKey>, SkootError> {
-        Ok(self.get(params).await?.facets.keys().cloned().collect())
+    async fn list_facets(&self, params: FacetListParams) -> Result<Vec<FacetMapKey>, SkootError> {
+        Ok(self
+            .get(params.project_get_params)
+            .await?
+            .facets
+            .iter()
+            .filter(|(_, facet)| facet.matches_labels(&params.labels))
+            .map(|(key, _)| key.clone())
+            .collect())
     }
 
     async fn output_get(
@@ -330,239 +1020,2273 @@ where
         self.output_service.get(params).await
     }
 
-    async fn archive(&self, params: ProjectArchiveParams) -> Result<String, SkootError> {
-        self.repo_service
-            .archive(params.initialized_project.repo)
-            .await
+    async fn outputs_download(
+        &self,
+        params: ProjectOutputsDownloadParams,
+    ) -> Result<Vec<DownloadedOutput>, SkootError> {
+        self.output_service.download(params).await
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use std::path::Path;
-
-    use skootrs_model::skootrs::{
-        facet::{
-            APIBundleFacet, APIContent, FacetCreateParams, FacetSetCreateParams, SourceBundleFacet,
-            SupportedFacetType,
-        },
-        label::Label,
-        EcosystemInitializeParams, GithubRepoParams, GithubUser, GoParams, InitializedEcosystem,
-        InitializedGithubRepo, InitializedGo, InitializedMaven, InitializedRepo, ProjectOutputType,
-        RepoCreateParams, SourceInitializeParams,
-    };
+    async fn output_verify(
+        &self,
+        params: AttestationVerifyParams,
+    ) -> Result<AttestationVerification, SkootError> {
+        self.output_service.verify(params).await
+    }
 
-    use super::*;
-    struct MockRepoService;
-    struct MockEcosystemService;
-    struct MockSourceService;
-    struct MockFacetService;
-    struct MockOutputService;
+    async fn get_facet_history(
+        &self,
+        params: FacetHistoryGetParams,
+    ) -> Result<FacetHistoryEntry, SkootError> {
+        let initialized_project = self.get(params.project_get_params).await?;
+        // History is read from the local clone since the entries are compressed.
+        let initialized_source = self
+            .repo_service
+            .clone_local_or_pull(initialized_project.repo, initialized_project.source.path)?;
+        history::read(&initialized_source, &params.facet_type, params.version)
+    }
 
-    impl RepoService for MockRepoService {
-        async fn initialize(
-            &self,
-            params: RepoCreateParams,
-        ) -> Result<InitializedRepo, SkootError> {
-            let RepoCreateParams::Github(inner_params) = params;
-
-            // Special case for testing error handling
-            if inner_params.name == "error" {
-                return Err("Error".into());
+    async fn audit(&self, params: ProjectGetParams) -> Result<ProjectAudit, SkootError> {
+        let initialized_project = self.get(params).await?;
+        let repo = &initialized_project.repo;
+        let api_repo = initialized_project.api_repo();
+        let facets = &initialized_project.facets;
+        let best_practices_badge = match initialized_project.best_practices_badge_id() {
+            Some(id) => self
+                .facet_service
+                .best_practices_badge(id)
+                .await
+                .map_err(|e| error!("Failed to get OpenSSF Best Practices Badge {id}: {e}"))
+                .ok(),
+            None => None,
+        };
+        let badge = best_practices_badge.as_ref();
+        let facet_audits = facets.values().map(|facet| async move {
+            match facet {
+                // The pinning policy covers the workflows of every facet, not just the facet's own files.
+                InitializedFacet::SourceBundle(s)
+                    if s.facet_type == SupportedFacetType::PinnedDependencies =>
+                {
+                    let mut contents = HashMap::new();
+                    for facet in facets.values() {
+                        if let InitializedFacet::SourceBundle(f) = facet {
+                            contents.extend(self.fetch_audited_contents(repo, f).await);
+                        }
+                    }
+                    audit_source_bundle(s, &contents)
+                }
+                InitializedFacet::SourceBundle(s) => {
+                    let contents = self.fetch_audited_contents(repo, s).await;
+                    audit_source_bundle(s, &contents)
+                }
+                InitializedFacet::APIBundle(a) if a.skipped_reason.is_some() => FacetAudit {
+                    facet_type: a.facet_type.clone(),
+                    status: FacetAuditStatus::Unverified,
+                    files: vec![],
+                },
+                // A project only complies once it has earned at least the passing badge.
+                InitializedFacet::APIBundle(a)
+                    if a.facet_type == SupportedFacetType::BestPracticesBadge =>
+                {
+                    let status = match badge {
+                        Some(badge) if badge.badge_level.is_earned() => FacetAuditStatus::Compliant,
+                        Some(_) => FacetAuditStatus::Missing,
+                        None => FacetAuditStatus::Unverified,
+                    };
+                    FacetAudit {
+                        facet_type: a.facet_type.clone(),
+                        status,
+                        files: vec![],
+                    }
+                }
+                InitializedFacet::APIBundle(a) => {
+                    let status = match self
+                        .facet_service
+                        .verify_api_bundle(api_repo, &a.facet_type)
+                        .await
+                    {
+                        Ok(true) => FacetAuditStatus::Compliant,
+                        Ok(false) => FacetAuditStatus::Missing,
+                        Err(e) => {
+                            error!("Failed to verify facet {}: {e}", a.facet_type);
+                            FacetAuditStatus::Unverified
+                        }
+                    };
+                    FacetAudit {
+                        facet_type: a.facet_type.clone(),
+                        status,
+                        files: vec![],
+                    }
+                }
             }
+        });
+        let mut facets = futures::future::join_all(facet_audits).await;
+        facets.sort_by_key(|f| f.facet_type.to_string());
+        // A project without a scan policy has nothing allowlisted.
+        let scan_policy = self
+            .repo_service
+            .fetch_file_content(repo, SCAN_POLICY_PATH)
+            .await
+            .map_err(|e| debug!("Failed to fetch {SCAN_POLICY_PATH}: {e}"))
+            .ok();
+        Ok(ProjectAudit {
+            project_url: repo.full_url(),
+            facets,
+            allowlist: audit_scan_policy(scan_policy.as_deref())?,
+            best_practices_badge,
+        })
+    }
 
-            let initialized_repo = InitializedRepo::Github(InitializedGithubRepo {
-                name: inner_params.name,
-                organization: inner_params.organization,
-            });
+    async fn verify_state(&self, source: InitializedSource) -> Result<ProjectAudit, SkootError> {
+        let skootrs_file = self
+            .source_service
+            .read_file(&source, "./", ".skootrs".to_string())?;
+        let initialized_project: InitializedProject = serde_json::from_str(&skootrs_file)?;
+        let contents = self.current_source_contents(&source, &initialized_project.facets);
+        let mut facets: Vec<FacetAudit> = initialized_project
+            .facets
+            .values()
+            .filter_map(|facet| match facet {
+                InitializedFacet::SourceBundle(s)
+                    if s.facet_type != SupportedFacetType::DefaultSourceCode =>
+                {
+                    Some(audit_source_bundle(s, &contents))
+                }
+                _ => None,
+            })
+            .collect();
+        facets.sort_by_key(|f| f.facet_type.to_string());
+        let scan_policy = self
+            .source_service
+            .read_file(&source, "./", SCAN_POLICY_PATH.to_string())
+            .ok();
+        Ok(ProjectAudit {
+            project_url: initialized_project.repo.full_url(),
+            facets,
+            allowlist: audit_scan_policy(scan_policy.as_deref())?,
+            best_practices_badge: None,
+        })
+    }
 
-            Ok(initialized_repo)
+    async fn link_mirror(
+        &self,
+        params: ProjectMirrorParams,
+    ) -> Result<InitializedProject, SkootError> {
+        let mut initialized_project = params.initialized_project;
+        let mirror = self
+            .repo_service
+            .get(skootrs_model::skootrs::InitializedRepoGetParams {
+                repo_url: params.mirror_url,
+            })
+            .await?;
+        if mirror.full_url() == initialized_project.repo.full_url() {
+            return Err(format!("{} can't be a mirror of itself", mirror.full_url()).into());
         }
-
-        fn clone_local(
-            &self,
-            initialized_repo: InitializedRepo,
-            path: String,
-        ) -> Result<InitializedSource, SkootError> {
-            let InitializedRepo::Github(inner_repo) = initialized_repo;
-
-            if inner_repo.name == "error" {
-                return Err("Error".into());
-            }
-
-            let initialized_source = InitializedSource {
-                path: format!("{}/{}", path, inner_repo.name),
+        let initialized_source = self.repo_service.clone_local_or_pull(
+            initialized_project.repo.clone(),
+            initialized_project.source.path.clone(),
+        )?;
+        let _lock = self.source_service.lock(&initialized_source)?;
+        self.source_service
+            .push_mirror(&initialized_source, &mirror)?;
+        let mirror_url = mirror.full_url();
+        let previous_api_repo = initialized_project.api_repo().full_url();
+        initialized_project.mirror = Some(mirror);
+        initialized_project.source = initialized_source;
+
+        // The API facets only have to be applied again if they moved over to the mirror.
+        if initialized_project.api_repo().full_url() != previous_api_repo {
+            let facet_set_params_generator = FacetSetParamsGenerator {
+                registry: &self.facet_registry,
             };
-
-            Ok(initialized_source)
+            let common_params = CommonFacetCreateParams::new(
+                initialized_project.name.clone(),
+                initialized_project.source.clone(),
+                initialized_project.api_repo().clone(),
+                initialized_project.modules.clone(),
+            )?;
+            let api_facet_set_params =
+                facet_set_params_generator.generate_default_api_bundle(&common_params)?;
+            let initialized_api_facets = self
+                .facet_service
+                .initialize_all(api_facet_set_params)
+                .await?;
+            initialized_project.facets.extend(
+                initialized_api_facets
+                    .into_iter()
+                    .map(|f| (FacetMapKey::Type(f.facet_type()), f)),
+            );
         }
+        info!(
+            "Linked mirror {mirror_url} to {}",
+            initialized_project.repo.full_url()
+        );
 
-        fn clone_local_or_pull(
-            &self,
-            initialized_repo: InitializedRepo,
-            path: String,
-        ) -> Result<InitializedSource, SkootError> {
-            self.clone_local(initialized_repo, path)
-        }
+        Ok(initialized_project)
+    }
 
-        async fn get(
-            &self,
-            params: skootrs_model::skootrs::InitializedRepoGetParams,
-        ) -> Result<InitializedRepo, SkootError> {
-            let repo_url = params.repo_url.clone();
-            if repo_url == "error" {
-                return Err("Error".into());
-            }
+    async fn relocate(
+        &self,
+        params: ProjectRelocateParams,
+    ) -> Result<ProjectRelocation, SkootError> {
+        let mut initialized_project = params.initialized_project;
+        let old_url = initialized_project.repo.full_url();
+        let repo = self
+            .repo_service
+            .get(skootrs_model::skootrs::InitializedRepoGetParams {
+                repo_url: old_url.clone(),
+            })
+            .await?;
+        let new_url = repo.full_url();
+        // Github URLs aren't case sensitive, so a repo that's only spelled differently wasn't moved.
+        if new_url.eq_ignore_ascii_case(&old_url) {
+            return Err(format!("{old_url} hasn't been renamed or transferred").into());
+        }
+        // Github keeps redirecting the old URL, so the existing clone can still be pulled before its remote is
+        // pointed at the new one.
+        initialized_project.source = self
+            .repo_service
+            .clone_local_or_pull(repo.clone(), initialized_project.source.path.clone())?;
+        self.source_service
+            .set_remote_url(&initialized_project.source, &new_url)?;
+        initialized_project.repo = repo;
+        info!("Relocated {old_url} to {new_url}");
+
+        Ok(ProjectRelocation {
+            old_url,
+            new_url,
+            project: initialized_project,
+            pull_request: None,
+        })
+    }
 
-            let initialized_repo = InitializedRepo::Github(InitializedGithubRepo {
-                name: "test".to_string(),
-                organization: GithubUser::User("testuser".to_string()),
-            });
+    async fn archive(&self, params: ProjectArchiveParams) -> Result<ProjectArchive, SkootError> {
+        let repo = params.initialized_project.repo;
+        let cleanup = if params.cleanup {
+            Some(self.repo_service.cleanup_actions(&repo).await?)
+        } else {
+            None
+        };
+        let project_url = self.repo_service.archive(repo).await?;
+        self.events
+            .emit(&CDEvent::project_archived(&project_url))
+            .await;
+        Ok(ProjectArchive {
+            project_url,
+            cleanup,
+        })
+    }
 
-            Ok(initialized_repo)
+    async fn delete(&self, params: ProjectDeleteParams) -> Result<String, SkootError> {
+        if !params.confirm {
+            return Err(SkootError::from(
+                "Deleting a project is permanent and must be confirmed",
+            ));
+        }
+        let initialized_project = params.initialized_project;
+        // A project that was never cloned here has no source to lock or delete.
+        let cloned = std::path::Path::new(&initialized_project.source.path).exists();
+        let lock = cloned
+            .then(|| self.source_service.lock(&initialized_project.source))
+            .transpose()?;
+        let url = self.repo_service.delete(initialized_project.repo).await?;
+        // The lock lives in the source directory so it has to be released before the directory is deleted.
+        drop(lock);
+        if cloned {
+            self.source_service.delete(initialized_project.source)?;
         }
+        info!("Deleted project {url}");
+        Ok(url)
+    }
+}
 
-        async fn fetch_file_content<P: AsRef<std::path::Path> + Send>(
-            &self,
-            _initialized_repo: &InitializedRepo,
-            path: P,
-        ) -> Result<String, SkootError> {
-            if path.as_ref().to_str().unwrap() == "error" {
-                return Err("Error".into());
+impl<RS, ES, SS, FS, OS> LocalProjectService<RS, ES, SS, FS, OS>
+where
+    RS: RepoService,
+    ES: EcosystemService,
+    SS: SourceService,
+    FS: RootFacetService,
+    OS: OutputService,
+{
+    /// Checks that a facet can be added, and the options it's added with against the options schema of its
+    /// registry entry. Facets that aren't declared in the registry don't take any options.
+    fn validate_facet_options(
+        &self,
+        facet_type: &SupportedFacetType,
+        options: &BTreeMap<String, serde_json::Value>,
+    ) -> Result<(), SkootError> {
+        if !FacetRegistry::is_implemented(facet_type) {
+            return Err(
+                format!("Facet {facet_type} isn't implemented yet, so it can't be added").into(),
+            );
+        }
+        match self.facet_registry.entry(facet_type) {
+            Some(entry) => entry.validate_options(options),
+            None if !options.is_empty() => {
+                Err(format!("Facet {facet_type} doesn't take any options").into())
             }
-
-            Ok("Worked".to_string())
+            None => Ok(()),
         }
+    }
 
-        async fn archive(&self, initialized_repo: InitializedRepo) -> Result<String, SkootError> {
-            Ok(initialized_repo.full_url())
+    /// Checks that a facet supports the ecosystem of at least one of the project's modules, since a facet
+    /// that's specific to some ecosystems, like `Container`, can't be generated for the others.
+    fn validate_facet_ecosystems(
+        &self,
+        facet_type: &SupportedFacetType,
+        modules: &[InitializedModule],
+    ) -> Result<(), SkootError> {
+        match self.facet_registry.entry(facet_type) {
+            Some(entry)
+                if !entry.ecosystems.is_empty()
+                    && !modules.iter().any(|m| entry.supports(&m.ecosystem)) =>
+            {
+                Err(format!(
+                    "Facet {facet_type} is only supported for {} projects",
+                    entry.ecosystems.join(", ")
+                )
+                .into())
+            }
+            _ => Ok(()),
         }
     }
 
-    impl EcosystemService for MockEcosystemService {
-        fn initialize(
-            &self,
-            params: EcosystemInitializeParams,
-            _source: InitializedSource,
-        ) -> Result<InitializedEcosystem, SkootError> {
-            let initialized_ecosystem = match params {
-                EcosystemInitializeParams::Go(g) => {
-                    if g.host == "error" {
-                        return Err("Error".into());
-                    }
-                    InitializedEcosystem::Go(InitializedGo {
-                        name: g.name,
-                        host: g.host,
-                    })
-                }
-                EcosystemInitializeParams::Maven(m) => {
-                    if m.group_id == "error" {
-                        return Err("Error".into());
-                    }
-                    InitializedEcosystem::Maven(InitializedMaven {
-                        group_id: m.group_id,
-                        artifact_id: m.artifact_id,
-                    })
-                }
-            };
-
-            Ok(initialized_ecosystem)
+    /// Emits an event for each of the facets of a project.
+    async fn emit_facet_events<'a>(
+        &self,
+        predicate: EventPredicate,
+        initialized_project: &InitializedProject,
+        facets: impl Iterator<Item = &'a InitializedFacet> + Send,
+    ) {
+        let project_url = initialized_project.repo.full_url();
+        for facet in facets {
+            self.events
+                .emit(&CDEvent::facet(
+                    predicate,
+                    &project_url,
+                    &facet.facet_type(),
+                    &facet.labels(),
+                ))
+                .await;
         }
     }
 
-    impl SourceService for MockSourceService {
-        fn initialize(
-            &self,
-            params: skootrs_model::skootrs::SourceInitializeParams,
-            initialized_repo: InitializedRepo,
-        ) -> Result<InitializedSource, SkootError> {
-            if params.parent_path == "error" {
-                return Err("Error".into());
+    /// Runs the steps of initializing a project, recording each one that completes in `progress` so a
+    /// failure can be rolled back.
+    async fn run_initialization(
+        &self,
+        params: ProjectCreateParams,
+        progress: &mut InitializationProgress,
+    ) -> Result<InitializedProject, SkootError> {
+        debug!("Starting repo initialization");
+        let initialized_repo = self
+            .repo_service
+            .initialize(params.repo_params.clone())
+            .await?;
+        progress.repo = Some(initialized_repo.clone());
+        progress.steps.push(InitializationStep::RepoCreated);
+        debug!("Starting source initialization");
+        let initialized_source: InitializedSource = self
+            .source_service
+            .initialize(params.source_params.clone(), initialized_repo.clone())?;
+        progress.source = Some(initialized_source.clone());
+        progress.steps.push(InitializationStep::SourceCloned);
+        // Held until the project is fully initialized so concurrent operations on the same project fail fast.
+        let _lock = self.source_service.lock(&initialized_source)?;
+        debug!("Starting ecosystem initialization");
+        let mut modules = vec![];
+        for module_params in &params.ecosystem_params {
+            let module_source = module_params
+                .initialized_module()
+                .source(&initialized_source);
+            let initialized_ecosystem = self
+                .ecosystem_service
+                .initialize(module_params.ecosystem.clone(), module_source)?;
+            modules.push(InitializedModule {
+                path: module_params.path.clone(),
+                ecosystem: initialized_ecosystem,
+            });
+        }
+        progress
+            .steps
+            .push(InitializationStep::EcosystemInitialized);
+        if self.commit_grouping == CommitGrouping::Facet {
+            self.source_service.commit_changes(
+                &initialized_source,
+                None,
+                &ecosystem_commit_message(&modules),
+            )?;
+        }
+        debug!("Starting facet initialization");
+        // TODO: This is ugly and this should probably be configured somewhere better, preferably outside of code.
+        let facet_set_params_generator = FacetSetParamsGenerator {
+            registry: &self.facet_registry,
+        };
+        let common_params = CommonFacetCreateParams::new(
+            params.name.clone(),
+            initialized_source.clone(),
+            initialized_repo.clone(),
+            modules.clone(),
+        )?;
+        let source_facet_set_params = facet_set_params_generator
+            .generate_default_source_bundle_facet_params(&common_params)?;
+        let api_facet_set_params =
+            facet_set_params_generator.generate_default_api_bundle(&common_params)?;
+        let initialized_source_facets = self
+            .facet_service
+            .initialize_all(source_facet_set_params)
+            .await?;
+        progress
+            .steps
+            .push(InitializationStep::SourceFacetsInitialized);
+        if self.commit_grouping == CommitGrouping::Facet {
+            // The facets are initialized concurrently, so their files are committed once they're all written.
+            for facet in &initialized_source_facets {
+                if let InitializedFacet::SourceBundle(facet) = facet {
+                    let paths = facet
+                        .source_files
+                        .iter()
+                        .flatten()
+                        .map(|sf| source_file_path(sf).trim_start_matches("./").to_string())
+                        .collect::<Vec<_>>();
+                    self.source_service.commit_changes(
+                        &initialized_source,
+                        Some(&paths),
+                        &facet_commit_message(&facet.facet_type),
+                    )?;
+                }
             }
+        }
+        let facet_types = initialized_source_facets
+            .iter()
+            .map(InitializedFacet::facet_type)
+            .collect::<Vec<_>>();
+        // Whatever is left, e.g. every change when the whole bundle is a single commit.
+        self.source_service.commit_and_push_changes(
+            initialized_source.clone(),
+            self.initial_commit_message(&facet_types),
+        )?;
+        // API facets like branch protection can lock the repo, so they must only run once the initial
+        // push has actually landed on the remote.
+        self.source_service.verify_push(&initialized_source)?;
+        progress.steps.push(InitializationStep::InitialCommitPushed);
+        let initialized_api_facets = self
+            .facet_service
+            .initialize_all(api_facet_set_params)
+            .await?;
+        // FIXME: Also add facet by name as well
+        let initialized_facets = [initialized_source_facets, initialized_api_facets]
+            .concat()
+            .into_iter()
+            .map(|f| (FacetMapKey::Type(f.facet_type()), f))
+            .collect::<HashMap<FacetMapKey, InitializedFacet>>();
 
-            let repo_name = match initialized_repo {
-                InitializedRepo::Github(g) => g.name,
-            };
+        info!("Completed project initialization");
 
-            let initialized_source = InitializedSource {
-                path: format!("{}/{}", params.parent_path, repo_name),
-            };
+        Ok(InitializedProject {
+            repo: initialized_repo,
+            mirror: None,
+            modules,
+            source: initialized_source,
+            facets: initialized_facets,
+            name: params.name.clone(),
+            skootrs_version: Some(SKOOTRS_VERSION.to_string()),
+            created_at: Some(chrono::Utc::now()),
+        })
+    }
 
-            Ok(initialized_source)
+    /// Returns the message of the commit that pushes a new project's initial source. It records every source
+    /// bundle facet when the bundle is a single commit, and none when each facet has a commit of its own.
+    fn initial_commit_message(&self, facet_types: &[SupportedFacetType]) -> String {
+        match self.commit_grouping {
+            CommitGrouping::Facet => commit_message(INITIAL_COMMIT_MESSAGE, &[]),
+            CommitGrouping::Bundle => commit_message(INITIAL_COMMIT_MESSAGE, facet_types),
         }
+    }
 
-        fn commit_and_push_changes(
-            &self,
-            _source: InitializedSource,
-            message: String,
-        ) -> Result<(), SkootError> {
-            if message == "error" {
-                return Err("Error".into());
+    /// Undoes the completed steps of a failed initialization, newest first. A step that can't be undone is
+    /// recorded instead of stopping the rollback.
+    async fn roll_back(
+        &self,
+        error: SkootError,
+        progress: InitializationProgress,
+    ) -> InitializationError {
+        let mut rollback = InitializationRollback {
+            completed_steps: progress.steps,
+            ..Default::default()
+        };
+        if let Some(source) = progress.source {
+            match self.source_service.delete(source.clone()) {
+                Ok(()) => rollback
+                    .rolled_back
+                    .push(format!("deleted local clone {}", source.path)),
+                Err(e) => rollback
+                    .failures
+                    .push(format!("local clone {}: {e}", source.path)),
             }
-
-            Ok(())
         }
-
-        fn write_file<P: AsRef<std::path::Path>, C: AsRef<[u8]>>(
-            &self,
-            _source: InitializedSource,
-            _path: P,
-            name: String,
-            _contents: C,
-        ) -> Result<(), SkootError> {
-            if name == "error" {
-                return Err("Error".into());
+        match progress.repo {
+            // A generic git remote existed before, so it isn't Skootrs' to delete. Only a push to it is left.
+            Some(InitializedRepo::GenericGit(g)) => {
+                if rollback
+                    .completed_steps
+                    .contains(&InitializationStep::InitialCommitPushed)
+                {
+                    rollback.failures.push(format!(
+                        "git remote {}: the initial commit has to be reverted by hand",
+                        g.remote_url
+                    ));
+                }
             }
+            Some(repo) => match self.repo_service.delete(repo.clone()).await {
+                Ok(url) => rollback.rolled_back.push(format!("deleted repo {url}")),
+                Err(e) => rollback
+                    .failures
+                    .push(format!("repo {}: {e}", repo.full_url())),
+            },
+            None => {}
+        }
+        InitializationError { error, rollback }
+    }
 
-            Ok(())
+    /// Gets the changes made to a project's source onto its repo. They're proposed as a pull request when
+    /// that's the configured change mode, since pushing to a protected default branch is refused, and pushed
+    /// to the default branch directly otherwise. The pull request's branch stays checked out so the project's
+    /// state is committed to it too.
+    async fn publish_changes(
+        &self,
+        initialized_project: &InitializedProject,
+        source: &InitializedSource,
+        expected_parent: &str,
+        title: &str,
+        changes: &[String],
+        facet_types: &[SupportedFacetType],
+    ) -> Result<(), SkootError> {
+        #[derive(Template)]
+        #[template(path = "pull_request.md", escape = "none")]
+        struct PullRequestTemplateParams<'a> {
+            project_name: &'a str,
+            title: &'a str,
+            changes: &'a [String],
         }
 
-        fn read_file<P: AsRef<std::path::Path>>(
-            &self,
-            _source: &InitializedSource,
-            _path: P,
-            name: String,
-        ) -> Result<String, SkootError> {
-            if name == "error" {
-                return Err("Error".into());
+        self.source_service
+            .verify_remote_head(source, expected_parent)?;
+        // Skootrs can only open pull requests on Github.
+        if self.change_requests.mode == ChangeMode::Push
+            || !matches!(initialized_project.repo, InitializedRepo::Github(_))
+        {
+            self.source_service
+                .commit_and_push_changes(source.clone(), commit_message(title, facet_types))?;
+            self.source_service.verify_push(source)?;
+            if let Some(mirror) = &initialized_project.mirror {
+                self.source_service.push_mirror(source, mirror)?;
             }
+            return Ok(());
+        }
 
-            Ok("Worked".to_string())
+        let branch = self
+            .change_requests
+            .branch_name(&branch_slug(title), Utc::now().timestamp());
+        let base = self.source_service.commit_and_push_branch(
+            source.clone(),
+            &branch,
+            commit_message(title, facet_types),
+        )?;
+        self.source_service.verify_push(source)?;
+        let body = PullRequestTemplateParams {
+            project_name: &initialized_project.name,
+            title,
+            changes,
         }
+        .render()
+        .map_err(SkootError::template)?;
+        let url = self
+            .repo_service
+            .open_pull_request(
+                &initialized_project.repo,
+                PullRequestParams {
+                    head: branch,
+                    base,
+                    title: title.to_string(),
+                    body,
+                    auto_merge: self.change_requests.auto_merge,
+                },
+            )
+            .await?;
+        info!(
+            "Proposed the changes to {} in {url}",
+            initialized_project.repo.full_url()
+        );
+        Ok(())
+    }
 
-        fn hash_file<P: AsRef<Path>>(
+    /// Hashes the files of the recorded source bundle facets as they currently are in the source. Files that
+    /// can't be hashed, e.g. because they were deleted, are left out.
+    fn current_source_hashes(
+        &self,
+        source: &InitializedSource,
+        facets: &HashMap<FacetMapKey, InitializedFacet>,
+    ) -> HashMap<String, String> {
+        recorded_source_files(facets)
+            .filter_map(|sf| {
+                self.source_service
+                    .hash_file(source, &sf.path, sf.name.clone())
+                    .ok()
+                    .map(|hash| (source_file_path(sf), hash))
+            })
+            .collect()
+    }
+
+    /// Reads the files of the recorded source bundle facets as they currently are in the source. Files that
+    /// can't be read, e.g. because they were deleted, are left out.
+    fn current_source_contents(
+        &self,
+        source: &InitializedSource,
+        facets: &HashMap<FacetMapKey, InitializedFacet>,
+    ) -> HashMap<String, String> {
+        recorded_source_files(facets)
+            .filter_map(|sf| {
+                self.source_service
+                    .read_file(source, &sf.path, sf.name.clone())
+                    .ok()
+                    .map(|content| (source_file_path(sf), content))
+            })
+            .collect()
+    }
+
+    /// Gets a project along with the local clone its files were read from, or `None` if they were read
+    /// through the repo host's API.
+    async fn get_with_local_source(
+        &self,
+        params: ProjectGetParams,
+    ) -> Result<(InitializedProject, Option<InitializedSource>), SkootError> {
+        let get_repo_params = skootrs_model::skootrs::InitializedRepoGetParams {
+            repo_url: params.project_url.clone(),
+        };
+        debug!("Getting repo: {get_repo_params:?}");
+        let repo = self.repo_service.get(get_repo_params).await?;
+        let local_source = source::local_source(
+            &self.source_service,
+            self.local_project_path.as_deref(),
+            &repo,
+            params.read_strategy,
+        )?;
+        // TODO: Skootrs file path should be kept as a global constant somewhere.
+        let skootrs_file = match &local_source {
+            Some(source) => self
+                .source_service
+                .read_file(source, "./", ".skootrs".to_string())?,
+            None => {
+                self.repo_service
+                    .fetch_file_content(&repo, ".skootrs")
+                    .await?
+            }
+        };
+        debug!("Skootrs file: {skootrs_file}");
+        let initialized_project: InitializedProject = serde_json::from_str(&skootrs_file)?;
+        Ok((initialized_project, local_source))
+    }
+
+    /// Reads the files of a source bundle facet from a local clone, keyed by their paths. Files that aren't
+    /// in the clone are left out.
+    fn read_source_contents(
+        &self,
+        source: &InitializedSource,
+        facet: &SourceBundleFacet,
+    ) -> HashMap<String, String> {
+        facet
+            .source_files
+            .iter()
+            .flatten()
+            .filter_map(|sf| {
+                self.source_service
+                    .read_file(source, &sf.path, sf.name.clone())
+                    .ok()
+                    .map(|content| (source_file_path(sf), content))
+            })
+            .collect()
+    }
+
+    /// Fetches the files of a source bundle facet from the repo at once, keyed by their paths. Files that
+    /// aren't in the repo are left out.
+    async fn fetch_source_contents(
+        &self,
+        repo: &skootrs_model::skootrs::InitializedRepo,
+        facet: &SourceBundleFacet,
+    ) -> Result<HashMap<String, String>, SkootError> {
+        // The repo host expects paths relative to the root of the repo without a leading `./`.
+        let paths = facet
+            .source_files
+            .iter()
+            .flatten()
+            .map(|sf| {
+                let path = source_file_path(sf);
+                (path.strip_prefix("./").unwrap_or(&path).to_string(), path)
+            })
+            .collect::<HashMap<_, _>>();
+        let stripped_paths = paths.keys().cloned().collect::<Vec<_>>();
+        let contents = self
+            .repo_service
+            .fetch_file_contents(repo, &stripped_paths)
+            .await?;
+        Ok(contents
+            .into_iter()
+            .filter_map(|(stripped_path, content)| {
+                paths
+                    .get(&stripped_path)
+                    .map(|path| (path.clone(), content))
+            })
+            .collect())
+    }
+
+    /// Fetches the files of a source bundle facet for an audit. Files that can't be fetched are left out, so
+    /// the audit reports them as missing.
+    async fn fetch_audited_contents(
+        &self,
+        repo: &skootrs_model::skootrs::InitializedRepo,
+        facet: &SourceBundleFacet,
+    ) -> HashMap<String, String> {
+        self.fetch_source_contents(repo, facet)
+            .await
+            .map_err(|e| debug!("Failed to fetch the files of {}: {e}", facet.facet_type))
+            .unwrap_or_default()
+    }
+
+    /// Writes the previous content of every facet that is being replaced to the facet's history so it can be
+    /// compared against later.
+    fn record_facet_history(
+        &self,
+        source: &InitializedSource,
+        recorded: &HashMap<FacetMapKey, InitializedFacet>,
+        drift: &[FacetDrift],
+        previous_contents: &HashMap<String, String>,
+        previous_commit: &str,
+    ) -> Result<(), SkootError> {
+        let replaced_at = chrono::Utc::now();
+        for facet_drift in drift.iter().filter(|d| {
+            matches!(
+                d.status,
+                FacetDriftStatus::Outdated | FacetDriftStatus::Drifted
+            )
+        }) {
+            let Some(InitializedFacet::SourceBundle(recorded_facet)) =
+                recorded.get(&FacetMapKey::Type(facet_drift.facet_type.clone()))
+            else {
+                continue;
+            };
+            let files = recorded_facet
+                .source_files
+                .iter()
+                .flatten()
+                .filter_map(|sf| {
+                    let path = source_file_path(sf);
+                    previous_contents
+                        .get(&path)
+                        .map(|content| (path, content.clone()))
+                })
+                .collect();
+            let version =
+                history::latest_version(source, &facet_drift.facet_type)?.map_or(1, |v| v + 1);
+            let entry = FacetHistoryEntry {
+                facet_type: facet_drift.facet_type.clone(),
+                version,
+                status: facet_drift.status.clone(),
+                previous_commit: previous_commit.to_string(),
+                replaced_at,
+                files,
+            };
+            self.source_service.write_file(
+                source.clone(),
+                history::history_path(&entry.facet_type),
+                history::history_file_name(version),
+                history::encode(&entry)?,
+            )?;
+            info!(
+                "Recorded version {version} of facet {} history",
+                entry.facet_type
+            );
+        }
+        Ok(())
+    }
+}
+
+/// The steps of an initialization that completed so far, along with what they created.
+#[derive(Default)]
+struct InitializationProgress {
+    steps: Vec<InitializationStep>,
+    repo: Option<InitializedRepo>,
+    source: Option<InitializedSource>,
+}
+
+/// The `InitializationError` struct represents a project that failed to initialize partway through and was
+/// rolled back.
+#[derive(Debug)]
+pub struct InitializationError {
+    /// The error the initialization failed with.
+    pub error: SkootError,
+    /// What was rolled back.
+    pub rollback: InitializationRollback,
+}
+
+impl fmt::Display for InitializationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Project initialization failed: {}", self.error)?;
+        if !self.rollback.completed_steps.is_empty() {
+            write!(
+                f,
+                "\nCompleted before the failure: {}",
+                join(&self.rollback.completed_steps)
+            )?;
+        }
+        if !self.rollback.rolled_back.is_empty() {
+            write!(f, "\nRolled back: {}", join(&self.rollback.rolled_back))?;
+        }
+        if !self.rollback.failures.is_empty() {
+            write!(
+                f,
+                "\nNot rolled back, clean these up by hand: {}",
+                join(&self.rollback.failures)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for InitializationError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Returns the part of a branch name describing a change, e.g. `updated-facets-for-project`.
+fn branch_slug(title: &str) -> String {
+    title
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_ascii_lowercase)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+fn join<T: fmt::Display>(items: &[T]) -> String {
+    items
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Returns the source files of the recorded source bundle facets.
+fn recorded_source_files(
+    facets: &HashMap<FacetMapKey, InitializedFacet>,
+) -> impl Iterator<Item = &SourceFile> {
+    facets
+        .values()
+        .filter_map(|facet| match facet {
+            InitializedFacet::SourceBundle(s) => s.source_files.as_ref(),
+            InitializedFacet::APIBundle(_) => None,
+        })
+        .flatten()
+}
+
+/// Returns a commit message with trailers recording the facets the commit applies and the version of Skootrs
+/// that made it, so e.g. `git log --grep "Skootrs-Facet: License"` finds when the `License` facet was applied.
+fn commit_message(subject: &str, facet_types: &[SupportedFacetType]) -> String {
+    let trailers = facet_types
+        .iter()
+        .map(|facet_type| format!("{FACET_TRAILER}: {facet_type}"))
+        .chain(std::iter::once(format!(
+            "{VERSION_TRAILER}: {SKOOTRS_VERSION}"
+        )))
+        .collect::<Vec<_>>();
+    format!("{subject}\n\n{}\n", trailers.join("\n"))
+}
+
+/// Returns the message of the commit of the files the ecosystems of a new project's modules generate.
+fn ecosystem_commit_message(modules: &[InitializedModule]) -> String {
+    let mut names = vec![];
+    for module in modules {
+        if !names.contains(&module.ecosystem.name()) {
+            names.push(module.ecosystem.name());
+        }
+    }
+    commit_message(&format!("Initialized {} project", names.join(", ")), &[])
+}
+
+/// Moves a file an ecosystem would write for a module into the module's directory.
+fn module_action(module: &InitializedModule, action: PlannedAction) -> PlannedAction {
+    match action {
+        PlannedAction::WriteFile {
+            path,
+            name,
+            content,
+            facet_type,
+        } => PlannedAction::WriteFile {
+            path: module.relative_path(&path),
+            name,
+            content,
+            facet_type,
+        },
+        action => action,
+    }
+}
+
+/// Returns the message of the commit of a source bundle facet's files.
+fn facet_commit_message(facet_type: &SupportedFacetType) -> String {
+    commit_message(
+        &format!("Added {facet_type} facet"),
+        std::slice::from_ref(facet_type),
+    )
+}
+
+/// Returns the path of a source file relative to the root of the source.
+fn source_file_path(source_file: &SourceFile) -> String {
+    std::path::Path::new(&source_file.path)
+        .join(&source_file.name)
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Diffs a file in the repo, or `None` if it isn't there, against its re-rendered content. The diff is
+/// labeled like a `git diff`, so it can be applied with `git apply`.
+fn diff_file(current: Option<&str>, rendered: &str, path: String) -> FacetFileDiff {
+    let (status, original_filename) = match current {
+        None => (FacetFileDiffStatus::Added, "/dev/null".to_string()),
+        Some(current) if current == rendered => {
+            return FacetFileDiff {
+                path,
+                status: FacetFileDiffStatus::Unchanged,
+                diff: String::new(),
+            }
+        }
+        Some(_) => (FacetFileDiffStatus::Modified, format!("a/{path}")),
+    };
+    let patch = diffy::DiffOptions::new()
+        .set_original_filename(original_filename)
+        .set_modified_filename(format!("b/{path}"))
+        .create_patch(current.unwrap_or_default(), rendered);
+    let new_file = if status == FacetFileDiffStatus::Added {
+        "new file mode 100644\n"
+    } else {
+        ""
+    };
+    FacetFileDiff {
+        diff: format!("diff --git a/{path} b/{path}\n{new_file}{patch}"),
+        path,
+        status,
+    }
+}
+
+/// Compares the files of a source bundle facet as they are in the repo against their recorded hashes.
+/// Missing files take precedence over drifted files since a missing file means the control is gone entirely.
+/// The `PinnedDependencies` facet is also drifted if any of the workflows in the contents breaks its policy.
+fn audit_source_bundle(
+    facet: &SourceBundleFacet,
+    contents: &HashMap<String, String>,
+) -> FacetAudit {
+    let Some(source_files) = &facet.source_files else {
+        return FacetAudit {
+            facet_type: facet.facet_type.clone(),
+            status: FacetAuditStatus::Unverified,
+            files: vec![],
+        };
+    };
+    let mut missing_files = vec![];
+    let mut drifted_files = vec![];
+    for sf in source_files {
+        let path = source_file_path(sf);
+        match contents.get(&path) {
+            None => missing_files.push(path),
+            Some(content) => {
+                let hash = format!("{:x}", sha2::Sha256::digest(content.as_bytes()));
+                if hash != sf.hash {
+                    drifted_files.push(path);
+                }
+            }
+        }
+    }
+    let (status, files) = if !missing_files.is_empty() {
+        (FacetAuditStatus::Missing, missing_files)
+    } else if !drifted_files.is_empty() {
+        (FacetAuditStatus::Drifted, drifted_files)
+    } else if facet.facet_type == SupportedFacetType::PinnedDependencies {
+        let unpinned_files = unpinned_workflows(contents);
+        if unpinned_files.is_empty() {
+            (FacetAuditStatus::Compliant, vec![])
+        } else {
+            (FacetAuditStatus::Drifted, unpinned_files)
+        }
+    } else {
+        (FacetAuditStatus::Compliant, vec![])
+    };
+    FacetAudit {
+        facet_type: facet.facet_type.clone(),
+        status,
+        files,
+    }
+}
+
+/// Returns the paths of the workflows that reference an action or reusable workflow by tag even though the
+/// project's pinning policy requires it to be pinned to a commit SHA.
+fn unpinned_workflows(contents: &HashMap<String, String>) -> Vec<String> {
+    let policy = contents
+        .get(&format!("./{PINNING_POLICY_PATH}"))
+        .and_then(|content| serde_yaml::from_str::<PinningPolicy>(content).ok())
+        .unwrap_or_default();
+    let mut unpinned_files = contents
+        .iter()
+        .filter(|(path, content)| is_workflow(path) && !policy.unpinned(content).is_empty())
+        .map(|(path, _)| path.clone())
+        .collect::<Vec<_>>();
+    unpinned_files.sort();
+    unpinned_files
+}
+
+/// Audits the findings allowlisted by a project's scan policy as of today. A malformed policy is an error
+/// rather than an empty allowlist so a typo can't silently drop the exceptions from the audit.
+fn audit_scan_policy(content: Option<&str>) -> Result<Vec<AllowlistAudit>, SkootError> {
+    let Some(content) = content else {
+        return Ok(vec![]);
+    };
+    let policy: ScanPolicy = serde_yaml::from_str(content)
+        .map_err(|e| SkootError::from(format!("Failed to parse {SCAN_POLICY_PATH}: {e}")))?;
+    Ok(policy.audit(chrono::Utc::now().date_naive()))
+}
+
+/// Returns the re-rendered facets whose recorded state was generated by an older template. Their files may
+/// still be up to date if the template changed in a way that doesn't affect them, in which case only the
+/// recorded template version changes.
+fn outdated_templates<'a>(
+    recorded: &HashMap<FacetMapKey, InitializedFacet>,
+    rendered: &'a [InitializedFacet],
+) -> Vec<&'a InitializedFacet> {
+    rendered
+        .iter()
+        .filter(|facet| {
+            recorded
+                .get(&FacetMapKey::Type(facet.facet_type()))
+                .is_some_and(|recorded_facet| recorded_facet.has_older_template_than(facet))
+        })
+        .collect()
+}
+
+/// Compares the recorded source bundle facets against the hashes of their files before they were re-rendered
+/// and the re-rendered facets.
+fn reconcile_source_facets(
+    recorded: &HashMap<FacetMapKey, InitializedFacet>,
+    current_hashes: &HashMap<String, String>,
+    rendered: &[InitializedFacet],
+) -> Vec<FacetDrift> {
+    rendered
+        .iter()
+        .filter_map(|facet| match facet {
+            InitializedFacet::SourceBundle(s) => Some(s),
+            InitializedFacet::APIBundle(_) => None,
+        })
+        .map(|rendered_facet| {
+            let rendered_files = rendered_facet.source_files.clone().unwrap_or_default();
+            let recorded_files =
+                match recorded.get(&FacetMapKey::Type(rendered_facet.facet_type.clone())) {
+                    Some(InitializedFacet::SourceBundle(s)) => s.source_files.clone(),
+                    _ => None,
+                };
+            let Some(recorded_files) = recorded_files else {
+                return FacetDrift {
+                    facet_type: rendered_facet.facet_type.clone(),
+                    status: FacetDriftStatus::Added,
+                    files: rendered_files.iter().map(source_file_path).collect(),
+                };
+            };
+
+            let drifted_files = recorded_files
+                .iter()
+                .filter(|sf| current_hashes.get(&source_file_path(sf)) != Some(&sf.hash))
+                .map(source_file_path)
+                .collect::<Vec<_>>();
+            if !drifted_files.is_empty() {
+                return FacetDrift {
+                    facet_type: rendered_facet.facet_type.clone(),
+                    status: FacetDriftStatus::Drifted,
+                    files: drifted_files,
+                };
+            }
+
+            let recorded_hashes = recorded_files
+                .iter()
+                .map(|sf| (source_file_path(sf), &sf.hash))
+                .collect::<HashMap<_, _>>();
+            let outdated_files = rendered_files
+                .iter()
+                .filter(|sf| recorded_hashes.get(&source_file_path(sf)) != Some(&&sf.hash))
+                .map(source_file_path)
+                .collect::<Vec<_>>();
+            let status = if outdated_files.is_empty() {
+                FacetDriftStatus::UpToDate
+            } else {
+                FacetDriftStatus::Outdated
+            };
+            FacetDrift {
+                facet_type: rendered_facet.facet_type.clone(),
+                status,
+                files: outdated_files,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, path::Path};
+
+    use skootrs_model::skootrs::{
+        best_practices::{BadgeLevel, BestPracticesBadge},
+        facet::{
+            APIBundleFacet, APIContent, FacetCreateParams, FacetSetCreateParams, SourceBundleFacet,
+            SupportedFacetType,
+        },
+        label::Label,
+        ActionsCleanup, EcosystemInitializeParams, GithubRepoParams, GithubUser, GoParams,
+        InitializedBitbucketRepo, InitializedEcosystem, InitializedGenericGitRepo,
+        InitializedGithubRepo, InitializedGo, InitializedMaven, InitializedPython, InitializedRepo,
+        InitializedRust, ProjectOutputType, RepoCreateParams, SourceInitializeParams,
+    };
+
+    use super::*;
+    use crate::service::source::{
+        ConcurrencyError, LocalSourceService, PushVerificationError, SourceLock,
+    };
+    struct MockRepoService;
+    struct MockEcosystemService;
+    struct MockSourceService;
+    struct MockFacetService;
+    struct MockOutputService;
+
+    impl RepoService for MockRepoService {
+        async fn initialize(
             &self,
-            _source: &InitializedSource,
+            params: RepoCreateParams,
+        ) -> Result<InitializedRepo, SkootError> {
+            let inner_params = match params {
+                RepoCreateParams::Github(g) => g,
+                RepoCreateParams::GenericGit(g) => {
+                    return Ok(InitializedRepo::GenericGit(
+                        InitializedGenericGitRepo::from_remote_url(g.remote_url)?,
+                    ));
+                }
+                RepoCreateParams::Bitbucket(b) => {
+                    return Ok(InitializedRepo::Bitbucket(InitializedBitbucketRepo {
+                        name: b.name,
+                        workspace: b.workspace,
+                    }));
+                }
+            };
+
+            // Special case for testing error handling
+            if inner_params.name == "error" {
+                return Err("Error".into());
+            }
+
+            let initialized_repo = InitializedRepo::Github(InitializedGithubRepo {
+                name: inner_params.name,
+                organization: inner_params.organization,
+            });
+
+            Ok(initialized_repo)
+        }
+
+        fn plan(
+            &self,
+            params: &RepoCreateParams,
+            path: &str,
+        ) -> Result<Vec<PlannedAction>, SkootError> {
+            let initialized_repo = params.initialized_repo()?;
+            Ok(vec![PlannedAction::Command {
+                description: "Clone".to_string(),
+                args: vec![
+                    "git".to_string(),
+                    "clone".to_string(),
+                    initialized_repo.full_url(),
+                ],
+                dir: path.to_string(),
+            }])
+        }
+
+        fn clone_local(
+            &self,
+            initialized_repo: InitializedRepo,
+            path: String,
+        ) -> Result<InitializedSource, SkootError> {
+            let name = initialized_repo.name();
+
+            if name == "error" {
+                return Err("Error".into());
+            }
+
+            let initialized_source = InitializedSource {
+                path: format!("{path}/{name}"),
+            };
+
+            Ok(initialized_source)
+        }
+
+        fn clone_local_or_pull(
+            &self,
+            initialized_repo: InitializedRepo,
+            path: String,
+        ) -> Result<InitializedSource, SkootError> {
+            self.clone_local(initialized_repo, path)
+        }
+
+        async fn get(
+            &self,
+            params: skootrs_model::skootrs::InitializedRepoGetParams,
+        ) -> Result<InitializedRepo, SkootError> {
+            let repo_url = params.repo_url.clone();
+            if repo_url == "error" {
+                return Err("Error".into());
+            }
+
+            let initialized_repo = InitializedRepo::Github(InitializedGithubRepo {
+                name: "test".to_string(),
+                organization: GithubUser::User("testuser".to_string()),
+            });
+
+            Ok(initialized_repo)
+        }
+
+        async fn fetch_file_content<P: AsRef<std::path::Path> + Send>(
+            &self,
+            _initialized_repo: &InitializedRepo,
             path: P,
-            _name: String,
         ) -> Result<String, SkootError> {
             if path.as_ref().to_str().unwrap() == "error" {
                 return Err("Error".into());
             }
 
-            Ok("fakehash".to_string())
+            Ok("Worked".to_string())
         }
 
-        fn pull_updates(&self, source: InitializedSource) -> Result<(), SkootError> {
-            if source.path == "error" {
+        async fn fetch_file_contents(
+            &self,
+            initialized_repo: &InitializedRepo,
+            paths: &[String],
+        ) -> Result<HashMap<String, String>, SkootError> {
+            let mut contents = HashMap::new();
+            for path in paths {
+                contents.insert(
+                    path.clone(),
+                    self.fetch_file_content(initialized_repo, path).await?,
+                );
+            }
+            Ok(contents)
+        }
+
+        async fn cleanup_actions(
+            &self,
+            _initialized_repo: &InitializedRepo,
+        ) -> Result<ActionsCleanup, SkootError> {
+            Ok(ActionsCleanup {
+                deleted_artifacts: 2,
+                deleted_caches: 1,
+                disabled_workflows: vec![".github/workflows/scorecard.yml".to_string()],
+                failures: vec![],
+            })
+        }
+
+        async fn open_pull_request(
+            &self,
+            initialized_repo: &InitializedRepo,
+            params: PullRequestParams,
+        ) -> Result<String, SkootError> {
+            // A repo named `closed` stands in for one that doesn't accept pull requests.
+            if initialized_repo.name() == "closed" || params.base != "main" {
                 return Err("Error".into());
             }
 
-            Ok(())
+            Ok(format!("{}/pull/1", initialized_repo.full_url()))
+        }
+
+        async fn archive(&self, initialized_repo: InitializedRepo) -> Result<String, SkootError> {
+            Ok(initialized_repo.full_url())
+        }
+
+        async fn delete(&self, initialized_repo: InitializedRepo) -> Result<String, SkootError> {
+            Ok(initialized_repo.full_url())
+        }
+    }
+
+    impl EcosystemService for MockEcosystemService {
+        fn initialize(
+            &self,
+            params: EcosystemInitializeParams,
+            _source: InitializedSource,
+        ) -> Result<InitializedEcosystem, SkootError> {
+            let initialized_ecosystem = match params {
+                EcosystemInitializeParams::Go(g) => {
+                    if g.host == "error" {
+                        return Err("Error".into());
+                    }
+                    InitializedEcosystem::Go(InitializedGo {
+                        name: g.name,
+                        host: g.host,
+                    })
+                }
+                EcosystemInitializeParams::Maven(m) => {
+                    if m.group_id == "error" {
+                        return Err("Error".into());
+                    }
+                    InitializedEcosystem::Maven(InitializedMaven {
+                        group_id: m.group_id,
+                        artifact_id: m.artifact_id,
+                    })
+                }
+                EcosystemInitializeParams::Python(p) => {
+                    if p.name == "error" {
+                        return Err("Error".into());
+                    }
+                    InitializedEcosystem::Python(InitializedPython {
+                        name: p.name,
+                        package_manager: p.package_manager,
+                    })
+                }
+                EcosystemInitializeParams::Rust(r) => {
+                    if r.name == "error" {
+                        return Err("Error".into());
+                    }
+                    InitializedEcosystem::Rust(InitializedRust {
+                        name: r.name,
+                        crates: r.crates,
+                    })
+                }
+            };
+
+            Ok(initialized_ecosystem)
+        }
+
+        fn plan(
+            &self,
+            params: &EcosystemInitializeParams,
+            _source: &InitializedSource,
+        ) -> Result<Vec<PlannedAction>, SkootError> {
+            if let EcosystemInitializeParams::Go(g) = params {
+                if g.host == "error" {
+                    return Err("Error".into());
+                }
+            }
+
+            Ok(vec![])
+        }
+    }
+
+    impl SourceService for MockSourceService {
+        fn initialize(
+            &self,
+            params: skootrs_model::skootrs::SourceInitializeParams,
+            initialized_repo: InitializedRepo,
+        ) -> Result<InitializedSource, SkootError> {
+            if params.parent_path == "error" {
+                return Err("Error".into());
+            }
+
+            let repo_name = initialized_repo.name();
+
+            let initialized_source = InitializedSource {
+                path: format!("{}/{}", params.parent_path, repo_name),
+            };
+
+            Ok(initialized_source)
+        }
+
+        fn commit_and_push_changes(
+            &self,
+            _source: InitializedSource,
+            message: String,
+        ) -> Result<(), SkootError> {
+            if message == "error" {
+                return Err("Error".into());
+            }
+
+            Ok(())
+        }
+
+        fn commit_changes(
+            &self,
+            _source: &InitializedSource,
+            _paths: Option<&[String]>,
+            message: &str,
+        ) -> Result<bool, SkootError> {
+            if message == "error" {
+                return Err("Error".into());
+            }
+
+            Ok(true)
+        }
+
+        fn commit_and_push_branch(
+            &self,
+            _source: InitializedSource,
+            branch: &str,
+            message: String,
+        ) -> Result<String, SkootError> {
+            if message == "error" || !branch.starts_with("skootrs/") {
+                return Err("Error".into());
+            }
+
+            Ok("main".to_string())
+        }
+
+        fn checkout_default_branch(&self, _source: &InitializedSource) -> Result<bool, SkootError> {
+            Ok(false)
+        }
+
+        fn write_file<P: AsRef<std::path::Path>, C: AsRef<[u8]>>(
+            &self,
+            _source: InitializedSource,
+            _path: P,
+            name: String,
+            _contents: C,
+        ) -> Result<(), SkootError> {
+            if name == "error" {
+                return Err("Error".into());
+            }
+
+            Ok(())
+        }
+
+        fn read_file<P: AsRef<std::path::Path>>(
+            &self,
+            _source: &InitializedSource,
+            _path: P,
+            name: String,
+        ) -> Result<String, SkootError> {
+            if name == "error" {
+                return Err("Error".into());
+            }
+
+            Ok("Worked".to_string())
+        }
+
+        fn remove_file<P: AsRef<Path>>(
+            &self,
+            _source: &InitializedSource,
+            _path: P,
+            name: String,
+        ) -> Result<(), SkootError> {
+            if name == "error" {
+                return Err("Error".into());
+            }
+
+            Ok(())
+        }
+
+        fn hash_file<P: AsRef<Path>>(
+            &self,
+            _source: &InitializedSource,
+            path: P,
+            _name: String,
+        ) -> Result<String, SkootError> {
+            if path.as_ref().to_str().unwrap() == "error" {
+                return Err("Error".into());
+            }
+
+            Ok("fakehash".to_string())
+        }
+
+        fn pull_updates(&self, source: InitializedSource) -> Result<(), SkootError> {
+            if source.path == "error" {
+                return Err("Error".into());
+            }
+
+            Ok(())
+        }
+
+        fn set_remote_url(&self, source: &InitializedSource, _url: &str) -> Result<(), SkootError> {
+            if source.path == "error" {
+                return Err("Error".into());
+            }
+
+            Ok(())
+        }
+
+        fn delete(&self, source: InitializedSource) -> Result<(), SkootError> {
+            if source.path == "error" {
+                return Err("Error".into());
+            }
+
+            Ok(())
+        }
+
+        fn verify_push(&self, source: &InitializedSource) -> Result<(), SkootError> {
+            if source.path.ends_with("unpushed") {
+                return Err(SkootError::other(PushVerificationError::RemoteRefMissing {
+                    branch: "main".to_string(),
+                }));
+            }
+
+            Ok(())
+        }
+
+        fn push_mirror(
+            &self,
+            _source: &InitializedSource,
+            mirror: &InitializedRepo,
+        ) -> Result<(), SkootError> {
+            if mirror.name() == "error" {
+                return Err(SkootError::other(PushVerificationError::PushFailed {
+                    stderr: "Permission denied".to_string(),
+                }));
+            }
+
+            Ok(())
+        }
+
+        fn head_commit(&self, _source: &InitializedSource) -> Result<String, SkootError> {
+            Ok("abc123".to_string())
+        }
+
+        fn verify_remote_head(
+            &self,
+            source: &InitializedSource,
+            expected_parent: &str,
+        ) -> Result<(), SkootError> {
+            if source.path.ends_with("conflict") {
+                return Err(SkootError::other(ConcurrencyError::Conflict {
+                    branch: "main".to_string(),
+                    expected: expected_parent.to_string(),
+                    actual: Some("def456".to_string()),
+                }));
+            }
+
+            Ok(())
+        }
+
+        fn lock(&self, source: &InitializedSource) -> Result<SourceLock, SkootError> {
+            if source.path.ends_with("locked") {
+                return Err(SkootError::other(ConcurrencyError::Locked {
+                    lock_path: format!("{}/.git/skootrs.lock", source.path),
+                }));
+            }
+
+            Ok(SourceLock::default())
+        }
+
+        fn reset_on_cancel(&self, _source: &InitializedSource, _commit: &str) {}
+    }
+
+    impl RootFacetService for MockFacetService {
+        async fn initialize(
+            &self,
+            params: FacetCreateParams,
+        ) -> Result<InitializedFacet, SkootError> {
+            match params {
+                FacetCreateParams::SourceBundle(s) => {
+                    if s.common.project_name == "error" {
+                        return Err("Error".into());
+                    }
+                    let source_bundle_facet = SourceBundleFacet {
+                        source_files: Some(vec![SourceFile {
+                            name: "README.md".to_string(),
+                            path: "./".to_string(),
+                            hash: "fakehash".to_string(),
+                        }]),
+                        facet_type: SupportedFacetType::Readme,
+                        source_files_content: None,
+                        labels: vec![Label::Custom("test".to_string())],
+                        packages: vec![],
+                        template_version: None,
+                        generator_fingerprint: None,
+                    };
+
+                    Ok(InitializedFacet::SourceBundle(source_bundle_facet))
+                }
+                FacetCreateParams::APIBundle(a) => {
+                    if a.common.project_name == "error" {
+                        return Err("Error".into());
+                    }
+                    let api_bundle_facet = APIBundleFacet {
+                        apis: vec![APIContent {
+                            name: "test".to_string(),
+                            url: "https://foo.bar/test".to_string(),
+                            response: "worked".to_string(),
+                        }],
+                        facet_type: SupportedFacetType::BranchProtection,
+                        labels: vec![Label::Custom("test".to_string())],
+                        skipped_reason: None,
+                        template_version: None,
+                        generator_fingerprint: None,
+                    };
+
+                    Ok(InitializedFacet::APIBundle(api_bundle_facet))
+                }
+            }
+        }
+
+        async fn initialize_all(
+            &self,
+            params: FacetSetCreateParams,
+        ) -> Result<Vec<InitializedFacet>, SkootError> {
+            let mut initialized_facets = Vec::new();
+            for facet_params in params.facets_params {
+                let initialized_facet = self.initialize(facet_params).await?;
+                initialized_facets.push(initialized_facet);
+            }
+
+            Ok(initialized_facets)
+        }
+
+        async fn verify_api_bundle(
+            &self,
+            _repo: &InitializedRepo,
+            facet_type: &SupportedFacetType,
+        ) -> Result<bool, SkootError> {
+            Ok(*facet_type == SupportedFacetType::BranchProtection)
+        }
+
+        async fn best_practices_badge(&self, id: u64) -> Result<BestPracticesBadge, SkootError> {
+            Ok(BestPracticesBadge {
+                id,
+                badge_level: BadgeLevel::Passing,
+                passing_percentage: 100,
+            })
+        }
+
+        fn render(&self, params: &FacetCreateParams) -> Result<Vec<SourceFileContent>, SkootError> {
+            match params {
+                FacetCreateParams::SourceBundle(s) => Ok(vec![SourceFileContent {
+                    path: s.common.source.path.clone(),
+                    name: format!("{}.md", s.facet_type),
+                    content: s.common.project_name.clone(),
+                }]),
+                FacetCreateParams::APIBundle(_) => Err("No content".into()),
+            }
+        }
+
+        fn plan(&self, params: &FacetCreateParams) -> Result<Vec<PlannedAction>, SkootError> {
+            match params {
+                FacetCreateParams::SourceBundle(s) => Ok(vec![PlannedAction::WriteFile {
+                    path: "./".to_string(),
+                    name: format!("{}.md", s.facet_type),
+                    content: "test".to_string(),
+                    facet_type: Some(s.facet_type.clone()),
+                }]),
+                FacetCreateParams::APIBundle(a) => Ok(vec![PlannedAction::ApiCall {
+                    description: "test".to_string(),
+                    method: "PUT".to_string(),
+                    url: "https://foo.bar/test".to_string(),
+                    body: None,
+                    facet_type: Some(a.facet_type.clone()),
+                }]),
+            }
+        }
+
+        async fn delete(
+            &self,
+            _source: &InitializedSource,
+            _repo: &InitializedRepo,
+            facet: &InitializedFacet,
+        ) -> Result<(), SkootError> {
+            if facet.facet_type() == SupportedFacetType::CodeReview {
+                return Err("Reverting facet CodeReview is not supported".into());
+            }
+
+            Ok(())
+        }
+    }
+
+    impl OutputService for MockOutputService {
+        async fn list(
+            &self,
+            _params: ProjectOutputsListParams,
+        ) -> Result<Vec<ProjectOutputReference>, SkootError> {
+            Ok(vec![ProjectOutputReference {
+                name: "test".into(),
+                output_type: ProjectOutputType::SBOM,
+                labels: vec![Label::Custom("test".to_string())],
+            }])
+        }
+
+        async fn get(
+            &self,
+            _params: skootrs_model::skootrs::ProjectOutputGetParams,
+        ) -> Result<skootrs_model::skootrs::ProjectOutput, SkootError> {
+            Ok(skootrs_model::skootrs::ProjectOutput {
+                reference: ProjectOutputReference {
+                    name: "test".into(),
+                    output_type: ProjectOutputType::SBOM,
+                    labels: vec![Label::Custom("test".to_string())],
+                },
+                output: "test".into(),
+            })
+        }
+
+        async fn download(
+            &self,
+            params: ProjectOutputsDownloadParams,
+        ) -> Result<Vec<DownloadedOutput>, SkootError> {
+            Ok(vec![DownloadedOutput {
+                name: "test".into(),
+                path: format!("{}/test", params.dir),
+                size: 4,
+                sha256: format!("{:x}", sha2::Sha256::digest(b"test")),
+                checksum_verified: true,
+            }])
+        }
+
+        async fn verify(
+            &self,
+            params: AttestationVerifyParams,
+        ) -> Result<AttestationVerification, SkootError> {
+            Ok(AttestationVerification {
+                name: params.project_output,
+                identity: params.initialized_project.repo.full_url(),
+                verified: false,
+                envelopes: vec![],
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_initialize_project() {
+        let project_params = ProjectCreateParams {
+            name: "test".to_string(),
+            repo_params: RepoCreateParams::Github(GithubRepoParams {
+                name: "test".to_string(),
+                description: "foobar".to_string(),
+                organization: GithubUser::User("testuser".to_string()),
+                license: None,
+            }),
+            ecosystem_params: vec![EcosystemInitializeParams::Go(GoParams {
+                name: "test".to_string(),
+                host: "github.com".to_string(),
+                go_version: None,
+            })
+            .into()],
+            source_params: SourceInitializeParams {
+                parent_path: "test".to_string(),
+            },
+            rollback_on_failure: false,
+        };
+
+        let local_project_service = LocalProjectService {
+            repo_service: MockRepoService,
+            ecosystem_service: MockEcosystemService,
+            source_service: MockSourceService,
+            facet_service: MockFacetService,
+            output_service: MockOutputService,
+            facet_registry: FacetRegistry::default(),
+            change_requests: ChangeRequestConfig::default(),
+            commit_grouping: CommitGrouping::default(),
+            events: EventEmitter::default(),
+            local_project_path: None,
+        };
+
+        let result = local_project_service.initialize(project_params).await;
+
+        assert!(result.is_ok());
+        let initialized_project = result.unwrap();
+
+        assert!(initialized_project.repo.full_url() == "https://github.com/testuser/test");
+        let module = match &initialized_project.modules[0].ecosystem {
+            InitializedEcosystem::Go(g) => g,
+            _ => panic!("Wrong ecosystem type"),
+        };
+        assert!(module.name == "test");
+        assert!(initialized_project.source.path == "test/test");
+        println!("{:#?}", initialized_project.facets);
+
+        // TODO: This will always be equal to 2 because we are initializing two facets in the mock facet service
+        // and the `HashMap` for the facets will keep getting the same key. This is probably not a great way
+        // of handling that.
+        assert_eq!(initialized_project.facets.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_plan_project() {
+        let project_params = ProjectCreateParams {
+            name: "test".to_string(),
+            repo_params: RepoCreateParams::Github(GithubRepoParams {
+                name: "test".to_string(),
+                description: "foobar".to_string(),
+                organization: GithubUser::User("testuser".to_string()),
+                license: None,
+            }),
+            ecosystem_params: vec![EcosystemInitializeParams::Go(GoParams {
+                name: "test".to_string(),
+                host: "github.com".to_string(),
+                go_version: None,
+            })
+            .into()],
+            source_params: SourceInitializeParams {
+                parent_path: "test".to_string(),
+            },
+            rollback_on_failure: false,
+        };
+
+        let local_project_service = LocalProjectService {
+            repo_service: MockRepoService,
+            ecosystem_service: MockEcosystemService,
+            source_service: MockSourceService,
+            facet_service: MockFacetService,
+            output_service: MockOutputService,
+            facet_registry: FacetRegistry::default(),
+            change_requests: ChangeRequestConfig::default(),
+            commit_grouping: CommitGrouping::default(),
+            events: EventEmitter::default(),
+            local_project_path: None,
+        };
+
+        let plan = local_project_service.plan(project_params).await.unwrap();
+        assert_eq!(plan.repo.full_url(), "https://github.com/testuser/test");
+        assert_eq!(plan.source.path, "test/test");
+
+        // The API facets can only be applied once the initial push has landed.
+        let push = plan
+            .actions
+            .iter()
+            .position(|action| matches!(action, PlannedAction::Push { .. }))
+            .unwrap();
+        assert!(plan.actions[..push]
+            .iter()
+            .all(|action| !matches!(action, PlannedAction::ApiCall { .. })));
+        let api_facets = plan.actions[push + 1..]
+            .iter()
+            .map(|action| match action {
+                PlannedAction::ApiCall { facet_type, .. } => facet_type.clone().unwrap(),
+                _ => panic!("Expected only API calls after the push, got {action}"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            api_facets,
+            vec![
+                SupportedFacetType::BranchProtection,
+                SupportedFacetType::VulnerabilityReporting
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_preview_facet() {
+        let project_params = ProjectCreateParams {
+            name: "test".to_string(),
+            repo_params: RepoCreateParams::Github(GithubRepoParams {
+                name: "test".to_string(),
+                description: "foobar".to_string(),
+                organization: GithubUser::User("testuser".to_string()),
+                license: None,
+            }),
+            ecosystem_params: vec![EcosystemInitializeParams::Go(GoParams {
+                name: "test".to_string(),
+                host: "github.com".to_string(),
+                go_version: None,
+            })
+            .into()],
+            source_params: SourceInitializeParams {
+                parent_path: "test".to_string(),
+            },
+            rollback_on_failure: false,
+        };
+
+        let local_project_service = LocalProjectService {
+            repo_service: MockRepoService,
+            ecosystem_service: MockEcosystemService,
+            source_service: MockSourceService,
+            facet_service: MockFacetService,
+            output_service: MockOutputService,
+            facet_registry: FacetRegistry::default(),
+            change_requests: ChangeRequestConfig::default(),
+            commit_grouping: CommitGrouping::default(),
+            events: EventEmitter::default(),
+            local_project_path: None,
+        };
+
+        let files = local_project_service
+            .preview_facet(FacetPreviewParams {
+                project_params: project_params.clone(),
+                facet_type: SupportedFacetType::Readme,
+                options: BTreeMap::new(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].name, "Readme.md");
+        assert_eq!(files[0].path, "test/test");
+
+        assert!(local_project_service
+            .preview_facet(FacetPreviewParams {
+                project_params: project_params.clone(),
+                facet_type: SupportedFacetType::Container,
+                options: BTreeMap::new(),
+            })
+            .await
+            .is_err());
+        assert!(local_project_service
+            .preview_facet(FacetPreviewParams {
+                project_params: project_params.clone(),
+                facet_type: SupportedFacetType::Allstar,
+                options: BTreeMap::new(),
+            })
+            .await
+            .is_err());
+
+        // Options that the facet doesn't take are refused before anything is rendered.
+        assert!(local_project_service
+            .preview_facet(FacetPreviewParams {
+                project_params,
+                facet_type: SupportedFacetType::Readme,
+                options: BTreeMap::from([("team".to_string(), "infra".into())]),
+            })
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_initialize_project_unverified_push() {
+        let project_params = ProjectCreateParams {
+            name: "unpushed".to_string(),
+            repo_params: RepoCreateParams::Github(GithubRepoParams {
+                name: "unpushed".to_string(),
+                description: "foobar".to_string(),
+                organization: GithubUser::User("testuser".to_string()),
+                license: None,
+            }),
+            ecosystem_params: vec![EcosystemInitializeParams::Go(GoParams {
+                name: "unpushed".to_string(),
+                host: "github.com".to_string(),
+                go_version: None,
+            })
+            .into()],
+            source_params: SourceInitializeParams {
+                parent_path: "test".to_string(),
+            },
+            rollback_on_failure: false,
+        };
+
+        let local_project_service = LocalProjectService {
+            repo_service: MockRepoService,
+            ecosystem_service: MockEcosystemService,
+            source_service: MockSourceService,
+            facet_service: MockFacetService,
+            output_service: MockOutputService,
+            facet_registry: FacetRegistry::default(),
+            change_requests: ChangeRequestConfig::default(),
+            commit_grouping: CommitGrouping::default(),
+            events: EventEmitter::default(),
+            local_project_path: None,
+        };
+
+        let err = local_project_service
+            .initialize(project_params)
+            .await
+            .unwrap_err();
+
+        assert!(err.downcast_ref::<PushVerificationError>().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_initialize_project_rollback() {
+        let project_params = ProjectCreateParams {
+            name: "unpushed".to_string(),
+            repo_params: RepoCreateParams::Github(GithubRepoParams {
+                name: "unpushed".to_string(),
+                description: "foobar".to_string(),
+                organization: GithubUser::User("testuser".to_string()),
+                license: None,
+            }),
+            ecosystem_params: vec![EcosystemInitializeParams::Go(GoParams {
+                name: "unpushed".to_string(),
+                host: "github.com".to_string(),
+                go_version: None,
+            })
+            .into()],
+            source_params: SourceInitializeParams {
+                parent_path: "test".to_string(),
+            },
+            rollback_on_failure: true,
+        };
+
+        let local_project_service = LocalProjectService {
+            repo_service: MockRepoService,
+            ecosystem_service: MockEcosystemService,
+            source_service: MockSourceService,
+            facet_service: MockFacetService,
+            output_service: MockOutputService,
+            facet_registry: FacetRegistry::default(),
+            change_requests: ChangeRequestConfig::default(),
+            commit_grouping: CommitGrouping::default(),
+            events: EventEmitter::default(),
+            local_project_path: None,
+        };
+
+        let err = local_project_service
+            .initialize(project_params)
+            .await
+            .unwrap_err();
+
+        let err = err.downcast_ref::<InitializationError>().unwrap();
+        assert!(err.error.downcast_ref::<PushVerificationError>().is_some());
+        assert_eq!(
+            err.rollback.completed_steps,
+            vec![
+                InitializationStep::RepoCreated,
+                InitializationStep::SourceCloned,
+                InitializationStep::EcosystemInitialized,
+                InitializationStep::SourceFacetsInitialized,
+            ]
+        );
+        assert_eq!(
+            err.rollback.rolled_back,
+            vec![
+                "deleted local clone test/unpushed",
+                "deleted repo https://github.com/testuser/unpushed"
+            ]
+        );
+        assert!(err.rollback.failures.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_initialize_project_locked() {
+        let project_params = ProjectCreateParams {
+            name: "locked".to_string(),
+            repo_params: RepoCreateParams::Github(GithubRepoParams {
+                name: "locked".to_string(),
+                description: "foobar".to_string(),
+                organization: GithubUser::User("testuser".to_string()),
+                license: None,
+            }),
+            ecosystem_params: vec![EcosystemInitializeParams::Go(GoParams {
+                name: "locked".to_string(),
+                host: "github.com".to_string(),
+                go_version: None,
+            })
+            .into()],
+            source_params: SourceInitializeParams {
+                parent_path: "test".to_string(),
+            },
+            rollback_on_failure: false,
+        };
+
+        let local_project_service = LocalProjectService {
+            repo_service: MockRepoService,
+            ecosystem_service: MockEcosystemService,
+            source_service: MockSourceService,
+            facet_service: MockFacetService,
+            output_service: MockOutputService,
+            facet_registry: FacetRegistry::default(),
+            change_requests: ChangeRequestConfig::default(),
+            commit_grouping: CommitGrouping::default(),
+            events: EventEmitter::default(),
+            local_project_path: None,
+        };
+
+        let err = local_project_service
+            .initialize(project_params)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            err.downcast_ref::<ConcurrencyError>(),
+            Some(ConcurrencyError::Locked { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_update_project_conflict() {
+        let initialized_project = InitializedProject {
+            repo: InitializedRepo::Github(InitializedGithubRepo {
+                name: "conflict".to_string(),
+                organization: GithubUser::User("testuser".to_string()),
+            }),
+            mirror: None,
+            modules: vec![InitializedEcosystem::Go(InitializedGo {
+                name: "conflict".to_string(),
+                host: "github.com".to_string(),
+            })
+            .into()],
+            source: InitializedSource {
+                path: "test".to_string(),
+            },
+            facets: HashMap::new(),
+            name: "conflict".to_string(),
+            skootrs_version: None,
+            created_at: None,
+        };
+
+        let local_project_service = LocalProjectService {
+            repo_service: MockRepoService,
+            ecosystem_service: MockEcosystemService,
+            source_service: MockSourceService,
+            facet_service: MockFacetService,
+            output_service: MockOutputService,
+            facet_registry: FacetRegistry::default(),
+            change_requests: ChangeRequestConfig::default(),
+            commit_grouping: CommitGrouping::default(),
+            events: EventEmitter::default(),
+            local_project_path: None,
+        };
+
+        let err = local_project_service
+            .update(ProjectUpdateParams {
+                initialized_project,
+                facet_types: None,
+            })
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            err.downcast_ref::<ConcurrencyError>(),
+            Some(ConcurrencyError::Conflict { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_add_facet() {
+        let initialized_project = InitializedProject {
+            repo: InitializedRepo::Github(InitializedGithubRepo {
+                name: "test".to_string(),
+                organization: GithubUser::User("testuser".to_string()),
+            }),
+            mirror: None,
+            modules: vec![InitializedEcosystem::Go(InitializedGo {
+                name: "test".to_string(),
+                host: "github.com".to_string(),
+            })
+            .into()],
+            source: InitializedSource {
+                path: "test".to_string(),
+            },
+            facets: HashMap::from([(
+                FacetMapKey::Type(SupportedFacetType::Readme),
+                InitializedFacet::SourceBundle(SourceBundleFacet {
+                    source_files: None,
+                    facet_type: SupportedFacetType::Readme,
+                    source_files_content: None,
+                    labels: vec![],
+                    packages: vec![],
+                    template_version: None,
+                    generator_fingerprint: None,
+                }),
+            )]),
+            name: "test".to_string(),
+            skootrs_version: None,
+            created_at: None,
+        };
+
+        let local_project_service = LocalProjectService {
+            repo_service: MockRepoService,
+            ecosystem_service: MockEcosystemService,
+            source_service: MockSourceService,
+            facet_service: MockFacetService,
+            output_service: MockOutputService,
+            facet_registry: FacetRegistry::default(),
+            change_requests: ChangeRequestConfig::default(),
+            commit_grouping: CommitGrouping::default(),
+            events: EventEmitter::default(),
+            local_project_path: None,
+        };
+
+        let err = local_project_service
+            .add_facet(FacetAddParams {
+                initialized_project: initialized_project.clone(),
+                facet_type: SupportedFacetType::Readme,
+                options: BTreeMap::new(),
+            })
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("already has the Readme facet"));
+
+        // Facet types that are only reserved so far are refused instead of panicking.
+        for facet_type in [
+            SupportedFacetType::StaticCodeAnalysis,
+            SupportedFacetType::Allstar,
+            SupportedFacetType::Other,
+        ] {
+            let err = local_project_service
+                .add_facet(FacetAddParams {
+                    initialized_project: initialized_project.clone(),
+                    facet_type: facet_type.clone(),
+                    options: BTreeMap::new(),
+                })
+                .await
+                .unwrap_err();
+            assert!(err
+                .to_string()
+                .contains(&format!("Facet {facet_type} isn't implemented yet")));
         }
+
+        let err = local_project_service
+            .add_facet(FacetAddParams {
+                initialized_project: initialized_project.clone(),
+                facet_type: SupportedFacetType::BranchProtection,
+                options: BTreeMap::from([("team".to_string(), "infra".into())]),
+            })
+            .await
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("team is not an option of facet BranchProtection"));
+
+        // The registry only supports the Container facet for Maven and Python projects.
+        let err = local_project_service
+            .add_facet(FacetAddParams {
+                initialized_project: initialized_project.clone(),
+                facet_type: SupportedFacetType::Container,
+                options: BTreeMap::new(),
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Facet Container is only supported for Maven, Python projects"
+        );
+
+        let updated_project = local_project_service
+            .add_facet(FacetAddParams {
+                initialized_project,
+                facet_type: SupportedFacetType::BranchProtection,
+                options: BTreeMap::new(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(updated_project.facets.len(), 2);
+        assert!(matches!(
+            updated_project
+                .facets
+                .get(&FacetMapKey::Type(SupportedFacetType::BranchProtection)),
+            Some(InitializedFacet::APIBundle(_))
+        ));
     }
 
-    impl RootFacetService for MockFacetService {
-        async fn initialize(
-            &self,
-            params: FacetCreateParams,
-        ) -> Result<InitializedFacet, SkootError> {
-            match params {
-                FacetCreateParams::SourceBundle(s) => {
-                    if s.common.project_name == "error" {
-                        return Err("Error".into());
-                    }
-                    let source_bundle_facet = SourceBundleFacet {
+    #[tokio::test]
+    async fn test_delete_facet() {
+        let api_bundle_facet = |facet_type: SupportedFacetType| {
+            InitializedFacet::APIBundle(APIBundleFacet {
+                facet_type,
+                apis: vec![],
+                labels: vec![],
+                skipped_reason: None,
+                template_version: None,
+                generator_fingerprint: None,
+            })
+        };
+        let initialized_project = InitializedProject {
+            repo: InitializedRepo::Github(InitializedGithubRepo {
+                name: "test".to_string(),
+                organization: GithubUser::User("testuser".to_string()),
+            }),
+            mirror: None,
+            modules: vec![InitializedEcosystem::Go(InitializedGo {
+                name: "test".to_string(),
+                host: "github.com".to_string(),
+            })
+            .into()],
+            source: InitializedSource {
+                path: "test".to_string(),
+            },
+            facets: HashMap::from([
+                (
+                    FacetMapKey::Type(SupportedFacetType::Readme),
+                    InitializedFacet::SourceBundle(SourceBundleFacet {
                         source_files: Some(vec![SourceFile {
                             name: "README.md".to_string(),
                             path: "./".to_string(),
@@ -570,87 +3294,305 @@ mod tests {
                         }]),
                         facet_type: SupportedFacetType::Readme,
                         source_files_content: None,
-                        labels: vec![Label::Custom("test".to_string())],
-                    };
+                        labels: vec![],
+                        packages: vec![],
+                        template_version: None,
+                        generator_fingerprint: None,
+                    }),
+                ),
+                (
+                    FacetMapKey::Type(SupportedFacetType::CodeReview),
+                    api_bundle_facet(SupportedFacetType::CodeReview),
+                ),
+            ]),
+            name: "test".to_string(),
+            skootrs_version: None,
+            created_at: None,
+        };
 
-                    Ok(InitializedFacet::SourceBundle(source_bundle_facet))
-                }
-                FacetCreateParams::APIBundle(a) => {
-                    if a.common.project_name == "error" {
-                        return Err("Error".into());
-                    }
-                    let api_bundle_facet = APIBundleFacet {
-                        apis: vec![APIContent {
-                            name: "test".to_string(),
-                            url: "https://foo.bar/test".to_string(),
-                            response: "worked".to_string(),
-                        }],
-                        facet_type: SupportedFacetType::BranchProtection,
-                        labels: vec![Label::Custom("test".to_string())],
-                    };
+        let local_project_service = LocalProjectService {
+            repo_service: MockRepoService,
+            ecosystem_service: MockEcosystemService,
+            source_service: MockSourceService,
+            facet_service: MockFacetService,
+            output_service: MockOutputService,
+            facet_registry: FacetRegistry::default(),
+            change_requests: ChangeRequestConfig::default(),
+            commit_grouping: CommitGrouping::default(),
+            events: EventEmitter::default(),
+            local_project_path: None,
+        };
 
-                    Ok(InitializedFacet::APIBundle(api_bundle_facet))
-                }
-            }
-        }
+        let updated_project = local_project_service
+            .delete_facet(FacetDeleteParams {
+                initialized_project: initialized_project.clone(),
+                facet_map_key: FacetMapKey::Type(SupportedFacetType::Readme),
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            updated_project.facets.keys().collect::<Vec<_>>(),
+            vec![&FacetMapKey::Type(SupportedFacetType::CodeReview)]
+        );
+
+        let err = local_project_service
+            .delete_facet(FacetDeleteParams {
+                initialized_project: updated_project,
+                facet_map_key: FacetMapKey::Type(SupportedFacetType::Readme),
+            })
+            .await
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("doesn't have a facet with key Type: Readme"));
+
+        // A facet that can't be torn down stays in the project.
+        assert!(local_project_service
+            .delete_facet(FacetDeleteParams {
+                initialized_project,
+                facet_map_key: FacetMapKey::Type(SupportedFacetType::CodeReview),
+            })
+            .await
+            .is_err());
+    }
 
-        async fn initialize_all(
-            &self,
-            params: FacetSetCreateParams,
-        ) -> Result<Vec<InitializedFacet>, SkootError> {
-            let mut initialized_facets = Vec::new();
-            for facet_params in params.facets_params {
-                let initialized_facet = self.initialize(facet_params).await?;
-                initialized_facets.push(initialized_facet);
-            }
+    #[tokio::test]
+    async fn test_add_facet_change_mode() {
+        let project = |repo: InitializedRepo| InitializedProject {
+            repo,
+            mirror: None,
+            modules: vec![InitializedEcosystem::Go(InitializedGo {
+                name: "test".to_string(),
+                host: "github.com".to_string(),
+            })
+            .into()],
+            source: InitializedSource {
+                path: "test".to_string(),
+            },
+            facets: HashMap::new(),
+            name: "test".to_string(),
+            skootrs_version: None,
+            created_at: None,
+        };
+        let closed = InitializedRepo::Github(InitializedGithubRepo {
+            name: "closed".to_string(),
+            organization: GithubUser::User("testuser".to_string()),
+        });
+        let service = |mode: ChangeMode| LocalProjectService {
+            repo_service: MockRepoService,
+            ecosystem_service: MockEcosystemService,
+            source_service: MockSourceService,
+            facet_service: MockFacetService,
+            output_service: MockOutputService,
+            facet_registry: FacetRegistry::default(),
+            change_requests: ChangeRequestConfig {
+                mode,
+                ..ChangeRequestConfig::default()
+            },
+            commit_grouping: CommitGrouping::default(),
+            events: EventEmitter::default(),
+            local_project_path: None,
+        };
+        let add_readme = |initialized_project: InitializedProject| FacetAddParams {
+            initialized_project,
+            facet_type: SupportedFacetType::Readme,
+            options: BTreeMap::new(),
+        };
 
-            Ok(initialized_facets)
-        }
+        // The change is proposed as a pull request by default, which the repo doesn't accept.
+        assert!(service(ChangeMode::PullRequest)
+            .add_facet(add_readme(project(closed.clone())))
+            .await
+            .is_err());
+        assert!(service(ChangeMode::Push)
+            .add_facet(add_readme(project(closed)))
+            .await
+            .is_ok());
+        // Pull requests can't be opened on generic git remotes, so they're pushed to directly.
+        assert!(service(ChangeMode::PullRequest)
+            .add_facet(add_readme(project(InitializedRepo::GenericGit(
+                InitializedGenericGitRepo::from_remote_url(
+                    "https://git.example.com/testuser/closed.git".to_string()
+                )
+                .unwrap()
+            ))))
+            .await
+            .is_ok());
     }
 
-    impl OutputService for MockOutputService {
-        async fn list(
-            &self,
-            _params: ProjectOutputsListParams,
-        ) -> Result<Vec<ProjectOutputReference>, SkootError> {
-            Ok(vec![ProjectOutputReference {
-                name: "test".into(),
-                output_type: ProjectOutputType::SBOM,
-                labels: vec![Label::Custom("test".to_string())],
-            }])
-        }
+    #[test]
+    fn test_branch_slug() {
+        assert_eq!(
+            branch_slug("Added CodeOfConduct facet to project"),
+            "added-codeofconduct-facet-to-project"
+        );
+    }
 
-        async fn get(
-            &self,
-            _params: skootrs_model::skootrs::ProjectOutputGetParams,
-        ) -> Result<skootrs_model::skootrs::ProjectOutput, SkootError> {
-            Ok(skootrs_model::skootrs::ProjectOutput {
-                reference: ProjectOutputReference {
-                    name: "test".into(),
-                    output_type: ProjectOutputType::SBOM,
-                    labels: vec![Label::Custom("test".to_string())],
-                },
-                output: "test".into(),
-            })
-        }
+    #[test]
+    fn test_commit_message() {
+        let message = facet_commit_message(&SupportedFacetType::License);
+        assert!(message.starts_with("Added License facet\n\nSkootrs-Facet: License\n"));
+        assert!(message.contains(&format!("Skootrs-Version: {SKOOTRS_VERSION}")));
+        assert!(!commit_message("Initialized project", &[]).contains(FACET_TRAILER));
     }
 
     #[tokio::test]
-    async fn test_initialize_project() {
-        let project_params = ProjectCreateParams {
-            name: "test".to_string(),
-            repo_params: RepoCreateParams::Github(GithubRepoParams {
+    async fn test_delete_project() {
+        let initialized_project = InitializedProject {
+            repo: InitializedRepo::Github(InitializedGithubRepo {
                 name: "test".to_string(),
-                description: "foobar".to_string(),
                 organization: GithubUser::User("testuser".to_string()),
             }),
-            ecosystem_params: EcosystemInitializeParams::Go(GoParams {
+            mirror: None,
+            modules: vec![InitializedEcosystem::Go(InitializedGo {
                 name: "test".to_string(),
                 host: "github.com".to_string(),
+            })
+            .into()],
+            source: InitializedSource {
+                path: "test".to_string(),
+            },
+            facets: HashMap::new(),
+            name: "test".to_string(),
+            skootrs_version: None,
+            created_at: None,
+        };
+
+        let local_project_service = LocalProjectService {
+            repo_service: MockRepoService,
+            ecosystem_service: MockEcosystemService,
+            source_service: MockSourceService,
+            facet_service: MockFacetService,
+            output_service: MockOutputService,
+            facet_registry: FacetRegistry::default(),
+            change_requests: ChangeRequestConfig::default(),
+            commit_grouping: CommitGrouping::default(),
+            events: EventEmitter::default(),
+            local_project_path: None,
+        };
+
+        let unconfirmed = local_project_service
+            .delete(ProjectDeleteParams {
+                initialized_project: initialized_project.clone(),
+                confirm: false,
+            })
+            .await;
+        assert!(unconfirmed.is_err());
+
+        let url = local_project_service
+            .delete(ProjectDeleteParams {
+                initialized_project: initialized_project.clone(),
+                confirm: true,
+            })
+            .await
+            .unwrap();
+        assert_eq!(url, "https://github.com/testuser/test");
+
+        // A local clone is locked before the repo is deleted.
+        let temp_dir = tempdir::TempDir::new("skootrs-delete").unwrap();
+        let mut cloned_project = initialized_project.clone();
+        cloned_project.source.path = temp_dir.path().join("locked").to_string_lossy().to_string();
+        std::fs::create_dir(&cloned_project.source.path).unwrap();
+        let locked = local_project_service
+            .delete(ProjectDeleteParams {
+                initialized_project: cloned_project,
+                confirm: true,
+            })
+            .await;
+        assert!(locked.is_err());
+
+        // A project that was never cloned here is only deleted remotely.
+        let mut uncloned_project = initialized_project;
+        uncloned_project.source.path = temp_dir.path().join("error").to_string_lossy().to_string();
+        assert!(local_project_service
+            .delete(ProjectDeleteParams {
+                initialized_project: uncloned_project,
+                confirm: true,
+            })
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_relocate_project() {
+        let project = |name: &str| InitializedProject {
+            repo: InitializedRepo::Github(InitializedGithubRepo {
+                name: name.to_string(),
+                organization: GithubUser::User("olduser".to_string()),
+            }),
+            mirror: None,
+            modules: vec![InitializedEcosystem::Go(InitializedGo {
+                name: name.to_string(),
+                host: "github.com".to_string(),
+            })
+            .into()],
+            source: InitializedSource {
+                path: "test".to_string(),
+            },
+            facets: HashMap::new(),
+            name: name.to_string(),
+            skootrs_version: None,
+            created_at: None,
+        };
+
+        let local_project_service = LocalProjectService {
+            repo_service: MockRepoService,
+            ecosystem_service: MockEcosystemService,
+            source_service: MockSourceService,
+            facet_service: MockFacetService,
+            output_service: MockOutputService,
+            facet_registry: FacetRegistry::default(),
+            change_requests: ChangeRequestConfig::default(),
+            commit_grouping: CommitGrouping::default(),
+            events: EventEmitter::default(),
+            local_project_path: None,
+        };
+
+        // The mock repo service finds every repo at `testuser/test`.
+        let relocation = local_project_service
+            .relocate(ProjectRelocateParams {
+                initialized_project: project("old"),
+            })
+            .await
+            .unwrap();
+        assert_eq!(relocation.old_url, "https://github.com/olduser/old");
+        assert_eq!(relocation.new_url, "https://github.com/testuser/test");
+        assert_eq!(relocation.project.repo.full_url(), relocation.new_url);
+        assert_eq!(relocation.project.source.path, "test/test");
+
+        let mut unmoved = project("test");
+        unmoved.repo = InitializedRepo::Github(InitializedGithubRepo {
+            name: "Test".to_string(),
+            organization: GithubUser::User("TestUser".to_string()),
+        });
+        assert!(local_project_service
+            .relocate(ProjectRelocateParams {
+                initialized_project: unmoved,
+            })
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_archive_project() {
+        let initialized_project = InitializedProject {
+            repo: InitializedRepo::Github(InitializedGithubRepo {
+                name: "test".to_string(),
+                organization: GithubUser::User("testuser".to_string()),
             }),
-            source_params: SourceInitializeParams {
-                parent_path: "test".to_string(),
+            mirror: None,
+            modules: vec![InitializedEcosystem::Go(InitializedGo {
+                name: "test".to_string(),
+                host: "github.com".to_string(),
+            })
+            .into()],
+            source: InitializedSource {
+                path: "test".to_string(),
             },
+            facets: HashMap::new(),
+            name: "test".to_string(),
+            skootrs_version: None,
+            created_at: None,
         };
 
         let local_project_service = LocalProjectService {
@@ -659,25 +3601,443 @@ mod tests {
             source_service: MockSourceService,
             facet_service: MockFacetService,
             output_service: MockOutputService,
+            facet_registry: FacetRegistry::default(),
+            change_requests: ChangeRequestConfig::default(),
+            commit_grouping: CommitGrouping::default(),
+            events: EventEmitter::default(),
+            local_project_path: None,
         };
 
-        let result = local_project_service.initialize(project_params).await;
+        let archived = local_project_service
+            .archive(ProjectArchiveParams {
+                initialized_project: initialized_project.clone(),
+                cleanup: false,
+            })
+            .await
+            .unwrap();
+        assert_eq!(archived.project_url, "https://github.com/testuser/test");
+        assert!(archived.cleanup.is_none());
+
+        let archived = local_project_service
+            .archive(ProjectArchiveParams {
+                initialized_project,
+                cleanup: true,
+            })
+            .await
+            .unwrap();
+        let cleanup = archived.cleanup.unwrap();
+        assert_eq!(cleanup.deleted_artifacts, 2);
+        assert_eq!(cleanup.deleted_caches, 1);
+        assert_eq!(
+            cleanup.disabled_workflows,
+            vec![".github/workflows/scorecard.yml"]
+        );
+    }
 
-        assert!(result.is_ok());
-        let initialized_project = result.unwrap();
+    #[tokio::test]
+    async fn test_link_mirror() {
+        let skipped_facet = InitializedFacet::APIBundle(APIBundleFacet {
+            facet_type: SupportedFacetType::BranchProtection,
+            apis: vec![],
+            labels: vec![],
+            skipped_reason: Some("no API".to_string()),
+            template_version: None,
+            generator_fingerprint: None,
+        });
+        let initialized_project = InitializedProject {
+            repo: InitializedRepo::GenericGit(
+                InitializedGenericGitRepo::from_remote_url(
+                    "https://git.example.com/team/test.git".to_string(),
+                )
+                .unwrap(),
+            ),
+            mirror: None,
+            modules: vec![InitializedEcosystem::Go(InitializedGo {
+                name: "test".to_string(),
+                host: "git.example.com".to_string(),
+            })
+            .into()],
+            source: InitializedSource {
+                path: "test".to_string(),
+            },
+            facets: HashMap::from([(
+                FacetMapKey::Type(SupportedFacetType::BranchProtection),
+                skipped_facet,
+            )]),
+            name: "test".to_string(),
+            skootrs_version: None,
+            created_at: None,
+        };
 
-        assert!(initialized_project.repo.full_url() == "https://github.com/testuser/test");
-        let module = match initialized_project.ecosystem {
-            InitializedEcosystem::Go(g) => g,
-            _ => panic!("Wrong ecosystem type"),
+        let local_project_service = LocalProjectService {
+            repo_service: MockRepoService,
+            ecosystem_service: MockEcosystemService,
+            source_service: MockSourceService,
+            facet_service: MockFacetService,
+            output_service: MockOutputService,
+            facet_registry: FacetRegistry::default(),
+            change_requests: ChangeRequestConfig::default(),
+            commit_grouping: CommitGrouping::default(),
+            events: EventEmitter::default(),
+            local_project_path: None,
         };
-        assert!(module.name == "test");
-        assert!(initialized_project.source.path == "test/test");
-        println!("{:#?}", initialized_project.facets);
 
-        // TODO: This will always be equal to 2 because we are initializing two facets in the mock facet service
-        // and the `HashMap` for the facets will keep getting the same key. This is probably not a great way
-        // of handling that.
-        assert_eq!(initialized_project.facets.len(), 2);
+        let linked = local_project_service
+            .link_mirror(ProjectMirrorParams {
+                initialized_project: initialized_project.clone(),
+                mirror_url: "https://github.com/testuser/test".to_string(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            linked.mirror.as_ref().map(InitializedRepo::full_url),
+            Some("https://github.com/testuser/test".to_string())
+        );
+        assert_eq!(linked.repo.full_url(), initialized_project.repo.full_url());
+        assert_eq!(
+            linked.api_repo().full_url(),
+            "https://github.com/testuser/test"
+        );
+        // The API facets that were skipped on the generic git remote are applied to the Github mirror.
+        match &linked.facets[&FacetMapKey::Type(SupportedFacetType::BranchProtection)] {
+            InitializedFacet::APIBundle(a) => assert!(a.skipped_reason.is_none()),
+            InitializedFacet::SourceBundle(_) => panic!("Expected an API bundle facet"),
+        }
+
+        let linked_to_itself = local_project_service
+            .link_mirror(ProjectMirrorParams {
+                initialized_project: InitializedProject {
+                    repo: linked.mirror.clone().unwrap(),
+                    ..initialized_project
+                },
+                mirror_url: "https://github.com/testuser/test".to_string(),
+            })
+            .await;
+        assert!(linked_to_itself.is_err());
+    }
+
+    #[test]
+    fn test_reconcile_source_facets() {
+        fn source_bundle(
+            facet_type: SupportedFacetType,
+            name: &str,
+            hash: &str,
+        ) -> InitializedFacet {
+            InitializedFacet::SourceBundle(SourceBundleFacet {
+                source_files: Some(vec![SourceFile {
+                    name: name.to_string(),
+                    path: "./".to_string(),
+                    hash: hash.to_string(),
+                }]),
+                facet_type,
+                source_files_content: None,
+                labels: vec![],
+                packages: vec![],
+                template_version: None,
+                generator_fingerprint: None,
+            })
+        }
+
+        let recorded = [
+            source_bundle(SupportedFacetType::Readme, "README.md", "readme"),
+            source_bundle(SupportedFacetType::License, "LICENSE", "license"),
+            source_bundle(
+                SupportedFacetType::SecurityPolicy,
+                "SECURITY.md",
+                "security",
+            ),
+        ]
+        .into_iter()
+        .map(|f| (FacetMapKey::Type(f.facet_type()), f))
+        .collect::<HashMap<_, _>>();
+        let current_hashes = [
+            ("./README.md".to_string(), "readme".to_string()),
+            ("./LICENSE".to_string(), "license".to_string()),
+            ("./SECURITY.md".to_string(), "edited".to_string()),
+        ]
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+        let rendered = [
+            source_bundle(SupportedFacetType::Readme, "README.md", "readme"),
+            source_bundle(SupportedFacetType::License, "LICENSE", "newlicense"),
+            source_bundle(
+                SupportedFacetType::SecurityPolicy,
+                "SECURITY.md",
+                "security",
+            ),
+            source_bundle(SupportedFacetType::Gitignore, ".gitignore", "gitignore"),
+        ];
+
+        let drift = reconcile_source_facets(&recorded, &current_hashes, &rendered);
+        let statuses = drift
+            .iter()
+            .map(|d| (d.facet_type.clone(), d.status.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            statuses,
+            vec![
+                (SupportedFacetType::Readme, FacetDriftStatus::UpToDate),
+                (SupportedFacetType::License, FacetDriftStatus::Outdated),
+                (
+                    SupportedFacetType::SecurityPolicy,
+                    FacetDriftStatus::Drifted
+                ),
+                (SupportedFacetType::Gitignore, FacetDriftStatus::Added),
+            ]
+        );
+        assert_eq!(drift[2].files, vec!["./SECURITY.md".to_string()]);
+
+        // Facets recorded before template versions were count as generated by an older template, and facets
+        // that weren't recorded at all are added rather than regenerated.
+        let mut rendered = rendered;
+        for facet in &mut rendered {
+            if let InitializedFacet::SourceBundle(s) = facet {
+                s.template_version = Some(1);
+            }
+        }
+        let outdated = outdated_templates(&recorded, &rendered);
+        assert_eq!(outdated.len(), 3);
+        assert!(outdated
+            .iter()
+            .all(|f| f.facet_type() != SupportedFacetType::Gitignore));
+        let regenerated = rendered
+            .iter()
+            .map(|f| (FacetMapKey::Type(f.facet_type()), f.clone()))
+            .collect::<HashMap<_, _>>();
+        assert!(outdated_templates(&regenerated, &rendered).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_verify_state() {
+        let temp_dir = tempdir::TempDir::new("skootrs-verify-state").unwrap();
+        let source = InitializedSource {
+            path: temp_dir.path().to_string_lossy().to_string(),
+        };
+        let source_bundle = |facet_type: SupportedFacetType, name: &str, content: &[u8]| {
+            InitializedFacet::SourceBundle(SourceBundleFacet {
+                source_files: Some(vec![SourceFile {
+                    name: name.to_string(),
+                    path: "./".to_string(),
+                    hash: format!("{:x}", sha2::Sha256::digest(content)),
+                }]),
+                facet_type,
+                source_files_content: None,
+                labels: vec![],
+                packages: vec![],
+                template_version: None,
+                generator_fingerprint: None,
+            })
+        };
+        let initialized_project = InitializedProject {
+            repo: InitializedRepo::GenericGit(
+                InitializedGenericGitRepo::from_remote_url(
+                    "https://git.example.com/team/test.git".to_string(),
+                )
+                .unwrap(),
+            ),
+            mirror: None,
+            modules: vec![InitializedEcosystem::Go(InitializedGo {
+                name: "test".to_string(),
+                host: "git.example.com".to_string(),
+            })
+            .into()],
+            source: source.clone(),
+            facets: HashMap::from([
+                (
+                    FacetMapKey::Type(SupportedFacetType::Readme),
+                    source_bundle(SupportedFacetType::Readme, "README.md", b"readme"),
+                ),
+                (
+                    FacetMapKey::Type(SupportedFacetType::DefaultSourceCode),
+                    source_bundle(
+                        SupportedFacetType::DefaultSourceCode,
+                        "main.go",
+                        b"package main",
+                    ),
+                ),
+                (
+                    FacetMapKey::Type(SupportedFacetType::BranchProtection),
+                    InitializedFacet::APIBundle(APIBundleFacet {
+                        facet_type: SupportedFacetType::BranchProtection,
+                        apis: vec![],
+                        labels: vec![],
+                        skipped_reason: None,
+                        template_version: None,
+                        generator_fingerprint: None,
+                    }),
+                ),
+            ]),
+            name: "test".to_string(),
+            skootrs_version: None,
+            created_at: None,
+        };
+        std::fs::write(
+            temp_dir.path().join(".skootrs"),
+            serde_json::to_string(&initialized_project).unwrap(),
+        )
+        .unwrap();
+        std::fs::write(temp_dir.path().join("README.md"), "readme").unwrap();
+        // The maintainers have changed the default source code, which isn't drift.
+        std::fs::write(temp_dir.path().join("main.go"), "package changed").unwrap();
+
+        let local_project_service = LocalProjectService {
+            repo_service: MockRepoService,
+            ecosystem_service: MockEcosystemService,
+            source_service: LocalSourceService::default(),
+            facet_service: MockFacetService,
+            output_service: MockOutputService,
+            facet_registry: FacetRegistry::default(),
+            change_requests: ChangeRequestConfig::default(),
+            commit_grouping: CommitGrouping::default(),
+            events: EventEmitter::default(),
+            local_project_path: None,
+        };
+
+        let audit = local_project_service
+            .verify_state(source.clone())
+            .await
+            .unwrap();
+        assert_eq!(audit.project_url, "https://git.example.com/team/test.git");
+        assert_eq!(audit.facets.len(), 1);
+        assert_eq!(audit.facets[0].facet_type, SupportedFacetType::Readme);
+        assert!(audit.allowlist.is_empty());
+        assert!(audit.is_compliant());
+
+        // An expired exception fails verification even though no facet has drifted.
+        std::fs::create_dir_all(temp_dir.path().join(".github")).unwrap();
+        std::fs::write(
+            temp_dir.path().join(SCAN_POLICY_PATH),
+            "allowlist:\n  - id: CVE-2020-0001\n    justification: No fix released\n    expires: 2020-01-01\n",
+        )
+        .unwrap();
+        let audit = local_project_service
+            .verify_state(source.clone())
+            .await
+            .unwrap();
+        assert_eq!(audit.allowlist.len(), 1);
+        assert_eq!(
+            audit.allowlist[0].status,
+            skootrs_model::skootrs::scan_policy::AllowlistAuditStatus::Expired
+        );
+        assert!(!audit.is_compliant());
+
+        std::fs::write(temp_dir.path().join(SCAN_POLICY_PATH), "allowlist: nope").unwrap();
+        assert!(local_project_service
+            .verify_state(source.clone())
+            .await
+            .is_err());
+        std::fs::remove_file(temp_dir.path().join(SCAN_POLICY_PATH)).unwrap();
+
+        std::fs::write(temp_dir.path().join("README.md"), "edited").unwrap();
+        let audit = local_project_service.verify_state(source).await.unwrap();
+        assert_eq!(audit.facets[0].status, FacetAuditStatus::Drifted);
+        assert!(!audit.is_compliant());
+    }
+
+    #[test]
+    fn test_audit_source_bundle() {
+        let facet = SourceBundleFacet {
+            source_files: Some(vec![
+                SourceFile {
+                    name: "README.md".to_string(),
+                    path: "./".to_string(),
+                    hash: format!("{:x}", sha2::Sha256::digest(b"readme")),
+                },
+                SourceFile {
+                    name: "SECURITY.md".to_string(),
+                    path: "./".to_string(),
+                    hash: format!("{:x}", sha2::Sha256::digest(b"security")),
+                },
+            ]),
+            facet_type: SupportedFacetType::Readme,
+            source_files_content: None,
+            labels: vec![],
+            packages: vec![],
+            template_version: None,
+            generator_fingerprint: None,
+        };
+
+        let mut contents = HashMap::from([
+            ("./README.md".to_string(), "readme".to_string()),
+            ("./SECURITY.md".to_string(), "security".to_string()),
+        ]);
+        let audit = audit_source_bundle(&facet, &contents);
+        assert_eq!(audit.status, FacetAuditStatus::Compliant);
+        assert!(audit.files.is_empty());
+
+        contents.insert("./SECURITY.md".to_string(), "edited".to_string());
+        let audit = audit_source_bundle(&facet, &contents);
+        assert_eq!(audit.status, FacetAuditStatus::Drifted);
+        assert_eq!(audit.files, vec!["./SECURITY.md".to_string()]);
+
+        contents.remove("./README.md");
+        let audit = audit_source_bundle(&facet, &contents);
+        assert_eq!(audit.status, FacetAuditStatus::Missing);
+        assert_eq!(audit.files, vec!["./README.md".to_string()]);
+    }
+
+    #[test]
+    fn test_diff_file() {
+        let diff = diff_file(Some("a\nb\n"), "a\nb\n", "README.md".to_string());
+        assert_eq!(diff.status, FacetFileDiffStatus::Unchanged);
+        assert!(diff.diff.is_empty());
+
+        let diff = diff_file(Some("a\nb\n"), "a\nc\n", "README.md".to_string());
+        assert_eq!(diff.status, FacetFileDiffStatus::Modified);
+        assert_eq!(
+            diff.diff,
+            "diff --git a/README.md b/README.md\n--- a/README.md\n+++ b/README.md\n@@ -1,2 +1,2 @@\n a\n-b\n+c\n"
+        );
+
+        let diff = diff_file(None, "a\n", ".github/workflows/ci.yml".to_string());
+        assert_eq!(diff.status, FacetFileDiffStatus::Added);
+        assert_eq!(
+            diff.diff,
+            "diff --git a/.github/workflows/ci.yml b/.github/workflows/ci.yml\nnew file mode 100644\n--- /dev/null\n+++ b/.github/workflows/ci.yml\n@@ -0,0 +1 @@\n+a\n"
+        );
+    }
+
+    #[test]
+    fn test_audit_pinned_dependencies() {
+        let policy = "exempt: []\n";
+        let facet = SourceBundleFacet {
+            source_files: Some(vec![SourceFile {
+                name: "pinning-policy.yml".to_string(),
+                path: "./.github".to_string(),
+                hash: format!("{:x}", sha2::Sha256::digest(policy.as_bytes())),
+            }]),
+            facet_type: SupportedFacetType::PinnedDependencies,
+            source_files_content: None,
+            labels: vec![],
+            packages: vec![],
+            template_version: None,
+            generator_fingerprint: None,
+        };
+
+        let mut contents = HashMap::from([
+            (
+                "./.github/pinning-policy.yml".to_string(),
+                policy.to_string(),
+            ),
+            (
+                "./.github/workflows/scorecard.yml".to_string(),
+                "- uses: actions/checkout@9bb56186c3b09b4f86b1c65136769dd318469633 # v4.1.2"
+                    .to_string(),
+            ),
+        ]);
+        let audit = audit_source_bundle(&facet, &contents);
+        assert_eq!(audit.status, FacetAuditStatus::Compliant);
+
+        // A workflow that was changed to use an action by tag breaks the policy.
+        contents.insert(
+            ".github/workflows/releases.yml".to_string(),
+            "- uses: googleapis/release-please-action@v4".to_string(),
+        );
+        let audit = audit_source_bundle(&facet, &contents);
+        assert_eq!(audit.status, FacetAuditStatus::Drifted);
+        assert_eq!(
+            audit.files,
+            vec![".github/workflows/releases.yml".to_string()]
+        );
     }
 }
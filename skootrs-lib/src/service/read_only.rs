@@ -0,0 +1,81 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Read-only mode lets Skootrs be pointed at a production org to explore it without any risk of changing it.
+//! The services call `ensure_writable` before every operation that has side effects, like creating a repo or
+//! pushing a commit, which fails with a `ReadOnlyError` instead when read-only mode is on. Operations that only
+//! read, like fetching a project's state or cloning it to look at its files, keep working.
+
+use std::{error::Error, fmt};
+
+use skootrs_model::skootrs::SkootError;
+
+/// The `ReadOnlyError` struct represents an operation with side effects that was blocked by read-only mode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadOnlyError {
+    /// What the blocked operation would have done, e.g. `archive https://github.com/org/repo`.
+    pub operation: String,
+}
+
+impl fmt::Display for ReadOnlyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Refusing to {} since Skootrs is in read-only mode",
+            self.operation
+        )
+    }
+}
+
+impl Error for ReadOnlyError {}
+
+/// Returns an error with a `ReadOnlyError` if `read_only` is set, so the operation can bail out before it
+/// changes anything.
+///
+/// # Errors
+///
+/// Returns a `ReadOnlyError` if `read_only` is set.
+pub fn ensure_writable(read_only: bool, operation: impl fmt::Display) -> Result<(), SkootError> {
+    if read_only {
+        return Err(SkootError::other(ReadOnlyError {
+            operation: operation.to_string(),
+        }));
+    }
+    Ok(())
+}
+
+/// Returns true if the error is the result of an operation being blocked by read-only mode.
+#[must_use]
+pub fn is_read_only(error: &SkootError) -> bool {
+    error.downcast_ref::<ReadOnlyError>().is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ensure_writable() {
+        assert!(ensure_writable(false, "push").is_ok());
+
+        let err = ensure_writable(true, "push to origin").unwrap_err();
+        assert!(is_read_only(&err));
+        assert_eq!(
+            err.to_string(),
+            "Refusing to push to origin since Skootrs is in read-only mode"
+        );
+        assert!(!is_read_only(&SkootError::from("some other error")));
+    }
+}
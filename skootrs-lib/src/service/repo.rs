@@ -16,13 +16,24 @@
 
 #![allow(clippy::module_name_repetitions)]
 
-use std::{process::Command, str::FromStr, sync::Arc};
+use std::{collections::HashMap, path::Path, process::Command, str::FromStr, sync::{Arc, Mutex}};
 
 use chrono::Utc;
-use octocrab::Octocrab;
-use tracing::{info, debug};
 
-use skootrs_model::{cd_events::repo_created::{RepositoryCreatedEvent, RepositoryCreatedEventContext, RepositoryCreatedEventContextId, RepositoryCreatedEventContextVersion, RepositoryCreatedEventSubject, RepositoryCreatedEventSubjectContent, RepositoryCreatedEventSubjectContentName, RepositoryCreatedEventSubjectContentUrl, RepositoryCreatedEventSubjectId}, skootrs::{InitializedRepoGetParams, GithubRepoParams, GithubUser, InitializedGithubRepo, InitializedRepo, InitializedSource, RepoCreateParams, SkootError}};
+use super::bitbucket::BitbucketClient;
+use super::cancellation::{self, CancellableCommand};
+use super::command::CommandRunner;
+use super::events::EventEmitter;
+use super::git2_source::{self, Git2SourceService, GitCredentials};
+use super::github_auth::GithubAuth;
+use super::read_only::ensure_writable;
+use super::repo_api::{BitbucketRepoApi, GithubRepoApi, RepoApiProvider};
+use super::retry::{retry, retry_rate_limited};
+use super::source::SourceService;
+use tracing::{info, debug, error};
+use http::{header::{HeaderMap, HeaderValue, ACCEPT, ETAG, IF_NONE_MATCH}, StatusCode};
+
+use skootrs_model::{cd_events::repo_created::{RepositoryCreatedEvent, RepositoryCreatedEventContext, RepositoryCreatedEventContextId, RepositoryCreatedEventContextVersion, RepositoryCreatedEventSubject, RepositoryCreatedEventSubjectContent, RepositoryCreatedEventSubjectContentName, RepositoryCreatedEventSubjectContentUrl, RepositoryCreatedEventSubjectId}, skootrs::{change_request::PullRequestParams, plan::PlannedAction, ActionsCleanup, Config, InitializedRepoGetParams, BitbucketRepoParams, GenericGitRepoParams, GithubRepoParams, GithubUser, InitializedBitbucketRepo, InitializedGenericGitRepo, InitializedGithubRepo, InitializedRepo, InitializedSource, RepoCreateParams, RetryPolicy, SkootError, GitBackend, bitbucket::BitbucketConfig, branch_protection::BranchProtectionConfig, code_review::CodeReviewConfig, license::SpdxLicense, ruleset::RepoRulesetConfig}};
 
 /// The `RepoService` trait provides an interface for initializing and managing a project's source code
 /// repository. This repo is usually something like Github or Gitlab.
@@ -35,7 +46,16 @@ pub trait RepoService {
     /// Returns an error if the source code repository can't be initialized.
     fn initialize(&self, params: RepoCreateParams) -> impl std::future::Future<Output = Result<InitializedRepo, SkootError>> + Send;  
 
-    /// Gets a project's source code repository metadata abstraction.
+    /// Returns the API calls and commands creating a project's source code repository and cloning it into
+    /// `path` would make and run, without changing anything.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the params are invalid.
+    fn plan(&self, params: &RepoCreateParams, path: &str) -> Result<Vec<PlannedAction>, SkootError>;
+
+    /// Gets a project's source code repository metadata abstraction. A Github repo that was renamed or
+    /// transferred is returned at its new location, following Github's redirect.
     ///
     /// # Errors
     ///
@@ -64,55 +84,208 @@ pub trait RepoService {
     /// Returns an error if the file can't be fetched from the repository for any reason.
     fn fetch_file_content<P: AsRef<std::path::Path> + Send>(&self, initialized_repo: &InitializedRepo, path: P) -> impl std::future::Future<Output = Result<String, SkootError>> + std::marker::Send;
 
+    /// Fetches several files from the repository at once, e.g. every file of a facet. The returned map is keyed
+    /// by the paths as they were given, and leaves out the files that aren't in the repository.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the files can't be fetched from the repository for any other reason.
+    fn fetch_file_contents(&self, initialized_repo: &InitializedRepo, paths: &[String]) -> impl std::future::Future<Output = Result<HashMap<String, String>, SkootError>> + Send;
+
+    /// Deletes the repository's Actions artifacts and caches and disables its scheduled workflows, so an archived
+    /// repo stops using runner minutes and storage. This has to be done before archiving since an archived repo
+    /// is read-only.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the artifacts, caches or workflows can't be listed. Failing to clean up a single item
+    /// is recorded in the returned `ActionsCleanup` instead.
+    fn cleanup_actions(&self, initialized_repo: &InitializedRepo) -> impl std::future::Future<Output = Result<ActionsCleanup, SkootError>> + Send;
+
+    /// Opens a pull request that proposes the changes on a branch, and enables auto-merge on it if that's
+    /// asked for. Returns the URL of the pull request.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pull request can't be opened, e.g. because the repo isn't on Github. Failing to
+    /// enable auto-merge is only logged, since the pull request is open by then.
+    fn open_pull_request(&self, initialized_repo: &InitializedRepo, params: PullRequestParams) -> impl std::future::Future<Output = Result<String, SkootError>> + Send;
+
     fn archive(&self, initialized_repo: InitializedRepo) -> impl std::future::Future<Output = Result<String, SkootError>> + Send;
+
+    /// Permanently deletes the source code repository. Returns the URL of the deleted repo.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the repository can't be deleted, e.g. the token lacks the `delete_repo` scope.
+    fn delete(&self, initialized_repo: InitializedRepo) -> impl std::future::Future<Output = Result<String, SkootError>> + Send;
 }
 
 /// The `LocalRepoService` struct provides an implementation of the `RepoService` trait for initializing
 /// and managing a project's source code repository from the local machine. This doesn't mean the repo is
 /// local, but that the operations like API calls are run from the local machine.
-#[derive(Debug)]
-pub struct LocalRepoService {}
+#[derive(Debug, Default)]
+pub struct LocalRepoService {
+    /// The credential used for the Github API.
+    pub github_auth: GithubAuth,
+    /// How Github API calls are retried when they fail with a transient error or hit a rate limit.
+    pub retry_policy: RetryPolicy,
+    /// How the Bitbucket API is reached for repos hosted on Bitbucket.
+    pub bitbucket: BitbucketConfig,
+    /// Whether operations that change the repo are refused.
+    pub read_only: bool,
+    /// Which git implementation repos are cloned with.
+    pub git_backend: GitBackend,
+    /// What remotes are authenticated to with when cloning with libgit2.
+    pub git_credentials: GitCredentials,
+    /// Where the events for created repos are sent.
+    pub events: EventEmitter,
+    /// The files fetched from Github, so they're only fetched again once they change.
+    pub content_cache: ContentCache,
+}
+
+/// The files fetched from Github. Clones of the cache share the same files, so it can be kept across the
+/// services of a long running process like the daemon.
+#[derive(Clone, Debug, Default)]
+pub struct ContentCache {
+    /// The last response of each Github API route along with its ETag. Github doesn't send a response again
+    /// while its ETag still matches, and doesn't count that against the rate limit.
+    responses: Arc<Mutex<HashMap<String, (String, String)>>>,
+    /// The content of each git blob by its SHA. A blob's content never changes, so it's only fetched once.
+    blobs: Arc<Mutex<HashMap<String, String>>>,
+}
+
+impl ContentCache {
+    fn response(&self, route: &str) -> Option<(String, String)> {
+        self.responses.lock().ok()?.get(route).cloned()
+    }
+
+    fn insert_response(&self, route: &str, etag: String, content: String) {
+        if let Ok(mut responses) = self.responses.lock() {
+            responses.insert(route.to_string(), (etag, content));
+        }
+    }
+
+    fn blob(&self, sha: &str) -> Option<String> {
+        self.blobs.lock().ok()?.get(sha).cloned()
+    }
+
+    fn insert_blob(&self, sha: &str, content: String) {
+        if let Ok(mut blobs) = self.blobs.lock() {
+            blobs.insert(sha.to_string(), content);
+        }
+    }
+}
+
+/// A git tree as returned by the Github API.
+#[derive(serde::Deserialize)]
+struct GithubTree {
+    tree: Vec<GithubTreeEntry>,
+    /// Whether the tree has too many entries for Github to return them all.
+    #[serde(default)]
+    truncated: bool,
+}
+
+/// An entry of a git tree as returned by the Github API.
+#[derive(serde::Deserialize)]
+struct GithubTreeEntry {
+    path: String,
+    #[serde(rename = "type")]
+    entry_type: String,
+    sha: String,
+}
 
 impl RepoService for LocalRepoService {
     async fn initialize(&self, params: RepoCreateParams) -> Result<InitializedRepo, SkootError> {
-        // TODO: The octocrab initialization should be done in a better place and be parameterized
-        let o: octocrab::Octocrab = octocrab::Octocrab::builder()
-            .personal_token(
-                    std::env::var("GITHUB_TOKEN").expect("GITHUB_TOKEN env var must be populated"),
-            )
-            .build()?;
-        octocrab::initialise(o);
+        ensure_writable(
+            self.read_only,
+            format_args!("create {}", params.initialized_repo()?.full_url()),
+        )?;
         match params {
             RepoCreateParams::Github(g) => {
                 let github_repo_handler = GithubRepoHandler {
-                    client: octocrab::instance(),
+                    client: Arc::new(self.github_auth.client()?),
+                    retry_policy: self.retry_policy.clone(),
                 };
-                Ok(InitializedRepo::Github(github_repo_handler.create(g).await?))
+                Ok(InitializedRepo::Github(github_repo_handler.create(g, &self.events).await?))
+            },
+            RepoCreateParams::GenericGit(g) => {
+                Ok(InitializedRepo::GenericGit(GenericGitRepoHandler::create(g)?))
+            },
+            RepoCreateParams::Bitbucket(b) => {
+                Ok(InitializedRepo::Bitbucket(self.bitbucket_repo_handler().create(b, &self.events).await?))
             },
         }
     }
 
+    fn plan(&self, params: &RepoCreateParams, path: &str) -> Result<Vec<PlannedAction>, SkootError> {
+        let initialized_repo = params.initialized_repo()?;
+        let mut actions = vec![];
+        if let RepoCreateParams::Github(g) = params {
+            actions.push(PlannedAction::ApiCall {
+                description: format!("Create Github repo {}/{}", g.organization.get_name(), g.name),
+                method: "POST".to_string(),
+                url: GithubRepoHandler::create_endpoint(&g.organization),
+                body: Some(serde_json::to_value(NewGithubRepoParams::from(g))?),
+                facet_type: None,
+            });
+        }
+        if let RepoCreateParams::Bitbucket(b) = params {
+            actions.push(PlannedAction::ApiCall {
+                description: format!("Create Bitbucket repo {}/{}", b.workspace, b.name),
+                method: "POST".to_string(),
+                url: BitbucketClient::repo_route(&InitializedBitbucketRepo { name: b.name.clone(), workspace: b.workspace.clone() }),
+                body: Some(BitbucketRepoHandler::new_repo_body(b)),
+                facet_type: None,
+            });
+        }
+        actions.push(PlannedAction::Command {
+            description: format!("Clone {}", initialized_repo.full_url()),
+            args: vec!["git".to_string(), "clone".to_string(), initialized_repo.full_url(), initialized_repo.name()],
+            dir: path.to_string(),
+        });
+        Ok(actions)
+    }
+
     fn clone_local(&self, initialized_repo: InitializedRepo, path: String) -> Result<InitializedSource, SkootError> {
+        if self.git_backend == GitBackend::Libgit2 {
+            return git2_source::clone(&self.git_credentials, &initialized_repo, &path);
+        }
         match initialized_repo {
             InitializedRepo::Github(g) => {
                 GithubRepoHandler::clone_local(&g, &path)
             },
+            InitializedRepo::GenericGit(g) => {
+                GenericGitRepoHandler::clone_local(&g, &path)
+            },
+            InitializedRepo::Bitbucket(b) => {
+                BitbucketRepoHandler::clone_local(&b, &path)
+            },
         }
     }
     
     fn clone_local_or_pull(&self, initialized_repo: InitializedRepo, path: String) -> Result<InitializedSource, SkootError> {
+        if self.git_backend == GitBackend::Libgit2 {
+            if git2::Repository::open(&path).is_err() {
+                return self.clone_local(initialized_repo, path);
+            }
+            let source = InitializedSource { path };
+            let source_service = Git2SourceService { credentials: self.git_credentials.clone(), ..Default::default() };
+            source_service.pull_updates(source.clone())?;
+            return Ok(source);
+        }
         // Check if path exists and is a git repo
         let output = Command::new("git")
             .arg("status")
             .current_dir(&path)
-            .output()?;
+            .cancellable_output()?;
 
         // If it is, pull updates
         if output.status.success() {
-            let _output = Command::new("git")
+            Command::new("git")
                 .arg("pull")
                 .current_dir(&path)
-                .output()?;
+                .run_checked()?;
             Ok(InitializedSource {
                 path,
             })
@@ -123,23 +296,42 @@ impl RepoService for LocalRepoService {
     }
     
     async fn get(&self, params: InitializedRepoGetParams) -> Result<InitializedRepo, SkootError> {
-        let parsed_url = url::Url::parse(&params.repo_url)?;
+        if let InitializedRepo::GenericGit(g) = InitializedRepo::try_from(params.repo_url.clone())? {
+            GenericGitRepoHandler::ls_remote(&g.remote_url)?;
+            return Ok(InitializedRepo::GenericGit(g));
+        }
+        if let InitializedRepo::Bitbucket(b) = InitializedRepo::try_from(params.repo_url.clone())? {
+            self.bitbucket_repo_handler().get(&b).await?;
+            return Ok(InitializedRepo::Bitbucket(b));
+        }
+        let parsed_url = url::Url::parse(&params.repo_url).map_err(SkootError::other)?;
         match parsed_url.host_str() {
             Some("github.com") => {
                 let path = parsed_url.path();
                 let parts: Vec<&str> = path.split('/').collect();
                 let organization = parts[1];    
                 let name = parts[2];
-                let exists = octocrab::instance().repos(organization, name).get().await.is_ok();
-                if !exists {
-                    return Err("Repo does not exist".into());
+                let client = &self.github_auth.client()?;
+                // Only a 404 means the repo doesn't exist. Other failures, like an exhausted rate limit, are
+                // reported as they are.
+                let repository = retry(&self.retry_policy, client, move || async move {
+                    client.repos(organization, name).get().await
+                }).await.map_err(|e| match e {
+                    octocrab::Error::GitHub { source, .. } if source.message == "Not Found" => SkootError::NotFound("Repo does not exist".to_string()),
+                    e => SkootError::github(e),
+                })?;
+                // Github redirects requests for a renamed or transferred repo to where it is now, and the
+                // repo it returns is the one at the new location.
+                let owner = repository.owner.map_or_else(|| organization.to_string(), |owner| owner.login);
+                if !owner.eq_ignore_ascii_case(organization) || !repository.name.eq_ignore_ascii_case(name) {
+                    info!("{} was renamed or transferred to https://github.com/{owner}/{}", params.repo_url, repository.name);
                 }
                 Ok(InitializedRepo::Github(InitializedGithubRepo {
-                    name: name.to_string(),
+                    name: repository.name,
                     // FIXME: This will probably break in weird ways since repos from a user and organization are handled
                     // slightly different in the Github API. I am not sure yet what the best way to determine if a repo
                     // belongs to a user or organization is.
-                    organization: GithubUser::User(organization.to_string()),
+                    organization: GithubUser::User(owner),
                 }))
             },
             Some(_) => Err("Unsupported repo host".into()),
@@ -151,53 +343,185 @@ impl RepoService for LocalRepoService {
         match &initialized_repo {
             InitializedRepo::Github(g) => {
                 let path_str = path.as_ref().to_str().ok_or_else(|| SkootError::from("Failed to convert path to string"))?;
-                let content_items = octocrab::instance().repos(
-                    g.organization.get_name(), g.name.clone()
-                )
-                .get_content()
-                .path(path_str)
+                let github_repo_handler = GithubRepoHandler {
+                    client: Arc::new(self.github_auth.client()?),
+                    retry_policy: self.retry_policy.clone(),
+                };
                 // TODO: Should this support multiple branches?
-                .r#ref("main")
-                .send()
-                .await?;
+                let route = format!("/repos/{}/{}/contents/{path_str}?ref=main", g.organization.get_name(), g.name);
+                github_repo_handler.get_cached(&self.content_cache, &route).await.map_err(|e| match e {
+                    SkootError::NotFound(_) => SkootError::NotFound(format!("{} isn't in {}", path_str, initialized_repo.full_url())),
+                    e => e,
+                })
+            }
+            InitializedRepo::GenericGit(g) => {
+                GenericGitRepoHandler::fetch_file_content(g, path.as_ref())
+            }
+            InitializedRepo::Bitbucket(b) => {
+                let path_str = path.as_ref().to_str().ok_or_else(|| SkootError::from("Failed to convert path to string"))?;
+                self.bitbucket_repo_handler().fetch_file_content(b, path_str).await
+            }
+        }
+    }
 
-                let content = content_items
-                .items
-                .first()
-                .ok_or_else(|| SkootError::from(format!("Failed to get {} from {}", path_str, initialized_repo.full_url())))?;
+    async fn fetch_file_contents(&self, initialized_repo: &InitializedRepo, paths: &[String]) -> Result<HashMap<String, String>, SkootError> {
+        match &initialized_repo {
+            // A single file is cheaper to fetch on its own than through the repo's tree.
+            InitializedRepo::Github(_) if paths.len() <= 1 => {
+                let mut contents = HashMap::new();
+                for path in paths {
+                    match self.fetch_file_content(initialized_repo, path).await {
+                        Ok(content) => {
+                            contents.insert(path.clone(), content);
+                        }
+                        Err(SkootError::NotFound(_)) => {}
+                        Err(e) => return Err(e),
+                    }
+                }
+                Ok(contents)
+            }
+            InitializedRepo::Github(g) => {
+                let github_repo_handler = GithubRepoHandler {
+                    client: Arc::new(self.github_auth.client()?),
+                    retry_policy: self.retry_policy.clone(),
+                };
+                github_repo_handler.fetch_file_contents(&self.content_cache, g, paths).await
+            }
+            InitializedRepo::GenericGit(g) => {
+                GenericGitRepoHandler::fetch_file_contents(g, paths)
+            }
+            InitializedRepo::Bitbucket(b) => {
+                self.bitbucket_repo_handler().fetch_file_contents(b, paths).await
+            }
+        }
+    }
+
+    async fn cleanup_actions(&self, initialized_repo: &InitializedRepo) -> Result<ActionsCleanup, SkootError> {
+        ensure_writable(
+            self.read_only,
+            format_args!("clean up the Actions of {}", initialized_repo.full_url()),
+        )?;
+        match initialized_repo {
+            InitializedRepo::Github(g) => {
+                let github_repo_handler = GithubRepoHandler {
+                    client: Arc::new(self.github_auth.client()?),
+                    retry_policy: self.retry_policy.clone(),
+                };
+                github_repo_handler.cleanup_actions(self, g).await
+            }
+            InitializedRepo::GenericGit(g) => {
+                Err(format!("Cleaning up Actions isn't supported for generic git remotes, clean up {} on its host instead", g.remote_url).into())
+            }
+            InitializedRepo::Bitbucket(b) => {
+                Err(format!("Cleaning up Actions isn't supported for Bitbucket, {} runs Bitbucket Pipelines instead", b.full_url()).into())
+            }
+        }
+    }
 
-                debug!("Content: {content:?}");
-                let content_decoded = content.decoded_content().ok_or_else(|| SkootError::from(format!("Failed to decode content from {path_str}")))?;
-                debug!("Content Decoded: {content_decoded:?}");
-                
-                Ok(content_decoded)
+    async fn open_pull_request(&self, initialized_repo: &InitializedRepo, params: PullRequestParams) -> Result<String, SkootError> {
+        ensure_writable(
+            self.read_only,
+            format_args!("open a pull request on {}", initialized_repo.full_url()),
+        )?;
+        match initialized_repo {
+            InitializedRepo::Github(g) => {
+                let github_repo_handler = GithubRepoHandler {
+                    client: Arc::new(self.github_auth.client()?),
+                    retry_policy: self.retry_policy.clone(),
+                };
+                github_repo_handler.open_pull_request(g, params).await
+            }
+            InitializedRepo::GenericGit(g) => {
+                Err(format!("Pull requests aren't supported for generic git remotes, open one for {} on {} instead", params.head, g.remote_url).into())
+            }
+            InitializedRepo::Bitbucket(b) => {
+                self.bitbucket_repo_handler().open_pull_request(b, params).await
             }
         }
     }
 
     async fn archive(&self, initialized_repo: InitializedRepo) -> Result<String, SkootError> {
+        ensure_writable(
+            self.read_only,
+            format_args!("archive {}", initialized_repo.full_url()),
+        )?;
+        match initialized_repo {
+            InitializedRepo::Github(g) => {
+                GithubRepoApi {
+                    repo: g,
+                    github_auth: self.github_auth.clone(),
+                    retry_policy: self.retry_policy.clone(),
+                    branch_protection: BranchProtectionConfig::default(),
+                    code_review: CodeReviewConfig::default(),
+                    repo_ruleset: RepoRulesetConfig::default(),
+                }.archive().await
+            }
+            InitializedRepo::GenericGit(g) => {
+                Err(format!("Archiving isn't supported for generic git remotes, archive {} on its host instead", g.remote_url).into())
+            }
+            InitializedRepo::Bitbucket(b) => {
+                BitbucketRepoApi {
+                    repo: b,
+                    client: BitbucketClient { config: self.bitbucket.clone() },
+                    branch_protection: BranchProtectionConfig::default(),
+                }.archive().await
+            }
+        }
+    }
+
+    async fn delete(&self, initialized_repo: InitializedRepo) -> Result<String, SkootError> {
+        ensure_writable(
+            self.read_only,
+            format_args!("delete {}", initialized_repo.full_url()),
+        )?;
         match initialized_repo {
             InitializedRepo::Github(g) => {
-                #[derive(serde::Serialize)]
-                struct ArchiveParams {
-                    archived: bool,
-                }
                 let owner = g.organization.get_name();
                 let repo = g.name.clone();
-                let body = ArchiveParams {
-                    archived: true,
-                };
 
-                info!("Archiving {owner}/{repo}");
+                info!("Deleting {owner}/{repo}");
 
-                // FIXME: This should work with `Octocrabe::instance()` but for some reason it doesn't pick up the token/session
-                let token = std::env::var("GITHUB_TOKEN").expect("GITHUB_TOKEN env variable is required");
-                let octocrab = Octocrab::builder().personal_token(token).build()?;
-                let archived_response: serde_json::Value = octocrab.patch(format!("/repos/{owner}/{repo}"), Some(&body)).await?;
-                info!("Archived: {archived_response}");
+                let octocrab = &self.github_auth.client()?;
+                let (owner_ref, repo_ref) = (&owner, &repo);
+                retry_rate_limited(&self.retry_policy, octocrab, move || async move {
+                    octocrab.repos(owner_ref, repo_ref).delete().await
+                }).await.map_err(SkootError::github)?;
+                info!("Deleted {owner}/{repo}");
 
                 Ok(g.full_url())
             }
+            InitializedRepo::GenericGit(g) => {
+                Err(format!("Deleting isn't supported for generic git remotes, delete {} on its host instead", g.remote_url).into())
+            }
+            InitializedRepo::Bitbucket(b) => {
+                info!("Deleting {}", b.full_url());
+                self.bitbucket_repo_handler().client.delete(&BitbucketClient::repo_route(&b)).await?;
+                info!("Deleted {}", b.full_url());
+                Ok(b.full_url())
+            }
+        }
+    }
+}
+
+impl LocalRepoService {
+    /// Returns the repo service the config describes, which reaches Github with the config's credential.
+    #[must_use]
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            github_auth: GithubAuth::from_config(config),
+            retry_policy: config.github_retry.clone(),
+            bitbucket: config.bitbucket.clone(),
+            read_only: config.read_only,
+            git_backend: config.git_backend,
+            git_credentials: GitCredentials::from_config(config),
+            events: EventEmitter::new(config.event_sinks.clone()),
+            content_cache: ContentCache::default(),
+        }
+    }
+
+    fn bitbucket_repo_handler(&self) -> BitbucketRepoHandler {
+        BitbucketRepoHandler {
+            client: BitbucketClient { config: self.bitbucket.clone() },
         }
     }
 }
@@ -206,54 +530,30 @@ impl RepoService for LocalRepoService {
 #[derive(Debug)]
 struct GithubRepoHandler {
     client: Arc<octocrab::Octocrab>,
+    retry_policy: RetryPolicy,
 }
 
 impl GithubRepoHandler {
-    async fn create(&self, github_params: GithubRepoParams) -> Result<InitializedGithubRepo, SkootError> {
-        let new_repo = NewGithubRepoParams {
-            name: github_params.name.clone(),
-            description: github_params.description.clone(),
-            private: false,
-            has_issues: true,
-            has_projects: true,
-            has_wiki: true,
-        };
+    /// The Github API has different endpoints for creating a repo for the current user and for an organization.
+    fn create_endpoint(organization: &GithubUser) -> String {
+        match organization {
+            GithubUser::User(_) => "/user/repos".to_string(),
+            GithubUser::Organization(name) => format!("/orgs/{name}/repos"),
+        }
+    }
 
-        let _response: serde_json::Value = match github_params.organization.clone() {
-            GithubUser::User(_) => octocrab::instance().post("/user/repos", Some(&new_repo)).await?,
-            GithubUser::Organization(name) => {
-                self.client
-                    .post(format!("/orgs/{name}/repos"), Some(&new_repo))
-                    .await?
-            }
-        };
+    async fn create(&self, github_params: GithubRepoParams, events: &EventEmitter) -> Result<InitializedGithubRepo, SkootError> {
+        let new_repo = NewGithubRepoParams::from(&github_params);
+        let endpoint = Self::create_endpoint(&github_params.organization);
 
-        info!("Github Repo Created: {}", github_params.name);
-        let rce = RepositoryCreatedEvent {
-             context: RepositoryCreatedEventContext {
-                id: RepositoryCreatedEventContextId::from_str(format!("{}/{}", github_params.organization.get_name(), github_params.name.clone()).as_str())?,
-                source: "skootrs.github.creator".into(),
-                timestamp: Utc::now(),
-                type_: skootrs_model::cd_events::repo_created::RepositoryCreatedEventContextType::DevCdeventsRepositoryCreated011,
-                version: RepositoryCreatedEventContextVersion::from_str("0.3.0")?,
-            }, 
-             custom_data: None,
-             custom_data_content_type: None,
-             subject: RepositoryCreatedEventSubject {
-                content: RepositoryCreatedEventSubjectContent{
-                    name: RepositoryCreatedEventSubjectContentName::from_str(github_params.name.as_str())?,
-                    owner: Some(github_params.organization.get_name()),
-                    url: RepositoryCreatedEventSubjectContentUrl::from_str(github_params.full_url().as_str())?,
-                    view_url: Some(github_params.full_url()),
-                },
-                id: RepositoryCreatedEventSubjectId::from_str(format!("{}/{}", github_params.organization.get_name(), github_params.name.clone()).as_str())?,
-                source: Some("skootrs.github.creator".into()),
-                type_: skootrs_model::cd_events::repo_created::RepositoryCreatedEventSubjectType::Repository,
-            } 
-        };
+        let (client, endpoint, new_repo) = (&*self.client, &endpoint, &new_repo);
+        let _response: serde_json::Value = retry_rate_limited(&self.retry_policy, client, move || async move {
+            client.post(endpoint, Some(new_repo)).await
+        }).await.map_err(SkootError::github)?;
 
-        // TODO: Turn this into an event
-        info!("{}", serde_json::to_string(&rce)?);
+        info!("Github Repo Created: {}", github_params.name);
+        let rce = repository_created_event(&github_params.organization.get_name(), &github_params.name, &github_params.full_url(), "skootrs.github.creator")?;
+        events.emit(&rce).await;
 
         Ok(InitializedGithubRepo {
             name: github_params.name.clone(),
@@ -261,19 +561,467 @@ impl GithubRepoHandler {
         })
     }
 
+    async fn open_pull_request(&self, repo: &InitializedGithubRepo, params: PullRequestParams) -> Result<String, SkootError> {
+        #[derive(serde::Serialize)]
+        struct NewPullRequest<'a> {
+            title: &'a str,
+            head: &'a str,
+            base: &'a str,
+            body: &'a str,
+        }
+        let endpoint = format!("/repos/{}/{}/pulls", repo.organization.get_name(), repo.name);
+        let new_pull_request = NewPullRequest {
+            title: &params.title,
+            head: &params.head,
+            base: &params.base,
+            body: &params.body,
+        };
+
+        let (client, endpoint, new_pull_request) = (&*self.client, &endpoint, &new_pull_request);
+        let pull_request: serde_json::Value = retry_rate_limited(&self.retry_policy, client, move || async move {
+            client.post(endpoint, Some(new_pull_request)).await
+        }).await.map_err(SkootError::github)?;
+        let url = pull_request["html_url"].as_str().ok_or("Github didn't return the URL of the pull request")?.to_string();
+        info!("Opened pull request {url}");
+
+        if params.auto_merge {
+            if let Err(e) = self.enable_auto_merge(&pull_request).await {
+                error!("Failed to enable auto-merge on {url}, the repo has to allow auto-merge: {e}");
+            }
+        }
+        Ok(url)
+    }
+
+    /// Auto-merge can only be enabled through the GraphQL API.
+    async fn enable_auto_merge(&self, pull_request: &serde_json::Value) -> Result<(), SkootError> {
+        let node_id = pull_request["node_id"].as_str().ok_or("Github didn't return the node ID of the pull request")?;
+        let query = serde_json::json!({
+            "query": "mutation($id: ID!) { enablePullRequestAutoMerge(input: {pullRequestId: $id}) { clientMutationId } }",
+            "variables": { "id": node_id },
+        });
+        let (client, query) = (&*self.client, &query);
+        let response: serde_json::Value = retry(&self.retry_policy, client, move || async move {
+            client.graphql(query).await
+        }).await.map_err(SkootError::github)?;
+        // GraphQL errors are returned with a successful status.
+        if let Some(errors) = response.get("errors") {
+            return Err(format!("Enabling auto-merge failed: {errors}").into());
+        }
+        debug!("Enabled auto-merge on pull request {node_id}");
+        Ok(())
+    }
+
     fn clone_local(initialized_github_repo: &InitializedGithubRepo, path: &str) -> Result<InitializedSource, SkootError> {
         debug!("Cloning {}", initialized_github_repo.full_url());
         let clone_url = initialized_github_repo.full_url();
-        let _output = Command::new("git")
+        remove_on_cancel(Path::new(path).join(&initialized_github_repo.name));
+        Command::new("git")
             .arg("clone")
             .arg(clone_url)
             .current_dir(path)
-            .output()?;
+            .run_checked()?;
 
         Ok(InitializedSource{
             path: format!("{}/{}", path, initialized_github_repo.name),
         })
     }
+
+    async fn cleanup_actions(&self, repo_service: &LocalRepoService, repo: &InitializedGithubRepo) -> Result<ActionsCleanup, SkootError> {
+        let owner = repo.organization.get_name();
+        let name = &repo.name;
+        let actions = format!("/repos/{owner}/{name}/actions");
+        let mut cleanup = ActionsCleanup::default();
+        info!("Cleaning up Actions for {owner}/{name}");
+
+        for artifact in self.list_actions_items(&format!("{actions}/artifacts"), "artifacts").await? {
+            let endpoint = format!("{actions}/artifacts/{}", artifact["id"]);
+            match self.delete_endpoint(&endpoint).await {
+                Ok(()) => cleanup.deleted_artifacts += 1,
+                Err(e) => cleanup.failures.push(format!("Failed to delete artifact {}: {e}", artifact["name"])),
+            }
+        }
+
+        for cache in self.list_actions_items(&format!("{actions}/caches"), "actions_caches").await? {
+            let endpoint = format!("{actions}/caches/{}", cache["id"]);
+            match self.delete_endpoint(&endpoint).await {
+                Ok(()) => cleanup.deleted_caches += 1,
+                Err(e) => cleanup.failures.push(format!("Failed to delete cache {}: {e}", cache["key"])),
+            }
+        }
+
+        let initialized_repo = InitializedRepo::Github(repo.clone());
+        for workflow in self.list_actions_items(&format!("{actions}/workflows"), "workflows").await? {
+            let path = workflow["path"].as_str().unwrap_or_default().to_string();
+            if workflow["state"] != "active" {
+                continue;
+            }
+            match repo_service.fetch_file_content(&initialized_repo, &path).await {
+                Ok(content) if is_scheduled(&content) => {}
+                Ok(_) => continue,
+                Err(e) => {
+                    cleanup.failures.push(format!("Failed to read workflow {path}: {e}"));
+                    continue;
+                }
+            }
+            let endpoint = format!("{actions}/workflows/{}/disable", workflow["id"]);
+            let (client, endpoint) = (&*self.client, endpoint.as_str());
+            let disabled = retry(&self.retry_policy, client, move || async move {
+                octocrab::map_github_error(client._put(endpoint, None::<&()>).await?).await
+            }).await;
+            match disabled {
+                Ok(_) => cleanup.disabled_workflows.push(path),
+                Err(e) => cleanup.failures.push(format!("Failed to disable workflow {path}: {e}")),
+            }
+        }
+
+        info!(
+            "Cleaned up Actions for {owner}/{name}: deleted {} artifacts and {} caches, disabled {} workflows, {} failures",
+            cleanup.deleted_artifacts, cleanup.deleted_caches, cleanup.disabled_workflows.len(), cleanup.failures.len()
+        );
+        Ok(cleanup)
+    }
+
+    /// Gets a Github API route, sending the ETag of its cached response so an unchanged response isn't sent
+    /// again. Files are fetched raw instead of base64 encoded.
+    async fn get_cached(&self, cache: &ContentCache, route: &str) -> Result<String, SkootError> {
+        let cached = cache.response(route);
+        let client = &*self.client;
+        let cached_etag = cached.as_ref().map(|(etag, _)| etag.as_str());
+        let response = retry(&self.retry_policy, client, move || async move {
+            let mut headers = HeaderMap::new();
+            headers.insert(ACCEPT, HeaderValue::from_static("application/vnd.github.raw+json"));
+            if let Some(etag) = cached_etag.and_then(|etag| HeaderValue::from_str(etag).ok()) {
+                headers.insert(IF_NONE_MATCH, etag);
+            }
+            let response = client._get_with_headers(route, Some(headers)).await?;
+            if response.status() == StatusCode::NOT_MODIFIED {
+                return Ok(None);
+            }
+            let etag = response.headers().get(ETAG).and_then(|etag| etag.to_str().ok()).map(ToString::to_string);
+            let response = octocrab::map_github_error(response).await?;
+            Ok(Some((etag, client.body_to_string(response).await?)))
+        }).await.map_err(|e| match e {
+            octocrab::Error::GitHub { source, .. } if source.message == "Not Found" => SkootError::NotFound(route.to_string()),
+            e => SkootError::github(e),
+        })?;
+        match (response, cached) {
+            (Some((etag, content)), _) => {
+                if let Some(etag) = etag {
+                    cache.insert_response(route, etag, content.clone());
+                }
+                Ok(content)
+            }
+            (None, Some((_, content))) => {
+                debug!("{route} hasn't changed since it was cached");
+                Ok(content)
+            }
+            // Github only says a response hasn't changed when it was sent the ETag of a cached response.
+            (None, None) => unreachable!("{route} wasn't modified without being cached"),
+        }
+    }
+
+    /// Fetches several files at once from the repo's tree, so each file is only fetched when its blob changed
+    /// since it was cached. Falls back to fetching the files one by one if the tree is too big for Github to
+    /// return whole.
+    async fn fetch_file_contents(&self, cache: &ContentCache, repo: &InitializedGithubRepo, paths: &[String]) -> Result<HashMap<String, String>, SkootError> {
+        let owner = &repo.organization.get_name();
+        let tree_route = format!("/repos/{owner}/{}/git/trees/main?recursive=1", repo.name);
+        let tree: GithubTree = serde_json::from_str(&self.get_cached(cache, &tree_route).await?)?;
+        let blobs = if tree.truncated {
+            paths.iter().map(|path| (path.as_str(), None)).collect::<Vec<_>>()
+        } else {
+            paths
+                .iter()
+                .filter_map(|path| {
+                    // Paths are relative to the root of the repo, with or without a leading `./`.
+                    let tree_path = path.strip_prefix("./").unwrap_or(path);
+                    tree.tree.iter().find(|entry| entry.path == tree_path && entry.entry_type == "blob").map(|entry| (path.as_str(), Some(entry.sha.as_str())))
+                })
+                .collect()
+        };
+        let content_futures = blobs.into_iter().map(|(path, sha)| async move {
+            let content = match sha {
+                Some(sha) => match cache.blob(sha) {
+                    Some(content) => content,
+                    None => {
+                        let content = self.get_cached(cache, &format!("/repos/{owner}/{}/git/blobs/{sha}", repo.name)).await?;
+                        cache.insert_blob(sha, content.clone());
+                        content
+                    }
+                },
+                None => {
+                    let tree_path = path.strip_prefix("./").unwrap_or(path);
+                    match self.get_cached(cache, &format!("/repos/{owner}/{}/contents/{tree_path}?ref=main", repo.name)).await {
+                        Ok(content) => content,
+                        Err(SkootError::NotFound(_)) => return Ok(None),
+                        Err(e) => return Err(e),
+                    }
+                }
+            };
+            Ok(Some((path.to_string(), content)))
+        });
+        Ok(futures::future::try_join_all(content_futures).await?.into_iter().flatten().collect())
+    }
+
+    /// Returns every item of a paginated Actions list endpoint, e.g. the `artifacts` of `/repos/{owner}/{repo}/actions/artifacts`.
+    async fn list_actions_items(&self, endpoint: &str, key: &str) -> Result<Vec<serde_json::Value>, SkootError> {
+        const PER_PAGE: usize = 100;
+        let mut items = Vec::new();
+        for page in 1.. {
+            let client = &*self.client;
+            let parameters = &[("per_page", PER_PAGE.to_string()), ("page", page.to_string())];
+            let response: serde_json::Value = retry(&self.retry_policy, client, move || async move {
+                client.get(endpoint, Some(parameters)).await
+            }).await.map_err(SkootError::github)?;
+            let page_items = response[key].as_array().cloned().unwrap_or_default();
+            let last_page = page_items.len() < PER_PAGE;
+            items.extend(page_items);
+            if last_page {
+                break;
+            }
+        }
+        Ok(items)
+    }
+
+    async fn delete_endpoint(&self, endpoint: &str) -> Result<(), SkootError> {
+        let client = &*self.client;
+        retry(&self.retry_policy, client, move || async move {
+            octocrab::map_github_error(client._delete(endpoint, None::<&()>).await?).await
+        }).await.map_err(SkootError::github)?;
+        Ok(())
+    }
+}
+
+/// Returns true if a workflow runs on a schedule, i.e. its `on` triggers include `schedule`.
+fn is_scheduled(workflow: &str) -> bool {
+    serde_yaml::from_str::<serde_yaml::Value>(workflow)
+        .ok()
+        .and_then(|workflow| workflow.get("on").cloned())
+        .is_some_and(|on| on.get("schedule").is_some())
+}
+
+/// The `GenericGitRepoHandler` struct represents a handler for managing repos on any git remote. There's no API
+/// to call so everything is done with git itself.
+struct GenericGitRepoHandler;
+
+impl GenericGitRepoHandler {
+    /// Git remotes can't be created without a host specific API so the remote has to exist already.
+    fn create(params: GenericGitRepoParams) -> Result<InitializedGenericGitRepo, SkootError> {
+        Self::ls_remote(&params.remote_url)?;
+        let initialized_repo = InitializedGenericGitRepo::from_remote_url(params.remote_url)?;
+        info!("Using git remote {} for {}", initialized_repo.remote_url, initialized_repo.name);
+        Ok(initialized_repo)
+    }
+
+    /// Checks that the remote exists and is reachable with the local git credentials.
+    fn ls_remote(remote_url: &str) -> Result<(), SkootError> {
+        let output = Command::new("git")
+            .arg("ls-remote")
+            .arg(remote_url)
+            .cancellable_output()?;
+        if !output.status.success() {
+            return Err(format!("Failed to reach git remote {remote_url}: {}", String::from_utf8_lossy(&output.stderr).trim()).into());
+        }
+        Ok(())
+    }
+
+    fn clone_local(initialized_repo: &InitializedGenericGitRepo, path: &str) -> Result<InitializedSource, SkootError> {
+        debug!("Cloning {}", initialized_repo.remote_url);
+        remove_on_cancel(Path::new(path).join(&initialized_repo.name));
+        let output = Command::new("git")
+            .arg("clone")
+            .arg(&initialized_repo.remote_url)
+            .arg(&initialized_repo.name)
+            .current_dir(path)
+            .cancellable_output()?;
+        if !output.status.success() {
+            return Err(format!("Failed to clone {}: {}", initialized_repo.remote_url, String::from_utf8_lossy(&output.stderr).trim()).into());
+        }
+
+        Ok(InitializedSource{
+            path: format!("{}/{}", path, initialized_repo.name),
+        })
+    }
+
+    /// Without an API the only way to read a file from the remote is to fetch it, so this makes a shallow
+    /// clone into a temporary directory and reads the file from there.
+    fn fetch_file_content(initialized_repo: &InitializedGenericGitRepo, path: &std::path::Path) -> Result<String, SkootError> {
+        let path_str = path.to_string_lossy().to_string();
+        Self::fetch_file_contents(initialized_repo, std::slice::from_ref(&path_str))?
+            .remove(&path_str)
+            .ok_or_else(|| SkootError::NotFound(format!("Failed to get {path_str} from {}", initialized_repo.remote_url)))
+    }
+
+    /// Reads several files from a single shallow clone of the remote. Files that aren't in the remote are left
+    /// out.
+    fn fetch_file_contents(initialized_repo: &InitializedGenericGitRepo, paths: &[String]) -> Result<HashMap<String, String>, SkootError> {
+        let clone_dir = std::env::temp_dir().join(format!(
+            "skootrs-{}-{}-{}",
+            initialized_repo.name,
+            std::process::id(),
+            Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        remove_on_cancel(clone_dir.clone());
+        let output = Command::new("git")
+            .arg("clone")
+            .arg("--depth=1")
+            .arg(&initialized_repo.remote_url)
+            .arg(&clone_dir)
+            .cancellable_output()?;
+        let contents = if output.status.success() {
+            Ok(paths
+                .iter()
+                .filter_map(|path| std::fs::read_to_string(clone_dir.join(path)).ok().map(|content| (path.clone(), content)))
+                .collect())
+        } else {
+            Err(format!("Failed to clone {}: {}", initialized_repo.remote_url, String::from_utf8_lossy(&output.stderr).trim()).into())
+        };
+        // Best effort cleanup, a leftover temporary directory shouldn't fail the read.
+        let _ = std::fs::remove_dir_all(&clone_dir);
+        contents
+    }
+}
+
+/// The `BitbucketRepoHandler` struct represents a handler for initializing and managing Bitbucket Cloud repos.
+struct BitbucketRepoHandler {
+    client: BitbucketClient,
+}
+
+impl BitbucketRepoHandler {
+    /// Repos are created public like Github repos, since Skootrs projects are open source.
+    fn new_repo_body(params: &BitbucketRepoParams) -> serde_json::Value {
+        serde_json::json!({
+            "scm": "git",
+            "is_private": false,
+            "description": params.description,
+            "has_issues": true,
+            "has_wiki": true,
+        })
+    }
+
+    async fn create(&self, params: BitbucketRepoParams, events: &EventEmitter) -> Result<InitializedBitbucketRepo, SkootError> {
+        let initialized_repo = InitializedBitbucketRepo {
+            name: params.name.clone(),
+            workspace: params.workspace.clone(),
+        };
+        let _response: serde_json::Value = self.client.post(&BitbucketClient::repo_route(&initialized_repo), &Self::new_repo_body(&params)).await?;
+
+        info!("Bitbucket Repo Created: {}", params.name);
+        let rce = repository_created_event(&params.workspace, &params.name, &initialized_repo.full_url(), "skootrs.bitbucket.creator")?;
+        events.emit(&rce).await;
+
+        Ok(initialized_repo)
+    }
+
+    async fn get(&self, repo: &InitializedBitbucketRepo) -> Result<(), SkootError> {
+        let _repo: serde_json::Value = self.client.get(&BitbucketClient::repo_route(repo)).await.map_err(|e| match e {
+            SkootError::NotFound(_) => SkootError::NotFound("Repo does not exist".to_string()),
+            e => e,
+        })?;
+        Ok(())
+    }
+
+    fn clone_local(initialized_repo: &InitializedBitbucketRepo, path: &str) -> Result<InitializedSource, SkootError> {
+        debug!("Cloning {}", initialized_repo.full_url());
+        remove_on_cancel(Path::new(path).join(&initialized_repo.name));
+        Command::new("git")
+            .arg("clone")
+            .arg(format!("{}.git", initialized_repo.full_url()))
+            .arg(&initialized_repo.name)
+            .current_dir(path)
+            .run_checked()?;
+
+        Ok(InitializedSource{
+            path: format!("{}/{}", path, initialized_repo.name),
+        })
+    }
+
+    // TODO: Should this support multiple branches?
+    async fn fetch_file_content(&self, repo: &InitializedBitbucketRepo, path: &str) -> Result<String, SkootError> {
+        let route = format!("{}/src/main/{path}", BitbucketClient::repo_route(repo));
+        self.client.get_text(&route).await.map_err(|e| match e {
+            SkootError::NotFound(_) => SkootError::NotFound(format!("{path} isn't in {}", repo.full_url())),
+            e => e,
+        })
+    }
+
+    /// Bitbucket doesn't have an API for fetching several files at once, so they're fetched one by one. Files
+    /// that aren't in the repo are left out.
+    async fn fetch_file_contents(&self, repo: &InitializedBitbucketRepo, paths: &[String]) -> Result<HashMap<String, String>, SkootError> {
+        let mut contents = HashMap::new();
+        for path in paths {
+            match self.fetch_file_content(repo, path).await {
+                Ok(content) => {
+                    contents.insert(path.clone(), content);
+                }
+                Err(SkootError::NotFound(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(contents)
+    }
+
+    async fn open_pull_request(&self, repo: &InitializedBitbucketRepo, params: PullRequestParams) -> Result<String, SkootError> {
+        let new_pull_request = serde_json::json!({
+            "title": params.title,
+            "description": params.body,
+            "source": { "branch": { "name": params.head } },
+            "destination": { "branch": { "name": params.base } },
+        });
+        let route = format!("{}/pullrequests", BitbucketClient::repo_route(repo));
+        let pull_request: serde_json::Value = self.client.post(&route, &new_pull_request).await?;
+        let url = pull_request["links"]["html"]["href"].as_str().ok_or("Bitbucket didn't return the URL of the pull request")?.to_string();
+        info!("Opened pull request {url}");
+
+        if params.auto_merge {
+            error!("Bitbucket doesn't support enabling auto-merge through its API, {url} has to be merged by hand");
+        }
+        Ok(url)
+    }
+}
+
+/// Returns the CDEvent for a repo that was created.
+fn repository_created_event(owner: &str, name: &str, url: &str, source: &str) -> Result<RepositoryCreatedEvent, SkootError> {
+    let id = format!("{owner}/{name}");
+    Ok(RepositoryCreatedEvent {
+         context: RepositoryCreatedEventContext {
+            id: RepositoryCreatedEventContextId::from_str(&id)?,
+            source: source.into(),
+            timestamp: Utc::now(),
+            type_: skootrs_model::cd_events::repo_created::RepositoryCreatedEventContextType::DevCdeventsRepositoryCreated011,
+            version: RepositoryCreatedEventContextVersion::from_str("0.3.0")?,
+        },
+         custom_data: None,
+         custom_data_content_type: None,
+         subject: RepositoryCreatedEventSubject {
+            content: RepositoryCreatedEventSubjectContent{
+                name: RepositoryCreatedEventSubjectContentName::from_str(name)?,
+                owner: Some(owner.to_string()),
+                url: RepositoryCreatedEventSubjectContentUrl::from_str(url)?,
+                view_url: Some(url.to_string()),
+            },
+            id: RepositoryCreatedEventSubjectId::from_str(&id)?,
+            source: Some(source.into()),
+            type_: skootrs_model::cd_events::repo_created::RepositoryCreatedEventSubjectType::Repository,
+        }
+    })
+}
+
+/// Removes a clone if the current operation is cancelled, so a half finished clone doesn't get in the way of
+/// the next attempt. Directories that already exist are left alone since the clone didn't create them.
+pub(crate) fn remove_on_cancel(clone_dir: std::path::PathBuf) {
+    if clone_dir.exists() {
+        return;
+    }
+    cancellation::on_cancel(move || {
+        if clone_dir.exists() {
+            if let Err(e) = std::fs::remove_dir_all(&clone_dir) {
+                tracing::error!("Failed to remove partial clone {}: {e}", clone_dir.display());
+            } else {
+                info!("Removed partial clone {}", clone_dir.display());
+            }
+        }
+    });
 }
 
 /// This is needed to easily send over Github new repo parameters to the post.
@@ -286,16 +1034,289 @@ struct NewGithubRepoParams {
     has_issues: bool,
     has_projects: bool,
     has_wiki: bool,
+    /// Github commits the license's text as the repo's first commit, which the `License` facet then replaces.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    license_template: Option<&'static str>,
+}
+
+impl From<&GithubRepoParams> for NewGithubRepoParams {
+    fn from(params: &GithubRepoParams) -> Self {
+        Self {
+            name: params.name.clone(),
+            description: params.description.clone(),
+            private: false,
+            has_issues: true,
+            has_projects: true,
+            has_wiki: true,
+            license_template: params.license.as_ref().map(SpdxLicense::github_key),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::io::{BufRead, BufReader, Write};
+
     use tempdir::TempDir;
 
     use super::*;
 
     // TODO: Mock out, or create test to create a repo/delete a repo
 
+    /// A request the fake Github API got, with the ETag it was sent in `If-None-Match`.
+    type FakeRequest = (String, Option<String>);
+
+    /// Serves a fake Github API on a local port. `respond` returns the status, ETag and body for the route and
+    /// `If-None-Match` header of each request. Returns the handler for it and the requests it got.
+    fn fake_github(
+        respond: impl Fn(&str, Option<&str>) -> (u16, Option<&'static str>, String) + Send + 'static,
+    ) -> (GithubRepoHandler, Arc<Mutex<Vec<FakeRequest>>>) {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let received = requests.clone();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut lines = BufReader::new(&stream)
+                    .lines()
+                    .map_while(Result::ok)
+                    .take_while(|line| !line.is_empty());
+                let route = lines
+                    .next()
+                    .unwrap_or_default()
+                    .split(' ')
+                    .nth(1)
+                    .unwrap_or_default()
+                    .to_string();
+                let if_none_match = lines.find_map(|line| {
+                    line.strip_prefix("if-none-match: ")
+                        .map(ToString::to_string)
+                });
+                let (status, etag, body) = respond(&route, if_none_match.as_deref());
+                received.lock().unwrap().push((route, if_none_match));
+                let reason = match status {
+                    200 => "OK",
+                    304 => "Not Modified",
+                    _ => "Not Found",
+                };
+                let etag = etag
+                    .map(|etag| format!("ETag: {etag}\r\n"))
+                    .unwrap_or_default();
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {status} {reason}\r\nContent-Length: {}\r\nConnection: close\r\n{etag}\r\n{body}",
+                    body.len()
+                );
+            }
+        });
+        let client = octocrab::Octocrab::builder()
+            .base_uri(url)
+            .unwrap()
+            .build()
+            .unwrap();
+        (
+            GithubRepoHandler {
+                client: Arc::new(client),
+                retry_policy: RetryPolicy::default(),
+            },
+            requests,
+        )
+    }
+
+    fn not_found() -> (u16, Option<&'static str>, String) {
+        (
+            404,
+            None,
+            r#"{"message": "Not Found", "documentation_url": "https://docs.github.com"}"#
+                .to_string(),
+        )
+    }
+
+    fn github_repo() -> InitializedGithubRepo {
+        InitializedGithubRepo {
+            name: "skootrs".to_string(),
+            organization: GithubUser::Organization("kusaridev".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_cached() {
+        let (handler, requests) = fake_github(|_, if_none_match| match if_none_match {
+            Some("\"v1\"") => (304, None, String::new()),
+            _ => (200, Some("\"v1\""), "readme".to_string()),
+        });
+        let cache = ContentCache::default();
+        let route = "/repos/kusaridev/skootrs/contents/README.md?ref=main";
+        assert_eq!(handler.get_cached(&cache, route).await.unwrap(), "readme");
+        // The cached response is revalidated with its ETag rather than sent again.
+        assert_eq!(handler.get_cached(&cache, route).await.unwrap(), "readme");
+        assert_eq!(
+            *requests.lock().unwrap(),
+            vec![
+                (route.to_string(), None),
+                (route.to_string(), Some("\"v1\"".to_string()))
+            ]
+        );
+
+        // Clones of the cache share its responses.
+        assert_eq!(
+            handler.get_cached(&cache.clone(), route).await.unwrap(),
+            "readme"
+        );
+        assert_eq!(requests.lock().unwrap()[2].1.as_deref(), Some("\"v1\""));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_file_contents() {
+        let (handler, requests) =
+            fake_github(|route, if_none_match| match (route, if_none_match) {
+                ("/repos/kusaridev/skootrs/git/trees/main?recursive=1", Some(_)) => {
+                    (304, None, String::new())
+                }
+                ("/repos/kusaridev/skootrs/git/trees/main?recursive=1", None) => (
+                    200,
+                    Some("\"tree\""),
+                    r#"{"tree": [
+                        {"path": "README.md", "type": "blob", "sha": "readme-sha"},
+                        {"path": ".github", "type": "tree", "sha": "github-sha"},
+                        {"path": ".github/SECURITY.md", "type": "blob", "sha": "security-sha"}
+                    ], "truncated": false}"#
+                        .to_string(),
+                ),
+                ("/repos/kusaridev/skootrs/git/blobs/readme-sha", _) => {
+                    (200, None, "readme".to_string())
+                }
+                ("/repos/kusaridev/skootrs/git/blobs/security-sha", _) => {
+                    (200, None, "policy".to_string())
+                }
+                _ => not_found(),
+            });
+        let cache = ContentCache::default();
+        let paths = [
+            "./README.md".to_string(),
+            ".github/SECURITY.md".to_string(),
+            ".github".to_string(),
+            "missing".to_string(),
+        ];
+        let expected = HashMap::from([
+            ("./README.md".to_string(), "readme".to_string()),
+            (".github/SECURITY.md".to_string(), "policy".to_string()),
+        ]);
+        // The files are found in the tree under their paths without `./`, and directories and missing files
+        // are left out.
+        assert_eq!(
+            handler
+                .fetch_file_contents(&cache, &github_repo(), &paths)
+                .await
+                .unwrap(),
+            expected
+        );
+        let mut routes = requests
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(route, _)| route.clone())
+            .collect::<Vec<_>>();
+        routes.sort();
+        assert_eq!(
+            routes,
+            vec![
+                "/repos/kusaridev/skootrs/git/blobs/readme-sha",
+                "/repos/kusaridev/skootrs/git/blobs/security-sha",
+                "/repos/kusaridev/skootrs/git/trees/main?recursive=1",
+            ]
+        );
+
+        // Once the tree is unchanged, the blobs come from the cache without asking Github again.
+        requests.lock().unwrap().clear();
+        assert_eq!(
+            handler
+                .fetch_file_contents(&cache, &github_repo(), &paths)
+                .await
+                .unwrap(),
+            expected
+        );
+        assert_eq!(
+            *requests.lock().unwrap(),
+            vec![(
+                "/repos/kusaridev/skootrs/git/trees/main?recursive=1".to_string(),
+                Some("\"tree\"".to_string())
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_file_contents_truncated_tree() {
+        let (handler, requests) = fake_github(|route, _| match route {
+            "/repos/kusaridev/skootrs/git/trees/main?recursive=1" => {
+                (200, None, r#"{"tree": [], "truncated": true}"#.to_string())
+            }
+            "/repos/kusaridev/skootrs/contents/README.md?ref=main" => {
+                (200, None, "readme".to_string())
+            }
+            _ => not_found(),
+        });
+        let cache = ContentCache::default();
+        let paths = ["./README.md".to_string(), "missing".to_string()];
+        // A tree too big to be returned whole falls back to fetching each file on its own.
+        assert_eq!(
+            handler
+                .fetch_file_contents(&cache, &github_repo(), &paths)
+                .await
+                .unwrap(),
+            HashMap::from([("./README.md".to_string(), "readme".to_string())])
+        );
+        let mut routes = requests
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(route, _)| route.clone())
+            .collect::<Vec<_>>();
+        routes.sort();
+        assert_eq!(
+            routes,
+            vec![
+                "/repos/kusaridev/skootrs/contents/README.md?ref=main",
+                "/repos/kusaridev/skootrs/contents/missing?ref=main",
+                "/repos/kusaridev/skootrs/git/trees/main?recursive=1",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_scheduled() {
+        assert!(is_scheduled(
+            "on:\n  schedule:\n    - cron: '0 0 * * 1'\n  push:\n    branches: [main]\n"
+        ));
+        assert!(!is_scheduled("on:\n  push:\n    branches: [main]\n"));
+        assert!(!is_scheduled("on: [push, pull_request]\n"));
+        assert!(!is_scheduled("not: [valid"));
+    }
+
+    #[tokio::test]
+    async fn test_read_only() {
+        let repo_service = LocalRepoService {
+            read_only: true,
+            ..Default::default()
+        };
+        let initialized_repo = InitializedRepo::Github(InitializedGithubRepo {
+            name: "skootrs".to_string(),
+            organization: GithubUser::Organization("kusaridev".to_string()),
+        });
+        // Refused before a Github client is even needed.
+        let err = repo_service
+            .archive(initialized_repo.clone())
+            .await
+            .unwrap_err();
+        assert!(crate::service::read_only::is_read_only(&err));
+        assert_eq!(
+            err.to_string(),
+            "Refusing to archive https://github.com/kusaridev/skootrs since Skootrs is in read-only mode"
+        );
+        let err = repo_service.delete(initialized_repo).await.unwrap_err();
+        assert!(crate::service::read_only::is_read_only(&err));
+    }
+
     #[test]
     fn test_clone_local_github_repo() {
         let initialized_github_repo = InitializedGithubRepo {
@@ -314,4 +1335,41 @@ mod tests {
             format!("{}/{}", path, initialized_github_repo.name)
         );
     }
+
+    #[test]
+    fn test_generic_git_repo() {
+        let temp_dir = TempDir::new("test").unwrap();
+        let remote_path = temp_dir.path().join("remote");
+        std::fs::create_dir(&remote_path).unwrap();
+        std::fs::write(remote_path.join(".skootrs"), "{}").unwrap();
+        for args in [
+            vec!["init", "--initial-branch=main"],
+            vec!["add", "."],
+            vec!["-c", "user.name=test", "-c", "user.email=test@example.com", "commit", "-m", "init"],
+        ] {
+            let output = Command::new("git").args(args).current_dir(&remote_path).output().unwrap();
+            assert!(output.status.success());
+        }
+
+        let initialized_repo = GenericGitRepoHandler::create(GenericGitRepoParams {
+            remote_url: remote_path.to_str().unwrap().to_string(),
+        })
+        .unwrap();
+        assert_eq!(initialized_repo.name, "remote");
+        let content = GenericGitRepoHandler::fetch_file_content(&initialized_repo, std::path::Path::new(".skootrs")).unwrap();
+        assert_eq!(content, "{}");
+        assert!(GenericGitRepoHandler::fetch_file_content(&initialized_repo, std::path::Path::new("missing")).is_err());
+        let contents = GenericGitRepoHandler::fetch_file_contents(&initialized_repo, &[".skootrs".to_string(), "missing".to_string()]).unwrap();
+        assert_eq!(contents, HashMap::from([(".skootrs".to_string(), "{}".to_string())]));
+
+        let clone_path = temp_dir.path().join("clones");
+        std::fs::create_dir(&clone_path).unwrap();
+        let initialized_source = GenericGitRepoHandler::clone_local(&initialized_repo, clone_path.to_str().unwrap()).unwrap();
+        assert!(std::path::Path::new(&initialized_source.path).join(".skootrs").exists());
+
+        assert!(GenericGitRepoHandler::create(GenericGitRepoParams {
+            remote_url: temp_dir.path().join("missing").to_str().unwrap().to_string(),
+        })
+        .is_err());
+    }
 }
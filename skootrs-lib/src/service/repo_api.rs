@@ -0,0 +1,999 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `RepoApiProvider` trait is how API bundle facets are enabled on a repo's host. Each host implements
+//! it with its own API, so the facets don't depend on any one host and a new host only has to implement the
+//! trait.
+
+#![allow(clippy::module_name_repetitions)]
+
+use std::future::Future;
+
+use askama::Template;
+use base64::Engine;
+use tracing::info;
+
+use skootrs_model::skootrs::{
+    bitbucket,
+    branch_protection::{BranchProtectionConfig, BranchProtectionRequest},
+    code_review::CodeReviewConfig,
+    facet::{APIBundleFacet, APIContent, SupportedFacetType},
+    plan::PlannedAction,
+    ruleset::{RepoRulesetConfig, RulesetScope},
+    GithubUser, InitializedBitbucketRepo, InitializedGithubRepo, RetryPolicy, SkootError,
+};
+
+use super::{
+    bitbucket::BitbucketClient,
+    github_auth::GithubAuth,
+    retry::{retry, retry_rate_limited},
+};
+
+/// The `RepoApiProvider` trait provides an interface for the API calls of a repo's host that enable the API
+/// bundle facets, e.g. branch protection and vulnerability reporting.
+pub trait RepoApiProvider {
+    /// Returns why the host can't enable a facet, or `None` if it can.
+    fn unsupported_reason(&self, facet_type: &SupportedFacetType) -> Option<String>;
+
+    /// Protects the branches of the repo.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the branches can't be protected.
+    fn enable_branch_protection(
+        &self,
+    ) -> impl Future<Output = Result<Vec<APIContent>, SkootError>> + Send;
+
+    /// Creates or updates the ruleset of the repo.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the ruleset can't be created or updated.
+    fn enable_repo_ruleset(
+        &self,
+    ) -> impl Future<Output = Result<Vec<APIContent>, SkootError>> + Send;
+
+    /// Enables private vulnerability reporting for the repo.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if vulnerability reporting can't be enabled.
+    fn enable_vuln_reporting(
+        &self,
+    ) -> impl Future<Output = Result<Vec<APIContent>, SkootError>> + Send;
+
+    /// Commits the repo's code owners and requires their reviews to merge.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the code owners can't be committed or the reviews can't be required.
+    fn require_reviews(&self) -> impl Future<Output = Result<Vec<APIContent>, SkootError>> + Send;
+
+    /// Archives the repo, returning its URL.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the repo can't be archived.
+    fn archive(&self) -> impl Future<Output = Result<String, SkootError>> + Send;
+
+    /// Returns the API calls enabling a facet would make.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the facet can't be planned.
+    fn plan(&self, facet_type: &SupportedFacetType) -> Result<Vec<PlannedAction>, SkootError>;
+
+    /// Checks that a facet is still enabled on the repo.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the facet can't be checked.
+    fn verify(
+        &self,
+        facet_type: &SupportedFacetType,
+    ) -> impl Future<Output = Result<bool, SkootError>> + Send;
+
+    /// Disables a facet on the repo.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the facet can't be disabled.
+    fn revert(
+        &self,
+        facet_type: &SupportedFacetType,
+    ) -> impl Future<Output = Result<(), SkootError>> + Send;
+}
+
+/// Enables an API bundle facet with a repo's host, or skips it if the host can't enable it.
+///
+/// # Errors
+///
+/// Returns an error if the facet isn't an API bundle facet or the host's API calls fail.
+pub async fn enable<P: RepoApiProvider + Sync>(
+    provider: &P,
+    facet_type: &SupportedFacetType,
+) -> Result<APIBundleFacet, SkootError> {
+    let skipped_reason = provider.unsupported_reason(facet_type);
+    let apis = if let Some(reason) = &skipped_reason {
+        info!("Skipping facet {facet_type}: {reason}");
+        vec![]
+    } else {
+        match facet_type {
+            SupportedFacetType::BranchProtection => provider.enable_branch_protection().await?,
+            SupportedFacetType::RepoRuleset => provider.enable_repo_ruleset().await?,
+            SupportedFacetType::VulnerabilityReporting => provider.enable_vuln_reporting().await?,
+            SupportedFacetType::CodeReview => provider.require_reviews().await?,
+            _ => {
+                return Err(
+                    format!("{facet_type} isn't enabled through the repo host's API").into(),
+                )
+            }
+        }
+    };
+    Ok(APIBundleFacet {
+        facet_type: facet_type.clone(),
+        apis,
+        labels: vec![],
+        skipped_reason,
+        template_version: None,
+        generator_fingerprint: None,
+    })
+}
+
+/// The `HostRepoApi` enum is the API of whichever host a repo is on.
+#[derive(Debug)]
+pub enum HostRepoApi {
+    Github(GithubRepoApi),
+    Bitbucket(BitbucketRepoApi),
+}
+
+impl RepoApiProvider for HostRepoApi {
+    fn unsupported_reason(&self, facet_type: &SupportedFacetType) -> Option<String> {
+        match self {
+            Self::Github(api) => api.unsupported_reason(facet_type),
+            Self::Bitbucket(api) => api.unsupported_reason(facet_type),
+        }
+    }
+
+    async fn enable_branch_protection(&self) -> Result<Vec<APIContent>, SkootError> {
+        match self {
+            Self::Github(api) => api.enable_branch_protection().await,
+            Self::Bitbucket(api) => api.enable_branch_protection().await,
+        }
+    }
+
+    async fn enable_repo_ruleset(&self) -> Result<Vec<APIContent>, SkootError> {
+        match self {
+            Self::Github(api) => api.enable_repo_ruleset().await,
+            Self::Bitbucket(api) => api.enable_repo_ruleset().await,
+        }
+    }
+
+    async fn enable_vuln_reporting(&self) -> Result<Vec<APIContent>, SkootError> {
+        match self {
+            Self::Github(api) => api.enable_vuln_reporting().await,
+            Self::Bitbucket(api) => api.enable_vuln_reporting().await,
+        }
+    }
+
+    async fn require_reviews(&self) -> Result<Vec<APIContent>, SkootError> {
+        match self {
+            Self::Github(api) => api.require_reviews().await,
+            Self::Bitbucket(api) => api.require_reviews().await,
+        }
+    }
+
+    async fn archive(&self) -> Result<String, SkootError> {
+        match self {
+            Self::Github(api) => api.archive().await,
+            Self::Bitbucket(api) => api.archive().await,
+        }
+    }
+
+    fn plan(&self, facet_type: &SupportedFacetType) -> Result<Vec<PlannedAction>, SkootError> {
+        match self {
+            Self::Github(api) => api.plan(facet_type),
+            Self::Bitbucket(api) => api.plan(facet_type),
+        }
+    }
+
+    async fn verify(&self, facet_type: &SupportedFacetType) -> Result<bool, SkootError> {
+        match self {
+            Self::Github(api) => api.verify(facet_type).await,
+            Self::Bitbucket(api) => api.verify(facet_type).await,
+        }
+    }
+
+    async fn revert(&self, facet_type: &SupportedFacetType) -> Result<(), SkootError> {
+        match self {
+            Self::Github(api) => api.revert(facet_type).await,
+            Self::Bitbucket(api) => api.revert(facet_type).await,
+        }
+    }
+}
+
+/// The `BitbucketRepoApi` struct enables API bundle facets with the Bitbucket Cloud API. Only
+/// `BranchProtection` has a Bitbucket analog, its branch restrictions, so the other facets are skipped.
+#[derive(Debug)]
+pub struct BitbucketRepoApi {
+    pub repo: InitializedBitbucketRepo,
+    pub client: BitbucketClient,
+    pub branch_protection: BranchProtectionConfig,
+}
+
+impl BitbucketRepoApi {
+    fn restrictions(&self) -> Vec<bitbucket::BranchRestriction> {
+        self.branch_protection
+            .branches
+            .iter()
+            .flat_map(|branch| bitbucket::branch_restrictions(&self.branch_protection, branch))
+            .collect()
+    }
+
+    fn unsupported(&self, facet_type: &SupportedFacetType) -> SkootError {
+        self.unsupported_reason(facet_type)
+            .unwrap_or_else(|| format!("{facet_type} isn't supported for Bitbucket repos"))
+            .into()
+    }
+}
+
+impl RepoApiProvider for BitbucketRepoApi {
+    fn unsupported_reason(&self, facet_type: &SupportedFacetType) -> Option<String> {
+        (*facet_type != SupportedFacetType::BranchProtection).then(|| {
+            format!(
+                "{} is a Bitbucket repo and Bitbucket has no API to enable {facet_type} with",
+                self.repo.full_url()
+            )
+        })
+    }
+
+    async fn enable_branch_protection(&self) -> Result<Vec<APIContent>, SkootError> {
+        let url = format!(
+            "{}/branch-restrictions",
+            BitbucketClient::repo_route(&self.repo)
+        );
+        let mut apis = vec![];
+        for branch in &self.branch_protection.branches {
+            let restrictions = bitbucket::branch_restrictions(&self.branch_protection, branch);
+            info!("Enabling branch restrictions for {url} on {branch}");
+            self.client
+                .enable_branch_restrictions(&self.repo, &restrictions)
+                .await?;
+            apis.push(APIContent {
+                name: format!("Enforce Branch Restrictions on {branch}"),
+                url: url.clone(),
+                response: serde_json::to_string_pretty(&restrictions)?,
+            });
+        }
+        Ok(apis)
+    }
+
+    async fn enable_repo_ruleset(&self) -> Result<Vec<APIContent>, SkootError> {
+        Err(self.unsupported(&SupportedFacetType::RepoRuleset))
+    }
+
+    async fn enable_vuln_reporting(&self) -> Result<Vec<APIContent>, SkootError> {
+        Err(self.unsupported(&SupportedFacetType::VulnerabilityReporting))
+    }
+
+    async fn require_reviews(&self) -> Result<Vec<APIContent>, SkootError> {
+        Err(self.unsupported(&SupportedFacetType::CodeReview))
+    }
+
+    async fn archive(&self) -> Result<String, SkootError> {
+        Err(format!(
+            "Bitbucket doesn't have archived repos, make {} read only in its settings instead",
+            self.repo.full_url()
+        )
+        .into())
+    }
+
+    /// Facets other than `BranchProtection` are skipped, so they make no API calls.
+    fn plan(&self, facet_type: &SupportedFacetType) -> Result<Vec<PlannedAction>, SkootError> {
+        if *facet_type != SupportedFacetType::BranchProtection {
+            return Ok(vec![]);
+        }
+        let url = format!(
+            "{}/branch-restrictions",
+            BitbucketClient::repo_route(&self.repo)
+        );
+        Ok(self
+            .restrictions()
+            .into_iter()
+            .map(|restriction| PlannedAction::ApiCall {
+                description: format!(
+                    "Add the {} branch restriction on {}",
+                    restriction.kind, restriction.pattern
+                ),
+                method: "POST".to_string(),
+                url: url.clone(),
+                body: serde_json::to_value(restriction).ok(),
+                facet_type: Some(facet_type.clone()),
+            })
+            .collect())
+    }
+
+    /// Checks that every branch restriction is still in place.
+    async fn verify(&self, facet_type: &SupportedFacetType) -> Result<bool, SkootError> {
+        if *facet_type != SupportedFacetType::BranchProtection {
+            return Err(
+                format!("Verifying {facet_type} isn't supported for Bitbucket repos").into(),
+            );
+        }
+        let existing = self.client.branch_restrictions(&self.repo).await?;
+        Ok(self
+            .restrictions()
+            .iter()
+            .all(|restriction| existing.iter().any(|e| e.matches(restriction))))
+    }
+
+    /// Removes the branch restrictions the facet added.
+    async fn revert(&self, facet_type: &SupportedFacetType) -> Result<(), SkootError> {
+        if *facet_type != SupportedFacetType::BranchProtection {
+            return Err(
+                format!("Reverting {facet_type} isn't supported for Bitbucket repos").into(),
+            );
+        }
+        info!(
+            "Removing the branch restrictions of {}",
+            self.repo.full_url()
+        );
+        self.client
+            .remove_branch_restrictions(&self.repo, &self.restrictions())
+            .await
+    }
+}
+
+/// The `GithubRepoApi` struct enables API bundle facets with the Github API.
+#[derive(Debug)]
+pub struct GithubRepoApi {
+    pub repo: InitializedGithubRepo,
+    /// The credential the API is called with. A client is built for each call so planning works without one.
+    pub github_auth: GithubAuth,
+    /// How calls are retried when they fail with a transient error or hit a rate limit.
+    pub retry_policy: RetryPolicy,
+    pub branch_protection: BranchProtectionConfig,
+    pub code_review: CodeReviewConfig,
+    pub repo_ruleset: RepoRulesetConfig,
+}
+
+impl GithubRepoApi {
+    fn branch_protection_endpoint(repo: &InitializedGithubRepo, branch: &str) -> String {
+        format!(
+            "/repos/{owner}/{repo}/branches/{branch}/protection",
+            owner = repo.organization.get_name(),
+            repo = repo.name,
+        )
+    }
+
+    fn required_signatures_endpoint(repo: &InitializedGithubRepo, branch: &str) -> String {
+        format!(
+            "{}/required_signatures",
+            Self::branch_protection_endpoint(repo, branch)
+        )
+    }
+
+    fn required_reviews_endpoint(repo: &InitializedGithubRepo, branch: &str) -> String {
+        format!(
+            "{}/required_pull_request_reviews",
+            Self::branch_protection_endpoint(repo, branch)
+        )
+    }
+
+    fn codeowners_endpoint(repo: &InitializedGithubRepo) -> String {
+        format!(
+            "/repos/{owner}/{repo}/contents/.github/CODEOWNERS",
+            owner = repo.organization.get_name(),
+            repo = repo.name,
+        )
+    }
+
+    /// Returns the body of the call committing the CODEOWNERS file, which replaces the file with the given
+    /// blob SHA if it already exists.
+    fn codeowners_request(
+        code_review: &CodeReviewConfig,
+        repo: &InitializedGithubRepo,
+        sha: Option<String>,
+    ) -> Result<CodeownersRequest, SkootError> {
+        #[derive(Template)]
+        #[template(path = "CODEOWNERS", escape = "none")]
+        struct CodeownersTemplateParams {
+            code_owners: Vec<String>,
+        }
+
+        let content = CodeownersTemplateParams {
+            code_owners: code_review.code_owners(repo),
+        }
+        .render()
+        .map_err(SkootError::template)?;
+        Ok(CodeownersRequest {
+            message: "Added CODEOWNERS".to_string(),
+            content: base64::engine::general_purpose::STANDARD.encode(content),
+            sha,
+        })
+    }
+
+    fn rulesets_endpoint(
+        repo: &InitializedGithubRepo,
+        scope: RulesetScope,
+    ) -> Result<String, SkootError> {
+        match (scope, &repo.organization) {
+            (RulesetScope::Repo, owner) => Ok(format!(
+                "/repos/{owner}/{repo}/rulesets",
+                owner = owner.get_name(),
+                repo = repo.name,
+            )),
+            (RulesetScope::Org, GithubUser::Organization(org)) => {
+                Ok(format!("/orgs/{org}/rulesets"))
+            }
+            (RulesetScope::Org, GithubUser::User(user)) => Err(format!(
+                "Can't create an org ruleset for {} since {user} is a user rather than an organization",
+                repo.full_url()
+            )
+            .into()),
+        }
+    }
+
+    fn vulnerability_reporting_endpoint(repo: &InitializedGithubRepo) -> String {
+        format!(
+            "/repos/{owner}/{repo}/private-vulnerability-reporting",
+            owner = repo.organization.get_name(),
+            repo = repo.name,
+        )
+    }
+
+    /// Returns the ID of the repo's ruleset if it was already created.
+    async fn find_ruleset(&self) -> Result<Option<u64>, SkootError> {
+        let repo = &self.repo;
+        #[derive(serde::Deserialize)]
+        struct Ruleset {
+            id: u64,
+            name: String,
+        }
+        // Rulesets inherited from the org are left out of a repo's rulesets so they can't be mistaken for the
+        // repo's own.
+        let endpoint = &format!(
+            "{}?includes_parents=false&per_page=100",
+            Self::rulesets_endpoint(repo, self.repo_ruleset.scope)?
+        );
+        let client = &self.github_auth.client()?;
+        let rulesets: Vec<Ruleset> = retry(&self.retry_policy, client, move || async move {
+            client.get(endpoint, None::<&()>).await
+        })
+        .await
+        .map_err(SkootError::github)?;
+        let name = self.repo_ruleset.ruleset_name(&repo.name);
+        Ok(rulesets
+            .into_iter()
+            .find(|ruleset| ruleset.name == name)
+            .map(|ruleset| ruleset.id))
+    }
+
+    /// Returns the blob SHA of the repo's CODEOWNERS file, or `None` if it doesn't have one.
+    async fn find_codeowners(&self) -> Result<Option<String>, SkootError> {
+        let repo = &self.repo;
+        #[derive(serde::Deserialize)]
+        struct Content {
+            sha: String,
+        }
+        let endpoint = &Self::codeowners_endpoint(repo);
+        let client = &self.github_auth.client()?;
+        let content: Result<Content, _> = retry(&self.retry_policy, client, move || async move {
+            client.get(endpoint, None::<&()>).await
+        })
+        .await;
+        match content {
+            Ok(content) => Ok(Some(content.sha)),
+            Err(octocrab::Error::GitHub { source, .. }) if source.message == "Not Found" => {
+                Ok(None)
+            }
+            Err(e) => Err(SkootError::github(e)),
+        }
+    }
+}
+
+impl RepoApiProvider for GithubRepoApi {
+    fn unsupported_reason(&self, _facet_type: &SupportedFacetType) -> Option<String> {
+        None
+    }
+
+    async fn enable_branch_protection(&self) -> Result<Vec<APIContent>, SkootError> {
+        let repo = &self.repo;
+        let body = &self.branch_protection.request();
+        let client = &self.github_auth.client()?;
+        let mut apis = vec![];
+        for branch in &self.branch_protection.branches {
+            let endpoint = &Self::branch_protection_endpoint(repo, branch);
+            info!("Enabling branch protection for {endpoint}");
+            let response: serde_json::Value =
+                retry(&self.retry_policy, client, move || async move {
+                    client.put(endpoint, Some(body)).await
+                })
+                .await
+                .map_err(SkootError::github)?;
+            apis.push(APIContent {
+                name: format!("Enforce Branch Protection on {branch}"),
+                url: endpoint.clone(),
+                response: serde_json::to_string_pretty(&response)?,
+            });
+
+            if self.branch_protection.required_signatures {
+                let endpoint = &Self::required_signatures_endpoint(repo, branch);
+                info!("Requiring signed commits for {endpoint}");
+                let response: serde_json::Value =
+                    retry(&self.retry_policy, client, move || async move {
+                        client.post(endpoint, None::<&()>).await
+                    })
+                    .await
+                    .map_err(SkootError::github)?;
+                apis.push(APIContent {
+                    name: format!("Require signed commits on {branch}"),
+                    url: endpoint.clone(),
+                    response: serde_json::to_string_pretty(&response)?,
+                });
+            }
+        }
+
+        Ok(apis)
+    }
+
+    async fn enable_repo_ruleset(&self) -> Result<Vec<APIContent>, SkootError> {
+        let repo = &self.repo;
+        let endpoint = Self::rulesets_endpoint(repo, self.repo_ruleset.scope)?;
+        let body = &self
+            .repo_ruleset
+            .request(&repo.name, &self.branch_protection);
+        let client = &self.github_auth.client()?;
+        // Github refuses a second ruleset with the same name, so a ruleset that already exists is updated.
+        let (name, url, response) = if let Some(id) = self.find_ruleset().await? {
+            let url = format!("{endpoint}/{id}");
+            info!("Updating ruleset {} with {url}", body.name);
+            let url_ref = &url;
+            let response: serde_json::Value =
+                retry(&self.retry_policy, client, move || async move {
+                    client.put(url_ref, Some(body)).await
+                })
+                .await
+                .map_err(SkootError::github)?;
+            ("Update repository ruleset", url, response)
+        } else {
+            info!("Creating ruleset {} with {endpoint}", body.name);
+            let endpoint_ref = &endpoint;
+            // Creating a ruleset isn't safe to repeat since it could create a second one.
+            let response: serde_json::Value =
+                retry_rate_limited(&self.retry_policy, client, move || async move {
+                    client.post(endpoint_ref, Some(body)).await
+                })
+                .await
+                .map_err(SkootError::github)?;
+            ("Create repository ruleset", endpoint, response)
+        };
+
+        Ok(vec![APIContent {
+            name: format!("{name} {}", body.name),
+            url,
+            response: serde_json::to_string_pretty(&response)?,
+        }])
+    }
+
+    async fn enable_vuln_reporting(&self) -> Result<Vec<APIContent>, SkootError> {
+        let vulnerability_reporting_endpoint = Self::vulnerability_reporting_endpoint(&self.repo);
+        info!(
+            "Enabling vulnerability reporting for {}",
+            &vulnerability_reporting_endpoint
+        );
+        // Note: This call just returns a status with no JSON output also the normal .put I think expects json
+        // output and will fail.
+        let (client, endpoint) = (
+            &self.github_auth.client()?,
+            &vulnerability_reporting_endpoint,
+        );
+        retry(&self.retry_policy, client, move || async move {
+            octocrab::map_github_error(client._put(endpoint, None::<&()>).await?).await
+        })
+        .await
+        .map_err(SkootError::github)?;
+        let apis = vec![APIContent {
+            name: "Enabling vulnerability reporting".to_string(),
+            url: vulnerability_reporting_endpoint.clone(),
+            response: "Success".to_string(),
+        }];
+        info!(
+            "Vulnerability reporting enabled for {}",
+            &vulnerability_reporting_endpoint
+        );
+
+        Ok(apis)
+    }
+
+    async fn require_reviews(&self) -> Result<Vec<APIContent>, SkootError> {
+        let repo = &self.repo;
+        let client = &self.github_auth.client()?;
+        let mut apis = vec![];
+
+        // The file is committed through the API since the initial push already landed.
+        let endpoint = &Self::codeowners_endpoint(repo);
+        let body =
+            &Self::codeowners_request(&self.code_review, repo, self.find_codeowners().await?)?;
+        info!("Committing CODEOWNERS with {endpoint}");
+        let response: serde_json::Value = retry(&self.retry_policy, client, move || async move {
+            client.put(endpoint, Some(body)).await
+        })
+        .await
+        .map_err(SkootError::github)?;
+        apis.push(APIContent {
+            name: "Commit CODEOWNERS".to_string(),
+            url: endpoint.clone(),
+            response: serde_json::to_string_pretty(&response)?,
+        });
+
+        let reviews = &self.code_review.request();
+        for branch in &self.code_review.branches {
+            let endpoint = &Self::required_reviews_endpoint(repo, branch);
+            info!("Requiring reviews with {endpoint}");
+            let response: Result<serde_json::Value, _> =
+                retry(&self.retry_policy, client, move || async move {
+                    client.patch(endpoint, Some(reviews)).await
+                })
+                .await;
+            let (url, response) = match response {
+                Ok(response) => (endpoint.clone(), response),
+                // The reviews of a branch can only be updated once it's protected, so a branch without the
+                // BranchProtection facet is protected with just the reviews.
+                Err(octocrab::Error::GitHub { source, .. })
+                    if source.message == "Branch not protected" =>
+                {
+                    let endpoint = &Self::branch_protection_endpoint(repo, branch);
+                    let body = &BranchProtectionRequest {
+                        required_status_checks: None,
+                        enforce_admins: false,
+                        required_pull_request_reviews: Some(reviews.clone()),
+                        restrictions: None,
+                        required_linear_history: false,
+                        allow_force_pushes: false,
+                        allow_deletions: false,
+                    };
+                    let response: serde_json::Value =
+                        retry(&self.retry_policy, client, move || async move {
+                            client.put(endpoint, Some(body)).await
+                        })
+                        .await
+                        .map_err(SkootError::github)?;
+                    (endpoint.clone(), response)
+                }
+                Err(e) => return Err(SkootError::github(e)),
+            };
+            apis.push(APIContent {
+                name: format!(
+                    "Require {} approving reviews on {branch}",
+                    self.code_review.required_approving_reviews
+                ),
+                url,
+                response: serde_json::to_string_pretty(&response)?,
+            });
+        }
+
+        Ok(apis)
+    }
+
+    async fn archive(&self) -> Result<String, SkootError> {
+        #[derive(serde::Serialize)]
+        struct ArchiveParams {
+            archived: bool,
+        }
+        let owner = self.repo.organization.get_name();
+        let name = &self.repo.name;
+        info!("Archiving {owner}/{name}");
+
+        let client = &self.github_auth.client()?;
+        let (endpoint, body) = (
+            &format!("/repos/{owner}/{name}"),
+            &ArchiveParams { archived: true },
+        );
+        let archived_response: serde_json::Value =
+            retry(&self.retry_policy, client, move || async move {
+                client.patch(endpoint, Some(body)).await
+            })
+            .await
+            .map_err(SkootError::github)?;
+        info!("Archived: {archived_response}");
+
+        Ok(self.repo.full_url())
+    }
+
+    fn plan(&self, facet_type: &SupportedFacetType) -> Result<Vec<PlannedAction>, SkootError> {
+        let (repo, branch_protection, code_review, repo_ruleset) = (
+            &self.repo,
+            &self.branch_protection,
+            &self.code_review,
+            &self.repo_ruleset,
+        );
+        let api_call = |description: String, method: &str, url, body| PlannedAction::ApiCall {
+            description,
+            method: method.to_string(),
+            url,
+            body,
+            facet_type: Some(facet_type.clone()),
+        };
+        match facet_type {
+            SupportedFacetType::BranchProtection => {
+                let body = serde_json::to_value(branch_protection.request())?;
+                let mut actions = vec![];
+                for branch in &branch_protection.branches {
+                    actions.push(api_call(
+                        format!("Enforce Branch Protection on {branch}"),
+                        "PUT",
+                        Self::branch_protection_endpoint(repo, branch),
+                        Some(body.clone()),
+                    ));
+                    if branch_protection.required_signatures {
+                        actions.push(api_call(
+                            format!("Require signed commits on {branch}"),
+                            "POST",
+                            Self::required_signatures_endpoint(repo, branch),
+                            None,
+                        ));
+                    }
+                }
+                Ok(actions)
+            }
+            SupportedFacetType::RepoRuleset => {
+                let request = repo_ruleset.request(&repo.name, branch_protection);
+                Ok(vec![api_call(
+                    format!("Create repository ruleset {}", request.name),
+                    "POST",
+                    Self::rulesets_endpoint(repo, repo_ruleset.scope)?,
+                    Some(serde_json::to_value(request)?),
+                )])
+            }
+            SupportedFacetType::VulnerabilityReporting => Ok(vec![api_call(
+                "Enabling vulnerability reporting".to_string(),
+                "PUT",
+                Self::vulnerability_reporting_endpoint(repo),
+                None,
+            )]),
+            SupportedFacetType::CodeReview => {
+                let mut actions = vec![api_call(
+                    "Commit CODEOWNERS".to_string(),
+                    "PUT",
+                    Self::codeowners_endpoint(repo),
+                    Some(serde_json::to_value(Self::codeowners_request(
+                        code_review,
+                        repo,
+                        None,
+                    )?)?),
+                )];
+                let body = serde_json::to_value(code_review.request())?;
+                for branch in &code_review.branches {
+                    actions.push(api_call(
+                        format!(
+                            "Require {} approving reviews on {branch}",
+                            code_review.required_approving_reviews
+                        ),
+                        "PATCH",
+                        Self::required_reviews_endpoint(repo, branch),
+                        Some(body.clone()),
+                    ));
+                }
+                Ok(actions)
+            }
+            _ => Err(format!("Planning API bundle facet {facet_type} is not supported").into()),
+        }
+    }
+
+    async fn verify(&self, facet_type: &SupportedFacetType) -> Result<bool, SkootError> {
+        let repo = &self.repo;
+        let client = &self.github_auth.client()?;
+        let owner = repo.organization.get_name();
+        let name = &repo.name;
+        match facet_type {
+            SupportedFacetType::BranchProtection => {
+                for branch in &self.branch_protection.branches {
+                    let endpoint = &Self::branch_protection_endpoint(repo, branch);
+                    let protection: Result<serde_json::Value, _> =
+                        retry(&self.retry_policy, client, move || async move {
+                            client.get(endpoint, None::<&()>).await
+                        })
+                        .await;
+                    match protection {
+                        Ok(_) => {}
+                        // Github responds with a 404 and this message when the branch isn't protected.
+                        Err(octocrab::Error::GitHub { source, .. })
+                            if source.message == "Branch not protected" =>
+                        {
+                            return Ok(false)
+                        }
+                        Err(e) => return Err(SkootError::github(e)),
+                    }
+                }
+                Ok(true)
+            }
+            SupportedFacetType::RepoRuleset => Ok(self.find_ruleset().await?.is_some()),
+            SupportedFacetType::CodeReview => {
+                #[derive(serde::Deserialize)]
+                struct RequiredReviews {
+                    #[serde(default)]
+                    required_approving_review_count: u8,
+                }
+                if self.find_codeowners().await?.is_none() {
+                    return Ok(false);
+                }
+                for branch in &self.code_review.branches {
+                    let endpoint = &Self::required_reviews_endpoint(repo, branch);
+                    let reviews: Result<RequiredReviews, _> =
+                        retry(&self.retry_policy, client, move || async move {
+                            client.get(endpoint, None::<&()>).await
+                        })
+                        .await;
+                    match reviews {
+                        Ok(reviews)
+                            if reviews.required_approving_review_count
+                                >= self.code_review.required_approving_reviews => {}
+                        Ok(_) => return Ok(false),
+                        // Github responds with a 404 when the branch isn't protected or doesn't require reviews.
+                        Err(octocrab::Error::GitHub { source, .. })
+                            if source.message == "Branch not protected"
+                                || source
+                                    .message
+                                    .starts_with("Required pull request reviews not enabled") =>
+                        {
+                            return Ok(false)
+                        }
+                        Err(e) => return Err(SkootError::github(e)),
+                    }
+                }
+                Ok(true)
+            }
+            SupportedFacetType::VulnerabilityReporting => {
+                #[derive(serde::Deserialize)]
+                struct VulnerabilityReporting {
+                    enabled: bool,
+                }
+                let endpoint = &format!("/repos/{owner}/{name}/private-vulnerability-reporting");
+                let reporting: VulnerabilityReporting =
+                    retry(&self.retry_policy, client, move || async move {
+                        client.get(endpoint, None::<&()>).await
+                    })
+                    .await
+                    .map_err(SkootError::github)?;
+                Ok(reporting.enabled)
+            }
+            _ => Err(SkootError::from(format!(
+                "Verifying facet {facet_type} is not supported"
+            ))),
+        }
+    }
+
+    async fn revert(&self, facet_type: &SupportedFacetType) -> Result<(), SkootError> {
+        let repo = &self.repo;
+        let client = &self.github_auth.client()?;
+        let owner = repo.organization.get_name();
+        let name = &repo.name;
+        let endpoints = match facet_type {
+            // Removing a branch's protection removes its required signatures too.
+            SupportedFacetType::BranchProtection => self
+                .branch_protection
+                .branches
+                .iter()
+                .map(|branch| Self::branch_protection_endpoint(repo, branch))
+                .collect(),
+            SupportedFacetType::RepoRuleset => match self.find_ruleset().await? {
+                Some(id) => vec![format!(
+                    "{}/{id}",
+                    Self::rulesets_endpoint(repo, self.repo_ruleset.scope)?
+                )],
+                None => {
+                    info!("The ruleset of {owner}/{name} is already gone");
+                    vec![]
+                }
+            },
+            SupportedFacetType::VulnerabilityReporting => {
+                vec![format!(
+                    "/repos/{owner}/{name}/private-vulnerability-reporting"
+                )]
+            }
+            SupportedFacetType::CodeReview => self
+                .code_review
+                .branches
+                .iter()
+                .map(|branch| Self::required_reviews_endpoint(repo, branch))
+                .collect(),
+            _ => {
+                return Err(SkootError::from(format!(
+                    "Reverting facet {facet_type} is not supported"
+                )))
+            }
+        };
+        for endpoint in &endpoints {
+            info!("Reverting {facet_type} with {endpoint}");
+            let endpoint = endpoint.as_str();
+            retry(&self.retry_policy, client, move || async move {
+                octocrab::map_github_error(client._delete(endpoint, None::<&()>).await?).await
+            })
+            .await
+            .map_err(SkootError::github)?;
+        }
+        // The reviews are reverted first so removing the file isn't blocked by them.
+        if *facet_type == SupportedFacetType::CodeReview {
+            if let Some(sha) = self.find_codeowners().await? {
+                let endpoint = &Self::codeowners_endpoint(repo);
+                let body = &serde_json::json!({ "message": "Removed CODEOWNERS", "sha": sha });
+                info!("Removing CODEOWNERS with {endpoint}");
+                retry(&self.retry_policy, client, move || async move {
+                    octocrab::map_github_error(client._delete(endpoint, Some(body)).await?).await
+                })
+                .await
+                .map_err(SkootError::github)?;
+            }
+        }
+        info!("Reverted {facet_type} for {owner}/{name}");
+        Ok(())
+    }
+}
+
+/// The body of Github's create or update file contents API call.
+#[derive(serde::Serialize)]
+struct CodeownersRequest {
+    message: String,
+    /// The content of the file, encoded with base64.
+    content: String,
+    /// The blob SHA of the file being replaced, or `None` if the file is created.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sha: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_enable_skips_unsupported_facets() {
+        let api = BitbucketRepoApi {
+            repo: InitializedBitbucketRepo {
+                name: "test".to_string(),
+                workspace: "testws".to_string(),
+            },
+            client: BitbucketClient::default(),
+            branch_protection: BranchProtectionConfig::default(),
+        };
+        assert!(api
+            .unsupported_reason(&SupportedFacetType::BranchProtection)
+            .is_none());
+
+        let facet = enable(&api, &SupportedFacetType::VulnerabilityReporting)
+            .await
+            .unwrap();
+        assert!(facet.apis.is_empty());
+        assert_eq!(
+            facet.skipped_reason.unwrap(),
+            "https://bitbucket.org/testws/test is a Bitbucket repo and Bitbucket has no API to enable \
+             VulnerabilityReporting with"
+        );
+
+        // Facets that aren't enabled through an API are refused rather than skipped.
+        let api = GithubRepoApi {
+            repo: InitializedGithubRepo {
+                name: "test".to_string(),
+                organization: GithubUser::Organization("testorg".to_string()),
+            },
+            github_auth: GithubAuth::default(),
+            retry_policy: RetryPolicy::default(),
+            branch_protection: BranchProtectionConfig::default(),
+            code_review: CodeReviewConfig::default(),
+            repo_ruleset: RepoRulesetConfig::default(),
+        };
+        assert!(enable(&api, &SupportedFacetType::License).await.is_err());
+    }
+}
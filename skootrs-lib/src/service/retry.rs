@@ -0,0 +1,265 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Github API calls fail now and then with transient errors, like a dropped connection or a 502, and long runs
+//! of calls like creating a batch of projects run into Github's rate limits. `retry` runs a call again with
+//! exponential backoff and jitter when that happens, and waits for an exhausted rate limit to reset first, so
+//! a run doesn't die midway.
+
+use std::{
+    collections::hash_map::RandomState,
+    future::Future,
+    hash::{BuildHasher, Hasher},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use octocrab::{models::Rate, Octocrab};
+use skootrs_model::skootrs::{RetryPolicy, SkootError};
+use tracing::warn;
+
+use super::metrics::{self, Counter, Histogram};
+
+/// How long to wait when a secondary rate limit is hit. Github doesn't say when those reset, and recommends
+/// waiting at least a minute.
+const SECONDARY_RATE_LIMIT_WAIT: Duration = Duration::from_secs(60);
+
+/// Whether a failed call is worth retrying.
+enum Failure {
+    /// Github rejected the call because a rate limit was hit, so it wasn't applied.
+    RateLimited,
+    /// The call failed in a way that may go away by itself. It may or may not have been applied.
+    Transient,
+    /// Retrying the call would fail the same way, e.g. because it isn't authorized.
+    Permanent,
+}
+
+fn classify(error: &octocrab::Error) -> Failure {
+    match error {
+        e if is_rate_limited(e) => Failure::RateLimited,
+        // `Serde` is what octocrab returns when an error response isn't Github's JSON error, which is usually a
+        // 502 or 503 from Github's load balancers.
+        octocrab::Error::Hyper { .. }
+        | octocrab::Error::Service { .. }
+        | octocrab::Error::Serde { .. } => Failure::Transient,
+        _ => Failure::Permanent,
+    }
+}
+
+/// Runs a Github API call that is safe to repeat, retrying it when it fails with a transient error or hits a
+/// rate limit.
+///
+/// # Errors
+///
+/// Returns the call's error if it isn't worth retrying, or if it still fails after `policy.max_retries`
+/// retries.
+pub async fn retry<T, F, Fut>(
+    policy: &RetryPolicy,
+    client: &Octocrab,
+    call: F,
+) -> Result<T, octocrab::Error>
+where
+    F: FnMut() -> Fut + Send,
+    Fut: Future<Output = Result<T, octocrab::Error>> + Send,
+{
+    run(policy, client, true, call).await
+}
+
+/// Runs a Github API call that isn't safe to repeat, like creating a repo. It's only retried when it hit a rate
+/// limit, since Github rejects those calls without applying them, while a transient error could hide that the
+/// call went through.
+///
+/// # Errors
+///
+/// Returns the call's error if it didn't hit a rate limit, or if it still does after `policy.max_retries`
+/// retries.
+pub async fn retry_rate_limited<T, F, Fut>(
+    policy: &RetryPolicy,
+    client: &Octocrab,
+    call: F,
+) -> Result<T, octocrab::Error>
+where
+    F: FnMut() -> Fut + Send,
+    Fut: Future<Output = Result<T, octocrab::Error>> + Send,
+{
+    run(policy, client, false, call).await
+}
+
+async fn run<T, F, Fut>(
+    policy: &RetryPolicy,
+    client: &Octocrab,
+    idempotent: bool,
+    mut call: F,
+) -> Result<T, octocrab::Error>
+where
+    F: FnMut() -> Fut + Send,
+    Fut: Future<Output = Result<T, octocrab::Error>> + Send,
+{
+    let mut retries = 0;
+    loop {
+        let started = Instant::now();
+        let result = call().await;
+        let metrics = metrics::global();
+        metrics.increment(Counter::GithubApiCalls, &[]);
+        metrics.observe(Histogram::GithubApiCallDuration, &[], started.elapsed());
+        let error = match result {
+            Ok(value) => return Ok(value),
+            Err(error) => error,
+        };
+        metrics.increment(Counter::GithubApiFailures, &[]);
+        if retries >= policy.max_retries {
+            return Err(error);
+        }
+        let wait = match classify(&error) {
+            Failure::RateLimited => match rate_limited_wait(policy, client, retries).await {
+                Ok(wait) => wait,
+                Err(e) => {
+                    warn!("Not retrying rate limited Github API call: {e}");
+                    return Err(error);
+                }
+            },
+            Failure::Transient if idempotent => jitter(policy.backoff(retries)),
+            Failure::Transient | Failure::Permanent => return Err(error),
+        };
+        retries += 1;
+        warn!(
+            "Github API call failed, retrying in {}ms ({retries}/{}): {}",
+            wait.as_millis(),
+            policy.max_retries,
+            error.to_string().lines().next().unwrap_or_default()
+        );
+        tokio::time::sleep(wait).await;
+    }
+}
+
+/// Returns how long to wait before retrying a rate limited call. The rate limit API says when an exhausted
+/// primary rate limit resets, and doesn't count against the limit itself. If it can't be reached the call
+/// backs off like any other failure.
+async fn rate_limited_wait(
+    policy: &RetryPolicy,
+    client: &Octocrab,
+    retries: u32,
+) -> Result<Duration, SkootError> {
+    let Ok(rate_limit) = client.ratelimit().get().await else {
+        return Ok(jitter(policy.backoff(retries)));
+    };
+    let max_wait = Duration::from_secs(policy.max_rate_limit_wait_secs);
+    let wait = rate_limit_wait(&rate_limit.resources.core, unix_now(), max_wait)?;
+    // Jitter on top of the wait keeps every caller that waited for the same reset from retrying at once.
+    Ok(wait + jitter(policy.backoff(retries)) / 2)
+}
+
+pub(crate) fn is_rate_limited(error: &octocrab::Error) -> bool {
+    matches!(
+        error,
+        octocrab::Error::GitHub { source, .. } if source.message.to_lowercase().contains("rate limit")
+    )
+}
+
+/// Returns how long to wait before retrying a rate limited call. `now` is the current Unix time in seconds.
+pub(crate) fn rate_limit_wait(
+    core: &Rate,
+    now: u64,
+    max_wait: Duration,
+) -> Result<Duration, SkootError> {
+    if core.remaining > 0 {
+        return Ok(SECONDARY_RATE_LIMIT_WAIT);
+    }
+    // Github counts the reset time in whole seconds, so wait an extra second to be sure it has passed.
+    let wait = Duration::from_secs((core.reset as u64).saturating_sub(now) + 1);
+    if wait > max_wait {
+        return Err(format!(
+            "Github API rate limit is exhausted for another {}s, try again later",
+            wait.as_secs()
+        )
+        .into());
+    }
+    Ok(wait)
+}
+
+/// Returns a random duration between half of `wait` and `wait`, so callers that failed together don't all
+/// retry at the same time.
+fn jitter(wait: Duration) -> Duration {
+    // `RandomState` is randomly seeded, which is random enough to spread retries out.
+    let random = RandomState::new().build_hasher().finish();
+    let half = wait / 2;
+    let spread = u64::try_from(half.as_nanos()).unwrap_or(u64::MAX);
+    half + Duration::from_nanos(random % spread.saturating_add(1))
+}
+
+pub(crate) fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use super::*;
+
+    fn rate(remaining: usize, reset: usize) -> Rate {
+        Rate {
+            limit: 5000,
+            used: 5000 - remaining,
+            remaining,
+            reset,
+        }
+    }
+
+    #[test]
+    fn test_rate_limit_wait() {
+        let max_wait = Duration::from_secs(15 * 60);
+        assert_eq!(
+            rate_limit_wait(&rate(0, 1_030), 1_000, max_wait).unwrap(),
+            Duration::from_secs(31)
+        );
+        // The reset time already passed.
+        assert_eq!(
+            rate_limit_wait(&rate(0, 990), 1_000, max_wait).unwrap(),
+            Duration::from_secs(1)
+        );
+        // Calls remain, so this is a secondary rate limit.
+        assert_eq!(
+            rate_limit_wait(&rate(10, 1_030), 1_000, max_wait).unwrap(),
+            SECONDARY_RATE_LIMIT_WAIT
+        );
+        assert!(rate_limit_wait(&rate(0, 1_000 + 3_600), 1_000, max_wait).is_err());
+    }
+
+    #[test]
+    fn test_jitter() {
+        let wait = Duration::from_secs(8);
+        for _ in 0..100 {
+            let jittered = jitter(wait);
+            assert!(jittered >= wait / 2 && jittered <= wait);
+        }
+        assert_eq!(jitter(Duration::ZERO), Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_retry_gives_up_on_permanent_errors() {
+        let client = &Octocrab::default();
+        let calls = &AtomicU32::new(0);
+        let result: Result<(), _> = retry(&RetryPolicy::default(), client, move || async move {
+            calls.fetch_add(1, Ordering::SeqCst);
+            // Building a request for an invalid URI fails before anything is sent.
+            client.get("not a uri\n", None::<&()>).await
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
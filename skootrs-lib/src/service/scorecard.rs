@@ -0,0 +1,122 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![allow(clippy::module_name_repetitions)]
+
+use reqwest::StatusCode;
+use tracing::info;
+
+use skootrs_model::skootrs::{
+    scorecard::{ScorecardConfig, ScorecardResults},
+    InitializedRepo, SkootError,
+};
+
+/// The `ScorecardService` trait provides an interface for fetching the live OpenSSF Scorecard results of a
+/// project's repo. The `Scorecard` facet only sets up the workflow that scores a project, this is how the
+/// scores themselves are checked.
+pub trait ScorecardService {
+    /// Gets the latest Scorecard results for a project's repo.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if Scorecard has no results for the repo or they can't be fetched.
+    fn results(
+        &self,
+        initialized_repo: &InitializedRepo,
+    ) -> impl std::future::Future<Output = Result<ScorecardResults, SkootError>> + Send;
+}
+
+/// The `LocalScorecardService` struct provides an implementation of the `ScorecardService` trait that queries
+/// the Scorecard API.
+#[derive(Debug, Default)]
+pub struct LocalScorecardService {
+    /// Where the results are fetched from.
+    pub config: ScorecardConfig,
+}
+
+impl ScorecardService for LocalScorecardService {
+    async fn results(
+        &self,
+        initialized_repo: &InitializedRepo,
+    ) -> Result<ScorecardResults, SkootError> {
+        match initialized_repo {
+            InitializedRepo::Github(g) => {
+                let url = format!(
+                    "{}/projects/github.com/{}/{}",
+                    self.config.api_url.trim_end_matches('/'),
+                    g.organization.get_name(),
+                    g.name
+                );
+                info!("Fetching the Scorecard results of {}", g.full_url());
+                let response = reqwest::get(&url).await.map_err(SkootError::other)?;
+                if response.status() == StatusCode::NOT_FOUND {
+                    return Err(format!(
+                        "Scorecard has no results for {}, the Scorecard facet's workflow publishes them once \
+                         it runs",
+                        g.full_url()
+                    )
+                    .into());
+                }
+                Ok(serde_json::from_str(
+                    &response
+                        .error_for_status()
+                        .map_err(SkootError::other)?
+                        .text()
+                        .await
+                        .map_err(SkootError::other)?,
+                )?)
+            }
+            InitializedRepo::GenericGit(g) => Err(SkootError::from(format!(
+                "Scorecard results aren't available for generic git remote {}",
+                g.remote_url
+            ))),
+            InitializedRepo::Bitbucket(b) => Err(SkootError::from(format!(
+                "Scorecard results aren't available for Bitbucket repo {}",
+                b.full_url()
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_results() {
+        let results: ScorecardResults = serde_json::from_str(
+            r#"{
+                "date": "2024-04-01",
+                "repo": { "name": "github.com/kusaridev/skootrs", "commit": "abc" },
+                "scorecard": { "version": "v4.13.1", "commit": "def" },
+                "score": 6.2,
+                "checks": [
+                    {
+                        "name": "Branch-Protection",
+                        "score": 3,
+                        "reason": "branch protection is not maximal on development and all release branches",
+                        "details": null,
+                        "documentation": { "short": "", "url": "" }
+                    },
+                    { "name": "Fuzzing", "score": 0, "reason": "project is not fuzzed" }
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(results.checks.len(), 2);
+        assert_eq!(results.checks[0].name, "Branch-Protection");
+        assert_eq!(results.checks[1].score, 0);
+    }
+}
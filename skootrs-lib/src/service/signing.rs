@@ -0,0 +1,156 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Repos that require signed commits refuse pushes of unsigned ones, including the ones Skootrs makes. The
+//! `git` backend signs commits by passing the `commit_signing` config to `git commit` as config options, and
+//! the libgit2 backend, which can't sign on its own, runs the same signing program git would.
+
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+use skootrs_model::skootrs::{CommitSigning, SkootError};
+
+use super::command::CommandError;
+
+/// Returns the `-c` options that make `git commit` sign the commit, or nothing if commits aren't signed.
+#[must_use]
+pub fn git_config_args(signing: &CommitSigning) -> Vec<String> {
+    let config = match signing {
+        CommitSigning::None => return Vec::new(),
+        CommitSigning::Gpg { key } => {
+            let mut config = vec!["gpg.format=openpgp".to_string()];
+            config.extend(key.iter().map(|key| format!("user.signingkey={key}")));
+            config
+        }
+        CommitSigning::Ssh { key } => {
+            vec![
+                "gpg.format=ssh".to_string(),
+                format!("user.signingkey={key}"),
+            ]
+        }
+        CommitSigning::Gitsign => vec![
+            "gpg.format=x509".to_string(),
+            "gpg.x509.program=gitsign".to_string(),
+        ],
+    };
+    config
+        .into_iter()
+        .chain(std::iter::once("commit.gpgsign=true".to_string()))
+        .flat_map(|option| ["-c".to_string(), option])
+        .collect()
+}
+
+/// Signs the contents of a commit the way `git commit` would, and returns the armored signature, or `None` if
+/// commits aren't signed.
+///
+/// # Errors
+///
+/// Returns a `CommandError` if the signing program fails, e.g. because the key can't be found, or an error if
+/// it can't be run.
+pub fn sign(signing: &CommitSigning, buffer: &str) -> Result<Option<String>, SkootError> {
+    let mut command = match signing {
+        CommitSigning::None => return Ok(None),
+        CommitSigning::Gpg { key } => {
+            let mut command = Command::new("gpg");
+            command.args(["--status-fd=2", "-bsa"]);
+            if let Some(key) = key {
+                command.args(["-u", key]);
+            }
+            command
+        }
+        CommitSigning::Ssh { key } => {
+            let mut command = Command::new("ssh-keygen");
+            command.args(["-Y", "sign", "-n", "git", "-f", key]);
+            command
+        }
+        CommitSigning::Gitsign => {
+            let mut command = Command::new("gitsign");
+            command.args(["--status-fd=2", "-bsa"]);
+            command
+        }
+    };
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| {
+            format!(
+                "Failed to run {:?} to sign a commit: {e}",
+                command.get_program()
+            )
+        })?;
+    child
+        .stdin
+        .take()
+        .ok_or("Failed to write the commit to the signing program")?
+        .write_all(buffer.as_bytes())?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(SkootError::other(CommandError {
+            command: command.get_program().to_string_lossy().to_string(),
+            code: output.status.code(),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        }));
+    }
+    Ok(Some(
+        String::from_utf8(output.stdout).map_err(SkootError::other)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_git_config_args() {
+        assert!(git_config_args(&CommitSigning::None).is_empty());
+        assert_eq!(
+            git_config_args(&CommitSigning::Gpg { key: None }),
+            ["-c", "gpg.format=openpgp", "-c", "commit.gpgsign=true"]
+        );
+        assert_eq!(
+            git_config_args(&CommitSigning::Ssh {
+                key: "/home/me/.ssh/id_ed25519".to_string()
+            }),
+            [
+                "-c",
+                "gpg.format=ssh",
+                "-c",
+                "user.signingkey=/home/me/.ssh/id_ed25519",
+                "-c",
+                "commit.gpgsign=true"
+            ]
+        );
+        assert_eq!(
+            git_config_args(&CommitSigning::Gitsign),
+            [
+                "-c",
+                "gpg.format=x509",
+                "-c",
+                "gpg.x509.program=gitsign",
+                "-c",
+                "commit.gpgsign=true"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sign_unsigned() {
+        assert_eq!(sign(&CommitSigning::None, "tree 0").unwrap(), None);
+    }
+}
@@ -15,16 +15,24 @@
 
 #![allow(clippy::module_name_repetitions)]
 
-use std::{fs, path::Path, process::Command};
+use std::{error::Error, fmt, fs, io::Write, path::Path, process::Command};
 
 use sha2::Digest;
-use tracing::{debug, info};
+use tracing::{debug, error, info};
 
 use skootrs_model::skootrs::{
-    InitializedRepo, InitializedSource, SkootError, SourceInitializeParams,
+    CommitSigning, Config, GitBackend, InitializedRepo, InitializedSource, ReadStrategy,
+    SkootError, SourceInitializeParams,
 };
 
-use super::repo::{LocalRepoService, RepoService};
+use super::{
+    cancellation::{self, CancellableCommand},
+    command::CommandRunner,
+    git2_source::{Git2SourceService, GitCredentials},
+    read_only::ensure_writable,
+    repo::{LocalRepoService, RepoService},
+    signing,
+};
 /// The `SourceService` trait provides an interface for and managing a project's source code.
 /// This code is usually something a local git repo. The service differs from the repo service
 /// in that it's focused on the files and not the repo itself.
@@ -52,6 +60,42 @@ pub trait SourceService {
         message: String,
     ) -> Result<(), SkootError>;
 
+    /// Commits the changes to the given files, or every change if `paths` is `None`, to the checked out branch
+    /// without pushing them, so a change can be split into several commits that are pushed together. The
+    /// paths are relative to the root of the source. Returns whether anything was committed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the changes can't be committed.
+    fn commit_changes(
+        &self,
+        source: &InitializedSource,
+        paths: Option<&[String]>,
+        message: &str,
+    ) -> Result<bool, SkootError>;
+
+    /// Commits changes to a new branch off the checked out one and pushes the branch to the remote, so the
+    /// changes can be proposed as a pull request. The new branch stays checked out so later commits, like
+    /// the project's state, are part of the same change. Returns the branch the new one was created from.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the branch can't be created, or the changes can't be committed and pushed to it.
+    fn commit_and_push_branch(
+        &self,
+        source: InitializedSource,
+        branch: &str,
+        message: String,
+    ) -> Result<String, SkootError>;
+
+    /// Checks the remote's default branch out again after changes were pushed to a branch of their own, so
+    /// the next change starts from it. Returns whether a different branch was checked out.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the default branch of the remote isn't known or can't be checked out.
+    fn checkout_default_branch(&self, source: &InitializedSource) -> Result<bool, SkootError>;
+
     /// Writes a file to the source code directory.
     ///
     /// # Errors
@@ -77,6 +121,19 @@ pub trait SourceService {
         name: String,
     ) -> Result<String, SkootError>;
 
+    /// Removes a file from the source code directory. A file that is already gone isn't an error, so a
+    /// facet whose files were deleted by hand can still be removed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but can't be removed.
+    fn remove_file<P: AsRef<Path>>(
+        &self,
+        source: &InitializedSource,
+        path: P,
+        name: String,
+    ) -> Result<(), SkootError>;
+
     /// `hash_file` returns the SHA256 hash of a file.
     ///
     /// # Errors
@@ -95,12 +152,193 @@ pub trait SourceService {
     ///
     /// Returns an error if the updates can't be pulled from the remote repo.
     fn pull_updates(&self, source: InitializedSource) -> Result<(), SkootError>;
+
+    /// Points the `origin` remote of the local clone at a new URL, e.g. after the repo was renamed or
+    /// transferred.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the remote can't be changed.
+    fn set_remote_url(&self, source: &InitializedSource, url: &str) -> Result<(), SkootError>;
+
+    /// Deletes the local source code directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the source code directory can't be deleted.
+    fn delete(&self, source: InitializedSource) -> Result<(), SkootError>;
+
+    /// Verifies that the remote branch the source tracks points at the local `HEAD` commit. This is used
+    /// to make sure a push actually landed before doing anything that depends on it, like enabling branch
+    /// protection.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `PushVerificationError` if the remote ref is missing or points at a different commit.
+    fn verify_push(&self, source: &InitializedSource) -> Result<(), SkootError>;
+
+    /// Pushes the local `HEAD` and tags to the current branch of a mirror of the source's repo. The canonical
+    /// repo is the source of truth, so the mirror's branch is overwritten if it has diverged.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `PushVerificationError` if the push to the mirror fails.
+    fn push_mirror(
+        &self,
+        source: &InitializedSource,
+        mirror: &InitializedRepo,
+    ) -> Result<(), SkootError>;
+
+    /// Returns the commit SHA of the local `HEAD`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the `HEAD` commit can't be determined.
+    fn head_commit(&self, source: &InitializedSource) -> Result<String, SkootError>;
+
+    /// Verifies that the remote branch the source tracks is still at `expected_parent`. This is the
+    /// optimistic concurrency check that keeps two writers from clobbering each other's changes to the
+    /// same project.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ConcurrencyError::Conflict` if the remote branch has moved.
+    fn verify_remote_head(
+        &self,
+        source: &InitializedSource,
+        expected_parent: &str,
+    ) -> Result<(), SkootError>;
+
+    /// Takes an exclusive lock on the source for the lifetime of the returned `SourceLock`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ConcurrencyError::Locked` if another operation already holds the lock.
+    fn lock(&self, source: &InitializedSource) -> Result<SourceLock, SkootError>;
+
+    /// Resets the source back to `commit` if the current operation is cancelled, discarding the files and
+    /// commits the operation made that never reached the remote.
+    fn reset_on_cancel(&self, source: &InitializedSource, commit: &str);
+}
+
+/// The name of the lock file kept in the `.git` directory of a source while an operation is running on it.
+const LOCK_FILE_NAME: &str = "skootrs.lock";
+
+/// A guard for an exclusive lock on a source. The lock is released when the guard is dropped.
+#[derive(Debug, Default)]
+pub struct SourceLock {
+    /// The path to the lock file, if the lock is backed by one.
+    pub path: Option<std::path::PathBuf>,
+}
+
+impl Drop for SourceLock {
+    fn drop(&mut self) {
+        if let Some(path) = &self.path {
+            if let Err(e) = fs::remove_file(path) {
+                error!("Failed to release lock {path:?}: {e}");
+            }
+        }
+    }
 }
 
+/// The `ConcurrencyError` enum represents conflicts between concurrent operations on the same project.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConcurrencyError {
+    /// Another operation holds the lock on the project's source.
+    Locked {
+        /// The path to the lock file.
+        lock_path: String,
+    },
+    /// The remote branch moved since the operation started.
+    Conflict {
+        /// The branch that was checked.
+        branch: String,
+        /// The commit the operation started from.
+        expected: String,
+        /// The commit the remote branch points to now, if it exists.
+        actual: Option<String>,
+    },
+}
+
+impl fmt::Display for ConcurrencyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Locked { lock_path } => write!(
+                f,
+                "Another Skootrs operation is in progress on this project. Retry once it finishes, \
+                 or remove {lock_path} if no other operation is running"
+            ),
+            Self::Conflict {
+                branch,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Remote branch {branch} moved from {expected} to {} while this operation was running. \
+                 Pull the latest changes and retry",
+                actual.as_deref().unwrap_or("<deleted>")
+            ),
+        }
+    }
+}
+
+impl Error for ConcurrencyError {}
+
+/// The `PushVerificationError` enum represents the ways the remote can disagree with the local source
+/// after a push.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PushVerificationError {
+    /// The push command itself exited unsuccessfully.
+    PushFailed {
+        /// The stderr output of the push.
+        stderr: String,
+    },
+    /// The branch doesn't exist on the remote.
+    RemoteRefMissing {
+        /// The branch that was expected on the remote.
+        branch: String,
+    },
+    /// The branch exists on the remote but doesn't point to the local `HEAD`.
+    CommitMismatch {
+        /// The branch that was checked.
+        branch: String,
+        /// The commit of the local `HEAD`.
+        local: String,
+        /// The commit the remote branch points to.
+        remote: String,
+    },
+}
+
+impl fmt::Display for PushVerificationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PushFailed { stderr } => write!(f, "Failed to push changes: {stderr}"),
+            Self::RemoteRefMissing { branch } => {
+                write!(f, "Remote branch {branch} does not exist after push")
+            }
+            Self::CommitMismatch {
+                branch,
+                local,
+                remote,
+            } => write!(
+                f,
+                "Remote branch {branch} is at {remote} but local HEAD is at {local}"
+            ),
+        }
+    }
+}
+
+impl Error for PushVerificationError {}
+
 /// The `LocalSourceService` struct provides an implementation of the `SourceService` trait for initializing
 /// and managing a project's source files from the local machine.
-#[derive(Debug)]
-pub struct LocalSourceService {}
+#[derive(Debug, Default)]
+pub struct LocalSourceService {
+    /// Whether operations that change the source are blocked.
+    pub read_only: bool,
+    /// How the commits made to the source are signed.
+    pub commit_signing: CommitSigning,
+}
 
 impl SourceService for LocalSourceService {
     /// Returns `Ok(())` if changes are committed and pushed back to the remote  if successful,
@@ -110,7 +348,7 @@ impl SourceService for LocalSourceService {
         params: SourceInitializeParams,
         initialized_repo: InitializedRepo,
     ) -> Result<InitializedSource, SkootError> {
-        let repo_service = LocalRepoService {};
+        let repo_service = LocalRepoService::default();
         repo_service.clone_local(initialized_repo, params.parent_path)
     }
 
@@ -119,28 +357,93 @@ impl SourceService for LocalSourceService {
         source: InitializedSource,
         message: String,
     ) -> Result<(), SkootError> {
-        let _output = Command::new("git")
-            .arg("add")
-            .arg(".")
-            .current_dir(&source.path)
-            .output()?;
+        ensure_writable(
+            self.read_only,
+            format_args!("commit and push {}", source.path),
+        )?;
+        self.commit(&source, &message)?;
 
-        let _output = Command::new("git")
-            .arg("commit")
-            .arg("-m")
-            .arg(message)
-            .current_dir(&source.path)
-            .output()?;
-        info!("Committed changes for {}", source.path);
-
-        let _output = Command::new("git")
+        // The push can't be undone, so a cancelled operation has to stop before it.
+        cancellation::check()?;
+        let output = Command::new("git")
             .arg("push")
             .current_dir(&source.path)
-            .output()?;
+            .cancellable_output()?;
+        if !output.status.success() {
+            return Err(SkootError::other(PushVerificationError::PushFailed {
+                stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            }));
+        }
         info!("Pushed changes for {}", source.path);
         Ok(())
     }
 
+    fn commit_changes(
+        &self,
+        source: &InitializedSource,
+        paths: Option<&[String]>,
+        message: &str,
+    ) -> Result<bool, SkootError> {
+        ensure_writable(self.read_only, format_args!("commit to {}", source.path))?;
+        self.commit_paths(source, paths, message)
+    }
+
+    fn commit_and_push_branch(
+        &self,
+        source: InitializedSource,
+        branch: &str,
+        message: String,
+    ) -> Result<String, SkootError> {
+        ensure_writable(
+            self.read_only,
+            format_args!("commit and push {} to {branch}", source.path),
+        )?;
+        let base = Self::current_branch(&source)?;
+        Command::new("git")
+            .args(["checkout", "-B", branch])
+            .current_dir(&source.path)
+            .run_checked()?;
+        self.commit(&source, &message)?;
+
+        // The push can't be undone, so a cancelled operation has to stop before it.
+        cancellation::check()?;
+        // The upstream is set so later pushes of the branch go to the same remote branch.
+        let output = Command::new("git")
+            .args(["push", "--set-upstream", "origin", branch])
+            .current_dir(&source.path)
+            .cancellable_output()?;
+        if !output.status.success() {
+            return Err(SkootError::other(PushVerificationError::PushFailed {
+                stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            }));
+        }
+        info!("Pushed changes for {} to {branch}", source.path);
+        Ok(base)
+    }
+
+    fn checkout_default_branch(&self, source: &InitializedSource) -> Result<bool, SkootError> {
+        // The remote is asked since a clone of an empty repo doesn't know its default branch.
+        let output = Command::new("git")
+            .args(["ls-remote", "--symref", "origin", "HEAD"])
+            .current_dir(&source.path)
+            .run_checked()?;
+        let remote_head = String::from_utf8(output.stdout).map_err(SkootError::other)?;
+        let default_branch = remote_head
+            .lines()
+            .find_map(|line| line.strip_prefix("ref: refs/heads/"))
+            .and_then(|line| line.split_whitespace().next())
+            .ok_or_else(|| format!("Failed to get the default branch of {}", source.path))?;
+        if Self::current_branch(source)? == default_branch {
+            return Ok(false);
+        }
+        Command::new("git")
+            .args(["checkout", default_branch])
+            .current_dir(&source.path)
+            .run_checked()?;
+        info!("Checked out {default_branch} of {}", source.path);
+        Ok(true)
+    }
+
     /// Returns `Ok(())` if a file is successfully written to some path within the source directory. Otherwise,
     /// it returns an error.
     fn write_file<P: AsRef<Path>, C: AsRef<[u8]>>(
@@ -151,6 +454,10 @@ impl SourceService for LocalSourceService {
         contents: C,
     ) -> Result<(), SkootError> {
         let full_path = Path::new(&source.path).join(&path);
+        ensure_writable(
+            self.read_only,
+            format_args!("write {:?}", full_path.join(&name)),
+        )?;
         // Ensure path exists
         info!("Creating path {:?}", &full_path);
         fs::create_dir_all(&full_path)?;
@@ -171,6 +478,24 @@ impl SourceService for LocalSourceService {
         Ok(contents)
     }
 
+    fn remove_file<P: AsRef<Path>>(
+        &self,
+        source: &InitializedSource,
+        path: P,
+        name: String,
+    ) -> Result<(), SkootError> {
+        let full_path = Path::new(&source.path).join(&path).join(name);
+        ensure_writable(self.read_only, format_args!("remove {full_path:?}"))?;
+        match fs::remove_file(&full_path) {
+            Ok(()) => {
+                debug!("{:?} file removed", &full_path);
+                Ok(())
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
     fn hash_file<P: AsRef<Path>>(
         &self,
         source: &InitializedSource,
@@ -190,46 +515,529 @@ impl SourceService for LocalSourceService {
     }
 
     fn pull_updates(&self, source: InitializedSource) -> Result<(), SkootError> {
-        let _output = Command::new("git")
+        Command::new("git")
             .arg("pull")
             .current_dir(&source.path)
-            .output()?;
+            .run_checked()?;
         info!("Pulled updates for {}", source.path);
         Ok(())
     }
+
+    fn set_remote_url(&self, source: &InitializedSource, url: &str) -> Result<(), SkootError> {
+        ensure_writable(
+            self.read_only,
+            format_args!("set the remote of {} to {url}", source.path),
+        )?;
+        Command::new("git")
+            .args(["remote", "set-url", "origin", url])
+            .current_dir(&source.path)
+            .run_checked()?;
+        info!("Set the remote of {} to {url}", source.path);
+        Ok(())
+    }
+
+    fn delete(&self, source: InitializedSource) -> Result<(), SkootError> {
+        ensure_writable(self.read_only, format_args!("delete {}", source.path))?;
+        if Path::new(&source.path).exists() {
+            fs::remove_dir_all(&source.path)?;
+            info!("Deleted {}", source.path);
+        } else {
+            debug!("{} doesn't exist, nothing to delete", source.path);
+        }
+        Ok(())
+    }
+
+    fn verify_push(&self, source: &InitializedSource) -> Result<(), SkootError> {
+        let local = self.head_commit(source)?;
+        let branch = Self::current_branch(source)?;
+        let Some(remote) = Self::remote_branch_commit(source, &branch)? else {
+            return Err(SkootError::other(PushVerificationError::RemoteRefMissing {
+                branch,
+            }));
+        };
+
+        if remote != local {
+            return Err(SkootError::other(PushVerificationError::CommitMismatch {
+                branch,
+                local,
+                remote,
+            }));
+        }
+
+        debug!(
+            "Verified remote branch {branch} is at {local} for {}",
+            source.path
+        );
+        Ok(())
+    }
+
+    fn push_mirror(
+        &self,
+        source: &InitializedSource,
+        mirror: &InitializedRepo,
+    ) -> Result<(), SkootError> {
+        ensure_writable(
+            self.read_only,
+            format_args!("push {} to mirror {}", source.path, mirror.full_url()),
+        )?;
+        let branch = Self::current_branch(source)?;
+        cancellation::check()?;
+        let output = Command::new("git")
+            .arg("push")
+            .arg("--force")
+            .arg("--follow-tags")
+            .arg(mirror.full_url())
+            .arg(format!("HEAD:refs/heads/{branch}"))
+            .current_dir(&source.path)
+            .cancellable_output()?;
+        if !output.status.success() {
+            return Err(SkootError::other(PushVerificationError::PushFailed {
+                stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            }));
+        }
+        info!(
+            "Pushed {branch} of {} to mirror {}",
+            source.path,
+            mirror.full_url()
+        );
+        Ok(())
+    }
+
+    fn head_commit(&self, source: &InitializedSource) -> Result<String, SkootError> {
+        let output = Command::new("git")
+            .arg("rev-parse")
+            .arg("HEAD")
+            .current_dir(&source.path)
+            .run_checked()?;
+        Ok(String::from_utf8(output.stdout)
+            .map_err(SkootError::other)?
+            .trim()
+            .to_string())
+    }
+
+    fn verify_remote_head(
+        &self,
+        source: &InitializedSource,
+        expected_parent: &str,
+    ) -> Result<(), SkootError> {
+        let branch = Self::current_branch(source)?;
+        let actual = Self::remote_branch_commit(source, &branch)?;
+        if actual.as_deref() != Some(expected_parent) {
+            return Err(SkootError::other(ConcurrencyError::Conflict {
+                branch,
+                expected: expected_parent.to_string(),
+                actual,
+            }));
+        }
+        Ok(())
+    }
+
+    fn lock(&self, source: &InitializedSource) -> Result<SourceLock, SkootError> {
+        let lock_path = Path::new(&source.path).join(".git").join(LOCK_FILE_NAME);
+        let lock_file = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&lock_path);
+        match lock_file {
+            Ok(mut lock_file) => {
+                writeln!(lock_file, "{}", std::process::id())?;
+                debug!("Acquired lock {:?}", &lock_path);
+                Ok(SourceLock {
+                    path: Some(lock_path),
+                })
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                Err(SkootError::other(ConcurrencyError::Locked {
+                    lock_path: lock_path.to_string_lossy().to_string(),
+                }))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn reset_on_cancel(&self, source: &InitializedSource, commit: &str) {
+        let path = source.path.clone();
+        let commit = commit.to_string();
+        cancellation::on_cancel(move || {
+            for args in [
+                vec!["reset", "--hard", commit.as_str()],
+                vec!["clean", "-fd"],
+            ] {
+                match Command::new("git").args(&args).current_dir(&path).output() {
+                    Ok(output) if output.status.success() => {}
+                    Ok(output) => error!(
+                        "Failed to reset {path} to {commit}: {}",
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    ),
+                    Err(e) => error!("Failed to reset {path} to {commit}: {e}"),
+                }
+            }
+            info!("Reset {path} to {commit} after the operation was cancelled");
+        });
+    }
+}
+
+impl LocalSourceService {
+    /// Commits every change in the source to the checked out branch. Nothing is committed if nothing changed.
+    fn commit(&self, source: &InitializedSource, message: &str) -> Result<(), SkootError> {
+        self.commit_paths(source, None, message).map(|_| ())
+    }
+
+    /// Commits the changes to the given files, or every change if `paths` is `None`, to the checked out branch.
+    /// Returns whether anything was committed.
+    fn commit_paths(
+        &self,
+        source: &InitializedSource,
+        paths: Option<&[String]>,
+        message: &str,
+    ) -> Result<bool, SkootError> {
+        let mut add = Command::new("git");
+        add.args(["add", "--all", "--"]);
+        match paths {
+            Some(paths) => add.args(paths),
+            None => add.arg("."),
+        };
+        add.current_dir(&source.path).run_checked()?;
+
+        // `git commit` fails if nothing changed, which isn't an error here since the push still has to happen.
+        let staged = Command::new("git")
+            .args(["diff", "--cached", "--quiet"])
+            .current_dir(&source.path)
+            .cancellable_output()?;
+        if staged.status.success() {
+            debug!("Nothing to commit for {}", source.path);
+            return Ok(false);
+        }
+        Command::new("git")
+            .args(signing::git_config_args(&self.commit_signing))
+            .arg("commit")
+            .arg("-m")
+            .arg(message)
+            .current_dir(&source.path)
+            .run_checked()?;
+        info!("Committed changes for {}", source.path);
+        Ok(true)
+    }
+
+    fn current_branch(source: &InitializedSource) -> Result<String, SkootError> {
+        let output = Command::new("git")
+            .arg("rev-parse")
+            .arg("--abbrev-ref")
+            .arg("HEAD")
+            .current_dir(&source.path)
+            .run_checked()?;
+        Ok(String::from_utf8(output.stdout)
+            .map_err(SkootError::other)?
+            .trim()
+            .to_string())
+    }
+
+    fn remote_branch_commit(
+        source: &InitializedSource,
+        branch: &str,
+    ) -> Result<Option<String>, SkootError> {
+        let output = Command::new("git")
+            .arg("ls-remote")
+            .arg("origin")
+            .arg(format!("refs/heads/{branch}"))
+            .current_dir(&source.path)
+            .run_checked()?;
+        let remote_refs = String::from_utf8(output.stdout).map_err(SkootError::other)?;
+        Ok(remote_refs
+            .split_whitespace()
+            .next()
+            .map(ToString::to_string))
+    }
+}
+
+/// The `ConfiguredSourceService` enum is the `SourceService` implementation selected by the `git_backend`
+/// config.
+#[derive(Debug)]
+pub enum ConfiguredSourceService {
+    /// Runs the `git` binary.
+    Cli(LocalSourceService),
+    /// Uses libgit2.
+    Libgit2(Git2SourceService),
+}
+
+impl ConfiguredSourceService {
+    /// Returns the implementation the `git_backend` config selects.
+    #[must_use]
+    pub fn from_config(config: &Config) -> Self {
+        match config.git_backend {
+            GitBackend::Cli => Self::Cli(LocalSourceService {
+                read_only: config.read_only,
+                commit_signing: config.commit_signing.clone(),
+            }),
+            GitBackend::Libgit2 => Self::Libgit2(Git2SourceService {
+                read_only: config.read_only,
+                commit_signing: config.commit_signing.clone(),
+                credentials: GitCredentials::from_config(config),
+            }),
+        }
+    }
+}
+
+impl SourceService for ConfiguredSourceService {
+    fn initialize(
+        &self,
+        params: SourceInitializeParams,
+        initialized_repo: InitializedRepo,
+    ) -> Result<InitializedSource, SkootError> {
+        match self {
+            Self::Cli(s) => s.initialize(params, initialized_repo),
+            Self::Libgit2(s) => s.initialize(params, initialized_repo),
+        }
+    }
+
+    fn commit_and_push_changes(
+        &self,
+        source: InitializedSource,
+        message: String,
+    ) -> Result<(), SkootError> {
+        match self {
+            Self::Cli(s) => s.commit_and_push_changes(source, message),
+            Self::Libgit2(s) => s.commit_and_push_changes(source, message),
+        }
+    }
+
+    fn commit_changes(
+        &self,
+        source: &InitializedSource,
+        paths: Option<&[String]>,
+        message: &str,
+    ) -> Result<bool, SkootError> {
+        match self {
+            Self::Cli(s) => s.commit_changes(source, paths, message),
+            Self::Libgit2(s) => s.commit_changes(source, paths, message),
+        }
+    }
+
+    fn commit_and_push_branch(
+        &self,
+        source: InitializedSource,
+        branch: &str,
+        message: String,
+    ) -> Result<String, SkootError> {
+        match self {
+            Self::Cli(s) => s.commit_and_push_branch(source, branch, message),
+            Self::Libgit2(s) => s.commit_and_push_branch(source, branch, message),
+        }
+    }
+
+    fn checkout_default_branch(&self, source: &InitializedSource) -> Result<bool, SkootError> {
+        match self {
+            Self::Cli(s) => s.checkout_default_branch(source),
+            Self::Libgit2(s) => s.checkout_default_branch(source),
+        }
+    }
+
+    fn write_file<P: AsRef<Path>, C: AsRef<[u8]>>(
+        &self,
+        source: InitializedSource,
+        path: P,
+        name: String,
+        contents: C,
+    ) -> Result<(), SkootError> {
+        match self {
+            Self::Cli(s) => s.write_file(source, path, name, contents),
+            Self::Libgit2(s) => s.write_file(source, path, name, contents),
+        }
+    }
+
+    fn read_file<P: AsRef<Path>>(
+        &self,
+        source: &InitializedSource,
+        path: P,
+        name: String,
+    ) -> Result<String, SkootError> {
+        match self {
+            Self::Cli(s) => s.read_file(source, path, name),
+            Self::Libgit2(s) => s.read_file(source, path, name),
+        }
+    }
+
+    fn remove_file<P: AsRef<Path>>(
+        &self,
+        source: &InitializedSource,
+        path: P,
+        name: String,
+    ) -> Result<(), SkootError> {
+        match self {
+            Self::Cli(s) => s.remove_file(source, path, name),
+            Self::Libgit2(s) => s.remove_file(source, path, name),
+        }
+    }
+
+    fn hash_file<P: AsRef<Path>>(
+        &self,
+        source: &InitializedSource,
+        path: P,
+        name: String,
+    ) -> Result<String, SkootError> {
+        match self {
+            Self::Cli(s) => s.hash_file(source, path, name),
+            Self::Libgit2(s) => s.hash_file(source, path, name),
+        }
+    }
+
+    fn pull_updates(&self, source: InitializedSource) -> Result<(), SkootError> {
+        match self {
+            Self::Cli(s) => s.pull_updates(source),
+            Self::Libgit2(s) => s.pull_updates(source),
+        }
+    }
+
+    fn set_remote_url(&self, source: &InitializedSource, url: &str) -> Result<(), SkootError> {
+        match self {
+            Self::Cli(s) => s.set_remote_url(source, url),
+            Self::Libgit2(s) => s.set_remote_url(source, url),
+        }
+    }
+
+    fn delete(&self, source: InitializedSource) -> Result<(), SkootError> {
+        match self {
+            Self::Cli(s) => s.delete(source),
+            Self::Libgit2(s) => s.delete(source),
+        }
+    }
+
+    fn verify_push(&self, source: &InitializedSource) -> Result<(), SkootError> {
+        match self {
+            Self::Cli(s) => s.verify_push(source),
+            Self::Libgit2(s) => s.verify_push(source),
+        }
+    }
+
+    fn push_mirror(
+        &self,
+        source: &InitializedSource,
+        mirror: &InitializedRepo,
+    ) -> Result<(), SkootError> {
+        match self {
+            Self::Cli(s) => s.push_mirror(source, mirror),
+            Self::Libgit2(s) => s.push_mirror(source, mirror),
+        }
+    }
+
+    fn head_commit(&self, source: &InitializedSource) -> Result<String, SkootError> {
+        match self {
+            Self::Cli(s) => s.head_commit(source),
+            Self::Libgit2(s) => s.head_commit(source),
+        }
+    }
+
+    fn verify_remote_head(
+        &self,
+        source: &InitializedSource,
+        expected_parent: &str,
+    ) -> Result<(), SkootError> {
+        match self {
+            Self::Cli(s) => s.verify_remote_head(source, expected_parent),
+            Self::Libgit2(s) => s.verify_remote_head(source, expected_parent),
+        }
+    }
+
+    fn lock(&self, source: &InitializedSource) -> Result<SourceLock, SkootError> {
+        match self {
+            Self::Cli(s) => s.lock(source),
+            Self::Libgit2(s) => s.lock(source),
+        }
+    }
+
+    fn reset_on_cancel(&self, source: &InitializedSource, commit: &str) {
+        match self {
+            Self::Cli(s) => s.reset_on_cancel(source, commit),
+            Self::Libgit2(s) => s.reset_on_cancel(source, commit),
+        }
+    }
+}
+
+/// Returns the local clone of a repo under `local_project_path` to read a project's files from, after
+/// pulling the latest changes into it. Returns `None` if the read strategy says the files should be read
+/// through the repo host's API instead.
+///
+/// # Errors
+///
+/// Returns an error if the read strategy is `ReadStrategy::Local` and there's no local clone of the repo
+/// or it can't be updated.
+pub fn local_source<S: SourceService>(
+    source_service: &S,
+    local_project_path: Option<&str>,
+    repo: &InitializedRepo,
+    read_strategy: ReadStrategy,
+) -> Result<Option<InitializedSource>, SkootError> {
+    if read_strategy == ReadStrategy::Remote {
+        return Ok(None);
+    }
+    let source = local_project_path
+        .map(|path| InitializedSource {
+            path: format!("{path}/{}", repo.name()),
+        })
+        .filter(|source| Path::new(&source.path).join(".git").exists())
+        .ok_or_else(|| {
+            SkootError::NotFound(format!("There's no local clone of {}", repo.full_url()))
+        })
+        .and_then(|source| {
+            source_service.pull_updates(source.clone())?;
+            Ok(source)
+        });
+    match source {
+        Ok(source) => Ok(Some(source)),
+        Err(e) if read_strategy == ReadStrategy::Auto => {
+            info!("Reading {} through its host's API: {e}", repo.full_url());
+            Ok(None)
+        }
+        Err(e) => Err(e),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use skootrs_model::skootrs::{GithubUser, InitializedGithubRepo};
+    use skootrs_model::skootrs::InitializedGenericGitRepo;
     use std::path::PathBuf;
     use tempdir::TempDir;
 
     #[test]
     fn test_initialize() {
-        let source_service = LocalSourceService {};
+        let source_service = LocalSourceService::default();
         let temp_dir = TempDir::new("test").unwrap();
         let parent_path = temp_dir.path().to_str().unwrap();
+        let remote = temp_dir.path().join("remote").join("skootrs");
+        fs::create_dir_all(&remote).unwrap();
+        git(&remote, &["init", "--bare", "--initial-branch=main"]);
         let params = SourceInitializeParams {
             parent_path: parent_path.to_string(),
         };
-        let initialized_repo = InitializedRepo::Github(InitializedGithubRepo {
+        let initialized_repo = InitializedRepo::GenericGit(InitializedGenericGitRepo {
             name: "skootrs".to_string(),
-            organization: GithubUser::Organization("kusaridev".to_string()),
+            remote_url: remote.to_str().unwrap().to_string(),
         });
-        let result = source_service.initialize(params, initialized_repo);
+        let result = source_service.initialize(params.clone(), initialized_repo);
         assert!(result.is_ok());
         let initialized_source = result.unwrap();
         assert_eq!(
             initialized_source.path,
             format!("{}/{}", parent_path, "skootrs")
         );
+
+        // A clone that fails is an error rather than a source that doesn't exist.
+        let missing_repo = InitializedRepo::GenericGit(InitializedGenericGitRepo {
+            name: "missing".to_string(),
+            remote_url: temp_dir
+                .path()
+                .join("missing")
+                .to_str()
+                .unwrap()
+                .to_string(),
+        });
+        assert!(source_service.initialize(params, missing_repo).is_err());
     }
 
     #[test]
     fn test_write_file() {
-        let source_service = LocalSourceService {};
+        let source_service = LocalSourceService::default();
         let temp_dir = TempDir::new("test").unwrap();
         let initialized_source = InitializedSource {
             path: temp_dir.path().to_str().unwrap().to_string(),
@@ -248,7 +1056,7 @@ mod tests {
 
     #[test]
     fn test_read_file() {
-        let source_service = LocalSourceService {};
+        let source_service = LocalSourceService::default();
         let temp_dir = TempDir::new("test").unwrap();
         let initialized_source = InitializedSource {
             path: temp_dir.path().to_str().unwrap().to_string(),
@@ -267,4 +1075,351 @@ mod tests {
             .unwrap();
         assert_eq!(file_contents, "File contents");
     }
+
+    #[test]
+    fn test_remove_file() {
+        let source_service = LocalSourceService::default();
+        let temp_dir = TempDir::new("test").unwrap();
+        let initialized_source = InitializedSource {
+            path: temp_dir.path().to_str().unwrap().to_string(),
+        };
+        let path = "subdirectory";
+        let name = "file.txt".to_string();
+        source_service
+            .write_file(
+                initialized_source.clone(),
+                path,
+                name.clone(),
+                "File contents",
+            )
+            .unwrap();
+        let file_path = temp_dir.path().join(path).join(&name);
+        assert!(file_path.exists());
+
+        source_service
+            .remove_file(&initialized_source, path, name.clone())
+            .unwrap();
+        assert!(!file_path.exists());
+        // Removing a file that is already gone is a no-op.
+        assert!(source_service
+            .remove_file(&initialized_source, path, name)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_read_only() {
+        let temp_dir = TempDir::new("test").unwrap();
+        let initialized_source = InitializedSource {
+            path: temp_dir.path().to_str().unwrap().to_string(),
+        };
+        LocalSourceService::default()
+            .write_file(initialized_source.clone(), "", "file.txt".into(), "before")
+            .unwrap();
+
+        let source_service = LocalSourceService {
+            read_only: true,
+            ..Default::default()
+        };
+        let err = source_service
+            .write_file(initialized_source.clone(), "", "file.txt".into(), "after")
+            .unwrap_err();
+        assert!(crate::service::read_only::is_read_only(&err));
+        assert!(source_service
+            .remove_file(&initialized_source, "", "file.txt".into())
+            .is_err());
+        assert!(source_service.delete(initialized_source.clone()).is_err());
+        assert_eq!(
+            source_service
+                .read_file(&initialized_source, "", "file.txt".into())
+                .unwrap(),
+            "before"
+        );
+    }
+
+    fn git(path: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args([
+                "-c",
+                "user.name=skootrs",
+                "-c",
+                "user.email=skootrs@example.com",
+            ])
+            .args(args)
+            .current_dir(path)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {args:?} failed");
+    }
+
+    fn init_origin_and_clone(temp_dir: &TempDir) -> InitializedSource {
+        let origin = temp_dir.path().join("origin.git");
+        fs::create_dir_all(&origin).unwrap();
+        git(&origin, &["init", "--bare", "--initial-branch=main"]);
+        git(temp_dir.path(), &["clone", "origin.git", "clone"]);
+        let clone = temp_dir.path().join("clone");
+        git(&clone, &["checkout", "-b", "main"]);
+        InitializedSource {
+            path: clone.to_str().unwrap().to_string(),
+        }
+    }
+
+    #[test]
+    fn test_verify_push() {
+        let source_service = LocalSourceService::default();
+        let temp_dir = TempDir::new("test").unwrap();
+        let initialized_source = init_origin_and_clone(&temp_dir);
+        let clone = PathBuf::from(&initialized_source.path);
+
+        fs::write(clone.join("README.md"), "pushed").unwrap();
+        git(&clone, &["add", "."]);
+        git(&clone, &["commit", "-m", "pushed"]);
+        git(&clone, &["push", "origin", "main"]);
+        assert!(source_service.verify_push(&initialized_source).is_ok());
+
+        fs::write(clone.join("README.md"), "not pushed").unwrap();
+        git(&clone, &["commit", "-am", "not pushed"]);
+        let err = source_service.verify_push(&initialized_source).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<PushVerificationError>(),
+            Some(PushVerificationError::CommitMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_set_remote_url() {
+        let temp_dir = TempDir::new("test").unwrap();
+        let initialized_source = init_origin_and_clone(&temp_dir);
+        let new_url = "https://github.com/new-org/payments";
+        LocalSourceService::default()
+            .set_remote_url(&initialized_source, new_url)
+            .unwrap();
+        let output = Command::new("git")
+            .args(["remote", "get-url", "origin"])
+            .current_dir(&initialized_source.path)
+            .output()
+            .unwrap();
+        assert_eq!(String::from_utf8(output.stdout).unwrap().trim(), new_url);
+
+        let source_service = LocalSourceService {
+            read_only: true,
+            ..Default::default()
+        };
+        let err = source_service
+            .set_remote_url(&initialized_source, "https://github.com/other-org/payments")
+            .unwrap_err();
+        assert!(crate::service::read_only::is_read_only(&err));
+    }
+
+    #[test]
+    fn test_verify_push_missing_remote_ref() {
+        let source_service = LocalSourceService::default();
+        let temp_dir = TempDir::new("test").unwrap();
+        let initialized_source = init_origin_and_clone(&temp_dir);
+        let clone = PathBuf::from(&initialized_source.path);
+
+        fs::write(clone.join("README.md"), "never pushed").unwrap();
+        git(&clone, &["add", "."]);
+        git(&clone, &["commit", "-m", "never pushed"]);
+        let err = source_service.verify_push(&initialized_source).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<PushVerificationError>(),
+            Some(&PushVerificationError::RemoteRefMissing {
+                branch: "main".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_commit_and_push_branch() {
+        let source_service = LocalSourceService::default();
+        let temp_dir = TempDir::new("test").unwrap();
+        let initialized_source = init_origin_and_clone(&temp_dir);
+        let clone = PathBuf::from(&initialized_source.path);
+        git(&clone, &["config", "user.name", "skootrs"]);
+        git(&clone, &["config", "user.email", "skootrs@example.com"]);
+        fs::write(clone.join("README.md"), "initial").unwrap();
+        git(&clone, &["add", "."]);
+        git(&clone, &["commit", "-m", "initial"]);
+        git(&clone, &["push", "origin", "main"]);
+        let main = source_service.head_commit(&initialized_source).unwrap();
+
+        fs::write(clone.join("README.md"), "changed").unwrap();
+        let base = source_service
+            .commit_and_push_branch(
+                initialized_source.clone(),
+                "skootrs/test",
+                "Changed readme".to_string(),
+            )
+            .unwrap();
+        assert_eq!(base, "main");
+        assert!(source_service.verify_push(&initialized_source).is_ok());
+        assert_eq!(
+            LocalSourceService::remote_branch_commit(&initialized_source, "main").unwrap(),
+            Some(main.clone())
+        );
+
+        assert!(source_service
+            .checkout_default_branch(&initialized_source)
+            .unwrap());
+        assert_eq!(
+            source_service.head_commit(&initialized_source).unwrap(),
+            main
+        );
+        assert_eq!(
+            fs::read_to_string(clone.join("README.md")).unwrap(),
+            "initial"
+        );
+        assert!(!source_service
+            .checkout_default_branch(&initialized_source)
+            .unwrap());
+    }
+
+    #[test]
+    fn test_push_mirror() {
+        let source_service = LocalSourceService::default();
+        let temp_dir = TempDir::new("test").unwrap();
+        let initialized_source = init_origin_and_clone(&temp_dir);
+        let clone = PathBuf::from(&initialized_source.path);
+        let mirror_path = temp_dir.path().join("mirror.git");
+        fs::create_dir_all(&mirror_path).unwrap();
+        git(&mirror_path, &["init", "--bare", "--initial-branch=main"]);
+        let mirror = InitializedRepo::GenericGit(InitializedGenericGitRepo {
+            name: "mirror".to_string(),
+            remote_url: mirror_path.to_str().unwrap().to_string(),
+        });
+        let mirror_head = || {
+            let output = Command::new("git")
+                .args(["rev-parse", "refs/heads/main"])
+                .current_dir(&mirror_path)
+                .output()
+                .unwrap();
+            String::from_utf8(output.stdout).unwrap().trim().to_string()
+        };
+
+        fs::write(clone.join("README.md"), "first").unwrap();
+        git(&clone, &["add", "."]);
+        git(&clone, &["commit", "-m", "first"]);
+        source_service
+            .push_mirror(&initialized_source, &mirror)
+            .unwrap();
+        assert_eq!(
+            mirror_head(),
+            source_service.head_commit(&initialized_source).unwrap()
+        );
+
+        // A mirror that diverged from the canonical repo is brought back in line with it.
+        git(&clone, &["commit", "--amend", "-m", "rewritten"]);
+        source_service
+            .push_mirror(&initialized_source, &mirror)
+            .unwrap();
+        assert_eq!(
+            mirror_head(),
+            source_service.head_commit(&initialized_source).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_lock() {
+        let source_service = LocalSourceService::default();
+        let temp_dir = TempDir::new("test").unwrap();
+        let initialized_source = init_origin_and_clone(&temp_dir);
+
+        let lock = source_service.lock(&initialized_source).unwrap();
+        let err = source_service.lock(&initialized_source).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ConcurrencyError>(),
+            Some(ConcurrencyError::Locked { .. })
+        ));
+
+        drop(lock);
+        assert!(source_service.lock(&initialized_source).is_ok());
+    }
+
+    #[test]
+    fn test_verify_remote_head_conflict() {
+        let source_service = LocalSourceService::default();
+        let temp_dir = TempDir::new("test").unwrap();
+        let initialized_source = init_origin_and_clone(&temp_dir);
+        let clone = PathBuf::from(&initialized_source.path);
+
+        fs::write(clone.join("README.md"), "first").unwrap();
+        git(&clone, &["add", "."]);
+        git(&clone, &["commit", "-m", "first"]);
+        git(&clone, &["push", "origin", "main"]);
+        let expected_parent = source_service.head_commit(&initialized_source).unwrap();
+        assert!(source_service
+            .verify_remote_head(&initialized_source, &expected_parent)
+            .is_ok());
+
+        // Another writer pushes on top of the same parent.
+        git(temp_dir.path(), &["clone", "origin.git", "other"]);
+        let other = temp_dir.path().join("other");
+        fs::write(other.join("README.md"), "second").unwrap();
+        git(&other, &["commit", "-am", "second"]);
+        git(&other, &["push", "origin", "main"]);
+
+        let err = source_service
+            .verify_remote_head(&initialized_source, &expected_parent)
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ConcurrencyError>(),
+            Some(ConcurrencyError::Conflict { .. })
+        ));
+    }
+
+    #[test]
+    fn test_local_source() {
+        let source_service = LocalSourceService::default();
+        let temp_dir = TempDir::new("test").unwrap();
+        let local_project_path = temp_dir.path().to_str().unwrap();
+        let repo = InitializedRepo::Github(skootrs_model::skootrs::InitializedGithubRepo {
+            name: "test".to_string(),
+            organization: skootrs_model::skootrs::GithubUser::User("testuser".to_string()),
+        });
+
+        assert!(local_source(
+            &source_service,
+            Some(local_project_path),
+            &repo,
+            ReadStrategy::Remote
+        )
+        .unwrap()
+        .is_none());
+        // There's no local clone, so only `Auto` falls back to the repo host's API.
+        assert!(local_source(
+            &source_service,
+            Some(local_project_path),
+            &repo,
+            ReadStrategy::Auto
+        )
+        .unwrap()
+        .is_none());
+        assert!(local_source(&source_service, None, &repo, ReadStrategy::Local).is_err());
+        assert!(local_source(
+            &source_service,
+            Some(local_project_path),
+            &repo,
+            ReadStrategy::Local
+        )
+        .is_err());
+
+        // A clone that can't be updated isn't read either.
+        fs::create_dir_all(temp_dir.path().join("test/.git")).unwrap();
+        assert!(local_source(
+            &source_service,
+            Some(local_project_path),
+            &repo,
+            ReadStrategy::Auto
+        )
+        .unwrap()
+        .is_none());
+        assert!(local_source(
+            &source_service,
+            Some(local_project_path),
+            &repo,
+            ReadStrategy::Local
+        )
+        .is_err());
+    }
 }
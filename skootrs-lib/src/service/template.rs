@@ -0,0 +1,307 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![allow(clippy::module_name_repetitions)]
+
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use skootrs_model::skootrs::{InitializedEcosystem, SkootError};
+
+/// The prefix of a template variable placeholder, e.g. `${skootrs.runs_on}`. This doesn't clash with askama's
+/// `{{ }}` or Github Actions' `${{ }}` expressions so it can be used in any template.
+const PLACEHOLDER_PREFIX: &str = "${skootrs.";
+
+/// The prefix of the environment variables that override configured template variables, e.g.
+/// `SKOOTRS_VAR_RUNS_ON` overrides `runs_on`.
+const ENV_PREFIX: &str = "SKOOTRS_VAR_";
+
+/// The template variables that are allowed to be injected into facet content along with their default values.
+/// Variables without a default have to be configured before a template that uses them can be rendered.
+const ALLOWED_TEMPLATE_VARIABLES: &[(&str, Option<&str>)] = &[
+    ("runs_on", Some("ubuntu-latest")),
+    ("registry_url", None),
+    ("security_contact_email", None),
+];
+
+/// The `TemplateVariableError` enum represents the ways injecting template variables can fail.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateVariableError {
+    /// The variable isn't in the allow list.
+    NotAllowed(String),
+    /// The variable is allowed but no value was configured for it and it has no default.
+    Unset(String),
+    /// The value could break out of the surrounding template, e.g. by adding new lines to a workflow.
+    UnsafeValue(String),
+    /// A placeholder was opened but never closed.
+    Unterminated,
+}
+
+impl fmt::Display for TemplateVariableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotAllowed(name) => write!(
+                f,
+                "Template variable {name} is not allowed, allowed variables are: {}",
+                ALLOWED_TEMPLATE_VARIABLES
+                    .iter()
+                    .map(|(name, _)| *name)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Self::Unset(name) => write!(
+                f,
+                "Template variable {name} has no value, set it in the config or with {ENV_PREFIX}{}",
+                name.to_uppercase()
+            ),
+            Self::UnsafeValue(name) => write!(
+                f,
+                "Template variable {name} contains new lines or template expressions"
+            ),
+            Self::Unterminated => write!(f, "Template variable placeholder is missing a closing }}"),
+        }
+    }
+}
+
+impl Error for TemplateVariableError {}
+
+/// The `TemplateVariables` struct holds the approved values that are injected into facet content,
+/// e.g. org specific runner labels for workflows.
+#[derive(Debug, Clone)]
+pub struct TemplateVariables {
+    values: HashMap<String, String>,
+}
+
+impl Default for TemplateVariables {
+    fn default() -> Self {
+        Self {
+            values: ALLOWED_TEMPLATE_VARIABLES
+                .iter()
+                .filter_map(|(name, default)| default.map(|d| ((*name).to_string(), d.to_string())))
+                .collect(),
+        }
+    }
+}
+
+impl TemplateVariables {
+    /// Creates the template variables from the configured values, which are in turn overridden by
+    /// `SKOOTRS_VAR_*` environment variables. Variables that aren't set fall back to their defaults.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a configured variable isn't allowed or its value is unsafe.
+    pub fn new(configured: &HashMap<String, String>) -> Result<Self, SkootError> {
+        let env = ALLOWED_TEMPLATE_VARIABLES.iter().filter_map(|(name, _)| {
+            std::env::var(format!("{ENV_PREFIX}{}", name.to_uppercase()))
+                .ok()
+                .map(|value| ((*name).to_string(), value))
+        });
+        Self::from_values(
+            configured
+                .iter()
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .chain(env),
+        )
+    }
+
+    fn from_values(values: impl IntoIterator<Item = (String, String)>) -> Result<Self, SkootError> {
+        let mut template_variables = Self::default();
+        for (name, value) in values {
+            if !ALLOWED_TEMPLATE_VARIABLES.iter().any(|(n, _)| *n == name) {
+                return Err(SkootError::other(TemplateVariableError::NotAllowed(name)));
+            }
+            if value.contains(['\n', '\r']) || value.contains("${") || value.contains("{{") {
+                return Err(SkootError::other(TemplateVariableError::UnsafeValue(name)));
+            }
+            template_variables.values.insert(name, value);
+        }
+        Ok(template_variables)
+    }
+
+    /// Replaces every `${skootrs.<name>}` placeholder in the content with the variable's value.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a placeholder refers to a variable that isn't allowed or doesn't have a value.
+    pub fn substitute(&self, content: &str) -> Result<String, SkootError> {
+        let mut substituted = String::with_capacity(content.len());
+        let mut rest = content;
+        while let Some(start) = rest.find(PLACEHOLDER_PREFIX) {
+            substituted.push_str(&rest[..start]);
+            let after_prefix = &rest[start + PLACEHOLDER_PREFIX.len()..];
+            let end = after_prefix
+                .find('}')
+                .ok_or_else(|| SkootError::template(TemplateVariableError::Unterminated))?;
+            let name = &after_prefix[..end];
+            if !ALLOWED_TEMPLATE_VARIABLES.iter().any(|(n, _)| *n == name) {
+                return Err(SkootError::template(TemplateVariableError::NotAllowed(
+                    name.to_string(),
+                )));
+            }
+            let value = self.values.get(name).ok_or_else(|| {
+                SkootError::template(TemplateVariableError::Unset(name.to_string()))
+            })?;
+            substituted.push_str(value);
+            rest = &after_prefix[end + 1..];
+        }
+        substituted.push_str(rest);
+        Ok(substituted)
+    }
+}
+
+/// The `TemplateOverrides` struct points at a directory of files that replace the built-in facet content,
+/// e.g. a company branded README. Overrides are laid out the same way as the generated files, so
+/// `<template_dir>/.github/workflows/releases.yml` replaces the generated release workflow. Overrides in an
+/// ecosystem directory like `<template_dir>/python/` take precedence over the ones for every ecosystem.
+/// Template variables are substituted in overrides like they are in the built-in content.
+#[derive(Debug, Clone, Default)]
+pub struct TemplateOverrides {
+    dir: Option<PathBuf>,
+}
+
+impl TemplateOverrides {
+    /// Creates the template overrides from the configured template directory. No content is overridden if
+    /// the directory isn't configured.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the configured directory doesn't exist.
+    pub fn new(template_dir: Option<&str>) -> Result<Self, SkootError> {
+        let dir = template_dir.map(PathBuf::from);
+        if let Some(dir) = &dir {
+            if !dir.is_dir() {
+                return Err(SkootError::Config(format!(
+                    "Template directory {} does not exist",
+                    dir.display()
+                )));
+            }
+        }
+        Ok(Self { dir })
+    }
+
+    /// Returns the content that overrides the generated file at `path`/`name`, or `None` if it isn't
+    /// overridden.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the override exists but can't be read.
+    pub fn find(
+        &self,
+        ecosystem: &InitializedEcosystem,
+        path: &str,
+        name: &str,
+    ) -> Result<Option<String>, SkootError> {
+        let Some(dir) = &self.dir else {
+            return Ok(None);
+        };
+        let relative_path = Path::new(path.trim_start_matches("./").trim_matches('/')).join(name);
+        for candidate in [
+            dir.join(ecosystem.name().to_lowercase())
+                .join(&relative_path),
+            dir.join(&relative_path),
+        ] {
+            if candidate.is_file() {
+                return Ok(Some(std::fs::read_to_string(candidate)?));
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_substitute() {
+        let template_variables = TemplateVariables::from_values([(
+            "registry_url".to_string(),
+            "ghcr.io/example".to_string(),
+        )])
+        .unwrap();
+        let content = template_variables
+            .substitute("runs-on: ${skootrs.runs_on}\nregistry: ${skootrs.registry_url}\nref: ${{ github.ref }}")
+            .unwrap();
+        assert_eq!(
+            content,
+            "runs-on: ubuntu-latest\nregistry: ghcr.io/example\nref: ${{ github.ref }}"
+        );
+    }
+
+    #[test]
+    fn test_substitute_errors() {
+        let template_variables = TemplateVariables::default();
+        let err = template_variables
+            .substitute("${skootrs.unknown}")
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<TemplateVariableError>(),
+            Some(&TemplateVariableError::NotAllowed("unknown".to_string()))
+        );
+        let err = template_variables
+            .substitute("${skootrs.registry_url}")
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<TemplateVariableError>(),
+            Some(&TemplateVariableError::Unset("registry_url".to_string()))
+        );
+        let err = TemplateVariables::from_values([(
+            "runs_on".to_string(),
+            "ubuntu-latest\n    steps: []".to_string(),
+        )])
+        .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<TemplateVariableError>(),
+            Some(&TemplateVariableError::UnsafeValue("runs_on".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_template_overrides() {
+        let temp_dir = tempdir::TempDir::new("test").unwrap();
+        let ecosystem = InitializedEcosystem::Python(skootrs_model::skootrs::InitializedPython {
+            name: "test".to_string(),
+            package_manager: skootrs_model::skootrs::PythonPackageManager::Pip,
+        });
+        std::fs::write(temp_dir.path().join("README.md"), "branded").unwrap();
+        let workflows = temp_dir.path().join("python/.github/workflows");
+        std::fs::create_dir_all(&workflows).unwrap();
+        std::fs::write(workflows.join("releases.yml"), "python release").unwrap();
+
+        let overrides = TemplateOverrides::new(temp_dir.path().to_str()).unwrap();
+        assert_eq!(
+            overrides.find(&ecosystem, "./", "README.md").unwrap(),
+            Some("branded".to_string())
+        );
+        assert_eq!(
+            overrides
+                .find(&ecosystem, ".github/workflows/", "releases.yml")
+                .unwrap(),
+            Some("python release".to_string())
+        );
+        assert_eq!(overrides.find(&ecosystem, "./", "LICENSE").unwrap(), None);
+        assert_eq!(
+            TemplateOverrides::default()
+                .find(&ecosystem, "./", "README.md")
+                .unwrap(),
+            None
+        );
+        assert!(TemplateOverrides::new(Some("/does/not/exist")).is_err());
+    }
+}
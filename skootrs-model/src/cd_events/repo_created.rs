@@ -23,6 +23,7 @@
 #![allow(clippy::return_self_not_must_use)]
 #![allow(clippy::default_trait_access)]
 #![allow(clippy::to_string_trait_impl)]
+#![allow(clippy::derivable_impls)]
 #![allow(missing_docs)]
 
 use serde::{Deserialize, Serialize};
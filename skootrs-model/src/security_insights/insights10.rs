@@ -22,6 +22,8 @@
 #![allow(clippy::default_trait_access)]
 #![allow(clippy::unwrap_used)]
 #![allow(missing_docs)]
+#![allow(clippy::used_underscore_items)]
+#![allow(clippy::struct_field_names)]
 
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
@@ -0,0 +1,142 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `attestation` module provides the in-toto attestations projects attach to their releases, e.g. the
+//! SBOMs and vulnerability reports policy engines consume, and the results of verifying them.
+
+#![allow(clippy::module_name_repetitions)]
+
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+#[cfg(feature = "openapi")]
+use utoipa::ToSchema;
+
+use super::{InitializedProject, ProjectReleaseParam};
+
+/// What an in-toto attestation attests to, which is given by the predicate type of its statement.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, EnumString, Display)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub enum PredicateType {
+    /// SLSA provenance of how the subjects were built, e.g. `https://slsa.dev/provenance/v1`.
+    #[default]
+    SLSAProvenance,
+    /// A SLSA verification summary of the subjects, e.g. `https://slsa.dev/verification_summary/v1`.
+    SLSAVerificationSummary,
+    /// An SPDX SBOM of the subjects, e.g. `https://spdx.dev/Document`.
+    SPDX,
+    /// A `CycloneDX` SBOM of the subjects, e.g. `https://cyclonedx.org/bom`.
+    CycloneDX,
+    /// The result of a vulnerability scan of the subjects, e.g.
+    /// `https://cosign.sigstore.dev/attestation/vuln/v1`.
+    Vulnerabilities,
+    /// An in-toto link of a step of the supply chain, e.g. `https://in-toto.io/attestation/link/v0.3`.
+    Link,
+    /// A predicate type Skootrs doesn't know, along with its URI.
+    Other(String),
+}
+
+impl PredicateType {
+    /// Returns the predicate type with the given URI. Every version of a known predicate is the same type.
+    #[must_use]
+    pub fn from_uri(uri: &str) -> Self {
+        let known = [
+            ("https://slsa.dev/provenance/", Self::SLSAProvenance),
+            (
+                "https://slsa.dev/verification_summary/",
+                Self::SLSAVerificationSummary,
+            ),
+            ("https://spdx.dev/Document", Self::SPDX),
+            ("https://cyclonedx.org/bom", Self::CycloneDX),
+            (
+                "https://cosign.sigstore.dev/attestation/vuln/",
+                Self::Vulnerabilities,
+            ),
+            ("https://in-toto.io/attestation/link/", Self::Link),
+        ];
+        known
+            .into_iter()
+            .find(|(prefix, _)| uri.starts_with(prefix))
+            .map_or_else(|| Self::Other(uri.to_string()), |(_, known)| known)
+    }
+}
+
+/// The parameters for verifying an in-toto attestation attached to a release of a project.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct AttestationVerifyParams {
+    /// The initialized project the attestation belongs to.
+    pub initialized_project: InitializedProject,
+    /// The name of the attestation's release asset, e.g. `skootrs.intoto.json`.
+    pub project_output: String,
+    /// The release the attestation is attached to.
+    pub release: ProjectReleaseParam,
+}
+
+/// The result of verifying an in-toto attestation against the identity of the project it belongs to.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct AttestationVerification {
+    /// The name of the attestation's release asset.
+    pub name: String,
+    /// The repo the attestation has to be signed by a workflow of.
+    pub identity: String,
+    /// Whether every envelope of the attestation verified. An attestation without envelopes doesn't verify.
+    pub verified: bool,
+    /// The DSSE envelopes of the attestation, along with whether each of them verified.
+    pub envelopes: Vec<VerifiedEnvelope>,
+}
+
+/// A DSSE envelope of an in-toto attestation.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct VerifiedEnvelope {
+    /// What the envelope's statement attests to.
+    pub predicate_type: PredicateType,
+    /// The names of the artifacts the statement is about.
+    pub subjects: Vec<String>,
+    /// Whether the envelope is signed by the project's identity and its subjects match the release.
+    pub verified: bool,
+    /// Why the envelope didn't verify, or `None` if it did.
+    pub reason: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_uri() {
+        assert_eq!(
+            PredicateType::from_uri("https://slsa.dev/provenance/v0.2"),
+            PredicateType::SLSAProvenance
+        );
+        assert_eq!(
+            PredicateType::from_uri("https://slsa.dev/provenance/v1"),
+            PredicateType::SLSAProvenance
+        );
+        assert_eq!(
+            PredicateType::from_uri("https://spdx.dev/Document/v2.3"),
+            PredicateType::SPDX
+        );
+        assert_eq!(
+            PredicateType::from_uri("https://cosign.sigstore.dev/attestation/vuln/v1"),
+            PredicateType::Vulnerabilities
+        );
+        assert_eq!(
+            PredicateType::from_uri("https://example.com/predicate/v1"),
+            PredicateType::Other("https://example.com/predicate/v1".to_string())
+        );
+    }
+}
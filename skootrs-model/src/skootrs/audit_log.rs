@@ -0,0 +1,185 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `audit_log` module provides the data model for the audit log, the append-only record of every change
+//! Skootrs makes to projects. Each entry carries the hash of the entry before it, so an entry that's edited,
+//! removed or reordered breaks the chain of hashes from there on.
+
+#![allow(clippy::module_name_repetitions)]
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+use utoipa::ToSchema;
+
+/// The `previous_hash` of the first entry of an audit log.
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Where the audit log is written and who the changes are recorded as made by.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(default)]
+pub struct AuditLogConfig {
+    /// The JSONL file entries are appended to. Nothing is logged if this isn't set.
+    pub path: Option<String>,
+    /// Who the CLI records changes as made by. Defaults to the `USER` environment variable. The daemon
+    /// records changes as made by the API key of the request instead.
+    pub actor: Option<String>,
+}
+
+/// A change Skootrs makes to a project.
+#[derive(
+    Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Display, EnumString,
+)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum AuditOperation {
+    /// A project was created, which creates its repo and applies its facets.
+    ProjectCreate,
+    /// A project's facets were brought up to date.
+    ProjectUpdate,
+    /// A facet was added to a project.
+    FacetAdd,
+    /// A facet was deleted from a project.
+    FacetDelete,
+    /// A mirror was linked to a project.
+    MirrorLink,
+    /// A project was recorded under the new URL of its renamed or transferred repo.
+    ProjectRelocate,
+    /// A project's repo was archived.
+    ProjectArchive,
+    /// A project's repo was deleted.
+    ProjectDelete,
+}
+
+/// Whether a change succeeded.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Display)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum AuditOutcome {
+    /// The change was made.
+    Succeeded,
+    /// The change failed, possibly partway through.
+    Failed,
+}
+
+/// An entry of the audit log, recording one change.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct AuditLogEntry {
+    /// The position of the entry in the log, starting from 1.
+    pub sequence: u64,
+    /// When the change finished.
+    pub timestamp: DateTime<Utc>,
+    /// Who made the change, e.g. `user:alice` or `api_key:ci`.
+    pub actor: String,
+    /// What the change was.
+    pub operation: AuditOperation,
+    /// The repo URL of the changed project. This is empty if a project failed to be created before its repo
+    /// URL was known.
+    pub project_url: String,
+    /// The parameters of the change, without the project's state.
+    #[cfg_attr(feature = "openapi", schema(value_type = Object))]
+    pub parameters: serde_json::Value,
+    /// Whether the change succeeded.
+    pub outcome: AuditOutcome,
+    /// What the change did, e.g. the facets it applied and the API calls they made, or the error it failed
+    /// with.
+    #[cfg_attr(feature = "openapi", schema(value_type = Object))]
+    pub result: serde_json::Value,
+    /// The hash of the entry before this one, or `GENESIS_HASH` for the first entry.
+    pub previous_hash: String,
+    /// The hex encoded SHA-256 hash of the entry with this field left empty.
+    pub hash: String,
+}
+
+/// Which entries of the audit log to list. Entries match if they match every filter that's set.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(default)]
+pub struct AuditLogFilter {
+    /// Only entries of changes made by this actor.
+    pub actor: Option<String>,
+    /// Only entries of this kind of change.
+    pub operation: Option<AuditOperation>,
+    /// Only entries of changes to the project with this repo URL.
+    pub project_url: Option<String>,
+    /// Only entries of changes that finished at or after this time.
+    pub since: Option<DateTime<Utc>>,
+}
+
+impl AuditLogFilter {
+    /// Returns true if the entry matches every filter that's set.
+    #[must_use]
+    pub fn matches(&self, entry: &AuditLogEntry) -> bool {
+        self.actor
+            .as_ref()
+            .is_none_or(|actor| *actor == entry.actor)
+            && self
+                .operation
+                .is_none_or(|operation| operation == entry.operation)
+            && self
+                .project_url
+                .as_ref()
+                .is_none_or(|project_url| *project_url == entry.project_url)
+            && self.since.is_none_or(|since| entry.timestamp >= since)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn test_filter_matches() {
+        let entry = AuditLogEntry {
+            sequence: 1,
+            timestamp: "2024-05-01T12:00:00Z".parse().unwrap(),
+            actor: "api_key:ci".to_string(),
+            operation: AuditOperation::ProjectArchive,
+            project_url: "https://github.com/kusaridev/skootrs".to_string(),
+            parameters: serde_json::json!({ "cleanup": true }),
+            outcome: AuditOutcome::Succeeded,
+            result: serde_json::Value::Null,
+            previous_hash: GENESIS_HASH.to_string(),
+            hash: String::new(),
+        };
+        assert!(AuditLogFilter::default().matches(&entry));
+        assert!(AuditLogFilter {
+            actor: Some("api_key:ci".to_string()),
+            operation: Some(AuditOperation::ProjectArchive),
+            since: Some("2024-05-01T00:00:00Z".parse().unwrap()),
+            ..Default::default()
+        }
+        .matches(&entry));
+        assert!(!AuditLogFilter {
+            operation: Some(AuditOperation::ProjectCreate),
+            ..Default::default()
+        }
+        .matches(&entry));
+        assert!(!AuditLogFilter {
+            since: Some("2024-05-02T00:00:00Z".parse().unwrap()),
+            ..Default::default()
+        }
+        .matches(&entry));
+        assert_eq!(
+            "facet_add".parse::<AuditOperation>().unwrap(),
+            AuditOperation::FacetAdd
+        );
+    }
+}
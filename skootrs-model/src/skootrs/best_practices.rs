@@ -0,0 +1,132 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `best_practices` module provides the data model for a project's `OpenSSF` Best Practices Badge, which
+//! the `BestPracticesBadge` facet records and `skootrs project audit` checks.
+
+#![allow(clippy::module_name_repetitions)]
+
+use serde::{Deserialize, Serialize};
+use strum::Display;
+#[cfg(feature = "openapi")]
+use utoipa::ToSchema;
+
+/// Where projects' `OpenSSF` Best Practices Badges are looked up.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(default)]
+pub struct BestPracticesConfig {
+    /// The URL of the `OpenSSF` Best Practices Badge site, e.g. `https://www.bestpractices.dev`. Its API only
+    /// has the projects that a maintainer registered on the site.
+    pub api_url: String,
+}
+
+impl Default for BestPracticesConfig {
+    fn default() -> Self {
+        Self {
+            api_url: "https://www.bestpractices.dev".to_string(),
+        }
+    }
+}
+
+impl BestPracticesConfig {
+    /// Returns where maintainers register a project for a badge. Registering needs a maintainer to log in,
+    /// so Skootrs can't do it on their behalf.
+    #[must_use]
+    pub fn registration_url(&self) -> String {
+        format!("{}/en/projects/new", self.api_url.trim_end_matches('/'))
+    }
+
+    /// Returns the page of a project's badge.
+    #[must_use]
+    pub fn project_url(&self, id: u64) -> String {
+        format!("{}/projects/{id}", self.api_url.trim_end_matches('/'))
+    }
+
+    /// Returns the image of a project's badge, which always shows the project's current badge level.
+    #[must_use]
+    pub fn badge_url(&self, id: u64) -> String {
+        format!("{}/badge", self.project_url(id))
+    }
+}
+
+/// The level of an `OpenSSF` Best Practices Badge.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Display)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum BadgeLevel {
+    /// The project is registered but doesn't meet the passing criteria yet.
+    InProgress,
+    /// The project meets the passing criteria.
+    Passing,
+    /// The project meets the silver criteria.
+    Silver,
+    /// The project meets the gold criteria.
+    Gold,
+}
+
+impl BadgeLevel {
+    /// Returns true if the project has earned a badge, i.e. it at least meets the passing criteria.
+    #[must_use]
+    pub const fn is_earned(self) -> bool {
+        !matches!(self, Self::InProgress)
+    }
+}
+
+/// A project's `OpenSSF` Best Practices Badge, as returned by the badge API.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct BestPracticesBadge {
+    /// The ID of the project on the badge site.
+    pub id: u64,
+    /// The level of the project's badge.
+    pub badge_level: BadgeLevel,
+    /// How much of the passing criteria the project meets, in percent.
+    #[serde(rename = "badge_percentage_0", default)]
+    pub passing_percentage: u8,
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn test_deserialize_badge() {
+        let badges: Vec<BestPracticesBadge> = serde_json::from_str(
+            r#"[{
+                "id": 8490,
+                "name": "skootrs",
+                "repo_url": "https://github.com/kusaridev/skootrs",
+                "badge_level": "in_progress",
+                "badge_percentage_0": 87,
+                "badge_percentage_1": 12
+            }]"#,
+        )
+        .unwrap();
+        assert_eq!(badges[0].id, 8490);
+        assert_eq!(badges[0].badge_level, BadgeLevel::InProgress);
+        assert!(!badges[0].badge_level.is_earned());
+        assert_eq!(badges[0].passing_percentage, 87);
+        assert_eq!(BadgeLevel::Passing.to_string(), "passing");
+
+        let config = BestPracticesConfig::default();
+        assert_eq!(
+            config.badge_url(8490),
+            "https://www.bestpractices.dev/projects/8490/badge"
+        );
+    }
+}
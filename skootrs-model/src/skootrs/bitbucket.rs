@@ -0,0 +1,165 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `bitbucket` module provides the data model for projects hosted on Bitbucket Cloud: how Skootrs
+//! authenticates to its API, and the branch restrictions that are Bitbucket's analog of Github's branch
+//! protection.
+
+#![allow(clippy::module_name_repetitions)]
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "openapi")]
+use utoipa::ToSchema;
+
+use super::branch_protection::BranchProtectionConfig;
+
+/// How Skootrs reaches the Bitbucket Cloud API.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(default)]
+pub struct BitbucketConfig {
+    /// The URL of the Bitbucket API, e.g. `https://api.bitbucket.org/2.0`.
+    pub api_url: String,
+    /// The Bitbucket username the token is an app password of. Without it the token is sent as a bearer
+    /// token, which is how repository, project and workspace access tokens are used.
+    pub username: Option<String>,
+    /// The app password or access token Skootrs uses, usually a reference like `${BITBUCKET_TOKEN}`. Falls
+    /// back to the `BITBUCKET_TOKEN` environment variable.
+    pub token: Option<String>,
+}
+
+impl Default for BitbucketConfig {
+    fn default() -> Self {
+        Self {
+            api_url: "https://api.bitbucket.org/2.0".to_string(),
+            username: None,
+            token: None,
+        }
+    }
+}
+
+/// A branch restriction as taken by Bitbucket's branch restrictions API. Each rule of a
+/// `BranchProtectionConfig` is a separate restriction on Bitbucket.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct BranchRestriction {
+    /// The kind of the restriction, e.g. `force` to prevent force pushes.
+    pub kind: String,
+    /// How the branches are matched. Skootrs always matches them by `glob`.
+    pub branch_match_kind: String,
+    /// The glob of the branches the restriction applies to.
+    pub pattern: String,
+    /// The number the restriction takes, e.g. how many approvals are needed to merge.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<u8>,
+}
+
+impl BranchRestriction {
+    fn new(kind: &str, branch: &str, value: Option<u8>) -> Self {
+        Self {
+            kind: kind.to_string(),
+            branch_match_kind: "glob".to_string(),
+            pattern: branch.to_string(),
+            value,
+        }
+    }
+}
+
+/// Returns the Bitbucket branch restrictions that enforce the rules on a branch. Linear history, signed
+/// commits and code owner reviews don't have a Bitbucket restriction, so they aren't enforced, and the
+/// restrictions always apply to admins too.
+#[must_use]
+pub fn branch_restrictions(rules: &BranchProtectionConfig, branch: &str) -> Vec<BranchRestriction> {
+    let mut restrictions = vec![];
+    if !rules.allow_force_pushes {
+        restrictions.push(BranchRestriction::new("force", branch, None));
+    }
+    if !rules.allow_deletions {
+        restrictions.push(BranchRestriction::new("delete", branch, None));
+    }
+    if rules.require_pull_requests {
+        // A push restriction without any users or groups lets nobody push, so changes can only be merged.
+        restrictions.push(BranchRestriction::new("push", branch, None));
+        restrictions.push(BranchRestriction::new(
+            "require_approvals_to_merge",
+            branch,
+            Some(rules.required_approving_reviews),
+        ));
+        if rules.dismiss_stale_reviews {
+            restrictions.push(BranchRestriction::new(
+                "reset_pullrequest_approvals_on_change",
+                branch,
+                None,
+            ));
+        }
+    }
+    if !rules.required_status_checks.is_empty() {
+        restrictions.push(BranchRestriction::new(
+            "require_passing_builds_to_merge",
+            branch,
+            Some(1),
+        ));
+        if rules.strict_status_checks {
+            restrictions.push(BranchRestriction::new(
+                "require_commits_behind",
+                branch,
+                Some(0),
+            ));
+        }
+    }
+    restrictions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_branch_restrictions() {
+        let kinds = |rules: &BranchProtectionConfig| {
+            branch_restrictions(rules, "main")
+                .into_iter()
+                .map(|r| (r.kind, r.value))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            kinds(&BranchProtectionConfig::default()),
+            vec![("force".to_string(), None), ("delete".to_string(), None)]
+        );
+
+        let rules = BranchProtectionConfig {
+            require_pull_requests: true,
+            required_approving_reviews: 2,
+            dismiss_stale_reviews: true,
+            required_status_checks: vec!["build".to_string()],
+            allow_deletions: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            kinds(&rules),
+            vec![
+                ("force".to_string(), None),
+                ("push".to_string(), None),
+                ("require_approvals_to_merge".to_string(), Some(2)),
+                ("reset_pullrequest_approvals_on_change".to_string(), None),
+                ("require_passing_builds_to_merge".to_string(), Some(1)),
+            ]
+        );
+        assert!(branch_restrictions(&rules, "release/*")
+            .iter()
+            .all(|r| r.pattern == "release/*" && r.branch_match_kind == "glob"));
+    }
+}
@@ -0,0 +1,227 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `branch_protection` module provides the rules the `BranchProtection` facet enables on a project's
+//! branches, along with the typed request Github's branch protection API takes. The rules come from the
+//! config and can be overridden for a single project with the options the facet is added with.
+
+#![allow(clippy::module_name_repetitions)]
+
+use std::collections::BTreeMap;
+
+use schemars::{schema::SchemaObject, schema_for, JsonSchema};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+
+use super::SkootError;
+
+/// The branch protection rules the `BranchProtection` facet enables.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(default)]
+#[allow(clippy::struct_excessive_bools)] // Each rule is a toggle in Github's API
+pub struct BranchProtectionConfig {
+    /// The branches that are protected.
+    pub branches: Vec<String>,
+    /// Whether changes have to go through a pull request.
+    pub require_pull_requests: bool,
+    /// How many approving reviews a pull request needs before it can be merged.
+    pub required_approving_reviews: u8,
+    /// Whether approvals are dismissed when new commits are pushed to the pull request.
+    pub dismiss_stale_reviews: bool,
+    /// Whether a pull request needs an approval from a code owner of the files it changes.
+    pub require_code_owner_reviews: bool,
+    /// The status checks that have to pass before a change can be merged, e.g. `build`.
+    pub required_status_checks: Vec<String>,
+    /// Whether a branch has to be up to date with the protected branch before it can be merged.
+    pub strict_status_checks: bool,
+    /// Whether merge commits are refused.
+    pub required_linear_history: bool,
+    /// Whether commits have to be signed.
+    pub required_signatures: bool,
+    /// Whether the rules apply to admins too.
+    pub enforce_admins: bool,
+    /// Whether force pushes are allowed.
+    pub allow_force_pushes: bool,
+    /// Whether the protected branches can be deleted.
+    pub allow_deletions: bool,
+}
+
+impl Default for BranchProtectionConfig {
+    fn default() -> Self {
+        Self {
+            branches: vec!["main".to_string()],
+            require_pull_requests: false,
+            required_approving_reviews: 1,
+            dismiss_stale_reviews: false,
+            require_code_owner_reviews: false,
+            required_status_checks: vec![],
+            strict_status_checks: false,
+            required_linear_history: true,
+            required_signatures: false,
+            enforce_admins: true,
+            allow_force_pushes: false,
+            allow_deletions: false,
+        }
+    }
+}
+
+impl BranchProtectionConfig {
+    /// Returns the rules with the options a facet was added with applied on top, e.g. a project that needs
+    /// two approving reviews instead of the configured one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an option isn't a rule or has the wrong type.
+    pub fn with_options(&self, options: &BTreeMap<String, Value>) -> Result<Self, SkootError> {
+        super::registry::with_options(self, options)
+    }
+
+    /// Returns the options schema of the `BranchProtection` facet, which lets each rule be overridden when
+    /// the facet is added.
+    #[must_use]
+    pub fn options_schema() -> SchemaObject {
+        schema_for!(Self).schema
+    }
+
+    /// Returns the request that protects a branch with these rules.
+    #[must_use]
+    pub fn request(&self) -> BranchProtectionRequest {
+        BranchProtectionRequest {
+            required_status_checks: (!self.required_status_checks.is_empty()).then(|| {
+                RequiredStatusChecks {
+                    strict: self.strict_status_checks,
+                    contexts: self.required_status_checks.clone(),
+                }
+            }),
+            enforce_admins: self.enforce_admins,
+            required_pull_request_reviews: self.require_pull_requests.then_some(
+                RequiredPullRequestReviews {
+                    dismiss_stale_reviews: self.dismiss_stale_reviews,
+                    require_code_owner_reviews: self.require_code_owner_reviews,
+                    required_approving_review_count: self.required_approving_reviews,
+                },
+            ),
+            restrictions: None,
+            required_linear_history: self.required_linear_history,
+            allow_force_pushes: self.allow_force_pushes,
+            allow_deletions: self.allow_deletions,
+        }
+    }
+}
+
+/// The body of Github's update branch protection API call. Signed commits are required with a separate
+/// call, so they aren't part of it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[allow(clippy::struct_excessive_bools)] // Clippy doesn't like the Github API
+pub struct BranchProtectionRequest {
+    /// The status checks that have to pass, or `None` to not require any.
+    pub required_status_checks: Option<RequiredStatusChecks>,
+    /// Whether the rules apply to admins too.
+    pub enforce_admins: bool,
+    /// The reviews a pull request needs, or `None` to not require pull requests.
+    pub required_pull_request_reviews: Option<RequiredPullRequestReviews>,
+    /// Who can push to the branch. Restricting pushes isn't supported, so this is always `None`, which
+    /// Github requires to be sent as `null`.
+    pub restrictions: Option<Value>,
+    /// Whether merge commits are refused.
+    pub required_linear_history: bool,
+    /// Whether force pushes are allowed.
+    pub allow_force_pushes: bool,
+    /// Whether the branch can be deleted.
+    pub allow_deletions: bool,
+}
+
+/// The status checks that have to pass before a change can be merged.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct RequiredStatusChecks {
+    /// Whether a branch has to be up to date with the protected branch before it can be merged.
+    pub strict: bool,
+    /// The names of the status checks.
+    pub contexts: Vec<String>,
+}
+
+/// The reviews a pull request needs before it can be merged.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct RequiredPullRequestReviews {
+    /// Whether approvals are dismissed when new commits are pushed.
+    pub dismiss_stale_reviews: bool,
+    /// Whether an approval from a code owner is needed.
+    pub require_code_owner_reviews: bool,
+    /// How many approving reviews are needed.
+    pub required_approving_review_count: u8,
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn test_branch_protection_request() {
+        // The defaults match the rules Skootrs has always enabled.
+        assert_eq!(
+            serde_json::to_value(BranchProtectionConfig::default().request()).unwrap(),
+            serde_json::json!({
+                "enforce_admins": true,
+                "required_pull_request_reviews": null,
+                "required_status_checks": null,
+                "restrictions": null,
+                "required_linear_history": true,
+                "allow_force_pushes": false,
+                "allow_deletions": false,
+            })
+        );
+
+        let config = BranchProtectionConfig::default()
+            .with_options(&BTreeMap::from([
+                ("require_pull_requests".to_string(), Value::from(true)),
+                ("required_approving_reviews".to_string(), Value::from(2)),
+                (
+                    "required_status_checks".to_string(),
+                    Value::from(vec!["build"]),
+                ),
+            ]))
+            .unwrap();
+        let request = config.request();
+        assert_eq!(
+            request.required_pull_request_reviews,
+            Some(RequiredPullRequestReviews {
+                dismiss_stale_reviews: false,
+                require_code_owner_reviews: false,
+                required_approving_review_count: 2,
+            })
+        );
+        assert_eq!(
+            request.required_status_checks,
+            Some(RequiredStatusChecks {
+                strict: false,
+                contexts: vec!["build".to_string()],
+            })
+        );
+        assert_eq!(config.branches, vec!["main".to_string()]);
+
+        assert!(BranchProtectionConfig::default()
+            .with_options(&BTreeMap::from([(
+                "required_approving_reviews".to_string(),
+                Value::from("two"),
+            )]))
+            .is_err());
+    }
+}
@@ -0,0 +1,112 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `change_request` module provides how changes to the facets of an existing project reach its repo.
+//! Once branch protection is enabled, pushing to the default branch is refused, so changes are proposed as
+//! pull requests by default.
+
+#![allow(clippy::module_name_repetitions)]
+
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+use utoipa::ToSchema;
+
+/// How changes to the facets of an existing project reach its repo. New projects are always pushed to
+/// directly, since branch protection is only enabled once their initial commit has landed.
+#[derive(
+    Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, Display, EnumString,
+)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum ChangeMode {
+    /// The changes are committed to a branch of their own and a pull request is opened for them. Repos on
+    /// generic git remotes are pushed to directly, since Skootrs can't open pull requests on them.
+    #[default]
+    PullRequest,
+    /// The changes are pushed to the default branch directly.
+    Push,
+}
+
+/// How changes to the facets of existing projects are proposed.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(default)]
+pub struct ChangeRequestConfig {
+    /// How the changes reach the repo.
+    pub mode: ChangeMode,
+    /// The prefix of the branches pull requests are opened from, e.g. `skootrs/`.
+    pub branch_prefix: String,
+    /// Whether auto-merge is enabled on the pull requests, so they're merged once the required checks and
+    /// reviews pass. The repo has to allow auto-merge.
+    pub auto_merge: bool,
+}
+
+impl Default for ChangeRequestConfig {
+    fn default() -> Self {
+        Self {
+            mode: ChangeMode::PullRequest,
+            branch_prefix: "skootrs/".to_string(),
+            auto_merge: false,
+        }
+    }
+}
+
+impl ChangeRequestConfig {
+    /// Returns the name of the branch a change is proposed from, e.g. `skootrs/update-facets-1712345678`.
+    #[must_use]
+    pub fn branch_name(&self, change: &str, timestamp: i64) -> String {
+        format!("{}{change}-{timestamp}", self.branch_prefix)
+    }
+}
+
+/// The parameters for opening a pull request that proposes a change to a project.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct PullRequestParams {
+    /// The branch with the change.
+    pub head: String,
+    /// The branch the change is proposed to, usually the default branch.
+    pub base: String,
+    /// The title of the pull request.
+    pub title: String,
+    /// The description of the pull request.
+    pub body: String,
+    /// Whether auto-merge is enabled on the pull request.
+    pub auto_merge: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn test_change_request_config() {
+        let config: ChangeRequestConfig = serde_json::from_str("{}").unwrap();
+        assert_eq!(config, ChangeRequestConfig::default());
+        assert_eq!(config.mode, ChangeMode::PullRequest);
+        assert_eq!(
+            config.branch_name("update-facets", 1_712_345_678),
+            "skootrs/update-facets-1712345678"
+        );
+
+        let config: ChangeRequestConfig =
+            serde_json::from_str(r#"{"mode": "push", "auto_merge": true}"#).unwrap();
+        assert_eq!(config.mode, ChangeMode::Push);
+        assert!(config.auto_merge);
+        assert_eq!(config.branch_prefix, "skootrs/");
+    }
+}
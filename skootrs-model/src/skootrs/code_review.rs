@@ -0,0 +1,168 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `code_review` module provides who owns a project's code and how many approvals the `CodeReview` facet
+//! requires before a pull request can be merged. The facet writes the owners to a CODEOWNERS file and
+//! requires the reviews on the project's branches.
+
+#![allow(clippy::module_name_repetitions)]
+
+use std::collections::BTreeMap;
+
+use schemars::{schema::SchemaObject, schema_for, JsonSchema};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+#[cfg(feature = "openapi")]
+use utoipa::ToSchema;
+
+use super::{branch_protection::RequiredPullRequestReviews, InitializedGithubRepo, SkootError};
+
+/// The most approving reviews Github lets a branch require.
+const MAX_APPROVING_REVIEWS: u8 = 6;
+
+/// Who reviews a project's changes and how many approvals the `CodeReview` facet requires.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(default)]
+pub struct CodeReviewConfig {
+    /// The owners of every file in the project, e.g. `@org/maintainers` or `@user`. The owner of the repo is
+    /// used if this is empty.
+    pub code_owners: Vec<String>,
+    /// The branches that require the reviews.
+    pub branches: Vec<String>,
+    /// How many approving reviews a pull request needs before it can be merged, from 1 to 6.
+    pub required_approving_reviews: u8,
+    /// Whether a pull request needs an approval from a code owner of the files it changes.
+    pub require_code_owner_reviews: bool,
+    /// Whether approvals are dismissed when new commits are pushed to the pull request.
+    pub dismiss_stale_reviews: bool,
+}
+
+impl Default for CodeReviewConfig {
+    fn default() -> Self {
+        Self {
+            code_owners: vec![],
+            branches: vec!["main".to_string()],
+            required_approving_reviews: 1,
+            require_code_owner_reviews: true,
+            dismiss_stale_reviews: false,
+        }
+    }
+}
+
+impl CodeReviewConfig {
+    /// Returns the config with the options a facet was added with applied on top, e.g. a project that needs
+    /// two approvals from its own team.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an option has the wrong type, if the approver count is out of range, or if a code
+    /// owner isn't a valid owner.
+    pub fn with_options(&self, options: &BTreeMap<String, Value>) -> Result<Self, SkootError> {
+        let config: Self = super::registry::with_options(self, options)?;
+        if !(1..=MAX_APPROVING_REVIEWS).contains(&config.required_approving_reviews) {
+            return Err(format!(
+                "required_approving_reviews has to be between 1 and {MAX_APPROVING_REVIEWS}, not {}",
+                config.required_approving_reviews
+            )
+            .into());
+        }
+        // Owners are written into CODEOWNERS line by line, so whitespace would break the file.
+        if let Some(owner) = config
+            .code_owners
+            .iter()
+            .find(|owner| !owner.contains('@') || owner.chars().any(char::is_whitespace))
+        {
+            return Err(format!(
+                "{owner} is not a valid code owner, it has to be a @user, an @org/team or an email"
+            )
+            .into());
+        }
+        Ok(config)
+    }
+
+    /// Returns the options schema of the `CodeReview` facet, which lets each part of the config be overridden
+    /// when the facet is added.
+    #[must_use]
+    pub fn options_schema() -> SchemaObject {
+        schema_for!(Self).schema
+    }
+
+    /// Returns the owners written to the project's CODEOWNERS file.
+    #[must_use]
+    pub fn code_owners(&self, repo: &InitializedGithubRepo) -> Vec<String> {
+        if self.code_owners.is_empty() {
+            vec![format!("@{}", repo.organization.get_name())]
+        } else {
+            self.code_owners.clone()
+        }
+    }
+
+    /// Returns the request that requires the reviews on a protected branch.
+    #[must_use]
+    pub const fn request(&self) -> RequiredPullRequestReviews {
+        RequiredPullRequestReviews {
+            dismiss_stale_reviews: self.dismiss_stale_reviews,
+            require_code_owner_reviews: self.require_code_owner_reviews,
+            required_approving_review_count: self.required_approving_reviews,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use crate::skootrs::GithubUser;
+
+    #[test]
+    fn test_code_review_config() {
+        let repo = InitializedGithubRepo {
+            name: "test".to_string(),
+            organization: GithubUser::Organization("testorg".to_string()),
+        };
+        let config = CodeReviewConfig::default();
+        assert_eq!(config.code_owners(&repo), vec!["@testorg".to_string()]);
+        assert_eq!(config.request().required_approving_review_count, 1);
+
+        let config = config
+            .with_options(&BTreeMap::from([
+                (
+                    "code_owners".to_string(),
+                    Value::from(vec!["@testorg/maintainers"]),
+                ),
+                ("required_approving_reviews".to_string(), Value::from(2)),
+            ]))
+            .unwrap();
+        assert_eq!(
+            config.code_owners(&repo),
+            vec!["@testorg/maintainers".to_string()]
+        );
+        assert_eq!(config.request().required_approving_review_count, 2);
+
+        assert!(config
+            .with_options(&BTreeMap::from([(
+                "required_approving_reviews".to_string(),
+                Value::from(7),
+            )]))
+            .is_err());
+        assert!(config
+            .with_options(&BTreeMap::from([(
+                "code_owners".to_string(),
+                Value::from(vec!["@testorg\n* @attacker"]),
+            )]))
+            .is_err());
+    }
+}
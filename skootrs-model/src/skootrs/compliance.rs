@@ -0,0 +1,593 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `compliance` module provides the data model for a project's compliance report, which maps the
+//! controls of the S2C2F, SLSA and `OpenSSF` Scorecard to the facets implementing them along with whether
+//! their audit found them in place. The report is rendered as an OSCAL component definition or as Markdown
+//! so it can be handed to auditors as is.
+
+#![allow(clippy::module_name_repetitions)]
+
+use std::fmt::Write;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use strum::{Display, EnumString, VariantNames};
+#[cfg(feature = "openapi")]
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::{
+    facet::{FacetAuditStatus, ProjectAudit, SupportedFacetType},
+    label::Label,
+    InitializedProject,
+};
+
+/// The version of OSCAL the component definitions are written in.
+pub const OSCAL_VERSION: &str = "1.1.2";
+
+/// The namespace of the OSCAL properties that are specific to Skootrs.
+const OSCAL_NAMESPACE: &str = "https://github.com/kusaridev/skootrs/ns/oscal";
+
+/// The document a compliance report is rendered as.
+#[derive(
+    Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, EnumString, VariantNames, Display,
+)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum ComplianceFormat {
+    /// An OSCAL component definition in JSON, with the project as its component.
+    Oscal,
+    /// A Markdown document with a table of controls per framework.
+    Markdown,
+}
+
+/// A framework of security controls that facets are labeled with.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Display)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub enum ControlFramework {
+    /// The Secure Supply Chain Consumption Framework.
+    S2C2F,
+    /// Supply-chain Levels for Software Artifacts.
+    SLSA,
+    /// The checks of the `OpenSSF` Scorecard.
+    Scorecard,
+}
+
+impl ControlFramework {
+    /// Returns where the framework's controls are defined, which is the source of OSCAL control
+    /// implementations.
+    #[must_use]
+    pub const fn source(self) -> &'static str {
+        match self {
+            Self::S2C2F => "https://github.com/ossf/s2c2f",
+            Self::SLSA => "https://slsa.dev/spec/v1.0/levels",
+            Self::Scorecard => "https://github.com/ossf/scorecard/blob/main/docs/checks.md",
+        }
+    }
+}
+
+/// How much of a control a project implements.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Display)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub enum ControlImplementationStatus {
+    /// The project has facets implementing the control, and each of them is compliant.
+    Implemented,
+    /// The project has facets implementing the control, but some of them drifted, went missing or couldn't
+    /// be verified.
+    Partial,
+    /// The project has no facet implementing the control.
+    NotImplemented,
+}
+
+impl ControlImplementationStatus {
+    /// Returns the OSCAL `implementation-status` the status corresponds to.
+    #[must_use]
+    pub const fn oscal(self) -> &'static str {
+        match self {
+            Self::Implemented => "implemented",
+            Self::Partial => "partial",
+            Self::NotImplemented => "planned",
+        }
+    }
+}
+
+/// A facet implementing a control.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct ControlFacet {
+    /// The type of the facet.
+    pub facet_type: SupportedFacetType,
+    /// The status the facet's audit found it in, or `None` if it wasn't audited.
+    pub status: Option<FacetAuditStatus>,
+}
+
+/// A control along with the facets of a project that implement it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct ControlCompliance {
+    /// The framework the control is part of.
+    pub framework: ControlFramework,
+    /// The ID of the control in its framework, e.g. `SCA-1` or `Branch-Protection`.
+    pub control_id: String,
+    /// What the control requires.
+    pub title: String,
+    /// The facets of the project labeled with the control.
+    pub facets: Vec<ControlFacet>,
+    /// How much of the control the project implements.
+    pub status: ControlImplementationStatus,
+}
+
+/// A project's compliance with the controls its facets can implement.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct ComplianceReport {
+    /// The name of the project.
+    pub project_name: String,
+    /// The URL of the project's repo.
+    pub project_url: String,
+    /// When the report was generated.
+    pub generated_at: DateTime<Utc>,
+    /// Every control facets can implement, in the order of their frameworks, whether the project
+    /// implements it or not.
+    pub controls: Vec<ControlCompliance>,
+}
+
+/// The controls facets are labeled with, along with their framework, ID and title.
+const CONTROLS: &[(Label, ControlFramework, &str, &str)] = &[
+    (
+        Label::S2C2FSCA1,
+        ControlFramework::S2C2F,
+        "SCA-1",
+        "Scan OSS for known vulnerabilities",
+    ),
+    (
+        Label::S2C2FUPD2,
+        ControlFramework::S2C2F,
+        "UPD-2",
+        "Enable automated OSS updates",
+    ),
+    (
+        Label::S2C2FAUD1,
+        ControlFramework::S2C2F,
+        "AUD-1",
+        "Verify the provenance of your OSS",
+    ),
+    (
+        Label::S2C2FAUD4,
+        ControlFramework::S2C2F,
+        "AUD-4",
+        "Validate SBOMs of OSS that you consume into your build",
+    ),
+    (
+        Label::SLSABuildLevel1,
+        ControlFramework::SLSA,
+        "Build-L1",
+        "Provenance exists",
+    ),
+    (
+        Label::SLSABuildLevel2,
+        ControlFramework::SLSA,
+        "Build-L2",
+        "Hosted build platform",
+    ),
+    (
+        Label::SLSABuildLevel3,
+        ControlFramework::SLSA,
+        "Build-L3",
+        "Hardened builds",
+    ),
+    (
+        Label::ScorecardBranchProtection,
+        ControlFramework::Scorecard,
+        "Branch-Protection",
+        "The project's default and release branches are protected",
+    ),
+    (
+        Label::ScorecardCIIBestPractices,
+        ControlFramework::Scorecard,
+        "CII-Best-Practices",
+        "The project has an OpenSSF Best Practices Badge",
+    ),
+    (
+        Label::ScorecardCodeReview,
+        ControlFramework::Scorecard,
+        "Code-Review",
+        "Changes are reviewed before they're merged",
+    ),
+    (
+        Label::ScorecardDependencyUpdateTool,
+        ControlFramework::Scorecard,
+        "Dependency-Update-Tool",
+        "The project uses a dependency update tool",
+    ),
+    (
+        Label::ScorecardFuzzing,
+        ControlFramework::Scorecard,
+        "Fuzzing",
+        "The project is fuzzed",
+    ),
+    (
+        Label::ScorecardLicense,
+        ControlFramework::Scorecard,
+        "License",
+        "The project declares a license",
+    ),
+    (
+        Label::ScorecardPackaging,
+        ControlFramework::Scorecard,
+        "Packaging",
+        "The project is published as a package",
+    ),
+    (
+        Label::ScorecardPinnedDependencies,
+        ControlFramework::Scorecard,
+        "Pinned-Dependencies",
+        "The project pins its dependencies",
+    ),
+    (
+        Label::ScorecardSAST,
+        ControlFramework::Scorecard,
+        "SAST",
+        "The project uses static analysis",
+    ),
+    (
+        Label::ScorecardSecurityPolicy,
+        ControlFramework::Scorecard,
+        "Security-Policy",
+        "The project has a security policy",
+    ),
+    (
+        Label::ScorecardSignedReleases,
+        ControlFramework::Scorecard,
+        "Signed-Releases",
+        "The project signs its releases",
+    ),
+];
+
+impl ComplianceReport {
+    /// Builds the compliance report of a project from the labels of its facets and their audit.
+    #[must_use]
+    pub fn new(
+        project: &InitializedProject,
+        audit: &ProjectAudit,
+        generated_at: DateTime<Utc>,
+    ) -> Self {
+        let controls = CONTROLS
+            .iter()
+            .map(|(label, framework, control_id, title)| {
+                let mut facets = project
+                    .facets
+                    .values()
+                    .filter(|facet| facet.labels().contains(label))
+                    .map(|facet| {
+                        let facet_type = facet.facet_type();
+                        ControlFacet {
+                            status: audit
+                                .facets
+                                .iter()
+                                .find(|f| f.facet_type == facet_type)
+                                .map(|f| f.status.clone()),
+                            facet_type,
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                facets.sort_by_key(|facet| facet.facet_type.to_string());
+                let status = if facets.is_empty() {
+                    ControlImplementationStatus::NotImplemented
+                } else if facets
+                    .iter()
+                    .all(|facet| facet.status == Some(FacetAuditStatus::Compliant))
+                {
+                    ControlImplementationStatus::Implemented
+                } else {
+                    ControlImplementationStatus::Partial
+                };
+                ControlCompliance {
+                    framework: *framework,
+                    control_id: (*control_id).to_string(),
+                    title: (*title).to_string(),
+                    facets,
+                    status,
+                }
+            })
+            .collect();
+        Self {
+            project_name: project.name.clone(),
+            project_url: project.repo.full_url(),
+            generated_at,
+            controls,
+        }
+    }
+
+    /// Renders the report as an OSCAL component definition with the project as its only component. Only
+    /// the controls the project has facets for are implemented requirements. The UUIDs are derived from the
+    /// project's URL, so the ones of the component and its requirements are the same across reports.
+    #[must_use]
+    pub fn to_oscal(&self) -> Value {
+        let uuid = |name: &str| Uuid::new_v5(&Uuid::NAMESPACE_URL, name.as_bytes()).to_string();
+        let generated_at = self.generated_at.to_rfc3339_opts(SecondsFormat::Secs, true);
+        let mut implementations = Vec::new();
+        for framework in [
+            ControlFramework::S2C2F,
+            ControlFramework::SLSA,
+            ControlFramework::Scorecard,
+        ] {
+            let requirements = self
+                .controls
+                .iter()
+                .filter(|control| {
+                    control.framework == framework
+                        && control.status != ControlImplementationStatus::NotImplemented
+                })
+                .map(|control| {
+                    json!({
+                        "uuid": uuid(&format!("{}#{framework}/{}", self.project_url, control.control_id)),
+                        "control-id": control.control_id.to_lowercase(),
+                        "description": format!("{}. Implemented by {}.", control.title, facet_list(control)),
+                        "props": std::iter::once(json!({
+                            "name": "implementation-status",
+                            "value": control.status.oscal(),
+                        }))
+                        .chain(control.facets.iter().map(|facet| json!({
+                            "name": "facet",
+                            "ns": OSCAL_NAMESPACE,
+                            "value": facet.facet_type.to_string(),
+                        })))
+                        .collect::<Vec<_>>(),
+                    })
+                })
+                .collect::<Vec<_>>();
+            if requirements.is_empty() {
+                continue;
+            }
+            implementations.push(json!({
+                "uuid": uuid(&format!("{}#{framework}", self.project_url)),
+                "source": framework.source(),
+                "description": format!("The {framework} controls implemented by the project's Skootrs facets."),
+                "implemented-requirements": requirements,
+            }));
+        }
+        let mut component = json!({
+            "uuid": uuid(&self.project_url),
+            "type": "software",
+            "title": self.project_name,
+            "description": format!("The project in {}, managed by Skootrs.", self.project_url),
+            "links": [{"href": self.project_url, "rel": "homepage"}],
+        });
+        // OSCAL doesn't allow empty arrays, so a project without any controls has no implementations.
+        if !implementations.is_empty() {
+            component["control-implementations"] = Value::Array(implementations);
+        }
+        json!({
+            "component-definition": {
+                "uuid": uuid(&format!("{}@{generated_at}", self.project_url)),
+                "metadata": {
+                    "title": format!("Compliance of {}", self.project_name),
+                    "last-modified": generated_at,
+                    "version": generated_at,
+                    "oscal-version": OSCAL_VERSION,
+                },
+                "components": [component],
+            }
+        })
+    }
+
+    /// Renders the report as a Markdown document with a table of controls per framework, including the
+    /// controls the project doesn't implement.
+    #[must_use]
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = String::new();
+        // Writing to a `String` can't fail so the results are ignored.
+        let _ = writeln!(markdown, "# Compliance of {}\n", self.project_name);
+        let _ = writeln!(markdown, "- Repo: {}", self.project_url);
+        let _ = writeln!(
+            markdown,
+            "- Generated: {}",
+            self.generated_at.to_rfc3339_opts(SecondsFormat::Secs, true)
+        );
+        for framework in [
+            ControlFramework::S2C2F,
+            ControlFramework::SLSA,
+            ControlFramework::Scorecard,
+        ] {
+            let _ = writeln!(markdown, "\n## {framework}\n");
+            let _ = writeln!(markdown, "| Control | Requirement | Status | Facets |");
+            let _ = writeln!(markdown, "| --- | --- | --- | --- |");
+            for control in self.controls.iter().filter(|c| c.framework == framework) {
+                let _ = writeln!(
+                    markdown,
+                    "| {} | {} | {} | {} |",
+                    control.control_id,
+                    control.title,
+                    control.status,
+                    facet_list(control)
+                );
+            }
+        }
+        markdown
+    }
+}
+
+/// Lists the facets of a control along with their audit status, e.g. `BranchProtection (Compliant)`.
+fn facet_list(control: &ControlCompliance) -> String {
+    if control.facets.is_empty() {
+        return "none".to_string();
+    }
+    control
+        .facets
+        .iter()
+        .map(|facet| {
+            facet.status.as_ref().map_or_else(
+                || format!("{} (not audited)", facet.facet_type),
+                |status| format!("{} ({status:?})", facet.facet_type),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use std::collections::HashMap;
+
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::skootrs::{
+        facet::{APIBundleFacet, FacetAudit, InitializedFacet, SourceBundleFacet},
+        FacetMapKey, GithubUser, InitializedGithubRepo, InitializedRepo, InitializedSource,
+    };
+
+    fn report() -> ComplianceReport {
+        let project = InitializedProject {
+            repo: InitializedRepo::Github(InitializedGithubRepo {
+                name: "test".to_string(),
+                organization: GithubUser::User("testuser".to_string()),
+            }),
+            mirror: None,
+            modules: vec![],
+            source: InitializedSource {
+                path: "test".to_string(),
+            },
+            facets: HashMap::from([
+                (
+                    FacetMapKey::Type(SupportedFacetType::SecurityPolicy),
+                    InitializedFacet::SourceBundle(SourceBundleFacet {
+                        source_files: None,
+                        facet_type: SupportedFacetType::SecurityPolicy,
+                        source_files_content: None,
+                        labels: vec![Label::ScorecardSecurityPolicy],
+                        packages: vec![],
+                        template_version: None,
+                        generator_fingerprint: None,
+                    }),
+                ),
+                (
+                    FacetMapKey::Type(SupportedFacetType::BranchProtection),
+                    InitializedFacet::APIBundle(APIBundleFacet {
+                        apis: vec![],
+                        facet_type: SupportedFacetType::BranchProtection,
+                        labels: vec![Label::ScorecardBranchProtection],
+                        skipped_reason: None,
+                        template_version: None,
+                        generator_fingerprint: None,
+                    }),
+                ),
+            ]),
+            name: "test".to_string(),
+            skootrs_version: None,
+            created_at: None,
+        };
+        let audit = ProjectAudit {
+            project_url: project.repo.full_url(),
+            facets: vec![
+                FacetAudit {
+                    facet_type: SupportedFacetType::SecurityPolicy,
+                    status: FacetAuditStatus::Compliant,
+                    files: vec![],
+                },
+                FacetAudit {
+                    facet_type: SupportedFacetType::BranchProtection,
+                    status: FacetAuditStatus::Missing,
+                    files: vec![],
+                },
+            ],
+            allowlist: vec![],
+            best_practices_badge: None,
+        };
+        ComplianceReport::new(
+            &project,
+            &audit,
+            Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_compliance_report() {
+        let report = report();
+        assert_eq!(report.controls.len(), CONTROLS.len());
+        let control = |id: &str| {
+            report
+                .controls
+                .iter()
+                .find(|control| control.control_id == id)
+                .unwrap()
+        };
+        assert_eq!(
+            control("Security-Policy").status,
+            ControlImplementationStatus::Implemented
+        );
+        assert_eq!(
+            control("Branch-Protection").status,
+            ControlImplementationStatus::Partial
+        );
+        assert_eq!(
+            control("SCA-1").status,
+            ControlImplementationStatus::NotImplemented
+        );
+
+        let markdown = report.to_markdown();
+        assert!(markdown.contains(
+            "| Branch-Protection | The project's default and release branches are protected | Partial | \
+             BranchProtection (Missing) |"
+        ));
+        assert!(markdown
+            .contains("| SCA-1 | Scan OSS for known vulnerabilities | NotImplemented | none |"));
+    }
+
+    #[test]
+    fn test_to_oscal() {
+        let report = report();
+        let oscal = report.to_oscal();
+        let definition = &oscal["component-definition"];
+        assert_eq!(definition["metadata"]["oscal-version"], OSCAL_VERSION);
+        assert_eq!(
+            definition["metadata"]["last-modified"],
+            "2024-05-01T12:00:00Z"
+        );
+        let implementations = definition["components"][0]["control-implementations"]
+            .as_array()
+            .unwrap();
+        // Only Scorecard controls are implemented.
+        assert_eq!(implementations.len(), 1);
+        assert_eq!(
+            implementations[0]["source"],
+            ControlFramework::Scorecard.source()
+        );
+        let requirements = implementations[0]["implemented-requirements"]
+            .as_array()
+            .unwrap();
+        assert_eq!(requirements.len(), 2);
+        assert_eq!(requirements[0]["control-id"], "branch-protection");
+        assert_eq!(requirements[0]["props"][0]["value"], "partial");
+        assert_eq!(requirements[0]["props"][1]["value"], "BranchProtection");
+        assert_eq!(requirements[1]["control-id"], "security-policy");
+
+        // The component's UUIDs don't change between reports.
+        let mut later = report;
+        later.generated_at = Utc.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap();
+        let later = later.to_oscal();
+        assert_eq!(
+            later["component-definition"]["components"][0]["uuid"],
+            definition["components"][0]["uuid"]
+        );
+        assert_ne!(later["component-definition"]["uuid"], definition["uuid"]);
+    }
+}
@@ -0,0 +1,164 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `container` module provides where the `Container` facet pushes a project's image to and what it's
+//! based on. The config can be overridden for a single project with the options the facet is added with.
+
+#![allow(clippy::module_name_repetitions)]
+
+use std::collections::BTreeMap;
+
+use schemars::{schema::SchemaObject, schema_for, JsonSchema};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+#[cfg(feature = "openapi")]
+use utoipa::ToSchema;
+
+use super::{InitializedModule, InitializedRepo, SkootError};
+
+/// Where the `Container` facet pushes a project's image to and what it's based on.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(default)]
+pub struct ContainerConfig {
+    /// The container registry the image is pushed to, e.g. `ghcr.io`. Registries other than `ghcr.io` are
+    /// logged in to with the `REGISTRY_USERNAME` and `REGISTRY_PASSWORD` secrets.
+    pub container_registry: String,
+    /// The image the final stage of the Dockerfile is based on, e.g.
+    /// `gcr.io/distroless/java21-debian12:nonroot`. Defaults to the distroless image for the project's
+    /// ecosystem. The image has to have a `nonroot` user for the container to run as.
+    pub base_image: Option<String>,
+}
+
+impl Default for ContainerConfig {
+    fn default() -> Self {
+        Self {
+            container_registry: "ghcr.io".to_string(),
+            base_image: None,
+        }
+    }
+}
+
+impl ContainerConfig {
+    /// Returns the config with the options a facet was added with applied on top, e.g. a project that's
+    /// based on a different image.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an option has the wrong type, or if the container registry or base image isn't a
+    /// valid reference.
+    pub fn with_options(&self, options: &BTreeMap<String, Value>) -> Result<Self, SkootError> {
+        let config: Self = super::registry::with_options(self, options)?;
+        if config.container_registry.is_empty()
+            || !config
+                .container_registry
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | ':'))
+        {
+            return Err(format!(
+                "{} is not a valid container registry",
+                config.container_registry
+            )
+            .into());
+        }
+        if let Some(base_image) = &config.base_image {
+            if base_image.is_empty()
+                || !base_image.chars().all(|c| {
+                    c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | '/' | ':' | '@')
+                })
+            {
+                return Err(format!("{base_image} is not a valid base image").into());
+            }
+        }
+        Ok(config)
+    }
+
+    /// Returns the options schema of the `Container` facet, which lets each part of the config be overridden
+    /// when the facet is added.
+    #[must_use]
+    pub fn options_schema() -> SchemaObject {
+        schema_for!(Self).schema
+    }
+
+    /// Returns the reference of the image the `Container` facet builds for a module, without a tag. The
+    /// images of modules that aren't at the root of the source are suffixed with the module's name.
+    #[must_use]
+    pub fn image(&self, repo: &InitializedRepo, module: &InitializedModule) -> String {
+        let repo_path = match repo {
+            InitializedRepo::Github(g) => format!("{}/{}", g.organization.get_name(), g.name),
+            InitializedRepo::GenericGit(g) => g.name.clone(),
+            InitializedRepo::Bitbucket(b) => format!("{}/{}", b.workspace, b.name),
+        };
+        let name = match module.slug() {
+            Some(slug) => format!("{repo_path}-{slug}"),
+            None => repo_path,
+        };
+        // Container registries only allow lowercase repository names.
+        format!("{}/{}", self.container_registry, name.to_lowercase())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use crate::skootrs::{
+        GithubUser, InitializedEcosystem, InitializedGithubRepo, InitializedMaven,
+    };
+
+    #[test]
+    fn test_image() {
+        let repo = InitializedRepo::Github(InitializedGithubRepo {
+            name: "Test".to_string(),
+            organization: GithubUser::Organization("TestOrg".to_string()),
+        });
+        let ecosystem = InitializedEcosystem::Maven(InitializedMaven {
+            group_id: "com.example".to_string(),
+            artifact_id: "test".to_string(),
+        });
+        let config = ContainerConfig::default();
+        assert_eq!(
+            config.image(&repo, &ecosystem.clone().into()),
+            "ghcr.io/testorg/test"
+        );
+        let module = InitializedModule {
+            path: "./services/API".to_string(),
+            ecosystem,
+        };
+        assert_eq!(
+            config.image(&repo, &module),
+            "ghcr.io/testorg/test-services-api"
+        );
+
+        let config = config
+            .with_options(&BTreeMap::from([(
+                "base_image".to_string(),
+                Value::from("cgr.dev/chainguard/jre:latest"),
+            )]))
+            .unwrap();
+        assert_eq!(
+            config.base_image.as_deref(),
+            Some("cgr.dev/chainguard/jre:latest")
+        );
+        for (option, value) in [
+            ("container_registry", "ghcr.io\n  run: whoami"),
+            ("base_image", "alpine\nRUN whoami"),
+        ] {
+            assert!(config
+                .with_options(&BTreeMap::from([(option.to_string(), Value::from(value))]))
+                .is_err());
+        }
+    }
+}
@@ -0,0 +1,118 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `contributing` module provides how the `ContributingPolicy` facet asks contributors to certify their
+//! changes, and who the `CodeOfConduct` facet tells people to report violations to.
+
+#![allow(clippy::module_name_repetitions)]
+
+use std::collections::BTreeMap;
+
+use schemars::{schema::SchemaObject, schema_for, JsonSchema};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+#[cfg(feature = "openapi")]
+use utoipa::ToSchema;
+
+use super::SkootError;
+
+/// How contributors certify that they're allowed to contribute their changes.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, JsonSchema)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum ContributionSignOff {
+    /// Every commit is signed off under the Developer Certificate of Origin, see <https://developercertificate.org>.
+    #[default]
+    Dco,
+    /// Contributors sign a Contributor License Agreement before their first change is accepted.
+    Cla,
+    /// Contributors don't have to certify their changes.
+    None,
+}
+
+/// How the `ContributingPolicy` and `CodeOfConduct` facets tell people to contribute to a project.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, JsonSchema)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(default)]
+pub struct ContributingConfig {
+    /// How contributors certify that they're allowed to contribute their changes.
+    pub sign_off: ContributionSignOff,
+    /// Where contributors sign the Contributor License Agreement. This has to be set when `sign_off` is `cla`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cla_url: Option<String>,
+    /// Where violations of the Code of Conduct are reported, e.g. an email address. The security policy's
+    /// contact email is used if this isn't set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conduct_contact: Option<String>,
+}
+
+impl ContributingConfig {
+    /// Returns the config with the options a facet was added with applied on top, e.g. a project that asks
+    /// for a CLA instead of signed off commits.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an option has the wrong type, or if a CLA is required without an https URL to sign
+    /// it at.
+    pub fn with_options(&self, options: &BTreeMap<String, Value>) -> Result<Self, SkootError> {
+        let config: Self = super::registry::with_options(self, options)?;
+        if config.sign_off == ContributionSignOff::Cla
+            && !config
+                .cla_url
+                .as_deref()
+                .is_some_and(|url| url.starts_with("https://"))
+        {
+            return Err("cla_url has to be an https URL when sign_off is cla".into());
+        }
+        Ok(config)
+    }
+
+    /// Returns the options schema of the `ContributingPolicy` facet, which lets each part of the config be
+    /// overridden when the facet is added.
+    #[must_use]
+    pub fn options_schema() -> SchemaObject {
+        schema_for!(Self).schema
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn test_contributing_config() {
+        let config = ContributingConfig::default();
+        assert_eq!(config.sign_off, ContributionSignOff::Dco);
+
+        let config = config
+            .with_options(&BTreeMap::from([
+                ("sign_off".to_string(), Value::from("cla")),
+                (
+                    "cla_url".to_string(),
+                    Value::from("https://cla.example.com"),
+                ),
+            ]))
+            .unwrap();
+        assert_eq!(config.sign_off, ContributionSignOff::Cla);
+
+        assert!(ContributingConfig::default()
+            .with_options(&BTreeMap::from([(
+                "sign_off".to_string(),
+                Value::from("cla")
+            )]))
+            .is_err());
+    }
+}
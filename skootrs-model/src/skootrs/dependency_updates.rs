@@ -0,0 +1,154 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `dependency_updates` module provides what the `DependencyUpdateTool` facet has Dependabot keep up to
+//! date, how often, and whether the updates are grouped. The config can be overridden for a single project
+//! with the options the facet is added with.
+
+#![allow(clippy::module_name_repetitions)]
+
+use std::collections::BTreeMap;
+
+use schemars::{schema::SchemaObject, schema_for, JsonSchema};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+#[cfg(feature = "openapi")]
+use utoipa::ToSchema;
+
+use super::SkootError;
+
+/// How often Dependabot checks for updates.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, JsonSchema)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateInterval {
+    /// Every weekday.
+    Daily,
+    /// Every Monday.
+    #[default]
+    Weekly,
+    /// On the first day of every month.
+    Monthly,
+}
+
+impl UpdateInterval {
+    /// Returns the interval as it's written in a Dependabot config.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Daily => "daily",
+            Self::Weekly => "weekly",
+            Self::Monthly => "monthly",
+        }
+    }
+}
+
+/// What the `DependencyUpdateTool` facet has Dependabot keep up to date.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(default)]
+pub struct DependencyUpdateConfig {
+    /// How often the dependencies of the project's ecosystem are checked for updates.
+    pub interval: UpdateInterval,
+    /// How often the actions the project's workflows use are checked for updates.
+    pub github_actions_interval: UpdateInterval,
+    /// The directories with a Dockerfile whose base images are kept up to date, relative to the module,
+    /// e.g. `/` for the Dockerfile the `Container` facet generates. The facet is opt-in, so there are none by
+    /// default.
+    pub docker_directories: Vec<String>,
+    /// How often the base images of the Dockerfiles are checked for updates.
+    pub docker_interval: UpdateInterval,
+    /// Whether the minor and patch updates of each ecosystem are grouped into a single pull request instead
+    /// of one per dependency. Major updates always get a pull request of their own.
+    pub group_updates: bool,
+}
+
+impl Default for DependencyUpdateConfig {
+    fn default() -> Self {
+        Self {
+            interval: UpdateInterval::Weekly,
+            github_actions_interval: UpdateInterval::Weekly,
+            docker_directories: vec![],
+            docker_interval: UpdateInterval::Weekly,
+            group_updates: true,
+        }
+    }
+}
+
+impl DependencyUpdateConfig {
+    /// Returns the config with the options a facet was added with applied on top, e.g. a project that checks
+    /// for updates daily.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an option isn't part of the config or has the wrong type, or if a Docker
+    /// directory isn't an absolute path without characters that aren't allowed in it.
+    pub fn with_options(&self, options: &BTreeMap<String, Value>) -> Result<Self, SkootError> {
+        let config: Self = super::registry::with_options(self, options)?;
+        for directory in &config.docker_directories {
+            if !directory.starts_with('/')
+                || directory.split('/').any(|part| part == "..")
+                || !directory
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '/' | '.' | '-' | '_'))
+            {
+                return Err(format!("{directory} is not a valid Docker directory").into());
+            }
+        }
+        Ok(config)
+    }
+
+    /// Returns the options schema of the `DependencyUpdateTool` facet, which lets each part of the config be
+    /// overridden when the facet is added.
+    #[must_use]
+    pub fn options_schema() -> SchemaObject {
+        schema_for!(Self).schema
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn test_with_options() {
+        let config = DependencyUpdateConfig::default()
+            .with_options(&BTreeMap::from([
+                ("interval".to_string(), Value::from("daily")),
+                (
+                    "docker_directories".to_string(),
+                    Value::from(vec!["/", "/deploy"]),
+                ),
+            ]))
+            .unwrap();
+        assert_eq!(config.interval, UpdateInterval::Daily);
+        assert_eq!(config.github_actions_interval.as_str(), "weekly");
+        assert_eq!(config.docker_directories, vec!["/", "/deploy"]);
+        assert!(config.group_updates);
+
+        for directory in ["deploy", "/../deploy", "/deploy\n  run: whoami"] {
+            assert!(
+                DependencyUpdateConfig::default()
+                    .with_options(&BTreeMap::from([(
+                        "docker_directories".to_string(),
+                        Value::from(vec![directory]),
+                    )]))
+                    .is_err(),
+                "{directory}"
+            );
+        }
+    }
+}
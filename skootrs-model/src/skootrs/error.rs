@@ -0,0 +1,151 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `error` module defines `SkootError`, the error type used throughout Skootrs. Its variants tell what
+//! kind of failure an error is, so callers like the REST API can tell a user's mistake from a failure of
+//! Github or of the machine Skootrs runs on without parsing the message.
+
+use std::error::Error;
+
+use super::validation::ValidationError;
+
+/// A boxed error of any type, which is what errors that don't have a variant of their own are kept as.
+pub type BoxedError = Box<dyn Error + Send + Sync>;
+
+/// The error type used throughout Skootrs.
+#[derive(Debug, thiserror::Error)]
+pub enum SkootError {
+    /// The Skootrs config, or config a project was created with, isn't valid.
+    #[error("Invalid config: {0}")]
+    Config(String),
+    /// A request to the Github API failed.
+    #[error("Github API request failed: {0}")]
+    GithubApi(#[source] BoxedError),
+    /// Reading or writing a file, or running a command, failed.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// A template couldn't be rendered.
+    #[error("Failed to render template: {0}")]
+    Template(#[source] BoxedError),
+    /// The parameters of a request aren't valid.
+    #[error(transparent)]
+    Validation(#[from] ValidationError),
+    /// Something the request refers to, like a project, facet or output, doesn't exist.
+    #[error("{0}")]
+    NotFound(String),
+    /// Data couldn't be serialized or deserialized.
+    #[error(transparent)]
+    Serialization(BoxedError),
+    /// Any other error.
+    #[error(transparent)]
+    Other(BoxedError),
+}
+
+impl SkootError {
+    /// Returns a `SkootError::GithubApi` for an error returned by the Github API.
+    pub fn github(error: impl Into<BoxedError>) -> Self {
+        Self::GithubApi(error.into())
+    }
+
+    /// Returns a `SkootError::Template` for an error rendering a template.
+    pub fn template(error: impl Into<BoxedError>) -> Self {
+        Self::Template(error.into())
+    }
+
+    /// Returns a `SkootError::Serialization` for an error (de)serializing data, e.g. YAML.
+    pub fn serialization(error: impl Into<BoxedError>) -> Self {
+        Self::Serialization(error.into())
+    }
+
+    /// Returns a `SkootError::Other` for an error that doesn't have a variant of its own.
+    pub fn other(error: impl Into<BoxedError>) -> Self {
+        Self::Other(error.into())
+    }
+
+    /// Returns the underlying error if it's of type `T`, e.g. to check whether a push failed because of a
+    /// concurrent change.
+    #[must_use]
+    pub fn downcast_ref<T: Error + 'static>(&self) -> Option<&T> {
+        match self {
+            Self::GithubApi(error)
+            | Self::Template(error)
+            | Self::Serialization(error)
+            | Self::Other(error) => error.downcast_ref(),
+            Self::Io(error) => (error as &dyn Error).downcast_ref(),
+            Self::Validation(error) => (error as &dyn Error).downcast_ref(),
+            Self::Config(_) | Self::NotFound(_) => None,
+        }
+    }
+}
+
+// Lets the error be logged as an error field, e.g. `error!(error = error.as_ref(), "...")`.
+impl AsRef<dyn Error + Send + Sync + 'static> for SkootError {
+    fn as_ref(&self) -> &(dyn Error + Send + Sync + 'static) {
+        self
+    }
+}
+
+impl From<serde_json::Error> for SkootError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Serialization(Box::new(error))
+    }
+}
+
+impl From<String> for SkootError {
+    fn from(message: String) -> Self {
+        Self::Other(message.into())
+    }
+}
+
+impl From<&str> for SkootError {
+    fn from(message: &str) -> Self {
+        Self::Other(message.into())
+    }
+}
+
+impl From<BoxedError> for SkootError {
+    fn from(error: BoxedError) -> Self {
+        // An error that was boxed on its way here keeps its variant.
+        match error.downcast::<Self>() {
+            Ok(error) => *error,
+            Err(error) => Self::Other(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn test_downcast_ref() {
+        let error = SkootError::other(ValidationError { errors: vec![] });
+        assert!(error.downcast_ref::<ValidationError>().is_some());
+        assert!(error.downcast_ref::<std::io::Error>().is_none());
+
+        let error = SkootError::from(std::io::Error::other("disk full"));
+        assert!(error.downcast_ref::<std::io::Error>().is_some());
+        assert_eq!(error.to_string(), "disk full");
+    }
+
+    #[test]
+    fn test_boxed_error_keeps_its_variant() {
+        let boxed: BoxedError = Box::new(SkootError::NotFound("Project not found".to_string()));
+        assert!(matches!(SkootError::from(boxed), SkootError::NotFound(_)));
+        let boxed: BoxedError = "some other error".into();
+        assert!(matches!(SkootError::from(boxed), SkootError::Other(_)));
+    }
+}
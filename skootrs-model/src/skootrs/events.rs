@@ -0,0 +1,243 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `events` module provides the data model for the [CDEvents](https://cdevents.dev) Skootrs emits when
+//! it changes a project, and the sinks they're sent to. Repos being created use the `repository.created`
+//! event from the spec, the events the spec has no type for are custom `dev.cdeventsx.skootrs-*` events.
+
+#![allow(clippy::module_name_repetitions)]
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use strum::Display;
+use utoipa::ToSchema;
+
+use super::{facet::SupportedFacetType, label::Label};
+
+/// The version of the `CDEvents` spec the custom events follow.
+pub const CDEVENTS_SPEC_VERSION: &str = "0.4.1";
+
+/// The source of the events Skootrs emits.
+pub const EVENT_SOURCE: &str = "skootrs";
+
+/// Where the events Skootrs emits are sent. Every configured sink gets every event.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum EventSinkConfig {
+    /// Each event is printed to stdout as a line of JSON.
+    Stdout,
+    /// Each event is sent as JSON in a POST request to a webhook.
+    Webhook {
+        /// The URL of the webhook.
+        url: String,
+        /// Headers sent along with each event, e.g. an `Authorization` header.
+        #[serde(default)]
+        headers: std::collections::BTreeMap<String, String>,
+    },
+    /// Each event is sent in a POST request to a `CloudEvents` endpoint in binary mode, with its context in the `ce-*` headers.
+    CloudEvents {
+        /// The URL of the endpoint.
+        url: String,
+    },
+    /// Each event is produced to a Kafka topic through a Kafka REST proxy, keyed by the ID of its subject.
+    Kafka {
+        /// The URL of the REST proxy, e.g. `http://localhost:8082`.
+        rest_proxy_url: String,
+        /// The topic the events are produced to.
+        topic: String,
+    },
+}
+
+/// What happened to the subject of a custom Skootrs event.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Display)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[strum(serialize_all = "lowercase")]
+pub enum EventPredicate {
+    /// A facet was applied to a project.
+    Created,
+    /// A facet of a project was changed.
+    Updated,
+    /// A project was archived.
+    Archived,
+    /// A release of a project was looked at, e.g. to list its outputs.
+    Observed,
+}
+
+/// A `CDEvent`. The content of the subject depends on the type of the event.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct CDEvent {
+    /// The context of the event.
+    pub context: CDEventContext,
+    /// What the event is about.
+    pub subject: CDEventSubject,
+}
+
+/// The context of a `CDEvent`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct CDEventContext {
+    /// The version of the `CDEvents` spec the event follows.
+    pub version: String,
+    /// The ID of the event.
+    pub id: String,
+    /// Where the event came from.
+    pub source: String,
+    /// The type of the event, e.g. `dev.cdeventsx.skootrs-facet.created.0.1.0`.
+    #[serde(rename = "type")]
+    pub type_: String,
+    /// When the event happened.
+    pub timestamp: DateTime<Utc>,
+}
+
+/// The subject of a `CDEvent`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct CDEventSubject {
+    /// The ID of the subject, e.g. the URL of a project.
+    pub id: String,
+    /// The type of the subject, e.g. `skootrs-facet`.
+    #[serde(rename = "type")]
+    pub type_: String,
+    /// What's known about the subject.
+    #[cfg_attr(feature = "openapi", schema(value_type = Object))]
+    pub content: serde_json::Value,
+}
+
+impl CDEvent {
+    /// Returns a custom Skootrs event, e.g. `dev.cdeventsx.skootrs-project.archived.0.1.0` for a `project`
+    /// subject that was `Archived`.
+    fn custom(
+        subject: &str,
+        predicate: EventPredicate,
+        subject_id: String,
+        content: serde_json::Value,
+    ) -> Self {
+        let timestamp = Utc::now();
+        Self {
+            context: CDEventContext {
+                version: CDEVENTS_SPEC_VERSION.to_string(),
+                id: format!(
+                    "{subject_id}/{predicate}/{}",
+                    timestamp.to_rfc3339_opts(SecondsFormat::Nanos, true)
+                ),
+                source: EVENT_SOURCE.to_string(),
+                type_: format!("dev.cdeventsx.skootrs-{subject}.{predicate}.0.1.0"),
+                timestamp,
+            },
+            subject: CDEventSubject {
+                id: subject_id,
+                type_: format!("skootrs-{subject}"),
+                content,
+            },
+        }
+    }
+
+    /// Returns the event for a facet of a project being created or updated.
+    #[must_use]
+    pub fn facet(
+        predicate: EventPredicate,
+        project_url: &str,
+        facet_type: &SupportedFacetType,
+        labels: &[Label],
+    ) -> Self {
+        Self::custom(
+            "facet",
+            predicate,
+            format!("{project_url}#{facet_type}"),
+            serde_json::json!({
+                "project": project_url,
+                "facetType": facet_type.to_string(),
+                "labels": labels.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            }),
+        )
+    }
+
+    /// Returns the event for a project being archived.
+    #[must_use]
+    pub fn project_archived(project_url: &str) -> Self {
+        Self::custom(
+            "project",
+            EventPredicate::Archived,
+            project_url.to_string(),
+            serde_json::json!({ "project": project_url }),
+        )
+    }
+
+    /// Returns the event for a release of a project being observed, along with the outputs it has. The tag is
+    /// `None` for the latest release.
+    #[must_use]
+    pub fn release_observed(project_url: &str, tag: Option<&str>, outputs: &[String]) -> Self {
+        Self::custom(
+            "release",
+            EventPredicate::Observed,
+            format!("{project_url}@{}", tag.unwrap_or("latest")),
+            serde_json::json!({
+                "project": project_url,
+                "tag": tag,
+                "outputs": outputs,
+            }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    #[test]
+    fn test_facet_event() {
+        let event = CDEvent::facet(
+            EventPredicate::Created,
+            "https://github.com/kusaridev/skootrs",
+            &SupportedFacetType::BranchProtection,
+            &[Label::ScorecardBranchProtection],
+        );
+        assert_eq!(
+            event.context.type_,
+            "dev.cdeventsx.skootrs-facet.created.0.1.0"
+        );
+        assert_eq!(
+            event.subject.id,
+            "https://github.com/kusaridev/skootrs#BranchProtection"
+        );
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["context"]["type"], event.context.type_);
+        assert_eq!(json["subject"]["type"], "skootrs-facet");
+        assert_eq!(
+            json["subject"]["content"]["labels"][0],
+            "ScorecardBranchProtection"
+        );
+    }
+
+    #[test]
+    fn test_event_sink_config() {
+        let sinks: Vec<EventSinkConfig> = serde_json::from_str(
+            r#"[
+                {"type": "stdout"},
+                {"type": "webhook", "url": "https://example.com/events"},
+                {"type": "kafka", "rest_proxy_url": "http://localhost:8082", "topic": "skootrs"}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(sinks[0], EventSinkConfig::Stdout);
+        assert!(
+            matches!(&sinks[1], EventSinkConfig::Webhook { headers, .. } if headers.is_empty())
+        );
+    }
+}
@@ -20,7 +20,10 @@
 
 #![allow(clippy::module_name_repetitions)]
 
-use std::{collections::HashMap, fmt};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
 
 use serde::{Deserialize, Serialize};
 use strum::VariantNames;
@@ -28,8 +31,12 @@ use strum::VariantNames;
 use utoipa::ToSchema;
 
 use super::{
+    best_practices::BestPracticesBadge,
     label::{Label, Labeled},
-    InitializedEcosystem, InitializedRepo, InitializedSource,
+    publishing::PackageCoordinates,
+    scan_policy::{AllowlistAudit, AllowlistAuditStatus},
+    FacetMapKey, InitializedEcosystem, InitializedModule, InitializedProject, InitializedRepo,
+    InitializedSource, ProjectCreateParams, ProjectGetParams, SkootError,
 };
 use strum::EnumString;
 
@@ -63,6 +70,31 @@ impl InitializedFacet {
             Self::APIBundle(a) => a.labels(),
         }
     }
+
+    /// Helper function to get the template version of the inner facet.
+    #[must_use]
+    pub const fn template_version(&self) -> Option<u32> {
+        match self {
+            Self::SourceBundle(facet) => facet.template_version,
+            Self::APIBundle(facet) => facet.template_version,
+        }
+    }
+
+    /// Helper function to get the generator fingerprint of the inner facet.
+    #[must_use]
+    pub fn generator_fingerprint(&self) -> Option<&str> {
+        match self {
+            Self::SourceBundle(facet) => facet.generator_fingerprint.as_deref(),
+            Self::APIBundle(facet) => facet.generator_fingerprint.as_deref(),
+        }
+    }
+
+    /// Returns true if the facet was generated by an older template than `other`, including when it was
+    /// recorded before template versions were, so it needs to be regenerated.
+    #[must_use]
+    pub fn has_older_template_than(&self, other: &Self) -> bool {
+        self.template_version() < other.template_version()
+    }
 }
 
 /// Represents the parameters for creating a facet. This should mirror the
@@ -76,6 +108,17 @@ pub enum FacetCreateParams {
     APIBundle(APIBundleFacetParams),
 }
 
+impl FacetCreateParams {
+    /// Helper function to get the facet type of the inner params.
+    #[must_use]
+    pub fn facet_type(&self) -> SupportedFacetType {
+        match self {
+            Self::SourceBundle(params) => params.facet_type.clone(),
+            Self::APIBundle(params) => params.facet_type.clone(),
+        }
+    }
+}
+
 /// This is required to create an ordering of what facets get applied.
 /// There could be issues like a security feature being enabled before
 /// some other feature, which could lead to it being blocked.
@@ -100,8 +143,93 @@ pub struct CommonFacetCreateParams {
     pub source: InitializedSource,
     /// The repo of the project the facet is being created for.
     pub repo: InitializedRepo,
-    /// The ecosystem of the project the facet is being created for.
+    /// The ecosystem the facet's content is generated for. Facets that are specific to an ecosystem, like the
+    /// release workflows, are generated for each of the project's modules in turn and the other facets for its
+    /// first module.
     pub ecosystem: InitializedEcosystem,
+    /// The directory of the module the facet's content is generated for, relative to the root of the source.
+    #[serde(default = "super::root_module_path")]
+    pub module_path: String,
+    /// Every module of the project.
+    #[serde(default)]
+    pub modules: Vec<InitializedModule>,
+    /// The ID of the project's `OpenSSF` Best Practices Badge, which the README shows the badge for, or `None`
+    /// if the project doesn't have the `BestPracticesBadge` facet.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub best_practices_badge_id: Option<u64>,
+    /// Whether the project has the `ArtifactSigning` facet, so the README and SECURITY-INSIGHTS tell
+    /// consumers how to verify its releases.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub artifact_signing: bool,
+}
+
+impl CommonFacetCreateParams {
+    /// Creates the common parameters for a project's facets, generating their content for its first module.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the project has no modules.
+    pub fn new(
+        project_name: String,
+        source: InitializedSource,
+        repo: InitializedRepo,
+        modules: Vec<InitializedModule>,
+    ) -> Result<Self, SkootError> {
+        let first = modules
+            .first()
+            .ok_or_else(|| SkootError::from(format!("Project {project_name} has no modules")))?;
+        Ok(Self {
+            ecosystem: first.ecosystem.clone(),
+            module_path: first.path.clone(),
+            project_name,
+            source,
+            repo,
+            modules,
+            best_practices_badge_id: None,
+            artifact_signing: false,
+        })
+    }
+
+    /// Returns the module the facet's content is generated for.
+    #[must_use]
+    pub fn module(&self) -> InitializedModule {
+        InitializedModule {
+            path: self.module_path.clone(),
+            ecosystem: self.ecosystem.clone(),
+        }
+    }
+
+    /// Returns every module of the project, or only the module the facet's content is generated for if the
+    /// parameters were created without the project's modules.
+    #[must_use]
+    pub fn project_modules(&self) -> Vec<InitializedModule> {
+        if self.modules.is_empty() {
+            vec![self.module()]
+        } else {
+            self.modules.clone()
+        }
+    }
+
+    /// Returns the parameters for generating the facet's content for one of the project's modules.
+    #[must_use]
+    pub fn for_module(&self, module: &InitializedModule) -> Self {
+        Self {
+            ecosystem: module.ecosystem.clone(),
+            module_path: module.path.clone(),
+            ..self.clone()
+        }
+    }
+
+    /// Returns the parameters for a facet that only applies to some of the project's modules, generating its
+    /// content for the first of them. The parameters are unchanged if there are none.
+    #[must_use]
+    pub fn with_modules(&self, modules: Vec<InitializedModule>) -> Self {
+        let Some(first) = modules.first() else {
+            return self.clone();
+        };
+        let params = self.for_module(first);
+        Self { modules, ..params }
+    }
 }
 
 /// Represents the content of a source file.
@@ -170,6 +298,17 @@ pub struct SourceBundleFacet {
     pub source_files_content: Option<HashMap<SourceFile, String>>,
     /// The labels for the facet.
     pub labels: Vec<Label>,
+    /// The packages the facet's workflows publish, e.g. the container image of a `PublishPackages` facet.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub packages: Vec<PackageCoordinates>,
+    /// The version of the template the facet was generated from. `None` for facets recorded before template
+    /// versions were.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template_version: Option<u32>,
+    /// A fingerprint of the generator that produced the facet, i.e. the Skootrs version, the template version
+    /// and the inputs the facet was generated with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generator_fingerprint: Option<String>,
 }
 
 /// Represents the parameters for creating a source bundle facet.
@@ -182,6 +321,10 @@ pub struct SourceBundleFacetCreateParams {
     pub facet_type: SupportedFacetType,
     /// The labels for the facet.
     pub labels: Vec<Label>,
+    /// The options the facet was added with, e.g. the GUAC endpoint a project forwards to.
+    #[serde(default)]
+    #[cfg_attr(feature = "openapi", schema(value_type = Object))]
+    pub options: BTreeMap<String, serde_json::Value>,
 }
 
 /// Represents the content of an API call. This just includes the
@@ -217,6 +360,18 @@ pub struct APIBundleFacet {
     pub facet_type: SupportedFacetType,
     /// The labels for the facet.
     pub labels: Vec<Label>,
+    /// Why the facet's API calls were skipped, e.g. because the repo host has no API. Skipped facets are
+    /// still recorded so it's clear which controls aren't in place.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skipped_reason: Option<String>,
+    /// The version of the template the facet was generated from. `None` for facets recorded before template
+    /// versions were.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template_version: Option<u32>,
+    /// A fingerprint of the generator that produced the facet, i.e. the Skootrs version, the template version
+    /// and the inputs the facet was generated with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generator_fingerprint: Option<String>,
 }
 
 /// Represents the parameters for creating an API bundle facet.
@@ -227,6 +382,20 @@ pub struct APIBundleFacetParams {
     pub common: CommonFacetCreateParams,
     /// The type of facet that is being created.
     pub facet_type: SupportedFacetType,
+    /// The labels for the facet.
+    #[serde(default)]
+    pub labels: Vec<Label>,
+    /// The options the facet was added with, e.g. overrides of the configured branch protection rules.
+    #[serde(default)]
+    #[cfg_attr(feature = "openapi", schema(value_type = Object))]
+    pub options: BTreeMap<String, serde_json::Value>,
+}
+
+impl Labeled for InitializedFacet {
+    fn labels(&self) -> Vec<Label> {
+        // Calls the inherent method, which takes precedence over this one.
+        Self::labels(self)
+    }
 }
 
 impl Labeled for SourceBundleFacet {
@@ -258,6 +427,9 @@ pub enum SupportedFacetType {
     /// A facet type that supports building the project via SLSA.
     SLSABuild,
 
+    /// A facet type that verifies the SLSA provenance of the project's releases.
+    SLSAVerification,
+
     /// A facet type that supports generation of SBOMs in the project.
     SBOMGenerator,
 
@@ -273,6 +445,10 @@ pub enum SupportedFacetType {
     /// A facet type showing that branch protection has been enabled on the project.
     BranchProtection,
 
+    /// A facet type showing that a repository ruleset protects the project's branches. Rulesets are Github's
+    /// successor to classic branch protection.
+    RepoRuleset,
+
     /// A facet type showing that code review is enabled on the project.
     CodeReview,
 
@@ -285,6 +461,13 @@ pub enum SupportedFacetType {
     /// A facet type showing that the project publishes its packages.
     PublishPackages,
 
+    /// A facet type for building the project into a hardened container image that's signed with cosign.
+    Container,
+
+    /// A facet type for signing the project's release assets and container images with cosign keyless
+    /// signing.
+    ArtifactSigning,
+
     /// A facet type showing that the project pins its dependencies.
     PinnedDependencies,
 
@@ -294,6 +477,12 @@ pub enum SupportedFacetType {
     /// A facet type for the project's security policy.
     SecurityPolicy,
 
+    /// A facet type for the project's code of conduct.
+    CodeOfConduct,
+
+    /// A facet type for the project's contributing guide.
+    ContributingPolicy,
+
     /// A facet type showing that the project runs a vulnerability scanner.
     VulnerabilityScanner,
 
@@ -306,6 +495,9 @@ pub enum SupportedFacetType {
     /// A facet type showing that the project is running `OpenSSF Scorecard`.
     Scorecard,
 
+    /// A facet type showing that the project's CI verifies its source against the recorded Skootrs state.
+    StateVerification,
+
     /// A facet type showing for the project's default source code. This should be something simple to just show that the project can build with
     /// trivial source code and all the other facets enabled.
     DefaultSourceCode,
@@ -313,6 +505,9 @@ pub enum SupportedFacetType {
     /// A facet type showing that the project has a mechanism for reporting vulnerabilities.
     VulnerabilityReporting,
 
+    /// A facet type showing that the project is registered for the `OpenSSF` Best Practices Badge.
+    BestPracticesBadge,
+
     /// A catch all facet type for other facets that don't fit into the above categories.
     #[default]
     Other,
@@ -323,3 +518,237 @@ impl fmt::Display for SupportedFacetType {
         fmt::Debug::fmt(self, f)
     }
 }
+
+impl SupportedFacetType {
+    /// Returns true if the facet is applied by calling the repo host's API rather than by writing files.
+    #[must_use]
+    pub const fn is_api_bundle(&self) -> bool {
+        matches!(
+            self,
+            Self::CodeReview
+                | Self::BranchProtection
+                | Self::RepoRuleset
+                | Self::VulnerabilityReporting
+                | Self::BestPracticesBadge
+        )
+    }
+}
+
+/// The status of a facet when its recorded state is compared against the project's source and the current
+/// facet templates.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub enum FacetDriftStatus {
+    /// The facet's files match both the recorded state and the current templates.
+    UpToDate,
+    /// The facet's files match the recorded state, but the current templates render different content.
+    Outdated,
+    /// The facet's files were changed or removed outside of Skootrs since the state was recorded.
+    Drifted,
+    /// The facet isn't in the recorded state.
+    Added,
+}
+
+/// The result of comparing a facet's recorded state against the project's source and the current facet
+/// templates.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct FacetDrift {
+    /// The type of the facet.
+    pub facet_type: SupportedFacetType,
+    /// The status of the facet.
+    pub status: FacetDriftStatus,
+    /// The paths of the facet's files that caused the status, relative to the root of the source.
+    pub files: Vec<String>,
+}
+
+/// A previous version of a facet's content that was replaced when the project was updated. This is kept
+/// so investigations can reconstruct exactly what a control looked like at a point in time.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct FacetHistoryEntry {
+    /// The type of the facet.
+    pub facet_type: SupportedFacetType,
+    /// The version of the entry. Versions start at 1 and increase every time the facet is replaced.
+    pub version: u32,
+    /// Why the content was replaced.
+    pub status: FacetDriftStatus,
+    /// The last commit that had this content, i.e. the parent of the commit that replaced it.
+    pub previous_commit: String,
+    /// When the content was replaced.
+    pub replaced_at: chrono::DateTime<chrono::Utc>,
+    /// The content of the facet's files keyed by their path relative to the root of the source.
+    pub files: BTreeMap<String, String>,
+}
+
+/// The parameters for getting a previous version of a facet's content.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct FacetHistoryGetParams {
+    /// The parameters for getting the project the facet belongs to.
+    pub project_get_params: ProjectGetParams,
+    /// The type of the facet.
+    pub facet_type: SupportedFacetType,
+    /// The version to get. Defaults to the latest version.
+    pub version: Option<u32>,
+}
+
+/// The parameters for adding a single facet to an existing project.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct FacetAddParams {
+    /// The initialized project to add the facet to.
+    pub initialized_project: InitializedProject,
+    /// The type of the facet to add.
+    pub facet_type: SupportedFacetType,
+    /// The options for the facet. They are checked against the options schema of the facet's registry entry.
+    #[serde(default)]
+    #[cfg_attr(feature = "openapi", schema(value_type = Object))]
+    pub options: BTreeMap<String, serde_json::Value>,
+}
+
+/// The parameters for deleting a single facet from an existing project.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct FacetDeleteParams {
+    /// The initialized project to delete the facet from.
+    pub initialized_project: InitializedProject,
+    /// The key of the facet to delete.
+    pub facet_map_key: FacetMapKey,
+}
+
+/// The parameters for previewing the content a facet would have in a project, without creating the project
+/// or writing any files.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct FacetPreviewParams {
+    /// The parameters of the project the facet is previewed for.
+    pub project_params: ProjectCreateParams,
+    /// The type of the facet to preview.
+    pub facet_type: SupportedFacetType,
+    /// The options for the facet. They are checked against the options schema of the facet's registry entry.
+    #[serde(default)]
+    #[cfg_attr(feature = "openapi", schema(value_type = Object))]
+    pub options: BTreeMap<String, serde_json::Value>,
+}
+
+/// The parameters for diffing a facet's files in a project's repo against what a project update would
+/// re-render them as.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct FacetDiffParams {
+    /// The initialized project the facet belongs to.
+    pub initialized_project: InitializedProject,
+    /// The key of the facet to diff.
+    pub facet_map_key: FacetMapKey,
+}
+
+/// How a file of a facet in the project's repo differs from what it would be re-rendered as.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub enum FacetFileDiffStatus {
+    /// The file in the repo is what it would be re-rendered as.
+    Unchanged,
+    /// The file in the repo would be changed.
+    Modified,
+    /// The file isn't in the repo and would be added.
+    Added,
+}
+
+/// The difference between a file of a facet in the project's repo and what it would be re-rendered as.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct FacetFileDiff {
+    /// The path of the file, relative to the root of the source.
+    pub path: String,
+    /// How the file differs.
+    pub status: FacetFileDiffStatus,
+    /// The unified diff from the file in the repo to the re-rendered file. Empty if the file is unchanged.
+    pub diff: String,
+}
+
+/// The difference between a facet's files in the project's repo and what a project update would re-render
+/// them as.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct FacetDiff {
+    /// The type of the diffed facet.
+    pub facet_type: SupportedFacetType,
+    /// The differences of each of the facet's re-rendered files.
+    pub files: Vec<FacetFileDiff>,
+}
+
+impl FacetDiff {
+    /// Returns true if an update would change any of the facet's files.
+    #[must_use]
+    pub fn has_changes(&self) -> bool {
+        self.files
+            .iter()
+            .any(|file| file.status != FacetFileDiffStatus::Unchanged)
+    }
+
+    /// Returns the unified diffs of every changed file as a single patch.
+    #[must_use]
+    pub fn patch(&self) -> String {
+        self.files.iter().map(|file| file.diff.as_str()).collect()
+    }
+}
+
+/// The status of a facet when it's audited against what's live in the project's repo.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub enum FacetAuditStatus {
+    /// The facet's files match their recorded hashes or the facet's API settings are still enabled.
+    Compliant,
+    /// At least one of the facet's files no longer matches its recorded hash.
+    Drifted,
+    /// At least one of the facet's files is gone from the repo or the facet's API settings are no longer
+    /// enabled.
+    Missing,
+    /// The facet couldn't be checked, e.g. because there's no way to verify its API settings.
+    Unverified,
+}
+
+/// The result of auditing a single facet.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct FacetAudit {
+    /// The type of the facet.
+    pub facet_type: SupportedFacetType,
+    /// The status of the facet.
+    pub status: FacetAuditStatus,
+    /// The paths of the facet's files that caused the status, relative to the root of the source.
+    pub files: Vec<String>,
+}
+
+/// The result of auditing every facet of a project against what's live in the project's repo.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct ProjectAudit {
+    /// The URL of the audited project's repo.
+    pub project_url: String,
+    /// The audit results for each of the project's facets.
+    pub facets: Vec<FacetAudit>,
+    /// The audit results for each finding allowlisted by the project's scan policy.
+    #[serde(default)]
+    pub allowlist: Vec<AllowlistAudit>,
+    /// The current status of the project's `OpenSSF` Best Practices Badge, or `None` if the project doesn't
+    /// have the `BestPracticesBadge` facet or the status couldn't be fetched.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub best_practices_badge: Option<BestPracticesBadge>,
+}
+
+impl ProjectAudit {
+    /// Returns true if every facet of the project is compliant and every allowlisted finding is still
+    /// excepted.
+    #[must_use]
+    pub fn is_compliant(&self) -> bool {
+        self.facets
+            .iter()
+            .all(|f| f.status == FacetAuditStatus::Compliant)
+            && self
+                .allowlist
+                .iter()
+                .all(|a| a.status == AllowlistAuditStatus::Active)
+    }
+}
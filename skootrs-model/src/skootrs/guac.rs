@@ -0,0 +1,154 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `guac` module provides where the `GUACForwardingConfig` facet forwards a project's SBOMs and SLSA
+//! attestations to. The endpoint comes from the config and can be overridden for a single project with the
+//! options the facet is added with.
+
+#![allow(clippy::module_name_repetitions)]
+
+use std::collections::BTreeMap;
+
+use schemars::{schema::SchemaObject, schema_for, JsonSchema};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+
+use super::SkootError;
+
+/// Where the `GUACForwardingConfig` facet forwards the SBOMs and SLSA attestations of a project's releases.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(default)]
+pub struct GuacForwardingConfig {
+    /// The GUAC GraphQL endpoint the documents are ingested into, e.g. `https://guac.example.com/query`.
+    pub gql_endpoint: Option<String>,
+    /// The name of the Github Actions secret holding the bearer token sent to the endpoint, or `None` if
+    /// the endpoint doesn't need one.
+    pub auth_token_secret: Option<String>,
+    /// The version of the GUAC image that ingests the documents.
+    pub guac_version: String,
+}
+
+impl Default for GuacForwardingConfig {
+    fn default() -> Self {
+        Self {
+            gql_endpoint: None,
+            auth_token_secret: None,
+            guac_version: "v0.8.0".to_string(),
+        }
+    }
+}
+
+impl GuacForwardingConfig {
+    /// Returns the config with the options a facet was added with applied on top, e.g. a project that
+    /// forwards to a team's own GUAC instance.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an option isn't part of the config or has the wrong type.
+    pub fn with_options(&self, options: &BTreeMap<String, Value>) -> Result<Self, SkootError> {
+        super::registry::with_options(self, options)
+    }
+
+    /// Returns the options schema of the `GUACForwardingConfig` facet, which lets each part of the config be
+    /// overridden when the facet is added.
+    #[must_use]
+    pub fn options_schema() -> SchemaObject {
+        schema_for!(Self).schema
+    }
+
+    /// Returns the endpoint the documents are forwarded to, after checking the config can be safely written
+    /// into a workflow.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no endpoint is configured, the endpoint isn't an http(s) URL, or the secret name
+    /// or version contain characters that aren't allowed in them.
+    pub fn endpoint(&self) -> Result<&str, SkootError> {
+        let Some(endpoint) = self.gql_endpoint.as_deref() else {
+            return Err("guac_forwarding.gql_endpoint has to be set to forward to GUAC".into());
+        };
+        if !(endpoint.starts_with("https://") || endpoint.starts_with("http://"))
+            || endpoint
+                .chars()
+                .any(|c| c.is_whitespace() || matches!(c, '\'' | '"' | '$' | '`' | '\\'))
+        {
+            return Err(format!("{endpoint} is not a valid GUAC GraphQL endpoint").into());
+        }
+        if let Some(secret) = &self.auth_token_secret {
+            let valid = secret
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                && secret
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_');
+            if !valid {
+                return Err(format!("{secret} is not a valid Github Actions secret name").into());
+            }
+        }
+        if !self
+            .guac_version
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+        {
+            return Err(format!("{} is not a valid GUAC version", self.guac_version).into());
+        }
+        Ok(endpoint)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn test_endpoint() {
+        assert!(GuacForwardingConfig::default().endpoint().is_err());
+
+        let config = GuacForwardingConfig::default()
+            .with_options(&BTreeMap::from([
+                (
+                    "gql_endpoint".to_string(),
+                    Value::from("https://guac.example.com/query"),
+                ),
+                ("auth_token_secret".to_string(), Value::from("GUAC_TOKEN")),
+            ]))
+            .unwrap();
+        assert_eq!(config.endpoint().unwrap(), "https://guac.example.com/query");
+        assert_eq!(config.guac_version, "v0.8.0");
+
+        for options in [
+            ("gql_endpoint", "guac.example.com/query"),
+            (
+                "gql_endpoint",
+                "https://guac.example.com/query\n  run: whoami",
+            ),
+            ("auth_token_secret", "GUAC TOKEN"),
+            ("auth_token_secret", "1TOKEN"),
+            ("guac_version", "latest; whoami"),
+        ] {
+            let invalid = config
+                .with_options(&BTreeMap::from([(
+                    options.0.to_string(),
+                    Value::from(options.1),
+                )]))
+                .unwrap();
+            assert!(invalid.endpoint().is_err(), "{options:?}");
+        }
+    }
+}
@@ -28,7 +28,9 @@ use utoipa::ToSchema;
 /// This is used to provide mechanism for mapping stuff like controls to elements
 /// of the project. This makes it easier to audit the project against some set of Security
 /// requirements.
-#[derive(Serialize, Deserialize, Clone, Debug, EnumString, VariantNames, Display)]
+#[derive(
+    Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash, EnumString, VariantNames, Display,
+)]
 #[cfg_attr(feature = "openapi", derive(ToSchema))]
 pub enum Label {
     /// S2C2F Requirement SCA-1
@@ -51,12 +53,105 @@ pub enum Label {
     /// SLSA Build Level 3
     SLSABuildLevel3,
 
+    /// `OpenSSF` Scorecard check `Branch-Protection`
+    ScorecardBranchProtection,
+    /// `OpenSSF` Scorecard check `CII-Best-Practices`
+    ScorecardCIIBestPractices,
+    /// `OpenSSF` Scorecard check `Code-Review`
+    ScorecardCodeReview,
+    /// `OpenSSF` Scorecard check `Dependency-Update-Tool`
+    ScorecardDependencyUpdateTool,
+    /// `OpenSSF` Scorecard check `Fuzzing`
+    ScorecardFuzzing,
+    /// `OpenSSF` Scorecard check `License`
+    ScorecardLicense,
+    /// `OpenSSF` Scorecard check `Packaging`
+    ScorecardPackaging,
+    /// `OpenSSF` Scorecard check `Pinned-Dependencies`
+    ScorecardPinnedDependencies,
+    /// `OpenSSF` Scorecard check `SAST`
+    ScorecardSAST,
+    /// `OpenSSF` Scorecard check `Security-Policy`
+    ScorecardSecurityPolicy,
+    /// `OpenSSF` Scorecard check `Signed-Releases`
+    ScorecardSignedReleases,
+
     /// Custom label allow extensibility for end users to define their own labels
     Custom(String),
 }
 
+impl Label {
+    /// Returns whether the label matches a query, e.g. `slsa` or `build` match `SLSABuildLevel3`. Queries
+    /// match any part of a label regardless of case, and custom labels are matched on their value.
+    #[must_use]
+    pub fn matches(&self, query: &str) -> bool {
+        let name = match self {
+            Self::Custom(label) => label.clone(),
+            _ => self.to_string(),
+        };
+        name.to_lowercase().contains(&query.to_lowercase())
+    }
+
+    /// Returns the label for an `OpenSSF` Scorecard check, e.g. `ScorecardBranchProtection` for
+    /// `Branch-Protection`, or `None` if no label covers the check.
+    #[must_use]
+    pub fn for_scorecard_check(check: &str) -> Option<Self> {
+        match check {
+            "Branch-Protection" => Some(Self::ScorecardBranchProtection),
+            "CII-Best-Practices" => Some(Self::ScorecardCIIBestPractices),
+            "Code-Review" => Some(Self::ScorecardCodeReview),
+            "Dependency-Update-Tool" => Some(Self::ScorecardDependencyUpdateTool),
+            "Fuzzing" => Some(Self::ScorecardFuzzing),
+            "License" => Some(Self::ScorecardLicense),
+            "Packaging" => Some(Self::ScorecardPackaging),
+            "Pinned-Dependencies" => Some(Self::ScorecardPinnedDependencies),
+            "SAST" => Some(Self::ScorecardSAST),
+            "Security-Policy" => Some(Self::ScorecardSecurityPolicy),
+            "Signed-Releases" => Some(Self::ScorecardSignedReleases),
+            _ => None,
+        }
+    }
+}
+
 /// A trait for getting the label from a project element.
 pub trait Labeled {
     /// Get the labels for the project element.
     fn labels(&self) -> Vec<Label>;
+
+    /// Returns whether each of the queries matches one of the element's labels. Every element matches when
+    /// there are no queries.
+    fn matches_labels(&self, queries: &[String]) -> bool {
+        let labels = self.labels();
+        queries
+            .iter()
+            .all(|query| labels.iter().any(|label| label.matches(query)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Element(Vec<Label>);
+
+    impl Labeled for Element {
+        fn labels(&self) -> Vec<Label> {
+            self.0.clone()
+        }
+    }
+
+    #[test]
+    fn test_matches_labels() {
+        let element = Element(vec![
+            Label::SLSABuildLevel3,
+            Label::ScorecardSignedReleases,
+            Label::Custom("team-payments".to_string()),
+        ]);
+        assert!(element.matches_labels(&[]));
+        assert!(element.matches_labels(&["slsa".to_string()]));
+        assert!(element.matches_labels(&["BUILD".to_string(), "scorecard".to_string()]));
+        assert!(element.matches_labels(&["payments".to_string()]));
+        assert!(!element.matches_labels(&["slsa".to_string(), "fuzzing".to_string()]));
+        assert!(!element.matches_labels(&["custom".to_string()]));
+    }
 }
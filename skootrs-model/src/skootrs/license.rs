@@ -0,0 +1,169 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `license` module provides the licenses the `License` facet can license a project under, identified by
+//! their SPDX identifiers.
+
+#![allow(clippy::module_name_repetitions)]
+
+use std::collections::BTreeMap;
+
+use schemars::{schema::SchemaObject, schema_for, JsonSchema};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+#[cfg(feature = "openapi")]
+use utoipa::ToSchema;
+
+use super::SkootError;
+
+/// A license the `License` facet can license a project under. Each license is (de)serialized as its SPDX
+/// identifier, e.g. `MIT`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, JsonSchema)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub enum SpdxLicense {
+    /// The Apache License 2.0.
+    #[default]
+    #[serde(rename = "Apache-2.0")]
+    Apache2,
+    /// The MIT License.
+    #[serde(rename = "MIT")]
+    Mit,
+    /// The BSD 2-Clause "Simplified" License.
+    #[serde(rename = "BSD-2-Clause")]
+    Bsd2Clause,
+    /// The BSD 3-Clause "New" or "Revised" License.
+    #[serde(rename = "BSD-3-Clause")]
+    Bsd3Clause,
+    /// The ISC License.
+    #[serde(rename = "ISC")]
+    Isc,
+    /// The Mozilla Public License 2.0.
+    #[serde(rename = "MPL-2.0")]
+    Mpl2,
+    /// The GNU General Public License v3.0 only. `GPL-3.0` is accepted too since it's the deprecated SPDX
+    /// identifier of the same license.
+    #[serde(rename = "GPL-3.0-only", alias = "GPL-3.0")]
+    Gpl3,
+    /// The GNU Affero General Public License v3.0 only. `AGPL-3.0` is accepted too since it's the deprecated
+    /// SPDX identifier of the same license.
+    #[serde(rename = "AGPL-3.0-only", alias = "AGPL-3.0")]
+    Agpl3,
+}
+
+impl SpdxLicense {
+    /// Returns the SPDX identifier of the license.
+    #[must_use]
+    pub const fn spdx_id(&self) -> &'static str {
+        match self {
+            Self::Apache2 => "Apache-2.0",
+            Self::Mit => "MIT",
+            Self::Bsd2Clause => "BSD-2-Clause",
+            Self::Bsd3Clause => "BSD-3-Clause",
+            Self::Isc => "ISC",
+            Self::Mpl2 => "MPL-2.0",
+            Self::Gpl3 => "GPL-3.0-only",
+            Self::Agpl3 => "AGPL-3.0-only",
+        }
+    }
+
+    /// Returns the name of the license, e.g. `MIT License`.
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Apache2 => "Apache License 2.0",
+            Self::Mit => "MIT License",
+            Self::Bsd2Clause => "BSD 2-Clause License",
+            Self::Bsd3Clause => "BSD 3-Clause License",
+            Self::Isc => "ISC License",
+            Self::Mpl2 => "Mozilla Public License 2.0",
+            Self::Gpl3 => "GNU General Public License v3.0",
+            Self::Agpl3 => "GNU Affero General Public License v3.0",
+        }
+    }
+
+    /// Returns the key Github knows the license by, which is what a repo is created with to show it.
+    #[must_use]
+    pub const fn github_key(&self) -> &'static str {
+        match self {
+            Self::Apache2 => "apache-2.0",
+            Self::Mit => "mit",
+            Self::Bsd2Clause => "bsd-2-clause",
+            Self::Bsd3Clause => "bsd-3-clause",
+            Self::Isc => "isc",
+            Self::Mpl2 => "mpl-2.0",
+            Self::Gpl3 => "gpl-3.0",
+            Self::Agpl3 => "agpl-3.0",
+        }
+    }
+}
+
+/// The license the `License` facet licenses projects under.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, JsonSchema)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(default)]
+pub struct LicenseConfig {
+    /// The SPDX identifier of the license, e.g. `MIT`.
+    pub spdx_id: SpdxLicense,
+}
+
+impl LicenseConfig {
+    /// Returns the config with the options a facet was added with applied on top, e.g. a project that's
+    /// licensed under the MIT License.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the license isn't one of the supported SPDX identifiers.
+    pub fn with_options(&self, options: &BTreeMap<String, Value>) -> Result<Self, SkootError> {
+        super::registry::with_options(self, options)
+    }
+
+    /// Returns the options schema of the `License` facet, which lets the license be chosen when the facet is
+    /// added.
+    #[must_use]
+    pub fn options_schema() -> SchemaObject {
+        schema_for!(Self).schema
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn test_license_config() {
+        assert_eq!(LicenseConfig::default().spdx_id, SpdxLicense::Apache2);
+
+        let config = LicenseConfig::default()
+            .with_options(&BTreeMap::from([(
+                "spdx_id".to_string(),
+                Value::from("AGPL-3.0"),
+            )]))
+            .unwrap();
+        assert_eq!(config.spdx_id, SpdxLicense::Agpl3);
+        assert_eq!(config.spdx_id.spdx_id(), "AGPL-3.0-only");
+        assert_eq!(
+            serde_json::to_value(config).unwrap(),
+            serde_json::json!({ "spdx_id": "AGPL-3.0-only" })
+        );
+
+        assert!(LicenseConfig::default()
+            .with_options(&BTreeMap::from([(
+                "spdx_id".to_string(),
+                Value::from("WTFPL"),
+            )]))
+            .is_err());
+    }
+}
@@ -0,0 +1,340 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `listing` module provides the filters, sort orders and pages for listing the projects Skootrs tracks,
+//! which fleets of hundreds of projects can't be listed all at once, and searching them.
+
+#![allow(clippy::module_name_repetitions)]
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+#[cfg(feature = "openapi")]
+use utoipa::ToSchema;
+
+use super::{facet::SupportedFacetType, InitializedProject, InitializedRepo};
+
+/// The number of projects on a page if the limit isn't given.
+pub const DEFAULT_PAGE_LIMIT: usize = 50;
+
+/// The most projects a page can have.
+pub const MAX_PAGE_LIMIT: usize = 500;
+
+/// Which projects are listed. A project is listed if it matches every filter that's set.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct ProjectFilter {
+    /// Only lists projects with a module in this ecosystem, e.g. `Go`. Matched case insensitively.
+    pub ecosystem: Option<String>,
+    /// Only lists projects in this Github organization or user, or Bitbucket workspace.
+    pub organization: Option<String>,
+    /// Only lists projects with a facet of this type.
+    pub facet_type: Option<SupportedFacetType>,
+}
+
+impl ProjectFilter {
+    /// Returns true if the filter needs a project's state to match it, rather than only its repo URL.
+    #[must_use]
+    pub const fn needs_state(&self) -> bool {
+        self.ecosystem.is_some() || self.facet_type.is_some()
+    }
+
+    /// Returns true if a project's repo matches the filters that only need the repo.
+    #[must_use]
+    pub fn matches_repo(&self, repo: &InitializedRepo) -> bool {
+        self.organization.as_ref().is_none_or(|organization| {
+            repo.organization()
+                .is_some_and(|owner| owner.eq_ignore_ascii_case(organization))
+        })
+    }
+
+    /// Returns true if a project matches every filter.
+    #[must_use]
+    pub fn matches(&self, project: &InitializedProject) -> bool {
+        let ecosystem = self.ecosystem.as_ref().is_none_or(|ecosystem| {
+            project
+                .modules
+                .iter()
+                .any(|module| module.ecosystem.name().eq_ignore_ascii_case(ecosystem))
+        });
+        let facet_type = self.facet_type.as_ref().is_none_or(|facet_type| {
+            project
+                .facets
+                .values()
+                .any(|facet| facet.facet_type() == *facet_type)
+        });
+        ecosystem && facet_type && self.matches_repo(&project.repo)
+    }
+}
+
+/// The order projects are listed in.
+#[derive(
+    Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, EnumString, Display,
+)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum ProjectSort {
+    /// By repo URL.
+    #[default]
+    Url,
+    /// Oldest first, by when the project was created.
+    Oldest,
+    /// Newest first, by when the project was created.
+    Newest,
+}
+
+impl ProjectSort {
+    /// Returns true if the order needs the projects' states, rather than only their repo URLs.
+    #[must_use]
+    pub const fn needs_state(self) -> bool {
+        !matches!(self, Self::Url)
+    }
+
+    /// Sorts projects by their repo URL and creation date. Projects that didn't record when they were created
+    /// come last in either order of creation.
+    pub fn sort(self, projects: &mut [ProjectSummary]) {
+        projects.sort_by(|a, b| a.url.cmp(&b.url));
+        match self {
+            Self::Url => {}
+            Self::Oldest => {
+                projects.sort_by_key(|project| (project.created_at.is_none(), project.created_at));
+            }
+            Self::Newest => projects.sort_by_key(|project| {
+                (
+                    project.created_at.is_none(),
+                    std::cmp::Reverse(project.created_at),
+                )
+            }),
+        }
+    }
+}
+
+/// A project in a listing.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct ProjectSummary {
+    /// The URL of the project's repo.
+    pub url: String,
+    /// The name of the project, or `None` if its state couldn't be fetched.
+    pub name: Option<String>,
+    /// The organization, user or workspace the project's repo is in, or `None` for a generic git remote.
+    pub organization: Option<String>,
+    /// The ecosystems of the project's modules, e.g. `Go`.
+    pub ecosystems: Vec<String>,
+    /// The types of the project's facets, sorted.
+    pub facet_types: Vec<SupportedFacetType>,
+    /// When the project was created, or `None` for projects recorded before the creation date was.
+    pub created_at: Option<DateTime<Utc>>,
+    /// Why the project's state couldn't be fetched, or `None` if it was.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ProjectSummary {
+    /// Returns the summary of a project with only what its repo URL tells, for stores that only record the
+    /// URL.
+    #[must_use]
+    pub fn from_url(url: String) -> Self {
+        let organization = InitializedRepo::try_from(url.clone())
+            .ok()
+            .and_then(|repo| repo.organization());
+        Self {
+            url,
+            name: None,
+            organization,
+            ecosystems: vec![],
+            facet_types: vec![],
+            created_at: None,
+            error: None,
+        }
+    }
+
+    /// Returns the summary of a project whose state couldn't be fetched, with what its repo URL tells.
+    #[must_use]
+    pub fn unavailable(url: String, error: String) -> Self {
+        Self {
+            error: Some(error),
+            ..Self::from_url(url)
+        }
+    }
+
+    /// Returns true if every word of a search query is part of the project's repo URL, name, organization,
+    /// ecosystems or facet types, regardless of case. An empty query matches every project.
+    #[must_use]
+    pub fn matches(&self, query: &str) -> bool {
+        let fields = std::iter::once(&self.url)
+            .chain(&self.name)
+            .chain(&self.organization)
+            .chain(&self.ecosystems)
+            .map(|field| field.to_lowercase())
+            .chain(
+                self.facet_types
+                    .iter()
+                    .map(|facet_type| facet_type.to_string().to_lowercase()),
+            )
+            .collect::<Vec<_>>();
+        query.split_whitespace().all(|word| {
+            let word = word.to_lowercase();
+            fields.iter().any(|field| field.contains(&word))
+        })
+    }
+}
+
+impl From<&InitializedProject> for ProjectSummary {
+    fn from(project: &InitializedProject) -> Self {
+        let mut facet_types = project
+            .facets
+            .values()
+            .map(super::facet::InitializedFacet::facet_type)
+            .collect::<Vec<_>>();
+        facet_types.sort_by_key(ToString::to_string);
+        facet_types.dedup();
+        Self {
+            url: project.repo.full_url(),
+            name: Some(project.name.clone()),
+            organization: project.repo.organization(),
+            ecosystems: project
+                .modules
+                .iter()
+                .map(|module| module.ecosystem.name().to_string())
+                .collect(),
+            facet_types,
+            created_at: project.created_at,
+            error: None,
+        }
+    }
+}
+
+/// A page of a project listing.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct ProjectPage {
+    /// The projects on the page.
+    pub projects: Vec<ProjectSummary>,
+    /// The number of projects matching the filters, across every page.
+    pub total: usize,
+    /// The number of matching projects before the page.
+    pub offset: usize,
+    /// The most projects the page can have.
+    pub limit: usize,
+    /// The offset of the next page, or `None` if this is the last one.
+    pub next_offset: Option<usize>,
+}
+
+impl ProjectPage {
+    /// Returns the page of `items` starting at `offset` with at most `limit` of them, which is clamped to
+    /// `MAX_PAGE_LIMIT`, along with the page to fill in with the items' summaries.
+    #[must_use]
+    pub fn paginate<T>(items: Vec<T>, offset: usize, limit: usize) -> (Vec<T>, Self) {
+        let limit = limit.clamp(1, MAX_PAGE_LIMIT);
+        let total = items.len();
+        let page = items
+            .into_iter()
+            .skip(offset)
+            .take(limit)
+            .collect::<Vec<_>>();
+        let end = offset.saturating_add(page.len());
+        (
+            page,
+            Self {
+                projects: vec![],
+                total,
+                offset,
+                limit,
+                next_offset: (end < total).then_some(end),
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    fn summary(url: &str, created_at: Option<&str>) -> ProjectSummary {
+        ProjectSummary {
+            created_at: created_at.map(|date| date.parse().unwrap()),
+            ..ProjectSummary::from_url(url.to_string())
+        }
+    }
+
+    #[test]
+    fn test_matches_repo() {
+        let repo = InitializedRepo::try_from("https://github.com/my-org/api".to_string()).unwrap();
+        assert!(ProjectFilter::default().matches_repo(&repo));
+        let filter = ProjectFilter {
+            organization: Some("My-Org".to_string()),
+            ..Default::default()
+        };
+        assert!(filter.matches_repo(&repo));
+        assert!(!filter.matches_repo(
+            &InitializedRepo::try_from("https://github.com/other-org/api".to_string()).unwrap()
+        ));
+        assert!(!filter.needs_state());
+    }
+
+    #[test]
+    fn test_matches() {
+        let project = ProjectSummary {
+            name: Some("payments".to_string()),
+            ecosystems: vec!["Go".to_string()],
+            facet_types: vec![SupportedFacetType::SecurityPolicy],
+            ..ProjectSummary::from_url("https://github.com/my-org/payments-api".to_string())
+        };
+        assert!(project.matches(""));
+        assert!(project.matches("payments"));
+        assert!(project.matches("MY-ORG go"));
+        assert!(project.matches("securitypolicy"));
+        assert!(!project.matches("payments maven"));
+        assert!(!project.matches("other-org"));
+    }
+
+    #[test]
+    fn test_sort() {
+        let mut projects = vec![
+            summary("https://github.com/org/c", Some("2024-03-01T00:00:00Z")),
+            summary("https://github.com/org/a", None),
+            summary("https://github.com/org/b", Some("2024-01-01T00:00:00Z")),
+        ];
+        let urls = |projects: &[ProjectSummary]| {
+            projects
+                .iter()
+                .map(|project| project.url.rsplit('/').next().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+        ProjectSort::Url.sort(&mut projects);
+        assert_eq!(urls(&projects), vec!["a", "b", "c"]);
+        ProjectSort::Oldest.sort(&mut projects);
+        assert_eq!(urls(&projects), vec!["b", "c", "a"]);
+        ProjectSort::Newest.sort(&mut projects);
+        assert_eq!(urls(&projects), vec!["c", "b", "a"]);
+    }
+
+    #[test]
+    fn test_paginate() {
+        let (items, page) = ProjectPage::paginate((0..5).collect(), 2, 2);
+        assert_eq!(items, vec![2, 3]);
+        assert_eq!((page.total, page.next_offset), (5, Some(4)));
+        let (items, page) = ProjectPage::paginate((0..5).collect(), 4, 2);
+        assert_eq!(items, vec![4]);
+        assert_eq!(page.next_offset, None);
+        let (items, page) = ProjectPage::paginate((0..5).collect::<Vec<_>>(), 9, 0);
+        assert_eq!(items, Vec::<i32>::new());
+        assert_eq!((page.limit, page.next_offset), (1, None));
+    }
+}
@@ -13,10 +13,41 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod attestation;
+pub mod audit_log;
+pub mod best_practices;
+pub mod bitbucket;
+pub mod branch_protection;
+pub mod change_request;
+pub mod code_review;
+pub mod compliance;
+pub mod container;
+pub mod contributing;
+pub mod dependency_updates;
+pub mod error;
+pub mod events;
 pub mod facet;
+pub mod guac;
 pub mod label;
+pub mod license;
+pub mod listing;
+pub mod pinning;
+pub mod plan;
+pub mod policy;
+pub mod profile;
+pub mod publishing;
+pub mod registry;
+pub mod report;
+pub mod rollout;
+pub mod ruleset;
+pub mod scan_policy;
+pub mod scorecard;
+pub mod signing;
+pub mod status;
+pub mod telemetry;
+pub mod validation;
 
-use std::{collections::HashMap, error::Error, fmt, str::FromStr};
+use std::{collections::HashMap, fmt, str::FromStr, time::Duration};
 
 use serde::{Deserialize, Serialize};
 use strum::{Display, EnumString, VariantNames};
@@ -28,8 +59,7 @@ use self::{
     label::Label,
 };
 
-/// A helper type for the error type used throughout Skootrs. This is a `Box<dyn Error + Send + Sync>`.
-pub type SkootError = Box<dyn Error + Send + Sync>;
+pub use self::error::SkootError;
 
 /// The general structure of the models here is the struct names take the form:
 /// `<Thing>Params` reflecting the parameters for something to be created or initilized, like the parameters
@@ -41,15 +71,19 @@ pub type SkootError = Box<dyn Error + Send + Sync>;
 /// which falls under service.
 // TODO: These categories of structs should be moved to their own modules.
 /// Consts for the supported ecosystems, repos, etc. for convenient use by things like the CLI.
-pub const SUPPORTED_ECOSYSTEMS: [&str; 2] = ["Go", "Maven"];
+pub const SUPPORTED_ECOSYSTEMS: [&str; 4] = ["Go", "Maven", "Python", "Rust"];
 
 /// The set of supported ecosystems.
-#[derive(Serialize, Deserialize, Clone, Debug, EnumString, VariantNames, Default)]
+#[derive(Serialize, Deserialize, Clone, Debug, EnumString, VariantNames, Default, Display)]
 #[cfg_attr(feature = "openapi", derive(ToSchema))]
 pub enum SupportedEcosystems {
     /// The Go ecosystem
     #[default]
     Go,
+    /// The Python ecosystem
+    Python,
+    /// The Rust ecosystem
+    Rust,
     // TODO: Add Maven support back.
     /*
     /// The Maven ecosystem
@@ -65,8 +99,14 @@ pub enum SupportedEcosystems {
 pub struct InitializedProject {
     /// The metadata associated with an Skootrs initilialized source repository.
     pub repo: InitializedRepo,
-    /// The metadata associated with an Skootrs initilialized ecosystem.
-    pub ecosystem: InitializedEcosystem,
+    /// A mirror of the project's repo on another host, e.g. a Github mirror kept for the community and
+    /// scorecard when the project's canonical repo is elsewhere. The canonical repo stays the source of truth
+    /// and is pushed to the mirror.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mirror: Option<InitializedRepo>,
+    /// The modules of the project, each an ecosystem initialized in a directory of the source. Projects with
+    /// a single ecosystem have one module at the root of the source.
+    pub modules: Vec<InitializedModule>,
     /// The metadata associated with an Skootrs initilialized source location.
     pub source: InitializedSource,
     /// The facets associated with the project.
@@ -74,6 +114,60 @@ pub struct InitializedProject {
     // TODO: What to do if there are name collisions?
     /// The name of the project.
     pub name: String,
+    /// The version of Skootrs that last created or updated the project. `None` for projects recorded before
+    /// the version was.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skootrs_version: Option<String>,
+    /// When the project was created. `None` for projects recorded before the creation date was.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl InitializedProject {
+    /// Returns the names of the ecosystems of the project's modules, e.g. `Go, Maven`.
+    #[must_use]
+    pub fn ecosystem_names(&self) -> String {
+        self.modules
+            .iter()
+            .map(|module| module.ecosystem.name())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Returns the repo API facets are applied to. Source facets always go to the canonical repo, but a
+    /// generic git remote has no API, so a project with a Github mirror gets its API facets on the mirror.
+    #[must_use]
+    pub const fn api_repo(&self) -> &InitializedRepo {
+        match &self.mirror {
+            Some(mirror @ InitializedRepo::Github(_)) => mirror,
+            _ => &self.repo,
+        }
+    }
+
+    /// Returns the ID of the project's `OpenSSF` Best Practices Badge, which the `BestPracticesBadge` facet
+    /// records when it's added, or `None` if the project doesn't have the facet.
+    #[must_use]
+    pub fn best_practices_badge_id(&self) -> Option<u64> {
+        let Some(InitializedFacet::APIBundle(facet)) = self
+            .facets
+            .get(&FacetMapKey::Type(SupportedFacetType::BestPracticesBadge))
+        else {
+            return None;
+        };
+        facet.apis.iter().find_map(|api| {
+            serde_json::from_str::<best_practices::BestPracticesBadge>(&api.response)
+                .ok()
+                .map(|badge| badge.id)
+        })
+    }
+
+    /// Returns true if the project has the `ArtifactSigning` facet, so its README and SECURITY-INSIGHTS tell
+    /// consumers how to verify its releases.
+    #[must_use]
+    pub fn signs_artifacts(&self) -> bool {
+        self.facets
+            .contains_key(&FacetMapKey::Type(SupportedFacetType::ArtifactSigning))
+    }
 }
 
 /// A helper enum for how a facet can be pulled from a `HashMap`
@@ -97,7 +191,7 @@ impl TryFrom<String> for FacetMapKey {
         }
         match parts.first() {
             Some(&"Name") => Ok(Self::Name(parts[1].to_string())),
-            Some(&"Type") => Ok(Self::Type(parts[1].parse()?)),
+            Some(&"Type") => Ok(Self::Type(parts[1].parse().map_err(SkootError::other)?)),
             _ => Err("Invalid facet map key".into()),
         }
     }
@@ -122,7 +216,7 @@ impl FromStr for FacetMapKey {
         }
         match parts.first() {
             Some(&"Name") => Ok(Self::Name(parts[1].to_string())),
-            Some(&"Type") => Ok(Self::Type(parts[1].parse()?)),
+            Some(&"Type") => Ok(Self::Type(parts[1].parse().map_err(SkootError::other)?)),
             _ => Err("Invalid facet map key".into()),
         }
     }
@@ -145,10 +239,79 @@ pub struct ProjectCreateParams {
     pub name: String,
     /// The parameters for creating the repository for the project.
     pub repo_params: RepoCreateParams,
-    /// The parameters for initializing the ecosystem for the project.
-    pub ecosystem_params: EcosystemInitializeParams,
+    /// The parameters for initializing the project's modules, each an ecosystem in a directory of the source,
+    /// e.g. a Go service and a Maven library in one repo. A single ecosystem is initialized at the root of the
+    /// source.
+    #[serde(deserialize_with = "deserialize_modules")]
+    pub ecosystem_params: Vec<ModuleInitializeParams>,
     /// The parameters for initializing the source code for the project.
     pub source_params: SourceInitializeParams,
+    /// Whether the repo and local clone created so far are deleted if initialization fails partway through.
+    /// They are left in place by default so the failure can be looked into.
+    #[serde(default)]
+    pub rollback_on_failure: bool,
+}
+
+/// The parameters for creating several projects at once, e.g. from a manifest of an organization's projects.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct ProjectBatchCreateParams {
+    /// The projects to create.
+    pub projects: Vec<ProjectCreateParams>,
+}
+
+/// The result of creating several projects at once. A project that fails to be created is recorded instead of
+/// stopping the other creations.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct ProjectBatchCreate {
+    /// The projects that were created.
+    pub created: Vec<InitializedProject>,
+    /// The projects that couldn't be created.
+    pub failed: Vec<ProjectBatchCreateFailure>,
+}
+
+/// A project that couldn't be created as part of a batch.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct ProjectBatchCreateFailure {
+    /// The name of the project.
+    pub name: String,
+    /// Why the project couldn't be created.
+    pub error: String,
+}
+
+/// A step of initializing a project, in the order the steps run.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Display)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub enum InitializationStep {
+    /// The project's repo was created, or an existing generic git remote was checked.
+    #[strum(to_string = "created the repo")]
+    RepoCreated,
+    /// The repo was cloned to the local machine.
+    #[strum(to_string = "cloned the source")]
+    SourceCloned,
+    /// The ecosystem was initialized in the source.
+    #[strum(to_string = "initialized the ecosystem")]
+    EcosystemInitialized,
+    /// The source bundle facets were written to the source.
+    #[strum(to_string = "wrote the source facets")]
+    SourceFacetsInitialized,
+    /// The initial commit was pushed and landed on the remote.
+    #[strum(to_string = "pushed the initial commit")]
+    InitialCommitPushed,
+}
+
+/// What was undone after a project failed to initialize partway through.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct InitializationRollback {
+    /// The steps that completed before initialization failed.
+    pub completed_steps: Vec<InitializationStep>,
+    /// What was undone, e.g. `deleted repo https://github.com/org/repo`.
+    pub rolled_back: Vec<String>,
+    /// What couldn't be undone and has to be cleaned up by hand, along with why.
+    pub failures: Vec<String>,
 }
 
 /// The parameters for updating a project.
@@ -157,6 +320,9 @@ pub struct ProjectCreateParams {
 pub struct ProjectUpdateParams {
     /// The initialized project to update.
     pub initialized_project: InitializedProject,
+    /// Limits the update to these facet types. Every facet is updated if this isn't set.
+    #[serde(default)]
+    pub facet_types: Option<Vec<SupportedFacetType>>,
 }
 
 /// The parameters for getting an existing Skootrs project.
@@ -165,6 +331,38 @@ pub struct ProjectUpdateParams {
 pub struct ProjectGetParams {
     /// The URL of the Skootrs project to get.
     pub project_url: String,
+    /// Where the project's state and facet files are read from.
+    #[serde(default)]
+    pub read_strategy: ReadStrategy,
+}
+
+/// Where a project's state and facet files are read from.
+#[derive(
+    Serialize,
+    Deserialize,
+    Clone,
+    Copy,
+    Debug,
+    EnumString,
+    VariantNames,
+    Default,
+    Display,
+    PartialEq,
+    Eq,
+)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum ReadStrategy {
+    /// Reads the files through the repo host's API.
+    #[default]
+    Remote,
+    /// Reads the files from the local clone under `Config::local_project_path`, after pulling the latest
+    /// changes into it. Fails if there's no local clone.
+    Local,
+    /// Reads the files from the local clone if there is one and it could be updated, and through the repo
+    /// host's API otherwise.
+    Auto,
 }
 
 /// The parameters for listing all the outputs for a Skootrs project.
@@ -175,6 +373,10 @@ pub struct ProjectOutputsListParams {
     pub initialized_project: InitializedProject,
     /// The release to get the outputs for.
     pub release: ProjectReleaseParam,
+    /// The workflow run to get the outputs for instead of the release. The outputs are then the artifacts
+    /// the run uploaded, e.g. SBOMs and scan results generated on push.
+    #[serde(default)]
+    pub workflow_run: Option<WorkflowRunParam>,
 }
 
 /// The parameters for getting a release from a project.
@@ -187,12 +389,22 @@ pub enum ProjectReleaseParam {
     Latest,
 }
 
+/// The parameters for getting a workflow run of a project, whose artifacts are its outputs.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub enum WorkflowRunParam {
+    /// The latest successful run on a branch, e.g. `main`.
+    LatestOn(String),
+    /// A run based on its ID.
+    Id(u64),
+}
+
 impl ProjectReleaseParam {
     /// Returns the tag of the release.
     #[must_use]
     pub fn tag(&self) -> Option<String> {
         match self {
-            Self::Tag(x) => Some(x.to_string()),
+            Self::Tag(x) => Some(x.clone()),
             Self::Latest => None,
         }
     }
@@ -211,6 +423,78 @@ pub struct ProjectOutputGetParams {
     pub project_output: String,
     /// The release to get the output from.
     pub release: ProjectReleaseParam,
+    /// The workflow run to get the output from instead of the release.
+    #[serde(default)]
+    pub workflow_run: Option<WorkflowRunParam>,
+}
+
+/// The parameters for downloading the outputs of a project's release to files.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct ProjectOutputsDownloadParams {
+    /// The initialized project to download the outputs of.
+    pub initialized_project: InitializedProject,
+    /// The release to download the outputs of.
+    pub release: ProjectReleaseParam,
+    /// The workflow run to download the outputs of instead of the release. Each artifact is downloaded as
+    /// the zip archive Github serves it as.
+    #[serde(default)]
+    pub workflow_run: Option<WorkflowRunParam>,
+    /// The name of the output to download. Every output of the release is downloaded if it isn't set.
+    #[serde(default)]
+    pub project_output: Option<String>,
+    /// The directory the outputs are written to, each to a file with the output's name.
+    pub dir: String,
+}
+
+/// An output of a project's release that was downloaded to a file.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct DownloadedOutput {
+    /// The name of the output.
+    pub name: String,
+    /// The path of the file the output was written to.
+    pub path: String,
+    /// The size of the output in bytes.
+    pub size: u64,
+    /// The SHA-256 digest of the output.
+    pub sha256: String,
+    /// Whether the digest was checked against a checksum published with the release. Only the size is
+    /// checked for outputs the release has no checksum for.
+    pub checksum_verified: bool,
+}
+
+/// The parameters for linking a mirror to a project.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct ProjectMirrorParams {
+    /// The initialized project to link the mirror to.
+    pub initialized_project: InitializedProject,
+    /// The URL of the mirror's repo, e.g. `https://github.com/kusaridev/skootrs`. The repo has to exist already.
+    pub mirror_url: String,
+}
+
+/// The parameters for relocating a project whose repo was renamed or transferred.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct ProjectRelocateParams {
+    /// The initialized project to relocate, as it was recorded under its old URL.
+    pub initialized_project: InitializedProject,
+}
+
+/// A project that was relocated because its repo was renamed or transferred.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct ProjectRelocation {
+    /// The URL the project was recorded under.
+    pub old_url: String,
+    /// The URL of the project's repo now.
+    pub new_url: String,
+    /// The project with its new repo recorded.
+    pub project: InitializedProject,
+    /// The pull request the project's new state was proposed in, if changes are proposed as pull requests.
+    #[serde(default)]
+    pub pull_request: Option<String>,
 }
 
 /// The parameters for archiving a project.
@@ -219,17 +503,156 @@ pub struct ProjectOutputGetParams {
 pub struct ProjectArchiveParams {
     /// The initialized project to archive.
     pub initialized_project: InitializedProject,
+    /// Whether to delete the repo's Actions artifacts and caches and disable its scheduled workflows before
+    /// archiving it, so the archived repo stops using runner minutes and storage.
+    #[serde(default)]
+    pub cleanup: bool,
+}
+
+/// The result of archiving a project.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct ProjectArchive {
+    /// The URL of the archived project's repo.
+    pub project_url: String,
+    /// What cleaning up the repo's Actions did, if the cleanup was requested.
+    pub cleanup: Option<ActionsCleanup>,
+}
+
+/// What cleaning up a repo's Actions did. A failure to clean up one item is recorded instead of stopping the
+/// cleanup.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct ActionsCleanup {
+    /// The number of workflow artifacts that were deleted.
+    pub deleted_artifacts: usize,
+    /// The number of Actions caches that were deleted.
+    pub deleted_caches: usize,
+    /// The paths of the scheduled workflows that were disabled.
+    pub disabled_workflows: Vec<String>,
+    /// The items that couldn't be cleaned up, along with why.
+    pub failures: Vec<String>,
+}
+
+/// The parameters for deleting a project.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct ProjectDeleteParams {
+    /// The initialized project to delete.
+    pub initialized_project: InitializedProject,
+    /// Confirms that the project's repo should be permanently deleted. Deletion is refused unless this is set.
+    #[serde(default)]
+    pub confirm: bool,
+}
+
+/// The info for a Skootrs project. This is the project state along with optional live statistics
+/// about the repo, e.g. whether the workflows managed by Skootrs are actually running.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct ProjectInfo {
+    /// The initialized project.
+    pub project: InitializedProject,
+    /// The live statistics for the project's repo if they were requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stats: Option<ProjectStats>,
+}
+
+/// A project's state along with whether its signature was verified.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct SignedProject {
+    /// The initialized project.
+    #[serde(flatten)]
+    pub project: InitializedProject,
+    /// Whether the project's state was signed the way it should be.
+    pub state_signature: StateSignatureStatus,
+}
+
+/// Live statistics about the activity in a project's repo.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct ProjectStats {
+    /// The most recent commit on the default branch.
+    pub last_commit: Option<CommitSummary>,
+    /// The number of open security alerts, e.g. Dependabot and code scanning alerts. This is `None` if
+    /// the alerts couldn't be fetched, usually because they aren't enabled or the token lacks access.
+    pub open_security_alerts: Option<u64>,
+    /// Statistics for the workflow runs over the last 30 days.
+    pub workflow_runs: WorkflowRunStats,
+}
+
+/// A summary of a single commit.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct CommitSummary {
+    /// The SHA of the commit.
+    pub sha: String,
+    /// The commit message.
+    pub message: String,
+    /// When the commit was made.
+    pub date: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Statistics for a set of CI workflow runs.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct WorkflowRunStats {
+    /// The number of workflow runs that have completed.
+    pub completed: u64,
+    /// The number of completed workflow runs that succeeded.
+    pub successful: u64,
+    /// The success rate of the completed workflow runs between 0 and 1. This is `None` when there were
+    /// no completed runs.
+    pub success_rate: Option<f64>,
+}
+
+impl WorkflowRunStats {
+    /// Builds the workflow run statistics from the conclusions of a set of runs. Runs without a conclusion
+    /// are still in progress and aren't counted.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn from_conclusions<'a>(conclusions: impl IntoIterator<Item = Option<&'a str>>) -> Self {
+        let (completed, successful) = conclusions.into_iter().flatten().fold(
+            (0, 0),
+            |(completed, successful), conclusion| {
+                (
+                    completed + 1,
+                    successful + u64::from(conclusion == "success"),
+                )
+            },
+        );
+        Self {
+            completed,
+            successful,
+            success_rate: (completed > 0).then(|| successful as f64 / completed as f64),
+        }
+    }
 }
 
 /// The set of supported output types
-#[derive(Serialize, Deserialize, Clone, Debug, EnumString, VariantNames, Default, Display)]
+#[derive(
+    Serialize, Deserialize, Clone, Debug, EnumString, VariantNames, Default, Display, PartialEq, Eq,
+)]
 #[cfg_attr(feature = "openapi", derive(ToSchema))]
 pub enum ProjectOutputType {
     #[default]
     /// An output type for an SBOM from a project.
     SBOM,
-    /// An output type for an in-toto attestation from a project.
+    /// An output type for an in-toto file from a project whose statement couldn't be read, e.g. because it
+    /// isn't a DSSE envelope.
     InToto,
+    /// An output type for an in-toto attestation in a DSSE envelope, e.g. `skootrs.intoto.json`, along with
+    /// what it attests to.
+    Attestation(attestation::PredicateType),
+    /// An output type for a package a project publishes to a registry, e.g. a container image.
+    Package,
+    /// An output type for the result of verifying the SLSA provenance of a release.
+    ProvenanceVerification,
+    /// An output type for the SLSA provenance attestation of a release, e.g. `multiple.intoto.jsonl`. Its
+    /// subjects are checked against the release's artifacts before it's returned.
+    SLSAAttestation,
+    /// An output type for a detached signature of a release artifact, e.g. `skootrs.tar.gz.sig`.
+    Signature,
     /// An output type for an unknown output from a project.
     Unknown(String),
     /// An output type for a custom output from a project.
@@ -246,6 +669,34 @@ pub struct ProjectOutput {
     pub output: String,
 }
 
+/// The release asset the `SLSAVerification` facet attaches the result of verifying a release's SLSA provenance
+/// to.
+pub const PROVENANCE_VERIFICATION_FILE: &str = "slsa-verification.json";
+
+/// The result of verifying the SLSA provenance of a release with slsa-verifier.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct ProvenanceVerificationResult {
+    /// The tag of the verified release.
+    pub tag: String,
+    /// Whether the provenance of every artifact of the release verified.
+    pub verified: bool,
+    /// When the release was verified.
+    pub verified_at: chrono::DateTime<chrono::Utc>,
+    /// The artifacts the provenance covers, along with whether each of them verified.
+    pub artifacts: Vec<VerifiedArtifact>,
+}
+
+/// An artifact covered by a release's SLSA provenance.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct VerifiedArtifact {
+    /// The name of the artifact's release asset.
+    pub name: String,
+    /// Whether the artifact matches the provenance. Artifacts missing from the release don't verify.
+    pub verified: bool,
+}
+
 /// A reference to the output of a project.
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[cfg_attr(feature = "openapi", derive(ToSchema))]
@@ -258,6 +709,19 @@ pub struct ProjectOutputReference {
     pub labels: Vec<Label>,
 }
 
+/// The parameters for listing the facets of a project.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct FacetListParams {
+    /// Parameters for first getting the project.
+    #[serde(flatten)]
+    pub project_get_params: ProjectGetParams,
+    /// Only facets with a label matching each of these are listed, e.g. `slsa`. Every facet is listed if
+    /// this is empty.
+    #[serde(default)]
+    pub labels: Vec<String>,
+}
+
 /// The parameters for getting a facet from a project.
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[cfg_attr(feature = "openapi", derive(ToSchema))]
@@ -274,6 +738,10 @@ pub struct FacetGetParams {
 pub enum InitializedRepo {
     /// An initialized Github repository.
     Github(InitializedGithubRepo),
+    /// A repository on any other git remote. Only git itself is used to manage it so API facets are skipped.
+    GenericGit(InitializedGenericGitRepo),
+    /// An initialized Bitbucket Cloud repository.
+    Bitbucket(InitializedBitbucketRepo),
 }
 
 impl InitializedRepo {
@@ -282,6 +750,8 @@ impl InitializedRepo {
     pub fn host_url(&self) -> String {
         match self {
             Self::Github(x) => x.host_url(),
+            Self::GenericGit(x) => x.host_url(),
+            Self::Bitbucket(x) => x.host_url(),
         }
     }
 
@@ -290,6 +760,29 @@ impl InitializedRepo {
     pub fn full_url(&self) -> String {
         match self {
             Self::Github(x) => x.full_url(),
+            Self::GenericGit(x) => x.full_url(),
+            Self::Bitbucket(x) => x.full_url(),
+        }
+    }
+
+    /// Returns the name of the repo.
+    #[must_use]
+    pub fn name(&self) -> String {
+        match self {
+            Self::Github(x) => x.name.clone(),
+            Self::GenericGit(x) => x.name.clone(),
+            Self::Bitbucket(x) => x.name.clone(),
+        }
+    }
+
+    /// Returns the Github organization or user, or the Bitbucket workspace, the repo is in, or `None` for a
+    /// generic git remote.
+    #[must_use]
+    pub fn organization(&self) -> Option<String> {
+        match self {
+            Self::Github(x) => Some(x.organization.get_name()),
+            Self::GenericGit(_) => None,
+            Self::Bitbucket(x) => Some(x.workspace.clone()),
         }
     }
 }
@@ -298,7 +791,20 @@ impl TryFrom<String> for InitializedRepo {
     type Error = SkootError;
 
     fn try_from(value: String) -> Result<Self, Self::Error> {
-        let parts = url::Url::parse(&value)?;
+        // Anything that isn't a Github or Bitbucket URL, including scp-like remotes that aren't URLs at all, is
+        // treated as a generic git remote that is managed with git alone.
+        let Ok(parts) = url::Url::parse(&value) else {
+            return Ok(Self::GenericGit(
+                InitializedGenericGitRepo::from_remote_url(value)?,
+            ));
+        };
+        let is_bitbucket = parts.host() == Some(Host::Domain("bitbucket.org"));
+        if !is_bitbucket && parts.host() != Some(Host::Domain("github.com")) {
+            return Ok(Self::GenericGit(
+                InitializedGenericGitRepo::from_remote_url(value)?,
+            ));
+        }
+
         let path_segments = parts
             .path_segments()
             .map_or(Vec::new(), Iterator::collect::<Vec<_>>);
@@ -308,20 +814,21 @@ impl TryFrom<String> for InitializedRepo {
 
         let organization = *path_segments
             .first()
-            .ok_or(format!("Invalid repo URL: {value}"))?;
+            .ok_or_else(|| format!("Invalid repo URL: {value}"))?;
         let name = *path_segments
             .get(1)
-            .ok_or(format!("Invalid repo URL: {value}"))?;
-        match parts.host() {
-            Some(Host::Domain("github.com")) => {
-                Ok(Self::Github(InitializedGithubRepo {
-                    name: name.to_string(),
-                    // FIXME: This will have issues if this isn't a user repo and in fact an organization user.
-                    organization: GithubUser::User(organization.into()),
-                }))
-            }
-            _ => Err("Unsupported repo host".into()),
+            .ok_or_else(|| format!("Invalid repo URL: {value}"))?;
+        if is_bitbucket {
+            return Ok(Self::Bitbucket(InitializedBitbucketRepo {
+                name: name.trim_end_matches(".git").to_string(),
+                workspace: organization.to_string(),
+            }));
         }
+        Ok(Self::Github(InitializedGithubRepo {
+            name: name.to_string(),
+            // FIXME: This will have issues if this isn't a user repo and in fact an organization user.
+            organization: GithubUser::User(organization.into()),
+        }))
     }
 }
 
@@ -354,6 +861,88 @@ impl InitializedGithubRepo {
     }
 }
 
+/// Represents an initialized Bitbucket Cloud repository.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct InitializedBitbucketRepo {
+    /// The name of the Bitbucket repository, which is also its slug.
+    pub name: String,
+    /// The workspace the Bitbucket repository belongs to.
+    pub workspace: String,
+}
+
+impl InitializedBitbucketRepo {
+    /// Returns the host URL of Bitbucket.
+    #[must_use]
+    pub fn host_url(&self) -> String {
+        "https://bitbucket.org".into()
+    }
+
+    /// Returns the full URL to the Bitbucket repo.
+    #[must_use]
+    pub fn full_url(&self) -> String {
+        format!("{}/{}/{}", self.host_url(), self.workspace, self.name)
+    }
+}
+
+/// Represents an initialized repository on a generic git remote.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct InitializedGenericGitRepo {
+    /// The name of the repository. This is also the name of the directory it's cloned into.
+    pub name: String,
+    /// The URL of the git remote, e.g. `https://git.example.com/team/project.git` or
+    /// `git@git.example.com:team/project.git`.
+    pub remote_url: String,
+}
+
+impl InitializedGenericGitRepo {
+    /// Creates the repo from its remote URL. The name of the repo is the last segment of the URL without
+    /// the `.git` suffix.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name of the repo can't be determined from the URL.
+    pub fn from_remote_url(remote_url: String) -> Result<Self, SkootError> {
+        let name = remote_url
+            .trim_end_matches('/')
+            .rsplit(['/', ':'])
+            .next()
+            .map(|name| name.trim_end_matches(".git"))
+            .filter(|name| !name.is_empty())
+            .ok_or_else(|| format!("Invalid git remote URL: {remote_url}"))?
+            .to_string();
+        Ok(Self { name, remote_url })
+    }
+
+    /// Returns the URL of the host of the git remote. For scp-like remotes, e.g.
+    /// `git@git.example.com:team/project.git`, this is just the host.
+    #[must_use]
+    pub fn host_url(&self) -> String {
+        url::Url::parse(&self.remote_url).map_or_else(
+            |_| {
+                self.remote_url
+                    .split_once(':')
+                    .map_or_else(String::new, |(host, _)| {
+                        host.rsplit('@').next().unwrap_or(host).to_string()
+                    })
+            },
+            |url| {
+                url.host_str().map_or_else(
+                    || url.origin().ascii_serialization(),
+                    |host| format!("{}://{host}", url.scheme()),
+                )
+            },
+        )
+    }
+
+    /// Returns the URL of the git remote.
+    #[must_use]
+    pub fn full_url(&self) -> String {
+        self.remote_url.clone()
+    }
+}
+
 /// Represents an initialized ecosystem. The enum is used to represent the different types of ecosystems
 /// that are supported by Skootrs currently.
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -363,6 +952,23 @@ pub enum InitializedEcosystem {
     Go(InitializedGo),
     /// An initialized Maven ecosystem `InitializedSource`.
     Maven(InitializedMaven),
+    /// An initialized Python ecosystem for `InitializedSource`.
+    Python(InitializedPython),
+    /// An initialized Rust ecosystem for `InitializedSource`.
+    Rust(InitializedRust),
+}
+
+impl InitializedEcosystem {
+    /// Returns the name of the ecosystem, e.g. `Go`.
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Go(_) => "Go",
+            Self::Maven(_) => "Maven",
+            Self::Python(_) => "Python",
+            Self::Rust(_) => "Rust",
+        }
+    }
 }
 
 /// The parameters for creating a repository.
@@ -371,6 +977,55 @@ pub enum InitializedEcosystem {
 pub enum RepoCreateParams {
     /// The parameters for creating a Github repository.
     Github(GithubRepoParams),
+    /// The parameters for using a repository on a generic git remote.
+    GenericGit(GenericGitRepoParams),
+    /// The parameters for creating a Bitbucket Cloud repository.
+    Bitbucket(BitbucketRepoParams),
+}
+
+impl RepoCreateParams {
+    /// Returns the repo these params create. This doesn't create the repo.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the remote URL of a generic git repo is invalid.
+    pub fn initialized_repo(&self) -> Result<InitializedRepo, SkootError> {
+        match self {
+            Self::Github(g) => Ok(InitializedRepo::Github(InitializedGithubRepo {
+                name: g.name.clone(),
+                organization: g.organization.clone(),
+            })),
+            Self::GenericGit(g) => Ok(InitializedRepo::GenericGit(
+                InitializedGenericGitRepo::from_remote_url(g.remote_url.clone())?,
+            )),
+            Self::Bitbucket(b) => Ok(InitializedRepo::Bitbucket(InitializedBitbucketRepo {
+                name: b.name.clone(),
+                workspace: b.workspace.clone(),
+            })),
+        }
+    }
+}
+
+/// The parameters for using a repository on a generic git remote. Skootrs can't create repositories on
+/// arbitrary hosts so the remote must already exist, and should be empty.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct GenericGitRepoParams {
+    /// The URL of the git remote.
+    pub remote_url: String,
+}
+
+/// The parameters for creating a Bitbucket Cloud repository. Repositories are created public, in the
+/// workspace's default project.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct BitbucketRepoParams {
+    /// The name of the Bitbucket repository, which is also its slug.
+    pub name: String,
+    /// The description of the Bitbucket repository.
+    pub description: String,
+    /// The workspace the Bitbucket repository belongs to.
+    pub workspace: String,
 }
 
 /// The parameters for initializing an ecosystem.
@@ -381,6 +1036,172 @@ pub enum EcosystemInitializeParams {
     Go(GoParams),
     /// The parameters for initializing a Maven ecosystem for `InitializedSource`.
     Maven(MavenParams),
+    /// The parameters for initializing a Python ecosystem for `InitializedSource`.
+    Python(PythonParams),
+    /// The parameters for initializing a Rust ecosystem for `InitializedSource`.
+    Rust(RustParams),
+}
+
+impl EcosystemInitializeParams {
+    /// Returns the ecosystem these params initialize. This doesn't initialize the ecosystem.
+    #[must_use]
+    pub fn initialized_ecosystem(&self) -> InitializedEcosystem {
+        match self {
+            Self::Go(g) => InitializedEcosystem::Go(InitializedGo {
+                name: g.name.clone(),
+                host: g.host.clone(),
+            }),
+            Self::Maven(m) => InitializedEcosystem::Maven(InitializedMaven {
+                group_id: m.group_id.clone(),
+                artifact_id: m.artifact_id.clone(),
+            }),
+            Self::Python(p) => InitializedEcosystem::Python(InitializedPython {
+                name: p.name.clone(),
+                package_manager: p.package_manager.clone(),
+            }),
+            Self::Rust(r) => InitializedEcosystem::Rust(InitializedRust {
+                name: r.name.clone(),
+                crates: r.crates.clone(),
+            }),
+        }
+    }
+}
+
+/// The path of the module at the root of a project's source.
+pub const ROOT_MODULE_PATH: &str = ".";
+
+fn root_module_path() -> String {
+    ROOT_MODULE_PATH.to_string()
+}
+
+/// The parameters for initializing a module of a project, i.e. an ecosystem in a directory of the source.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct ModuleInitializeParams {
+    /// The directory the module is initialized in, relative to the root of the source, e.g. `services/api`.
+    /// Defaults to the root of the source.
+    #[serde(default = "root_module_path")]
+    pub path: String,
+    /// The parameters for initializing the module's ecosystem.
+    pub ecosystem: EcosystemInitializeParams,
+}
+
+impl ModuleInitializeParams {
+    /// Returns the module these params initialize. This doesn't initialize the module.
+    #[must_use]
+    pub fn initialized_module(&self) -> InitializedModule {
+        InitializedModule {
+            path: self.path.clone(),
+            ecosystem: self.ecosystem.initialized_ecosystem(),
+        }
+    }
+}
+
+impl From<EcosystemInitializeParams> for ModuleInitializeParams {
+    /// Initializes the ecosystem at the root of the source.
+    fn from(ecosystem: EcosystemInitializeParams) -> Self {
+        Self {
+            path: root_module_path(),
+            ecosystem,
+        }
+    }
+}
+
+/// Deserializes the modules of a project. A single ecosystem, like projects were created with before they
+/// could have several modules, is a module at the root of the source.
+fn deserialize_modules<'de, D>(deserializer: D) -> Result<Vec<ModuleInitializeParams>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Modules {
+        Single(EcosystemInitializeParams),
+        Multiple(Vec<ModuleInitializeParams>),
+    }
+
+    Ok(match Modules::deserialize(deserializer)? {
+        Modules::Single(ecosystem) => vec![ecosystem.into()],
+        Modules::Multiple(modules) => modules,
+    })
+}
+
+/// A module of a project, i.e. an ecosystem initialized in a directory of the source.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct InitializedModule {
+    /// The directory of the module relative to the root of the source, `.` for the root.
+    pub path: String,
+    /// The ecosystem initialized in the module.
+    pub ecosystem: InitializedEcosystem,
+}
+
+impl InitializedModule {
+    /// Returns the module's directory without a leading `./` or any leading or trailing `/`, which is empty
+    /// for the root of the source.
+    #[must_use]
+    pub fn dir(&self) -> &str {
+        let dir = self.path.trim_start_matches("./").trim_matches('/');
+        if dir == ROOT_MODULE_PATH {
+            ""
+        } else {
+            dir
+        }
+    }
+
+    /// Returns true if the module is at the root of the source.
+    #[must_use]
+    pub fn is_root(&self) -> bool {
+        self.dir().is_empty()
+    }
+
+    /// Returns the working copy of the module's directory in the project's source.
+    #[must_use]
+    pub fn source(&self, source: &InitializedSource) -> InitializedSource {
+        if self.is_root() {
+            return source.clone();
+        }
+        InitializedSource {
+            path: format!("{}/{}", source.path.trim_end_matches('/'), self.dir()),
+        }
+    }
+
+    /// Returns a path relative to the module's directory as a path relative to the root of the source, e.g.
+    /// `./` becomes `services/api/`.
+    #[must_use]
+    pub fn relative_path(&self, path: &str) -> String {
+        if self.is_root() {
+            return path.to_string();
+        }
+        format!("{}/{}", self.dir(), path.trim_start_matches("./"))
+    }
+
+    /// Returns the module's directory as an absolute path from the root of the repo, e.g. `/services/api`,
+    /// which is how Dependabot refers to directories.
+    #[must_use]
+    pub fn repo_path(&self) -> String {
+        format!("/{}", self.dir())
+    }
+
+    /// Returns a name for the module that can be used in file names, e.g. `services-api`, or `None` for the
+    /// root of the source.
+    #[must_use]
+    pub fn slug(&self) -> Option<String> {
+        if self.is_root() {
+            return None;
+        }
+        Some(self.dir().replace('/', "-"))
+    }
+}
+
+impl From<InitializedEcosystem> for InitializedModule {
+    /// Returns the ecosystem as a module at the root of the source.
+    fn from(ecosystem: InitializedEcosystem) -> Self {
+        Self {
+            path: root_module_path(),
+            ecosystem,
+        }
+    }
 }
 
 /// The parameter for getting an initialized repository
@@ -407,7 +1228,7 @@ impl GithubUser {
     #[must_use]
     pub fn get_name(&self) -> String {
         match self {
-            Self::User(x) | Self::Organization(x) => x.to_string(),
+            Self::User(x) | Self::Organization(x) => x.clone(),
         }
     }
 }
@@ -422,6 +1243,9 @@ pub struct GithubRepoParams {
     pub description: String,
     /// The organization the Github repository belongs to.
     pub organization: GithubUser,
+    /// The license the Github repository is created with, so Github shows it from the start.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<license::SpdxLicense>,
 }
 
 impl GithubRepoParams {
@@ -467,23 +1291,66 @@ pub struct InitializedSource {
 }
 
 /// Represents the Maven ecosystem.
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
 #[cfg_attr(feature = "openapi", derive(ToSchema))]
 pub struct MavenParams {
     /// The group ID of the Maven project.
     pub group_id: String,
     /// The artifact ID of the Maven project.
     pub artifact_id: String,
+    /// The archetype the project is generated from. Defaults to `maven-archetype-quickstart`.
+    #[serde(default)]
+    pub archetype: MavenArchetype,
+    /// The Java release the project is compiled for, e.g. `21`, or `None` to keep what the archetype
+    /// generates.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub java_version: Option<String>,
+    /// Whether the Maven wrapper is generated, so the project is built with the same version of Maven
+    /// everywhere.
+    #[serde(default)]
+    pub wrapper: bool,
+    /// Whether the pom is hardened: the enforcer plugin fails builds whose dependencies don't converge, and
+    /// Maven fails on dependencies whose checksums don't match.
+    #[serde(default)]
+    pub harden_pom: bool,
+}
+
+/// The coordinates of a Maven archetype.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct MavenArchetype {
+    /// The group ID of the archetype.
+    pub group_id: String,
+    /// The artifact ID of the archetype.
+    pub artifact_id: String,
+    /// The version of the archetype, or `None` for the latest one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+impl Default for MavenArchetype {
+    fn default() -> Self {
+        Self {
+            group_id: "org.apache.maven.archetypes".to_string(),
+            artifact_id: "maven-archetype-quickstart".to_string(),
+            version: None,
+        }
+    }
 }
 
 /// Represents the Go ecosystem.
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
 #[cfg_attr(feature = "openapi", derive(ToSchema))]
 pub struct GoParams {
     /// The name of the Go module.
     pub name: String,
     /// The host of the Go module.
     pub host: String,
+    /// The Go release the module is built with, e.g. `1.22.3`, which is written to its `go.mod` as the
+    /// `toolchain` directive, along with the language version of its minor release as the `go` directive.
+    /// Defaults to the release of the installed `go`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub go_version: Option<String>,
 }
 
 /// Represents an initialized go module.
@@ -514,6 +1381,106 @@ pub struct InitializedMaven {
     pub artifact_id: String,
 }
 
+/// The set of supported Python package managers.
+#[derive(
+    Serialize, Deserialize, Clone, Debug, EnumString, VariantNames, Default, Display, PartialEq, Eq,
+)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub enum PythonPackageManager {
+    /// Plain pip with a PEP 621 `pyproject.toml` built by setuptools.
+    #[default]
+    Pip,
+    /// Poetry with its own `pyproject.toml` sections.
+    Poetry,
+}
+
+/// Represents the Python ecosystem.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct PythonParams {
+    /// The name of the Python distribution.
+    pub name: String,
+    /// The package manager used for the project.
+    pub package_manager: PythonPackageManager,
+}
+
+impl PythonParams {
+    /// Returns the importable package name for the distribution name, e.g. "my-project" becomes "`my_project`".
+    #[must_use]
+    pub fn package(&self) -> String {
+        python_package_name(&self.name)
+    }
+}
+
+/// Represents an initialized Python project.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct InitializedPython {
+    /// The name of the Python distribution.
+    pub name: String,
+    /// The package manager used for the project.
+    pub package_manager: PythonPackageManager,
+}
+
+impl InitializedPython {
+    /// Returns the importable package name for the distribution name, e.g. "my-project" becomes "`my_project`".
+    #[must_use]
+    pub fn package(&self) -> String {
+        python_package_name(&self.name)
+    }
+}
+
+fn python_package_name(name: &str) -> String {
+    name.to_lowercase().replace(['-', '.'], "_")
+}
+
+/// Represents the Rust ecosystem. Projects are always laid out as a Cargo workspace with each crate under
+/// `crates/`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct RustParams {
+    /// The name of the Cargo workspace.
+    pub name: String,
+    /// The crates in the workspace. A single crate named after the workspace is created if this is empty.
+    #[serde(default)]
+    pub crates: Vec<String>,
+}
+
+impl RustParams {
+    /// Returns the names of the crates in the workspace.
+    #[must_use]
+    pub fn members(&self) -> Vec<String> {
+        rust_workspace_members(&self.name, &self.crates)
+    }
+}
+
+/// Represents an initialized Rust project.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct InitializedRust {
+    /// The name of the Cargo workspace.
+    pub name: String,
+    /// The crates in the workspace.
+    #[serde(default)]
+    pub crates: Vec<String>,
+}
+
+impl InitializedRust {
+    /// Returns the names of the crates in the workspace.
+    #[must_use]
+    pub fn members(&self) -> Vec<String> {
+        rust_workspace_members(&self.name, &self.crates)
+    }
+}
+
+fn rust_workspace_members(name: &str, crates: &[String]) -> Vec<String> {
+    if crates.is_empty() {
+        vec![name.to_string()]
+    } else {
+        crates.to_vec()
+    }
+}
+
 impl GoParams {
     /// Returns the module name in the format "{host}/{name}".
     #[must_use]
@@ -522,18 +1489,697 @@ impl GoParams {
     }
 }
 
-/// A set of configuration options for Skootrs.
+/// A set of configuration options for Skootrs. Options that aren't set fall back to their defaults.
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(default)]
 pub struct Config {
     /// The local path to cached projects. This is used by `LocalProjectService` for performing operations locally.
     pub local_project_path: String,
+    /// Org specific values that are injected into facet templates, e.g. `runs_on` for workflow runner labels.
+    /// Only allow-listed variables are accepted.
+    #[serde(default)]
+    pub template_variables: HashMap<String, String>,
+    /// A directory of files that replace the built-in facet content, e.g. a company branded README. Files in
+    /// an ecosystem directory like `python/` only replace the content for that ecosystem.
+    #[serde(default)]
+    pub template_dir: Option<String>,
+    /// A directory of profiles for `skootrs project create --profile`, each a `<name>.yaml` file. Profiles
+    /// in it take precedence over the built-in ones with the same name.
+    #[serde(default)]
+    pub profile_dir: Option<String>,
+    /// The facets that are applied to projects and the order they are applied in.
+    #[serde(default)]
+    pub facet_registry: registry::FacetRegistry,
+    /// The Github token Skootrs uses, usually a reference like `${GITHUB_TOKEN}` or
+    /// `file:/run/secrets/github_token`. Falls back to the `GITHUB_TOKEN` environment variable.
+    #[serde(default)]
+    pub github_token: Option<String>,
+    /// The Github App installation Skootrs authenticates as instead of a token. This takes precedence over
+    /// `github_token`, and is how org-wide automation should authenticate since it isn't tied to a person.
+    #[serde(default)]
+    pub github_app: Option<GithubAppConfig>,
+    /// Which Github credential Skootrs authenticates with. By default it's picked from the ones that are
+    /// configured.
+    #[serde(default)]
+    pub github_auth: GithubAuthMode,
+    /// How Github API calls that fail with a transient error or hit a rate limit are retried.
+    #[serde(default)]
+    pub github_retry: RetryPolicy,
+    /// Which git implementation clones, commits and pushes projects' sources.
+    #[serde(default)]
+    pub git_backend: GitBackend,
+    /// How the changes facets make to a new project's source are split into commits.
+    #[serde(default)]
+    pub commit_grouping: CommitGrouping,
+    /// How the commits Skootrs makes are signed. They aren't signed by default.
+    #[serde(default)]
+    pub commit_signing: CommitSigning,
+    /// How the `.skootrs` state files of projects are signed. They aren't signed by default.
+    #[serde(default)]
+    pub state_signing: StateSigning,
+    /// Where the `CDEvents` Skootrs emits when it changes a project are sent. Events aren't sent anywhere by
+    /// default.
+    #[serde(default)]
+    pub event_sinks: Vec<events::EventSinkConfig>,
+    /// Where the live `OpenSSF` Scorecard results of projects are fetched from.
+    #[serde(default)]
+    pub scorecard: scorecard::ScorecardConfig,
+    /// Where projects' `OpenSSF` Best Practices Badges are looked up.
+    #[serde(default)]
+    pub best_practices: best_practices::BestPracticesConfig,
+    /// How Skootrs reaches the Bitbucket Cloud API for projects hosted on Bitbucket.
+    #[serde(default)]
+    pub bitbucket: bitbucket::BitbucketConfig,
+    /// How changes to the facets of existing projects reach their repos, by default as pull requests.
+    #[serde(default)]
+    pub change_requests: change_request::ChangeRequestConfig,
+    /// Where Skootrs keeps track of the projects it manages.
+    #[serde(default)]
+    pub statestore: StateStoreConfig,
+    /// The options of the daemon.
+    #[serde(default)]
+    pub daemon: DaemonConfig,
+    /// Where traces are exported, by default to a local Jaeger.
+    pub telemetry: telemetry::TelemetryConfig,
+    /// Where the audit log of every change Skootrs makes to projects is written. Nothing is logged by default.
+    pub audit_log: audit_log::AuditLogConfig,
+    /// The values the interactive prompts start from, so the common case is a matter of pressing enter.
+    #[serde(default)]
+    pub defaults: ProjectDefaults,
+    /// How projects' security policies tell people to report vulnerabilities.
+    #[serde(default)]
+    pub security_policy: SecurityPolicyConfig,
+    /// The rules the `BranchProtection` facet enables on projects' branches.
+    #[serde(default)]
+    pub branch_protection: branch_protection::BranchProtectionConfig,
+    /// Who owns projects' code and how many approvals the `CodeReview` facet requires.
+    #[serde(default)]
+    pub code_review: code_review::CodeReviewConfig,
+    /// The license the `License` facet licenses projects under.
+    #[serde(default)]
+    pub license: license::LicenseConfig,
+    /// How the `ContributingPolicy` and `CodeOfConduct` facets tell people to contribute to projects.
+    #[serde(default)]
+    pub contributing: contributing::ContributingConfig,
+    /// Where the `RepoRuleset` facet creates its ruleset and what it's called.
+    #[serde(default)]
+    pub repo_ruleset: ruleset::RepoRulesetConfig,
+    /// Where the `GUACForwardingConfig` facet forwards the SBOMs and SLSA attestations of projects' releases.
+    #[serde(default)]
+    pub guac_forwarding: guac::GuacForwardingConfig,
+    /// Which actions and reusable workflows the `PinnedDependencies` facet lets projects reference by tag.
+    #[serde(default)]
+    pub pinning: pinning::PinningPolicy,
+    /// What the `DependencyUpdateTool` facet has Dependabot keep up to date in projects, and how often.
+    #[serde(default)]
+    pub dependency_updates: dependency_updates::DependencyUpdateConfig,
+    /// Which findings fail the vulnerability scans the `VulnerabilityScanner` facet generates.
+    #[serde(default)]
+    pub vulnerability_scanner: scan_policy::VulnerabilityScannerConfig,
+    /// Where the `PublishPackages` facet publishes projects' container images and Maven artifacts.
+    #[serde(default)]
+    pub publish_packages: publishing::PublishPackagesConfig,
+    /// Where the `Container` facet pushes projects' images to and what they're based on.
+    #[serde(default)]
+    pub container: container::ContainerConfig,
+    /// What the `ArtifactSigning` facet signs along with the assets of projects' releases.
+    #[serde(default)]
+    pub artifact_signing: signing::ArtifactSigningConfig,
+    /// Whether every operation that would change a repo, a local clone or a Github setting is refused. This is
+    /// for exploring a production org with `get`, `list`, `audit` and `verify` without any risk.
+    #[serde(default)]
+    pub read_only: bool,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             local_project_path: "/tmp".into(),
+            template_variables: HashMap::new(),
+            template_dir: None,
+            profile_dir: None,
+            facet_registry: registry::FacetRegistry::default(),
+            github_token: None,
+            github_app: None,
+            github_auth: GithubAuthMode::default(),
+            github_retry: RetryPolicy::default(),
+            git_backend: GitBackend::default(),
+            commit_grouping: CommitGrouping::default(),
+            commit_signing: CommitSigning::default(),
+            state_signing: StateSigning::default(),
+            event_sinks: vec![],
+            scorecard: scorecard::ScorecardConfig::default(),
+            best_practices: best_practices::BestPracticesConfig::default(),
+            bitbucket: bitbucket::BitbucketConfig::default(),
+            change_requests: change_request::ChangeRequestConfig::default(),
+            statestore: StateStoreConfig::default(),
+            daemon: DaemonConfig::default(),
+            telemetry: telemetry::TelemetryConfig::default(),
+            audit_log: audit_log::AuditLogConfig::default(),
+            defaults: ProjectDefaults::default(),
+            security_policy: SecurityPolicyConfig::default(),
+            branch_protection: branch_protection::BranchProtectionConfig::default(),
+            code_review: code_review::CodeReviewConfig::default(),
+            license: license::LicenseConfig::default(),
+            contributing: contributing::ContributingConfig::default(),
+            repo_ruleset: ruleset::RepoRulesetConfig::default(),
+            guac_forwarding: guac::GuacForwardingConfig::default(),
+            pinning: pinning::PinningPolicy::default(),
+            dependency_updates: dependency_updates::DependencyUpdateConfig::default(),
+            vulnerability_scanner: scan_policy::VulnerabilityScannerConfig::default(),
+            publish_packages: publishing::PublishPackagesConfig::default(),
+            container: container::ContainerConfig::default(),
+            artifact_signing: signing::ArtifactSigningConfig::default(),
+            read_only: false,
+        }
+    }
+}
+
+impl Config {
+    /// Checks that the config is consistent: the facet registry is valid, and the credential the Github auth
+    /// mode asks for is configured.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error describing the first problem found.
+    pub fn validate(&self) -> Result<(), SkootError> {
+        self.facet_registry.validate()?;
+        match self.github_auth {
+            GithubAuthMode::Token if self.github_token.is_none() => {
+                Err("github_auth is `token` but github_token isn't set".into())
+            }
+            GithubAuthMode::App if self.github_app.is_none() => {
+                Err("github_auth is `app` but github_app isn't set".into())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The values the interactive prompts for new projects start from.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(default)]
+pub struct ProjectDefaults {
+    /// The Github organization or user new projects are created in.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub organization: Option<String>,
+    /// The ecosystem new projects are initialized with.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ecosystem: Option<SupportedEcosystems>,
+}
+
+/// How the SECURITY.md and SECURITY-INSIGHTS.yml of projects tell people to report vulnerabilities. Reports
+/// go through Github's private vulnerability reporting when the `VulnerabilityReporting` facet enables it, and
+/// the contact email is offered besides it or instead of it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(default)]
+pub struct SecurityPolicyConfig {
+    /// The email address vulnerabilities can be reported to, e.g. the org's security team.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contact_email: Option<String>,
+    /// The ASCII armored PGP public key reports sent to the contact email can be encrypted with, usually a
+    /// reference like `file:/etc/skootrs/security.asc`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pgp_key: Option<String>,
+    /// How many days it takes at most to acknowledge a report.
+    pub acknowledgement_days: u32,
+    /// How many days a vulnerability is kept under embargo while a fix is prepared before it's disclosed.
+    pub embargo_days: u32,
+}
+
+impl Default for SecurityPolicyConfig {
+    fn default() -> Self {
+        Self {
+            contact_email: None,
+            pgp_key: None,
+            acknowledgement_days: 3,
+            embargo_days: 90,
+        }
+    }
+}
+
+/// A Github App installation Skootrs authenticates as. Short lived installation tokens are minted from the
+/// App's private key as they are needed.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct GithubAppConfig {
+    /// The ID of the Github App.
+    pub app_id: u64,
+    /// The ID of the App's installation on the org or user that owns the projects.
+    pub installation_id: u64,
+    /// The App's PEM encoded private key, usually a reference like `file:/run/secrets/github_app_key`.
+    pub private_key: String,
+}
+
+/// Which Github credential Skootrs authenticates with.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum GithubAuthMode {
+    /// The Github App if `github_app` is set, otherwise the token if `github_token` is set, otherwise the
+    /// `GITHUB_TOKEN` environment variable.
+    #[default]
+    Auto,
+    /// The token in `github_token`.
+    Token,
+    /// The Github App installation in `github_app`.
+    App,
+    /// The `GITHUB_TOKEN` environment variable, even if a credential is configured.
+    Ambient,
+}
+
+/// How Github API calls that fail with a transient error or hit a rate limit are retried. Retries back off
+/// exponentially so a long run of calls, like creating a batch of projects, doesn't die midway.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// How many times a call is retried before giving up. `0` turns retrying off.
+    pub max_retries: u32,
+    /// How long to wait before the first retry, in milliseconds. The wait doubles with every retry.
+    pub initial_backoff_ms: u64,
+    /// The longest to wait between two retries, in milliseconds.
+    pub max_backoff_ms: u64,
+    /// The longest to wait for an exhausted rate limit to reset, in seconds. A call that would have to wait
+    /// longer fails instead.
+    pub max_rate_limit_wait_secs: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            initial_backoff_ms: 1000,
+            max_backoff_ms: 60_000,
+            max_rate_limit_wait_secs: 900,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Returns how long to wait before the `retry`th retry, counting from 0, before any jitter is applied.
+    #[must_use]
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let backoff = self
+            .initial_backoff_ms
+            .saturating_mul(2_u64.saturating_pow(retry));
+        Duration::from_millis(backoff.min(self.max_backoff_ms))
+    }
+}
+
+/// The git implementation Skootrs uses for projects' sources.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum GitBackend {
+    /// Runs the `git` binary, using its config and credential helpers as they are.
+    #[default]
+    Cli,
+    /// Uses libgit2, so Skootrs doesn't depend on a `git` binary and every failure is reported as an error.
+    Libgit2,
+}
+
+/// How the changes facets make to a new project's source are split into commits. Every commit records the
+/// facets it applies and the version of Skootrs that made it in `Skootrs-Facet` and `Skootrs-Version`
+/// trailers, so `git log` shows when each facet was applied.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum CommitGrouping {
+    /// A commit for the files the ecosystem generates, then one for each source bundle facet.
+    #[default]
+    Facet,
+    /// A single commit for the whole source bundle.
+    Bundle,
+}
+
+/// How Skootrs signs the commits it makes, so repos that require signed commits accept them. The signing
+/// programs are run the same way git runs them, so their usual config applies.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(tag = "method", rename_all = "lowercase")]
+pub enum CommitSigning {
+    /// Commits aren't signed.
+    #[default]
+    None,
+    /// Commits are signed with a GPG key.
+    Gpg {
+        /// The ID of the key. The default key of `gpg` is used if it isn't set.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        key: Option<String>,
+    },
+    /// Commits are signed with an SSH key.
+    Ssh {
+        /// The path of the key, e.g. `/home/me/.ssh/id_ed25519`. A public key can be given if its private key
+        /// is in the SSH agent.
+        key: String,
+    },
+    /// Commits are signed keylessly with gitsign, using a short-lived Sigstore certificate for your OIDC
+    /// identity.
+    Gitsign,
+}
+
+/// How Skootrs signs the `.skootrs` state file of the projects it manages, so anyone auditing a project can
+/// tell the state was written by Skootrs and hasn't been changed since. The state is signed with cosign every
+/// time it's written, and the signature bundle is kept next to it in `.skootrs.sig`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(tag = "method", rename_all = "lowercase")]
+pub enum StateSigning {
+    /// The state isn't signed and its signature isn't checked.
+    #[default]
+    None,
+    /// The state is signed keylessly, using a short-lived Sigstore certificate for your OIDC identity.
+    Sigstore {
+        /// The identity the certificate has to be issued to, e.g. `release-bot@example.com`.
+        identity: String,
+        /// The OIDC issuer that has to have vouched for the identity, e.g. `https://accounts.google.com`.
+        issuer: String,
+    },
+    /// The state is signed with a cosign key pair.
+    Key {
+        /// The path or KMS URI of the private key.
+        key: String,
+        /// The path or KMS URI of the public key signatures are verified with.
+        public_key: String,
+    },
+}
+
+/// Whether a project's state was signed the way the `state_signing` config says it should be.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum StateSignatureStatus {
+    /// State signing isn't configured, so the signature wasn't checked.
+    Unchecked,
+    /// The state was signed by the configured identity or key and hasn't changed since.
+    Verified,
+    /// The state isn't signed.
+    Missing,
+    /// The signature doesn't match the state, or wasn't made by the configured identity or key.
+    Invalid {
+        /// Why the signature was rejected.
+        reason: String,
+    },
+}
+
+/// The backend Skootrs uses to keep track of the projects it manages. The state of each project is always
+/// kept in its repo, the backend records which projects exist.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(tag = "backend", rename_all = "lowercase")]
+pub enum StateStoreConfig {
+    /// A JSON file with the repo URLs of the projects. Each project's state is fetched from its repo when
+    /// it's needed, and kept in the file along with when it was fetched.
+    Json {
+        /// The path of the JSON file.
+        path: String,
+        /// How many seconds a project's state is reused for before it's fetched from its repo again. `0`
+        /// fetches it every time.
+        #[serde(default = "default_state_cache_ttl_secs")]
+        ttl_secs: u64,
+    },
+    /// A `SurrealDB` database that also stores a copy of each project's state, so many projects can be
+    /// listed and queried without fetching them from their repos.
+    Surreal {
+        /// Where the database is, e.g. `surrealkv://./skootrs.db` for a local file or `ws://db:8000` for a
+        /// remote server.
+        endpoint: String,
+        /// The namespace the database is in.
+        #[serde(default = "default_surreal_name")]
+        namespace: String,
+        /// The name of the database.
+        #[serde(default = "default_surreal_name")]
+        database: String,
+        /// The root user to sign in to a remote server as.
+        #[serde(default)]
+        username: Option<String>,
+        /// The password of the root user, usually a reference like `${SURREAL_PASS}`.
+        #[serde(default)]
+        password: Option<String>,
+    },
+}
+
+/// The options of the daemon, which serves the REST API and optionally the gRPC API.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(default)]
+pub struct DaemonConfig {
+    /// The API keys the daemon accepts in the `skootrs_apikey` header. Every request to the API has to have
+    /// one, so the API rejects all requests if none are configured.
+    pub api_keys: Vec<ApiKeyConfig>,
+    /// The secret GitHub webhook deliveries to `/webhooks/github` are signed with, usually a reference like
+    /// `${SKOOTRS_WEBHOOK_SECRET}`. Every delivery is rejected if it isn't set.
+    pub webhook_secret: Option<String>,
+    /// How the daemon reconciles the projects in the state store in the background.
+    pub reconciliation: ReconciliationConfig,
+    /// How many projects the daemon creates at once. Creations beyond that wait in the queue.
+    pub job_workers: usize,
+    /// The directory the daemon keeps its own state in, e.g. the queue of jobs. It's created if it doesn't
+    /// exist.
+    pub data_dir: String,
+    /// The port the gRPC API is served on when the daemon is started with `--grpc`.
+    pub grpc_port: u16,
+}
+
+impl Default for DaemonConfig {
+    fn default() -> Self {
+        Self {
+            api_keys: vec![],
+            webhook_secret: None,
+            reconciliation: ReconciliationConfig::default(),
+            job_workers: 2,
+            data_dir: "./skootdaemon".to_string(),
+            grpc_port: 50051,
+        }
+    }
+}
+
+/// The options of the daemon's background reconciliation of projects.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(default)]
+pub struct ReconciliationConfig {
+    /// How many seconds to wait between reconciling every project. Projects aren't reconciled in the
+    /// background if this isn't set.
+    pub interval_secs: Option<u64>,
+    /// Whether projects that aren't compliant are updated, which re-applies the facets that drifted. Otherwise
+    /// they're only audited.
+    pub update: bool,
+}
+
+impl Default for ReconciliationConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: None,
+            update: true,
+        }
+    }
+}
+
+/// The result of the daemon reconciling a project in the background.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct ProjectReconciliation {
+    /// The URL of the project's repo.
+    pub project_url: String,
+    /// When the project was reconciled.
+    pub reconciled_at: chrono::DateTime<chrono::Utc>,
+    /// The audit of the project before it was updated, if it could be audited.
+    pub audit: Option<facet::ProjectAudit>,
+    /// Whether the project was updated because it wasn't compliant.
+    pub updated: bool,
+    /// Why the project couldn't be audited or updated.
+    pub error: Option<String>,
+}
+
+/// The status of a job the daemon runs in the background.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub enum JobStatus {
+    /// The job is waiting for a worker.
+    Queued,
+    /// A worker is running the job.
+    Running,
+    /// The job completed.
+    Succeeded,
+    /// The job failed, or the daemon stopped while it was running.
+    Failed,
+}
+
+/// A project creation the daemon runs in the background, so the request creating the project doesn't have to
+/// wait for it.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct Job {
+    /// The ID the job is looked up by.
+    pub id: String,
+    /// The status of the job.
+    pub status: JobStatus,
+    /// The step the job is at, e.g. `Recording the project`.
+    pub progress: Option<String>,
+    /// Why the job failed.
+    pub error: Option<String>,
+    /// The parameters the project is created with.
+    pub params: ProjectCreateParams,
+    /// Who queued the job, e.g. `api_key:ci`. The project's creation is recorded in the audit log as made by
+    /// them.
+    #[serde(default)]
+    pub actor: Option<String>,
+    /// The URL of the created project's repo, once the job succeeded.
+    pub project_url: Option<String>,
+    /// When the job was queued.
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    /// When the job last changed.
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl Job {
+    /// Returns a queued job creating a project, queued by `actor`.
+    #[must_use]
+    pub fn new(id: String, params: ProjectCreateParams, actor: Option<String>) -> Self {
+        let now = chrono::Utc::now();
+        Self {
+            id,
+            status: JobStatus::Queued,
+            progress: None,
+            error: None,
+            params,
+            actor,
+            project_url: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Marks the job as running the given step.
+    pub fn progress(&mut self, step: &str) {
+        self.status = JobStatus::Running;
+        self.progress = Some(step.to_string());
+        self.updated_at = chrono::Utc::now();
+    }
+
+    /// Marks the job as succeeded, having created the project with the given repo URL.
+    pub fn succeed(&mut self, project_url: String) {
+        self.status = JobStatus::Succeeded;
+        self.progress = None;
+        self.project_url = Some(project_url);
+        self.updated_at = chrono::Utc::now();
+    }
+
+    /// Marks the job as failed.
+    pub fn fail(&mut self, error: String) {
+        self.status = JobStatus::Failed;
+        self.error = Some(error);
+        self.updated_at = chrono::Utc::now();
+    }
+}
+
+/// An API key the REST daemon accepts.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct ApiKeyConfig {
+    /// A name for who the key was given to, e.g. `ci`.
+    pub name: String,
+    /// The key itself, usually a reference like `${SKOOTRS_CI_API_KEY}` or `file:/run/secrets/ci_api_key`.
+    pub key: String,
+    /// What the key is allowed to do. Keys are read-only by default.
+    #[serde(default = "default_api_key_scopes")]
+    pub scopes: Vec<ApiKeyScope>,
+}
+
+impl ApiKeyConfig {
+    /// Returns true if the key is allowed to do what the scope covers. A key that can write can also read.
+    #[must_use]
+    pub fn permits(&self, scope: &ApiKeyScope) -> bool {
+        self.scopes.contains(scope)
+            || (*scope == ApiKeyScope::Read && self.scopes.contains(&ApiKeyScope::Write))
+    }
+
+    /// Returns the key of `api_keys` that `presented` is, which both the REST and gRPC APIs check requests
+    /// with.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `ApiKeyError` if `presented` isn't one of `api_keys`, or if its key doesn't permit `scope`.
+    pub fn authorize<'a>(
+        api_keys: &'a [Self],
+        presented: &str,
+        scope: &ApiKeyScope,
+    ) -> Result<&'a Self, ApiKeyError> {
+        // Every configured key is compared so the time it takes doesn't give away which keys are close.
+        let api_key = api_keys
+            .iter()
+            .fold(None, |found, api_key| {
+                let matches = constant_time_eq(api_key.key.as_bytes(), presented.as_bytes());
+                found.or_else(|| matches.then_some(api_key))
+            })
+            .ok_or(ApiKeyError::InvalidKey)?;
+        if !api_key.permits(scope) {
+            return Err(ApiKeyError::MissingScope {
+                name: api_key.name.clone(),
+                scope: scope.clone(),
+            });
+        }
+        Ok(api_key)
+    }
+}
+
+/// Why an API key was rejected.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ApiKeyError {
+    /// The key isn't one of the configured keys.
+    #[error("The API key isn't valid")]
+    InvalidKey,
+    /// The key isn't allowed to do what was asked.
+    #[error("The API key {name} doesn't have the {scope} scope")]
+    MissingScope {
+        /// The name of the key.
+        name: String,
+        /// The scope that was needed.
+        scope: ApiKeyScope,
+    },
+}
+
+/// Compares two byte strings in time that only depends on their lengths, e.g. a presented secret with the
+/// expected one.
+#[must_use]
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn default_api_key_scopes() -> Vec<ApiKeyScope> {
+    vec![ApiKeyScope::Read]
+}
+
+/// What an API key is allowed to do.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Display)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum ApiKeyScope {
+    /// Getting and listing projects, facets and outputs.
+    Read,
+    /// Creating, updating and archiving projects.
+    Write,
+}
+
+fn default_surreal_name() -> String {
+    "skootrs".to_string()
+}
+
+const fn default_state_cache_ttl_secs() -> u64 {
+    300
+}
+
+impl Default for StateStoreConfig {
+    fn default() -> Self {
+        Self::Json {
+            path: "./skootcache".to_string(),
+            ttl_secs: default_state_cache_ttl_secs(),
         }
     }
 }
@@ -548,5 +2194,228 @@ mod tests {
             InitializedRepo::try_from("https://github.com/kusaridev/skootrs".to_string()).unwrap();
         assert_eq!(repo.host_url(), "https://github.com");
         assert_eq!(repo.full_url(), "https://github.com/kusaridev/skootrs");
+
+        let repo: InitializedRepo =
+            InitializedRepo::try_from("https://git.example.com/team/project.git".to_string())
+                .unwrap();
+        assert!(matches!(repo, InitializedRepo::GenericGit(_)));
+        assert_eq!(repo.name(), "project");
+        assert_eq!(repo.host_url(), "https://git.example.com");
+        assert_eq!(repo.full_url(), "https://git.example.com/team/project.git");
+
+        let repo: InitializedRepo =
+            InitializedRepo::try_from("https://bitbucket.org/team/project.git".to_string())
+                .unwrap();
+        assert!(matches!(repo, InitializedRepo::Bitbucket(_)));
+        assert_eq!(repo.name(), "project");
+        assert_eq!(repo.full_url(), "https://bitbucket.org/team/project");
+    }
+
+    #[test]
+    fn test_generic_git_repo_scp_like_url() {
+        let repo = InitializedGenericGitRepo::from_remote_url(
+            "git@git.example.com:team/project.git".into(),
+        )
+        .unwrap();
+        assert_eq!(repo.name, "project");
+        assert_eq!(repo.host_url(), "git.example.com");
+    }
+
+    #[test]
+    fn test_ecosystem_params_modules() {
+        let single: ProjectCreateParams = serde_json::from_value(serde_json::json!({
+            "name": "project",
+            "repo_params": { "Github": { "name": "project", "description": "", "organization": { "User": "me" } } },
+            "ecosystem_params": { "Go": { "name": "project", "host": "github.com/me" } },
+            "source_params": { "parent_path": "/tmp" },
+        }))
+        .unwrap();
+        assert_eq!(single.ecosystem_params.len(), 1);
+        assert!(single.ecosystem_params[0].initialized_module().is_root());
+
+        let monorepo: ProjectCreateParams = serde_json::from_value(serde_json::json!({
+            "name": "project",
+            "repo_params": { "Github": { "name": "project", "description": "", "organization": { "User": "me" } } },
+            "ecosystem_params": [
+                { "path": "services/api", "ecosystem": { "Go": { "name": "api", "host": "github.com/me" } } },
+                { "path": "libs/core", "ecosystem": { "Maven": { "group_id": "com.me", "artifact_id": "core" } } },
+            ],
+            "source_params": { "parent_path": "/tmp" },
+        }))
+        .unwrap();
+        let modules = monorepo
+            .ecosystem_params
+            .iter()
+            .map(ModuleInitializeParams::initialized_module)
+            .collect::<Vec<_>>();
+        assert_eq!(modules[1].ecosystem.name(), "Maven");
+        assert_eq!(
+            modules[0]
+                .source(&InitializedSource {
+                    path: "/tmp/project".to_string(),
+                })
+                .path,
+            "/tmp/project/services/api"
+        );
+        assert_eq!(modules[0].relative_path("./"), "services/api/");
+        assert_eq!(modules[0].relative_path("cmd/"), "services/api/cmd/");
+        assert_eq!(modules[0].repo_path(), "/services/api");
+        assert_eq!(modules[0].slug().as_deref(), Some("services-api"));
+        assert_eq!(
+            single.ecosystem_params[0].initialized_module().repo_path(),
+            "/"
+        );
+        assert_eq!(single.ecosystem_params[0].initialized_module().slug(), None);
+    }
+
+    #[test]
+    fn test_project_api_repo() {
+        let canonical =
+            InitializedRepo::try_from("https://git.example.com/team/project.git".to_string())
+                .unwrap();
+        let mut project = InitializedProject {
+            repo: canonical.clone(),
+            mirror: None,
+            modules: vec![InitializedModule {
+                path: ".".to_string(),
+                ecosystem: InitializedEcosystem::Go(InitializedGo {
+                    name: "project".to_string(),
+                    host: "git.example.com".to_string(),
+                }),
+            }],
+            source: InitializedSource {
+                path: "project".to_string(),
+            },
+            facets: HashMap::new(),
+            name: "project".to_string(),
+            skootrs_version: None,
+            created_at: None,
+        };
+        assert_eq!(project.api_repo().full_url(), canonical.full_url());
+
+        // A mirror on another generic git remote has no API either, so the API facets stay where they are.
+        project.mirror = Some(
+            InitializedRepo::try_from("https://mirror.example.com/team/project.git".to_string())
+                .unwrap(),
+        );
+        assert_eq!(project.api_repo().full_url(), canonical.full_url());
+
+        project.mirror =
+            Some(InitializedRepo::try_from("https://github.com/team/project".to_string()).unwrap());
+        assert_eq!(
+            project.api_repo().full_url(),
+            "https://github.com/team/project"
+        );
+
+        // State written before mirrors existed has no mirror.
+        let mut state = serde_json::to_value(&project).unwrap();
+        state.as_object_mut().unwrap().remove("mirror");
+        let project: InitializedProject = serde_json::from_value(state).unwrap();
+        assert!(project.mirror.is_none());
+    }
+
+    #[test]
+    fn test_api_key_scopes() {
+        let config: DaemonConfig = serde_json::from_str(
+            r#"{"api_keys": [{"name": "dashboard", "key": "abc"}, {"name": "ci", "key": "def", "scopes": ["write"]}]}"#,
+        )
+        .unwrap();
+        let (dashboard, ci) = (&config.api_keys[0], &config.api_keys[1]);
+        assert!(dashboard.permits(&ApiKeyScope::Read));
+        assert!(!dashboard.permits(&ApiKeyScope::Write));
+        assert!(ci.permits(&ApiKeyScope::Read));
+        assert!(ci.permits(&ApiKeyScope::Write));
+    }
+
+    #[test]
+    fn test_api_key_authorize() {
+        let config: DaemonConfig = serde_json::from_str(
+            r#"{"api_keys": [{"name": "dashboard", "key": "abc"}, {"name": "ci", "key": "def", "scopes": ["write"]}]}"#,
+        )
+        .unwrap();
+        let api_key =
+            ApiKeyConfig::authorize(&config.api_keys, "def", &ApiKeyScope::Write).unwrap();
+        assert_eq!(api_key.name, "ci");
+        assert_eq!(
+            ApiKeyConfig::authorize(&config.api_keys, "abc", &ApiKeyScope::Write),
+            Err(ApiKeyError::MissingScope {
+                name: "dashboard".to_string(),
+                scope: ApiKeyScope::Write,
+            })
+        );
+        // A prefix of a valid key isn't valid either.
+        for presented in ["xyz", "ab", ""] {
+            assert_eq!(
+                ApiKeyConfig::authorize(&config.api_keys, presented, &ApiKeyScope::Read),
+                Err(ApiKeyError::InvalidKey)
+            );
+        }
+    }
+
+    #[test]
+    fn test_config_defaults() {
+        let config: Config = serde_json::from_str("{}").unwrap();
+        assert!(config.telemetry.exports());
+        assert!(config.defaults.organization.is_none());
+
+        let config: Config = serde_json::from_str(
+            r#"{"telemetry": false, "defaults": {"organization": "kusaridev", "ecosystem": "Rust"}}"#,
+        )
+        .unwrap();
+        assert!(!config.telemetry.exports());
+        assert_eq!(config.defaults.organization.as_deref(), Some("kusaridev"));
+        assert!(matches!(
+            config.defaults.ecosystem,
+            Some(SupportedEcosystems::Rust)
+        ));
+    }
+
+    #[test]
+    fn test_retry_policy_backoff() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.backoff(0), Duration::from_secs(1));
+        assert_eq!(policy.backoff(3), Duration::from_secs(8));
+        assert_eq!(policy.backoff(10), Duration::from_secs(60));
+        assert_eq!(policy.backoff(u32::MAX), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_python_package_name() {
+        let params = PythonParams {
+            name: "My-Project.core".to_string(),
+            package_manager: PythonPackageManager::Poetry,
+        };
+        assert_eq!(params.package(), "my_project_core");
+    }
+
+    #[test]
+    fn test_rust_workspace_members() {
+        let mut params = RustParams {
+            name: "my-workspace".to_string(),
+            crates: vec![],
+        };
+        assert_eq!(params.members(), vec!["my-workspace".to_string()]);
+        params.crates = vec!["my-core".to_string(), "my-cli".to_string()];
+        assert_eq!(
+            params.members(),
+            vec!["my-core".to_string(), "my-cli".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_workflow_run_stats_from_conclusions() {
+        let stats = WorkflowRunStats::from_conclusions([
+            Some("success"),
+            Some("failure"),
+            None,
+            Some("success"),
+            Some("cancelled"),
+        ]);
+        assert_eq!(stats.completed, 4);
+        assert_eq!(stats.successful, 2);
+        assert_eq!(stats.success_rate, Some(0.5));
+
+        let empty = WorkflowRunStats::from_conclusions([None]);
+        assert_eq!(empty.success_rate, None);
     }
 }
@@ -0,0 +1,141 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `pinning` module provides the policy the `PinnedDependencies` facet enforces on a project's workflows.
+//! Every action and reusable workflow has to be pinned to a full commit SHA, since tags can be moved to point
+//! at different code, except for the ones the policy exempts.
+
+#![allow(clippy::module_name_repetitions)]
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "openapi")]
+use utoipa::ToSchema;
+
+/// The path of the pinning policy file relative to the root of the project's source. The pin check workflow
+/// reads the exemptions from it, and audits check the project's workflows against it.
+pub const PINNING_POLICY_PATH: &str = ".github/pinning-policy.yml";
+
+/// The pinning policy of a project's workflows.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(default)]
+pub struct PinningPolicy {
+    /// The prefixes of the actions and reusable workflows that can be referenced by tag, e.g.
+    /// `slsa-framework/slsa-github-generator/`.
+    pub exempt: Vec<String>,
+}
+
+impl Default for PinningPolicy {
+    fn default() -> Self {
+        Self {
+            // The SLSA generator's provenance only verifies when it's referenced by a release tag.
+            exempt: vec!["slsa-framework/slsa-github-generator/".to_string()],
+        }
+    }
+}
+
+impl PinningPolicy {
+    /// Returns true if the reference of an action or reusable workflow follows the policy. Local actions are
+    /// part of the project, so they're always pinned, and Docker images have to be pinned to a digest.
+    #[must_use]
+    pub fn is_pinned(&self, reference: &str) -> bool {
+        if reference.starts_with("./") || self.exempt.iter().any(|e| reference.starts_with(e)) {
+            return true;
+        }
+        if reference.starts_with("docker://") {
+            return reference.contains("@sha256:");
+        }
+        reference.split_once('@').is_some_and(|(_, version)| {
+            version.len() == 40
+                && version
+                    .chars()
+                    .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c))
+        })
+    }
+
+    /// Returns the references of a workflow that don't follow the policy.
+    #[must_use]
+    pub fn unpinned<'a>(&self, workflow: &'a str) -> Vec<&'a str> {
+        workflow
+            .lines()
+            .filter_map(action_reference)
+            .filter(|reference| !self.is_pinned(reference))
+            .collect()
+    }
+}
+
+/// Returns true if the path of a file, relative to the root of the source, is a workflow. Facets write their
+/// files both with and without a leading `./`.
+#[must_use]
+pub fn is_workflow(path: &str) -> bool {
+    let path = path.strip_prefix("./").unwrap_or(path);
+    path.strip_prefix(".github/workflows/")
+        .is_some_and(|name| !name.is_empty() && !name.contains('/'))
+}
+
+/// Returns the action or reusable workflow a line of a workflow uses, e.g. `actions/checkout@v4` for
+/// `- uses: actions/checkout@v4 # checkout`, or `None` if the line doesn't use one.
+#[must_use]
+pub fn action_reference(line: &str) -> Option<&str> {
+    let line = line.trim_start();
+    let line = line.strip_prefix('-').map_or(line, str::trim_start);
+    let reference = line
+        .strip_prefix("uses:")?
+        .trim_start()
+        .trim_start_matches(['\'', '"']);
+    let end = reference
+        .find(|c: char| c.is_whitespace() || matches!(c, '\'' | '"' | '#'))
+        .unwrap_or(reference.len());
+    let reference = &reference[..end];
+    (!reference.is_empty()).then_some(reference)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unpinned() {
+        let workflow = r#"
+jobs:
+  build:
+    uses: slsa-framework/slsa-github-generator/.github/workflows/generator_generic_slsa3.yml@v1.10.0
+    steps:
+      - uses: actions/checkout@9bb56186c3b09b4f86b1c65136769dd318469633 # v4.1.2
+      - name: Release
+        uses: "googleapis/release-please-action@v4"
+      - uses: ./.github/actions/build
+      - uses: docker://alpine:3.19
+      - uses: docker://alpine@sha256:c5b1261d6d3e43071626931fc004f70149baeba2c8ec672bd4f27761f8e1ad6b
+      - uses: actions/setup-go@0c52d547c9bc32b1aa3301fd7a9cb496313a4491a # not a SHA
+      - run: echo "uses: not/an-action@v1"
+"#;
+        assert_eq!(
+            PinningPolicy::default().unpinned(workflow),
+            vec![
+                "googleapis/release-please-action@v4",
+                "docker://alpine:3.19",
+                "actions/setup-go@0c52d547c9bc32b1aa3301fd7a9cb496313a4491a",
+            ]
+        );
+        assert!(is_workflow("./.github/workflows/scorecard.yml"));
+        assert!(is_workflow(".github/workflows/releases.yml"));
+        assert!(!is_workflow("./.github/dependabot.yml"));
+        assert_eq!(
+            PinningPolicy { exempt: vec![] }.unpinned(workflow)[0],
+            "slsa-framework/slsa-github-generator/.github/workflows/generator_generic_slsa3.yml@v1.10.0"
+        );
+    }
+}
@@ -0,0 +1,231 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `plan` module provides the data model for planning an operation without running it. A plan lists the
+//! API calls, commands, files and pushes the operation would make, in the order it would make them, so they
+//! can be reviewed before anything is changed.
+
+#![allow(clippy::module_name_repetitions)]
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::{facet::SupportedFacetType, InitializedModule, InitializedRepo, InitializedSource};
+
+/// A single change that an operation would make.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub enum PlannedAction {
+    /// A call to the repo host's API.
+    ApiCall {
+        /// What the call does.
+        description: String,
+        /// The HTTP method of the call.
+        method: String,
+        /// The API endpoint that would be called.
+        url: String,
+        /// The body that would be sent with the call.
+        #[cfg_attr(feature = "openapi", schema(value_type = Object))]
+        body: Option<serde_json::Value>,
+        /// The facet the call belongs to, if any.
+        facet_type: Option<SupportedFacetType>,
+    },
+    /// A command that would be run on the local machine.
+    Command {
+        /// What the command does.
+        description: String,
+        /// The command and its arguments.
+        args: Vec<String>,
+        /// The directory the command would be run in.
+        dir: String,
+    },
+    /// A file that would be written to the project's source.
+    WriteFile {
+        /// The path of the file relative to the root of the source.
+        path: String,
+        /// The name of the file.
+        name: String,
+        /// The content that would be written to the file.
+        content: String,
+        /// The facet the file belongs to, or `None` for the files the ecosystem generates.
+        facet_type: Option<SupportedFacetType>,
+    },
+    /// A commit of the changes so far that would be pushed to the remote along with a later one.
+    Commit {
+        /// The commit message.
+        message: String,
+    },
+    /// A commit of the changes so far that would be pushed to the remote.
+    Push {
+        /// The commit message.
+        message: String,
+    },
+}
+
+impl fmt::Display for PlannedAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ApiCall {
+                description,
+                method,
+                url,
+                ..
+            } => write!(f, "api    {method} {url} ({description})"),
+            Self::Command {
+                description,
+                args,
+                dir,
+            } => write!(f, "run    {} in {dir} ({description})", args.join(" ")),
+            Self::WriteFile {
+                path,
+                name,
+                content,
+                facet_type,
+            } => {
+                let path = path.trim_end_matches('/');
+                write!(f, "file   {path}/{name} ({} lines", content.lines().count())?;
+                if let Some(facet_type) = facet_type {
+                    write!(f, ", {facet_type}")?;
+                }
+                write!(f, ")")
+            }
+            // Only the subject is shown, the trailers are the same for every commit.
+            Self::Commit { message } => write!(f, "commit {:?}", subject(message)),
+            Self::Push { message } => write!(f, "push   {:?}", subject(message)),
+        }
+    }
+}
+
+/// Returns the first line of a commit message.
+fn subject(message: &str) -> &str {
+    message.lines().next().unwrap_or_default()
+}
+
+/// The plan for creating a project.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct ProjectPlan {
+    /// The name of the project.
+    pub name: String,
+    /// The repo the project would be created in.
+    pub repo: InitializedRepo,
+    /// The modules the project would be initialized with.
+    pub modules: Vec<InitializedModule>,
+    /// Where the project's source would be cloned to.
+    pub source: InitializedSource,
+    /// The changes creating the project would make, in order.
+    pub actions: Vec<PlannedAction>,
+}
+
+impl fmt::Display for ProjectPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Creating project {} at {} would perform the following actions:",
+            self.name,
+            self.repo.full_url()
+        )?;
+        writeln!(f)?;
+        for action in &self.actions {
+            writeln!(f, "  + {action}")?;
+        }
+        let count = |matches: fn(&PlannedAction) -> bool| {
+            self.actions.iter().filter(|action| matches(action)).count()
+        };
+        writeln!(f)?;
+        write!(
+            f,
+            "Plan: {} API calls, {} commands, {} files to write, {} commits, {} pushes.",
+            count(|a| matches!(a, PlannedAction::ApiCall { .. })),
+            count(|a| matches!(a, PlannedAction::Command { .. })),
+            count(|a| matches!(a, PlannedAction::WriteFile { .. })),
+            count(|a| matches!(a, PlannedAction::Commit { .. } | PlannedAction::Push { .. })),
+            count(|a| matches!(a, PlannedAction::Push { .. })),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::skootrs::{
+        GithubUser, InitializedEcosystem, InitializedGithubRepo, InitializedGo, InitializedModule,
+    };
+
+    #[test]
+    fn test_project_plan_display() {
+        let plan = ProjectPlan {
+            name: "test".to_string(),
+            repo: InitializedRepo::Github(InitializedGithubRepo {
+                name: "test".to_string(),
+                organization: GithubUser::User("testuser".to_string()),
+            }),
+            modules: vec![InitializedModule {
+                path: ".".to_string(),
+                ecosystem: InitializedEcosystem::Go(InitializedGo {
+                    name: "test".to_string(),
+                    host: "github.com".to_string(),
+                }),
+            }],
+            source: InitializedSource {
+                path: "/tmp/test".to_string(),
+            },
+            actions: vec![
+                PlannedAction::ApiCall {
+                    description: "Create Github repo testuser/test".to_string(),
+                    method: "POST".to_string(),
+                    url: "/user/repos".to_string(),
+                    body: None,
+                    facet_type: None,
+                },
+                PlannedAction::Command {
+                    description: "Initialize the Go module".to_string(),
+                    args: vec!["go".to_string(), "mod".to_string(), "init".to_string()],
+                    dir: "/tmp/test".to_string(),
+                },
+                PlannedAction::WriteFile {
+                    path: "./".to_string(),
+                    name: "README.md".to_string(),
+                    content: "# test\n\nA project\n".to_string(),
+                    facet_type: Some(SupportedFacetType::Readme),
+                },
+                PlannedAction::Commit {
+                    message: "Add Readme facet\n\nSkootrs-Facet: Readme\n".to_string(),
+                },
+                PlannedAction::Push {
+                    message: "Initialized project".to_string(),
+                },
+            ],
+        };
+
+        let rendered = plan.to_string();
+        assert!(rendered.starts_with(
+            "Creating project test at https://github.com/testuser/test would perform the following actions:"
+        ));
+        assert!(
+            rendered.contains("  + api    POST /user/repos (Create Github repo testuser/test)\n")
+        );
+        assert!(
+            rendered.contains("  + run    go mod init in /tmp/test (Initialize the Go module)\n")
+        );
+        assert!(rendered.contains("  + file   ./README.md (3 lines, Readme)\n"));
+        assert!(rendered.contains("  + commit \"Add Readme facet\"\n"));
+        assert!(rendered.contains("  + push   \"Initialized project\"\n"));
+        assert!(rendered
+            .ends_with("Plan: 1 API calls, 1 commands, 1 files to write, 2 commits, 1 pushes."));
+    }
+}
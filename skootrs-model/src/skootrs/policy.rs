@@ -0,0 +1,114 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `policy` module provides the data model for evaluating projects against policies their organization
+//! writes in OPA Rego or CUE, e.g. which facets every project needs, how its branches have to be protected
+//! and what its releases have to carry. Skootrs only gathers what the policies are evaluated against, the
+//! policies themselves are evaluated by `opa` or `cue`.
+
+#![allow(clippy::module_name_repetitions)]
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+#[cfg(feature = "openapi")]
+use utoipa::ToSchema;
+
+use super::{
+    facet::ProjectAudit, status::ProjectStatus, InitializedProject, ProjectOutputReference,
+};
+
+/// The package Rego policies define their rules in. Every message in its `deny` set is a violation.
+pub const REGO_PACKAGE: &str = "skootrs";
+
+/// The language a policy bundle is written in.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, EnumString, Display)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub enum PolicyLanguage {
+    /// OPA Rego, evaluated with `opa eval`. The policies define a `deny` set of messages in the `skootrs`
+    /// package.
+    Rego,
+    /// CUE, evaluated with `cue vet`. The policies are constraints the input has to unify with.
+    Cue,
+}
+
+impl PolicyLanguage {
+    /// Returns the language of a policy file from its extension, or `None` if it isn't a policy file.
+    #[must_use]
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "rego" => Some(Self::Rego),
+            "cue" => Some(Self::Cue),
+            _ => None,
+        }
+    }
+}
+
+/// What policies are evaluated against: the project's state along with its live audit and latest release.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct PolicyInput {
+    /// The project as Skootrs recorded it, including its facets and their settings.
+    pub project: InitializedProject,
+    /// The audit of the project's facets against its repo, e.g. whether branch protection is still enabled.
+    pub audit: ProjectAudit,
+    /// The one-page summary of the project, e.g. whether an SBOM is attached to its latest release.
+    pub status: ProjectStatus,
+    /// The outputs of the project's latest release, or `None` if it hasn't been released or the release
+    /// couldn't be fetched.
+    pub latest_release: Option<Vec<ProjectOutputReference>>,
+}
+
+/// The result of evaluating a project against a policy bundle.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct PolicyEvaluation {
+    /// The URL of the evaluated project's repo.
+    pub project_url: String,
+    /// The path of the policy bundle the project was evaluated against.
+    pub policy: String,
+    /// The language of the policy bundle.
+    pub language: PolicyLanguage,
+    /// Why the project doesn't pass the policies. A project passes if there are no violations.
+    pub violations: Vec<String>,
+}
+
+impl PolicyEvaluation {
+    /// Returns true if the project doesn't violate any of the policies.
+    #[must_use]
+    pub const fn passed(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_path() {
+        assert_eq!(
+            PolicyLanguage::from_path(Path::new("policies/facets.rego")),
+            Some(PolicyLanguage::Rego)
+        );
+        assert_eq!(
+            PolicyLanguage::from_path(Path::new("release.cue")),
+            Some(PolicyLanguage::Cue)
+        );
+        assert_eq!(PolicyLanguage::from_path(Path::new("README.md")), None);
+        assert_eq!(PolicyLanguage::from_path(Path::new("policies")), None);
+    }
+}
@@ -0,0 +1,238 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `profile` module provides named presets of facets and settings for different kinds of projects, e.g.
+//! a public CLI that needs every control or an internal library that doesn't need a code of conduct.
+//! `skootrs project create --profile <name>` applies a profile on top of the config before the project is
+//! created.
+
+#![allow(clippy::module_name_repetitions)]
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "openapi")]
+use utoipa::ToSchema;
+
+use super::{
+    branch_protection::BranchProtectionConfig,
+    facet::SupportedFacetType,
+    license::{LicenseConfig, SpdxLicense},
+    Config, ProjectDefaults, SkootError,
+};
+
+/// The profiles that are built into Skootrs. A profile file with the same name takes precedence.
+pub const BUILTIN_PROFILES: [&str; 2] = ["strict-oss", "internal-library"];
+
+/// A preset of facets and settings applied on top of the config. Anything the profile doesn't set is left as
+/// the config has it.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(default, deny_unknown_fields)]
+pub struct Profile {
+    /// What kind of project the profile is for.
+    pub description: String,
+    /// The facets that are applied even if the facet registry has them turned off.
+    pub enable: Vec<SupportedFacetType>,
+    /// The facets that aren't applied even if the facet registry has them turned on.
+    pub disable: Vec<SupportedFacetType>,
+    /// The license that replaces the configured one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license: Option<LicenseConfig>,
+    /// The branch protection rules that replace the configured ones.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch_protection: Option<BranchProtectionConfig>,
+    /// The organization and ecosystem new projects default to. Only the ones that are set replace the
+    /// configured defaults.
+    pub defaults: ProjectDefaults,
+}
+
+impl Profile {
+    /// Returns a profile that's built into Skootrs, or `None` if there's no built-in profile by that name.
+    #[must_use]
+    pub fn builtin(name: &str) -> Option<Self> {
+        use SupportedFacetType::{
+            CodeOfConduct, CodeReview, ContributingPolicy, License, PinnedDependencies, Scorecard,
+            SecurityInsights, VulnerabilityReporting,
+        };
+        match name {
+            "strict-oss" => Some(Self {
+                description: "A public open source project with every control turned on"
+                    .to_string(),
+                enable: vec![CodeReview, PinnedDependencies],
+                license: Some(LicenseConfig {
+                    spdx_id: SpdxLicense::Apache2,
+                }),
+                branch_protection: Some(BranchProtectionConfig {
+                    require_pull_requests: true,
+                    dismiss_stale_reviews: true,
+                    require_code_owner_reviews: true,
+                    strict_status_checks: true,
+                    required_signatures: true,
+                    ..BranchProtectionConfig::default()
+                }),
+                ..Self::default()
+            }),
+            // Internal libraries aren't licensed to anyone or open to outside contributors and reports.
+            "internal-library" => Some(Self {
+                description: "A library that's only used inside the organization".to_string(),
+                disable: vec![
+                    License,
+                    CodeOfConduct,
+                    ContributingPolicy,
+                    SecurityInsights,
+                    Scorecard,
+                    VulnerabilityReporting,
+                ],
+                branch_protection: Some(BranchProtectionConfig {
+                    require_pull_requests: true,
+                    enforce_admins: false,
+                    ..BranchProtectionConfig::default()
+                }),
+                ..Self::default()
+            }),
+            _ => None,
+        }
+    }
+
+    /// Checks the profile against a config: every facet it turns on or off has to be declared in the facet
+    /// registry, and no facet can be turned both on and off.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the first facet that isn't declared or is turned both on and off.
+    pub fn validate(&self, config: &Config) -> Result<(), SkootError> {
+        for facet_type in self.enable.iter().chain(&self.disable) {
+            if config.facet_registry.entry(facet_type).is_none() {
+                return Err(
+                    format!("Facet {facet_type} isn't declared in the facet registry").into(),
+                );
+            }
+        }
+        if let Some(facet_type) = self.enable.iter().find(|f| self.disable.contains(f)) {
+            return Err(format!("Facet {facet_type} is both enabled and disabled").into());
+        }
+        Ok(())
+    }
+
+    /// Returns the config with the profile applied on top of it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the profile isn't valid for the config.
+    pub fn apply(&self, config: &Config) -> Result<Config, SkootError> {
+        self.validate(config)?;
+        let mut config = config.clone();
+        let registry = &mut config.facet_registry;
+        for entry in registry
+            .source_bundle
+            .iter_mut()
+            .chain(registry.api_bundle.iter_mut())
+        {
+            if self.enable.contains(&entry.facet_type) {
+                entry.enabled = true;
+            } else if self.disable.contains(&entry.facet_type) {
+                entry.enabled = false;
+            }
+        }
+        if let Some(license) = &self.license {
+            config.license = license.clone();
+        }
+        if let Some(branch_protection) = &self.branch_protection {
+            config.branch_protection = branch_protection.clone();
+        }
+        if let Some(organization) = &self.defaults.organization {
+            config.defaults.organization = Some(organization.clone());
+        }
+        if let Some(ecosystem) = &self.defaults.ecosystem {
+            config.defaults.ecosystem = Some(ecosystem.clone());
+        }
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use crate::skootrs::SupportedEcosystems;
+
+    #[test]
+    fn test_builtin_profiles() {
+        let config = Config::default();
+        for name in BUILTIN_PROFILES {
+            let profile = Profile::builtin(name).unwrap();
+            assert!(profile.validate(&config).is_ok(), "{name}");
+        }
+        assert!(Profile::builtin("missing").is_none());
+
+        let strict = Profile::builtin("strict-oss")
+            .unwrap()
+            .apply(&config)
+            .unwrap();
+        assert!(
+            strict
+                .facet_registry
+                .entry(&SupportedFacetType::CodeReview)
+                .unwrap()
+                .enabled
+        );
+        assert!(strict.branch_protection.require_pull_requests);
+
+        let internal = Profile::builtin("internal-library")
+            .unwrap()
+            .apply(&config)
+            .unwrap();
+        assert!(
+            !internal
+                .facet_registry
+                .entry(&SupportedFacetType::License)
+                .unwrap()
+                .enabled
+        );
+    }
+
+    #[test]
+    fn test_profile_file() {
+        let profile: Profile = serde_json::from_value(serde_json::json!({
+            "description": "A Python service",
+            "enable": ["Fuzzing"],
+            "license": { "spdx_id": "MIT" },
+            "defaults": { "ecosystem": "Python" },
+        }))
+        .unwrap();
+        let config = profile.apply(&Config::default()).unwrap();
+        assert_eq!(config.license.spdx_id, SpdxLicense::Mit);
+        assert!(matches!(
+            config.defaults.ecosystem,
+            Some(SupportedEcosystems::Python)
+        ));
+        assert!(config.defaults.organization.is_none());
+
+        assert!(
+            serde_json::from_value::<Profile>(serde_json::json!({ "enabled": ["Fuzzing"] }))
+                .is_err()
+        );
+        let conflicting = Profile {
+            enable: vec![SupportedFacetType::Fuzzing],
+            disable: vec![SupportedFacetType::Fuzzing],
+            ..Profile::default()
+        };
+        assert!(conflicting.validate(&Config::default()).is_err());
+        let undeclared = Profile {
+            enable: vec![SupportedFacetType::Allstar],
+            ..Profile::default()
+        };
+        assert!(undeclared.validate(&Config::default()).is_err());
+    }
+}
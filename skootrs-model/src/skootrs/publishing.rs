@@ -0,0 +1,234 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `publishing` module provides where the `PublishPackages` facet publishes a project's packages to, and
+//! the coordinates of the packages it publishes. The coordinates are recorded in the facet so the published
+//! packages can be found again with `skootrs output`.
+
+#![allow(clippy::module_name_repetitions)]
+
+use std::collections::BTreeMap;
+
+use schemars::{schema::SchemaObject, schema_for, JsonSchema};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+#[cfg(feature = "openapi")]
+use utoipa::ToSchema;
+
+use super::{InitializedEcosystem, InitializedRepo, SkootError};
+
+/// The Maven repository the `PublishPackages` facet deploys Maven projects to.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, JsonSchema)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum MavenRepository {
+    /// The project's own package registry on Github Packages, which only needs the workflow's token.
+    #[default]
+    GithubPackages,
+    /// Maven Central through the Central Portal. The project has to be signed with GPG and its namespace
+    /// verified on the Central Portal.
+    MavenCentral,
+}
+
+/// Where the `PublishPackages` facet publishes a project's packages to.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(default)]
+pub struct PublishPackagesConfig {
+    /// The container registry Go projects' images are pushed to, e.g. `ghcr.io`. Registries other than
+    /// `ghcr.io` are logged in to with the `REGISTRY_USERNAME` and `REGISTRY_PASSWORD` secrets.
+    pub container_registry: String,
+    /// The Maven repository Maven projects are deployed to.
+    pub maven_repository: MavenRepository,
+}
+
+impl Default for PublishPackagesConfig {
+    fn default() -> Self {
+        Self {
+            container_registry: "ghcr.io".to_string(),
+            maven_repository: MavenRepository::GithubPackages,
+        }
+    }
+}
+
+impl PublishPackagesConfig {
+    /// Returns the config with the options a facet was added with applied on top, e.g. a project that
+    /// publishes to Maven Central.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an option has the wrong type, or if the container registry isn't a valid host.
+    pub fn with_options(&self, options: &BTreeMap<String, Value>) -> Result<Self, SkootError> {
+        let config: Self = super::registry::with_options(self, options)?;
+        if config.container_registry.is_empty()
+            || !config
+                .container_registry
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | ':'))
+        {
+            return Err(format!(
+                "{} is not a valid container registry",
+                config.container_registry
+            )
+            .into());
+        }
+        Ok(config)
+    }
+
+    /// Returns the options schema of the `PublishPackages` facet, which lets each part of the config be
+    /// overridden when the facet is added.
+    #[must_use]
+    pub fn options_schema() -> SchemaObject {
+        schema_for!(Self).schema
+    }
+
+    /// Returns the coordinates of the packages the `PublishPackages` facet publishes for a project.
+    #[must_use]
+    pub fn packages(
+        &self,
+        repo: &InitializedRepo,
+        ecosystem: &InitializedEcosystem,
+    ) -> Vec<PackageCoordinates> {
+        let repo_path = match repo {
+            InitializedRepo::Github(g) => format!("{}/{}", g.organization.get_name(), g.name),
+            InitializedRepo::GenericGit(g) => g.name.clone(),
+            InitializedRepo::Bitbucket(b) => format!("{}/{}", b.workspace, b.name),
+        };
+        match ecosystem {
+            // Container registries only allow lowercase repository names.
+            InitializedEcosystem::Go(_) => vec![PackageCoordinates {
+                kind: PackageKind::Container,
+                registry: self.container_registry.clone(),
+                name: repo_path.to_lowercase(),
+            }],
+            InitializedEcosystem::Maven(maven) => vec![PackageCoordinates {
+                kind: PackageKind::Maven,
+                registry: match self.maven_repository {
+                    MavenRepository::GithubPackages => {
+                        format!("https://maven.pkg.github.com/{repo_path}")
+                    }
+                    MavenRepository::MavenCentral => "https://repo1.maven.org/maven2".to_string(),
+                },
+                name: format!("{}:{}", maven.group_id, maven.artifact_id),
+            }],
+            InitializedEcosystem::Rust(rust) => rust
+                .members()
+                .into_iter()
+                .map(|member| PackageCoordinates {
+                    kind: PackageKind::Crate,
+                    registry: "crates.io".to_string(),
+                    name: member,
+                })
+                .collect(),
+            // Python packages are published to PyPI by the SLSABuild facet.
+            InitializedEcosystem::Python(_) => vec![],
+        }
+    }
+}
+
+/// The kind of a published package.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub enum PackageKind {
+    /// A container image.
+    Container,
+    /// A Maven artifact.
+    Maven,
+    /// A Rust crate.
+    Crate,
+}
+
+/// Where a package a project publishes can be found.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct PackageCoordinates {
+    /// The kind of the package.
+    pub kind: PackageKind,
+    /// The registry the package is published to, e.g. `ghcr.io` or `https://repo1.maven.org/maven2`.
+    pub registry: String,
+    /// The name of the package in the registry, e.g. `org/repo` for a container image or
+    /// `com.example:app` for a Maven artifact.
+    pub name: String,
+}
+
+impl PackageCoordinates {
+    /// Returns where the package is found, e.g. the image reference without a tag for a container image.
+    #[must_use]
+    pub fn locator(&self) -> String {
+        match self.kind {
+            PackageKind::Container => format!("{}/{}", self.registry, self.name),
+            PackageKind::Maven => {
+                let (group_id, artifact_id) = self.name.split_once(':').unwrap_or(("", &self.name));
+                format!(
+                    "{}/{}/{artifact_id}",
+                    self.registry,
+                    group_id.replace('.', "/")
+                )
+            }
+            PackageKind::Crate => format!("https://crates.io/crates/{}", self.name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use crate::skootrs::{GithubUser, InitializedGithubRepo, InitializedGo, InitializedMaven};
+
+    #[test]
+    fn test_packages() {
+        let repo = InitializedRepo::Github(InitializedGithubRepo {
+            name: "Test".to_string(),
+            organization: GithubUser::Organization("TestOrg".to_string()),
+        });
+        let go = InitializedEcosystem::Go(InitializedGo {
+            name: "test".to_string(),
+            host: "github.com/testorg".to_string(),
+        });
+        let maven = InitializedEcosystem::Maven(InitializedMaven {
+            group_id: "com.example".to_string(),
+            artifact_id: "test".to_string(),
+        });
+
+        let config = PublishPackagesConfig::default();
+        assert_eq!(
+            config.packages(&repo, &go)[0].locator(),
+            "ghcr.io/testorg/test"
+        );
+        assert_eq!(
+            config.packages(&repo, &maven)[0].locator(),
+            "https://maven.pkg.github.com/TestOrg/Test/com/example/test"
+        );
+
+        let config = config
+            .with_options(&BTreeMap::from([(
+                "maven_repository".to_string(),
+                Value::from("maven_central"),
+            )]))
+            .unwrap();
+        assert_eq!(
+            config.packages(&repo, &maven)[0].locator(),
+            "https://repo1.maven.org/maven2/com/example/test"
+        );
+
+        assert!(config
+            .with_options(&BTreeMap::from([(
+                "container_registry".to_string(),
+                Value::from("ghcr.io\n  run: whoami"),
+            )]))
+            .is_err());
+    }
+}
@@ -0,0 +1,520 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `registry` module provides the facet registry, which declares the set of facets Skootrs applies to
+//! projects along with the order they are applied in. The default registry matches the facets Skootrs has
+//! always applied, and organizations can replace it in their config to enable or disable facets without
+//! recompiling Skootrs.
+
+#![allow(clippy::module_name_repetitions)]
+
+use std::collections::{BTreeMap, HashSet};
+
+use schemars::schema::{InstanceType, Schema, SchemaObject, SingleOrVec};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use utoipa::ToSchema;
+
+use super::{
+    branch_protection::BranchProtectionConfig, code_review::CodeReviewConfig,
+    container::ContainerConfig, contributing::ContributingConfig,
+    dependency_updates::DependencyUpdateConfig, facet::SupportedFacetType,
+    guac::GuacForwardingConfig, label::Label, license::LicenseConfig,
+    publishing::PublishPackagesConfig, scan_policy::VulnerabilityScannerConfig,
+    InitializedEcosystem, InitializedModule, SkootError,
+};
+
+/// A facet declared in the `FacetRegistry`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct FacetRegistryEntry {
+    /// The type of the facet.
+    pub facet_type: SupportedFacetType,
+    /// The labels that are attached to the facet, e.g. the controls it implements.
+    #[serde(default)]
+    pub labels: Vec<Label>,
+    /// The ecosystems the facet applies to, e.g. `Python`. The facet applies to every ecosystem if this is
+    /// empty.
+    #[serde(default)]
+    pub ecosystems: Vec<String>,
+    /// Whether the facet is applied. This allows a facet to be turned off without removing its entry.
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// A JSON Schema object describing the options the facet takes. Each property is an option, and its
+    /// type, `enum`, `default` and `description` are used to prompt for it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "openapi", schema(value_type = Option<Object>))]
+    pub options: Option<SchemaObject>,
+}
+
+const fn default_enabled() -> bool {
+    true
+}
+
+impl FacetRegistryEntry {
+    const fn new(facet_type: SupportedFacetType, labels: Vec<Label>) -> Self {
+        Self {
+            facet_type,
+            labels,
+            ecosystems: vec![],
+            enabled: true,
+            options: None,
+        }
+    }
+
+    /// Returns true if the facet supports the ecosystem, whether or not it's enabled.
+    #[must_use]
+    pub fn supports(&self, ecosystem: &InitializedEcosystem) -> bool {
+        self.ecosystems.is_empty()
+            || self
+                .ecosystems
+                .iter()
+                .any(|e| e.eq_ignore_ascii_case(ecosystem.name()))
+    }
+
+    /// Returns true if the facet is enabled and applies to the ecosystem.
+    #[must_use]
+    pub fn applies_to(&self, ecosystem: &InitializedEcosystem) -> bool {
+        self.enabled && self.supports(ecosystem)
+    }
+
+    /// Returns true if the facet is enabled and applies to the ecosystem of any of the modules.
+    #[must_use]
+    pub fn applies_to_any(&self, modules: &[InitializedModule]) -> bool {
+        modules.iter().any(|m| self.applies_to(&m.ecosystem))
+    }
+
+    /// Checks the options given for the facet against its options schema.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an option isn't in the schema, if a required option is missing, or if an option
+    /// has the wrong type or isn't one of the allowed values.
+    pub fn validate_options(&self, options: &BTreeMap<String, Value>) -> Result<(), SkootError> {
+        let Some(schema) = &self.options else {
+            if options.is_empty() {
+                return Ok(());
+            }
+            return Err(format!("Facet {} doesn't take any options", self.facet_type).into());
+        };
+        let object = schema.object.as_deref();
+        if let Some(missing) = object
+            .iter()
+            .flat_map(|o| &o.required)
+            .find(|name| !options.contains_key(*name))
+        {
+            return Err(
+                format!("Option {missing} is required for facet {}", self.facet_type).into(),
+            );
+        }
+        for (name, value) in options {
+            let property = match object.and_then(|o| o.properties.get(name)) {
+                Some(Schema::Object(property)) => property,
+                Some(Schema::Bool(true)) => continue,
+                Some(Schema::Bool(false)) | None => {
+                    return Err(format!(
+                        "Option {name} is not an option of facet {}",
+                        self.facet_type
+                    )
+                    .into())
+                }
+            };
+            if let Some(allowed) = &property.enum_values {
+                if !allowed.contains(value) {
+                    return Err(format!("Option {name} must be one of {allowed:?}").into());
+                }
+            }
+            if let Some(instance_type) = &property.instance_type {
+                let matches = match instance_type {
+                    SingleOrVec::Single(t) => value_has_type(value, **t),
+                    SingleOrVec::Vec(ts) => ts.iter().any(|t| value_has_type(value, *t)),
+                };
+                if !matches {
+                    return Err(format!("Option {name} must be of type {instance_type:?}").into());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns a facet's config with the options the facet was added with applied on top, each option replacing
+/// the field of the config with the same name.
+///
+/// # Errors
+///
+/// Returns an error if an option has the wrong type for its field.
+pub fn with_options<T: Serialize + DeserializeOwned + Clone>(
+    config: &T,
+    options: &BTreeMap<String, Value>,
+) -> Result<T, SkootError> {
+    if options.is_empty() {
+        return Ok(config.clone());
+    }
+    let mut merged = serde_json::to_value(config)?;
+    if let Value::Object(merged) = &mut merged {
+        merged.extend(options.clone());
+    }
+    Ok(serde_json::from_value(merged)?)
+}
+
+/// Returns true if the JSON value is of the JSON Schema instance type.
+fn value_has_type(value: &Value, instance_type: InstanceType) -> bool {
+    match instance_type {
+        InstanceType::Null => value.is_null(),
+        InstanceType::Boolean => value.is_boolean(),
+        InstanceType::Object => value.is_object(),
+        InstanceType::Array => value.is_array(),
+        InstanceType::Number => value.is_number(),
+        InstanceType::String => value.is_string(),
+        InstanceType::Integer => value.is_i64() || value.is_u64(),
+    }
+}
+
+/// The `FacetRegistry` declares which facets are applied to projects. Facets are applied in the order they
+/// are declared, with all the source bundle facets applied before the API bundle facets so that things like
+/// branch protection don't block the initial commits.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct FacetRegistry {
+    /// The facets that are applied by writing files to the project's source.
+    pub source_bundle: Vec<FacetRegistryEntry>,
+    /// The facets that are applied by calling the repo host's API.
+    pub api_bundle: Vec<FacetRegistryEntry>,
+}
+
+impl Default for FacetRegistry {
+    #[allow(clippy::too_many_lines)]
+    fn default() -> Self {
+        use SupportedFacetType::{
+            ArtifactSigning, BestPracticesBadge, BranchProtection, CodeOfConduct, CodeReview,
+            Container, ContributingPolicy, DefaultSourceCode, DependencyUpdateTool, Fuzzing,
+            GUACForwardingConfig, Gitignore, License, PinnedDependencies, PublishPackages, Readme,
+            RepoRuleset, SBOMGenerator, SLSABuild, SLSAVerification, Scorecard, SecurityInsights,
+            SecurityPolicy, StateVerification, VulnerabilityReporting, VulnerabilityScanner, SAST,
+        };
+        Self {
+            source_bundle: vec![
+                FacetRegistryEntry::new(Readme, vec![]),
+                FacetRegistryEntry {
+                    options: Some(LicenseConfig::options_schema()),
+                    ..FacetRegistryEntry::new(License, vec![Label::ScorecardLicense])
+                },
+                FacetRegistryEntry::new(Gitignore, vec![]),
+                FacetRegistryEntry::new(SecurityPolicy, vec![Label::ScorecardSecurityPolicy]),
+                FacetRegistryEntry::new(CodeOfConduct, vec![]),
+                FacetRegistryEntry {
+                    options: Some(ContributingConfig::options_schema()),
+                    ..FacetRegistryEntry::new(ContributingPolicy, vec![])
+                },
+                FacetRegistryEntry::new(SecurityInsights, vec![]),
+                FacetRegistryEntry {
+                    ecosystems: vec!["Go".to_string(), "Maven".to_string(), "Python".to_string()],
+                    ..FacetRegistryEntry::new(
+                        SLSABuild,
+                        vec![
+                            Label::SLSABuildLevel3,
+                            Label::S2C2FAUD1,
+                            Label::ScorecardSignedReleases,
+                        ],
+                    )
+                },
+                FacetRegistryEntry {
+                    ecosystems: vec!["Go".to_string(), "Maven".to_string(), "Python".to_string()],
+                    ..FacetRegistryEntry::new(SLSAVerification, vec![])
+                },
+                // Signing adds a signature, certificate and bundle to every release asset, so it has to be enabled
+                // explicitly. Rust crates are published to crates.io with build provenance rather than attached
+                // to releases.
+                FacetRegistryEntry {
+                    enabled: false,
+                    ecosystems: vec!["Go".to_string(), "Maven".to_string(), "Python".to_string()],
+                    ..FacetRegistryEntry::new(ArtifactSigning, vec![Label::ScorecardSignedReleases])
+                },
+                FacetRegistryEntry::new(SBOMGenerator, vec![]),
+                // Go projects publish a container image and Maven projects their artifacts. Rust workspaces
+                // are versioned with release-please and published to crates.io with build provenance
+                // instead of SLSABuild.
+                FacetRegistryEntry {
+                    ecosystems: vec!["Go".to_string(), "Maven".to_string(), "Rust".to_string()],
+                    options: Some(PublishPackagesConfig::options_schema()),
+                    ..FacetRegistryEntry::new(
+                        PublishPackages,
+                        vec![Label::SLSABuildLevel2, Label::ScorecardPackaging],
+                    )
+                },
+                // Not every project ships as a container image, so it has to be enabled explicitly. Go projects'
+                // images are built with ko by PublishPackages instead, and Rust projects are libraries.
+                FacetRegistryEntry {
+                    enabled: false,
+                    ecosystems: vec!["Maven".to_string(), "Python".to_string()],
+                    options: Some(ContainerConfig::options_schema()),
+                    ..FacetRegistryEntry::new(Container, vec![])
+                },
+                FacetRegistryEntry {
+                    options: Some(DependencyUpdateConfig::options_schema()),
+                    ..FacetRegistryEntry::new(
+                        DependencyUpdateTool,
+                        vec![Label::S2C2FUPD2, Label::ScorecardDependencyUpdateTool],
+                    )
+                },
+                // Fuzzing right now requires a bunch of resources that are unavailable to most projects
+                // without some sort of manual intervention, so it has to be enabled explicitly.
+                FacetRegistryEntry {
+                    enabled: false,
+                    ..FacetRegistryEntry::new(Fuzzing, vec![Label::ScorecardFuzzing])
+                },
+                FacetRegistryEntry::new(Scorecard, vec![]),
+                FacetRegistryEntry::new(StateVerification, vec![]),
+                FacetRegistryEntry::new(SAST, vec![Label::S2C2FSCA1, Label::ScorecardSAST]),
+                FacetRegistryEntry {
+                    options: Some(VulnerabilityScannerConfig::options_schema()),
+                    ..FacetRegistryEntry::new(VulnerabilityScanner, vec![Label::S2C2FSCA1])
+                },
+                FacetRegistryEntry::new(DefaultSourceCode, vec![]),
+                // Pinning resolves every action the workflows use to a commit SHA when they're generated, which
+                // calls out to Github, so it has to be enabled explicitly.
+                FacetRegistryEntry {
+                    enabled: false,
+                    ..FacetRegistryEntry::new(
+                        PinnedDependencies,
+                        vec![Label::ScorecardPinnedDependencies],
+                    )
+                },
+                // Forwarding needs a GUAC instance to forward to, so it has to be enabled explicitly.
+                FacetRegistryEntry {
+                    enabled: false,
+                    options: Some(GuacForwardingConfig::options_schema()),
+                    ..FacetRegistryEntry::new(GUACForwardingConfig, vec![])
+                },
+            ],
+            api_bundle: vec![
+                FacetRegistryEntry {
+                    options: Some(BranchProtectionConfig::options_schema()),
+                    ..FacetRegistryEntry::new(
+                        BranchProtection,
+                        vec![Label::ScorecardBranchProtection],
+                    )
+                },
+                // Rulesets enforce the same rules as branch protection, so they're an opt-in alternative.
+                FacetRegistryEntry {
+                    enabled: false,
+                    options: Some(BranchProtectionConfig::options_schema()),
+                    ..FacetRegistryEntry::new(RepoRuleset, vec![Label::ScorecardBranchProtection])
+                },
+                FacetRegistryEntry::new(VulnerabilityReporting, vec![]),
+                // A maintainer has to log in to the badge site to register the project before the badge can be
+                // looked up, so it has to be added explicitly once the project is registered.
+                FacetRegistryEntry {
+                    enabled: false,
+                    ..FacetRegistryEntry::new(
+                        BestPracticesBadge,
+                        vec![Label::ScorecardCIIBestPractices],
+                    )
+                },
+                // Requiring reviews locks out projects that only have a single maintainer, so it has to be
+                // enabled explicitly.
+                FacetRegistryEntry {
+                    enabled: false,
+                    options: Some(CodeReviewConfig::options_schema()),
+                    ..FacetRegistryEntry::new(CodeReview, vec![Label::ScorecardCodeReview])
+                },
+            ],
+        }
+    }
+}
+
+impl FacetRegistry {
+    /// Returns the source bundle facets that apply to any of the modules in the order they are applied.
+    pub fn source_bundle_facets<'a>(
+        &'a self,
+        modules: &'a [InitializedModule],
+    ) -> impl Iterator<Item = &'a FacetRegistryEntry> {
+        self.source_bundle
+            .iter()
+            .filter(move |entry| entry.applies_to_any(modules))
+    }
+
+    /// Returns the API bundle facets that apply to any of the modules in the order they are applied.
+    pub fn api_bundle_facets<'a>(
+        &'a self,
+        modules: &'a [InitializedModule],
+    ) -> impl Iterator<Item = &'a FacetRegistryEntry> {
+        self.api_bundle
+            .iter()
+            .filter(move |entry| entry.applies_to_any(modules))
+    }
+
+    /// Returns the entry for a facet type, or `None` if it isn't declared in the registry. Disabled entries
+    /// are returned too so facets that aren't applied by default can still be added explicitly.
+    #[must_use]
+    pub fn entry(&self, facet_type: &SupportedFacetType) -> Option<&FacetRegistryEntry> {
+        self.source_bundle
+            .iter()
+            .chain(&self.api_bundle)
+            .find(|entry| &entry.facet_type == facet_type)
+    }
+
+    /// Returns true if Skootrs knows how to apply the facet, which is the case for every facet the default
+    /// registry declares. The other facet types, like `Allstar`, are reserved for facets that aren't
+    /// implemented yet.
+    #[must_use]
+    pub fn is_implemented(facet_type: &SupportedFacetType) -> bool {
+        Self::default().entry(facet_type).is_some()
+    }
+
+    /// Checks that every facet is declared once and in the right bundle.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a facet is declared more than once, or if a facet is declared in the wrong bundle.
+    pub fn validate(&self) -> Result<(), SkootError> {
+        let mut seen = HashSet::new();
+        for (entry, is_api_bundle) in self
+            .source_bundle
+            .iter()
+            .map(|e| (e, false))
+            .chain(self.api_bundle.iter().map(|e| (e, true)))
+        {
+            if !seen.insert(&entry.facet_type) {
+                return Err(format!(
+                    "Facet {} is declared more than once in the facet registry",
+                    entry.facet_type
+                )
+                .into());
+            }
+            if entry.facet_type.is_api_bundle() != is_api_bundle {
+                return Err(format!(
+                    "Facet {} is declared in the wrong bundle of the facet registry",
+                    entry.facet_type
+                )
+                .into());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use crate::skootrs::{InitializedGo, InitializedPython, PythonPackageManager};
+
+    #[test]
+    fn test_facet_registry() {
+        let registry = FacetRegistry::default();
+        assert!(registry.validate().is_ok());
+
+        let go = InitializedEcosystem::Go(InitializedGo {
+            name: "test".to_string(),
+            host: "github.com/test".to_string(),
+        });
+        let python = InitializedEcosystem::Python(InitializedPython {
+            name: "test".to_string(),
+            package_manager: PythonPackageManager::Pip,
+        });
+        let go_module = InitializedModule::from(go);
+        let python_module = InitializedModule::from(python);
+        let go_facets = registry
+            .source_bundle_facets(std::slice::from_ref(&go_module))
+            .map(|e| e.facet_type.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(go_facets.first(), Some(&SupportedFacetType::Readme));
+        assert_eq!(
+            go_facets.last(),
+            Some(&SupportedFacetType::DefaultSourceCode)
+        );
+        assert!(!go_facets.contains(&SupportedFacetType::Fuzzing));
+        assert!(go_facets.contains(&SupportedFacetType::VulnerabilityScanner));
+        assert!(registry
+            .source_bundle_facets(std::slice::from_ref(&python_module))
+            .any(|e| e.facet_type == SupportedFacetType::VulnerabilityScanner));
+
+        // A facet applies to a project if it applies to any of its modules.
+        let python_facets = registry
+            .source_bundle_facets(std::slice::from_ref(&python_module))
+            .count();
+        let monorepo_facets = registry
+            .source_bundle_facets(&[go_module, python_module])
+            .map(|e| e.facet_type.clone())
+            .collect::<Vec<_>>();
+        assert!(monorepo_facets.len() >= python_facets);
+        assert!(monorepo_facets.len() >= go_facets.len());
+
+        assert!(FacetRegistry::is_implemented(
+            &SupportedFacetType::CodeReview
+        ));
+        assert!(!FacetRegistry::is_implemented(&SupportedFacetType::Allstar));
+        assert!(!FacetRegistry::is_implemented(&SupportedFacetType::Other));
+
+        let mut misplaced = registry.clone();
+        let api_bundle_entry = misplaced.api_bundle.pop().unwrap();
+        misplaced.source_bundle.push(api_bundle_entry);
+        assert!(misplaced.validate().is_err());
+
+        let mut duplicated = registry;
+        duplicated
+            .source_bundle
+            .push(FacetRegistryEntry::new(SupportedFacetType::Readme, vec![]));
+        assert!(duplicated.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_options() {
+        let entry: FacetRegistryEntry = serde_json::from_value(serde_json::json!({
+            "facet_type": "Other",
+            "options": {
+                "type": "object",
+                "properties": {
+                    "team": { "type": "string", "description": "The owning team" },
+                    "tier": { "type": "string", "enum": ["gold", "silver"], "default": "silver" },
+                    "retention_days": { "type": "integer" }
+                },
+                "required": ["team"]
+            }
+        }))
+        .unwrap();
+
+        let options =
+            |value: Value| -> BTreeMap<String, Value> { serde_json::from_value(value).unwrap() };
+        assert!(entry
+            .validate_options(&options(
+                serde_json::json!({ "team": "infra", "tier": "gold" })
+            ))
+            .is_ok());
+        assert!(entry
+            .validate_options(&options(serde_json::json!({ "tier": "gold" })))
+            .is_err());
+        assert!(entry
+            .validate_options(&options(
+                serde_json::json!({ "team": "infra", "tier": "bronze" })
+            ))
+            .is_err());
+        assert!(entry
+            .validate_options(&options(
+                serde_json::json!({ "team": "infra", "retention_days": "30" })
+            ))
+            .is_err());
+        assert!(entry
+            .validate_options(&options(
+                serde_json::json!({ "team": "infra", "owner": "me" })
+            ))
+            .is_err());
+        assert!(FacetRegistryEntry::new(SupportedFacetType::Readme, vec![])
+            .validate_options(&options(serde_json::json!({ "team": "infra" })))
+            .is_err());
+    }
+}
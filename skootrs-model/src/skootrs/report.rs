@@ -0,0 +1,270 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `report` module provides the data model for reports that summarize the
+//! security control coverage across all the projects managed by Skootrs.
+
+#![allow(clippy::module_name_repetitions)]
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+};
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::{
+    facet::{FacetAuditStatus, ProjectAudit},
+    InitializedProject,
+};
+
+/// A report summarizing the facet coverage across a set of projects.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct OrgReport {
+    /// The number of projects per ecosystem. A project with modules in several ecosystems counts towards each
+    /// of them.
+    pub projects_by_ecosystem: BTreeMap<String, u64>,
+    /// The number of projects that have each facet type enabled.
+    pub facets_enabled: BTreeMap<String, u64>,
+    /// The number of projects whose files for each facet type drifted from what Skootrs generated.
+    #[serde(default)]
+    pub facets_drifted: BTreeMap<String, u64>,
+    /// The repo URLs of the projects that couldn't be fetched for the report.
+    pub failed_projects: Vec<String>,
+}
+
+impl OrgReport {
+    /// Adds an initialized project to the report.
+    pub fn add_project(&mut self, project: &InitializedProject) {
+        let ecosystems = project
+            .modules
+            .iter()
+            .map(|module| module.ecosystem.name())
+            .collect::<BTreeSet<_>>();
+        for ecosystem in ecosystems {
+            *self
+                .projects_by_ecosystem
+                .entry(ecosystem.to_string())
+                .or_default() += 1;
+        }
+        for facet in project.facets.values() {
+            *self
+                .facets_enabled
+                .entry(facet.facet_type().to_string())
+                .or_default() += 1;
+        }
+    }
+
+    /// Adds the audit of a project to the report.
+    pub fn add_audit(&mut self, audit: &ProjectAudit) {
+        for facet in &audit.facets {
+            if facet.status == FacetAuditStatus::Drifted {
+                *self
+                    .facets_drifted
+                    .entry(facet.facet_type.to_string())
+                    .or_default() += 1;
+            }
+        }
+    }
+
+    /// Adds a project that couldn't be fetched to the report.
+    pub fn add_failed_project(&mut self, repo_url: String) {
+        self.failed_projects.push(repo_url);
+    }
+
+    /// The total number of projects in the report, not including the ones that couldn't be fetched.
+    #[must_use]
+    pub fn projects_total(&self) -> u64 {
+        self.projects_by_ecosystem.values().sum()
+    }
+
+    /// Renders the report in the Prometheus text exposition format. The output can be written to a file for
+    /// the node exporter textfile collector or pushed to a Prometheus Pushgateway.
+    #[must_use]
+    pub fn to_prometheus(&self) -> String {
+        let mut metrics = String::new();
+        // Writing to a `String` can't fail so the results are ignored.
+        let _ = writeln!(
+            metrics,
+            "# HELP skootrs_projects_total The number of projects managed by Skootrs."
+        );
+        let _ = writeln!(metrics, "# TYPE skootrs_projects_total gauge");
+        for (ecosystem, count) in &self.projects_by_ecosystem {
+            let _ = writeln!(
+                metrics,
+                "skootrs_projects_total{{ecosystem=\"{}\"}} {count}",
+                escape_label_value(ecosystem)
+            );
+        }
+        let _ = writeln!(
+            metrics,
+            "# HELP skootrs_facet_enabled The number of projects with a facet type enabled."
+        );
+        let _ = writeln!(metrics, "# TYPE skootrs_facet_enabled gauge");
+        for (facet_type, count) in &self.facets_enabled {
+            let _ = writeln!(
+                metrics,
+                "skootrs_facet_enabled{{type=\"{}\"}} {count}",
+                escape_label_value(facet_type)
+            );
+        }
+        let _ = writeln!(
+            metrics,
+            "# HELP skootrs_facets_drifted The number of projects whose files for a facet type drifted."
+        );
+        let _ = writeln!(metrics, "# TYPE skootrs_facets_drifted gauge");
+        for (facet_type, count) in &self.facets_drifted {
+            let _ = writeln!(
+                metrics,
+                "skootrs_facets_drifted{{type=\"{}\"}} {count}",
+                escape_label_value(facet_type)
+            );
+        }
+        let _ = writeln!(
+            metrics,
+            "# HELP skootrs_projects_failed The number of projects that couldn't be fetched for the report."
+        );
+        let _ = writeln!(metrics, "# TYPE skootrs_projects_failed gauge");
+        let _ = writeln!(
+            metrics,
+            "skootrs_projects_failed {}",
+            self.failed_projects.len()
+        );
+        metrics
+    }
+}
+
+/// Escapes a Prometheus label value.
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::skootrs::{
+        facet::{
+            APIBundleFacet, FacetAudit, InitializedFacet, SourceBundleFacet, SupportedFacetType,
+        },
+        FacetMapKey, InitializedEcosystem, InitializedGithubRepo, InitializedGo, InitializedMaven,
+        InitializedModule, InitializedRepo, InitializedSource,
+    };
+
+    fn project(
+        ecosystem: InitializedEcosystem,
+        facets: &[SupportedFacetType],
+    ) -> InitializedProject {
+        InitializedProject {
+            repo: InitializedRepo::Github(InitializedGithubRepo {
+                name: "test".to_string(),
+                organization: crate::skootrs::GithubUser::User("testuser".to_string()),
+            }),
+            mirror: None,
+            modules: vec![InitializedModule {
+                path: ".".to_string(),
+                ecosystem,
+            }],
+            source: InitializedSource {
+                path: "test".to_string(),
+            },
+            facets: facets
+                .iter()
+                .map(|facet_type| {
+                    let facet = if *facet_type == SupportedFacetType::BranchProtection {
+                        InitializedFacet::APIBundle(APIBundleFacet {
+                            facet_type: facet_type.clone(),
+                            apis: vec![],
+                            labels: vec![],
+                            skipped_reason: None,
+                            template_version: None,
+                            generator_fingerprint: None,
+                        })
+                    } else {
+                        InitializedFacet::SourceBundle(SourceBundleFacet {
+                            source_files: None,
+                            facet_type: facet_type.clone(),
+                            source_files_content: None,
+                            labels: vec![],
+                            packages: vec![],
+                            template_version: None,
+                            generator_fingerprint: None,
+                        })
+                    };
+                    (FacetMapKey::Type(facet_type.clone()), facet)
+                })
+                .collect::<HashMap<_, _>>(),
+            name: "test".to_string(),
+            skootrs_version: None,
+            created_at: None,
+        }
+    }
+
+    #[test]
+    fn test_org_report_to_prometheus() {
+        let mut report = OrgReport::default();
+        report.add_project(&project(
+            InitializedEcosystem::Go(InitializedGo {
+                name: "test".to_string(),
+                host: "github.com".to_string(),
+            }),
+            &[
+                SupportedFacetType::Readme,
+                SupportedFacetType::BranchProtection,
+            ],
+        ));
+        report.add_project(&project(
+            InitializedEcosystem::Maven(InitializedMaven {
+                group_id: "com.example".to_string(),
+                artifact_id: "test".to_string(),
+            }),
+            &[SupportedFacetType::Readme],
+        ));
+        report.add_audit(&ProjectAudit {
+            project_url: "https://github.com/testuser/test".to_string(),
+            facets: vec![
+                FacetAudit {
+                    facet_type: SupportedFacetType::Readme,
+                    status: FacetAuditStatus::Drifted,
+                    files: vec!["README.md".to_string()],
+                },
+                FacetAudit {
+                    facet_type: SupportedFacetType::BranchProtection,
+                    status: FacetAuditStatus::Missing,
+                    files: vec![],
+                },
+            ],
+            allowlist: vec![],
+            best_practices_badge: None,
+        });
+        report.add_failed_project("https://github.com/testuser/missing".to_string());
+
+        assert_eq!(report.projects_total(), 2);
+        let metrics = report.to_prometheus();
+        assert!(metrics.contains("skootrs_projects_total{ecosystem=\"Go\"} 1\n"));
+        assert!(metrics.contains("skootrs_projects_total{ecosystem=\"Maven\"} 1\n"));
+        assert!(metrics.contains("skootrs_facet_enabled{type=\"Readme\"} 2\n"));
+        assert!(metrics.contains("skootrs_facet_enabled{type=\"BranchProtection\"} 1\n"));
+        assert!(metrics.contains("skootrs_facets_drifted{type=\"Readme\"} 1\n"));
+        assert!(!metrics.contains("skootrs_facets_drifted{type=\"BranchProtection\"}"));
+        assert!(metrics.contains("skootrs_projects_failed 1\n"));
+    }
+}
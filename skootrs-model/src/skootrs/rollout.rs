@@ -0,0 +1,207 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `rollout` module provides the data model for rolling a facet out across all the projects of an
+//! organization. A rollout is planned up front as a set of waves, starting with a small canary wave, and the
+//! waves are applied one at a time so a bad facet can be caught before it reaches every repo.
+
+#![allow(clippy::module_name_repetitions)]
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::facet::SupportedFacetType;
+
+/// The parameters for planning or starting a rollout.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct RolloutParams {
+    /// The organization to roll out to, or `None` to be prompted for it.
+    pub organization: Option<String>,
+    /// The type of the facet to roll out, or `None` to be prompted for it.
+    pub facet_type: Option<SupportedFacetType>,
+    /// The number of projects in the canary wave.
+    pub canary_size: usize,
+    /// The maximum number of projects in each wave after the canary.
+    pub batch_size: usize,
+}
+
+/// The change a rollout makes to a project.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub enum RolloutChange {
+    /// The project doesn't have the facet yet so it will be added.
+    Add,
+    /// The project already has the facet so it will be re-rendered from the current templates.
+    Update,
+}
+
+/// The status of a single project in a rollout.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub enum RolloutProjectStatus {
+    /// The project hasn't been updated yet.
+    Pending,
+    /// The project was updated.
+    Updated,
+    /// Updating the project failed. It is retried when the rollout is resumed.
+    Failed {
+        /// The error that caused the update to fail.
+        error: String,
+    },
+}
+
+/// A project that is part of a rollout.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct RolloutProject {
+    /// The URL of the project's repo.
+    pub project_url: String,
+    /// The change the rollout makes to the project.
+    pub change: RolloutChange,
+    /// The status of the project in the rollout.
+    pub status: RolloutProjectStatus,
+}
+
+/// A batch of projects that are updated together.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct RolloutWave {
+    /// The projects in the wave.
+    pub projects: Vec<RolloutProject>,
+}
+
+impl RolloutWave {
+    /// Returns true if every project in the wave was updated.
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.projects
+            .iter()
+            .all(|p| p.status == RolloutProjectStatus::Updated)
+    }
+}
+
+/// The status of a rollout.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub enum RolloutStatus {
+    /// The rollout was planned but no wave has been applied yet.
+    Planned,
+    /// A wave is being applied.
+    InProgress,
+    /// The rollout was paused between waves, either by the user or because a project failed to update.
+    Paused,
+    /// Every wave was applied.
+    Completed,
+}
+
+/// A rollout of a facet across the projects of an organization.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct Rollout {
+    /// The organization the rollout targets.
+    pub organization: String,
+    /// The type of the facet being rolled out.
+    pub facet_type: SupportedFacetType,
+    /// The waves of the rollout in the order they are applied. The first wave is the canary.
+    pub waves: Vec<RolloutWave>,
+    /// The status of the rollout.
+    pub status: RolloutStatus,
+}
+
+impl Rollout {
+    /// Plans a rollout by splitting the projects into a canary wave of `canary_size` projects followed by
+    /// waves of at most `batch_size` projects.
+    #[must_use]
+    pub fn plan(
+        organization: String,
+        facet_type: SupportedFacetType,
+        projects: Vec<RolloutProject>,
+        canary_size: usize,
+        batch_size: usize,
+    ) -> Self {
+        let canary_size = canary_size.min(projects.len());
+        let mut projects = projects.into_iter();
+        let mut waves = vec![];
+        let canary = projects.by_ref().take(canary_size).collect::<Vec<_>>();
+        if !canary.is_empty() {
+            waves.push(RolloutWave { projects: canary });
+        }
+        let rest = projects.collect::<Vec<_>>();
+        waves.extend(rest.chunks(batch_size.max(1)).map(|chunk| RolloutWave {
+            projects: chunk.to_vec(),
+        }));
+        Self {
+            organization,
+            facet_type,
+            waves,
+            status: RolloutStatus::Planned,
+        }
+    }
+
+    /// Returns the index of the next wave to apply, or `None` if every wave was applied.
+    #[must_use]
+    pub fn next_wave(&self) -> Option<usize> {
+        self.waves.iter().position(|w| !w.is_complete())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(n: usize) -> RolloutProject {
+        RolloutProject {
+            project_url: format!("https://github.com/testorg/project{n}"),
+            change: RolloutChange::Add,
+            status: RolloutProjectStatus::Pending,
+        }
+    }
+
+    #[test]
+    fn test_plan_rollout() {
+        let mut rollout = Rollout::plan(
+            "testorg".to_string(),
+            SupportedFacetType::SLSABuild,
+            (0..6).map(project).collect(),
+            1,
+            2,
+        );
+        let wave_sizes = rollout
+            .waves
+            .iter()
+            .map(|w| w.projects.len())
+            .collect::<Vec<_>>();
+        assert_eq!(wave_sizes, vec![1, 2, 2, 1]);
+        assert_eq!(rollout.status, RolloutStatus::Planned);
+        assert_eq!(rollout.next_wave(), Some(0));
+
+        rollout.waves[0].projects[0].status = RolloutProjectStatus::Updated;
+        assert_eq!(rollout.next_wave(), Some(1));
+
+        rollout.waves[1].projects[0].status = RolloutProjectStatus::Updated;
+        rollout.waves[1].projects[1].status = RolloutProjectStatus::Failed {
+            error: "Error".to_string(),
+        };
+        assert_eq!(rollout.next_wave(), Some(1));
+
+        for wave in &mut rollout.waves {
+            for project in &mut wave.projects {
+                project.status = RolloutProjectStatus::Updated;
+            }
+        }
+        assert_eq!(rollout.next_wave(), None);
+    }
+}
@@ -0,0 +1,319 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `ruleset` module provides the repository rulesets the `RepoRuleset` facet creates. Rulesets are
+//! Github's successor to classic branch protection, so they enforce the same configured branch protection
+//! rules, and can be used instead of the `BranchProtection` facet or alongside it.
+
+#![allow(clippy::module_name_repetitions)]
+
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+use utoipa::ToSchema;
+
+use super::branch_protection::BranchProtectionConfig;
+
+/// The ID of Github's built-in repository admin role, which is allowed to bypass a ruleset when admins aren't
+/// held to the rules.
+const REPOSITORY_ADMIN_ROLE_ID: u64 = 5;
+
+/// Where the `RepoRuleset` facet creates its ruleset.
+#[derive(
+    Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, Display, EnumString,
+)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum RulesetScope {
+    /// The ruleset is created on the project's repo.
+    #[default]
+    Repo,
+    /// The ruleset is created on the organization and targets only the project's repo, so repo admins can't
+    /// change it.
+    Org,
+}
+
+/// The options of the ruleset the `RepoRuleset` facet creates. The rules themselves are the configured
+/// branch protection rules.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(default)]
+pub struct RepoRulesetConfig {
+    /// The name of the ruleset. Org rulesets are suffixed with the name of the repo they target.
+    pub name: String,
+    /// Where the ruleset is created.
+    pub scope: RulesetScope,
+}
+
+impl Default for RepoRulesetConfig {
+    fn default() -> Self {
+        Self {
+            name: "skootrs".to_string(),
+            scope: RulesetScope::Repo,
+        }
+    }
+}
+
+impl RepoRulesetConfig {
+    /// Returns the name of the ruleset for a repo.
+    #[must_use]
+    pub fn ruleset_name(&self, repo: &str) -> String {
+        match self.scope {
+            RulesetScope::Repo => self.name.clone(),
+            RulesetScope::Org => format!("{}-{repo}", self.name),
+        }
+    }
+
+    /// Returns the request that creates the ruleset for a repo enforcing the branch protection rules.
+    #[must_use]
+    pub fn request(&self, repo: &str, rules: &BranchProtectionConfig) -> RulesetRequest {
+        let mut ruleset_rules = vec![];
+        if !rules.allow_deletions {
+            ruleset_rules.push(RulesetRule::Deletion);
+        }
+        if !rules.allow_force_pushes {
+            ruleset_rules.push(RulesetRule::NonFastForward);
+        }
+        if rules.required_linear_history {
+            ruleset_rules.push(RulesetRule::RequiredLinearHistory);
+        }
+        if rules.required_signatures {
+            ruleset_rules.push(RulesetRule::RequiredSignatures);
+        }
+        if rules.require_pull_requests {
+            ruleset_rules.push(RulesetRule::PullRequest(PullRequestParameters {
+                required_approving_review_count: rules.required_approving_reviews,
+                dismiss_stale_reviews_on_push: rules.dismiss_stale_reviews,
+                require_code_owner_review: rules.require_code_owner_reviews,
+                require_last_push_approval: false,
+                required_review_thread_resolution: false,
+            }));
+        }
+        if !rules.required_status_checks.is_empty() {
+            ruleset_rules.push(RulesetRule::RequiredStatusChecks(
+                RequiredStatusChecksParameters {
+                    strict_required_status_checks_policy: rules.strict_status_checks,
+                    required_status_checks: rules
+                        .required_status_checks
+                        .iter()
+                        .map(|context| StatusCheck {
+                            context: context.clone(),
+                        })
+                        .collect(),
+                },
+            ));
+        }
+        let bypass_actors = if rules.enforce_admins {
+            vec![]
+        } else {
+            vec![BypassActor {
+                actor_id: REPOSITORY_ADMIN_ROLE_ID,
+                actor_type: "RepositoryRole".to_string(),
+                bypass_mode: "always".to_string(),
+            }]
+        };
+        RulesetRequest {
+            name: self.ruleset_name(repo),
+            target: "branch".to_string(),
+            enforcement: "active".to_string(),
+            bypass_actors,
+            conditions: RulesetConditions {
+                ref_name: NameCondition {
+                    include: rules
+                        .branches
+                        .iter()
+                        .map(|branch| format!("refs/heads/{branch}"))
+                        .collect(),
+                    exclude: vec![],
+                },
+                repository_name: (self.scope == RulesetScope::Org).then(|| NameCondition {
+                    include: vec![repo.to_string()],
+                    exclude: vec![],
+                }),
+            },
+            rules: ruleset_rules,
+        }
+    }
+}
+
+/// The body of Github's create and update ruleset API calls.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct RulesetRequest {
+    /// The name of the ruleset.
+    pub name: String,
+    /// What the ruleset applies to, which is always `branch`.
+    pub target: String,
+    /// Whether the ruleset is enforced, which is always `active`.
+    pub enforcement: String,
+    /// Who can bypass the ruleset.
+    pub bypass_actors: Vec<BypassActor>,
+    /// Which branches, and for org rulesets which repos, the ruleset applies to.
+    pub conditions: RulesetConditions,
+    /// The rules that are enforced.
+    pub rules: Vec<RulesetRule>,
+}
+
+/// An actor that can bypass a ruleset.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct BypassActor {
+    /// The ID of the actor, e.g. a role.
+    pub actor_id: u64,
+    /// The type of the actor, e.g. `RepositoryRole`.
+    pub actor_type: String,
+    /// When the actor can bypass the ruleset, e.g. `always`.
+    pub bypass_mode: String,
+}
+
+/// The conditions a ruleset applies under.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct RulesetConditions {
+    /// The refs the ruleset applies to.
+    pub ref_name: NameCondition,
+    /// The repos an org ruleset applies to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repository_name: Option<NameCondition>,
+}
+
+/// The names a ruleset condition includes and excludes.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct NameCondition {
+    /// The names, or patterns, that are included.
+    pub include: Vec<String>,
+    /// The names, or patterns, that are excluded.
+    pub exclude: Vec<String>,
+}
+
+/// A rule a ruleset enforces.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(tag = "type", content = "parameters", rename_all = "snake_case")]
+pub enum RulesetRule {
+    /// The branches can't be deleted.
+    Deletion,
+    /// The branches can't be force pushed to.
+    NonFastForward,
+    /// Merge commits are refused.
+    RequiredLinearHistory,
+    /// Commits have to be signed.
+    RequiredSignatures,
+    /// Changes have to go through a pull request.
+    PullRequest(PullRequestParameters),
+    /// Status checks have to pass before a change can be merged.
+    RequiredStatusChecks(RequiredStatusChecksParameters),
+}
+
+/// The parameters of the `pull_request` rule.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[allow(clippy::struct_excessive_bools)] // Clippy doesn't like the Github API
+pub struct PullRequestParameters {
+    /// How many approving reviews are needed.
+    pub required_approving_review_count: u8,
+    /// Whether approvals are dismissed when new commits are pushed.
+    pub dismiss_stale_reviews_on_push: bool,
+    /// Whether an approval from a code owner is needed.
+    pub require_code_owner_review: bool,
+    /// Whether the last push has to be approved by someone other than its author.
+    pub require_last_push_approval: bool,
+    /// Whether every review thread has to be resolved.
+    pub required_review_thread_resolution: bool,
+}
+
+/// The parameters of the `required_status_checks` rule.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct RequiredStatusChecksParameters {
+    /// Whether a branch has to be up to date with the target branch before it can be merged.
+    pub strict_required_status_checks_policy: bool,
+    /// The status checks that have to pass.
+    pub required_status_checks: Vec<StatusCheck>,
+}
+
+/// A status check that has to pass.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct StatusCheck {
+    /// The name of the status check.
+    pub context: String,
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn test_ruleset_request() {
+        let rules = BranchProtectionConfig {
+            require_pull_requests: true,
+            required_signatures: true,
+            ..Default::default()
+        };
+        let request = RepoRulesetConfig::default().request("test", &rules);
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            serde_json::json!({
+                "name": "skootrs",
+                "target": "branch",
+                "enforcement": "active",
+                "bypass_actors": [],
+                "conditions": {
+                    "ref_name": { "include": ["refs/heads/main"], "exclude": [] }
+                },
+                "rules": [
+                    { "type": "deletion" },
+                    { "type": "non_fast_forward" },
+                    { "type": "required_linear_history" },
+                    { "type": "required_signatures" },
+                    {
+                        "type": "pull_request",
+                        "parameters": {
+                            "required_approving_review_count": 1,
+                            "dismiss_stale_reviews_on_push": false,
+                            "require_code_owner_review": false,
+                            "require_last_push_approval": false,
+                            "required_review_thread_resolution": false
+                        }
+                    }
+                ]
+            })
+        );
+
+        let org = RepoRulesetConfig {
+            scope: RulesetScope::Org,
+            ..Default::default()
+        };
+        let request = org.request(
+            "test",
+            &BranchProtectionConfig {
+                enforce_admins: false,
+                ..Default::default()
+            },
+        );
+        assert_eq!(request.name, "skootrs-test");
+        assert_eq!(
+            request.conditions.repository_name,
+            Some(NameCondition {
+                include: vec!["test".to_string()],
+                exclude: vec![],
+            })
+        );
+        assert_eq!(request.bypass_actors.len(), 1);
+    }
+}
@@ -0,0 +1,291 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `scan_policy` module provides the data model for a project's vulnerability scan policy. The policy
+//! allowlists findings the project can't fix yet so they don't fail the generated scan workflows. Every
+//! exception has to be justified and expires, so exceptions are tracked and can't be forgotten about.
+//! Which findings fail the scans in the first place is configured by the `VulnerabilityScannerConfig`.
+
+#![allow(clippy::module_name_repetitions)]
+
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use schemars::{schema::SchemaObject, schema_for, JsonSchema};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+#[cfg(feature = "openapi")]
+use utoipa::ToSchema;
+
+use super::SkootError;
+
+/// The path of the scan policy file relative to the root of the project's source. The file belongs to the
+/// project's maintainers, so it isn't part of any facet and isn't checked for drift.
+pub const SCAN_POLICY_PATH: &str = ".github/vulnerability-allowlist.yml";
+
+/// The longest an allowlisted finding can be excepted for, in days from when it's audited.
+pub const MAX_ALLOWLIST_DAYS: i64 = 365;
+
+/// The lowest severity of a finding that fails the generated OSV-Scanner workflow.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, JsonSchema)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum SeverityThreshold {
+    /// Every finding fails the scan.
+    #[default]
+    Low,
+    /// Findings with a CVSS score of 4.0 or more fail the scan.
+    Medium,
+    /// Findings with a CVSS score of 7.0 or more fail the scan.
+    High,
+    /// Findings with a CVSS score of 9.0 or more fail the scan.
+    Critical,
+}
+
+impl SeverityThreshold {
+    /// Returns the lowest CVSS score of a finding with the severity.
+    #[must_use]
+    pub const fn min_score(self) -> f32 {
+        match self {
+            Self::Low => 0.1,
+            Self::Medium => 4.0,
+            Self::High => 7.0,
+            Self::Critical => 9.0,
+        }
+    }
+}
+
+/// Which findings fail the OSV-Scanner workflow the `VulnerabilityScanner` facet generates. Python projects
+/// are scanned with pip-audit instead, which fails on every finding that isn't allowlisted, and Go modules
+/// are also scanned with govulncheck, which fails on every vulnerability their code reaches.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(default)]
+pub struct VulnerabilityScannerConfig {
+    /// The lowest severity of a finding that fails the scan. Findings without a severity always fail it.
+    pub severity_threshold: SeverityThreshold,
+    /// Whether pull requests fail when they add findings. Findings that are already on the base branch only
+    /// fail the scans of the main branch, so a new advisory doesn't block unrelated pull requests.
+    pub fail_on_new_findings: bool,
+    /// The version of OSV-Scanner that scans the dependencies.
+    pub osv_scanner_version: String,
+    /// The version of govulncheck that scans Go modules.
+    pub govulncheck_version: String,
+}
+
+impl Default for VulnerabilityScannerConfig {
+    fn default() -> Self {
+        Self {
+            severity_threshold: SeverityThreshold::Low,
+            fail_on_new_findings: true,
+            osv_scanner_version: "v1.9.1".to_string(),
+            govulncheck_version: "v1.1.4".to_string(),
+        }
+    }
+}
+
+impl VulnerabilityScannerConfig {
+    /// Returns the config with the options a facet was added with applied on top, e.g. a project that only
+    /// fails on high severity findings.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an option has the wrong type, or if the version isn't a valid version.
+    pub fn with_options(&self, options: &BTreeMap<String, Value>) -> Result<Self, SkootError> {
+        let config: Self = super::registry::with_options(self, options)?;
+        for (tool, version) in [
+            ("OSV-Scanner", &config.osv_scanner_version),
+            ("govulncheck", &config.govulncheck_version),
+        ] {
+            if !version
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-'))
+            {
+                return Err(format!("{version} is not a valid {tool} version").into());
+            }
+        }
+        Ok(config)
+    }
+
+    /// Returns the options schema of the `VulnerabilityScanner` facet, which lets each part of the config be
+    /// overridden when the facet is added.
+    #[must_use]
+    pub fn options_schema() -> SchemaObject {
+        schema_for!(Self).schema
+    }
+}
+
+/// A project's vulnerability scan policy.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct ScanPolicy {
+    /// The findings that don't fail the project's vulnerability scans.
+    #[serde(default)]
+    pub allowlist: Vec<AllowlistedFinding>,
+}
+
+/// A single finding that is excepted from the project's vulnerability scans.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct AllowlistedFinding {
+    /// The ID of the finding as reported by the scanner, e.g. `CVE-2024-1234` or `GHSA-xxxx-xxxx-xxxx`.
+    pub id: String,
+    /// Why the finding is excepted, e.g. because there is no fix yet or the vulnerable code isn't reachable.
+    #[serde(default)]
+    pub justification: String,
+    /// The last day the finding is excepted on.
+    #[cfg_attr(feature = "openapi", schema(value_type = String, format = Date))]
+    pub expires: NaiveDate,
+}
+
+/// The status of an allowlisted finding when the scan policy is audited.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub enum AllowlistAuditStatus {
+    /// The exception is justified and hasn't expired.
+    Active,
+    /// The exception has expired, so the finding fails the scans again.
+    Expired,
+    /// The exception doesn't say why the finding is excepted.
+    Unjustified,
+    /// The exception expires further out than the longest an exception can last.
+    ExpiresTooLate,
+}
+
+/// The result of auditing a single allowlisted finding.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct AllowlistAudit {
+    /// The ID of the finding.
+    pub id: String,
+    /// The last day the finding is excepted on.
+    #[cfg_attr(feature = "openapi", schema(value_type = String, format = Date))]
+    pub expires: NaiveDate,
+    /// The status of the exception.
+    pub status: AllowlistAuditStatus,
+}
+
+impl ScanPolicy {
+    /// Audits every allowlisted finding of the policy as of `today`.
+    #[must_use]
+    pub fn audit(&self, today: NaiveDate) -> Vec<AllowlistAudit> {
+        self.allowlist
+            .iter()
+            .map(|finding| {
+                let status = if finding.expires < today {
+                    AllowlistAuditStatus::Expired
+                } else if finding.justification.trim().is_empty() {
+                    AllowlistAuditStatus::Unjustified
+                } else if (finding.expires - today).num_days() > MAX_ALLOWLIST_DAYS {
+                    AllowlistAuditStatus::ExpiresTooLate
+                } else {
+                    AllowlistAuditStatus::Active
+                };
+                AllowlistAudit {
+                    id: finding.id.clone(),
+                    expires: finding.expires,
+                    status,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn test_scan_policy_audit() {
+        let today = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+        let finding = |id: &str, justification: &str, expires: NaiveDate| AllowlistedFinding {
+            id: id.to_string(),
+            justification: justification.to_string(),
+            expires,
+        };
+        let policy = ScanPolicy {
+            allowlist: vec![
+                finding("CVE-2024-0001", "No fix released yet", today),
+                finding(
+                    "CVE-2024-0002",
+                    "No fix released yet",
+                    NaiveDate::from_ymd_opt(2024, 5, 31).unwrap(),
+                ),
+                finding("CVE-2024-0003", " ", today),
+                finding(
+                    "CVE-2024-0004",
+                    "Not reachable",
+                    NaiveDate::from_ymd_opt(2025, 7, 1).unwrap(),
+                ),
+            ],
+        };
+
+        let statuses = policy
+            .audit(today)
+            .into_iter()
+            .map(|a| a.status)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            statuses,
+            vec![
+                AllowlistAuditStatus::Active,
+                AllowlistAuditStatus::Expired,
+                AllowlistAuditStatus::Unjustified,
+                AllowlistAuditStatus::ExpiresTooLate,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_vulnerability_scanner_config() {
+        let config = VulnerabilityScannerConfig::default()
+            .with_options(&BTreeMap::from([(
+                "severity_threshold".to_string(),
+                Value::from("high"),
+            )]))
+            .unwrap();
+        assert_eq!(config.severity_threshold, SeverityThreshold::High);
+        assert!(config.fail_on_new_findings);
+        assert!(VulnerabilityScannerConfig::default()
+            .with_options(&BTreeMap::from([(
+                "osv_scanner_version".to_string(),
+                Value::from("v1.9.1 && curl"),
+            )]))
+            .is_err());
+        assert!(VulnerabilityScannerConfig::default()
+            .with_options(&BTreeMap::from([(
+                "govulncheck_version".to_string(),
+                Value::from("latest; curl"),
+            )]))
+            .is_err());
+    }
+
+    #[test]
+    fn test_scan_policy_deserialize() {
+        let policy: ScanPolicy = serde_json::from_str(
+            r#"{"allowlist": [{"id": "CVE-2024-0001", "justification": "No fix", "expires": "2024-06-01"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            policy.allowlist[0].expires,
+            NaiveDate::from_ymd_opt(2024, 6, 1).unwrap()
+        );
+        assert_eq!(
+            serde_json::from_str::<ScanPolicy>("{}").unwrap(),
+            ScanPolicy::default()
+        );
+    }
+}
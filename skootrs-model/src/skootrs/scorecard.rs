@@ -0,0 +1,254 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `scorecard` module provides the data model for a project's live `OpenSSF` Scorecard results, and the gap
+//! report that maps each failing check to the facets that address it.
+
+#![allow(clippy::module_name_repetitions)]
+
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+use utoipa::ToSchema;
+
+use super::{facet::SupportedFacetType, label::Label, registry::FacetRegistry};
+
+/// The highest score a Scorecard check can get.
+pub const MAX_CHECK_SCORE: i64 = 10;
+
+/// Where the live Scorecard results of projects are fetched from.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(default)]
+pub struct ScorecardConfig {
+    /// The URL of the Scorecard API, e.g. `https://api.securityscorecards.dev`. The API only has results for
+    /// public repos that the `Scorecard` facet's workflow publishes results for, or that Scorecard scans on
+    /// its own.
+    pub api_url: String,
+}
+
+impl Default for ScorecardConfig {
+    fn default() -> Self {
+        Self {
+            api_url: "https://api.securityscorecards.dev".to_string(),
+        }
+    }
+}
+
+/// The live Scorecard results of a project, as returned by the Scorecard API.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct ScorecardResults {
+    /// When Scorecard last scanned the project.
+    pub date: String,
+    /// The aggregate score of the project, out of 10.
+    pub score: f64,
+    /// The results of each check.
+    pub checks: Vec<ScorecardCheck>,
+}
+
+/// The result of a single Scorecard check.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct ScorecardCheck {
+    /// The name of the check, e.g. `Branch-Protection`.
+    pub name: String,
+    /// The score of the check out of 10, or -1 if the check was inconclusive.
+    pub score: i64,
+    /// Why the check got its score.
+    #[serde(default)]
+    pub reason: String,
+}
+
+/// Where a project stands on a Scorecard check.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Display, EnumString)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub enum ScorecardCheckStatus {
+    /// The check has the highest score.
+    Passing,
+    /// The check isn't passing and a facet that addresses it isn't applied to the project.
+    FacetMissing,
+    /// The check isn't passing even though a facet that addresses it is applied, e.g. because the facet's
+    /// workflow hasn't run yet or the facet was changed in the repo.
+    FacetApplied,
+    /// The check isn't passing and no facet addresses it.
+    Unaddressed,
+    /// Scorecard couldn't score the check.
+    Inconclusive,
+}
+
+/// A Scorecard check of a project, along with the facets that address it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct ScorecardCheckGap {
+    /// The name of the check, e.g. `Branch-Protection`.
+    pub check: String,
+    /// The score of the check out of 10, or -1 if the check was inconclusive.
+    pub score: i64,
+    /// Why the check got its score.
+    pub reason: String,
+    /// Where the project stands on the check.
+    pub status: ScorecardCheckStatus,
+    /// The facets in the facet registry that address the check.
+    pub facets: Vec<SupportedFacetType>,
+    /// The facets that address the check but aren't applied to the project. Adding one of them with
+    /// `skootrs facet add` should close the gap.
+    pub missing_facets: Vec<SupportedFacetType>,
+}
+
+/// The report of a project's live Scorecard results and the gaps in them.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct ScorecardReport {
+    /// The URL of the project's repo.
+    pub project_url: String,
+    /// When Scorecard last scanned the project.
+    pub date: String,
+    /// The aggregate score of the project, out of 10.
+    pub score: f64,
+    /// Every check of the project, the ones with the lowest scores first.
+    pub checks: Vec<ScorecardCheckGap>,
+}
+
+impl ScorecardReport {
+    /// Builds the report of a project's Scorecard results. A check is addressed by the facets in the registry
+    /// that are labeled with it, e.g. `BranchProtection` addresses `Branch-Protection`.
+    #[must_use]
+    pub fn new(
+        project_url: String,
+        results: ScorecardResults,
+        registry: &FacetRegistry,
+        applied_facets: &[SupportedFacetType],
+    ) -> Self {
+        let mut checks: Vec<ScorecardCheckGap> = results
+            .checks
+            .into_iter()
+            .map(|check| {
+                let facets: Vec<SupportedFacetType> = Label::for_scorecard_check(&check.name)
+                    .map_or_else(Vec::new, |label| {
+                        registry
+                            .source_bundle
+                            .iter()
+                            .chain(&registry.api_bundle)
+                            .filter(|entry| entry.labels.contains(&label))
+                            .map(|entry| entry.facet_type.clone())
+                            .collect()
+                    });
+                let missing_facets: Vec<SupportedFacetType> = facets
+                    .iter()
+                    .filter(|facet| !applied_facets.contains(facet))
+                    .cloned()
+                    .collect();
+                let status = match check.score {
+                    score if score < 0 => ScorecardCheckStatus::Inconclusive,
+                    score if score >= MAX_CHECK_SCORE => ScorecardCheckStatus::Passing,
+                    _ if facets.is_empty() => ScorecardCheckStatus::Unaddressed,
+                    // Facets like `BranchProtection` and `RepoRuleset` are alternatives, so any one of them
+                    // being applied addresses the check.
+                    _ if missing_facets.len() < facets.len() => ScorecardCheckStatus::FacetApplied,
+                    _ => ScorecardCheckStatus::FacetMissing,
+                };
+                ScorecardCheckGap {
+                    check: check.name,
+                    score: check.score,
+                    reason: check.reason,
+                    status,
+                    facets,
+                    missing_facets,
+                }
+            })
+            .collect();
+        checks.sort_by(|a, b| a.score.cmp(&b.score).then_with(|| a.check.cmp(&b.check)));
+        Self {
+            project_url,
+            date: results.date,
+            score: results.score,
+            checks,
+        }
+    }
+
+    /// Returns the checks that aren't passing.
+    pub fn gaps(&self) -> impl Iterator<Item = &ScorecardCheckGap> {
+        self.checks
+            .iter()
+            .filter(|check| check.status != ScorecardCheckStatus::Passing)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(name: &str, score: i64) -> ScorecardCheck {
+        ScorecardCheck {
+            name: name.to_string(),
+            score,
+            reason: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_scorecard_report() {
+        let results = ScorecardResults {
+            date: "2024-04-01".to_string(),
+            score: 5.5,
+            checks: vec![
+                check("License", 10),
+                check("Branch-Protection", 3),
+                check("Fuzzing", 0),
+                check("Maintained", 0),
+                check("Packaging", -1),
+            ],
+        };
+        let report = ScorecardReport::new(
+            "https://github.com/kusaridev/skootrs".to_string(),
+            results,
+            &FacetRegistry::default(),
+            &[
+                SupportedFacetType::License,
+                SupportedFacetType::BranchProtection,
+            ],
+        );
+        let status = |name: &str| {
+            report
+                .checks
+                .iter()
+                .find(|check| check.check == name)
+                .map(|check| check.status)
+        };
+        assert_eq!(status("License"), Some(ScorecardCheckStatus::Passing));
+        assert_eq!(
+            status("Branch-Protection"),
+            Some(ScorecardCheckStatus::FacetApplied)
+        );
+        assert_eq!(status("Fuzzing"), Some(ScorecardCheckStatus::FacetMissing));
+        assert_eq!(
+            status("Maintained"),
+            Some(ScorecardCheckStatus::Unaddressed)
+        );
+        assert_eq!(
+            status("Packaging"),
+            Some(ScorecardCheckStatus::Inconclusive)
+        );
+        assert_eq!(report.checks[0].check, "Packaging");
+        assert_eq!(report.gaps().count(), 4);
+
+        let fuzzing = report
+            .checks
+            .iter()
+            .find(|check| check.check == "Fuzzing")
+            .map(|check| check.missing_facets.clone());
+        assert_eq!(fuzzing, Some(vec![SupportedFacetType::Fuzzing]));
+    }
+}
@@ -0,0 +1,132 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `signing` module provides what the `ArtifactSigning` facet signs with cosign keyless signing, and
+//! how consumers verify it.
+
+#![allow(clippy::module_name_repetitions)]
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "openapi")]
+use utoipa::ToSchema;
+
+use super::{InitializedRepo, SkootError};
+
+/// The workflow the `ArtifactSigning` facet signs releases in. Its path is part of the identity in the
+/// signing certificates.
+pub const SIGNING_WORKFLOW: &str = ".github/workflows/sign-release.yml";
+
+/// The issuer of the OIDC tokens Github Actions signs with.
+pub const CERTIFICATE_OIDC_ISSUER: &str = "https://token.actions.githubusercontent.com";
+
+/// What the `ArtifactSigning` facet signs along with the assets of a project's releases.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(default)]
+pub struct ArtifactSigningConfig {
+    /// Whether the container images Skootrs publishes for a project are signed for each release too: the
+    /// image Go projects publish with ko and the image of the `Container` facet. Images that weren't
+    /// published for a release are skipped.
+    pub sign_images: bool,
+    /// Further images that are signed for each release, without a tag, e.g. `docker.io/my-org/my-project`.
+    pub images: Vec<String>,
+}
+
+impl Default for ArtifactSigningConfig {
+    fn default() -> Self {
+        Self {
+            sign_images: true,
+            images: vec![],
+        }
+    }
+}
+
+impl ArtifactSigningConfig {
+    /// Checks the images are plain references, since they're written into the signing workflow.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an image isn't a valid reference or has a tag or digest.
+    pub fn validate(&self) -> Result<(), SkootError> {
+        for image in &self.images {
+            let (repository, name) = image.rsplit_once('/').unwrap_or(("", image));
+            if name.is_empty()
+                || name.contains([':', '@'])
+                || !image.chars().all(|c| {
+                    c.is_ascii_lowercase()
+                        || c.is_ascii_digit()
+                        || matches!(c, '.' | '-' | '_' | '/' | ':')
+                })
+                || repository.contains('@')
+            {
+                return Err(format!(
+                    "{image} is not a valid image to sign, it has to be a lowercase reference without a tag"
+                )
+                .into());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the identity in the certificates of the signatures the `ArtifactSigning` facet makes for a repo.
+/// The signing workflow runs on the default branch after each release.
+#[must_use]
+pub fn certificate_identity(repo: &InitializedRepo) -> String {
+    format!("{}/{SIGNING_WORKFLOW}@refs/heads/main", repo.full_url())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::skootrs::{GithubUser, InitializedGithubRepo};
+
+    #[test]
+    fn test_validate() {
+        assert!(ArtifactSigningConfig::default().validate().is_ok());
+        for image in ["docker.io/my-org/my-project", "localhost:5000/project"] {
+            let config = ArtifactSigningConfig {
+                images: vec![image.to_string()],
+                ..Default::default()
+            };
+            assert!(config.validate().is_ok(), "{image}");
+        }
+        for image in [
+            "",
+            "ghcr.io/my-org/my-project:v1",
+            "ghcr.io/my-org/my-project@sha256:abc",
+            "ghcr.io/My-Org/project",
+            "ghcr.io/project\n  run: whoami",
+        ] {
+            let config = ArtifactSigningConfig {
+                images: vec![image.to_string()],
+                ..Default::default()
+            };
+            assert!(config.validate().is_err(), "{image}");
+        }
+    }
+
+    #[test]
+    fn test_certificate_identity() {
+        let repo = InitializedRepo::Github(InitializedGithubRepo {
+            name: "test".to_string(),
+            organization: GithubUser::Organization("testorg".to_string()),
+        });
+        assert_eq!(
+            certificate_identity(&repo),
+            "https://github.com/testorg/test/.github/workflows/sign-release.yml@refs/heads/main"
+        );
+    }
+}
@@ -0,0 +1,273 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `status` module provides the data model for the one-page summary of a project that
+//! `skootrs project status` shows, combining its cached state, its audit and its latest release.
+
+#![allow(clippy::module_name_repetitions)]
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "openapi")]
+use utoipa::ToSchema;
+
+use super::{
+    facet::{FacetAuditStatus, InitializedFacet, ProjectAudit, SupportedFacetType},
+    InitializedProject, ProjectOutputReference, ProjectOutputType,
+};
+
+/// A one-page summary of a project.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct ProjectStatus {
+    /// The URL of the project's repo.
+    pub project_url: String,
+    /// The number of source bundle facets of the project, i.e. the ones made of files in its repo.
+    pub source_bundle_facets: u64,
+    /// The number of API bundle facets of the project, i.e. the ones made of settings of its repo.
+    pub api_bundle_facets: u64,
+    /// The live status of the project's branch protection, or `None` if the project doesn't have the
+    /// `BranchProtection` facet.
+    pub branch_protection: Option<FacetAuditStatus>,
+    /// The outputs of the project's latest release, or `None` if the project hasn't been released or the
+    /// release couldn't be fetched.
+    pub latest_release: Option<ReleaseStatus>,
+    /// The facets whose files or settings drifted from what Skootrs recorded, e.g.
+    /// `SecurityPolicy is Drifted: SECURITY.md`.
+    pub drift: Vec<String>,
+}
+
+/// What's attached to a project's release.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct ReleaseStatus {
+    /// The number of outputs of the release.
+    pub outputs: u64,
+    /// Whether an SBOM is attached to the release.
+    pub sbom: bool,
+    /// Whether SLSA provenance or another in-toto attestation is attached to the release.
+    pub provenance: bool,
+}
+
+impl ReleaseStatus {
+    /// Summarizes the outputs of a release. Published packages aren't attached to the release, so they
+    /// aren't counted.
+    #[must_use]
+    pub fn new(outputs: &[ProjectOutputReference]) -> Self {
+        let attached = outputs
+            .iter()
+            .filter(|output| !matches!(output.output_type, ProjectOutputType::Package));
+        Self {
+            outputs: attached.clone().count() as u64,
+            sbom: attached
+                .clone()
+                .any(|output| matches!(output.output_type, ProjectOutputType::SBOM)),
+            provenance: attached.clone().any(|output| {
+                matches!(
+                    output.output_type,
+                    ProjectOutputType::SLSAAttestation
+                        | ProjectOutputType::InToto
+                        | ProjectOutputType::Attestation(_)
+                )
+            }),
+        }
+    }
+}
+
+impl ProjectStatus {
+    /// Builds the summary of a project from its cached state, its audit and the outputs of its latest
+    /// release, if it has one.
+    #[must_use]
+    pub fn new(
+        project: &InitializedProject,
+        audit: &ProjectAudit,
+        latest_release: Option<&[ProjectOutputReference]>,
+    ) -> Self {
+        let (source_bundle_facets, api_bundle_facets) =
+            project
+                .facets
+                .values()
+                .fold((0, 0), |(source, api), facet| match facet {
+                    InitializedFacet::SourceBundle(_) => (source + 1, api),
+                    InitializedFacet::APIBundle(_) => (source, api + 1),
+                });
+        Self {
+            project_url: project.repo.full_url(),
+            source_bundle_facets,
+            api_bundle_facets,
+            branch_protection: audit
+                .facets
+                .iter()
+                .find(|f| f.facet_type == SupportedFacetType::BranchProtection)
+                .map(|f| f.status.clone()),
+            latest_release: latest_release.map(ReleaseStatus::new),
+            drift: audit
+                .facets
+                .iter()
+                .filter(|f| {
+                    matches!(
+                        f.status,
+                        FacetAuditStatus::Drifted | FacetAuditStatus::Missing
+                    )
+                })
+                .map(|f| {
+                    if f.files.is_empty() {
+                        format!("{} is {:?}", f.facet_type, f.status)
+                    } else {
+                        format!("{} is {:?}: {}", f.facet_type, f.status, f.files.join(", "))
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::skootrs::{
+        facet::{APIBundleFacet, FacetAudit, SourceBundleFacet},
+        label::Label,
+        FacetMapKey, GithubUser, InitializedEcosystem, InitializedGithubRepo, InitializedGo,
+        InitializedRepo, InitializedSource,
+    };
+
+    fn source_facet(facet_type: SupportedFacetType) -> InitializedFacet {
+        InitializedFacet::SourceBundle(SourceBundleFacet {
+            source_files: None,
+            facet_type,
+            source_files_content: None,
+            labels: vec![],
+            packages: vec![],
+            template_version: None,
+            generator_fingerprint: None,
+        })
+    }
+
+    fn project() -> InitializedProject {
+        InitializedProject {
+            repo: InitializedRepo::Github(InitializedGithubRepo {
+                name: "test".to_string(),
+                organization: GithubUser::User("testuser".to_string()),
+            }),
+            mirror: None,
+            modules: vec![InitializedEcosystem::Go(InitializedGo {
+                name: "test".to_string(),
+                host: "github.com/testuser".to_string(),
+            })
+            .into()],
+            source: InitializedSource {
+                path: "test".to_string(),
+            },
+            facets: HashMap::from([
+                (
+                    FacetMapKey::Type(SupportedFacetType::Readme),
+                    source_facet(SupportedFacetType::Readme),
+                ),
+                (
+                    FacetMapKey::Type(SupportedFacetType::SecurityPolicy),
+                    source_facet(SupportedFacetType::SecurityPolicy),
+                ),
+                (
+                    FacetMapKey::Type(SupportedFacetType::BranchProtection),
+                    InitializedFacet::APIBundle(APIBundleFacet {
+                        apis: vec![],
+                        facet_type: SupportedFacetType::BranchProtection,
+                        labels: vec![Label::ScorecardBranchProtection],
+                        skipped_reason: None,
+                        template_version: None,
+                        generator_fingerprint: None,
+                    }),
+                ),
+            ]),
+            name: "test".to_string(),
+            skootrs_version: None,
+            created_at: None,
+        }
+    }
+
+    fn audit() -> ProjectAudit {
+        ProjectAudit {
+            project_url: project().repo.full_url(),
+            facets: vec![
+                FacetAudit {
+                    facet_type: SupportedFacetType::BranchProtection,
+                    status: FacetAuditStatus::Missing,
+                    files: vec![],
+                },
+                FacetAudit {
+                    facet_type: SupportedFacetType::Readme,
+                    status: FacetAuditStatus::Compliant,
+                    files: vec![],
+                },
+                FacetAudit {
+                    facet_type: SupportedFacetType::SecurityPolicy,
+                    status: FacetAuditStatus::Drifted,
+                    files: vec!["SECURITY.md".to_string()],
+                },
+            ],
+            allowlist: vec![],
+            best_practices_badge: None,
+        }
+    }
+
+    #[test]
+    fn test_facet_counts() {
+        let status = ProjectStatus::new(&project(), &audit(), None);
+        assert_eq!(status.source_bundle_facets, 2);
+        assert_eq!(status.api_bundle_facets, 1);
+        assert_eq!(status.branch_protection, Some(FacetAuditStatus::Missing));
+    }
+
+    #[test]
+    fn test_latest_release() {
+        let outputs = [
+            ProjectOutputReference {
+                output_type: ProjectOutputType::SBOM,
+                name: "test.spdx.json".to_string(),
+                labels: vec![],
+            },
+            ProjectOutputReference {
+                output_type: ProjectOutputType::Package,
+                name: "ghcr.io/testuser/test".to_string(),
+                labels: vec![],
+            },
+        ];
+        assert_eq!(
+            ProjectStatus::new(&project(), &audit(), Some(&outputs)).latest_release,
+            Some(ReleaseStatus {
+                outputs: 1,
+                sbom: true,
+                provenance: false,
+            })
+        );
+        assert_eq!(
+            ProjectStatus::new(&project(), &audit(), None).latest_release,
+            None
+        );
+    }
+
+    #[test]
+    fn test_drift() {
+        assert_eq!(
+            ProjectStatus::new(&project(), &audit(), None).drift,
+            vec![
+                "BranchProtection is Missing".to_string(),
+                "SecurityPolicy is Drifted: SECURITY.md".to_string(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,185 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `telemetry` module provides the `telemetry` config, which picks where Skootrs exports the traces of
+//! what it does and how many of them it keeps.
+
+#![allow(clippy::module_name_repetitions)]
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// How Skootrs exports the traces of what it does. Older configs set `telemetry` to `true` or `false`, which
+/// still mean exporting to a local Jaeger and only logging spans to stdout.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(from = "TelemetrySetting")]
+pub struct TelemetryConfig {
+    /// Where traces are exported.
+    pub exporter: TelemetryExporter,
+    /// The share of traces that are sampled, from `0.0` for none to `1.0` for all. Traces started by a
+    /// caller of the daemon follow the caller's sampling decision instead.
+    pub sampling_ratio: f64,
+    /// The name Skootrs reports its traces under.
+    pub service_name: String,
+}
+
+/// Where traces are exported. Spans are logged to stdout whichever exporter is used.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TelemetryExporter {
+    /// Traces aren't exported, spans are only logged to stdout.
+    Stdout,
+    /// Traces are exported to a local Jaeger with OTLP over gRPC on `localhost:4317`.
+    #[default]
+    Jaeger,
+    /// Traces are sent to an `OpenTelemetry` collector with OTLP.
+    Otlp {
+        /// How the traces are sent.
+        #[serde(default)]
+        protocol: OtlpProtocol,
+        /// The collector's endpoint, e.g. `http://localhost:4317` for gRPC or
+        /// `http://localhost:4318/v1/traces` for HTTP.
+        endpoint: String,
+        /// Headers sent along with each export, e.g. an `Authorization` header. They're sent as metadata
+        /// over gRPC.
+        #[serde(default)]
+        headers: BTreeMap<String, String>,
+    },
+}
+
+/// The transports and encodings of OTLP.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum OtlpProtocol {
+    /// gRPC, usually on port 4317.
+    #[default]
+    Grpc,
+    /// HTTP with protobuf bodies, usually on port 4318.
+    HttpProto,
+    /// HTTP with JSON bodies, usually on port 4318.
+    HttpJson,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self {
+            exporter: TelemetryExporter::default(),
+            sampling_ratio: default_sampling_ratio(),
+            service_name: default_service_name(),
+        }
+    }
+}
+
+impl TelemetryConfig {
+    /// Returns true if traces are exported anywhere.
+    #[must_use]
+    pub fn exports(&self) -> bool {
+        self.exporter != TelemetryExporter::Stdout
+    }
+}
+
+const fn default_sampling_ratio() -> f64 {
+    1.0
+}
+
+fn default_service_name() -> String {
+    "skootrs".to_string()
+}
+
+/// What the `telemetry` option can be set to: a boolean like older configs have, or the full config.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TelemetrySetting {
+    Enabled(bool),
+    Config {
+        #[serde(default)]
+        exporter: TelemetryExporter,
+        #[serde(default = "default_sampling_ratio")]
+        sampling_ratio: f64,
+        #[serde(default = "default_service_name")]
+        service_name: String,
+    },
+}
+
+impl From<TelemetrySetting> for TelemetryConfig {
+    fn from(setting: TelemetrySetting) -> Self {
+        match setting {
+            TelemetrySetting::Enabled(enabled) => Self {
+                exporter: if enabled {
+                    TelemetryExporter::Jaeger
+                } else {
+                    TelemetryExporter::Stdout
+                },
+                ..Self::default()
+            },
+            TelemetrySetting::Config {
+                exporter,
+                sampling_ratio,
+                service_name,
+            } => Self {
+                exporter,
+                sampling_ratio,
+                service_name,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn test_telemetry_config_deserialize() {
+        let config: TelemetryConfig = serde_json::from_str("true").unwrap();
+        assert_eq!(config, TelemetryConfig::default());
+        let config: TelemetryConfig = serde_json::from_str("false").unwrap();
+        assert_eq!(config.exporter, TelemetryExporter::Stdout);
+        assert!(!config.exports());
+
+        let config: TelemetryConfig = serde_json::from_str(
+            r#"{"exporter": {"type": "otlp", "protocol": "http_json", "endpoint": "http://collector:4318/v1/traces"}, "sampling_ratio": 0.1}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.exporter,
+            TelemetryExporter::Otlp {
+                protocol: OtlpProtocol::HttpJson,
+                endpoint: "http://collector:4318/v1/traces".to_string(),
+                headers: BTreeMap::new(),
+            }
+        );
+        assert!((config.sampling_ratio - 0.1).abs() < f64::EPSILON);
+        assert_eq!(config.service_name, "skootrs");
+
+        let config: TelemetryConfig = serde_json::from_str(
+            r#"{"exporter": {"type": "otlp", "endpoint": "http://collector:4317"}}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            config.exporter,
+            TelemetryExporter::Otlp {
+                protocol: OtlpProtocol::Grpc,
+                ..
+            }
+        ));
+    }
+}
@@ -0,0 +1,572 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `validation` module checks the parameters of a new project before anything is created. Project names
+//! end up in repo URLs, module paths, package coordinates and local paths, so a name that isn't valid in all
+//! of them is rejected up front with the field it's in, instead of failing partway through creating the
+//! project.
+
+use std::{error::Error, fmt};
+
+use super::{EcosystemInitializeParams, ProjectCreateParams, RepoCreateParams};
+
+/// The longest name Github allows for a repo.
+const MAX_PROJECT_NAME_LENGTH: usize = 100;
+
+/// The longest name Github allows for a user or organization.
+const MAX_GITHUB_OWNER_LENGTH: usize = 39;
+
+/// The longest name crates.io allows for a crate.
+const MAX_CRATE_NAME_LENGTH: usize = 64;
+
+/// A field of the parameters that isn't valid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldError {
+    /// The path of the field, e.g. `ecosystem_params.group_id`.
+    pub field: String,
+    /// Why the field isn't valid.
+    pub message: String,
+}
+
+/// The `ValidationError` struct represents every field of a project's parameters that isn't valid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    /// The fields that aren't valid.
+    pub errors: Vec<FieldError>,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "The project parameters aren't valid: ")?;
+        let errors = self
+            .errors
+            .iter()
+            .map(|e| format!("{} {}", e.field, e.message))
+            .collect::<Vec<_>>();
+        write!(f, "{}", errors.join("; "))
+    }
+}
+
+impl Error for ValidationError {}
+
+impl ProjectCreateParams {
+    /// Checks every field of the parameters that ends up in a name, path or URL.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `ValidationError` with every field that isn't valid.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        let mut errors = Vec::new();
+        let mut check = |field: &str, message: Option<String>| {
+            if let Some(message) = message {
+                errors.push(FieldError {
+                    field: field.to_string(),
+                    message,
+                });
+            }
+        };
+        check("name", project_name_error(&self.name));
+        if let RepoCreateParams::Github(repo) = &self.repo_params {
+            check("repo_params.name", project_name_error(&repo.name));
+            check(
+                "repo_params.organization",
+                github_owner_error(&repo.organization.get_name()),
+            );
+        }
+        if let RepoCreateParams::Bitbucket(repo) = &self.repo_params {
+            check("repo_params.name", project_name_error(&repo.name));
+        }
+        if self.ecosystem_params.is_empty() {
+            check("ecosystem_params", Some("can't be empty".to_string()));
+        }
+        let single = self.ecosystem_params.len() == 1;
+        let mut module_dirs = Vec::new();
+        for (i, module) in self.ecosystem_params.iter().enumerate() {
+            // A project with a single module reports its fields like projects did before they could have
+            // several.
+            let prefix = if single && module.initialized_module().is_root() {
+                "ecosystem_params".to_string()
+            } else {
+                format!("ecosystem_params[{i}].ecosystem")
+            };
+            let dir = module
+                .path
+                .trim_start_matches("./")
+                .trim_matches('/')
+                .to_string();
+            let path_error = module_path_error(&module.path).or_else(|| {
+                module_dirs
+                    .contains(&dir)
+                    .then(|| format!("{:?} is the path of another module", module.path))
+            });
+            check(&format!("ecosystem_params[{i}].path"), path_error);
+            module_dirs.push(dir);
+            match &module.ecosystem {
+                EcosystemInitializeParams::Go(go) => {
+                    check(&format!("{prefix}.host"), go_host_error(&go.host));
+                    check(&format!("{prefix}.name"), go_element_error(&go.name));
+                    if let Some(go_version) = &go.go_version {
+                        check(
+                            &format!("{prefix}.go_version"),
+                            go_version_error(go_version),
+                        );
+                    }
+                }
+                EcosystemInitializeParams::Maven(maven) => {
+                    check(
+                        &format!("{prefix}.group_id"),
+                        group_id_error(&maven.group_id),
+                    );
+                    check(
+                        &format!("{prefix}.artifact_id"),
+                        artifact_id_error(&maven.artifact_id),
+                    );
+                    check(
+                        &format!("{prefix}.archetype.group_id"),
+                        group_id_error(&maven.archetype.group_id),
+                    );
+                    check(
+                        &format!("{prefix}.archetype.artifact_id"),
+                        artifact_id_error(&maven.archetype.artifact_id),
+                    );
+                    if let Some(version) = &maven.archetype.version {
+                        check(
+                            &format!("{prefix}.archetype.version"),
+                            maven_version_error(version),
+                        );
+                    }
+                    if let Some(java_version) = &maven.java_version {
+                        check(
+                            &format!("{prefix}.java_version"),
+                            java_version_error(java_version),
+                        );
+                    }
+                }
+                EcosystemInitializeParams::Python(python) => {
+                    check(&format!("{prefix}.name"), python_name_error(&python.name));
+                }
+                EcosystemInitializeParams::Rust(rust) => {
+                    check(&format!("{prefix}.name"), crate_name_error(&rust.name));
+                    for (i, name) in rust.crates.iter().enumerate() {
+                        check(&format!("{prefix}.crates[{i}]"), crate_name_error(name));
+                    }
+                }
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationError { errors })
+        }
+    }
+}
+
+/// Returns a project name that's valid everywhere it's used, e.g. "My Project" becomes "my-project". Letters
+/// are lowercased, anything else that isn't allowed becomes a `-`, and leading dots and dashes are dropped so
+/// the name can't refer to a parent directory.
+#[must_use]
+pub fn sanitize_project_name(name: &str) -> String {
+    let mut sanitized = String::new();
+    for c in name.trim().chars().flat_map(char::to_lowercase) {
+        let c = if is_project_name_char(c) { c } else { '-' };
+        // Runs of replaced characters, and of dots, collapse into one.
+        if (c == '-' || c == '.') && sanitized.ends_with(c) {
+            continue;
+        }
+        sanitized.push(c);
+    }
+    let sanitized = sanitized
+        .trim_start_matches(['.', '-'])
+        .trim_end_matches('-');
+    sanitized.chars().take(MAX_PROJECT_NAME_LENGTH).collect()
+}
+
+const fn is_project_name_char(c: char) -> bool {
+    matches!(c, 'a'..='z' | '0'..='9' | '.' | '_' | '-')
+}
+
+/// Returns why a project or repo name isn't valid, or `None` if it is.
+fn project_name_error(name: &str) -> Option<String> {
+    if name.is_empty() {
+        Some("can't be empty".to_string())
+    } else if name.len() > MAX_PROJECT_NAME_LENGTH {
+        Some(format!(
+            "can't be longer than {MAX_PROJECT_NAME_LENGTH} characters"
+        ))
+    } else if !name.chars().all(is_project_name_char) {
+        Some(format!(
+            "{name:?} can only contain lowercase letters, digits, '.', '_' and '-', try {:?}",
+            sanitize_project_name(name)
+        ))
+    } else if name.starts_with(['.', '-']) || name.contains("..") {
+        Some(format!(
+            "{name:?} can't start with '.' or '-' or contain '..'"
+        ))
+    } else {
+        None
+    }
+}
+
+/// Returns why a Github user or organization name isn't valid, or `None` if it is.
+fn github_owner_error(name: &str) -> Option<String> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_GITHUB_OWNER_LENGTH
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        && !name.starts_with('-')
+        && !name.ends_with('-');
+    (!valid).then(|| {
+        format!(
+            "{name:?} has to be at most {MAX_GITHUB_OWNER_LENGTH} letters, digits or '-', and can't start \
+             or end with '-'"
+        )
+    })
+}
+
+/// Returns why the directory of a module isn't valid, or `None` if it is. Modules have to be in the source, so
+/// the path has to be relative and can't refer to a parent directory.
+fn module_path_error(path: &str) -> Option<String> {
+    let valid = !path.starts_with('/')
+        && path
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-' | '/'))
+        && !path.split('/').any(|component| component == "..");
+    (!valid).then(|| {
+        format!(
+            "{path:?} has to be a directory in the source, e.g. services/api, made of letters, digits, '.', \
+             '_', '-' and '/'"
+        )
+    })
+}
+
+/// Returns why the host part of a Go module path isn't valid, or `None` if it is. The first element is a
+/// domain name, e.g. `github.com/myorg`.
+fn go_host_error(host: &str) -> Option<String> {
+    let mut elements = host.split('/');
+    let domain = elements.next().unwrap_or_default();
+    let valid_domain = domain.contains('.')
+        && !domain.starts_with(['.', '-'])
+        && domain
+            .chars()
+            .all(|c| matches!(c, 'a'..='z' | '0'..='9' | '.' | '-'));
+    if !valid_domain {
+        return Some(format!(
+            "{host:?} has to start with a lowercase domain name, e.g. github.com"
+        ));
+    }
+    elements.find_map(go_element_error)
+}
+
+/// Returns why an element of a Go module path isn't valid, or `None` if it is.
+fn go_element_error(element: &str) -> Option<String> {
+    let valid = !element.is_empty()
+        && !element.starts_with('.')
+        && !element.ends_with('.')
+        && element
+            .chars()
+            .all(|c| matches!(c, 'a'..='z' | '0'..='9' | '.' | '_' | '~' | '-'));
+    (!valid).then(|| {
+        format!(
+            "{element:?} isn't a valid Go module path element, which can only contain lowercase letters, \
+             digits, '.', '_', '~' and '-' and can't start or end with '.'"
+        )
+    })
+}
+
+/// Returns why a Go release isn't valid, or `None` if it is. Releases before 1.21 don't understand the
+/// `toolchain` directive. The patch version can be left out for the first release of a minor version, e.g.
+/// `1.22` for `1.22.0`.
+fn go_version_error(go_version: &str) -> Option<String> {
+    let numbers = go_version
+        .split('.')
+        .map(|number| {
+            number
+                .parse::<u32>()
+                .ok()
+                .filter(|_| number.starts_with(|c: char| c.is_ascii_digit()))
+        })
+        .collect::<Option<Vec<_>>>();
+    let valid = matches!(numbers.as_deref(), Some([1, minor] | [1, minor, _]) if *minor >= 21);
+    (!valid).then(|| format!("{go_version:?} has to be a Go release of 1.21 or later, e.g. 1.22.3"))
+}
+
+/// Returns why a Maven group ID isn't valid, or `None` if it is. Group IDs are Java package names, e.g.
+/// `com.example.project`.
+fn group_id_error(group_id: &str) -> Option<String> {
+    let valid = group_id.split('.').all(|segment| {
+        segment.starts_with(|c: char| c.is_ascii_lowercase() || c == '_')
+            && segment
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+    });
+    (!valid).then(|| {
+        format!("{group_id:?} has to be a lowercase Java package name, e.g. com.example.project")
+    })
+}
+
+/// Returns why a Maven artifact ID isn't valid, or `None` if it is.
+fn artifact_id_error(artifact_id: &str) -> Option<String> {
+    let valid = artifact_id.starts_with(|c: char| c.is_ascii_lowercase() || c.is_ascii_digit())
+        && artifact_id
+            .chars()
+            .all(|c| matches!(c, 'a'..='z' | '0'..='9' | '.' | '_' | '-'));
+    (!valid).then(|| {
+        format!(
+            "{artifact_id:?} can only contain lowercase letters, digits, '.', '_' and '-', and has to start \
+             with a letter or digit"
+        )
+    })
+}
+
+/// Returns why a Maven version isn't valid, or `None` if it is.
+fn maven_version_error(version: &str) -> Option<String> {
+    let valid = version.starts_with(|c: char| c.is_ascii_alphanumeric())
+        && version
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'));
+    (!valid).then(|| {
+        format!(
+            "{version:?} can only contain letters, digits, '.', '_' and '-', and has to start with a letter \
+             or digit"
+        )
+    })
+}
+
+/// Returns why a Java release isn't valid, or `None` if it is. Releases are what `javac --release` takes,
+/// which starts at 8.
+fn java_version_error(java_version: &str) -> Option<String> {
+    let valid = java_version
+        .parse::<u32>()
+        .is_ok_and(|release| release >= 8 && !java_version.starts_with('+'));
+    (!valid).then(|| format!("{java_version:?} has to be a Java release of 8 or later, e.g. 21"))
+}
+
+/// Returns why a Python distribution name isn't valid, or `None` if it is.
+fn python_name_error(name: &str) -> Option<String> {
+    let alphanumeric = |c: char| c.is_ascii_lowercase() || c.is_ascii_digit();
+    let valid = name.starts_with(alphanumeric)
+        && name.ends_with(alphanumeric)
+        && name
+            .chars()
+            .all(|c| alphanumeric(c) || matches!(c, '.' | '_' | '-'));
+    (!valid).then(|| {
+        format!(
+            "{name:?} can only contain lowercase letters, digits, '.', '_' and '-', and has to start and end \
+             with a letter or digit"
+        )
+    })
+}
+
+/// Returns why a crate name isn't valid, or `None` if it is.
+fn crate_name_error(name: &str) -> Option<String> {
+    let valid = name.len() <= MAX_CRATE_NAME_LENGTH
+        && name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-');
+    (!valid).then(|| {
+        format!(
+            "{name:?} has to start with a lowercase letter and can only contain lowercase letters, digits, \
+             '_' and '-'"
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use crate::skootrs::{
+        GithubRepoParams, GithubUser, GoParams, MavenArchetype, MavenParams,
+        ModuleInitializeParams, RustParams, SourceInitializeParams,
+    };
+
+    fn params(name: &str, ecosystem_params: EcosystemInitializeParams) -> ProjectCreateParams {
+        ProjectCreateParams {
+            name: name.to_string(),
+            repo_params: RepoCreateParams::Github(GithubRepoParams {
+                name: name.to_string(),
+                description: String::new(),
+                organization: GithubUser::Organization("my-org".to_string()),
+                license: None,
+            }),
+            ecosystem_params: vec![ecosystem_params.into()],
+            source_params: SourceInitializeParams {
+                parent_path: "/tmp".to_string(),
+            },
+            rollback_on_failure: false,
+        }
+    }
+
+    fn go(name: &str, host: &str) -> EcosystemInitializeParams {
+        EcosystemInitializeParams::Go(GoParams {
+            name: name.to_string(),
+            host: host.to_string(),
+            go_version: None,
+        })
+    }
+
+    fn fields(params: &ProjectCreateParams) -> Vec<String> {
+        params.validate().map_or_else(
+            |e| e.errors.into_iter().map(|e| e.field).collect(),
+            |()| vec![],
+        )
+    }
+
+    #[test]
+    fn test_validate_project_name() {
+        assert!(params("my-project", go("my-project", "github.com/my-org"))
+            .validate()
+            .is_ok());
+        for name in ["My Project", "../etc", ".hidden", "a/b", ""] {
+            assert_eq!(
+                fields(&params(name, go("my-project", "github.com/my-org"))),
+                vec!["name", "repo_params.name"],
+                "{name}"
+            );
+        }
+        let error = params("My Project", go("my-project", "github.com/my-org"))
+            .validate()
+            .unwrap_err();
+        assert!(error.to_string().contains("try \"my-project\""));
+    }
+
+    #[test]
+    fn test_validate_ecosystem_params() {
+        assert_eq!(
+            fields(&params("my-project", go("My_Module", "GitHub.com/my-org"))),
+            vec!["ecosystem_params.host", "ecosystem_params.name"]
+        );
+        assert_eq!(
+            fields(&params("my-project", go("my-project", "github.com/../x"))),
+            vec!["ecosystem_params.host"]
+        );
+        for (go_version, valid) in [
+            ("1.22.3", true),
+            ("1.21", true),
+            ("1.20.14", false),
+            ("go1.22.3", false),
+            ("1.22.+3", false),
+        ] {
+            let go = EcosystemInitializeParams::Go(GoParams {
+                name: "my-project".to_string(),
+                host: "github.com/my-org".to_string(),
+                go_version: Some(go_version.to_string()),
+            });
+            assert_eq!(
+                fields(&params("my-project", go)),
+                if valid {
+                    vec![]
+                } else {
+                    vec!["ecosystem_params.go_version"]
+                },
+                "{go_version}"
+            );
+        }
+        assert_eq!(
+            fields(&params(
+                "my-project",
+                EcosystemInitializeParams::Maven(MavenParams {
+                    group_id: "com.my-org".to_string(),
+                    artifact_id: "my-project".to_string(),
+                    ..Default::default()
+                })
+            )),
+            vec!["ecosystem_params.group_id"]
+        );
+        assert_eq!(
+            fields(&params(
+                "my-project",
+                EcosystemInitializeParams::Maven(MavenParams {
+                    group_id: "com.myorg".to_string(),
+                    artifact_id: "my-project".to_string(),
+                    archetype: MavenArchetype {
+                        group_id: "org.apache.maven.archetypes".to_string(),
+                        artifact_id: "maven-archetype-webapp".to_string(),
+                        version: Some("1.5 -X".to_string()),
+                    },
+                    java_version: Some("7".to_string()),
+                    ..Default::default()
+                })
+            )),
+            vec![
+                "ecosystem_params.archetype.version",
+                "ecosystem_params.java_version"
+            ]
+        );
+        assert_eq!(
+            fields(&params(
+                "my-project",
+                EcosystemInitializeParams::Rust(RustParams {
+                    name: "my-project".to_string(),
+                    crates: vec!["core".to_string(), "1st".to_string()],
+                })
+            )),
+            vec!["ecosystem_params.crates[1]"]
+        );
+
+        let mut invalid_owner = params("my-project", go("my-project", "github.com/my-org"));
+        if let RepoCreateParams::Github(repo) = &mut invalid_owner.repo_params {
+            repo.organization = GithubUser::User("-me-".to_string());
+        }
+        assert_eq!(fields(&invalid_owner), vec!["repo_params.organization"]);
+    }
+
+    #[test]
+    fn test_validate_modules() {
+        let module = |path: &str, ecosystem| ModuleInitializeParams {
+            path: path.to_string(),
+            ecosystem,
+        };
+        let mut monorepo = params("my-project", go("api", "github.com/my-org"));
+        monorepo.ecosystem_params = vec![
+            module("services/api", go("api", "github.com/my-org")),
+            module(
+                "libs/core",
+                EcosystemInitializeParams::Maven(MavenParams {
+                    group_id: "com.myorg".to_string(),
+                    artifact_id: "core".to_string(),
+                    ..Default::default()
+                }),
+            ),
+        ];
+        assert!(monorepo.validate().is_ok());
+
+        monorepo.ecosystem_params[1].path = "./services/api/".to_string();
+        monorepo
+            .ecosystem_params
+            .push(module("../outside", go("Outside", "github.com/my-org")));
+        assert_eq!(
+            fields(&monorepo),
+            vec![
+                "ecosystem_params[1].path",
+                "ecosystem_params[2].path",
+                "ecosystem_params[2].ecosystem.name"
+            ]
+        );
+
+        monorepo.ecosystem_params.clear();
+        assert_eq!(fields(&monorepo), vec!["ecosystem_params"]);
+    }
+
+    #[test]
+    fn test_sanitize_project_name() {
+        assert_eq!(sanitize_project_name("My Project"), "my-project");
+        assert_eq!(sanitize_project_name("  ../../etc/passwd "), "etc-passwd");
+        assert_eq!(sanitize_project_name("a...b__c"), "a.b__c");
+        assert_eq!(sanitize_project_name("Ünïcode!"), "n-code");
+        assert_eq!(sanitize_project_name("x".repeat(200).as_str()).len(), 100);
+        assert!(project_name_error(&sanitize_project_name("Some Name/..")).is_none());
+    }
+}
@@ -0,0 +1,286 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    fmt,
+    future::{ready, Future, Ready},
+    pin::Pin,
+    rc::Rc,
+    sync::Arc,
+};
+
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::{Method, StatusCode},
+    HttpMessage, HttpRequest, HttpResponse, ResponseError,
+};
+use skootrs_model::skootrs::{ApiKeyConfig, ApiKeyError, ApiKeyScope};
+
+use crate::server::project::ErrorResponse;
+
+/// The header API keys are sent in. This has to match the `api_key` security scheme in the OpenAPI docs.
+pub(super) const API_KEY_HEADER: &str = "skootrs_apikey";
+
+/// The paths of the API docs, which can be read without an API key.
+const PUBLIC_PATH_PREFIXES: [&str; 4] = ["/swagger-ui", "/api-docs", "/redoc", "/rapidoc"];
+
+/// The paths of the webhooks, which are authenticated by their signature instead of an API key.
+const WEBHOOK_PATH_PREFIX: &str = "/webhooks/";
+
+/// The name of the API key a request was authorized with.
+#[derive(Clone, Debug)]
+struct ApiKeyName(String);
+
+/// Returns who the changes a request makes are recorded in the audit log as made by, which is the API key the
+/// request was authorized with.
+pub(super) fn actor(req: &HttpRequest) -> String {
+    req.extensions().get::<ApiKeyName>().map_or_else(
+        || "anonymous".to_string(),
+        |name| format!("api_key:{}", name.0),
+    )
+}
+
+/// Why a request was rejected
+#[derive(Debug)]
+pub(super) enum AuthError {
+    /// The request didn't have an API key.
+    MissingKey,
+    /// The request's API key isn't one of the configured keys, or isn't allowed to do what the request does.
+    Rejected(ApiKeyError),
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingKey => write!(f, "The {API_KEY_HEADER} header is missing"),
+            Self::Rejected(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl ResponseError for AuthError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            Self::MissingKey | Self::Rejected(ApiKeyError::InvalidKey) => StatusCode::UNAUTHORIZED,
+            Self::Rejected(ApiKeyError::MissingScope { .. }) => StatusCode::FORBIDDEN,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let body = match self.status_code() {
+            StatusCode::FORBIDDEN => ErrorResponse::Forbidden(self.to_string()),
+            _ => ErrorResponse::Unauthorized(self.to_string()),
+        };
+        HttpResponse::build(self.status_code()).json(body)
+    }
+}
+
+/// Middleware that rejects requests to the API that don't have an API key with the scope they need. Requests
+/// that only read need the `read` scope, and everything else needs the `write` scope.
+#[derive(Clone)]
+pub(super) struct ApiKeyAuth {
+    api_keys: Arc<Vec<ApiKeyConfig>>,
+}
+
+impl ApiKeyAuth {
+    pub(super) fn new(api_keys: Vec<ApiKeyConfig>) -> Self {
+        Self {
+            api_keys: Arc::new(api_keys),
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ApiKeyAuth
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Transform = ApiKeyAuthMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ApiKeyAuthMiddleware {
+            service: Rc::new(service),
+            api_keys: self.api_keys.clone(),
+        }))
+    }
+}
+
+pub(super) struct ApiKeyAuthMiddleware<S> {
+    service: Rc<S>,
+    api_keys: Arc<Vec<ApiKeyConfig>>,
+}
+
+impl<S> ApiKeyAuthMiddleware<S> {
+    /// Returns the name of the API key the request is authorized with, or `None` if it doesn't need one.
+    fn authorize(&self, req: &ServiceRequest) -> Result<Option<String>, AuthError> {
+        if PUBLIC_PATH_PREFIXES
+            .iter()
+            .any(|prefix| req.path().starts_with(prefix))
+            || req.path().starts_with(WEBHOOK_PATH_PREFIX)
+        {
+            return Ok(None);
+        }
+        let key = req
+            .headers()
+            .get(API_KEY_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .ok_or(AuthError::MissingKey)?;
+        let scope = match *req.method() {
+            Method::GET | Method::HEAD | Method::OPTIONS => ApiKeyScope::Read,
+            _ => ApiKeyScope::Write,
+        };
+        let api_key =
+            ApiKeyConfig::authorize(&self.api_keys, key, &scope).map_err(AuthError::Rejected)?;
+        Ok(Some(api_key.name.clone()))
+    }
+}
+
+impl<S, B> Service<ServiceRequest> for ApiKeyAuthMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        match self.authorize(&req) {
+            Ok(name) => {
+                if let Some(name) = name {
+                    req.extensions_mut().insert(ApiKeyName(name));
+                }
+                Box::pin(self.service.call(req))
+            }
+            Err(err) => Box::pin(ready(Err(err.into()))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        test::{self, TestRequest},
+        web, App,
+    };
+
+    use super::*;
+
+    fn api_key(name: &str, key: &str, scopes: Vec<ApiKeyScope>) -> ApiKeyConfig {
+        ApiKeyConfig {
+            name: name.to_string(),
+            key: key.to_string(),
+            scopes,
+        }
+    }
+
+    /// Responds with who the request is recorded as made by, so tests can tell which key authorized it.
+    async fn whoami(req: HttpRequest) -> String {
+        actor(&req)
+    }
+
+    /// Sends a request through the middleware and returns the status of the response, along with who the
+    /// request was recorded as made by if it got through.
+    async fn call(req: TestRequest) -> (StatusCode, Option<String>) {
+        let app = test::init_service(
+            App::new()
+                .wrap(ApiKeyAuth::new(vec![
+                    api_key("dashboard", "read-key", vec![ApiKeyScope::Read]),
+                    api_key("ci", "write-key", vec![ApiKeyScope::Write]),
+                ]))
+                .route("/projects", web::get().to(whoami))
+                .route("/projects", web::post().to(whoami))
+                .route("/api-docs/openapi.json", web::get().to(whoami))
+                .route("/swagger-ui/index.html", web::get().to(whoami))
+                .route("/webhooks/github", web::post().to(whoami)),
+        )
+        .await;
+        match test::try_call_service(&app, req.to_request()).await {
+            Ok(resp) => {
+                let status = resp.status();
+                let body = test::read_body(resp).await;
+                (status, Some(String::from_utf8(body.to_vec()).unwrap()))
+            }
+            Err(err) => (err.as_response_error().status_code(), None),
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_missing_key() {
+        let req = TestRequest::get().uri("/projects");
+        assert_eq!(call(req).await, (StatusCode::UNAUTHORIZED, None));
+    }
+
+    #[actix_web::test]
+    async fn test_unknown_key() {
+        let req = TestRequest::get()
+            .uri("/projects")
+            .insert_header((API_KEY_HEADER, "not-a-key"));
+        assert_eq!(call(req).await, (StatusCode::UNAUTHORIZED, None));
+        // A prefix of a valid key isn't valid either.
+        let req = TestRequest::get()
+            .uri("/projects")
+            .insert_header((API_KEY_HEADER, "read"));
+        assert_eq!(call(req).await, (StatusCode::UNAUTHORIZED, None));
+    }
+
+    #[actix_web::test]
+    async fn test_scopes() {
+        let req = TestRequest::post()
+            .uri("/projects")
+            .insert_header((API_KEY_HEADER, "read-key"));
+        assert_eq!(call(req).await, (StatusCode::FORBIDDEN, None));
+
+        let req = TestRequest::get()
+            .uri("/projects")
+            .insert_header((API_KEY_HEADER, "read-key"));
+        assert_eq!(
+            call(req).await,
+            (StatusCode::OK, Some("api_key:dashboard".to_string()))
+        );
+
+        // A key that can write can also read.
+        for req in [TestRequest::post(), TestRequest::get()] {
+            let req = req
+                .uri("/projects")
+                .insert_header((API_KEY_HEADER, "write-key"));
+            assert_eq!(
+                call(req).await,
+                (StatusCode::OK, Some("api_key:ci".to_string()))
+            );
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_public_paths() {
+        for req in [
+            TestRequest::get().uri("/api-docs/openapi.json"),
+            TestRequest::get().uri("/swagger-ui/index.html"),
+            // Webhook deliveries are checked by their signature instead of an API key.
+            TestRequest::post().uri("/webhooks/github"),
+        ] {
+            assert_eq!(
+                call(req).await,
+                (StatusCode::OK, Some("anonymous".to_string()))
+            );
+        }
+    }
+}
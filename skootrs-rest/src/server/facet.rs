@@ -0,0 +1,154 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use actix_web::{
+    web::{self, Data, Path, Query, ServiceConfig},
+    HttpResponse, Responder,
+};
+use serde::Deserialize;
+use skootrs_lib::service::{github_auth::GithubAuth, project::ProjectService};
+use skootrs_model::skootrs::{FacetGetParams, FacetListParams, FacetMapKey, ProjectGetParams, ReadStrategy};
+use utoipa::IntoParams;
+
+use crate::server::project::{project_service, service_error, ErrorResponse};
+
+/// Configures the routes for a project's facets
+pub(super) fn configure(config: &mut ServiceConfig) {
+    config
+        .service(web::resource("/projects/{project_url}/facets").route(web::get().to(list_facets)))
+        .service(
+            web::resource("/projects/{project_url}/facets/{facet_map_key}")
+                .route(web::get().to(get_facet)),
+        )
+        .service(
+            web::resource("/projects/{project_url}/audit").route(web::get().to(audit_project)),
+        );
+}
+
+/// The labels to filter a project's facets by
+#[derive(Deserialize, IntoParams)]
+pub(super) struct LabelQuery {
+    /// Comma separated labels, e.g. `slsa,build`. Only facets with a label matching each of them are listed.
+    labels: Option<String>,
+}
+
+impl LabelQuery {
+    fn labels(&self) -> Vec<String> {
+        self.labels
+            .iter()
+            .flat_map(|labels| labels.split(','))
+            .map(str::trim)
+            .filter(|label| !label.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+/// List the facets of a project
+///
+/// Returns the keys the project's facets are stored under, e.g. `Type: Readme`.
+#[utoipa::path(
+    get,
+    path = "/projects/{project_url}/facets",
+    params(
+        ("project_url" = String, Path, description = "The URL encoded repo URL of the project"),
+        LabelQuery,
+    ),
+    responses(
+        (status = 200, description = "The keys of the project's facets", body = [FacetMapKey]),
+        (status = 404, description = "Project not found", body = ErrorResponse, example = json!(ErrorResponse::NotFound("Project not found".into()))),
+    )
+)]
+pub(super) async fn list_facets(
+    project_url: Path<String>,
+    query: Query<LabelQuery>,
+    github_auth: Data<GithubAuth>,
+) -> Result<impl Responder, actix_web::Error> {
+    let facets = project_service(&github_auth)?
+        .list_facets(FacetListParams {
+            project_get_params: ProjectGetParams {
+                project_url: project_url.into_inner(),
+                read_strategy: ReadStrategy::default(),
+            },
+            labels: query.labels(),
+        })
+        .await
+        .map_err(service_error)?;
+    Ok(HttpResponse::Ok().json(facets))
+}
+
+/// Get a facet of a project
+///
+/// Returns the facet along with the current content of its files in the project's repo.
+#[utoipa::path(
+    get,
+    path = "/projects/{project_url}/facets/{facet_map_key}",
+    params(
+        ("project_url" = String, Path, description = "The URL encoded repo URL of the project"),
+        ("facet_map_key" = String, Path, description = "The URL encoded key of the facet, e.g. `Type: Readme`"),
+    ),
+    responses(
+        (status = 200, description = "The facet with its content", body = InitializedFacet),
+        (status = 400, description = "Invalid facet key", body = ErrorResponse),
+        (status = 404, description = "Project or facet not found", body = ErrorResponse, example = json!(ErrorResponse::NotFound("Facet not found".into()))),
+    )
+)]
+pub(super) async fn get_facet(
+    path: Path<(String, String)>,
+    github_auth: Data<GithubAuth>,
+) -> Result<impl Responder, actix_web::Error> {
+    let (project_url, facet_map_key) = path.into_inner();
+    let facet_map_key = FacetMapKey::try_from(facet_map_key)
+        .map_err(|err| actix_web::error::ErrorBadRequest(err.to_string()))?;
+    let facet = project_service(&github_auth)?
+        .get_facet_with_content(FacetGetParams {
+            project_get_params: ProjectGetParams {
+                project_url,
+                read_strategy: ReadStrategy::default(),
+            },
+            facet_map_key,
+        })
+        .await
+        .map_err(service_error)?;
+    Ok(HttpResponse::Ok().json(facet))
+}
+
+/// Audit the facets of a project
+///
+/// Checks each of the project's facets against what's live in the project's repo.
+#[utoipa::path(
+    get,
+    path = "/projects/{project_url}/audit",
+    params(
+        ("project_url" = String, Path, description = "The URL encoded repo URL of the project"),
+    ),
+    responses(
+        (status = 200, description = "The audit results for the project's facets", body = ProjectAudit),
+        (status = 404, description = "Project not found", body = ErrorResponse, example = json!(ErrorResponse::NotFound("Project not found".into()))),
+    )
+)]
+pub(super) async fn audit_project(
+    project_url: Path<String>,
+    github_auth: Data<GithubAuth>,
+) -> Result<impl Responder, actix_web::Error> {
+    let audit = project_service(&github_auth)?
+        .audit(ProjectGetParams {
+            project_url: project_url.into_inner(),
+            read_strategy: ReadStrategy::default(),
+        })
+        .await
+        .map_err(service_error)?;
+    Ok(HttpResponse::Ok().json(audit))
+}
@@ -0,0 +1,204 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Creating a project takes longer than a client should wait on a request, so `POST /projects` only queues a
+//! job and returns it. A pool of workers creates the queued projects, and the status of each job is served at
+//! `/jobs/{id}`. Jobs are kept in the daemon's data directory, so the queue survives the daemon
+//! restarting.
+
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use actix_web::{
+    web::{self, Data, Path, ServiceConfig},
+    HttpResponse, Responder,
+};
+use skootrs_lib::service::{audit_log::AuditLog, github_auth::GithubAuth};
+use skootrs_model::skootrs::{Job, ProjectCreateParams, SkootError};
+use skootrs_statestore::{job::JsonJobStore, InMemoryProjectReferenceCache};
+use tokio::sync::{
+    mpsc::{UnboundedReceiver, UnboundedSender},
+    Mutex,
+};
+use tracing::{error, info};
+
+use crate::server::project::{create_and_record, service_error};
+
+/// The daemon's jobs.
+pub(super) type JobStore = Mutex<JsonJobStore>;
+
+/// The queue of IDs of the jobs the workers run.
+pub(super) type JobQueue = UnboundedSender<String>;
+
+/// Makes job IDs unique when several jobs are queued in the same nanosecond.
+static JOB_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Configures the services and routes for jobs
+pub(super) fn configure(
+    jobs: Data<JobStore>,
+    queue: Data<JobQueue>,
+) -> impl FnOnce(&mut ServiceConfig) {
+    |config: &mut ServiceConfig| {
+        config
+            .app_data(jobs)
+            .app_data(queue)
+            .service(web::resource("/jobs").route(web::get().to(list_jobs)))
+            .service(web::resource("/jobs/{id}").route(web::get().to(get_job)));
+    }
+}
+
+/// List jobs
+#[utoipa::path(
+    get,
+    path = "/jobs",
+    responses(
+        (status = 200, description = "Every job the daemon has queued", body = [Job]),
+    )
+)]
+pub(super) async fn list_jobs(jobs: Data<JobStore>) -> Result<impl Responder, actix_web::Error> {
+    let jobs = jobs.lock().await.jobs.values().cloned().collect::<Vec<_>>();
+    Ok(HttpResponse::Ok().json(jobs))
+}
+
+/// Get a job
+///
+/// Returns the status of a job, the step it's at while it's running, the URL of the created project once it
+/// succeeded, and the error if it failed.
+#[utoipa::path(
+    get,
+    path = "/jobs/{id}",
+    params(
+        ("id" = String, Path, description = "The ID of the job"),
+    ),
+    responses(
+        (status = 200, description = "The job", body = Job),
+        (status = 404, description = "Job not found", body = ErrorResponse),
+    )
+)]
+pub(super) async fn get_job(
+    id: Path<String>,
+    jobs: Data<JobStore>,
+) -> Result<impl Responder, actix_web::Error> {
+    let job = jobs.lock().await.get(&id).map_err(service_error)?;
+    Ok(HttpResponse::Ok().json(job))
+}
+
+/// Stores a new job creating a project with `params` for `actor` and queues it for the workers.
+pub(super) async fn queue_job(
+    jobs: &JobStore,
+    queue: &JobQueue,
+    params: ProjectCreateParams,
+    actor: String,
+) -> Result<Job, SkootError> {
+    let job = Job::new(job_id(), params, Some(actor));
+    jobs.lock().await.put(job.clone())?;
+    queue
+        .send(job.id.clone())
+        .map_err(|_| SkootError::other("The job workers have stopped"))?;
+    Ok(job)
+}
+
+fn job_id() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or_default();
+    format!(
+        "{nanos:x}-{:x}",
+        JOB_COUNTER.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+/// Runs `workers` workers that each take the next queued job and create its project, until the queue is
+/// closed.
+pub(super) async fn run_workers(
+    workers: usize,
+    github_auth: Data<GithubAuth>,
+    audit_log: Data<AuditLog>,
+    project_store: Data<Mutex<InMemoryProjectReferenceCache>>,
+    jobs: Data<JobStore>,
+    receiver: UnboundedReceiver<String>,
+) {
+    let receiver = Arc::new(Mutex::new(receiver));
+    let handles = (0..workers.max(1))
+        .map(|_| {
+            actix_web::rt::spawn(run_worker(
+                github_auth.clone(),
+                audit_log.clone(),
+                project_store.clone(),
+                jobs.clone(),
+                receiver.clone(),
+            ))
+        })
+        .collect::<Vec<_>>();
+    for handle in handles {
+        if let Err(err) = handle.await {
+            error!("Job worker stopped: {err}");
+        }
+    }
+}
+
+async fn run_worker(
+    github_auth: Data<GithubAuth>,
+    audit_log: Data<AuditLog>,
+    project_store: Data<Mutex<InMemoryProjectReferenceCache>>,
+    jobs: Data<JobStore>,
+    receiver: Arc<Mutex<UnboundedReceiver<String>>>,
+) {
+    loop {
+        // The lock is released before the job runs, so the other workers can take the next job meanwhile.
+        let Some(id) = receiver.lock().await.recv().await else {
+            return;
+        };
+        if let Err(err) = run_job(&github_auth, &audit_log, &project_store, &jobs, &id).await {
+            error!("Unable to record job {id}: {err}");
+        }
+    }
+}
+
+async fn run_job(
+    github_auth: &GithubAuth,
+    audit_log: &AuditLog,
+    project_store: &Mutex<InMemoryProjectReferenceCache>,
+    jobs: &JobStore,
+    id: &str,
+) -> Result<(), SkootError> {
+    let mut job = jobs.lock().await.get(id)?;
+    job.progress("Creating the project");
+    jobs.lock().await.put(job.clone())?;
+    info!("Running job {id} creating {}", job.params.name);
+    // Jobs queued before jobs recorded who queued them are recorded as made by the daemon.
+    let actor = job.actor.clone().unwrap_or_else(|| "daemon".to_string());
+    match create_and_record(
+        github_auth,
+        &audit_log.with_actor(actor),
+        project_store,
+        job.params.clone(),
+    )
+    .await
+    {
+        Ok(project) => job.succeed(project.repo.full_url()),
+        Err(err) => {
+            error!("Job {id} failed: {err}");
+            job.fail(err.to_string());
+        }
+    }
+    jobs.lock().await.put(job)
+}
@@ -0,0 +1,49 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The daemon's metrics, served at `/metrics` for Prometheus to scrape. Scrapes need an API key like every
+//! other request.
+
+use actix_web::{
+    web::{self, ServiceConfig},
+    HttpResponse, Responder,
+};
+use skootrs_lib::service::metrics;
+
+/// The content type of the Prometheus text exposition format.
+const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Configures the route for the daemon's metrics
+pub(super) fn configure(config: &mut ServiceConfig) {
+    config.service(web::resource("/metrics").route(web::get().to(get_metrics)));
+}
+
+/// Get the daemon's metrics
+///
+/// Returns counters and histograms of the projects created, the facets created by type, the Github API calls
+/// made and failed, and the projects reconciled in the background, along with how long they took, in the
+/// Prometheus text exposition format.
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    responses(
+        (status = 200, description = "The daemon's metrics", body = String, content_type = "text/plain"),
+    )
+)]
+pub(super) async fn get_metrics() -> impl Responder {
+    HttpResponse::Ok()
+        .content_type(PROMETHEUS_CONTENT_TYPE)
+        .body(metrics::global().render())
+}
@@ -14,4 +14,11 @@
 // limitations under the License.
 
 pub mod rest;
-mod project;
\ No newline at end of file
+mod auth;
+mod project;
+mod facet;
+mod job;
+mod metrics;
+mod output;
+mod scheduler;
+mod webhook;
\ No newline at end of file
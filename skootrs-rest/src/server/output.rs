@@ -0,0 +1,141 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use actix_web::{
+    web::{self, Data, Path, Query, ServiceConfig},
+    HttpResponse, Responder,
+};
+use serde::Deserialize;
+use skootrs_lib::service::{github_auth::GithubAuth, project::ProjectService};
+use skootrs_model::skootrs::{
+    ProjectOutputGetParams, ProjectOutputType, ProjectOutputsListParams, ProjectReleaseParam,
+    WorkflowRunParam,
+};
+use utoipa::IntoParams;
+
+use crate::server::project::{fetch_project, project_service, service_error, ErrorResponse};
+
+/// The release or workflow run to get a project's outputs from
+#[derive(Deserialize, IntoParams)]
+pub(super) struct ReleaseQuery {
+    /// The tag of the release. The latest release is used if it isn't set.
+    tag: Option<String>,
+    /// The ID of a workflow run to get the artifacts of instead of a release.
+    run_id: Option<u64>,
+    /// A branch whose latest successful workflow run to get the artifacts of instead of a release, e.g.
+    /// `main`.
+    run_branch: Option<String>,
+}
+
+impl ReleaseQuery {
+    /// Returns the workflow run the query asks for, if any. A run ID takes precedence over a branch.
+    fn workflow_run(&self) -> Option<WorkflowRunParam> {
+        self.run_id
+            .map(WorkflowRunParam::Id)
+            .or_else(|| self.run_branch.clone().map(WorkflowRunParam::LatestOn))
+    }
+}
+
+impl From<ReleaseQuery> for ProjectReleaseParam {
+    fn from(query: ReleaseQuery) -> Self {
+        query.tag.map_or(Self::Latest, Self::Tag)
+    }
+}
+
+/// Configures the routes for a project's outputs
+pub(super) fn configure(config: &mut ServiceConfig) {
+    config
+        .service(
+            web::resource("/projects/{project_url}/outputs").route(web::get().to(list_outputs)),
+        )
+        .service(
+            web::resource("/projects/{project_url}/outputs/{output_type}/{name}")
+                .route(web::get().to(get_output)),
+        );
+}
+
+/// List the outputs of a project
+///
+/// Returns references to the outputs of a release of the project, e.g. its SBOMs, or to the artifacts of one
+/// of its workflow runs.
+#[utoipa::path(
+    get,
+    path = "/projects/{project_url}/outputs",
+    params(
+        ("project_url" = String, Path, description = "The URL encoded repo URL of the project"),
+        ReleaseQuery,
+    ),
+    responses(
+        (status = 200, description = "References to the release's outputs", body = [ProjectOutputReference]),
+        (status = 404, description = "Project or release not found", body = ErrorResponse, example = json!(ErrorResponse::NotFound("Project not found".into()))),
+    )
+)]
+pub(super) async fn list_outputs(
+    project_url: Path<String>,
+    release: Query<ReleaseQuery>,
+    github_auth: Data<GithubAuth>,
+) -> Result<impl Responder, actix_web::Error> {
+    let initialized_project = fetch_project(&github_auth, project_url.into_inner()).await?;
+    let outputs = project_service(&github_auth)?
+        .outputs_list(ProjectOutputsListParams {
+            initialized_project,
+            workflow_run: release.workflow_run(),
+            release: release.into_inner().into(),
+        })
+        .await
+        .map_err(service_error)?;
+    Ok(HttpResponse::Ok().json(outputs))
+}
+
+/// Get an output of a project
+///
+/// Returns the content of an output of a release of the project, e.g. an SBOM.
+#[utoipa::path(
+    get,
+    path = "/projects/{project_url}/outputs/{output_type}/{name}",
+    params(
+        ("project_url" = String, Path, description = "The URL encoded repo URL of the project"),
+        ("output_type" = String, Path, description = "The type of the output, e.g. `SBOM`"),
+        ("name" = String, Path, description = "The name of the output"),
+        ReleaseQuery,
+    ),
+    responses(
+        (status = 200, description = "The output", body = ProjectOutput),
+        (status = 400, description = "Invalid output type", body = ErrorResponse),
+        (status = 404, description = "Project or output not found", body = ErrorResponse, example = json!(ErrorResponse::NotFound("Output not found".into()))),
+    )
+)]
+pub(super) async fn get_output(
+    path: Path<(String, String, String)>,
+    release: Query<ReleaseQuery>,
+    github_auth: Data<GithubAuth>,
+) -> Result<impl Responder, actix_web::Error> {
+    let (project_url, output_type, project_output) = path.into_inner();
+    let project_output_type = output_type
+        .parse::<ProjectOutputType>()
+        .map_err(|err| actix_web::error::ErrorBadRequest(err.to_string()))?;
+    let initialized_project = fetch_project(&github_auth, project_url).await?;
+    let output = project_service(&github_auth)?
+        .output_get(ProjectOutputGetParams {
+            initialized_project,
+            project_output_type,
+            project_output,
+            workflow_run: release.workflow_run(),
+            release: release.into_inner().into(),
+        })
+        .await
+        .map_err(service_error)?;
+    Ok(HttpResponse::Ok().json(output))
+}
@@ -13,14 +13,17 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use actix_web::{Responder, web::{ServiceConfig, Data, Json, self}, HttpResponse};
+use actix_web::{Responder, web::{ServiceConfig, Data, Json, Path, Query, self}, HttpRequest, HttpResponse};
 use serde::{Serialize, Deserialize};
-use skootrs_statestore::{InMemoryProjectReferenceCache, ProjectReferenceCache};
+use skootrs_statestore::{GitProjectStateStore, InMemoryProjectReferenceCache, ProjectReferenceCache, ProjectStateStore};
 use tokio::sync::Mutex;
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
 
-use skootrs_model::skootrs::ProjectCreateParams;
-use skootrs_lib::service::{ecosystem::LocalEcosystemService, facet::LocalFacetService, output::LocalOutputService, project::{LocalProjectService, ProjectService}, repo::LocalRepoService, source::LocalSourceService};
+use skootrs_model::skootrs::{facet::SupportedFacetType, listing::{ProjectFilter, ProjectPage, ProjectSort, ProjectSummary, DEFAULT_PAGE_LIMIT}, InitializedProject, InitializedRepo, ProjectArchiveParams, ProjectCreateParams, ProjectGetParams, ProjectUpdateParams, ReadStrategy, SkootError, StateSigning};
+use crate::server::auth::actor;
+use crate::server::job::{queue_job, JobQueue, JobStore};
+
+use skootrs_lib::service::{audit_log::{AuditLog, AuditedProjectService}, github_auth::GithubAuth, project::{DaemonProjectService, ProjectService}, source::{ConcurrencyError, LocalSourceService}};
 
 /// An Error response for the REST API
 #[derive(Serialize, Deserialize, Clone, ToSchema)]
@@ -29,10 +32,54 @@ pub(super) enum ErrorResponse {
     NotFound(String),
     /// When a Project was unable to be initialized.
     InitializationError(String),
-    /// When todo endpoint was called without correct credentials
+    /// When an endpoint was called without a valid API key.
     Unauthorized(String),
+    /// When an endpoint was called with an API key that isn't allowed to call it.
+    Forbidden(String),
+    /// When a Project was changed by another operation while it was being updated or archived.
+    Conflict(String),
+    /// When a Project was unable to be updated or archived.
+    UpdateError(String),
+}
+
+/// The request body for updating a project
+#[derive(Serialize, Deserialize, Clone, Default, ToSchema)]
+pub(super) struct ProjectUpdateRequest {
+    /// Limits the update to these facet types. Every facet is updated if this isn't set.
+    #[serde(default)]
+    facet_types: Option<Vec<SupportedFacetType>>,
+}
+
+/// The query parameters for archiving a project
+#[derive(Deserialize, IntoParams)]
+pub(super) struct ArchiveQuery {
+    /// Delete the repo's Actions artifacts and caches and disable its scheduled workflows before archiving it.
+    #[serde(default)]
+    cleanup: bool,
+}
+
+/// The query parameters for listing projects
+#[derive(Deserialize, IntoParams)]
+pub(super) struct ProjectListQuery {
+    /// The most projects to return, up to 500. Defaults to 50.
+    limit: Option<usize>,
+    /// The number of matching projects to skip, e.g. the `next_offset` of the previous page.
+    #[serde(default)]
+    offset: usize,
+    /// Only list projects with a module in this ecosystem, e.g. `Go`.
+    ecosystem: Option<String>,
+    /// Only list projects in this Github organization or user, or Bitbucket workspace.
+    organization: Option<String>,
+    /// Only list projects with a facet of this type, e.g. `SecurityPolicy`.
+    facet_type: Option<SupportedFacetType>,
+    /// The order to list projects in: `url`, `oldest` or `newest`. Defaults to `url`.
+    #[serde(default)]
+    sort: ProjectSort,
 }
 
+/// The project service the REST API is served by.
+pub(super) type RestProjectService = DaemonProjectService;
+
 /// Configures the services and routes for the Skootrs REST API
 pub(super) fn configure(store: Data<Mutex<InMemoryProjectReferenceCache>>) -> impl FnOnce(&mut ServiceConfig) {
     |config: &mut ServiceConfig| {
@@ -41,10 +88,30 @@ pub(super) fn configure(store: Data<Mutex<InMemoryProjectReferenceCache>>) -> im
             .service(web::resource("/projects")
                 .route(web::post().to(create_project))
                 .route(web::get().to(list_projects))
+            )
+            .service(web::resource("/projects/{project_url}")
+                .route(web::get().to(get_project))
+                .route(web::put().to(update_project))
+            )
+            .service(web::resource("/projects/{project_url}/archive")
+                .route(web::post().to(archive_project))
             );
     }
 }
 
+/// Returns the project service the REST API is served by, the same one the gRPC API is.
+pub(super) fn project_service(github_auth: &GithubAuth) -> Result<RestProjectService, actix_web::Error> {
+    RestProjectService::for_daemon(github_auth).map_err(service_error)
+}
+
+/// Returns the project service for requests that change projects, which records the changes in the audit log.
+pub(super) fn audited_project_service(github_auth: &GithubAuth, audit_log: &AuditLog) -> Result<AuditedProjectService<RestProjectService>, actix_web::Error> {
+    Ok(AuditedProjectService {
+        project_service: project_service(github_auth)?,
+        audit_log: audit_log.clone(),
+    })
+}
+
 /// Create a new project
 /// 
 /// Example: 
@@ -66,38 +133,290 @@ pub(super) fn configure(store: Data<Mutex<InMemoryProjectReferenceCache>>) -> im
     path = "/projects",
     request_body = ProjectParams,
     responses( 
-        (status = 201, description = "Project created successfully", body = InitializedProject),
-        (status = 409, description = "Project unable to be created", body = ErrorResponse, example = json!(ErrorResponse::InitializationError("Unable to create repo".into())))
+        (status = 202, description = "Project creation queued, poll `/jobs/{id}` for its status", body = Job),
+        (status = 400, description = "Invalid project parameters", body = ErrorResponse),
+        (status = 500, description = "Unable to queue the project creation", body = ErrorResponse),
     )
 )]
-pub(super) async fn create_project(params: Json<ProjectCreateParams>, project_store: Data<Mutex<InMemoryProjectReferenceCache>>) -> Result<impl Responder, actix_web::Error> {
-    // TODO: This should be initialized elsewhere
-    let project_service = LocalProjectService {
-        repo_service: LocalRepoService {},
-        ecosystem_service: LocalEcosystemService {},
-        source_service: LocalSourceService {},
-        facet_service: LocalFacetService {},
-        output_service: LocalOutputService {},
-    };
+pub(super) async fn create_project(
+    req: HttpRequest,
+    params: Json<ProjectCreateParams>,
+    jobs: Data<JobStore>,
+    queue: Data<JobQueue>,
+) -> Result<impl Responder, actix_web::Error> {
+    let params = params.into_inner();
+    // Invalid parameters are rejected with a `400` rather than queued as a job that's bound to fail.
+    params.validate().map_err(|err| service_error(err.into()))?;
+    let job = queue_job(&jobs, &queue, params, actor(&req))
+        .await
+        .map_err(service_error)?;
+    Ok(HttpResponse::Accepted().json(job))
+}
 
-    let initialized_project = project_service.initialize(params.into_inner()).await
-    .map_err(|err| actix_web::error::ErrorInternalServerError(err.to_string()))?;
-    project_store.lock().await.set(initialized_project.repo.full_url()).await.map_err(|err| actix_web::error::ErrorInternalServerError(err.to_string()))?;
-    // TODO: Should this return an internal server error if it can't save the cache?
-    project_store.lock().await.save().map_err(|err| actix_web::error::ErrorInternalServerError(err.to_string()))?;
-    Ok(HttpResponse::Ok().json(initialized_project))
+/// Creates a project and records it in the project store, and its creation in the audit log.
+pub(super) async fn create_and_record(
+    github_auth: &GithubAuth,
+    audit_log: &AuditLog,
+    project_store: &Mutex<InMemoryProjectReferenceCache>,
+    params: ProjectCreateParams,
+) -> Result<InitializedProject, SkootError> {
+    let project_service = audited_project_service(github_auth, audit_log)
+        .map_err(|err| SkootError::other(err.to_string()))?;
+    let initialized_project = project_service.initialize(params).await?;
+    project_store.lock().await.set(initialized_project.repo.full_url()).await?;
+    // TODO: Should this fail the job if it can't save the cache?
+    project_store.lock().await.save()?;
+    Ok(initialized_project)
 }
 
-/// Get all projects
+/// List projects
+///
+/// Lists a page of the projects in the project store, filtered by ecosystem, organization and facet type. Filtering
+/// by ecosystem or facet type, or sorting by creation date, fetches the state of every project from its repo, while
+/// otherwise only the states of the projects on the page are fetched. Projects whose state can't be fetched are
+/// listed with the error, but never match those filters.
 #[utoipa::path(
     get,
     path = "/projects",
+    params(ProjectListQuery),
     responses(
-        (status = 200, description = "List all projects", body = [InitializedProject]),
+        (status = 200, description = "A page of the projects", body = ProjectPage),
         (status = 500, description = "Internal server error", body = ErrorResponse, example = json!(ErrorResponse::InitializationError("Unable to list repos".into()))),
     )
 )]
-pub(super) async fn list_projects(project_store: Data<InMemoryProjectReferenceCache>) -> Result<impl Responder, actix_web::Error> {
-    let projects = project_store.list().await.map_err(|err| actix_web::error::ErrorInternalServerError(err.to_string()))?;
-    Ok(HttpResponse::Ok().json(projects))
+pub(super) async fn list_projects(query: Query<ProjectListQuery>, project_store: Data<Mutex<InMemoryProjectReferenceCache>>, github_auth: Data<GithubAuth>) -> Result<impl Responder, actix_web::Error> {
+    let query = query.into_inner();
+    let filter = ProjectFilter {
+        ecosystem: query.ecosystem,
+        organization: query.organization,
+        facet_type: query.facet_type,
+    };
+    let urls = project_store
+        .lock()
+        .await
+        .list()
+        .await
+        .map_err(service_error)?
+        .into_iter()
+        .filter(|url| InitializedRepo::try_from(url.clone()).map_or(filter.organization.is_none(), |repo| filter.matches_repo(&repo)))
+        .collect::<Vec<_>>();
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_LIMIT);
+    let (projects, mut page) = if filter.needs_state() || query.sort.needs_state() {
+        let mut projects = vec![];
+        for url in urls {
+            match fetch_project(&github_auth, url.clone()).await {
+                Ok(project) if filter.matches(&project) => projects.push(ProjectSummary::from(&project)),
+                Ok(_) => {}
+                Err(err) if !filter.needs_state() => projects.push(ProjectSummary::unavailable(url, err.to_string())),
+                Err(_) => {}
+            }
+        }
+        query.sort.sort(&mut projects);
+        ProjectPage::paginate(projects, query.offset, limit)
+    } else {
+        let mut urls = urls;
+        urls.sort();
+        let (urls, page) = ProjectPage::paginate(urls, query.offset, limit);
+        let mut projects = vec![];
+        for url in urls {
+            projects.push(match fetch_project(&github_auth, url.clone()).await {
+                Ok(project) => ProjectSummary::from(&project),
+                Err(err) => ProjectSummary::unavailable(url, err.to_string()),
+            });
+        }
+        (projects, page)
+    };
+    page.projects = projects;
+    Ok(HttpResponse::Ok().json(page))
+}
+
+/// Get a project
+///
+/// The project is identified by its URL encoded repo URL, e.g. `https%3A%2F%2Fgithub.com%2Fkusaridev%2Fskootrs`.
+#[utoipa::path(
+    get,
+    path = "/projects/{project_url}",
+    params(
+        ("project_url" = String, Path, description = "The URL encoded repo URL of the project"),
+    ),
+    responses(
+        (status = 200, description = "The project", body = InitializedProject),
+        (status = 404, description = "Project not found", body = ErrorResponse, example = json!(ErrorResponse::NotFound("Project not found".into()))),
+    )
+)]
+pub(super) async fn get_project(project_url: Path<String>, github_auth: Data<GithubAuth>) -> Result<impl Responder, actix_web::Error> {
+    let initialized_project = fetch_project(&github_auth, project_url.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(initialized_project))
+}
+
+/// Update a project
+///
+/// Brings the project's facets up to date and records the updated state in the project's repo. The body is
+/// optional, and every facet is updated if it's left out.
+#[utoipa::path(
+    put,
+    path = "/projects/{project_url}",
+    params(
+        ("project_url" = String, Path, description = "The URL encoded repo URL of the project"),
+    ),
+    request_body(content = Option<ProjectUpdateRequest>, description = "The facets to update"),
+    responses(
+        (status = 200, description = "Project updated successfully", body = InitializedProject),
+        (status = 404, description = "Project not found", body = ErrorResponse, example = json!(ErrorResponse::NotFound("Project not found".into()))),
+        (status = 409, description = "Project changed during the update", body = ErrorResponse, example = json!(ErrorResponse::Conflict("Remote branch main moved".into()))),
+        (status = 500, description = "Project unable to be updated", body = ErrorResponse, example = json!(ErrorResponse::UpdateError("Unable to push changes".into()))),
+    )
+)]
+pub(super) async fn update_project(req: HttpRequest, project_url: Path<String>, params: Option<Json<ProjectUpdateRequest>>, project_store: Data<Mutex<InMemoryProjectReferenceCache>>, github_auth: Data<GithubAuth>, audit_log: Data<AuditLog>) -> Result<impl Responder, actix_web::Error> {
+    let initialized_project = fetch_project(&github_auth, project_url.into_inner()).await?;
+    let params = params.map(Json::into_inner).unwrap_or_default();
+    let updated_project = update_and_record(
+        &github_auth,
+        &audit_log.with_actor(actor(&req)),
+        &project_store,
+        ProjectUpdateParams {
+            initialized_project,
+            facet_types: params.facet_types,
+        },
+    )
+    .await?;
+    Ok(HttpResponse::Ok().json(updated_project))
+}
+
+/// Updates a project and records its updated state, both in its repo and in the project store, and the update
+/// in the audit log.
+pub(super) async fn update_and_record(github_auth: &GithubAuth, audit_log: &AuditLog, project_store: &Mutex<InMemoryProjectReferenceCache>, params: ProjectUpdateParams) -> Result<InitializedProject, actix_web::Error> {
+    let updated_project = audited_project_service(github_auth, audit_log)?
+        .update(params)
+        .await
+        .map_err(service_error)?;
+    // The state has to be kept current so the next update can tell which facets drifted.
+    let git_state_store = GitProjectStateStore {
+        source: updated_project.source.clone(),
+        source_service: LocalSourceService::default(),
+        signing: StateSigning::default(),
+    };
+    git_state_store.update(updated_project.clone()).await.map_err(service_error)?;
+    let mut project_store = project_store.lock().await;
+    project_store.set(updated_project.repo.full_url()).await.map_err(service_error)?;
+    project_store.save().map_err(service_error)?;
+    Ok(updated_project)
+}
+
+/// Archive a project
+///
+/// Archives the project's repo and stops tracking the project.
+#[utoipa::path(
+    post,
+    path = "/projects/{project_url}/archive",
+    params(
+        ("project_url" = String, Path, description = "The URL encoded repo URL of the project"),
+        ArchiveQuery,
+    ),
+    responses(
+        (status = 200, description = "Project archived successfully", body = ProjectArchive),
+        (status = 404, description = "Project not found", body = ErrorResponse, example = json!(ErrorResponse::NotFound("Project not found".into()))),
+        (status = 500, description = "Project unable to be archived", body = ErrorResponse, example = json!(ErrorResponse::UpdateError("Unable to archive repo".into()))),
+    )
+)]
+pub(super) async fn archive_project(req: HttpRequest, project_url: Path<String>, query: Query<ArchiveQuery>, project_store: Data<Mutex<InMemoryProjectReferenceCache>>, github_auth: Data<GithubAuth>, audit_log: Data<AuditLog>) -> Result<impl Responder, actix_web::Error> {
+    let initialized_project = fetch_project(&github_auth, project_url.into_inner()).await?;
+    let url = initialized_project.repo.full_url();
+    let archive = audited_project_service(&github_auth, &audit_log.with_actor(actor(&req)))?
+        .archive(ProjectArchiveParams {
+            initialized_project,
+            cleanup: query.cleanup,
+        })
+        .await
+        .map_err(service_error)?;
+    let mut project_store = project_store.lock().await;
+    project_store.delete(url).await.map_err(service_error)?;
+    project_store.save().map_err(service_error)?;
+    Ok(HttpResponse::Ok().json(archive))
+}
+
+/// Maps an error from the project service to the response for its kind of failure: invalid parameters are a bad
+/// request, a missing project or facet is not found, a failure of the Github API is a bad gateway, and another
+/// operation getting in the way is a conflict. Anything else is an internal server error.
+pub(super) fn service_error(err: SkootError) -> actix_web::Error {
+    if err.downcast_ref::<ConcurrencyError>().is_some() {
+        return actix_web::error::ErrorConflict(err.to_string());
+    }
+    match err {
+        SkootError::Validation(_) => actix_web::error::ErrorBadRequest(err.to_string()),
+        SkootError::NotFound(_) => actix_web::error::ErrorNotFound(err.to_string()),
+        SkootError::GithubApi(_) => actix_web::error::ErrorBadGateway(err.to_string()),
+        _ => actix_web::error::ErrorInternalServerError(err.to_string()),
+    }
+}
+
+/// Fetches a project's state from its repo.
+pub(super) async fn fetch_project(
+    github_auth: &GithubAuth,
+    project_url: String,
+) -> Result<InitializedProject, actix_web::Error> {
+    project_service(github_auth)?
+        .get(ProjectGetParams {
+            project_url,
+            read_strategy: ReadStrategy::default(),
+        })
+        .await
+        .map_err(service_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http::StatusCode, test, App};
+    use skootrs_statestore::job::JsonJobStore;
+    use tokio::sync::mpsc;
+
+    use super::*;
+
+    fn create_params(name: &str) -> serde_json::Value {
+        serde_json::json!({
+            "name": name,
+            "repo_params": {
+                "Github": { "name": name, "description": "test", "organization": { "Organization": "testorg" } }
+            },
+            "ecosystem_params": { "Go": { "name": name, "host": "github.com/testorg" } },
+            "source_params": { "parent_path": "/tmp" }
+        })
+    }
+
+    #[actix_web::test]
+    async fn test_create_project_validates_params() {
+        let path =
+            std::env::temp_dir().join(format!("skootrs-rest-jobs-{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+        let _ = std::fs::remove_file(path);
+        let jobs = Data::new(Mutex::new(JsonJobStore::load_or_create(path).unwrap()));
+        let (queue, mut receiver) = mpsc::unbounded_channel::<String>();
+        let app = test::init_service(
+            App::new()
+                .app_data(jobs.clone())
+                .app_data(Data::new(queue))
+                .route("/projects", web::post().to(create_project)),
+        )
+        .await;
+
+        // An invalid name is rejected before a job is queued for it.
+        let req = test::TestRequest::post()
+            .uri("/projects")
+            .set_json(create_params("not a valid name"))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        assert!(jobs.lock().await.jobs.is_empty());
+        assert!(receiver.try_recv().is_err());
+
+        let req = test::TestRequest::post()
+            .uri("/projects")
+            .set_json(create_params("test"))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::ACCEPTED);
+        let job_id = receiver.try_recv().unwrap();
+        assert!(jobs.lock().await.get(&job_id).is_ok());
+        std::fs::remove_file(path).unwrap();
+    }
 }
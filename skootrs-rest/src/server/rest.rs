@@ -13,34 +13,59 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::net::Ipv4Addr;
+use std::{collections::BTreeMap, net::Ipv4Addr, path::Path, sync::Arc};
 
 use actix_web::{App, HttpServer, web::Data};
-use skootrs_statestore::InMemoryProjectReferenceCache;
-use tokio::sync::Mutex;
+use skootrs_lib::service::{audit_log::AuditLog, github_auth::GithubAuth};
+use skootrs_statestore::{job::JsonJobStore, InMemoryProjectReferenceCache};
+use tokio::sync::{mpsc, Mutex};
 use tracing_actix_web::TracingLogger;
-use utoipa::{OpenApi, Modify, openapi::security::{SecurityScheme, ApiKey, ApiKeyValue}};
+use utoipa::{OpenApi, Modify, openapi::security::{SecurityScheme, SecurityRequirement, ApiKey, ApiKeyValue}};
 use utoipa_rapidoc::RapiDoc;
 use utoipa_redoc::{Redoc, Servable};
 use utoipa_swagger_ui::SwaggerUi;
 
-use crate::server::project::ErrorResponse;
-use skootrs_model::{skootrs::{InitializedProject, ProjectCreateParams, InitializedRepo, InitializedGithubRepo, InitializedEcosystem, RepoCreateParams, EcosystemInitializeParams, GithubUser, GithubRepoParams, SourceInitializeParams, InitializedSource, MavenParams, GoParams, InitializedGo, InitializedMaven, facet::{CommonFacetCreateParams, InitializedFacet, FacetCreateParams, SupportedFacetType}}, cd_events::repo_created::{RepositoryCreatedEvent, RepositoryCreatedEventContext, RepositoryCreatedEventContextId, RepositoryCreatedEventContextVersion, RepositoryCreatedEventSubject, RepositoryCreatedEventSubjectContent, RepositoryCreatedEventSubjectContentUrl, RepositoryCreatedEventSubjectId}, security_insights::insights10::{SecurityInsightsVersion100YamlSchema, SecurityInsightsVersion100YamlSchemaContributionPolicy, SecurityInsightsVersion100YamlSchemaContributionPolicyAutomatedToolsListItem, SecurityInsightsVersion100YamlSchemaContributionPolicyAutomatedToolsListItemComment, SecurityInsightsVersion100YamlSchemaDependencies, SecurityInsightsVersion100YamlSchemaDependenciesDependenciesLifecycle, SecurityInsightsVersion100YamlSchemaDependenciesDependenciesLifecycleComment, SecurityInsightsVersion100YamlSchemaDependenciesEnvDependenciesPolicy, SecurityInsightsVersion100YamlSchemaDependenciesEnvDependenciesPolicyComment, SecurityInsightsVersion100YamlSchemaDependenciesSbomItem, SecurityInsightsVersion100YamlSchemaDependenciesSbomItemSbomCreation, SecurityInsightsVersion100YamlSchemaHeader, SecurityInsightsVersion100YamlSchemaHeaderCommitHash, SecurityInsightsVersion100YamlSchemaProjectLifecycle, SecurityInsightsVersion100YamlSchemaProjectLifecycleReleaseProcess, SecurityInsightsVersion100YamlSchemaSecurityArtifacts, SecurityInsightsVersion100YamlSchemaSecurityArtifactsSelfAssessment, SecurityInsightsVersion100YamlSchemaSecurityArtifactsSelfAssessmentComment, SecurityInsightsVersion100YamlSchemaSecurityArtifactsThreatModel, SecurityInsightsVersion100YamlSchemaSecurityArtifactsThreatModelComment, SecurityInsightsVersion100YamlSchemaSecurityAssessmentsItem, SecurityInsightsVersion100YamlSchemaSecurityAssessmentsItemComment, SecurityInsightsVersion100YamlSchemaSecurityContactsItem, SecurityInsightsVersion100YamlSchemaSecurityContactsItemValue, SecurityInsightsVersion100YamlSchemaSecurityTestingItem, SecurityInsightsVersion100YamlSchemaSecurityTestingItemComment, SecurityInsightsVersion100YamlSchemaSecurityTestingItemIntegration, SecurityInsightsVersion100YamlSchemaVulnerabilityReporting, SecurityInsightsVersion100YamlSchemaVulnerabilityReportingComment, SecurityInsightsVersion100YamlSchemaVulnerabilityReportingPgpKey}};
-use skootrs_model::skootrs::facet::{SourceBundleFacet, SourceBundleFacetCreateParams, APIBundleFacet, APIBundleFacetParams, SourceFileContent, APIContent};
+use crate::server::auth::{ApiKeyAuth, API_KEY_HEADER};
+use crate::server::project::{ErrorResponse, ProjectUpdateRequest};
+use crate::server::scheduler::Reconciliations;
+use skootrs_model::{skootrs::{InitializedProject, ProjectCreateParams, InitializedRepo, InitializedGithubRepo, InitializedBitbucketRepo, BitbucketRepoParams, InitializedEcosystem, InitializedModule, RepoCreateParams, EcosystemInitializeParams, ModuleInitializeParams, GithubUser, GithubRepoParams, SourceInitializeParams, InitializedSource, MavenParams, GoParams, InitializedGo, InitializedMaven, PythonParams, PythonPackageManager, InitializedPython, RustParams, InitializedRust, facet::{CommonFacetCreateParams, InitializedFacet, FacetCreateParams, SupportedFacetType}}, cd_events::repo_created::{RepositoryCreatedEvent, RepositoryCreatedEventContext, RepositoryCreatedEventContextId, RepositoryCreatedEventContextVersion, RepositoryCreatedEventSubject, RepositoryCreatedEventSubjectContent, RepositoryCreatedEventSubjectContentUrl, RepositoryCreatedEventSubjectId}, security_insights::insights10::{SecurityInsightsVersion100YamlSchema, SecurityInsightsVersion100YamlSchemaContributionPolicy, SecurityInsightsVersion100YamlSchemaContributionPolicyAutomatedToolsListItem, SecurityInsightsVersion100YamlSchemaContributionPolicyAutomatedToolsListItemComment, SecurityInsightsVersion100YamlSchemaDependencies, SecurityInsightsVersion100YamlSchemaDependenciesDependenciesLifecycle, SecurityInsightsVersion100YamlSchemaDependenciesDependenciesLifecycleComment, SecurityInsightsVersion100YamlSchemaDependenciesEnvDependenciesPolicy, SecurityInsightsVersion100YamlSchemaDependenciesEnvDependenciesPolicyComment, SecurityInsightsVersion100YamlSchemaDependenciesSbomItem, SecurityInsightsVersion100YamlSchemaDependenciesSbomItemSbomCreation, SecurityInsightsVersion100YamlSchemaHeader, SecurityInsightsVersion100YamlSchemaHeaderCommitHash, SecurityInsightsVersion100YamlSchemaProjectLifecycle, SecurityInsightsVersion100YamlSchemaProjectLifecycleReleaseProcess, SecurityInsightsVersion100YamlSchemaSecurityArtifacts, SecurityInsightsVersion100YamlSchemaSecurityArtifactsSelfAssessment, SecurityInsightsVersion100YamlSchemaSecurityArtifactsSelfAssessmentComment, SecurityInsightsVersion100YamlSchemaSecurityArtifactsThreatModel, SecurityInsightsVersion100YamlSchemaSecurityArtifactsThreatModelComment, SecurityInsightsVersion100YamlSchemaSecurityAssessmentsItem, SecurityInsightsVersion100YamlSchemaSecurityAssessmentsItemComment, SecurityInsightsVersion100YamlSchemaSecurityContactsItem, SecurityInsightsVersion100YamlSchemaSecurityContactsItemValue, SecurityInsightsVersion100YamlSchemaSecurityTestingItem, SecurityInsightsVersion100YamlSchemaSecurityTestingItemComment, SecurityInsightsVersion100YamlSchemaSecurityTestingItemIntegration, SecurityInsightsVersion100YamlSchemaVulnerabilityReporting, SecurityInsightsVersion100YamlSchemaVulnerabilityReportingComment, SecurityInsightsVersion100YamlSchemaVulnerabilityReportingPgpKey}};
+use skootrs_model::skootrs::facet::{SourceBundleFacet, SourceBundleFacetCreateParams, APIBundleFacet, APIBundleFacetParams, SourceFileContent, APIContent, FacetAudit, FacetAuditStatus, ProjectAudit};
+use skootrs_model::skootrs::scan_policy::{AllowlistAudit, AllowlistAuditStatus};
+use skootrs_model::skootrs::best_practices::{BestPracticesBadge, BadgeLevel};
+use skootrs_model::skootrs::attestation::PredicateType;
+use skootrs_model::skootrs::listing::{ProjectPage, ProjectSort, ProjectSummary};
+use skootrs_model::skootrs::{ProjectGetParams, FacetMapKey, FacetGetParams, ProjectOutputType, ProjectOutput, ProjectOutputReference, ProjectReleaseParam, WorkflowRunParam, ProjectArchive, ActionsCleanup, DaemonConfig, ProjectReconciliation, Job, JobStatus, label::Label};
 
-/// Run the Skootrs REST API server.
+/// Run the Skootrs REST API server. Requests to the API are only accepted with one of the API keys in `config`,
+/// and the server calls the Github API with `github_auth`. The changes made to projects are recorded in
+/// `audit_log`, as made by the API key of the request that made them, and projects are tracked in
+/// `project_store`, which the gRPC API shares when it's served too.
 #[actix_web::main]
-pub async fn run_server() -> std::io::Result<()> {
+pub async fn run_server(config: DaemonConfig, github_auth: GithubAuth, audit_log: AuditLog, project_store: Arc<Mutex<InMemoryProjectReferenceCache>>) -> std::io::Result<()> {
     #[derive(OpenApi)]
     #[openapi(
         paths(
             crate::server::project::create_project,
             crate::server::project::list_projects,
+            crate::server::project::get_project,
+            crate::server::project::update_project,
+            crate::server::project::archive_project,
+            crate::server::facet::list_facets,
+            crate::server::facet::get_facet,
+            crate::server::facet::audit_project,
+            crate::server::output::list_outputs,
+            crate::server::output::get_output,
+            crate::server::webhook::github_webhook,
+            crate::server::scheduler::list_reconciliations,
+            crate::server::job::list_jobs,
+            crate::server::job::get_job,
+            crate::server::metrics::get_metrics,
         ),
         components(
             schemas(
                 // Server only schemas
                 ErrorResponse, 
+                ProjectUpdateRequest,
 
                 // Skootrs Model schemas
                 InitializedProject,
@@ -48,16 +73,44 @@ pub async fn run_server() -> std::io::Result<()> {
                 InitializedRepo,
                 InitializedGithubRepo,
                 InitializedEcosystem,
+                InitializedModule,
                 RepoCreateParams,
                 EcosystemInitializeParams,
+                ModuleInitializeParams,
                 GithubUser,
                 GithubRepoParams,
+                InitializedBitbucketRepo,
+                BitbucketRepoParams,
                 SourceInitializeParams,
                 InitializedSource,
                 MavenParams,
                 GoParams,
                 InitializedGo,
                 InitializedMaven,
+                PythonParams,
+                PythonPackageManager,
+                InitializedPython,
+                RustParams,
+                InitializedRust,
+                ProjectGetParams,
+                ProjectPage,
+                ProjectSummary,
+                ProjectSort,
+                FacetMapKey,
+                FacetGetParams,
+                Label,
+                // Output Schemas
+                ProjectOutputType,
+                PredicateType,
+                ProjectOutput,
+                ProjectOutputReference,
+                ProjectReleaseParam,
+                WorkflowRunParam,
+                ProjectArchive,
+                ActionsCleanup,
+                ProjectReconciliation,
+                Job,
+                JobStatus,
                 // Facet Schemas
                 CommonFacetCreateParams,
                 InitializedFacet,
@@ -70,6 +123,13 @@ pub async fn run_server() -> std::io::Result<()> {
                 APIBundleFacetParams,
                 SourceFileContent,
                 APIContent,
+                FacetAudit,
+                FacetAuditStatus,
+                ProjectAudit,
+                AllowlistAudit,
+                AllowlistAuditStatus,
+                BestPracticesBadge,
+                BadgeLevel,
 
                 // CD Events Schemas
                 RepositoryCreatedEvent,
@@ -128,19 +188,54 @@ pub async fn run_server() -> std::io::Result<()> {
             let components = openapi.components.as_mut().expect("Components must exist");
             components.add_security_scheme(
                 "api_key",
-                SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("skootrs_apikey"))),
+                SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new(API_KEY_HEADER))),
             );
+            openapi.security = Some(vec![SecurityRequirement::new("api_key", Vec::<String>::new())]);
         }
     }
 
-    let store: Data<Mutex<InMemoryProjectReferenceCache>> = Data::new(Mutex::new(InMemoryProjectReferenceCache::new("/tmp/cache.json".into())));
+    let store: Data<Mutex<InMemoryProjectReferenceCache>> = Data::from(project_store);
+    let github_auth = Data::new(github_auth);
+    let audit_log = Data::new(audit_log);
+    let reconciliations: Data<Reconciliations> = Data::new(Mutex::new(BTreeMap::new()));
+    actix_web::rt::spawn(crate::server::scheduler::run(config.reconciliation, github_auth.clone(), audit_log.clone(), store.clone(), reconciliations.clone()));
+    // Jobs that were queued when the daemon last stopped are queued again.
+    std::fs::create_dir_all(&config.data_dir)?;
+    let jobs_path = Path::new(&config.data_dir).join("jobs.json");
+    let mut job_store = JsonJobStore::load_or_create(&jobs_path.to_string_lossy())
+        .map_err(std::io::Error::other)?;
+    let resumed_jobs = job_store.resume().map_err(std::io::Error::other)?;
+    let jobs = Data::new(Mutex::new(job_store));
+    let (queue, receiver) = mpsc::unbounded_channel();
+    for id in resumed_jobs {
+        queue.send(id).map_err(std::io::Error::other)?;
+    }
+    let queue = Data::new(queue);
+    actix_web::rt::spawn(crate::server::job::run_workers(
+        config.job_workers,
+        github_auth.clone(),
+        audit_log.clone(),
+        store.clone(),
+        jobs.clone(),
+        receiver,
+    ));
     // Make instance variable of ApiDoc so all worker threads gets the same instance.
     let openapi = ApiDoc::openapi();
+    let auth = ApiKeyAuth::new(config.api_keys);
 
     HttpServer::new(move || {
         App::new()
             .wrap(TracingLogger::default())
+            .wrap(auth.clone())
+            .app_data(github_auth.clone())
+            .app_data(audit_log.clone())
             .configure(crate::server::project::configure(store.clone()))
+            .configure(crate::server::facet::configure)
+            .configure(crate::server::output::configure)
+            .configure(crate::server::metrics::configure)
+            .configure(crate::server::webhook::configure(config.webhook_secret.clone()))
+            .configure(crate::server::scheduler::configure(reconciliations.clone()))
+            .configure(crate::server::job::configure(jobs.clone(), queue.clone()))
             .service(Redoc::with_url("/redoc", openapi.clone()))
             .service(
                 SwaggerUi::new("/swagger-ui/{_:.*}").url("/api-docs/openapi.json", openapi.clone()),
@@ -0,0 +1,209 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The scheduler reconciles every project in the state store on an interval, so projects are kept secure even
+//! when nothing asks the daemon to update them. Each project is audited, and the ones that aren't compliant
+//! are updated. The result of the last reconciliation of each project is served at `/reconciliations`.
+
+use std::{
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
+
+use actix_web::{
+    web::{self, Data, ServiceConfig},
+    HttpResponse, Responder,
+};
+use chrono::Utc;
+use skootrs_lib::service::{
+    audit_log::AuditLog,
+    github_auth::GithubAuth,
+    metrics::{self, Counter, Histogram},
+    project::ProjectService,
+};
+use skootrs_model::skootrs::{
+    facet::ProjectAudit, ProjectGetParams, ProjectReconciliation, ProjectUpdateParams,
+    ReadStrategy, ReconciliationConfig,
+};
+use skootrs_statestore::{InMemoryProjectReferenceCache, ProjectReferenceCache};
+use tokio::sync::Mutex;
+use tracing::{error, info, warn};
+
+use crate::server::project::{fetch_project, project_service, update_and_record};
+
+/// The result of the last reconciliation of each project, by the URL of its repo.
+pub(super) type Reconciliations = Mutex<BTreeMap<String, ProjectReconciliation>>;
+
+/// Configures the routes for the results of reconciliations
+pub(super) fn configure(reconciliations: Data<Reconciliations>) -> impl FnOnce(&mut ServiceConfig) {
+    |config: &mut ServiceConfig| {
+        config
+            .app_data(reconciliations)
+            .service(web::resource("/reconciliations").route(web::get().to(list_reconciliations)));
+    }
+}
+
+/// List the reconciliations of projects
+///
+/// Returns the result of the last background reconciliation of each project. It's empty if background
+/// reconciliation isn't enabled.
+#[utoipa::path(
+    get,
+    path = "/reconciliations",
+    responses(
+        (status = 200, description = "The last reconciliation of each project", body = [ProjectReconciliation]),
+    )
+)]
+pub(super) async fn list_reconciliations(
+    reconciliations: Data<Reconciliations>,
+) -> Result<impl Responder, actix_web::Error> {
+    let reconciliations = reconciliations
+        .lock()
+        .await
+        .values()
+        .cloned()
+        .collect::<Vec<_>>();
+    Ok(HttpResponse::Ok().json(reconciliations))
+}
+
+/// Reconciles every project in the state store on the configured interval, forever. Returns right away if
+/// background reconciliation isn't enabled. The updates are recorded in the audit log as made by
+/// `daemon:scheduler`.
+pub(super) async fn run(
+    config: ReconciliationConfig,
+    github_auth: Data<GithubAuth>,
+    audit_log: Data<AuditLog>,
+    project_store: Data<Mutex<InMemoryProjectReferenceCache>>,
+    reconciliations: Data<Reconciliations>,
+) {
+    let Some(interval_secs) = config.interval_secs else {
+        return;
+    };
+    info!("Reconciling projects every {interval_secs} seconds");
+    let audit_log = audit_log.with_actor("daemon:scheduler");
+    let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+    // A slow pass delays the next one instead of starting several at once.
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        let project_urls = match project_store.lock().await.list().await {
+            Ok(project_urls) => project_urls,
+            Err(err) => {
+                error!("Failed to list the projects to reconcile: {err}");
+                continue;
+            }
+        };
+        for project_url in project_urls {
+            let started = Instant::now();
+            let reconciliation = reconcile(
+                &config,
+                &github_auth,
+                &audit_log,
+                &project_store,
+                project_url.clone(),
+            )
+            .await;
+            record_metrics(&reconciliation, started.elapsed());
+            if let Some(error) = &reconciliation.error {
+                warn!("Failed to reconcile {project_url}: {error}");
+            }
+            reconciliations
+                .lock()
+                .await
+                .insert(project_url, reconciliation);
+        }
+    }
+}
+
+/// Records a project's reconciliation in the daemon's metrics, by whether it failed, updated the project, or
+/// found it compliant or not.
+fn record_metrics(reconciliation: &ProjectReconciliation, duration: Duration) {
+    let outcome = if reconciliation.error.is_some() {
+        "failed"
+    } else if reconciliation.updated {
+        "updated"
+    } else if reconciliation
+        .audit
+        .as_ref()
+        .is_some_and(ProjectAudit::is_compliant)
+    {
+        "compliant"
+    } else {
+        "noncompliant"
+    };
+    let metrics = metrics::global();
+    metrics.increment(Counter::Reconciliations, &[("outcome", outcome)]);
+    metrics.observe(Histogram::ReconciliationDuration, &[], duration);
+}
+
+/// Audits a project, and updates it if it isn't compliant and updates are enabled. Errors are recorded in the
+/// result instead of stopping the other projects from being reconciled.
+async fn reconcile(
+    config: &ReconciliationConfig,
+    github_auth: &GithubAuth,
+    audit_log: &AuditLog,
+    project_store: &Mutex<InMemoryProjectReferenceCache>,
+    project_url: String,
+) -> ProjectReconciliation {
+    let mut reconciliation = ProjectReconciliation {
+        project_url: project_url.clone(),
+        reconciled_at: Utc::now(),
+        audit: None,
+        updated: false,
+        error: None,
+    };
+    let audit = match project_service(github_auth) {
+        Ok(project_service) => project_service
+            .audit(ProjectGetParams {
+                project_url: project_url.clone(),
+                read_strategy: ReadStrategy::default(),
+            })
+            .await
+            .map_err(|err| err.to_string()),
+        Err(err) => Err(err.to_string()),
+    };
+    let audit = match audit {
+        Ok(audit) => audit,
+        Err(err) => {
+            reconciliation.error = Some(err);
+            return reconciliation;
+        }
+    };
+    let compliant = audit.is_compliant();
+    reconciliation.audit = Some(audit);
+    if compliant || !config.update {
+        return reconciliation;
+    }
+    info!("Updating {project_url} since it isn't compliant");
+    let updated = async {
+        let initialized_project = fetch_project(github_auth, project_url).await?;
+        update_and_record(
+            github_auth,
+            audit_log,
+            project_store,
+            ProjectUpdateParams {
+                initialized_project,
+                facet_types: None,
+            },
+        )
+        .await
+    }
+    .await;
+    match updated {
+        Ok(_) => reconciliation.updated = true,
+        Err(err) => reconciliation.error = Some(err.to_string()),
+    }
+    reconciliation
+}
@@ -0,0 +1,309 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! GitHub webhooks let the daemon reconcile a project as soon as its repo changes instead of waiting for the
+//! next `skootrs project update`. A push to the default branch can drift the source facets, and a change to
+//! the branch protection or rulesets can undo the API facets, so either one triggers an update of the project
+//! that re-applies what drifted.
+
+use actix_web::{
+    web::{self, Bytes, Data, ServiceConfig},
+    HttpRequest, HttpResponse, Responder,
+};
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+use skootrs_lib::service::{audit_log::AuditLog, github_auth::GithubAuth};
+use skootrs_model::skootrs::{
+    constant_time_eq,
+    facet::{InitializedFacet, SupportedFacetType},
+    ProjectUpdateParams,
+};
+use skootrs_statestore::{InMemoryProjectReferenceCache, ProjectReferenceCache};
+use tokio::sync::Mutex;
+use tracing::{error, info};
+
+use crate::server::project::{fetch_project, service_error, update_and_record, ErrorResponse};
+
+/// The header GitHub sends the HMAC-SHA256 signature of a delivery's body in.
+const SIGNATURE_HEADER: &str = "X-Hub-Signature-256";
+
+/// The header GitHub sends the name of a delivery's event in.
+const EVENT_HEADER: &str = "X-GitHub-Event";
+
+/// The secret GitHub webhook deliveries are signed with.
+#[derive(Clone)]
+pub(super) struct WebhookSecret(Option<String>);
+
+/// The parts of a webhook delivery's payload that are needed to find the project it's about.
+#[derive(Deserialize)]
+struct WebhookPayload {
+    /// The ref that was pushed to, e.g. `refs/heads/main`. Only set for `push` events.
+    #[serde(rename = "ref")]
+    git_ref: Option<String>,
+    repository: WebhookRepository,
+}
+
+#[derive(Deserialize)]
+struct WebhookRepository {
+    /// The URL of the repo, which is the URL the project is tracked by.
+    html_url: String,
+    default_branch: String,
+}
+
+/// What a webhook delivery makes the project reconcile.
+#[derive(Debug, PartialEq, Eq)]
+enum Reconciliation {
+    /// Every facet, since the source changed.
+    AllFacets,
+    /// Only the API facets, since the repo's settings changed.
+    ApiFacets,
+}
+
+/// Configures the routes for webhooks
+pub(super) fn configure(webhook_secret: Option<String>) -> impl FnOnce(&mut ServiceConfig) {
+    |config: &mut ServiceConfig| {
+        config
+            .app_data(Data::new(WebhookSecret(webhook_secret)))
+            .service(web::resource("/webhooks/github").route(web::post().to(github_webhook)));
+    }
+}
+
+/// Receive a GitHub webhook
+///
+/// Reconciles a project when its default branch is pushed to, or when its branch protection or rulesets
+/// change. The delivery has to be signed with the configured webhook secret instead of having an API key.
+/// Deliveries for repos that aren't managed by Skootrs, and for other events, are accepted and ignored. The
+/// reconciliation runs after the delivery is answered, since GitHub gives up on deliveries after 10 seconds.
+#[utoipa::path(
+    post,
+    path = "/webhooks/github",
+    request_body(content = String, description = "The payload of the webhook delivery"),
+    responses(
+        (status = 202, description = "The project is being reconciled"),
+        (status = 204, description = "The delivery doesn't need a reconciliation"),
+        (status = 400, description = "The payload couldn't be parsed", body = ErrorResponse),
+        (status = 401, description = "The signature is missing or invalid", body = ErrorResponse, example = json!(ErrorResponse::Unauthorized("The webhook signature isn't valid".into()))),
+    ),
+    security(()),
+)]
+pub(super) async fn github_webhook(
+    req: HttpRequest,
+    body: Bytes,
+    webhook_secret: Data<WebhookSecret>,
+    project_store: Data<Mutex<InMemoryProjectReferenceCache>>,
+    github_auth: Data<GithubAuth>,
+    audit_log: Data<AuditLog>,
+) -> Result<impl Responder, actix_web::Error> {
+    let header = |name: &str| {
+        req.headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default()
+    };
+    if !verify_signature(webhook_secret.0.as_deref(), &body, header(SIGNATURE_HEADER)) {
+        return Ok(
+            HttpResponse::Unauthorized().json(ErrorResponse::Unauthorized(
+                "The webhook signature isn't valid".to_string(),
+            )),
+        );
+    }
+    let event = header(EVENT_HEADER).to_string();
+    // A `ping` is sent when the webhook is created and doesn't have a repo to reconcile.
+    if event == "ping" {
+        return Ok(HttpResponse::NoContent().finish());
+    }
+    let payload: WebhookPayload = serde_json::from_slice(&body)
+        .map_err(|err| actix_web::error::ErrorBadRequest(err.to_string()))?;
+    let Some(reconciliation) = reconciliation(&event, &payload) else {
+        return Ok(HttpResponse::NoContent().finish());
+    };
+    let project_url = payload.repository.html_url;
+    let managed = project_store
+        .lock()
+        .await
+        .list()
+        .await
+        .map_err(service_error)?
+        .contains(&project_url);
+    if !managed {
+        return Ok(HttpResponse::NoContent().finish());
+    }
+    info!("Reconciling {project_url} after a {event} event");
+    let audit_log = audit_log.with_actor("webhook:github");
+    actix_web::rt::spawn(async move {
+        if let Err(err) = reconcile(
+            &github_auth,
+            &audit_log,
+            &project_store,
+            project_url.clone(),
+            reconciliation,
+        )
+        .await
+        {
+            error!("Failed to reconcile {project_url}: {err}");
+        }
+    });
+    Ok(HttpResponse::Accepted().finish())
+}
+
+/// Returns what an event makes a project reconcile, or `None` if the event doesn't affect its facets. Pushes
+/// to other branches, like the ones Skootrs proposes changes on, are ignored.
+fn reconciliation(event: &str, payload: &WebhookPayload) -> Option<Reconciliation> {
+    match event {
+        "push" => {
+            let branch = payload
+                .git_ref
+                .as_deref()
+                .and_then(|git_ref| git_ref.strip_prefix("refs/heads/"));
+            (branch == Some(payload.repository.default_branch.as_str()))
+                .then_some(Reconciliation::AllFacets)
+        }
+        "branch_protection_rule" | "repository_ruleset" => Some(Reconciliation::ApiFacets),
+        _ => None,
+    }
+}
+
+/// Updates a project against its recorded state. Source facets that drifted are pushed or proposed as a pull
+/// request according to the `change_requests` config, and API facets are applied again.
+async fn reconcile(
+    github_auth: &GithubAuth,
+    audit_log: &AuditLog,
+    project_store: &Mutex<InMemoryProjectReferenceCache>,
+    project_url: String,
+    reconciliation: Reconciliation,
+) -> Result<(), actix_web::Error> {
+    let initialized_project = fetch_project(github_auth, project_url).await?;
+    let facet_types = match reconciliation {
+        Reconciliation::AllFacets => None,
+        Reconciliation::ApiFacets => Some(
+            initialized_project
+                .facets
+                .values()
+                .filter(|facet| matches!(facet, InitializedFacet::APIBundle(_)))
+                .map(InitializedFacet::facet_type)
+                .collect::<Vec<SupportedFacetType>>(),
+        ),
+    };
+    update_and_record(
+        github_auth,
+        audit_log,
+        project_store,
+        ProjectUpdateParams {
+            initialized_project,
+            facet_types,
+        },
+    )
+    .await?;
+    Ok(())
+}
+
+/// Returns whether the signature GitHub sent is the HMAC-SHA256 of the body with the secret. Every delivery
+/// is rejected when there's no secret.
+fn verify_signature(secret: Option<&str>, body: &[u8], signature: &str) -> bool {
+    let Some(secret) = secret else {
+        return false;
+    };
+    let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(secret.as_bytes()) else {
+        return false;
+    };
+    mac.update(body);
+    let expected = format!("sha256={:x}", mac.finalize().into_bytes());
+    constant_time_eq(expected.as_bytes(), signature.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The example delivery from GitHub's docs on validating webhook deliveries.
+    const SECRET: &str = "It's a Secret to Everybody";
+    const BODY: &[u8] = b"Hello, World!";
+    const SIGNATURE: &str =
+        "sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17";
+
+    fn payload(git_ref: Option<&str>) -> WebhookPayload {
+        WebhookPayload {
+            git_ref: git_ref.map(ToString::to_string),
+            repository: WebhookRepository {
+                html_url: "https://github.com/kusaridev/skootrs".to_string(),
+                default_branch: "main".to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        assert!(verify_signature(Some(SECRET), BODY, SIGNATURE));
+        // The body was changed after it was signed.
+        assert!(!verify_signature(Some(SECRET), b"Hello, World?", SIGNATURE));
+        assert!(!verify_signature(Some("another secret"), BODY, SIGNATURE));
+    }
+
+    #[test]
+    fn test_verify_signature_malformed_header() {
+        // A missing header is read as an empty signature.
+        assert!(!verify_signature(Some(SECRET), BODY, ""));
+        assert!(!verify_signature(
+            Some(SECRET),
+            BODY,
+            SIGNATURE.trim_start_matches("sha256=")
+        ));
+        assert!(!verify_signature(
+            Some(SECRET),
+            BODY,
+            &SIGNATURE.replace("sha256=", "sha1=")
+        ));
+        assert!(!verify_signature(Some(SECRET), BODY, &SIGNATURE[..20]));
+        assert!(!verify_signature(
+            Some(SECRET),
+            BODY,
+            &SIGNATURE.to_uppercase()
+        ));
+    }
+
+    #[test]
+    fn test_verify_signature_without_secret() {
+        assert!(!verify_signature(None, BODY, SIGNATURE));
+        assert!(!verify_signature(None, BODY, ""));
+    }
+
+    #[test]
+    fn test_reconciliation() {
+        assert_eq!(
+            reconciliation("push", &payload(Some("refs/heads/main"))),
+            Some(Reconciliation::AllFacets)
+        );
+        // Pushes to other branches, like the ones Skootrs proposes changes on, and to tags are ignored.
+        assert_eq!(
+            reconciliation("push", &payload(Some("refs/heads/skootrs/update"))),
+            None
+        );
+        assert_eq!(
+            reconciliation("push", &payload(Some("refs/tags/main"))),
+            None
+        );
+        assert_eq!(reconciliation("push", &payload(None)), None);
+        assert_eq!(
+            reconciliation("branch_protection_rule", &payload(None)),
+            Some(Reconciliation::ApiFacets)
+        );
+        assert_eq!(
+            reconciliation("repository_ruleset", &payload(None)),
+            Some(Reconciliation::ApiFacets)
+        );
+        assert_eq!(reconciliation("issues", &payload(None)), None);
+    }
+}
@@ -0,0 +1,146 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The jobs the daemon runs in the background are kept in a JSON file in the daemon's data directory, so the
+//! jobs and their results survive the daemon restarting.
+
+use std::collections::BTreeMap;
+
+use skootrs_model::skootrs::{Job, JobStatus, SkootError};
+
+/// The `JsonJobStore` struct keeps the daemon's jobs, by their ID, in the JSON file at `save_path`.
+pub struct JsonJobStore {
+    pub save_path: String,
+    pub jobs: BTreeMap<String, Job>,
+}
+
+impl JsonJobStore {
+    /// Load the jobs from the file at `path` or start with no jobs if the file does not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but can't be read, or if a new file can't be created.
+    pub fn load_or_create(path: &str) -> Result<Self, SkootError> {
+        let mut store = Self {
+            save_path: path.to_string(),
+            jobs: BTreeMap::new(),
+        };
+        if std::path::Path::new(path).exists() {
+            store.jobs = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        } else {
+            store.save()?;
+        }
+        Ok(store)
+    }
+
+    /// Save the jobs to the file at `save_path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the jobs can't be saved.
+    pub fn save(&self) -> Result<(), SkootError> {
+        std::fs::write(&self.save_path, serde_json::to_string(&self.jobs)?)?;
+        Ok(())
+    }
+
+    /// Stores a job, replacing the job with the same ID, and saves the jobs.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the jobs can't be saved.
+    pub fn put(&mut self, job: Job) -> Result<(), SkootError> {
+        self.jobs.insert(job.id.clone(), job);
+        self.save()
+    }
+
+    /// Returns the job with the given ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SkootError::NotFound` if there's no job with the ID.
+    pub fn get(&self, id: &str) -> Result<Job, SkootError> {
+        self.jobs
+            .get(id)
+            .cloned()
+            .ok_or_else(|| SkootError::NotFound(format!("Job {id} not found")))
+    }
+
+    /// Prepares the jobs left behind by the previous run of the daemon and returns the IDs of the ones to run
+    /// again. Queued jobs never started so they're run again, while jobs that were running are failed since
+    /// creating a project partway again could create a second repo.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the jobs can't be saved.
+    pub fn resume(&mut self) -> Result<Vec<String>, SkootError> {
+        let mut queued = vec![];
+        for job in self.jobs.values_mut() {
+            match job.status {
+                JobStatus::Queued => queued.push(job.id.clone()),
+                JobStatus::Running => {
+                    job.fail("The daemon stopped while the job was running".to_string());
+                }
+                JobStatus::Succeeded | JobStatus::Failed => {}
+            }
+        }
+        self.save()?;
+        Ok(queued)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use skootrs_model::skootrs::ProjectCreateParams;
+
+    use super::*;
+
+    fn job(id: &str) -> Job {
+        let params: ProjectCreateParams = serde_json::from_value(serde_json::json!({
+            "name": "test",
+            "repo_params": {
+                "Github": { "name": "test", "description": "test", "organization": { "Organization": "testorg" } }
+            },
+            "ecosystem_params": { "Go": { "name": "test", "host": "github.com/testorg" } },
+            "source_params": { "parent_path": "/tmp" }
+        }))
+        .unwrap();
+        Job::new(id.to_string(), params, None)
+    }
+
+    #[test]
+    fn test_resume() {
+        let path = std::env::temp_dir().join(format!("skootrs-jobs-{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+        let _ = std::fs::remove_file(path);
+
+        let mut store = JsonJobStore::load_or_create(path).unwrap();
+        store.put(job("queued")).unwrap();
+        let mut running = job("running");
+        running.progress("Creating the project");
+        store.put(running).unwrap();
+        let mut succeeded = job("succeeded");
+        succeeded.succeed("https://github.com/testorg/test".to_string());
+        store.put(succeeded).unwrap();
+
+        // The jobs are read back as the daemon left them.
+        let mut store = JsonJobStore::load_or_create(path).unwrap();
+        assert_eq!(store.resume().unwrap(), vec!["queued".to_string()]);
+        assert_eq!(store.get("running").unwrap().status, JobStatus::Failed);
+        assert_eq!(store.get("succeeded").unwrap().status, JobStatus::Succeeded);
+        assert!(matches!(store.get("missing"), Err(SkootError::NotFound(_))));
+        std::fs::remove_file(path).unwrap();
+    }
+}
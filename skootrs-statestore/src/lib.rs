@@ -14,16 +14,38 @@
 // limitations under the License.
 
 //! This is the crate where the statestore where the management of `Skootrs` project state is defined.
-//! The statestore currently supports an in memory `SurrealDB` instance that writes to a file.
+//! The state of each project is kept in its repo, and the projects an installation knows about are tracked by
+//! a `ProjectReferenceCache`. The cache is either a JSON file of repo URLs that keeps each project's state for a
+//! while after it's fetched, or with the `surrealdb` feature a `SurrealDB` database that also stores a copy of
+//! each project's state.
 
-use std::collections::HashSet;
+pub mod job;
+pub mod migration;
+pub mod signature;
+#[cfg(feature = "surrealdb")]
+pub mod surreal;
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    path::Path,
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 
 use skootrs_lib::service::{
     repo::{LocalRepoService, RepoService},
     source::{LocalSourceService, SourceService},
 };
 
-use skootrs_model::skootrs::{InitializedProject, InitializedRepo, InitializedSource, SkootError};
+use signature::{STATE_FILE, STATE_SIGNATURE_FILE};
+use skootrs_model::skootrs::{
+    change_request::{ChangeMode, ChangeRequestConfig, PullRequestParams},
+    listing::ProjectSummary,
+    Config, InitializedProject, InitializedRepo, InitializedRepoGetParams, InitializedSource,
+    SignedProject, SkootError, StateSigning, StateStoreConfig,
+};
 
 pub trait ProjectStateStore {
     fn create(
@@ -43,28 +65,122 @@ pub struct GitProjectStateStore<S: SourceService> {
     // TODO: This should be a git repo type of some sort
     pub source: InitializedSource,
     pub source_service: S,
+    /// How the state is signed when it's written and verified when it's read.
+    pub signing: StateSigning,
 }
 
-impl ProjectStateStore for GitProjectStateStore<LocalSourceService> {
-    async fn create(&self, project: InitializedProject) -> Result<(), SkootError> {
+impl<S: SourceService + Send + Sync> GitProjectStateStore<S> {
+    /// Writes and signs the project's state in the source without committing it.
+    fn write(&self, project: &InitializedProject) -> Result<(), SkootError> {
         self.source_service.write_file(
             self.source.clone(),
             "./",
-            ".skootrs".to_string(),
-            serde_json::to_string(&project)?,
+            STATE_FILE.to_string(),
+            migration::to_string(project)?,
         )?;
+        signature::sign(&self.signing, Path::new(&self.source.path))?;
+        Ok(())
+    }
+
+    /// Updates the project's state the way `change_requests` says the changes of an update reach the repo.
+    /// This is for changes that only touch the state, like a relocated repo, so there's no pull request of
+    /// facet changes for the state to be committed to. A pull request of its own is opened for the state,
+    /// with `title` as its title and commit message, unless changes are pushed to the default branch.
+    /// Returns the URL of the pull request if one was opened.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the state can't be written or pushed, or the pull request can't be opened.
+    pub async fn propose<R: RepoService + Sync>(
+        &self,
+        project: InitializedProject,
+        repo_service: &R,
+        change_requests: &ChangeRequestConfig,
+        title: &str,
+    ) -> Result<Option<String>, SkootError> {
+        // Skootrs can only open pull requests on Github.
+        if change_requests.mode == ChangeMode::Push
+            || !matches!(project.repo, InitializedRepo::Github(_))
+        {
+            self.update(project).await?;
+            return Ok(None);
+        }
+        let _lock = self.source_service.lock(&self.source)?;
+        let expected_parent = self.source_service.head_commit(&self.source)?;
+        self.write(&project)?;
+        self.source_service
+            .verify_remote_head(&self.source, &expected_parent)?;
+        let change = title
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(str::to_ascii_lowercase)
+            .collect::<Vec<_>>()
+            .join("-");
+        let branch = change_requests.branch_name(&change, Utc::now().timestamp());
+        let base = self.source_service.commit_and_push_branch(
+            self.source.clone(),
+            &branch,
+            title.to_string(),
+        )?;
+        self.source_service.verify_push(&self.source)?;
+        let pull_request = repo_service
+            .open_pull_request(
+                &project.repo,
+                PullRequestParams {
+                    head: branch,
+                    base,
+                    title: title.to_string(),
+                    body: format!(
+                        "Skootrs updated the state of {}. Merge this pull request to record the change.",
+                        project.name
+                    ),
+                    auto_merge: change_requests.auto_merge,
+                },
+            )
+            .await;
+        // The next change has to start from the default branch, even if the pull request wasn't opened.
+        self.source_service.checkout_default_branch(&self.source)?;
+        pull_request.map(Some)
+    }
+}
+
+impl<S: SourceService + Send + Sync> ProjectStateStore for GitProjectStateStore<S> {
+    async fn create(&self, project: InitializedProject) -> Result<(), SkootError> {
+        let _lock = self.source_service.lock(&self.source)?;
+        let expected_parent = self.source_service.head_commit(&self.source)?;
+        self.write(&project)?;
+        self.source_service
+            .verify_remote_head(&self.source, &expected_parent)?;
         self.source_service.commit_and_push_changes(
             self.source.clone(),
             "Updated skootrs project state".to_string(),
         )?;
+        // A change proposed as a pull request leaves its branch checked out so the state is committed to it
+        // too. The next change has to start from the default branch again.
+        let proposed = self.source_service.checkout_default_branch(&self.source)?;
+        // Otherwise the mirror would always be a commit behind the canonical repo. A proposed change reaches
+        // the mirror with the first push after it's merged.
+        if let (false, Some(mirror)) = (proposed, &project.mirror) {
+            self.source_service.push_mirror(&self.source, mirror)?;
+        }
         Ok(())
     }
 
     async fn read(&self) -> Result<Option<InitializedProject>, SkootError> {
         let project = self
             .source_service
-            .read_file(&self.source, "./", ".skootrs".to_string())?;
-        Ok(Some(serde_json::from_str(&project).unwrap()))
+            .read_file(&self.source, "./", STATE_FILE.to_string())?;
+        let state_signature = self
+            .source_service
+            .read_file(&self.source, "./", STATE_SIGNATURE_FILE.to_string())
+            .ok();
+        // Tampered state is refused rather than acted on.
+        signature::ensure_verified(&signature::verify(
+            &self.signing,
+            &project,
+            state_signature.as_deref(),
+        )?)?;
+        Ok(Some(migration::from_str(&project)?))
     }
 
     async fn update(&self, project: InitializedProject) -> Result<(), SkootError> {
@@ -89,9 +205,103 @@ pub trait ProjectReferenceCache {
     ) -> impl std::future::Future<Output = Result<(), SkootError>> + Send;
 }
 
+/// How long a project's state is reused for if the config doesn't say.
+pub const DEFAULT_STATE_CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// A project's state as it was when it was fetched from its repo.
+#[derive(Clone, Debug)]
+pub struct CachedProject {
+    /// The project's state.
+    pub project: InitializedProject,
+    /// When the state was fetched.
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// A cached state as it's saved. The state is kept as JSON so it goes through the same migrations as the
+/// state in the repo when it's loaded.
+#[derive(Serialize, Deserialize)]
+struct SavedProject {
+    fetched_at: DateTime<Utc>,
+    project: serde_json::Value,
+}
+
+/// The contents of the cache file.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum SavedCache {
+    Projects {
+        urls: BTreeSet<String>,
+        #[serde(default)]
+        states: BTreeMap<String, SavedProject>,
+    },
+    /// Files saved before states were cached only have the repo URLs.
+    Urls(HashSet<String>),
+}
+
+/// What refreshing the tracked projects did.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProjectRefresh {
+    /// The projects whose state was fetched from their repos again.
+    pub refreshed: Vec<String>,
+    /// The projects that aren't tracked anymore because their repos don't exist, e.g. since they were deleted
+    /// or renamed.
+    pub evicted: Vec<String>,
+    /// The projects whose state couldn't be fetched, with the error. Their cached state is kept.
+    pub failed: BTreeMap<String, String>,
+    /// The refreshed projects whose repos were renamed or transferred, with the URLs they're at now. They're
+    /// still tracked under their old URLs until they're relocated.
+    #[serde(default)]
+    pub renamed: BTreeMap<String, String>,
+}
+
+/// Fetches a project's state from its repo, or returns `None` if the repo doesn't exist anymore. A repo
+/// that's there without a state file is an error rather than `None`, so it stays tracked.
+pub(crate) async fn fetch_state(
+    repo_service: &LocalRepoService,
+    repo_url: &str,
+) -> Result<Option<InitializedProject>, SkootError> {
+    let repo = InitializedRepo::try_from(repo_url.to_string())?;
+    match repo_service.fetch_file_content(&repo, STATE_FILE).await {
+        Ok(state) => migration::from_str(&state).map(Some),
+        Err(SkootError::NotFound(e)) => {
+            let params = InitializedRepoGetParams {
+                repo_url: repo_url.to_string(),
+            };
+            match repo_service.get(params).await {
+                Err(SkootError::NotFound(_)) => Ok(None),
+                _ => Err(SkootError::NotFound(e)),
+            }
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Returns the URL a Github repo was renamed or transferred to, or `None` if it's still at `repo_url`. Repos
+/// on other hosts are never found renamed, since only Github redirects to a moved repo.
+pub(crate) async fn renamed_to(
+    repo_service: &LocalRepoService,
+    repo_url: &str,
+) -> Result<Option<String>, SkootError> {
+    if !matches!(
+        InitializedRepo::try_from(repo_url.to_string())?,
+        InitializedRepo::Github(_)
+    ) {
+        return Ok(None);
+    }
+    let params = InitializedRepoGetParams {
+        repo_url: repo_url.to_string(),
+    };
+    let new_url = repo_service.get(params).await?.full_url();
+    Ok((!new_url.eq_ignore_ascii_case(repo_url)).then_some(new_url))
+}
+
 pub struct InMemoryProjectReferenceCache {
     pub save_path: String,
     pub cache: HashSet<String>,
+    /// The last fetched state of the tracked projects, by repo URL.
+    pub projects: HashMap<String, CachedProject>,
+    /// How long a project's state is reused for before it's fetched from its repo again.
+    pub ttl: Duration,
     pub local_source_service: LocalSourceService,
     pub local_repo_service: LocalRepoService,
     pub clone_path: String,
@@ -102,23 +312,34 @@ impl ProjectReferenceCache for InMemoryProjectReferenceCache {
         Ok(self.cache.clone())
     }
 
+    /// Returns a tracked project's cached state if it was fetched less than `ttl` ago, or fetches it from the
+    /// repo otherwise. A tracked project whose repo doesn't exist anymore stops being tracked.
     async fn get(&mut self, repo_url: String) -> Result<InitializedProject, SkootError> {
-        let repo = InitializedRepo::try_from(repo_url)?;
-        let project = self
-            .local_repo_service
-            .fetch_file_content(&repo, ".skootrs")
-            .await?;
-        let initialized_project: InitializedProject = serde_json::from_str(&project)?;
-        Ok(initialized_project)
+        if let Some(cached) = self.projects.get(&repo_url) {
+            let age = (Utc::now() - cached.fetched_at)
+                .to_std()
+                .unwrap_or_default();
+            if age < self.ttl {
+                return Ok(cached.project.clone());
+            }
+        }
+        self.refresh(&repo_url).await?.ok_or_else(|| {
+            SkootError::NotFound(format!(
+                "{repo_url} doesn't exist, it may have been deleted or renamed"
+            ))
+        })
     }
 
+    /// Tracks a project. Its cached state is dropped since it was probably just changed.
     async fn set(&mut self, repo_url: String) -> Result<(), SkootError> {
+        self.projects.remove(&repo_url);
         self.cache.insert(repo_url);
         self.save()?;
         Ok(())
     }
 
     async fn delete(&mut self, repo_url: String) -> Result<(), SkootError> {
+        self.projects.remove(&repo_url);
         self.cache.remove(&repo_url);
         self.save()?;
         Ok(())
@@ -132,8 +353,10 @@ impl InMemoryProjectReferenceCache {
         Self {
             save_path,
             cache: HashSet::new(),
-            local_source_service: LocalSourceService {},
-            local_repo_service: LocalRepoService {},
+            projects: HashMap::new(),
+            ttl: DEFAULT_STATE_CACHE_TTL,
+            local_source_service: LocalSourceService::default(),
+            local_repo_service: LocalRepoService::default(),
             clone_path: "/tmp".to_string(),
         }
     }
@@ -153,15 +376,38 @@ impl InMemoryProjectReferenceCache {
         })
     }
 
-    /// Load the cache from the file at `save_path`.
+    /// Load the cache from the file at `save_path`. Cached states that can't be read, e.g. because they were
+    /// saved by a newer version of Skootrs, are dropped and fetched again when they're needed.
     ///
     /// # Errors
     ///
     /// Returns an error if the cache can't be loaded.
     pub fn load(&mut self) -> Result<(), SkootError> {
-        let deserialized_cache: HashSet<String> =
+        let deserialized_cache: SavedCache =
             serde_json::from_str(&std::fs::read_to_string(&self.save_path)?)?;
-        self.cache = deserialized_cache;
+        match deserialized_cache {
+            SavedCache::Projects { urls, states } => {
+                self.cache = urls.into_iter().collect();
+                self.projects = states
+                    .into_iter()
+                    .filter(|(url, _)| self.cache.contains(url))
+                    .filter_map(|(url, saved)| {
+                        let project = migration::from_value(saved.project).ok()?;
+                        Some((
+                            url,
+                            CachedProject {
+                                project,
+                                fetched_at: saved.fetched_at,
+                            },
+                        ))
+                    })
+                    .collect();
+            }
+            SavedCache::Urls(urls) => {
+                self.cache = urls;
+                self.projects.clear();
+            }
+        }
         Ok(())
     }
 
@@ -171,8 +417,429 @@ impl InMemoryProjectReferenceCache {
     ///
     /// Returns an error if the cache can't be saved.
     pub fn save(&self) -> Result<(), SkootError> {
-        let serialized_cache = serde_json::to_string(&self.cache)?;
+        let states = self
+            .projects
+            .iter()
+            .map(|(url, cached)| {
+                Ok((
+                    url.clone(),
+                    SavedProject {
+                        fetched_at: cached.fetched_at,
+                        project: migration::to_value(&cached.project)?,
+                    },
+                ))
+            })
+            .collect::<Result<_, SkootError>>()?;
+        let serialized_cache = serde_json::to_string(&SavedCache::Projects {
+            urls: self.cache.iter().cloned().collect(),
+            states,
+        })?;
         std::fs::write(&self.save_path, serialized_cache)?;
         Ok(())
     }
+
+    /// Tracks a project that was just created or changed, caching its state as it is now.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cache can't be saved.
+    pub fn put(&mut self, project: &InitializedProject) -> Result<(), SkootError> {
+        let url = project.repo.full_url();
+        self.cache.insert(url.clone());
+        self.projects.insert(
+            url,
+            CachedProject {
+                project: project.clone(),
+                fetched_at: Utc::now(),
+            },
+        );
+        self.save()
+    }
+
+    /// Fetches a project's state from its repo, caching it if the project is tracked. Returns `None` if the repo
+    /// doesn't exist anymore, in which case the project stops being tracked.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the state can't be fetched or the cache can't be saved.
+    pub async fn refresh(
+        &mut self,
+        repo_url: &str,
+    ) -> Result<Option<InitializedProject>, SkootError> {
+        let Some(project) = fetch_state(&self.local_repo_service, repo_url).await? else {
+            if self.cache.remove(repo_url) {
+                self.projects.remove(repo_url);
+                self.save()?;
+            }
+            return Ok(None);
+        };
+        if self.cache.contains(repo_url) {
+            self.projects.insert(
+                repo_url.to_string(),
+                CachedProject {
+                    project: project.clone(),
+                    fetched_at: Utc::now(),
+                },
+            );
+            self.save()?;
+        }
+        Ok(Some(project))
+    }
+}
+
+/// The `ConfiguredProjectReferenceCache` enum is the `ProjectReferenceCache` backend selected by the
+/// `statestore` config.
+pub enum ConfiguredProjectReferenceCache {
+    /// A JSON file of repo URLs and cached states.
+    Json(InMemoryProjectReferenceCache),
+    /// A `SurrealDB` database.
+    #[cfg(feature = "surrealdb")]
+    Surreal(surreal::SurrealProjectReferenceCache),
+}
+
+impl ConfiguredProjectReferenceCache {
+    /// Opens the backend selected by the `statestore` config. Project state is fetched from the repos with
+    /// the Github credential in the config.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backend can't be opened, or if it's a `SurrealDB` database and Skootrs was
+    /// built without the `surrealdb` feature.
+    pub async fn open(config: &Config) -> Result<Self, SkootError> {
+        let local_repo_service = LocalRepoService::from_config(config);
+        match &config.statestore {
+            StateStoreConfig::Json { path, ttl_secs } => {
+                let mut cache = InMemoryProjectReferenceCache::load_or_create(path)?;
+                cache.local_repo_service = local_repo_service;
+                cache.ttl = Duration::from_secs(*ttl_secs);
+                Ok(Self::Json(cache))
+            }
+            #[cfg(feature = "surrealdb")]
+            StateStoreConfig::Surreal {
+                endpoint,
+                namespace,
+                database,
+                username,
+                password,
+            } => {
+                let credentials = username.as_deref().zip(password.as_deref());
+                let mut cache = surreal::SurrealProjectReferenceCache::connect(
+                    endpoint,
+                    namespace,
+                    database,
+                    credentials,
+                )
+                .await?;
+                cache.local_repo_service = local_repo_service;
+                Ok(Self::Surreal(cache))
+            }
+            #[cfg(not(feature = "surrealdb"))]
+            StateStoreConfig::Surreal { .. } => {
+                Err("Skootrs was built without the surrealdb feature".into())
+            }
+        }
+    }
+
+    /// Fetches a project's state from its repo, or reads it from `local_source` if a local clone of the repo
+    /// is given, and verifies its signature. Unlike `get`, a missing or invalid signature is reported in the
+    /// result rather than hidden.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the state can't be fetched or parsed, or its signature can't be checked.
+    pub async fn get_signed(
+        &self,
+        repo_url: String,
+        signing: &StateSigning,
+        local_source: Option<&InitializedSource>,
+    ) -> Result<SignedProject, SkootError> {
+        let (state, state_signature) = if let Some(source) = local_source {
+            let source_service = LocalSourceService::default();
+            (
+                source_service.read_file(source, "./", STATE_FILE.to_string())?,
+                source_service
+                    .read_file(source, "./", STATE_SIGNATURE_FILE.to_string())
+                    .ok(),
+            )
+        } else {
+            let repo_service = self.repo_service();
+            let repo = InitializedRepo::try_from(repo_url)?;
+            (
+                repo_service.fetch_file_content(&repo, STATE_FILE).await?,
+                repo_service
+                    .fetch_file_content(&repo, STATE_SIGNATURE_FILE)
+                    .await
+                    .ok(),
+            )
+        };
+        Ok(SignedProject {
+            state_signature: signature::verify(signing, &state, state_signature.as_deref())?,
+            project: migration::from_str(&state)?,
+        })
+    }
+
+    /// Searches the tracked projects for the ones matching every word of a query, by their repo URL, name,
+    /// organization, ecosystems and facet types. Projects are matched by their cached or stored state, and by
+    /// their repo URL alone if the JSON file has no state cached for them. `refresh` fetches every project's
+    /// state from its repo first, see `refresh`, and a project whose state can't be fetched is listed with
+    /// the error.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tracked projects can't be read.
+    pub async fn search(
+        &mut self,
+        query: &str,
+        refresh: bool,
+    ) -> Result<Vec<ProjectSummary>, SkootError> {
+        let mut errors = if refresh {
+            self.refresh(None).await?.failed
+        } else {
+            BTreeMap::new()
+        };
+        let mut projects: Vec<ProjectSummary> = match self {
+            Self::Json(cache) => cache
+                .cache
+                .iter()
+                .map(|url| {
+                    cache.projects.get(url).map_or_else(
+                        || ProjectSummary::from_url(url.clone()),
+                        |cached| ProjectSummary::from(&cached.project),
+                    )
+                })
+                .collect(),
+            #[cfg(feature = "surrealdb")]
+            Self::Surreal(cache) => cache
+                .select_all()
+                .await?
+                .iter()
+                .map(ProjectSummary::from)
+                .collect(),
+        };
+        for project in &mut projects {
+            project.error = errors.remove(&project.url);
+        }
+        projects.retain(|project| project.matches(query));
+        projects.sort_by(|a, b| a.url.cmp(&b.url));
+        Ok(projects)
+    }
+
+    /// Fetches the state of a tracked project, or of every tracked project if `repo_url` isn't given, from
+    /// their repos again, replacing the cached or stored copies. Projects whose repos don't exist anymore,
+    /// e.g. because they were deleted, stop being tracked. Projects whose Github repos were renamed or
+    /// transferred are reported with their new URLs.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `repo_url` isn't tracked or the tracked projects can't be read. Projects that can't
+    /// be refreshed are recorded in the result instead.
+    pub async fn refresh(
+        &mut self,
+        repo_url: Option<String>,
+    ) -> Result<ProjectRefresh, SkootError> {
+        let tracked = self.list().await?;
+        let urls = match repo_url {
+            Some(url) if !tracked.contains(&url) => {
+                return Err(SkootError::NotFound(format!("{url} isn't tracked")));
+            }
+            Some(url) => vec![url],
+            None => tracked
+                .into_iter()
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect(),
+        };
+        let mut refresh = ProjectRefresh::default();
+        for url in urls {
+            let refreshed = match self {
+                Self::Json(cache) => cache.refresh(&url).await.map(|project| project.is_some()),
+                #[cfg(feature = "surrealdb")]
+                Self::Surreal(cache) => cache.refresh(&url).await,
+            };
+            match refreshed {
+                Ok(true) => {
+                    if let Ok(Some(new_url)) = renamed_to(self.repo_service(), &url).await {
+                        refresh.renamed.insert(url.clone(), new_url);
+                    }
+                    refresh.refreshed.push(url);
+                }
+                Ok(false) => refresh.evicted.push(url),
+                Err(e) => {
+                    refresh.failed.insert(url, e.to_string());
+                }
+            }
+        }
+        Ok(refresh)
+    }
+
+    /// Records a project under the new URL of its renamed or transferred repo, forgetting its old URL.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the project can't be recorded or its old URL can't be forgotten.
+    pub async fn relocate(
+        &mut self,
+        old_url: String,
+        project: &InitializedProject,
+    ) -> Result<(), SkootError> {
+        self.delete(old_url).await?;
+        self.track(project).await
+    }
+
+    /// Returns the repo service project states are fetched with.
+    const fn repo_service(&self) -> &LocalRepoService {
+        match self {
+            Self::Json(cache) => &cache.local_repo_service,
+            #[cfg(feature = "surrealdb")]
+            Self::Surreal(cache) => &cache.local_repo_service,
+        }
+    }
+
+    /// Records a project that was just created or changed, along with its state so it doesn't have to be
+    /// fetched back from the repo.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the project can't be recorded.
+    pub async fn track(&mut self, project: &InitializedProject) -> Result<(), SkootError> {
+        match self {
+            Self::Json(cache) => cache.put(project),
+            #[cfg(feature = "surrealdb")]
+            Self::Surreal(cache) => cache.put(project).await,
+        }
+    }
+}
+
+impl ProjectReferenceCache for ConfiguredProjectReferenceCache {
+    async fn list(&self) -> Result<HashSet<String>, SkootError> {
+        match self {
+            Self::Json(cache) => cache.list().await,
+            #[cfg(feature = "surrealdb")]
+            Self::Surreal(cache) => cache.list().await,
+        }
+    }
+
+    async fn get(&mut self, repo_url: String) -> Result<InitializedProject, SkootError> {
+        match self {
+            Self::Json(cache) => cache.get(repo_url).await,
+            #[cfg(feature = "surrealdb")]
+            Self::Surreal(cache) => cache.get(repo_url).await,
+        }
+    }
+
+    async fn set(&mut self, repo_url: String) -> Result<(), SkootError> {
+        match self {
+            Self::Json(cache) => cache.set(repo_url).await,
+            #[cfg(feature = "surrealdb")]
+            Self::Surreal(cache) => cache.set(repo_url).await,
+        }
+    }
+
+    async fn delete(&mut self, repo_url: String) -> Result<(), SkootError> {
+        match self {
+            Self::Json(cache) => cache.delete(repo_url).await,
+            #[cfg(feature = "surrealdb")]
+            Self::Surreal(cache) => cache.delete(repo_url).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use skootrs_model::skootrs::{
+        GithubUser, InitializedEcosystem, InitializedGithubRepo, InitializedGo, InitializedModule,
+    };
+
+    use super::*;
+
+    fn project(name: &str) -> InitializedProject {
+        InitializedProject {
+            repo: InitializedRepo::Github(InitializedGithubRepo {
+                name: name.to_string(),
+                organization: GithubUser::Organization("testorg".to_string()),
+            }),
+            mirror: None,
+            modules: vec![InitializedModule {
+                path: ".".to_string(),
+                ecosystem: InitializedEcosystem::Go(InitializedGo {
+                    name: name.to_string(),
+                    host: "github.com/testorg".to_string(),
+                }),
+            }],
+            source: InitializedSource {
+                path: format!("/tmp/{name}"),
+            },
+            facets: HashMap::new(),
+            name: name.to_string(),
+            skootrs_version: None,
+            created_at: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cached_states() {
+        let path = std::env::temp_dir().join(format!("skootrs-states-{}.json", std::process::id()));
+        let save_path = path.to_string_lossy().to_string();
+        // A file saved before states were cached.
+        std::fs::write(&path, r#"["https://github.com/testorg/old"]"#).unwrap();
+        let mut cache = InMemoryProjectReferenceCache::load_or_create(&save_path).unwrap();
+        assert_eq!(cache.cache.len(), 1);
+        assert_eq!(cache.projects.len(), 0);
+
+        let payments = project("payments");
+        cache.put(&payments).unwrap();
+        let mut cache = InMemoryProjectReferenceCache::load_or_create(&save_path).unwrap();
+        assert_eq!(cache.cache.len(), 2);
+        // The state is cached, so it isn't fetched from the repo.
+        let url = payments.repo.full_url();
+        assert_eq!(cache.get(url.clone()).await.unwrap().name, "payments");
+
+        let mut configured = ConfiguredProjectReferenceCache::Json(cache);
+        let found = configured.search("go", false).await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].name.as_deref(), Some("payments"));
+
+        let ConfiguredProjectReferenceCache::Json(mut cache) = configured else {
+            unreachable!()
+        };
+        cache.set(url.clone()).await.unwrap();
+        assert!(!cache.projects.contains_key(&url));
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn test_search() {
+        let path = std::env::temp_dir().join(format!("skootrs-cache-{}.json", std::process::id()));
+        let mut cache = InMemoryProjectReferenceCache::new(path.to_string_lossy().to_string());
+        for url in [
+            "https://github.com/my-org/payments-api",
+            "https://github.com/my-org/search",
+            "https://github.com/other-org/payments",
+        ] {
+            cache.set(url.to_string()).await.unwrap();
+        }
+        let mut cache = ConfiguredProjectReferenceCache::Json(cache);
+
+        let urls = |projects: Vec<ProjectSummary>| {
+            projects
+                .into_iter()
+                .map(|project| project.url)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            urls(cache.search("payments", false).await.unwrap()),
+            vec![
+                "https://github.com/my-org/payments-api",
+                "https://github.com/other-org/payments"
+            ]
+        );
+        assert_eq!(
+            urls(cache.search("MY-ORG payments", false).await.unwrap()),
+            vec!["https://github.com/my-org/payments-api"]
+        );
+        assert_eq!(cache.search("", false).await.unwrap().len(), 3);
+        let _ = std::fs::remove_file(path);
+    }
 }
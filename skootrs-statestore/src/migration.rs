@@ -0,0 +1,227 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `.skootrs` state file is the project's state with a `schema_version` next to its fields. Files written
+//! by older versions of Skootrs are upgraded to the current schema one version at a time when they're read,
+//! and written back with the current version the next time the project's state is saved.
+//!
+//! Changing the `InitializedProject` model in a way older files can't be deserialized into needs a new
+//! schema version and a migration that upgrades the previous version's JSON to it.
+
+use serde_json::Value;
+use skootrs_model::skootrs::{InitializedProject, SkootError, ROOT_MODULE_PATH};
+
+/// The field the schema version of a state file is recorded in.
+const SCHEMA_VERSION_FIELD: &str = "schema_version";
+
+/// The schema version of state files written before the schema was versioned.
+const UNVERSIONED_SCHEMA_VERSION: u64 = 1;
+
+/// The schema version of the state files this version of Skootrs writes.
+pub const CURRENT_SCHEMA_VERSION: u64 = 3;
+
+/// Upgrades the JSON of a state file from one schema version to the next.
+type Migration = fn(Value) -> Result<Value, SkootError>;
+
+/// The migrations in order, i.e. the first one upgrades version 1 to 2. There's one for every version before
+/// `CURRENT_SCHEMA_VERSION`.
+const MIGRATIONS: [Migration; 2] = [v1_to_v2, v2_to_v3];
+
+/// Version 2 only added the schema version, so version 1 files are already valid version 2 files.
+// Every migration has the same signature so they can be chained.
+#[allow(clippy::unnecessary_wraps)]
+const fn v1_to_v2(state: Value) -> Result<Value, SkootError> {
+    Ok(state)
+}
+
+/// Version 3 replaced the project's single `ecosystem` with a list of `modules`, so the ecosystem becomes a
+/// module at the root of the source.
+fn v2_to_v3(mut state: Value) -> Result<Value, SkootError> {
+    let fields = state
+        .as_object_mut()
+        .ok_or_else(|| SkootError::from("The project state isn't a JSON object"))?;
+    let ecosystem = fields
+        .remove("ecosystem")
+        .ok_or_else(|| SkootError::from("The project state has no ecosystem"))?;
+    fields.insert(
+        "modules".to_string(),
+        serde_json::json!([{ "path": ROOT_MODULE_PATH, "ecosystem": ecosystem }]),
+    );
+    Ok(state)
+}
+
+/// Parses the content of a state file, upgrading it to the current schema first if it's older.
+///
+/// # Errors
+///
+/// Returns an error if the content isn't JSON, was written by a newer version of Skootrs, can't be upgraded,
+/// or isn't a valid project once upgraded.
+pub fn from_str(content: &str) -> Result<InitializedProject, SkootError> {
+    from_value(serde_json::from_str(content)?)
+}
+
+/// Deserializes the JSON of a state file, upgrading it to the current schema first if it's older.
+///
+/// # Errors
+///
+/// Returns an error if the JSON was written by a newer version of Skootrs, can't be upgraded, or isn't a
+/// valid project once upgraded.
+pub fn from_value(mut state: Value) -> Result<InitializedProject, SkootError> {
+    let fields = state
+        .as_object_mut()
+        .ok_or_else(|| SkootError::from("The project state isn't a JSON object"))?;
+    let version = match fields.remove(SCHEMA_VERSION_FIELD) {
+        None => UNVERSIONED_SCHEMA_VERSION,
+        Some(version) => version.as_u64().ok_or_else(|| {
+            SkootError::from(format!(
+                "The project state's schema version {version} isn't a number"
+            ))
+        })?,
+    };
+    if version > CURRENT_SCHEMA_VERSION {
+        return Err(format!(
+            "The project state has schema version {version}, which was written by a newer version of \
+             Skootrs that supports up to version {CURRENT_SCHEMA_VERSION}"
+        )
+        .into());
+    }
+    let migrations = MIGRATIONS
+        .iter()
+        .zip(UNVERSIONED_SCHEMA_VERSION..)
+        .filter(|(_, from)| *from >= version);
+    for (migration, from) in migrations {
+        state = migration(state).map_err(|e| {
+            format!(
+                "Failed to upgrade the project state from schema version {from} to {}: {e}",
+                from + 1
+            )
+        })?;
+    }
+    Ok(serde_json::from_value(state)?)
+}
+
+/// Serializes a project as a state file with the current schema version.
+///
+/// # Errors
+///
+/// Returns an error if the project can't be serialized.
+pub fn to_value(project: &InitializedProject) -> Result<Value, SkootError> {
+    let mut state = serde_json::to_value(project)?;
+    if let Some(fields) = state.as_object_mut() {
+        fields.insert(
+            SCHEMA_VERSION_FIELD.to_string(),
+            CURRENT_SCHEMA_VERSION.into(),
+        );
+    }
+    Ok(state)
+}
+
+/// Serializes a project as the content of a state file with the current schema version.
+///
+/// # Errors
+///
+/// Returns an error if the project can't be serialized.
+pub fn to_string(project: &InitializedProject) -> Result<String, SkootError> {
+    Ok(serde_json::to_string(&to_value(project)?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use std::collections::HashMap;
+
+    use skootrs_model::skootrs::{
+        GithubUser, InitializedEcosystem, InitializedGithubRepo, InitializedGo, InitializedModule,
+        InitializedRepo, InitializedSource,
+    };
+
+    use super::*;
+
+    fn project() -> InitializedProject {
+        InitializedProject {
+            repo: InitializedRepo::Github(InitializedGithubRepo {
+                name: "test".to_string(),
+                organization: GithubUser::Organization("testorg".to_string()),
+            }),
+            mirror: None,
+            modules: vec![InitializedModule {
+                path: ROOT_MODULE_PATH.to_string(),
+                ecosystem: InitializedEcosystem::Go(InitializedGo {
+                    name: "test".to_string(),
+                    host: "github.com/testorg".to_string(),
+                }),
+            }],
+            source: InitializedSource {
+                path: "/tmp/test".to_string(),
+            },
+            facets: HashMap::new(),
+            name: "test".to_string(),
+            skootrs_version: None,
+            created_at: None,
+        }
+    }
+
+    #[test]
+    fn test_migrations_cover_every_version() {
+        assert_eq!(
+            MIGRATIONS.len() as u64,
+            CURRENT_SCHEMA_VERSION - UNVERSIONED_SCHEMA_VERSION
+        );
+    }
+
+    /// Returns the project as it was written before it could have several modules.
+    fn single_ecosystem_project() -> Value {
+        let mut state = serde_json::to_value(project()).unwrap();
+        let fields = state.as_object_mut().unwrap();
+        let module = fields.remove("modules").unwrap()[0].clone();
+        fields.insert("ecosystem".to_string(), module["ecosystem"].clone());
+        state
+    }
+
+    #[test]
+    fn test_unversioned_state_is_upgraded() {
+        let unversioned = serde_json::to_string(&single_ecosystem_project()).unwrap();
+        assert!(!unversioned.contains(SCHEMA_VERSION_FIELD));
+        let upgraded = from_str(&unversioned).unwrap();
+        assert_eq!(upgraded.name, "test");
+
+        let written = to_value(&upgraded).unwrap();
+        assert_eq!(written[SCHEMA_VERSION_FIELD], CURRENT_SCHEMA_VERSION);
+        assert_eq!(from_value(written).unwrap().name, "test");
+    }
+
+    #[test]
+    fn test_single_ecosystem_becomes_root_module() {
+        let mut v2 = single_ecosystem_project();
+        v2[SCHEMA_VERSION_FIELD] = 2.into();
+        let upgraded = from_value(v2).unwrap();
+        assert_eq!(upgraded.modules.len(), 1);
+        assert!(upgraded.modules[0].is_root());
+        assert_eq!(upgraded.modules[0].ecosystem.name(), "Go");
+    }
+
+    #[test]
+    fn test_unsupported_state() {
+        let mut newer = to_value(&project()).unwrap();
+        newer[SCHEMA_VERSION_FIELD] = (CURRENT_SCHEMA_VERSION + 1).into();
+        let error = from_value(newer).unwrap_err().to_string();
+        assert!(error.contains("newer version of Skootrs"));
+
+        let mut invalid = to_value(&project()).unwrap();
+        invalid[SCHEMA_VERSION_FIELD] = "two".into();
+        assert!(from_value(invalid).is_err());
+        assert!(from_str("[]").is_err());
+    }
+}
@@ -0,0 +1,175 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `.skootrs` state file can be signed so external parties auditing a project can tell it wasn't
+//! tampered with. The state is signed with `cosign sign-blob` every time it's written, and the signature
+//! bundle is committed next to it in `.skootrs.sig`.
+
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use skootrs_lib::service::command::{CommandError, CommandRunner};
+use skootrs_model::skootrs::{SkootError, StateSignatureStatus, StateSigning};
+
+/// The name of the state file.
+pub const STATE_FILE: &str = ".skootrs";
+
+/// The name of the file the signature bundle of the state file is kept in.
+pub const STATE_SIGNATURE_FILE: &str = ".skootrs.sig";
+
+/// Signs the state file in a project's source and writes the signature bundle next to it. Returns false
+/// without signing anything if state signing isn't configured.
+///
+/// # Errors
+///
+/// Returns an error if cosign can't be run or fails to sign the state, e.g. because the key can't be read.
+pub fn sign(signing: &StateSigning, source_path: &Path) -> Result<bool, SkootError> {
+    let mut command = Command::new("cosign");
+    command.args(["sign-blob", "--yes"]);
+    match signing {
+        StateSigning::None => return Ok(false),
+        StateSigning::Sigstore { .. } => {}
+        StateSigning::Key { key, .. } => {
+            command.args(["--key", key.as_str()]);
+        }
+    }
+    command
+        .arg("--bundle")
+        .arg(source_path.join(STATE_SIGNATURE_FILE))
+        .arg(source_path.join(STATE_FILE))
+        .run_checked()
+        .map_err(|e| format!("Failed to sign the project state: {e}"))?;
+    Ok(true)
+}
+
+/// Verifies the content of a state file against its signature bundle, or `None` if it has none.
+///
+/// # Errors
+///
+/// Returns an error if cosign can't be run. A signature cosign rejects is `StateSignatureStatus::Invalid`
+/// rather than an error.
+pub fn verify(
+    signing: &StateSigning,
+    state: &str,
+    signature: Option<&str>,
+) -> Result<StateSignatureStatus, SkootError> {
+    let key_args = match signing {
+        StateSigning::None => return Ok(StateSignatureStatus::Unchecked),
+        StateSigning::Sigstore { identity, issuer } => vec![
+            "--certificate-identity",
+            identity.as_str(),
+            "--certificate-oidc-issuer",
+            issuer.as_str(),
+        ],
+        StateSigning::Key { public_key, .. } => vec!["--key", public_key.as_str()],
+    };
+    let Some(signature) = signature else {
+        return Ok(StateSignatureStatus::Missing);
+    };
+    // cosign verifies files, so the state and signature are written to a directory of their own. They can't
+    // be verified in place since the state may have been fetched from the repo rather than read from a clone.
+    let dir = ScratchDir::new()?;
+    std::fs::write(dir.path.join(STATE_FILE), state)?;
+    std::fs::write(dir.path.join(STATE_SIGNATURE_FILE), signature)?;
+
+    let mut command = Command::new("cosign");
+    command.arg("verify-blob").args(key_args);
+    let result = command
+        .arg("--bundle")
+        .arg(dir.path.join(STATE_SIGNATURE_FILE))
+        .arg(dir.path.join(STATE_FILE))
+        .run_checked();
+    match result {
+        Ok(_) => Ok(StateSignatureStatus::Verified),
+        Err(e) => match e.downcast_ref::<CommandError>() {
+            Some(error) => Ok(StateSignatureStatus::Invalid {
+                reason: error.stderr.clone(),
+            }),
+            None => Err(e),
+        },
+    }
+}
+
+/// Returns an error if a state file's signature shows it was tampered with or never signed. Unsigned state
+/// is fine if state signing isn't configured.
+///
+/// # Errors
+///
+/// Returns an error if the signature is missing or invalid.
+pub fn ensure_verified(status: &StateSignatureStatus) -> Result<(), SkootError> {
+    match status {
+        StateSignatureStatus::Unchecked | StateSignatureStatus::Verified => Ok(()),
+        StateSignatureStatus::Missing => {
+            Err(format!("The project state has no signature in {STATE_SIGNATURE_FILE}").into())
+        }
+        StateSignatureStatus::Invalid { reason } => {
+            Err(format!("The project state's signature is invalid: {reason}").into())
+        }
+    }
+}
+
+/// A temporary directory that's removed when it's dropped.
+struct ScratchDir {
+    path: PathBuf,
+}
+
+impl ScratchDir {
+    fn new() -> Result<Self, SkootError> {
+        // Concurrent verifications in the same process each get a directory of their own.
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "skootrs-state-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::create_dir_all(&path)?;
+        Ok(Self { path })
+    }
+}
+
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    #[test]
+    fn test_unsigned_state() {
+        assert!(!sign(&StateSigning::None, Path::new("/nonexistent")).unwrap());
+        let status = verify(&StateSigning::None, "{}", None).unwrap();
+        assert_eq!(status, StateSignatureStatus::Unchecked);
+        assert!(ensure_verified(&status).is_ok());
+
+        let signing = StateSigning::Key {
+            key: "cosign.key".to_string(),
+            public_key: "cosign.pub".to_string(),
+        };
+        let status = verify(&signing, "{}", None).unwrap();
+        assert_eq!(status, StateSignatureStatus::Missing);
+        assert!(ensure_verified(&status).is_err());
+        assert!(ensure_verified(&StateSignatureStatus::Invalid {
+            reason: "none of the expected identities matched".to_string()
+        })
+        .is_err());
+    }
+}
@@ -0,0 +1,423 @@
+//
+// Copyright 2024 The Skootrs Authors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A `SurrealDB` backed statestore. Each project is a record in the `project` table keyed by its repo URL,
+//! with a copy of the project's state and the fields it's queried by. The database can be a local file or
+//! a remote server, which lets a daemon keep track of many projects without fetching each from its repo.
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use skootrs_lib::service::repo::{LocalRepoService, RepoService};
+use skootrs_model::skootrs::{
+    facet::SupportedFacetType, FacetMapKey, InitializedProject, InitializedRepo, SkootError,
+};
+use surrealdb::{
+    engine::any::{self, Any},
+    opt::auth::Root,
+    Surreal,
+};
+
+use crate::{fetch_state, migration, signature, ProjectReferenceCache, ProjectStateStore};
+
+/// The table projects are stored in.
+const PROJECT_TABLE: &str = "project";
+
+/// A project as it's stored in the database. The project's state is kept as JSON so the database doesn't
+/// have to understand the model, with the fields that are queried on pulled out next to it.
+#[derive(Serialize, Deserialize, Debug)]
+struct ProjectRecord {
+    repo_url: String,
+    name: String,
+    ecosystems: Vec<String>,
+    facets: Vec<String>,
+    project: serde_json::Value,
+}
+
+impl TryFrom<&InitializedProject> for ProjectRecord {
+    type Error = SkootError;
+
+    fn try_from(project: &InitializedProject) -> Result<Self, Self::Error> {
+        Ok(Self {
+            repo_url: project.repo.full_url(),
+            name: project.name.clone(),
+            ecosystems: project
+                .modules
+                .iter()
+                .map(|module| module.ecosystem.name().to_string())
+                .collect(),
+            facets: project.facets.keys().map(ToString::to_string).collect(),
+            project: migration::to_value(project)?,
+        })
+    }
+}
+
+impl TryFrom<ProjectRecord> for InitializedProject {
+    type Error = SkootError;
+
+    fn try_from(record: ProjectRecord) -> Result<Self, Self::Error> {
+        migration::from_value(record.project)
+    }
+}
+
+/// The `SurrealProjectReferenceCache` struct keeps track of every project Skootrs manages in a `SurrealDB`
+/// database, along with a copy of each project's state.
+#[derive(Debug)]
+pub struct SurrealProjectReferenceCache {
+    pub db: Surreal<Any>,
+    pub local_repo_service: LocalRepoService,
+}
+
+impl SurrealProjectReferenceCache {
+    /// Connects to the database at `endpoint`, e.g. `surrealkv://./skootrs.db` for a local file or
+    /// `ws://db:8000` for a remote server, and signs in as the root user if credentials are given.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database can't be connected to or signed in to.
+    pub async fn connect(
+        endpoint: &str,
+        namespace: &str,
+        database: &str,
+        credentials: Option<(&str, &str)>,
+    ) -> Result<Self, SkootError> {
+        let db = any::connect(endpoint)
+            .await
+            .map_err(|e| format!("Failed to connect to SurrealDB at {endpoint}: {e}"))?;
+        if let Some((username, password)) = credentials {
+            db.signin(Root { username, password })
+                .await
+                .map_err(SkootError::other)?;
+        }
+        db.use_ns(namespace)
+            .use_db(database)
+            .await
+            .map_err(SkootError::other)?;
+        Ok(Self {
+            db,
+            local_repo_service: LocalRepoService::default(),
+        })
+    }
+
+    /// Returns the state store for a single project in the database.
+    #[must_use]
+    pub fn state_store(&self, repo: &InitializedRepo) -> SurrealProjectStateStore {
+        SurrealProjectStateStore {
+            db: self.db.clone(),
+            repo_url: repo.full_url(),
+        }
+    }
+
+    /// Stores a project, replacing the stored copy if there is one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the project can't be stored.
+    pub async fn put(&self, project: &InitializedProject) -> Result<(), SkootError> {
+        let record = ProjectRecord::try_from(project)?;
+        let _: Option<ProjectRecord> = self
+            .db
+            .upsert((PROJECT_TABLE, record.repo_url.clone()))
+            .content(record)
+            .await
+            .map_err(SkootError::other)?;
+        Ok(())
+    }
+
+    /// Fetches a project's state from its repo and stores it. Returns `false` if the repo doesn't exist
+    /// anymore, in which case the project is deleted from the database.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the state can't be fetched, stored or deleted.
+    pub async fn refresh(&mut self, repo_url: &str) -> Result<bool, SkootError> {
+        if let Some(project) = fetch_state(&self.local_repo_service, repo_url).await? {
+            self.put(&project).await?;
+            Ok(true)
+        } else {
+            self.delete(repo_url.to_string()).await?;
+            Ok(false)
+        }
+    }
+
+    /// Returns the stored copy of every project.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the projects can't be read.
+    pub async fn select_all(&self) -> Result<Vec<InitializedProject>, SkootError> {
+        let records: Vec<ProjectRecord> = self
+            .db
+            .select(PROJECT_TABLE)
+            .await
+            .map_err(SkootError::other)?;
+        records.into_iter().map(TryInto::try_into).collect()
+    }
+
+    /// Returns the projects with a module in an ecosystem, e.g. `Go`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the projects can't be queried.
+    pub async fn projects_in_ecosystem(
+        &self,
+        ecosystem: &str,
+    ) -> Result<Vec<InitializedProject>, SkootError> {
+        self.query(
+            "SELECT * FROM type::table($table) WHERE ecosystems CONTAINS $value",
+            ecosystem.to_string(),
+        )
+        .await
+    }
+
+    /// Returns the projects that don't have a facet, e.g. to find the projects a new facet still has to be
+    /// rolled out to.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the projects can't be queried.
+    pub async fn projects_missing_facet(
+        &self,
+        facet_type: &SupportedFacetType,
+    ) -> Result<Vec<InitializedProject>, SkootError> {
+        self.query(
+            "SELECT * FROM type::table($table) WHERE facets CONTAINSNOT $value",
+            FacetMapKey::Type(facet_type.clone()).to_string(),
+        )
+        .await
+    }
+
+    async fn query(
+        &self,
+        query: &str,
+        value: String,
+    ) -> Result<Vec<InitializedProject>, SkootError> {
+        let records: Vec<ProjectRecord> = self
+            .db
+            .query(query)
+            .bind(("table", PROJECT_TABLE))
+            .bind(("value", value))
+            .await
+            .map_err(SkootError::other)?
+            .take(0)
+            .map_err(SkootError::other)?;
+        records.into_iter().map(TryInto::try_into).collect()
+    }
+}
+
+impl ProjectReferenceCache for SurrealProjectReferenceCache {
+    async fn list(&self) -> Result<HashSet<String>, SkootError> {
+        let records: Vec<ProjectRecord> = self
+            .db
+            .select(PROJECT_TABLE)
+            .await
+            .map_err(SkootError::other)?;
+        Ok(records.into_iter().map(|r| r.repo_url).collect())
+    }
+
+    async fn get(&mut self, repo_url: String) -> Result<InitializedProject, SkootError> {
+        let record: Option<ProjectRecord> = self
+            .db
+            .select((PROJECT_TABLE, repo_url.clone()))
+            .await
+            .map_err(SkootError::other)?;
+        record
+            .ok_or_else(|| {
+                SkootError::NotFound(format!("Project {repo_url} isn't in the statestore"))
+            })?
+            .try_into()
+    }
+
+    async fn set(&mut self, repo_url: String) -> Result<(), SkootError> {
+        let repo = InitializedRepo::try_from(repo_url)?;
+        let project = self
+            .local_repo_service
+            .fetch_file_content(&repo, signature::STATE_FILE)
+            .await?;
+        self.put(&migration::from_str(&project)?).await
+    }
+
+    async fn delete(&mut self, repo_url: String) -> Result<(), SkootError> {
+        let _: Option<ProjectRecord> = self
+            .db
+            .delete((PROJECT_TABLE, repo_url))
+            .await
+            .map_err(SkootError::other)?;
+        Ok(())
+    }
+}
+
+/// The `SurrealProjectStateStore` struct stores the state of a single project in a `SurrealDB` database.
+#[derive(Clone, Debug)]
+pub struct SurrealProjectStateStore {
+    pub db: Surreal<Any>,
+    pub repo_url: String,
+}
+
+impl ProjectStateStore for SurrealProjectStateStore {
+    async fn create(&self, project: InitializedProject) -> Result<(), SkootError> {
+        let record = ProjectRecord::try_from(&project)?;
+        let _: Option<ProjectRecord> = self
+            .db
+            .create((PROJECT_TABLE, self.repo_url.clone()))
+            .content(record)
+            .await
+            .map_err(SkootError::other)?;
+        Ok(())
+    }
+
+    async fn read(&self) -> Result<Option<InitializedProject>, SkootError> {
+        let record: Option<ProjectRecord> = self
+            .db
+            .select((PROJECT_TABLE, self.repo_url.clone()))
+            .await
+            .map_err(SkootError::other)?;
+        record.map(TryInto::try_into).transpose()
+    }
+
+    async fn update(&self, project: InitializedProject) -> Result<(), SkootError> {
+        let record = ProjectRecord::try_from(&project)?;
+        let _: Option<ProjectRecord> = self
+            .db
+            .upsert((PROJECT_TABLE, self.repo_url.clone()))
+            .content(record)
+            .await
+            .map_err(SkootError::other)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use std::collections::HashMap;
+
+    use skootrs_model::skootrs::{
+        facet::{InitializedFacet, SourceBundleFacet},
+        GithubUser, InitializedEcosystem, InitializedGithubRepo, InitializedGo, InitializedMaven,
+        InitializedModule, InitializedSource,
+    };
+
+    use super::*;
+
+    fn project(
+        name: &str,
+        ecosystem: InitializedEcosystem,
+        facets: &[SupportedFacetType],
+    ) -> InitializedProject {
+        InitializedProject {
+            repo: InitializedRepo::Github(InitializedGithubRepo {
+                name: name.to_string(),
+                organization: GithubUser::Organization("testorg".to_string()),
+            }),
+            mirror: None,
+            modules: vec![InitializedModule {
+                path: ".".to_string(),
+                ecosystem,
+            }],
+            source: InitializedSource {
+                path: format!("/tmp/{name}"),
+            },
+            facets: facets
+                .iter()
+                .map(|facet_type| {
+                    (
+                        FacetMapKey::Type(facet_type.clone()),
+                        InitializedFacet::SourceBundle(SourceBundleFacet {
+                            source_files: None,
+                            facet_type: facet_type.clone(),
+                            source_files_content: None,
+                            labels: vec![],
+                            packages: vec![],
+                            template_version: None,
+                            generator_fingerprint: None,
+                        }),
+                    )
+                })
+                .collect::<HashMap<_, _>>(),
+            name: name.to_string(),
+            skootrs_version: None,
+            created_at: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_surreal_project_reference_cache() {
+        let mut cache = SurrealProjectReferenceCache::connect("mem://", "skootrs", "skootrs", None)
+            .await
+            .unwrap();
+        let go = project(
+            "go-project",
+            InitializedEcosystem::Go(InitializedGo {
+                name: "go-project".to_string(),
+                host: "github.com".to_string(),
+            }),
+            &[
+                SupportedFacetType::Readme,
+                SupportedFacetType::SecurityPolicy,
+            ],
+        );
+        let maven = project(
+            "maven-project",
+            InitializedEcosystem::Maven(InitializedMaven {
+                group_id: "com.example".to_string(),
+                artifact_id: "maven-project".to_string(),
+            }),
+            &[SupportedFacetType::Readme],
+        );
+
+        let state_store = cache.state_store(&go.repo);
+        assert!(state_store.read().await.unwrap().is_none());
+        state_store.create(go.clone()).await.unwrap();
+        assert!(state_store.create(go.clone()).await.is_err());
+        cache.put(&maven).await.unwrap();
+
+        assert_eq!(
+            cache.list().await.unwrap(),
+            HashSet::from([go.repo.full_url(), maven.repo.full_url()])
+        );
+        let fetched = cache.get(go.repo.full_url()).await.unwrap();
+        assert_eq!(fetched.name, "go-project");
+        assert_eq!(fetched.facets.len(), 2);
+
+        let go_projects = cache.projects_in_ecosystem("Go").await.unwrap();
+        assert_eq!(go_projects.len(), 1);
+        assert_eq!(go_projects[0].name, "go-project");
+        let missing_policy = cache
+            .projects_missing_facet(&SupportedFacetType::SecurityPolicy)
+            .await
+            .unwrap();
+        assert_eq!(missing_policy.len(), 1);
+        assert_eq!(missing_policy[0].name, "maven-project");
+
+        let mut updated = go.clone();
+        updated
+            .facets
+            .remove(&FacetMapKey::Type(SupportedFacetType::SecurityPolicy));
+        state_store.update(updated).await.unwrap();
+        assert_eq!(
+            cache
+                .projects_missing_facet(&SupportedFacetType::SecurityPolicy)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+
+        cache.delete(go.repo.full_url()).await.unwrap();
+        assert!(cache.get(go.repo.full_url()).await.is_err());
+        assert_eq!(cache.select_all().await.unwrap().len(), 1);
+    }
+}